@@ -990,6 +990,26 @@ impl Context {
         callback.await.map_err(anyhow::Error::from)
     }
 
+    /// Returns the number of transactions ready to be included in a block (in mempool and in
+    /// consensus), and their gas unit prices at the given percentiles.
+    pub async fn get_backlog_gas_price_percentiles(
+        &self,
+        percentiles: Vec<u8>,
+    ) -> Result<(u64, Vec<u64>)> {
+        let (req_sender, callback) = oneshot::channel();
+
+        self.mp_sender
+            .clone()
+            .send(MempoolClientRequest::GetBacklogGasPricePercentiles(
+                percentiles,
+                req_sender,
+            ))
+            .await
+            .map_err(anyhow::Error::from)?;
+
+        callback.await.map_err(anyhow::Error::from)
+    }
+
     pub fn get_transaction_by_version(
         &self,
         version: u64,
@@ -1455,6 +1475,37 @@ impl Context {
         Ok(estimation)
     }
 
+    /// Raises a gas estimation based on recent blocks to account for the transactions that are
+    /// waiting to be included in a block, given their number and their median and 90th percentile
+    /// gas unit prices. The backlog is ignored if it fits into a single block.
+    pub fn adjust_gas_estimation_for_backlog(
+        &self,
+        estimation: GasEstimation,
+        num_backlog_txns: u64,
+        backlog_p50_price: u64,
+        backlog_p90_price: u64,
+    ) -> GasEstimation {
+        let config = &self.node_config.api.gas_estimation;
+        if !config.enabled
+            || config.static_override.is_some()
+            || num_backlog_txns <= config.full_block_txns as u64
+        {
+            return estimation;
+        }
+
+        let gas_estimate = estimation.gas_estimate.max(backlog_p50_price);
+        let prioritized_gas_estimate = estimation
+            .prioritized_gas_estimate
+            .unwrap_or(estimation.gas_estimate)
+            .max(self.next_bucket(backlog_p90_price))
+            .max(gas_estimate);
+        GasEstimation {
+            deprioritized_gas_estimate: estimation.deprioritized_gas_estimate,
+            gas_estimate,
+            prioritized_gas_estimate: Some(prioritized_gas_estimate),
+        }
+    }
+
     fn min_gas_unit_price<E: InternalError>(&self, ledger_info: &LedgerInfo) -> Result<u64, E> {
         let (_, gas_schedule) = self.get_gas_schedule(ledger_info)?;
         Ok(gas_schedule.vm.txn.min_price_per_gas_unit.into())
@@ -1,14 +1,15 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::tests::new_test_context_with_orderless_flags;
+use crate::tests::{new_test_context_with_config, new_test_context_with_orderless_flags};
 use aptos_api_test_context::{current_function_name, pretty, TestContext};
+use aptos_config::config::NodeConfig;
 use aptos_crypto::ed25519::Ed25519Signature;
 use aptos_types::{
     account_address::AccountAddress,
     transaction::{
         authenticator::{AccountAuthenticator, TransactionAuthenticator},
-        EntryFunction, ReplayProtector, SignedTransaction, TransactionPayload,
+        EntryFunction, RawTransaction, ReplayProtector, SignedTransaction, TransactionPayload,
     },
 };
 use move_core_types::{ident_str, language_storage::ModuleId};
@@ -684,3 +685,64 @@ async fn test_bcs_execute_fee_payer_transaction_no_authenticator_fail(
         .unwrap()
         .contains("INVALID_SIGNATURE"));
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_estimate_gas() {
+    let mut node_config = NodeConfig::default();
+    node_config.api.transaction_gas_estimation_enabled = true;
+    let mut context =
+        new_test_context_with_config(current_function_name!(), node_config, false, false);
+    let alice = &mut context.gen_account();
+    let bob = &mut context.gen_account();
+    let txn = context.mint_user_account(alice).await;
+    context.commit_block(&vec![txn]).await;
+
+    let txn = context.account_transfer_to(alice, bob.address(), SMALL_TRANSFER_AMOUNT);
+    let txn = SignedTransaction::new(
+        txn.into_raw_transaction(),
+        alice.public_key().clone(),
+        Ed25519Signature::dummy_signature(),
+    );
+    let resp = context
+        .post_bcs_txn("/transactions/estimate_gas", bcs::to_bytes(&txn).unwrap())
+        .await;
+
+    let parse_u64 = |value: &serde_json::Value| value.as_str().unwrap().parse::<u64>().unwrap();
+    let max_gas_amount = parse_u64(&resp["max_gas_amount"]);
+    let gas_used = parse_u64(&resp["gas_used"]);
+    assert!(gas_used > 0);
+    assert!(max_gas_amount >= gas_used);
+    assert!(parse_u64(&resp["execution_io_gas_units"]) > 0);
+    assert!(resp["execution_io_breakdown"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|entry| entry["name"] == "intrinsic"));
+    assert!(resp["gas_unit_price_estimation"]["gas_estimate"].is_u64());
+
+    // The transaction succeeds with the estimated max gas amount, but not with less
+    let simulate = |max_gas_amount: u64| {
+        let raw_txn = txn.clone().into_raw_transaction();
+        let raw_txn = RawTransaction::new(
+            raw_txn.sender(),
+            raw_txn.sequence_number(),
+            raw_txn.into_payload(),
+            max_gas_amount,
+            txn.gas_unit_price(),
+            txn.expiration_timestamp_secs(),
+            txn.chain_id(),
+        );
+        let txn = SignedTransaction::new(
+            raw_txn,
+            alice.public_key().clone(),
+            Ed25519Signature::dummy_signature(),
+        );
+        context.post_bcs_txn("/transactions/simulate", bcs::to_bytes(&txn).unwrap())
+    };
+    assert!(simulate(max_gas_amount).await[0]["success"]
+        .as_bool()
+        .unwrap());
+    assert!(!simulate(max_gas_amount - 1).await[0]["success"]
+        .as_bool()
+        .unwrap());
+}
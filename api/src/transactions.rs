@@ -23,26 +23,31 @@ use crate::{
 use anyhow::Context as AnyhowContext;
 use aptos_api_types::{
     transaction::TransactionSummary, verify_function_identifier, verify_module_identifier, Address,
-    AptosError, AptosErrorCode, AsConverter, EncodeSubmissionRequest, GasEstimation,
-    GasEstimationBcs, HashValue, HexEncodedBytes, LedgerInfo, MoveType, PendingTransaction,
-    SubmitTransactionRequest, Transaction, TransactionData, TransactionOnChainData,
-    TransactionsBatchSingleSubmissionFailure, TransactionsBatchSubmissionResult, UserTransaction,
-    VerifyInput, VerifyInputWithRecursion, U64,
+    AptosError, AptosErrorCode, AsConverter, EncodeSubmissionRequest, GasBreakdownEntry,
+    GasEstimation, GasEstimationBcs, HashValue, HexEncodedBytes, LedgerInfo, MoveType,
+    PendingTransaction, SubmitTransactionRequest, Transaction, TransactionData,
+    TransactionGasEstimation, TransactionOnChainData, TransactionsBatchSingleSubmissionFailure,
+    TransactionsBatchSubmissionResult, UserTransaction, VerifyInput, VerifyInputWithRecursion, U64,
 };
 use aptos_crypto::{hash::CryptoHash, signing_message};
+use aptos_gas_profiling::{GasProfiler, TransactionGasLog};
 use aptos_logger::error;
 use aptos_types::{
     account_address::AccountAddress,
     mempool_status::MempoolStatusCode,
+    state_store::{StateView, TStateView},
     transaction::{
         EntryFunction, ExecutionStatus, MultisigTransactionPayload, RawTransaction,
         RawTransactionWithData, Script, SignedTransaction, TransactionExecutable,
-        TransactionPayload, TransactionPayloadInner,
+        TransactionExecutableRef, TransactionPayload, TransactionPayloadInner, TransactionStatus,
     },
     vm_status::StatusCode,
     AptosCoinType, CoinType,
 };
-use aptos_vm::{AptosSimulationVM, AptosVM};
+use aptos_vm::{data_cache::AsMoveResolver, AptosSimulationVM, AptosVM};
+use aptos_vm_environment::environment::AptosEnvironment;
+use aptos_vm_logging::log_schema::AdapterLogSchema;
+use aptos_vm_types::module_and_script_storage::AsAptosCodeStorage;
 use move_core_types::{ident_str, language_storage::ModuleId, vm_status::VMStatus};
 use poem_openapi::{
     param::{Path, Query},
@@ -582,18 +587,7 @@ impl TransactionsApi {
             let mut signed_transaction = api.get_signed_transaction(&ledger_info, data)?;
 
             // Confirm the API simulation filter allows the transaction
-            let api_filter = &context.node_config.transaction_filters.api_filter;
-            if api_filter.is_enabled()
-                && !api_filter
-                    .transaction_filter()
-                    .allows_transaction(&signed_transaction)
-            {
-                return Err(SubmitTransactionError::forbidden_with_code(
-                    "Transaction not allowed by simulation filter",
-                    AptosErrorCode::InvalidInput,
-                    &ledger_info,
-                ));
-            }
+            api.check_simulation_filter(&ledger_info, &signed_transaction)?;
 
             let estimated_gas_unit_price = match (
                 estimate_gas_unit_price.0.unwrap_or_default(),
@@ -614,68 +608,13 @@ impl TransactionsApi {
 
             // If estimate max gas amount is provided, we will just make it the maximum value
             let estimated_max_gas_amount = if estimate_max_gas_amount.0.unwrap_or_default() {
-                // Retrieve max possible gas units
-                let (_, gas_params) = context.get_gas_schedule(&ledger_info)?;
-                let min_number_of_gas_units =
-                    u64::from(gas_params.vm.txn.min_transaction_gas_units)
-                        / u64::from(gas_params.vm.txn.gas_unit_scaling_factor);
-                let max_number_of_gas_units =
-                    u64::from(gas_params.vm.txn.maximum_number_of_gas_units);
-
-                // Retrieve account balance to determine max gas available, right now this is using
-                // a view function, but we may want to re-evaluate this based on performance
-                let (_, _, state_view) = context
-                    .state_view::<BasicErrorWith404>(Option::None)
-                    .map_err(|err| {
-                        SubmitTransactionError::bad_request_with_code_no_info(
-                            err,
-                            AptosErrorCode::InvalidInput,
-                        )
-                    })?;
-                let output = AptosVM::execute_view_function(
-                    &state_view,
-                    ModuleId::new(AccountAddress::ONE, ident_str!("coin").into()),
-                    ident_str!("balance").into(),
-                    vec![AptosCoinType::type_tag()],
-                    vec![signed_transaction.sender().to_vec()],
-                    context.node_config.api.max_gas_view_function,
-                );
-                let values = output.values.map_err(|status| {
-                    let (err_string, vm_error_code) =
-                        convert_view_function_error(&status, &state_view, &context);
-                    SubmitTransactionError::bad_request_with_optional_vm_status_and_ledger_info(
-                        anyhow::anyhow!(err_string),
-                        AptosErrorCode::InvalidInput,
-                        vm_error_code,
-                        Some(&ledger_info),
-                    )
-                })?;
-                let balance: u64 = bcs::from_bytes(&values[0]).map_err(|err| {
-                    SubmitTransactionError::bad_request_with_code_no_info(
-                        err,
-                        AptosErrorCode::InvalidInput,
-                    )
-                })?;
-
                 let gas_unit_price =
                     estimated_gas_unit_price.unwrap_or_else(|| signed_transaction.gas_unit_price());
-
-                // With 0 gas price, we set it to max gas units, since we can't divide by 0
-                let max_account_gas_units = if gas_unit_price == 0 {
-                    balance
-                } else {
-                    balance / gas_unit_price
-                };
-
-                // To give better error messaging, we should not go below the minimum number of gas units
-                let max_account_gas_units =
-                    std::cmp::max(min_number_of_gas_units, max_account_gas_units);
-
-                // Minimum of the max account and the max total needs to be used for estimation
-                Some(std::cmp::min(
-                    max_account_gas_units,
-                    max_number_of_gas_units,
-                ))
+                Some(api.max_gas_amount(
+                    &ledger_info,
+                    signed_transaction.sender(),
+                    gas_unit_price,
+                )?)
             } else {
                 None
             };
@@ -694,6 +633,98 @@ impl TransactionsApi {
         .await
     }
 
+    /// Estimate gas for transaction
+    ///
+    /// Simulates the transaction to find the smallest max gas amount it succeeds with, and reports
+    /// how the gas used splits into execution & IO gas and storage fees. The max gas amount of the
+    /// transaction is ignored: the search starts from the maximum gas the sender can pay for, and
+    /// narrows it down by simulating the transaction repeatedly.
+    ///
+    /// The response also includes recommended gas unit prices. Unlike the ones returned by
+    /// `/estimate_gas_price`, they also take into account the transactions waiting in mempool and
+    /// consensus to be included in a block.
+    ///
+    /// As for `/transactions/simulate`, the transaction must not have a valid signature.
+    ///
+    /// The endpoint is disabled unless `transaction_gas_estimation_enabled` is set in the API config.
+    #[oai(
+        path = "/transactions/estimate_gas",
+        method = "post",
+        operation_id = "estimate_gas",
+        tag = "ApiTags::Transactions"
+    )]
+    async fn estimate_gas(
+        &self,
+        accept_type: AcceptType,
+        /// If set to true, the gas unit price in the transaction will be ignored
+        /// and the estimated value will be used
+        estimate_gas_unit_price: Query<Option<bool>>,
+        data: SubmitTransactionPost,
+    ) -> SimulateTransactionResult<TransactionGasEstimation> {
+        data.verify()
+            .context("Simulated transaction invalid")
+            .map_err(|err| {
+                SubmitTransactionError::bad_request_with_code_no_info(
+                    err,
+                    AptosErrorCode::InvalidInput,
+                )
+            })?;
+        fail_point_poem("endpoint_estimate_gas")?;
+        if !self.context.node_config.api.transaction_simulation_enabled
+            || !self
+                .context
+                .node_config
+                .api
+                .transaction_gas_estimation_enabled
+        {
+            return Err(api_disabled("Estimate gas"));
+        }
+        self.context
+            .check_api_output_enabled("Estimate gas", &accept_type)?;
+
+        // The backlog only refines the gas unit price estimation, so carry on without it if
+        // mempool cannot be reached
+        let backlog = self
+            .context
+            .get_backlog_gas_price_percentiles(vec![50, 90])
+            .await
+            .ok();
+
+        let api = self.clone();
+        api_spawn_blocking(move || {
+            let ledger_info = api.context.get_latest_ledger_info()?;
+            let signed_transaction = api.get_signed_transaction(&ledger_info, data)?;
+            api.check_simulation_filter(&ledger_info, &signed_transaction)?;
+
+            let mut gas_unit_price_estimation = api.context.estimate_gas_price(&ledger_info)?;
+            if let Some((num_backlog_txns, &[p50_price, p90_price])) = backlog
+                .as_ref()
+                .map(|(num_txns, prices)| (*num_txns, prices.as_slice()))
+            {
+                gas_unit_price_estimation = api.context.adjust_gas_estimation_for_backlog(
+                    gas_unit_price_estimation,
+                    num_backlog_txns,
+                    p50_price,
+                    p90_price,
+                );
+            }
+
+            let gas_unit_price = if estimate_gas_unit_price.0.unwrap_or_default() {
+                gas_unit_price_estimation.gas_estimate
+            } else {
+                signed_transaction.gas_unit_price()
+            };
+            api.estimate_gas_for_transaction(
+                &accept_type,
+                ledger_info,
+                signed_transaction,
+                gas_unit_price,
+                gas_unit_price_estimation,
+            )
+        })
+        .await
+    }
+
     /// Encode submission
     ///
     /// This endpoint accepts an EncodeSubmissionRequest, which internally is a
@@ -1521,6 +1552,204 @@ impl TransactionsApi {
         ))
     }
 
+    fn check_simulation_filter(
+        &self,
+        ledger_info: &LedgerInfo,
+        txn: &SignedTransaction,
+    ) -> Result<(), SubmitTransactionError> {
+        let api_filter = &self.context.node_config.transaction_filters.api_filter;
+        if api_filter.is_enabled() && !api_filter.transaction_filter().allows_transaction(txn) {
+            return Err(SubmitTransactionError::forbidden_with_code(
+                "Transaction not allowed by simulation filter",
+                AptosErrorCode::InvalidInput,
+                ledger_info,
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns the maximum number of gas units the sender can pay for at the given gas unit
+    /// price, within the bounds allowed for a transaction
+    fn max_gas_amount(
+        &self,
+        ledger_info: &LedgerInfo,
+        sender: AccountAddress,
+        gas_unit_price: u64,
+    ) -> Result<u64, SubmitTransactionError> {
+        let context = &self.context;
+
+        // Retrieve max possible gas units
+        let (_, gas_params) = context.get_gas_schedule(ledger_info)?;
+        let min_number_of_gas_units = u64::from(gas_params.vm.txn.min_transaction_gas_units)
+            / u64::from(gas_params.vm.txn.gas_unit_scaling_factor);
+        let max_number_of_gas_units = u64::from(gas_params.vm.txn.maximum_number_of_gas_units);
+
+        // Retrieve account balance to determine max gas available, right now this is using
+        // a view function, but we may want to re-evaluate this based on performance
+        let (_, _, state_view) = context
+            .state_view::<BasicErrorWith404>(Option::None)
+            .map_err(|err| {
+                SubmitTransactionError::bad_request_with_code_no_info(
+                    err,
+                    AptosErrorCode::InvalidInput,
+                )
+            })?;
+        let output = AptosVM::execute_view_function(
+            &state_view,
+            ModuleId::new(AccountAddress::ONE, ident_str!("coin").into()),
+            ident_str!("balance").into(),
+            vec![AptosCoinType::type_tag()],
+            vec![sender.to_vec()],
+            context.node_config.api.max_gas_view_function,
+        );
+        let values = output.values.map_err(|status| {
+            let (err_string, vm_error_code) =
+                convert_view_function_error(&status, &state_view, context);
+            SubmitTransactionError::bad_request_with_optional_vm_status_and_ledger_info(
+                anyhow::anyhow!(err_string),
+                AptosErrorCode::InvalidInput,
+                vm_error_code,
+                Some(ledger_info),
+            )
+        })?;
+        let balance: u64 = bcs::from_bytes(&values[0]).map_err(|err| {
+            SubmitTransactionError::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+        })?;
+
+        // With 0 gas price, we set it to max gas units, since we can't divide by 0
+        let max_account_gas_units = if gas_unit_price == 0 {
+            balance
+        } else {
+            balance / gas_unit_price
+        };
+
+        // To give better error messaging, we should not go below the minimum number of gas units
+        let max_account_gas_units = std::cmp::max(min_number_of_gas_units, max_account_gas_units);
+
+        // Minimum of the max account and the max total needs to be used for estimation
+        Ok(std::cmp::min(
+            max_account_gas_units,
+            max_number_of_gas_units,
+        ))
+    }
+
+    /// Estimate the gas of a transaction in the VM
+    ///
+    /// The transaction is first simulated with the maximum gas the sender can pay for. It cannot
+    /// succeed with a max gas amount lower than the gas it used then, and usually succeeds with
+    /// exactly that amount. Otherwise, the smallest max gas amount it succeeds with is found by
+    /// binary search. Only the simulation with the estimated max gas amount is profiled.
+    pub fn estimate_gas_for_transaction(
+        &self,
+        accept_type: &AcceptType,
+        ledger_info: LedgerInfo,
+        txn: SignedTransaction,
+        gas_unit_price: u64,
+        gas_unit_price_estimation: GasEstimation,
+    ) -> SimulateTransactionResult<TransactionGasEstimation> {
+        // The caller must ensure that the signature is not valid, as otherwise
+        // a malicious actor could execute the transaction without their knowledge
+        if txn.verify_signature().is_ok() {
+            return Err(SubmitTransactionError::bad_request_with_code(
+                "Simulated transactions must not have a valid signature",
+                AptosErrorCode::InvalidInput,
+                &ledger_info,
+            ));
+        }
+
+        let state_view = self.context.latest_state_view_poem(&ledger_info)?;
+        let simulate = |max_gas_amount| {
+            let txn = override_gas_parameters(&txn, Some(max_gas_amount), Some(gas_unit_price));
+            let (vm_status, output) =
+                AptosSimulationVM::create_vm_and_simulate_signed_transaction(&txn, &state_view);
+            match output.status() {
+                TransactionStatus::Keep(ExecutionStatus::Success) => Ok(output.gas_used()),
+                _ => Err(vm_status),
+            }
+        };
+
+        let mut max_gas_amount = self.max_gas_amount(&ledger_info, txn.sender(), gas_unit_price)?;
+        let gas_used = simulate(max_gas_amount).map_err(|vm_status| {
+            SubmitTransactionError::bad_request_with_vm_status(
+                format!(
+                    "Transaction fails with the maximum gas amount the sender can pay for ({})",
+                    max_gas_amount
+                ),
+                AptosErrorCode::VmError,
+                vm_status.status_code(),
+                &ledger_info,
+            )
+        })?;
+
+        // The transaction fails with `low` and succeeds with `max_gas_amount`
+        let mut low = gas_used.saturating_sub(1);
+        let mut probe = gas_used;
+        while max_gas_amount - low > 1 {
+            match simulate(probe) {
+                Ok(_) => max_gas_amount = probe,
+                Err(_) => low = probe,
+            }
+            probe = low + (max_gas_amount - low) / 2;
+        }
+
+        let (gas_used, gas_log) = simulate_with_gas_profiler(
+            &state_view,
+            &override_gas_parameters(&txn, Some(max_gas_amount), Some(gas_unit_price)),
+        )
+        .map_err(|vm_status| {
+            SubmitTransactionError::internal_with_vm_status(
+                format!(
+                    "Transaction fails with the estimated max gas amount ({}) when profiled",
+                    max_gas_amount
+                ),
+                AptosErrorCode::VmError,
+                vm_status.status_code(),
+                &ledger_info,
+            )
+        })?;
+
+        let erased_gas_log = gas_log.to_erased();
+        let gas_scaling_factor = u64::from(gas_log.exec_io.gas_scaling_factor);
+        let mut exec_io = erased_gas_log.exec_io.tree;
+        exec_io.include_child_costs();
+        let mut storage = erased_gas_log.storage.tree;
+        storage.include_child_costs();
+
+        let estimation = TransactionGasEstimation {
+            max_gas_amount: max_gas_amount.into(),
+            gas_used: gas_used.into(),
+            gas_unit_price: gas_unit_price.into(),
+            execution_io_gas_units: u64::from(gas_log.exec_io.total)
+                .div_ceil(gas_scaling_factor)
+                .into(),
+            storage_fee_octas: u64::from(gas_log.storage.total).into(),
+            storage_fee_refund_octas: u64::from(gas_log.storage.total_refund).into(),
+            execution_io_breakdown: exec_io
+                .children
+                .into_iter()
+                .map(|node| GasBreakdownEntry {
+                    name: node.text,
+                    amount: u64::from(node.val).div_ceil(gas_scaling_factor).into(),
+                })
+                .collect(),
+            storage_fee_breakdown: storage
+                .children
+                .into_iter()
+                .map(|node| GasBreakdownEntry {
+                    name: node.text,
+                    amount: u64::from(node.val.cost).into(),
+                })
+                .collect(),
+            gas_unit_price_estimation,
+        };
+        BasicResponse::try_from_rust_value((
+            estimation,
+            &ledger_info,
+            BasicResponseStatus::Ok,
+            accept_type,
+        ))
+    }
+
     // TODO: This function leverages a lot of types from aptos_types, use the
     // local API types and just return those directly, instead of converting
     // from these types in render_transactions.
@@ -1764,6 +1993,44 @@ fn override_gas_parameters(
     SignedTransaction::new_signed_transaction(raw_txn, signed_txn.authenticator())
 }
 
+/// Simulates a transaction with the gas profiler, and returns the gas it used and its gas log if
+/// it succeeded
+fn simulate_with_gas_profiler(
+    state_view: &impl StateView,
+    txn: &SignedTransaction,
+) -> Result<(u64, TransactionGasLog), VMStatus> {
+    let env = AptosEnvironment::new(state_view);
+    let vm = AptosSimulationVM::new(&env, state_view);
+    let log_context = AdapterLogSchema::new(state_view.id(), 0);
+    let resolver = state_view.as_move_resolver();
+    let code_storage = state_view.as_aptos_code_storage(&env);
+
+    let (vm_status, vm_output, gas_profiler) = vm
+        .simulate_user_transaction_with_modified_gas_meter(
+            &resolver,
+            &code_storage,
+            txn,
+            &log_context,
+            |gas_meter| match txn.executable_ref() {
+                Ok(TransactionExecutableRef::EntryFunction(entry_func)) => {
+                    GasProfiler::new_function(
+                        gas_meter,
+                        entry_func.module().clone(),
+                        entry_func.function().to_owned(),
+                        entry_func.ty_args().to_vec(),
+                    )
+                },
+                _ => GasProfiler::new_script(gas_meter),
+            },
+        )?;
+    match vm_output.status() {
+        TransactionStatus::Keep(ExecutionStatus::Success) => {
+            Ok((vm_output.gas_used(), gas_profiler.finish()))
+        },
+        _ => Err(vm_status),
+    }
+}
+
 enum GetByVersionResponse {
     VersionTooNew,
     VersionTooOld,
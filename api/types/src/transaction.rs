@@ -2399,3 +2399,36 @@ pub struct GasEstimation {
     /// The prioritized estimate for the gas unit price
     pub prioritized_gas_estimate: Option<u64>,
 }
+
+/// Struct holding the outputs of the estimate gas for transaction API
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct TransactionGasEstimation {
+    /// The smallest max gas amount the transaction succeeds with
+    pub max_gas_amount: U64,
+    /// The gas units used by the transaction with the estimated max gas amount
+    pub gas_used: U64,
+    /// The gas unit price the transaction was simulated with
+    pub gas_unit_price: U64,
+    /// The gas units charged for execution and IO, rounded up
+    pub execution_io_gas_units: U64,
+    /// The storage fee charged, in octas
+    pub storage_fee_octas: U64,
+    /// The storage fee refunded, in octas
+    pub storage_fee_refund_octas: U64,
+    /// The execution and IO gas units per category, rounded up
+    pub execution_io_breakdown: Vec<GasBreakdownEntry>,
+    /// The storage fee per category, in octas
+    pub storage_fee_breakdown: Vec<GasBreakdownEntry>,
+    /// The recommended gas unit prices, based on recent blocks and on the transactions waiting to
+    /// be included in a block
+    pub gas_unit_price_estimation: GasEstimation,
+}
+
+/// An entry in a breakdown of gas costs
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct GasBreakdownEntry {
+    /// The name of the category
+    pub name: String,
+    /// The cost of the category
+    pub amount: U64,
+}
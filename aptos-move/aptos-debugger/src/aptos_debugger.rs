@@ -1,6 +1,7 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::execution_trace::TraceEvent;
 use anyhow::{bail, format_err};
 use aptos_block_executor::txn_provider::{default::DefaultTxnProvider, TxnProvider};
 use aptos_gas_profiling::{GasProfiler, TransactionGasLog};
@@ -12,7 +13,7 @@ use aptos_types::{
         transaction_slice_metadata::TransactionSliceMetadata,
     },
     contract_event::ContractEvent,
-    state_store::TStateView,
+    state_store::{StateView, TStateView},
     transaction::{
         signature_verified_transaction::SignatureVerifiedTransaction, BlockOutput,
         SignedTransaction, Transaction, TransactionExecutableRef, TransactionInfo,
@@ -30,6 +31,7 @@ use aptos_vm_environment::environment::AptosEnvironment;
 use aptos_vm_logging::log_schema::AdapterLogSchema;
 use aptos_vm_types::{module_and_script_storage::AsAptosCodeStorage, output::VMOutput};
 use itertools::Itertools;
+use move_vm_runtime::execution_trace::ExecutionTraceRecorder;
 use std::{path::Path, sync::Arc, time::Instant};
 
 pub struct AptosDebugger {
@@ -168,6 +170,34 @@ impl AptosDebugger {
         Ok((status, output, gas_profiler.finish()))
     }
 
+    /// Executes a single user transaction on top of the given state, and records its
+    /// execution trace (instructions executed, gas remaining and resources accessed).
+    pub fn execute_transaction_with_trace(
+        state_view: &impl StateView,
+        txn: SignedTransaction,
+    ) -> anyhow::Result<(VMStatus, VMOutput, Vec<TraceEvent>)> {
+        let log_context = AdapterLogSchema::new(state_view.id(), 0);
+        let txn = txn
+            .check_signature()
+            .map_err(|err| format_err!("Unexpected VM Error: {:?}", err))?;
+
+        // Module bundle is deprecated!
+        if let TransactionPayload::ModuleBundle(_) = txn.payload() {
+            bail!("Module bundle payload has been removed")
+        }
+
+        let env = AptosEnvironment::new(state_view);
+        let vm = AptosVM::new(&env, state_view);
+        let resolver = state_view.as_move_resolver();
+        let code_storage = state_view.as_aptos_code_storage(&env);
+
+        // Execution must happen on this thread for the trace to be recorded.
+        let recorder = ExecutionTraceRecorder::start();
+        let (status, output) =
+            vm.execute_user_transaction(&resolver, &code_storage, &txn, &log_context);
+        Ok((status, output, recorder.finish()))
+    }
+
     pub async fn execute_past_transactions(
         &self,
         begin: Version,
@@ -2,12 +2,16 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use anyhow::{bail, Result};
+use aptos_gas_profiling::AggregatedTransactionGasLogs;
 use aptos_move_debugger::aptos_debugger::AptosDebugger;
 use aptos_rest_client::Client;
 use aptos_types::transaction::Transaction;
 use aptos_vm::AptosVM;
 use clap::{Parser, Subcommand};
-use std::path::{Path, PathBuf};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
 use url::Url;
 
 #[derive(Subcommand)]
@@ -25,6 +29,15 @@ pub struct Args {
 
     #[clap(long)]
     version: u64,
+
+    /// Number of consecutive transactions to profile, starting at the version. If greater than
+    /// one, gas is aggregated across all user transactions in the range.
+    #[clap(long, default_value_t = 1)]
+    limit: u64,
+
+    /// Number of hottest functions and storage slots to show for aggregated profiles.
+    #[clap(long, default_value_t = 20)]
+    top: usize,
 }
 
 #[tokio::main]
@@ -44,6 +57,10 @@ async fn main() -> Result<()> {
         Target::DB { path } => AptosDebugger::db(path)?,
     };
 
+    if args.limit > 1 {
+        return profile_transactions(&debugger, version, args.limit, args.top).await;
+    }
+
     // Execute the transaction w/ the gas profiler
     let (txn, _txn_info) = debugger
         .get_committed_transaction_at_version(version)
@@ -69,7 +86,75 @@ async fn main() -> Result<()> {
         format!("Gas Report - Transaction {}", version),
     )?;
 
+    gas_log.generate_speedscope_profile(
+        report_path.join("profile.speedscope.json"),
+        &format!("Transaction {}", version),
+    )?;
+    gas_log.generate_pprof_profile(report_path.join("profile.pb.gz"))?;
+
     println!("Gas profiling report saved to {}.", report_path.display());
 
     Ok(())
 }
+
+/// Profiles all user transactions in the version range, and aggregates their gas logs.
+async fn profile_transactions(
+    debugger: &AptosDebugger,
+    begin: u64,
+    limit: u64,
+    top: usize,
+) -> Result<()> {
+    let (txns, _txn_infos) = debugger.get_committed_transactions(begin, limit).await?;
+
+    let mut aggregated = AggregatedTransactionGasLogs::new();
+    for (version, txn) in (begin..).zip(txns) {
+        let txn = match txn {
+            Transaction::UserTransaction(txn) => txn,
+            _ => continue,
+        };
+        let (_status, _output, gas_log) =
+            debugger.execute_transaction_at_version_with_gas_profiler(version, txn)?;
+        aggregated.add(&gas_log);
+    }
+    if aggregated.num_transactions == 0 {
+        bail!("no user transactions in the version range");
+    }
+
+    // Show results to the user
+    println!(
+        "Profiled {} user transactions. Execution & IO: {} internal gas units, storage fees: {} \
+         Octa.",
+        aggregated.num_transactions,
+        aggregated.total_execution_and_io,
+        aggregated.total_storage_fee
+    );
+
+    println!("\nHottest functions (exclusive internal gas, inclusive internal gas, calls):");
+    for (name, gas) in aggregated.hottest_functions(top) {
+        println!(
+            "    {} {} {} {}",
+            gas.exclusive, gas.inclusive, gas.calls, name
+        );
+    }
+
+    println!("\nHottest storage slots (IO internal gas, storage fee in Octa, reads, writes):");
+    for (name, gas) in aggregated.hottest_storage_slots(top) {
+        println!(
+            "    {} {} {} {} {}",
+            gas.io, gas.storage_fee, gas.reads, gas.writes, name
+        );
+    }
+
+    let report_path =
+        Path::new("gas-profiling").join(format!("txns-{}-{}", begin, begin + limit - 1));
+    fs::create_dir_all(&report_path)?;
+    aggregated.generate_speedscope_profile(
+        report_path.join("profile.speedscope.json"),
+        &format!("Transactions {} to {}", begin, begin + limit - 1),
+    )?;
+    aggregated.generate_pprof_profile(report_path.join("profile.pb.gz"))?;
+
+    println!("\nGas profiles saved to {}.", report_path.display());
+
+    Ok(())
+}
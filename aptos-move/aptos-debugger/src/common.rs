@@ -1,7 +1,7 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{execute_past_transactions, execute_pending_block};
+use crate::{execute_past_transactions, execute_pending_block, replay_dag};
 use anyhow::Result;
 use clap::Parser;
 use std::path::PathBuf;
@@ -35,6 +35,7 @@ pub struct Opts {
 pub enum Command {
     ExecutePastTransactions(execute_past_transactions::Command),
     ExecutePendingBlock(execute_pending_block::Command),
+    ReplayDag(replay_dag::Command),
 }
 
 impl Command {
@@ -42,6 +43,7 @@ impl Command {
         match self {
             Command::ExecutePastTransactions(cmd) => cmd.run().await,
             Command::ExecutePendingBlock(cmd) => cmd.run().await,
+            Command::ReplayDag(cmd) => cmd.run().await,
         }
     }
 }
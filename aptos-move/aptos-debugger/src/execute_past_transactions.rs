@@ -5,6 +5,10 @@ use crate::{aptos_debugger::AptosDebugger, common::Opts};
 use anyhow::Result;
 use aptos_rest_client::Client;
 use clap::Parser;
+#[cfg(feature = "debug-adapter")]
+use move_dap::{DebugSession, SourceResolver};
+#[cfg(feature = "debug-adapter")]
+use std::path::PathBuf;
 use url::Url;
 
 #[derive(Parser)]
@@ -26,6 +30,19 @@ pub struct Command {
 
     #[clap(long)]
     use_same_block_boundaries: bool,
+
+    /// Wait for a Debug Adapter Protocol client (e.g., an editor) to connect on this
+    /// address (e.g., `127.0.0.1:4711`) and debug the replayed transactions at the
+    /// source level. Transactions are executed sequentially while debugging.
+    #[cfg(feature = "debug-adapter")]
+    #[clap(long)]
+    debug_adapter_address: Option<String>,
+
+    /// The build directories (i.e., `build/<package>`) of the packages to debug. The
+    /// compiled modules must match the ones on chain for source locations to be correct.
+    #[cfg(feature = "debug-adapter")]
+    #[clap(long, requires = "debug_adapter_address")]
+    debug_package_dir: Vec<PathBuf>,
 }
 
 impl Command {
@@ -38,15 +55,32 @@ impl Command {
             unreachable!("Must provide one target.");
         };
 
+        // The debugger is detached once the session is dropped (after execution)
+        #[cfg(feature = "debug-adapter")]
+        let (concurrency_levels, debug_session) = match &self.debug_adapter_address {
+            Some(address) => {
+                let mut resolver = SourceResolver::new();
+                for package_dir in &self.debug_package_dir {
+                    resolver.add_package_build_dir(package_dir)?;
+                }
+                (vec![1], Some(DebugSession::start(address, resolver)?))
+            },
+            None => (self.opts.concurrency_level, None),
+        };
+        #[cfg(not(feature = "debug-adapter"))]
+        let concurrency_levels = self.opts.concurrency_level;
+
         let result = debugger
             .execute_past_transactions(
                 self.begin_version,
                 self.limit,
                 self.use_same_block_boundaries,
                 self.repeat_execution_times.unwrap_or(1),
-                &self.opts.concurrency_level,
+                &concurrency_levels,
             )
             .await?;
+        #[cfg(feature = "debug-adapter")]
+        drop(debug_session);
 
         if !self.skip_result {
             println!("{result:#?}",);
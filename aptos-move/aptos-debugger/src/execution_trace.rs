@@ -0,0 +1,352 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Structured execution traces of replayed transactions. Traces are stored as JSON lines (one
+//! event per line), so that traces recorded by different binaries (e.g., different VM versions)
+//! can be compared to find the first instruction at which executions diverge.
+
+use anyhow::Context;
+pub use move_vm_runtime::execution_trace::TraceEvent;
+use std::{
+    fmt,
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::Path,
+};
+
+/// The number of events preceding a divergence that are shown for context
+const NUM_CONTEXT_EVENTS: usize = 5;
+
+/// Saves the trace to the given file, as JSON lines
+pub fn save_trace(path: &Path, events: &[TraceEvent]) -> anyhow::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    for event in events {
+        serde_json::to_writer(&mut writer, event)?;
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Loads a trace saved using [save_trace]
+pub fn load_trace(path: &Path) -> anyhow::Result<Vec<TraceEvent>> {
+    let reader = BufReader::new(
+        File::open(path).with_context(|| format!("Failed to open trace {}", path.display()))?,
+    );
+    let mut events = vec![];
+    for (line_number, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let event = serde_json::from_str(&line).with_context(|| {
+            format!(
+                "Failed to parse event on line {} of {}",
+                line_number + 1,
+                path.display()
+            )
+        })?;
+        events.push(event);
+    }
+    Ok(events)
+}
+
+/// The way in which two traces diverge
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DivergenceKind {
+    /// The executions differ, e.g., a different instruction is executed, or
+    /// different resources are accessed.
+    Execution,
+    /// The same instruction is executed, but it is charged a different amount of gas
+    GasCost { left: u64, right: u64 },
+}
+
+/// The first point at which two traces diverge
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TraceDivergence {
+    /// The index of the divergent event in both traces
+    pub index: usize,
+    pub kind: DivergenceKind,
+    /// The divergent events (None if the trace ended)
+    pub left: Option<TraceEvent>,
+    pub right: Option<TraceEvent>,
+    /// The (identical) events preceding the divergence
+    pub context: Vec<TraceEvent>,
+}
+
+impl fmt::Display for TraceDivergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            DivergenceKind::Execution => writeln!(f, "Executions diverge at event {}", self.index)?,
+            DivergenceKind::GasCost { left, right } => writeln!(
+                f,
+                "Gas charges diverge at event {} (left: {}, right: {})",
+                self.index, left, right
+            )?,
+        }
+        writeln!(f, "Preceding events:")?;
+        for event in &self.context {
+            writeln!(f, "    {}", format_event(Some(event)))?;
+        }
+        writeln!(f, "Left:  {}", format_event(self.left.as_ref()))?;
+        write!(f, "Right: {}", format_event(self.right.as_ref()))
+    }
+}
+
+fn format_event(event: Option<&TraceEvent>) -> String {
+    match event {
+        Some(TraceEvent::Instruction {
+            function,
+            pc,
+            instruction,
+            gas_remaining,
+        }) => format!(
+            "{}[{}] {} (gas remaining: {})",
+            function, pc, instruction, gas_remaining
+        ),
+        Some(TraceEvent::ResourceRead {
+            address,
+            resource,
+            bytes,
+        }) => format!(
+            "read {} at {} ({} bytes)",
+            resource,
+            address.to_hex_literal(),
+            bytes
+        ),
+        Some(TraceEvent::ResourceWrite { address, resource }) => {
+            format!("write {} at {}", resource, address.to_hex_literal())
+        },
+        Some(TraceEvent::ResourceGroupRead {
+            address,
+            group,
+            bytes,
+        }) => format!(
+            "read group {} at {} ({} bytes)",
+            group,
+            address.to_hex_literal(),
+            bytes
+        ),
+        Some(TraceEvent::TableRead { handle, key, bytes }) => match bytes {
+            Some(bytes) => format!(
+                "read table {} key 0x{} ({} bytes)",
+                handle.to_hex_literal(),
+                key,
+                bytes
+            ),
+            None => format!(
+                "read table {} key 0x{} (missing)",
+                handle.to_hex_literal(),
+                key
+            ),
+        },
+        Some(TraceEvent::ModuleRead {
+            address,
+            module,
+            bytes,
+        }) => format!(
+            "load module {}::{} ({} bytes)",
+            address.to_hex_literal(),
+            module,
+            bytes
+        ),
+        None => "<end of trace>".to_string(),
+    }
+}
+
+/// Returns the first point at which the traces diverge (if any). Instructions
+/// are compared without the remaining gas (which depends on the gas limit and
+/// intrinsic costs). If `compare_gas` is set, the gas charged for executing
+/// each instruction (i.e., the difference in the remaining gas until the next
+/// instruction) is also compared.
+pub fn find_first_divergence(
+    left: &[TraceEvent],
+    right: &[TraceEvent],
+    compare_gas: bool,
+) -> Option<TraceDivergence> {
+    let create_divergence = |index: usize, kind: DivergenceKind| TraceDivergence {
+        index,
+        kind,
+        left: left.get(index).cloned(),
+        right: right.get(index).cloned(),
+        context: left[index.saturating_sub(NUM_CONTEXT_EVENTS)..index].to_vec(),
+    };
+
+    // The index and remaining gas (in both traces) of the last instruction
+    let mut last_instruction: Option<(usize, u64, u64)> = None;
+    for index in 0..left.len().max(right.len()) {
+        let (left_event, right_event) = match (left.get(index), right.get(index)) {
+            (Some(left_event), Some(right_event)) => (left_event, right_event),
+            _ => return Some(create_divergence(index, DivergenceKind::Execution)),
+        };
+        if without_gas(left_event) != without_gas(right_event) {
+            return Some(create_divergence(index, DivergenceKind::Execution));
+        }
+
+        if let (
+            TraceEvent::Instruction {
+                gas_remaining: left_gas,
+                ..
+            },
+            TraceEvent::Instruction {
+                gas_remaining: right_gas,
+                ..
+            },
+        ) = (left_event, right_event)
+        {
+            if let (true, Some((last_index, last_left_gas, last_right_gas))) =
+                (compare_gas, last_instruction)
+            {
+                let left_cost = last_left_gas.saturating_sub(*left_gas);
+                let right_cost = last_right_gas.saturating_sub(*right_gas);
+                if left_cost != right_cost {
+                    return Some(create_divergence(last_index, DivergenceKind::GasCost {
+                        left: left_cost,
+                        right: right_cost,
+                    }));
+                }
+            }
+            last_instruction = Some((index, *left_gas, *right_gas));
+        }
+    }
+    None
+}
+
+/// Returns the event with the remaining gas cleared (if it is an instruction)
+fn without_gas(event: &TraceEvent) -> TraceEvent {
+    match event {
+        TraceEvent::Instruction {
+            function,
+            pc,
+            instruction,
+            ..
+        } => TraceEvent::Instruction {
+            function: function.clone(),
+            pc: *pc,
+            instruction: instruction.clone(),
+            gas_remaining: 0,
+        },
+        event => event.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_types::account_address::AccountAddress;
+
+    fn create_instruction(pc: u16, instruction: &str, gas_remaining: u64) -> TraceEvent {
+        TraceEvent::Instruction {
+            function: "0x1::coin::transfer".to_string(),
+            pc,
+            instruction: instruction.to_string(),
+            gas_remaining,
+        }
+    }
+
+    fn create_trace(gas_costs: &[u64]) -> Vec<TraceEvent> {
+        let mut gas_remaining = 1000;
+        let mut trace = vec![];
+        for (pc, gas_cost) in gas_costs.iter().enumerate() {
+            trace.push(create_instruction(pc as u16, "Nop", gas_remaining));
+            gas_remaining -= gas_cost;
+        }
+        trace
+    }
+
+    #[test]
+    fn test_identical_traces() {
+        let trace = create_trace(&[1, 2, 3]);
+        assert_eq!(find_first_divergence(&trace, &trace, true), None);
+
+        // Verify a different gas limit isn't a divergence
+        let other_trace: Vec<_> = trace
+            .iter()
+            .map(|event| match event {
+                TraceEvent::Instruction {
+                    pc, gas_remaining, ..
+                } => create_instruction(*pc, "Nop", gas_remaining + 500),
+                event => event.clone(),
+            })
+            .collect();
+        assert_eq!(find_first_divergence(&trace, &other_trace, true), None);
+    }
+
+    #[test]
+    fn test_execution_divergence() {
+        // Verify different instructions diverge
+        let mut left = create_trace(&[1, 1, 1, 1]);
+        let mut right = left.clone();
+        right[2] = create_instruction(2, "Ret", 998);
+        let divergence = find_first_divergence(&left, &right, false).unwrap();
+        assert_eq!(divergence.index, 2);
+        assert_eq!(divergence.kind, DivergenceKind::Execution);
+        assert_eq!(divergence.context, left[..2].to_vec());
+
+        // Verify different resource accesses diverge
+        let mut right = left.clone();
+        left.insert(1, TraceEvent::ResourceWrite {
+            address: AccountAddress::ONE,
+            resource: "0x1::coin::CoinStore".to_string(),
+        });
+        right.insert(1, TraceEvent::ResourceRead {
+            address: AccountAddress::ONE,
+            resource: "0x1::coin::CoinStore".to_string(),
+            bytes: 10,
+        });
+        let divergence = find_first_divergence(&left, &right, false).unwrap();
+        assert_eq!(divergence.index, 1);
+
+        // Verify a shorter trace diverges where it ends
+        let right = left[..3].to_vec();
+        let divergence = find_first_divergence(&left, &right, false).unwrap();
+        assert_eq!(divergence.index, 3);
+        assert_eq!(divergence.right, None);
+    }
+
+    #[test]
+    fn test_gas_divergence() {
+        // Verify gas differences are only detected when comparing gas
+        let left = create_trace(&[1, 2, 3, 4]);
+        let right = create_trace(&[1, 2, 5, 4]);
+        assert_eq!(find_first_divergence(&left, &right, false), None);
+
+        // Verify the instruction charged differently is identified
+        let divergence = find_first_divergence(&left, &right, true).unwrap();
+        assert_eq!(divergence.index, 2);
+        assert_eq!(divergence.kind, DivergenceKind::GasCost {
+            left: 3,
+            right: 5
+        });
+    }
+
+    #[test]
+    fn test_save_and_load_trace() {
+        let mut trace = create_trace(&[1, 2, 3]);
+        trace.push(TraceEvent::ResourceRead {
+            address: AccountAddress::ONE,
+            resource: "0x1::account::Account".to_string(),
+            bytes: 128,
+        });
+        trace.push(TraceEvent::ResourceGroupRead {
+            address: AccountAddress::ONE,
+            group: "0x1::object::ObjectGroup".to_string(),
+            bytes: 256,
+        });
+        trace.push(TraceEvent::TableRead {
+            handle: AccountAddress::TWO,
+            key: "01ab".to_string(),
+            bytes: None,
+        });
+        trace.push(TraceEvent::ModuleRead {
+            address: AccountAddress::ONE,
+            module: "coin".to_string(),
+            bytes: 4096,
+        });
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("trace.jsonl");
+        save_trace(&path, &trace).unwrap();
+        assert_eq!(load_trace(&path).unwrap(), trace);
+    }
+}
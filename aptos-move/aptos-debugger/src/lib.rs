@@ -6,3 +6,5 @@ pub mod bcs_txn_decoder;
 pub mod common;
 pub mod execute_past_transactions;
 pub mod execute_pending_block;
+pub mod execution_trace;
+pub mod replay_dag;
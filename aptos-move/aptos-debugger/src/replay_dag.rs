@@ -0,0 +1,63 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{aptos_debugger::AptosDebugger, common::Target};
+use anyhow::{ensure, format_err, Result};
+use aptos_rest_client::Client;
+use aptos_types::{
+    epoch_state::EpochState,
+    on_chain_config::{ConfigurationResource, OnChainConfig, OnChainConsensusConfig, ValidatorSet},
+    transaction::Version,
+};
+use clap::Parser;
+use std::sync::Arc;
+use url::Url;
+
+/// Replays the DAG order rule over nodes persisted in consensus db, using the validator set
+/// and the consensus config of the epoch `version` belongs to.
+#[derive(Parser)]
+pub struct Command {
+    #[clap(flatten)]
+    target: Target,
+
+    /// Any version in the epoch of the persisted DAG.
+    #[clap(long)]
+    version: Version,
+
+    #[clap(flatten)]
+    dag: aptos_consensus::util::dag_tool::Command,
+}
+
+impl Command {
+    pub async fn run(self) -> Result<()> {
+        let debugger = if let Some(rest_endpoint) = self.target.rest_endpoint {
+            AptosDebugger::rest_client(Client::new(Url::parse(&rest_endpoint)?))?
+        } else if let Some(db_path) = self.target.db_path {
+            AptosDebugger::db(db_path)?
+        } else {
+            unreachable!("Must provide one target.");
+        };
+
+        let state_view = debugger.state_view_at_version(self.version);
+        let configuration = ConfigurationResource::fetch_config(&state_view)
+            .ok_or_else(|| format_err!("ConfigurationResource not found"))?;
+        let validator_set = ValidatorSet::fetch_config(&state_view)
+            .ok_or_else(|| format_err!("ValidatorSet not found"))?;
+        let consensus_config = OnChainConsensusConfig::fetch_config(&state_view)
+            .ok_or_else(|| format_err!("OnChainConsensusConfig not found"))?;
+        ensure!(
+            consensus_config.is_dag_enabled(),
+            "DAG consensus isn't enabled at version {}.",
+            self.version
+        );
+        let epoch_state = EpochState {
+            epoch: configuration.epoch(),
+            verifier: Arc::new((&validator_set).into()),
+        };
+
+        self.dag.run(
+            Arc::new(epoch_state),
+            consensus_config.unwrap_dag_config_v1(),
+        )
+    }
+}
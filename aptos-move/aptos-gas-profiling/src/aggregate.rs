@@ -2,11 +2,18 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    log::{ExecutionAndIOCosts, ExecutionGasEvent},
+    export::{self, StackProfile},
+    log::{CallFrame, ExecutionAndIOCosts, ExecutionGasEvent, TransactionGasLog},
     render::{Render, TableKey},
 };
-use aptos_gas_algebra::{GasQuantity, GasScalingFactor, InternalGas};
-use std::collections::{btree_map, BTreeMap};
+use anyhow::Result;
+use aptos_gas_algebra::{Fee, GasQuantity, GasScalingFactor, InternalGas};
+use aptos_types::state_store::state_key::StateKey;
+use std::{
+    collections::{btree_map, BTreeMap},
+    fs,
+    path::Path,
+};
 
 /// Represents an aggregation of execution gas events, including the count and total gas costs for each type of event.
 ///
@@ -138,3 +145,267 @@ impl ExecutionAndIOCosts {
         }
     }
 }
+
+/// Gas used by a function, aggregated across all of its calls.
+#[derive(Debug, Clone)]
+pub struct FunctionGas {
+    pub calls: usize,
+    /// Gas charged in the function itself (including calls to natives and resource loads).
+    pub exclusive: InternalGas,
+    /// Gas charged in the function and all functions it calls. Recursive calls are only counted
+    /// once.
+    pub inclusive: InternalGas,
+}
+
+/// Gas used to access a storage slot, aggregated across all accesses.
+#[derive(Debug, Clone)]
+pub struct StorageSlotGas {
+    pub reads: usize,
+    pub writes: usize,
+    /// IO gas for loading and writing the slot.
+    pub io: InternalGas,
+    /// Storage fees (in Octa) for writing the slot.
+    pub storage_fee: Fee,
+}
+
+impl Default for FunctionGas {
+    fn default() -> Self {
+        Self {
+            calls: 0,
+            exclusive: InternalGas::zero(),
+            inclusive: InternalGas::zero(),
+        }
+    }
+}
+
+impl Default for StorageSlotGas {
+    fn default() -> Self {
+        Self {
+            reads: 0,
+            writes: 0,
+            io: InternalGas::zero(),
+            storage_fee: Fee::zero(),
+        }
+    }
+}
+
+/// Gas logs of many transactions (e.g., all transactions in a version range), merged together to
+/// find the hottest functions and storage slots, or to export a single profile.
+#[derive(Debug)]
+pub struct AggregatedTransactionGasLogs {
+    pub num_transactions: usize,
+    pub total_execution_and_io: InternalGas,
+    pub total_storage_fee: Fee,
+
+    functions: BTreeMap<String, FunctionGas>,
+    storage_slots: BTreeMap<String, StorageSlotGas>,
+    /// Folded stacks of execution & IO gas.
+    execution_and_io_stacks: BTreeMap<String, u64>,
+    /// Folded stacks of storage fees.
+    storage_stacks: BTreeMap<String, u64>,
+}
+
+impl Default for AggregatedTransactionGasLogs {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AggregatedTransactionGasLogs {
+    pub fn new() -> Self {
+        Self {
+            num_transactions: 0,
+            total_execution_and_io: InternalGas::zero(),
+            total_storage_fee: Fee::zero(),
+            functions: BTreeMap::new(),
+            storage_slots: BTreeMap::new(),
+            execution_and_io_stacks: BTreeMap::new(),
+            storage_stacks: BTreeMap::new(),
+        }
+    }
+
+    /// Merges the gas log of a transaction into the aggregation.
+    pub fn add(&mut self, log: &TransactionGasLog) {
+        self.num_transactions += 1;
+        self.total_execution_and_io += log.exec_io.total;
+        self.total_storage_fee += log.storage.total;
+
+        self.add_call_frame(&log.exec_io.call_graph, &mut vec![]);
+
+        for event in log.exec_io.gas_events() {
+            if let ExecutionGasEvent::LoadResource { addr, ty, cost } = event {
+                let slot = self
+                    .storage_slots
+                    .entry(format!(
+                        "{}::{}",
+                        addr.to_hex_literal(),
+                        ty.to_canonical_string()
+                    ))
+                    .or_default();
+                slot.reads += 1;
+                slot.io += *cost;
+            }
+        }
+        for write in &log.exec_io.write_set_transient {
+            let slot = self.storage_slots.entry(slot_name(&write.key)).or_default();
+            slot.writes += 1;
+            slot.io += write.cost;
+        }
+        for write in &log.storage.write_set_storage {
+            let slot = self.storage_slots.entry(slot_name(&write.key)).or_default();
+            slot.storage_fee += write.cost;
+        }
+
+        for (stack, cost) in log.exec_io.to_folded_stacks() {
+            *self.execution_and_io_stacks.entry(stack).or_default() += cost;
+        }
+        for (stack, cost) in log.storage.to_folded_stacks() {
+            *self.storage_stacks.entry(stack).or_default() += cost;
+        }
+    }
+
+    /// Records the frame (and its callees), and returns the inclusive gas of the frame.
+    fn add_call_frame(&mut self, frame: &CallFrame, stack: &mut Vec<String>) -> InternalGas {
+        use ExecutionGasEvent::*;
+
+        let name = format!("{}", frame.name);
+        stack.push(name.clone());
+
+        let mut exclusive = InternalGas::zero();
+        let mut inclusive = InternalGas::zero();
+        for event in &frame.events {
+            match event {
+                Loc(_) => (),
+                Bytecode { cost, .. }
+                | CallNative { cost, .. }
+                | LoadResource { cost, .. }
+                | CreateTy { cost } => exclusive += *cost,
+                Call(inner_frame) => inclusive += self.add_call_frame(inner_frame, stack),
+            }
+        }
+        inclusive += exclusive;
+
+        stack.pop();
+        let function = self.functions.entry(name.clone()).or_default();
+        function.calls += 1;
+        function.exclusive += exclusive;
+        if !stack.contains(&name) {
+            function.inclusive += inclusive;
+        }
+        inclusive
+    }
+
+    /// Returns the functions which used the most gas (exclusive of callees), from high to low.
+    pub fn hottest_functions(&self, limit: usize) -> Vec<(&str, &FunctionGas)> {
+        let mut functions = self
+            .functions
+            .iter()
+            .map(|(name, gas)| (name.as_str(), gas))
+            .collect::<Vec<_>>();
+        functions.sort_by(|(_, gas1), (_, gas2)| gas2.exclusive.cmp(&gas1.exclusive));
+        functions.truncate(limit);
+        functions
+    }
+
+    /// Returns the storage slots which used the most IO gas, from high to low.
+    pub fn hottest_storage_slots(&self, limit: usize) -> Vec<(&str, &StorageSlotGas)> {
+        let mut slots = self
+            .storage_slots
+            .iter()
+            .map(|(name, gas)| (name.as_str(), gas))
+            .collect::<Vec<_>>();
+        slots.sort_by(|(_, gas1), (_, gas2)| {
+            gas2.io
+                .cmp(&gas1.io)
+                .then(gas2.storage_fee.cmp(&gas1.storage_fee))
+        });
+        slots.truncate(limit);
+        slots
+    }
+
+    fn stack_profiles(&self) -> [StackProfile; 2] {
+        [
+            StackProfile {
+                name: "execution_and_io",
+                unit: "internal_gas",
+                stacks: &self.execution_and_io_stacks,
+            },
+            StackProfile {
+                name: "storage_fee",
+                unit: "octa",
+                stacks: &self.storage_stacks,
+            },
+        ]
+    }
+
+    /// Saves the aggregated profile as a speedscope file.
+    pub fn generate_speedscope_profile(&self, path: impl AsRef<Path>, name: &str) -> Result<()> {
+        let profile = export::to_speedscope(name, &self.stack_profiles());
+        fs::write(path, serde_json::to_vec(&profile)?)?;
+        Ok(())
+    }
+
+    /// Saves the aggregated profile as a gzip-compressed pprof file.
+    pub fn generate_pprof_profile(&self, path: impl AsRef<Path>) -> Result<()> {
+        fs::write(path, export::to_pprof(&self.stack_profiles())?)?;
+        Ok(())
+    }
+}
+
+/// Renders the state key in full, so that slots from different transactions can be distinguished.
+fn slot_name(key: &StateKey) -> String {
+    use aptos_types::{access_path::Path, state_store::state_key::inner::StateKeyInner::*};
+
+    match key.inner() {
+        AccessPath(ap) => match ap.get_path() {
+            Path::Code(module_id) => format!("code<{}>", module_id.short_str_lossless()),
+            Path::Resource(struct_tag) | Path::ResourceGroup(struct_tag) => format!(
+                "{}::{}",
+                ap.address.to_hex_literal(),
+                struct_tag.to_canonical_string()
+            ),
+        },
+        TableItem { handle, key } => format!(
+            "table_item<{},0x{}>",
+            handle.0.to_hex_literal(),
+            to_hex(key)
+        ),
+        Raw(bytes) => format!("raw<0x{}>", to_hex(bytes)),
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+impl TransactionGasLog {
+    /// Saves the profile of the transaction as a speedscope file.
+    pub fn generate_speedscope_profile(&self, path: impl AsRef<Path>, name: &str) -> Result<()> {
+        let mut aggregated = AggregatedTransactionGasLogs::new();
+        aggregated.add(self);
+        aggregated.generate_speedscope_profile(path, name)
+    }
+
+    /// Saves the profile of the transaction as a gzip-compressed pprof file.
+    pub fn generate_pprof_profile(&self, path: impl AsRef<Path>) -> Result<()> {
+        let mut aggregated = AggregatedTransactionGasLogs::new();
+        aggregated.add(self);
+        aggregated.generate_pprof_profile(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_types::state_store::table::TableHandle;
+    use move_core_types::account_address::AccountAddress;
+
+    #[test]
+    fn test_slot_name() {
+        let table_item = StateKey::table_item(&TableHandle(AccountAddress::ONE), &[0xAB, 0x01]);
+        assert_eq!(slot_name(&table_item), "table_item<0x1,0xab01>");
+
+        let raw = StateKey::raw(&[0x00, 0xFF]);
+        assert_eq!(slot_name(&raw), "raw<0x00ff>");
+    }
+}
@@ -0,0 +1,343 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Exports folded stacks of gas costs into formats understood by external profiling tools:
+//!   - [speedscope](https://www.speedscope.app), as JSON using the speedscope file format,
+//!   - [pprof](https://github.com/google/pprof), as a gzip-compressed `profile.proto`.
+
+use flate2::{write::GzEncoder, Compression};
+use serde_json::{json, Value};
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::Write,
+};
+
+/// Folded stacks of a single kind of cost (e.g., execution gas), along with the
+/// name and unit of the costs.
+pub(crate) struct StackProfile<'a> {
+    pub name: &'a str,
+    pub unit: &'a str,
+    /// Maps semicolon-separated frames (root first) to their costs.
+    pub stacks: &'a BTreeMap<String, u64>,
+}
+
+/// Interns frame names, assigning each a unique (0-based) index.
+#[derive(Default)]
+struct FrameTable {
+    names: Vec<String>,
+    indices: HashMap<String, usize>,
+}
+
+impl FrameTable {
+    fn intern(&mut self, name: &str) -> usize {
+        if let Some(idx) = self.indices.get(name) {
+            return *idx;
+        }
+        let idx = self.names.len();
+        self.names.push(name.to_string());
+        self.indices.insert(name.to_string(), idx);
+        idx
+    }
+
+    fn intern_stack(&mut self, stack: &str) -> Vec<usize> {
+        stack.split(';').map(|frame| self.intern(frame)).collect()
+    }
+}
+
+/// Creates a speedscope file, with one sampled profile per kind of cost.
+pub(crate) fn to_speedscope(name: &str, profiles: &[StackProfile]) -> Value {
+    let mut frames = FrameTable::default();
+
+    let profiles = profiles
+        .iter()
+        .map(|profile| {
+            let mut samples = vec![];
+            let mut weights = vec![];
+            for (stack, cost) in profile.stacks {
+                samples.push(frames.intern_stack(stack));
+                weights.push(*cost);
+            }
+            json!({
+                "type": "sampled",
+                "name": format!("{} ({})", profile.name, profile.unit),
+                "unit": "none",
+                "startValue": 0,
+                "endValue": weights.iter().sum::<u64>(),
+                "samples": samples,
+                "weights": weights,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    json!({
+        "$schema": "https://www.speedscope.app/file-format-schema.json",
+        "name": name,
+        "exporter": "aptos-gas-profiling",
+        "activeProfileIndex": 0,
+        "shared": {
+            "frames": frames
+                .names
+                .iter()
+                .map(|name| json!({ "name": name }))
+                .collect::<Vec<_>>(),
+        },
+        "profiles": profiles,
+    })
+}
+
+/// Minimal protobuf encoder for the messages of `profile.proto`.
+#[derive(Default)]
+struct ProtoBuf(Vec<u8>);
+
+impl ProtoBuf {
+    const WIRE_TYPE_LEN: u64 = 2;
+    const WIRE_TYPE_VARINT: u64 = 0;
+
+    fn varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.0.push((value as u8) | 0x80);
+            value >>= 7;
+        }
+        self.0.push(value as u8);
+    }
+
+    fn uint64(&mut self, field: u64, value: u64) {
+        self.varint((field << 3) | Self::WIRE_TYPE_VARINT);
+        self.varint(value);
+    }
+
+    fn bytes(&mut self, field: u64, bytes: &[u8]) {
+        self.varint((field << 3) | Self::WIRE_TYPE_LEN);
+        self.varint(bytes.len() as u64);
+        self.0.extend_from_slice(bytes);
+    }
+
+    fn packed(&mut self, field: u64, values: impl IntoIterator<Item = u64>) {
+        let mut packed = ProtoBuf::default();
+        for value in values {
+            packed.varint(value);
+        }
+        self.bytes(field, &packed.0);
+    }
+
+    fn message(&mut self, field: u64, build: impl FnOnce(&mut ProtoBuf)) {
+        let mut message = ProtoBuf::default();
+        build(&mut message);
+        self.bytes(field, &message.0);
+    }
+}
+
+/// Creates a gzip-compressed pprof profile. Every kind of cost is a separate sample type, so that
+/// tools can switch between them (e.g., using `-sample_index` in `go tool pprof`).
+pub(crate) fn to_pprof(profiles: &[StackProfile]) -> anyhow::Result<Vec<u8>> {
+    // Field numbers of the Profile message.
+    const SAMPLE_TYPE: u64 = 1;
+    const SAMPLE: u64 = 2;
+    const LOCATION: u64 = 4;
+    const FUNCTION: u64 = 5;
+    const STRING_TABLE: u64 = 6;
+
+    // Strings are referenced by their index, where the first one must be empty.
+    let mut strings = FrameTable::default();
+    strings.intern("");
+    // Frames are referenced by their (1-based) ids, which are used for both locations and
+    // functions.
+    let mut frames = FrameTable::default();
+
+    let mut profile = ProtoBuf::default();
+    for sample_type in profiles {
+        let type_idx = strings.intern(sample_type.name) as u64;
+        let unit_idx = strings.intern(sample_type.unit) as u64;
+        profile.message(SAMPLE_TYPE, |value_type| {
+            value_type.uint64(1, type_idx);
+            value_type.uint64(2, unit_idx);
+        });
+    }
+
+    for (idx, sample_profile) in profiles.iter().enumerate() {
+        for (stack, cost) in sample_profile.stacks {
+            // Locations are ordered from the leaf to the root.
+            let location_ids = frames
+                .intern_stack(stack)
+                .into_iter()
+                .rev()
+                .map(|frame_idx| frame_idx as u64 + 1)
+                .collect::<Vec<_>>();
+            let values = (0..profiles.len()).map(|i| if i == idx { *cost } else { 0 });
+            profile.message(SAMPLE, |sample| {
+                sample.packed(1, location_ids);
+                sample.packed(2, values);
+            });
+        }
+    }
+
+    for (frame_idx, name) in frames.names.iter().enumerate() {
+        let id = frame_idx as u64 + 1;
+        let name_idx = strings.intern(name) as u64;
+        profile.message(LOCATION, |location| {
+            location.uint64(1, id);
+            location.message(4, |line| line.uint64(1, id));
+        });
+        profile.message(FUNCTION, |function| {
+            function.uint64(1, id);
+            function.uint64(2, name_idx);
+            function.uint64(3, name_idx);
+        });
+    }
+
+    for string in &strings.names {
+        profile.bytes(STRING_TABLE, string.as_bytes());
+    }
+
+    let mut encoder = GzEncoder::new(vec![], Compression::default());
+    encoder.write_all(&profile.0)?;
+    Ok(encoder.finish()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::GzDecoder;
+    use pprof::protos::{Message, Profile};
+    use std::io::Read;
+
+    fn create_stacks(stacks: &[(&str, u64)]) -> BTreeMap<String, u64> {
+        stacks
+            .iter()
+            .map(|(stack, cost)| (stack.to_string(), *cost))
+            .collect()
+    }
+
+    #[test]
+    fn test_speedscope() {
+        let execution = create_stacks(&[("txn;0x1::coin::transfer", 10), ("txn", 5)]);
+        let io = create_stacks(&[("txn;0x1::coin::transfer;read", 3)]);
+        let profile = to_speedscope("test", &[
+            StackProfile {
+                name: "execution",
+                unit: "gas units",
+                stacks: &execution,
+            },
+            StackProfile {
+                name: "io",
+                unit: "gas units",
+                stacks: &io,
+            },
+        ]);
+
+        assert_eq!(
+            profile,
+            json!({
+                "$schema": "https://www.speedscope.app/file-format-schema.json",
+                "name": "test",
+                "exporter": "aptos-gas-profiling",
+                "activeProfileIndex": 0,
+                "shared": {
+                    "frames": [
+                        { "name": "txn" },
+                        { "name": "0x1::coin::transfer" },
+                        { "name": "read" },
+                    ],
+                },
+                "profiles": [
+                    {
+                        "type": "sampled",
+                        "name": "execution (gas units)",
+                        "unit": "none",
+                        "startValue": 0,
+                        "endValue": 15,
+                        "samples": [[0], [0, 1]],
+                        "weights": [5, 10],
+                    },
+                    {
+                        "type": "sampled",
+                        "name": "io (gas units)",
+                        "unit": "none",
+                        "startValue": 0,
+                        "endValue": 3,
+                        "samples": [[0, 1, 2]],
+                        "weights": [3],
+                    },
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn test_pprof_round_trip() {
+        let execution = create_stacks(&[("txn;0x1::coin::transfer", 10), ("txn", 5)]);
+        let io = create_stacks(&[("txn;0x1::coin::transfer;read", 3)]);
+        let profiles = [
+            StackProfile {
+                name: "execution",
+                unit: "gas units",
+                stacks: &execution,
+            },
+            StackProfile {
+                name: "io",
+                unit: "octas",
+                stacks: &io,
+            },
+        ];
+
+        // Decode the profile using the pprof protobuf definitions
+        let mut bytes = vec![];
+        GzDecoder::new(to_pprof(&profiles).unwrap().as_slice())
+            .read_to_end(&mut bytes)
+            .unwrap();
+        let profile = Profile::parse_from_bytes(&bytes).unwrap();
+        let strings = profile.get_string_table();
+        assert_eq!(strings[0], "");
+
+        // Verify the sample types
+        let sample_types = profile
+            .get_sample_type()
+            .iter()
+            .map(|sample_type| {
+                (
+                    strings[sample_type.get_field_type() as usize].as_str(),
+                    strings[sample_type.get_unit() as usize].as_str(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(sample_types, vec![
+            ("execution", "gas units"),
+            ("io", "octas")
+        ]);
+
+        // Rebuild the folded stacks of every sample type from the samples
+        let function_names = profile
+            .get_function()
+            .iter()
+            .map(|function| (function.get_id(), &strings[function.get_name() as usize]))
+            .collect::<HashMap<_, _>>();
+        let frame_names = profile
+            .get_location()
+            .iter()
+            .map(|location| {
+                let lines = location.get_line();
+                assert_eq!(lines.len(), 1);
+                (
+                    location.get_id(),
+                    function_names[&lines[0].get_function_id()],
+                )
+            })
+            .collect::<HashMap<_, _>>();
+        let mut decoded = vec![BTreeMap::new(); profiles.len()];
+        for sample in profile.get_sample() {
+            let stack = sample
+                .get_location_id()
+                .iter()
+                .rev()
+                .map(|location_id| frame_names[location_id].as_str())
+                .collect::<Vec<_>>()
+                .join(";");
+            for (idx, value) in sample.get_value().iter().enumerate() {
+                if *value != 0 {
+                    decoded[idx].insert(stack.clone(), *value as u64);
+                }
+            }
+        }
+        assert_eq!(decoded, vec![execution, io]);
+    }
+}
@@ -10,7 +10,7 @@ use move_core_types::gas_algebra::InternalGas;
 use regex::Captures;
 
 #[derive(Debug)]
-struct LineBuffer(Vec<String>);
+struct LineBuffer(Vec<(String, u64)>);
 
 impl LineBuffer {
     fn new() -> Self {
@@ -21,19 +21,26 @@ impl LineBuffer {
         let count: u64 = count.into();
 
         if count > 0 {
-            self.0.push(format!("{} {}", item.as_ref(), count));
+            self.0.push((item.as_ref().to_string(), count));
         }
     }
 
-    fn into_inner(self) -> Vec<String> {
+    fn into_inner(self) -> Vec<(String, u64)> {
         self.0
     }
 }
 
+fn to_folded_stack_lines(stacks: Vec<(String, u64)>) -> Vec<String> {
+    stacks
+        .into_iter()
+        .map(|(stack, count)| format!("{} {}", stack, count))
+        .collect()
+}
+
 impl StorageFees {
-    /// Convert the storage fee log into folded stack lines, which can
-    /// then be used to generate a flamegraph.
-    fn to_folded_stack_lines(&self) -> Vec<String> {
+    /// Convert the storage fee log into folded stacks (semicolon-separated frames
+    /// and the fees in Octa), which can then be used to generate a flamegraph.
+    pub(crate) fn to_folded_stacks(&self) -> Vec<(String, u64)> {
         let mut lines = LineBuffer::new();
 
         lines.push("transaction", self.txn_storage);
@@ -59,7 +66,7 @@ impl StorageFees {
     /// Tries to generate a flamegraph from the execution log.
     /// None will be returned if the log is empty.
     pub fn to_flamegraph(&self, title: String) -> anyhow::Result<Option<Vec<u8>>> {
-        let lines = self.to_folded_stack_lines();
+        let lines = to_folded_stack_lines(self.to_folded_stacks());
 
         if lines.is_empty() {
             return Ok(None);
@@ -97,9 +104,9 @@ impl StorageFees {
 }
 
 impl ExecutionAndIOCosts {
-    /// Convert the execution gas log into folded stack lines, which can
-    /// then be used to generate a flamegraph.
-    fn to_folded_stack_lines(&self) -> Vec<String> {
+    /// Convert the execution gas log into folded stacks (semicolon-separated frames
+    /// and the internal gas), which can then be used to generate a flamegraph.
+    pub(crate) fn to_folded_stacks(&self) -> Vec<(String, u64)> {
         let mut lines = LineBuffer::new();
 
         lines.push("intrinsic", self.intrinsic_cost);
@@ -199,7 +206,7 @@ impl ExecutionAndIOCosts {
     /// Tries to generate a flamegraph from the execution log.
     /// None will be returned if the log is empty.
     pub fn to_flamegraph(&self, title: String) -> anyhow::Result<Option<Vec<u8>>> {
-        let lines = self.to_folded_stack_lines();
+        let lines = to_folded_stack_lines(self.to_folded_stacks());
 
         if lines.is_empty() {
             return Ok(None);
@@ -3,6 +3,7 @@
 
 mod aggregate;
 mod erased;
+mod export;
 mod flamegraph;
 mod log;
 mod misc;
@@ -10,5 +11,6 @@ mod profiler;
 mod render;
 mod report;
 
+pub use aggregate::{AggregatedTransactionGasLogs, FunctionGas, StorageSlotGas};
 pub use log::{FrameName, TransactionGasLog};
 pub use profiler::GasProfiler;
@@ -0,0 +1,68 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+use url::Url;
+
+/// The state a session starts from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BaseState {
+    /// Genesis built from the framework of the current development branch. The genesis state is
+    /// stored as part of the session delta.
+    Empty,
+    /// Fork of a remote network. The state is the one right after the transaction at
+    /// `network_version` was committed, and is fetched lazily through the REST API.
+    Remote {
+        node_url: Url,
+        network_version: u64,
+        api_key: Option<String>,
+    },
+}
+
+/// Configuration of a session, persisted as `config.json` in the session directory.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Config {
+    pub base: BaseState,
+    /// Number of operations applied to the session so far.
+    pub ops: u64,
+}
+
+impl Config {
+    pub fn new() -> Self {
+        Self {
+            base: BaseState::Empty,
+            ops: 0,
+        }
+    }
+
+    pub fn with_remote(node_url: Url, network_version: u64, api_key: Option<String>) -> Self {
+        Self {
+            base: BaseState::Remote {
+                node_url,
+                network_version,
+                api_key,
+            },
+            ops: 0,
+        }
+    }
+
+    pub fn load_from_file(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read session config {}", path.display()))?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    pub fn save_to_file(&self, path: &Path) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write session config {}", path.display()))
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self::new()
+    }
+}
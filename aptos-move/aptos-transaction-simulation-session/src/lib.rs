@@ -0,0 +1,30 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Simulation sessions, built on top of [`aptos_transaction_simulation`].
+//!
+//! ## Overview
+//! A [`Session`] starts either from a local genesis, or from a fork of a remote network at a given
+//! version. In the latter case, state values are fetched lazily through the REST API of the network
+//! and cached, so only the state touched by the simulation is ever downloaded.
+//!
+//! Transactions, time advances and resource edits are then applied on top of the base state. The
+//! changes never leave the session: nothing is submitted to the network.
+//!
+//! ## Session Directory
+//! Sessions are persisted after every operation, so that they can be resumed later (e.g., by
+//! separate invocations of the CLI). A session directory contains:
+//! - `config.json`: the base state of the session and the number of operations applied so far.
+//! - `delta.json`: the state changes applied on top of the base state.
+//! - `remote_cache.json`: the state values fetched from the remote network, if any.
+//! - `[<n>] <operation>/`: the outputs of the n-th operation, e.g., the events and the write set of
+//!   a transaction.
+
+mod config;
+mod session;
+mod state_file;
+mod state_store;
+
+pub use config::{BaseState, Config};
+pub use session::{Session, SessionStateStore};
+pub use state_store::CachedStateView;
@@ -0,0 +1,693 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    config::{BaseState, Config},
+    state_file::{load_state_values, save_state_values},
+    state_store::CachedStateView,
+};
+use anyhow::{anyhow, bail, Context, Result};
+use aptos_api_types::{MoveStructValue, MoveValue};
+use aptos_resource_viewer::AptosValueAnnotator;
+use aptos_rest_client::{AptosBaseUrl, Client};
+use aptos_transaction_simulation::{
+    DeltaStateStore, EitherStateView, EmptyStateView, SimulationStateStore, GENESIS_CHANGE_SET_HEAD,
+};
+use aptos_types::{
+    account_address::AccountAddress,
+    chain_id::ChainId,
+    state_store::{state_key::StateKey, state_value::StateValue, TStateView},
+    transaction::{
+        RawTransaction, SignedTransaction, TransactionExecutableRef, TransactionOutput,
+        TransactionPayload, TransactionStatus,
+    },
+};
+use aptos_validator_interface::{DebuggerStateView, RestDebuggerInterface};
+use aptos_vm::{data_cache::AsMoveResolver, AptosSimulationVM, AptosVM};
+use aptos_vm_environment::environment::AptosEnvironment;
+use aptos_vm_logging::log_schema::AdapterLogSchema;
+use aptos_vm_types::module_and_script_storage::AsAptosCodeStorage;
+use move_core_types::{
+    identifier::Identifier,
+    language_storage::{ModuleId, StructTag, TypeTag},
+    vm_status::VMStatus,
+};
+use serde::Serialize;
+use serde_json::json;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+use url::Url;
+
+const CONFIG_FILE: &str = "config.json";
+const DELTA_FILE: &str = "delta.json";
+const REMOTE_CACHE_FILE: &str = "remote_cache.json";
+
+/// The state store of a session: the changes of the session stacked on top of either nothing
+/// (the genesis is part of the changes) or the cached state of a remote network.
+pub type SessionStateStore =
+    DeltaStateStore<EitherStateView<EmptyStateView, CachedStateView<DebuggerStateView>>>;
+
+/// A simulation session, persisted as a directory. See the crate documentation for the layout of
+/// the directory.
+pub struct Session {
+    path: PathBuf,
+    config: Config,
+    state_store: SessionStateStore,
+}
+
+impl Session {
+    /// Creates a new session in the given directory, starting from the genesis built from the
+    /// framework of the current development branch.
+    pub fn init(session_path: impl AsRef<Path>) -> Result<Self> {
+        let path = create_session_dir(session_path.as_ref())?;
+        let config = Config::new();
+
+        let state_store =
+            SessionStateStore::new_with_base(base_state_view(&config.base, HashMap::new())?);
+        state_store.set_chain_id(ChainId::test())?;
+        state_store.apply_write_set(GENESIS_CHANGE_SET_HEAD.write_set())?;
+
+        let session = Self {
+            path,
+            config,
+            state_store,
+        };
+        session.save()?;
+        Ok(session)
+    }
+
+    /// Creates a new session in the given directory, forking the remote network at the given
+    /// version.
+    ///
+    /// The state is fetched from the network in the background, so this must be called within a
+    /// Tokio runtime.
+    pub fn init_with_remote_state(
+        session_path: impl AsRef<Path>,
+        node_url: Url,
+        network_version: u64,
+        api_key: Option<String>,
+    ) -> Result<Self> {
+        let path = create_session_dir(session_path.as_ref())?;
+        let config = Config::with_remote(node_url, network_version, api_key);
+        let state_store =
+            SessionStateStore::new_with_base(base_state_view(&config.base, HashMap::new())?);
+
+        let session = Self {
+            path,
+            config,
+            state_store,
+        };
+        session.save()?;
+        Ok(session)
+    }
+
+    /// Loads an existing session from the given directory.
+    ///
+    /// If the session forks a remote network, this must be called within a Tokio runtime.
+    pub fn load(session_path: impl AsRef<Path>) -> Result<Self> {
+        let path = session_path.as_ref().to_path_buf();
+        let config = Config::load_from_file(&path.join(CONFIG_FILE))?;
+
+        let cache_path = path.join(REMOTE_CACHE_FILE);
+        let cache = if cache_path.exists() {
+            load_state_values(&cache_path)?
+        } else {
+            HashMap::new()
+        };
+        let base = base_state_view(&config.base, cache)?;
+        let delta = load_state_values(&path.join(DELTA_FILE))?;
+
+        Ok(Self {
+            path,
+            config,
+            state_store: SessionStateStore::new_with_base_and_delta(base, delta),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Returns the current state of the session. Changes made directly to the state store are
+    /// only persisted by the next operation on the session.
+    pub fn state_store(&self) -> &SessionStateStore {
+        &self.state_store
+    }
+
+    /// Executes a user transaction on top of the session state, and applies its write set if the
+    /// transaction is kept.
+    pub fn execute_transaction(
+        &mut self,
+        txn: SignedTransaction,
+    ) -> Result<(VMStatus, TransactionOutput)> {
+        let env = AptosEnvironment::new(&self.state_store);
+        let vm = AptosVM::new(&env, &self.state_store);
+        let log_context = AdapterLogSchema::new(self.state_store.id(), 0);
+
+        let resolver = self.state_store.as_move_resolver();
+        let code_storage = self.state_store.as_aptos_code_storage(&env);
+
+        let (vm_status, vm_output) =
+            vm.execute_user_transaction(&resolver, &code_storage, &txn, &log_context);
+        let txn_output = vm_output
+            .try_materialize_into_transaction_output(&resolver)
+            .map_err(|status| anyhow!("Failed to materialize transaction output: {}", status))?;
+
+        let op_name = format!("execute {}", payload_name(txn.payload()));
+        self.commit_transaction(&op_name, txn.sender(), &vm_status, &txn_output)?;
+        Ok((vm_status, txn_output))
+    }
+
+    /// Executes a transaction on behalf of its sender without the sender's keys, on top of the
+    /// session state, and applies its write set if the transaction is kept.
+    ///
+    /// The transaction is run by the simulation VM, see
+    /// [`AptosSimulationVM::create_vm_and_simulate_impersonated_transaction`].
+    pub fn execute_impersonated_transaction(
+        &mut self,
+        raw_txn: RawTransaction,
+    ) -> Result<(VMStatus, TransactionOutput)> {
+        if !self
+            .state_store
+            .get_features()?
+            .is_transaction_simulation_enhancement_enabled()
+        {
+            bail!(
+                "Impersonation requires the TRANSACTION_SIMULATION_ENHANCEMENT feature, enable it \
+                 by editing the 0x1::features::Features resource of the session"
+            );
+        }
+
+        let sender = raw_txn.sender();
+        let op_name = format!("impersonate {}", payload_name(raw_txn.payload()));
+        let (vm_status, txn_output) =
+            AptosSimulationVM::create_vm_and_simulate_impersonated_transaction(
+                raw_txn,
+                &self.state_store,
+            );
+
+        self.commit_transaction(&op_name, sender, &vm_status, &txn_output)?;
+        Ok((vm_status, txn_output))
+    }
+
+    /// Applies the write set of a kept transaction, and records its outputs.
+    fn commit_transaction(
+        &mut self,
+        op_name: &str,
+        sender: AccountAddress,
+        vm_status: &VMStatus,
+        txn_output: &TransactionOutput,
+    ) -> Result<()> {
+        if let TransactionStatus::Keep(_) = txn_output.status() {
+            self.state_store.apply_write_set(txn_output.write_set())?;
+        }
+
+        let op_dir = self.create_op_dir(op_name)?;
+
+        let annotator = AptosValueAnnotator::new(&self.state_store);
+        let events = txn_output
+            .events()
+            .iter()
+            .map(|event| -> Result<serde_json::Value> {
+                let data = annotator.view_value(event.type_tag(), event.event_data())?;
+                Ok(json!({
+                    "type": event.type_tag().to_canonical_string(),
+                    "data": MoveValue::try_from(data)?,
+                }))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        save_json(
+            &op_dir.join("summary.json"),
+            &json!({
+                "sender": sender,
+                "vm_status": vm_status.to_string(),
+                "status": format!("{:?}", txn_output.status()),
+                "gas_used": txn_output.gas_used(),
+            }),
+        )?;
+        save_json(&op_dir.join("events.json"), &events)?;
+        save_state_values(
+            &op_dir.join("write_set.json"),
+            txn_output
+                .write_set()
+                .write_op_iter()
+                .map(|(state_key, write_op)| (state_key, write_op.as_state_value_opt())),
+        )?;
+
+        self.save()
+    }
+
+    /// Executes a view function on top of the session state, and returns the BCS serialized return
+    /// values.
+    pub fn execute_view_function(
+        &mut self,
+        module_id: ModuleId,
+        function_name: Identifier,
+        ty_args: Vec<TypeTag>,
+        args: Vec<Vec<u8>>,
+    ) -> Result<Vec<Vec<u8>>> {
+        let output = AptosVM::execute_view_function(
+            &self.state_store,
+            module_id.clone(),
+            function_name.clone(),
+            ty_args,
+            args,
+            u64::MAX,
+        );
+        let values = output
+            .values
+            .map_err(|err| anyhow!("Failed to execute view function: {:?}", err))?;
+
+        let op_dir = self.create_op_dir(&format!(
+            "view {}::{}::{}",
+            module_id.address().short_str_lossless(),
+            module_id.name(),
+            function_name
+        ))?;
+        save_json(
+            &op_dir.join("summary.json"),
+            &json!({
+                "gas_used": output.gas_used,
+                "values": values.iter().map(hex::encode).collect::<Vec<_>>(),
+            }),
+        )?;
+
+        self.save()?;
+        Ok(values)
+    }
+
+    /// Advances the on-chain time (`0x1::timestamp`) by the given duration, and returns the new
+    /// time in microseconds.
+    pub fn advance_time(&mut self, duration: Duration) -> Result<u64> {
+        let old_time = self.state_store.get_timestamp_micros()?;
+        let new_time = u64::try_from(duration.as_micros())
+            .ok()
+            .and_then(|duration| old_time.checked_add(duration))
+            .ok_or_else(|| anyhow!("Time overflow when advancing by {:?}", duration))?;
+        self.set_time(old_time, new_time, "advance time")?;
+        Ok(new_time)
+    }
+
+    /// Warps the on-chain time (`0x1::timestamp`) to the given time in microseconds, which may be
+    /// in the past.
+    pub fn warp_time(&mut self, timestamp_micros: u64) -> Result<()> {
+        let old_time = self.state_store.get_timestamp_micros()?;
+        self.set_time(old_time, timestamp_micros, "warp time")
+    }
+
+    fn set_time(&mut self, old_time: u64, new_time: u64, op_name: &str) -> Result<()> {
+        self.state_store.set_timestamp_micros(new_time)?;
+
+        let op_dir = self.create_op_dir(op_name)?;
+        save_json(
+            &op_dir.join("summary.json"),
+            &json!({
+                "old_time_microseconds": old_time,
+                "new_time_microseconds": new_time,
+            }),
+        )?;
+
+        self.save()
+    }
+
+    /// Sets the balance of the primary fungible store of `owner` for the fungible asset with the
+    /// given metadata address. The supply of the asset is not updated.
+    pub fn set_fungible_balance(
+        &mut self,
+        owner: AccountAddress,
+        metadata: AccountAddress,
+        balance: u64,
+    ) -> Result<()> {
+        let old_balance = self.state_store.get_fungible_balance(owner, metadata)?;
+        self.state_store
+            .set_fungible_balance(owner, metadata, balance)?;
+
+        let op_dir = self.create_op_dir(&format!("set balance {}", owner.short_str_lossless()))?;
+        save_json(
+            &op_dir.join("summary.json"),
+            &json!({
+                "owner": owner,
+                "metadata": metadata,
+                "old_balance": old_balance,
+                "new_balance": balance,
+            }),
+        )?;
+
+        self.save()
+    }
+
+    /// Overwrites the raw state value under the given key, or removes it if `value` is `None`.
+    /// Unlike [`Session::set_resource`], the value is not validated.
+    pub fn set_raw_state_value(
+        &mut self,
+        state_key: StateKey,
+        value: Option<StateValue>,
+    ) -> Result<()> {
+        save_state_values(
+            &self
+                .create_op_dir("set raw state value")?
+                .join("write_set.json"),
+            [(&state_key, value.as_ref())],
+        )?;
+        match value {
+            Some(value) => self.state_store.set_state_value(state_key, value)?,
+            None => self.state_store.remove_state_value(&state_key)?,
+        }
+
+        self.save()
+    }
+
+    /// Overwrites (or creates) the resource of the given type at the given address with the BCS
+    /// serialized value. Resources that are members of a resource group are written into their
+    /// group.
+    pub fn set_resource(
+        &mut self,
+        addr: AccountAddress,
+        struct_tag: &StructTag,
+        blob: Vec<u8>,
+    ) -> Result<()> {
+        // Make sure that the value matches the layout of the resource before writing it.
+        AptosValueAnnotator::new(&self.state_store)
+            .view_resource(struct_tag, &blob)
+            .with_context(|| format!("Invalid value for resource {}", struct_tag))?;
+        self.write_resource_bytes(addr, struct_tag, Some(blob.clone()))?;
+
+        let op_dir = self.create_op_dir(&format!("set resource {}", struct_tag.name))?;
+        save_json(
+            &op_dir.join("summary.json"),
+            &json!({
+                "address": addr,
+                "resource": struct_tag.to_canonical_string(),
+                "bcs": hex::encode(blob),
+            }),
+        )?;
+
+        self.save()
+    }
+
+    /// Removes the resource of the given type at the given address.
+    pub fn remove_resource(&mut self, addr: AccountAddress, struct_tag: &StructTag) -> Result<()> {
+        self.write_resource_bytes(addr, struct_tag, None)?;
+
+        let op_dir = self.create_op_dir(&format!("remove resource {}", struct_tag.name))?;
+        save_json(
+            &op_dir.join("summary.json"),
+            &json!({
+                "address": addr,
+                "resource": struct_tag.to_canonical_string(),
+            }),
+        )?;
+
+        self.save()
+    }
+
+    /// Returns the JSON representation of the resource of the given type at the given address, if
+    /// it exists.
+    pub fn view_resource(
+        &self,
+        addr: AccountAddress,
+        struct_tag: &StructTag,
+    ) -> Result<Option<serde_json::Value>> {
+        let blob = match self.read_resource_bytes(addr, struct_tag)? {
+            Some(blob) => blob,
+            None => return Ok(None),
+        };
+        let annotated =
+            AptosValueAnnotator::new(&self.state_store).view_resource(struct_tag, &blob)?;
+        Ok(Some(serde_json::to_value(MoveStructValue::try_from(
+            annotated,
+        )?)?))
+    }
+
+    fn resource_group(&self, struct_tag: &StructTag) -> Option<StructTag> {
+        AptosValueAnnotator::new(&self.state_store).view_resource_group_member(struct_tag)
+    }
+
+    fn read_resource_group(
+        &self,
+        addr: AccountAddress,
+        group_tag: &StructTag,
+    ) -> Result<(StateKey, Option<StateValue>, BTreeMap<StructTag, Vec<u8>>)> {
+        let state_key = StateKey::resource_group(&addr, group_tag);
+        let state_value = self.state_store.get_state_value(&state_key)?;
+        let group = match &state_value {
+            Some(state_value) => bcs::from_bytes(state_value.bytes())?,
+            None => BTreeMap::new(),
+        };
+        Ok((state_key, state_value, group))
+    }
+
+    fn read_resource_bytes(
+        &self,
+        addr: AccountAddress,
+        struct_tag: &StructTag,
+    ) -> Result<Option<Vec<u8>>> {
+        Ok(match self.resource_group(struct_tag) {
+            Some(group_tag) => {
+                let (_, _, mut group) = self.read_resource_group(addr, &group_tag)?;
+                group.remove(struct_tag)
+            },
+            None => self
+                .state_store
+                .get_state_value_bytes(&StateKey::resource(&addr, struct_tag)?)?
+                .map(|bytes| bytes.to_vec()),
+        })
+    }
+
+    /// Writes the resource, keeping the metadata of the existing state value (if any).
+    fn write_resource_bytes(
+        &self,
+        addr: AccountAddress,
+        struct_tag: &StructTag,
+        blob: Option<Vec<u8>>,
+    ) -> Result<()> {
+        let (state_key, state_value, bytes) = match self.resource_group(struct_tag) {
+            Some(group_tag) => {
+                let (state_key, state_value, mut group) =
+                    self.read_resource_group(addr, &group_tag)?;
+                match blob {
+                    Some(blob) => {
+                        group.insert(struct_tag.clone(), blob);
+                    },
+                    None => {
+                        if group.remove(struct_tag).is_none() {
+                            bail!("Resource {} does not exist at {}", struct_tag, addr);
+                        }
+                    },
+                }
+                let bytes = (!group.is_empty())
+                    .then(|| bcs::to_bytes(&group))
+                    .transpose()?;
+                (state_key, state_value, bytes)
+            },
+            None => {
+                let state_key = StateKey::resource(&addr, struct_tag)?;
+                let state_value = self.state_store.get_state_value(&state_key)?;
+                if blob.is_none() && state_value.is_none() {
+                    bail!("Resource {} does not exist at {}", struct_tag, addr);
+                }
+                (state_key, state_value, blob)
+            },
+        };
+
+        match (state_value, bytes) {
+            (Some(mut state_value), Some(bytes)) => {
+                state_value.set_bytes(bytes.into());
+                self.state_store.set_state_value(state_key, state_value)
+            },
+            (None, Some(bytes)) => self
+                .state_store
+                .set_state_value(state_key, StateValue::new_legacy(bytes.into())),
+            (_, None) => self.state_store.remove_state_value(&state_key),
+        }
+    }
+
+    /// Creates the output directory of the next operation.
+    fn create_op_dir(&mut self, name: &str) -> Result<PathBuf> {
+        let op_dir = self.path.join(format!("[{}] {}", self.config.ops, name));
+        fs::create_dir_all(&op_dir)
+            .with_context(|| format!("Failed to create directory {}", op_dir.display()))?;
+        self.config.ops += 1;
+        Ok(op_dir)
+    }
+
+    fn save(&self) -> Result<()> {
+        self.config.save_to_file(&self.path.join(CONFIG_FILE))?;
+
+        let delta = self.state_store.delta();
+        save_state_values(
+            &self.path.join(DELTA_FILE),
+            delta.iter().map(|(k, v)| (k, v.as_ref())),
+        )?;
+
+        if let EitherStateView::Right(remote) = self.state_store.base() {
+            let cache = remote.cache();
+            save_state_values(
+                &self.path.join(REMOTE_CACHE_FILE),
+                cache.iter().map(|(k, v)| (k, v.as_ref())),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn create_session_dir(path: &Path) -> Result<PathBuf> {
+    if path.join(CONFIG_FILE).exists() {
+        bail!("A session already exists at {}", path.display());
+    }
+    fs::create_dir_all(path)
+        .with_context(|| format!("Failed to create session directory {}", path.display()))?;
+    Ok(path.to_path_buf())
+}
+
+fn base_state_view(
+    base: &BaseState,
+    cache: HashMap<StateKey, Option<StateValue>>,
+) -> Result<EitherStateView<EmptyStateView, CachedStateView<DebuggerStateView>>> {
+    Ok(match base {
+        BaseState::Empty => EitherStateView::Left(EmptyStateView),
+        BaseState::Remote {
+            node_url,
+            network_version,
+            api_key,
+        } => {
+            let mut builder = Client::builder(AptosBaseUrl::Custom(node_url.clone()));
+            if let Some(api_key) = api_key {
+                builder = builder.api_key(api_key)?;
+            }
+            let debugger = Arc::new(RestDebuggerInterface::new(builder.build()));
+            // The debugger state view at version `v` reads the state right before the transaction
+            // at `v` is executed, i.e., right after the transaction at `v - 1` is committed.
+            let state_view = DebuggerStateView::new(debugger, network_version + 1);
+            EitherStateView::Right(CachedStateView::new_with_cache(state_view, cache))
+        },
+    })
+}
+
+/// Returns a short description of a transaction payload, used to name its operation.
+fn payload_name(payload: &TransactionPayload) -> String {
+    match payload.executable_ref() {
+        Ok(TransactionExecutableRef::EntryFunction(entry_func)) => format!(
+            "{}::{}::{}",
+            entry_func.module().address().short_str_lossless(),
+            entry_func.module().name(),
+            entry_func.function()
+        ),
+        Ok(TransactionExecutableRef::Script(_)) => "script".to_string(),
+        _ => "transaction".to_string(),
+    }
+}
+
+fn save_json<T: Serialize + ?Sized>(path: &Path, value: &T) -> Result<()> {
+    fs::write(path, serde_json::to_string_pretty(value)?)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_cached_packages::aptos_stdlib;
+    use aptos_temppath::TempPath;
+    use aptos_types::{
+        account_config::AccountResource,
+        event::{EventHandle, EventKey},
+    };
+    use std::str::FromStr;
+
+    #[test]
+    fn test_session_persists_operations() {
+        let path = TempPath::new();
+        let mut session = Session::init(path.path()).unwrap();
+
+        let time = session.advance_time(Duration::from_secs(10)).unwrap();
+        assert_eq!(time, 10_000_000);
+
+        let struct_tag = StructTag::from_str("0x1::account::Account").unwrap();
+        let addr = AccountAddress::from_hex_literal("0xcafe").unwrap();
+        let account = AccountResource::new(
+            7,
+            vec![0; 32],
+            EventHandle::new(EventKey::new(0, addr), 0),
+            EventHandle::new(EventKey::new(1, addr), 0),
+        );
+        session
+            .set_resource(addr, &struct_tag, bcs::to_bytes(&account).unwrap())
+            .unwrap();
+
+        let session = Session::load(path.path()).unwrap();
+        assert_eq!(session.config().ops, 2);
+        assert_eq!(session.state_store().get_timestamp_micros().unwrap(), time);
+        assert_eq!(
+            session
+                .state_store()
+                .get_resource::<AccountResource>(addr)
+                .unwrap(),
+            Some(account)
+        );
+        assert_eq!(
+            session.view_resource(addr, &struct_tag).unwrap().unwrap()["sequence_number"],
+            json!("7")
+        );
+        assert!(path.path().join("[0] advance time").exists());
+        assert!(path.path().join("[1] set resource Account").exists());
+    }
+
+    #[test]
+    fn test_set_resource_rejects_invalid_value() {
+        let path = TempPath::new();
+        let mut session = Session::init(path.path()).unwrap();
+
+        let struct_tag = StructTag::from_str("0x1::account::Account").unwrap();
+        assert!(session
+            .set_resource(AccountAddress::ONE, &struct_tag, vec![1, 2, 3])
+            .is_err());
+        assert_eq!(session.config().ops, 0);
+    }
+
+    #[test]
+    fn test_impersonated_transfer() {
+        let path = TempPath::new();
+        let mut session = Session::init(path.path()).unwrap();
+
+        let sender = AccountAddress::from_hex_literal("0xcafe").unwrap();
+        let receiver = AccountAddress::from_hex_literal("0xbeef").unwrap();
+        session
+            .set_fungible_balance(sender, AccountAddress::TEN, 100_000_000)
+            .unwrap();
+
+        let raw_txn = RawTransaction::new(
+            sender,
+            0,
+            aptos_stdlib::aptos_account_transfer(receiver, 1_000),
+            1_000_000,
+            100,
+            u64::MAX,
+            ChainId::test(),
+        );
+        let (vm_status, txn_output) = session.execute_impersonated_transaction(raw_txn).unwrap();
+        assert_eq!(vm_status, VMStatus::Executed);
+        assert!(txn_output.status().status().unwrap().is_success());
+
+        assert_eq!(
+            session
+                .state_store()
+                .get_fungible_balance(receiver, AccountAddress::TEN)
+                .unwrap(),
+            Some(1_000)
+        );
+        assert!(path
+            .path()
+            .join("[1] impersonate 0x1::aptos_account::transfer")
+            .exists());
+    }
+}
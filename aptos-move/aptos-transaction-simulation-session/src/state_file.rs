@@ -0,0 +1,73 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Files of state values, used for the session delta, the remote cache and the write sets of the
+//! transactions. Keys and values are BCS serialized and hex encoded, deletions (or values that do
+//! not exist) are `null`. Entries are sorted so that the files can be diffed.
+
+use anyhow::{Context, Result};
+use aptos_types::state_store::{state_key::StateKey, state_value::StateValue};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::Path,
+};
+
+pub(crate) fn save_state_values<'a>(
+    path: &Path,
+    state_values: impl IntoIterator<Item = (&'a StateKey, Option<&'a StateValue>)>,
+) -> Result<()> {
+    let entries = state_values
+        .into_iter()
+        .map(|(state_key, state_value)| {
+            let key = hex::encode(bcs::to_bytes(state_key)?);
+            let value = state_value
+                .map(|state_value| bcs::to_bytes(state_value).map(hex::encode))
+                .transpose()?;
+            Ok((key, value))
+        })
+        .collect::<Result<BTreeMap<_, _>>>()?;
+    fs::write(path, serde_json::to_string_pretty(&entries)?)
+        .with_context(|| format!("Failed to write state values to {}", path.display()))
+}
+
+pub(crate) fn load_state_values(path: &Path) -> Result<HashMap<StateKey, Option<StateValue>>> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read state values from {}", path.display()))?;
+    let entries: BTreeMap<String, Option<String>> = serde_json::from_str(&content)?;
+    entries
+        .into_iter()
+        .map(|(key, value)| {
+            let state_key = bcs::from_bytes(&hex::decode(key)?)?;
+            let state_value = value
+                .map(|value| -> Result<StateValue> { Ok(bcs::from_bytes(&hex::decode(value)?)?) })
+                .transpose()?;
+            Ok((state_key, state_value))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_temppath::TempPath;
+
+    #[test]
+    fn test_state_values_round_trip() {
+        let state_values = HashMap::from([
+            (
+                StateKey::raw(b"key1"),
+                Some(StateValue::new_legacy(b"value1".to_vec().into())),
+            ),
+            (StateKey::raw(b"key2"), None),
+        ]);
+
+        let path = TempPath::new();
+        save_state_values(
+            path.path(),
+            state_values.iter().map(|(k, v)| (k, v.as_ref())),
+        )
+        .unwrap();
+        assert_eq!(load_state_values(path.path()).unwrap(), state_values);
+    }
+}
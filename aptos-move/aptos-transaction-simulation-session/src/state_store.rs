@@ -0,0 +1,99 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_types::{
+    state_store::{
+        state_key::StateKey, state_storage_usage::StateStorageUsage, state_value::StateValue,
+        StateViewId, StateViewResult, TStateView,
+    },
+    transaction::Version,
+};
+use parking_lot::RwLock;
+use std::collections::HashMap;
+
+/// A read-through cache on top of a state view, e.g., one that fetches the state of a remote
+/// network. Values that do not exist are cached as well.
+///
+/// The cached values can be extracted and used to pre-populate the cache later, so that the state
+/// read by a session is fetched only once across invocations.
+pub struct CachedStateView<V> {
+    base: V,
+    cache: RwLock<HashMap<StateKey, Option<StateValue>>>,
+}
+
+impl<V> CachedStateView<V> {
+    pub fn new(base: V) -> Self {
+        Self::new_with_cache(base, HashMap::new())
+    }
+
+    pub fn new_with_cache(base: V, cache: HashMap<StateKey, Option<StateValue>>) -> Self {
+        Self {
+            base,
+            cache: RwLock::new(cache),
+        }
+    }
+
+    /// Returns all the values read so far.
+    pub fn cache(&self) -> HashMap<StateKey, Option<StateValue>> {
+        self.cache.read().clone()
+    }
+}
+
+impl<V> TStateView for CachedStateView<V>
+where
+    V: TStateView<Key = StateKey>,
+{
+    type Key = StateKey;
+
+    fn id(&self) -> StateViewId {
+        self.base.id()
+    }
+
+    fn get_state_value(&self, state_key: &Self::Key) -> StateViewResult<Option<StateValue>> {
+        if let Some(value) = self.cache.read().get(state_key) {
+            return Ok(value.clone());
+        }
+
+        let value = self.base.get_state_value(state_key)?;
+        self.cache.write().insert(state_key.clone(), value.clone());
+        Ok(value)
+    }
+
+    fn get_usage(&self) -> StateViewResult<StateStorageUsage> {
+        Ok(StateStorageUsage::Untracked)
+    }
+
+    fn next_version(&self) -> Version {
+        self.base.next_version()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_transaction_simulation::{InMemoryStateStore, SimulationStateStore};
+
+    #[test]
+    fn test_cached_state_view_reads_base_once() {
+        let key = StateKey::raw(b"key");
+        let missing_key = StateKey::raw(b"missing");
+        let value = StateValue::new_legacy(b"value".to_vec().into());
+
+        let base = InMemoryStateStore::new_with_state_values([(key.clone(), value.clone())]);
+        let view = CachedStateView::new(&base);
+        assert_eq!(view.get_state_value(&key).unwrap(), Some(value.clone()));
+        assert_eq!(view.get_state_value(&missing_key).unwrap(), None);
+
+        // Later changes to the base are not observed, the values are served from the cache.
+        base.remove_state_value(&key).unwrap();
+        base.set_state_value(missing_key.clone(), value.clone())
+            .unwrap();
+        assert_eq!(view.get_state_value(&key).unwrap(), Some(value.clone()));
+        assert_eq!(view.get_state_value(&missing_key).unwrap(), None);
+
+        assert_eq!(
+            view.cache(),
+            HashMap::from([(key, Some(value)), (missing_key, None)])
+        );
+    }
+}
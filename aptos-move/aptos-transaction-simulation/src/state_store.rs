@@ -4,8 +4,14 @@
 use crate::{genesis::GENESIS_CHANGE_SET_HEAD, Account, AccountData};
 use anyhow::{anyhow, bail, Result};
 use aptos_types::{
+    account_address::create_derived_object_address,
+    account_config::{
+        ConcurrentFungibleBalanceResource, FungibleStoreResource, ObjectCoreResource,
+        ObjectGroupResource,
+    },
     chain_id::ChainId,
-    on_chain_config::{FeatureFlag, Features, OnChainConfig},
+    event::{EventHandle, EventKey},
+    on_chain_config::{CurrentTimeMicroseconds, FeatureFlag, Features, OnChainConfig},
     state_store::{
         state_key::StateKey, state_slot::StateSlot, state_storage_usage::StateStorageUsage,
         state_value::StateValue, StateViewId, StateViewResult, TStateView,
@@ -16,7 +22,11 @@ use aptos_types::{
 use bytes::Bytes;
 use move_binary_format::{deserializer::DeserializerConfig, CompiledModule};
 use move_core_types::{
-    account_address::AccountAddress, language_storage::ModuleId, move_resource::MoveResource,
+    account_address::AccountAddress,
+    ident_str,
+    identifier::IdentStr,
+    language_storage::{ModuleId, StructTag},
+    move_resource::{MoveResource, MoveStructType},
 };
 use parking_lot::RwLock;
 use serde::Serialize;
@@ -223,6 +233,196 @@ pub trait SimulationStateStore: TStateView<Key = StateKey> {
         self.add_account_data(&data)?;
         Ok(data)
     }
+
+    // Cheatcodes: state edits that cannot be done with transactions. They only exist on
+    // simulation state stores, which are never used by validators or fullnodes.
+
+    /// Gets the on-chain time (`0x1::timestamp`), in microseconds.
+    fn get_timestamp_micros(&self) -> Result<u64>
+    where
+        Self: Sized,
+    {
+        Ok(self
+            .get_on_chain_config::<CurrentTimeMicroseconds>()?
+            .microseconds)
+    }
+
+    /// Warps the on-chain time (`0x1::timestamp`) to the given time, in microseconds. The time can
+    /// go backwards.
+    fn set_timestamp_micros(&self, microseconds: u64) -> Result<()> {
+        self.set_on_chain_config(&CurrentTimeMicroseconds { microseconds })
+    }
+
+    /// Gets the balance of the primary fungible store of `owner` for the fungible asset with the
+    /// given metadata address, or `None` if the store does not exist.
+    fn get_fungible_balance(
+        &self,
+        owner: AccountAddress,
+        metadata: AccountAddress,
+    ) -> Result<Option<u64>> {
+        let store_address = create_derived_object_address(owner, metadata);
+        let state_key =
+            StateKey::resource_group(&store_address, &ObjectGroupResource::struct_tag());
+        let group = match self.get_state_value_bytes(&state_key)? {
+            Some(bytes) => bcs::from_bytes::<ObjectGroupResource>(&bytes)?,
+            None => return Ok(None),
+        };
+
+        if let Some(bytes) = group
+            .group
+            .get(&ConcurrentFungibleBalanceResource::struct_tag())
+        {
+            let balance = bcs::from_bytes::<ConcurrentFungibleBalanceResource>(bytes)?;
+            return Ok(Some(balance.balance()));
+        }
+        match group.group.get(&FungibleStoreResource::struct_tag()) {
+            Some(bytes) => Ok(Some(
+                bcs::from_bytes::<FungibleStoreResource>(bytes)?.balance(),
+            )),
+            None => Ok(None),
+        }
+    }
+
+    /// Sets the balance of the primary fungible store of `owner` for the fungible asset with the
+    /// given metadata address, creating the store if it does not exist (as
+    /// `0x1::primary_fungible_store::create_primary_store` would).
+    ///
+    /// The supply of the fungible asset is not updated.
+    fn set_fungible_balance(
+        &self,
+        owner: AccountAddress,
+        metadata: AccountAddress,
+        balance: u64,
+    ) -> Result<()>
+    where
+        Self: Sized,
+    {
+        let store_address = create_derived_object_address(owner, metadata);
+        let state_key =
+            StateKey::resource_group(&store_address, &ObjectGroupResource::struct_tag());
+
+        let state_value = match self.get_state_value(&state_key)? {
+            Some(mut state_value) => {
+                let mut group = bcs::from_bytes::<ObjectGroupResource>(state_value.bytes())?;
+                let mut store: FungibleStoreResource =
+                    match group.group.get(&FungibleStoreResource::struct_tag()) {
+                        Some(bytes) => bcs::from_bytes(bytes)?,
+                        None => bail!(
+                            "failed to set fungible balance -- object {} is not a fungible store",
+                            store_address
+                        ),
+                    };
+                if group
+                    .group
+                    .contains_key(&ConcurrentFungibleBalanceResource::struct_tag())
+                {
+                    group.insert(
+                        ConcurrentFungibleBalanceResource::struct_tag(),
+                        bcs::to_bytes(&ConcurrentFungibleBalanceResource::new(balance))?,
+                    );
+                } else {
+                    store.balance = balance;
+                    group.insert(FungibleStoreResource::struct_tag(), bcs::to_bytes(&store)?);
+                }
+                state_value.set_bytes(group.to_bytes()?.into());
+                state_value
+            },
+            None => {
+                let metadata_group = match self.get_state_value_bytes(&StateKey::resource_group(
+                    &metadata,
+                    &ObjectGroupResource::struct_tag(),
+                ))? {
+                    Some(bytes) => bcs::from_bytes::<ObjectGroupResource>(&bytes)?,
+                    None => ObjectGroupResource::default(),
+                };
+                if !metadata_group.group.contains_key(&framework_struct_tag(
+                    FUNGIBLE_ASSET,
+                    ident_str!("Metadata"),
+                )) || !metadata_group.group.contains_key(&framework_struct_tag(
+                    ident_str!("primary_fungible_store"),
+                    ident_str!("DeriveRefPod"),
+                )) {
+                    bail!(
+                        "failed to set fungible balance -- {} is not the metadata of a fungible \
+                         asset with primary stores",
+                        metadata
+                    );
+                }
+
+                // The first GUID of the object is used by the transfer events.
+                let mut object_core = ObjectCoreResource::new(
+                    owner,
+                    false,
+                    EventHandle::new(
+                        EventKey::new(OBJECT_INIT_GUID_CREATION_NUM, store_address),
+                        0,
+                    ),
+                );
+                object_core.guid_creation_num = OBJECT_INIT_GUID_CREATION_NUM + 1;
+
+                let use_concurrent_balance = self
+                    .get_features()
+                    .unwrap_or_default()
+                    .is_enabled(FeatureFlag::DEFAULT_TO_CONCURRENT_FUNGIBLE_BALANCE);
+
+                let mut group = ObjectGroupResource::default();
+                group.insert(
+                    ObjectCoreResource::struct_tag(),
+                    bcs::to_bytes(&object_core)?,
+                );
+                group.insert(
+                    FungibleStoreResource::struct_tag(),
+                    bcs::to_bytes(&FungibleStoreResource::new(
+                        metadata,
+                        if use_concurrent_balance { 0 } else { balance },
+                        false,
+                    ))?,
+                );
+                if use_concurrent_balance {
+                    group.insert(
+                        ConcurrentFungibleBalanceResource::struct_tag(),
+                        bcs::to_bytes(&ConcurrentFungibleBalanceResource::new(balance))?,
+                    );
+                }
+                if metadata_group.group.contains_key(&framework_struct_tag(
+                    FUNGIBLE_ASSET,
+                    ident_str!("Untransferable"),
+                )) {
+                    // Empty Move structs are serialized with a single `dummy_field: bool`.
+                    group.insert(
+                        framework_struct_tag(ident_str!("object"), ident_str!("Untransferable")),
+                        bcs::to_bytes(&false)?,
+                    );
+                }
+                StateValue::new_legacy(group.to_bytes()?.into())
+            },
+        };
+        self.set_state_value(state_key, state_value)
+    }
+
+    /// Sets the balance of the primary APT fungible store of `owner`. APT held in a
+    /// `0x1::coin::CoinStore` is not affected.
+    fn set_apt_fungible_balance(&self, owner: AccountAddress, balance: u64) -> Result<()>
+    where
+        Self: Sized,
+    {
+        self.set_fungible_balance(owner, AccountAddress::TEN, balance)
+    }
+}
+
+/// The creation number of the first GUID of an object (`0x1::object::INIT_GUID_CREATION_NUM`).
+const OBJECT_INIT_GUID_CREATION_NUM: u64 = 0x4000000000000;
+
+const FUNGIBLE_ASSET: &IdentStr = ident_str!("fungible_asset");
+
+/// Returns the tag of a struct without type arguments in the Aptos framework.
+fn framework_struct_tag(module: &IdentStr, name: &IdentStr) -> StructTag {
+    StructTag {
+        address: AccountAddress::ONE,
+        module: module.to_owned(),
+        name: name.to_owned(),
+        type_args: vec![],
+    }
 }
 
 /***************************************************************************************************
@@ -437,6 +637,29 @@ impl<V> DeltaStateStore<V> {
             states: RwLock::new(state_vals.into_iter().map(|(k, v)| (k, Some(v))).collect()),
         }
     }
+
+    /// Creates a new [`DeltaStateStore`] with a given base state view and the given state
+    /// changes, where `None` marks a deletion.
+    pub fn new_with_base_and_delta(
+        base: V,
+        delta: impl IntoIterator<Item = (StateKey, Option<StateValue>)>,
+    ) -> Self {
+        Self {
+            base,
+            states: RwLock::new(delta.into_iter().collect()),
+        }
+    }
+
+    /// Returns the base state view.
+    pub fn base(&self) -> &V {
+        &self.base
+    }
+
+    /// Returns the state changes stacked on top of the base state view, where `None` marks a
+    /// deletion.
+    pub fn delta(&self) -> HashMap<StateKey, Option<StateValue>> {
+        self.states.read().clone()
+    }
 }
 
 impl<V> Clone for DeltaStateStore<V>
@@ -550,6 +773,104 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_set_fungible_balance() {
+        let state_store = InMemoryStateStore::from_head_genesis();
+        let owner = AccountAddress::from_hex_literal("0xcafe").unwrap();
+        let metadata = AccountAddress::TEN;
+
+        assert_eq!(
+            state_store.get_fungible_balance(owner, metadata).unwrap(),
+            None
+        );
+
+        // Creates the store.
+        state_store
+            .set_fungible_balance(owner, metadata, 100)
+            .unwrap();
+        assert_eq!(
+            state_store.get_fungible_balance(owner, metadata).unwrap(),
+            Some(100)
+        );
+
+        let store_address = create_derived_object_address(owner, metadata);
+        let group: ObjectGroupResource = bcs::from_bytes(
+            &state_store
+                .get_state_value_bytes(&StateKey::resource_group(
+                    &store_address,
+                    &ObjectGroupResource::struct_tag(),
+                ))
+                .unwrap()
+                .unwrap(),
+        )
+        .unwrap();
+        let object_core: ObjectCoreResource =
+            bcs::from_bytes(&group.group[&ObjectCoreResource::struct_tag()]).unwrap();
+        assert_eq!(object_core.owner, owner);
+        assert!(!object_core.allow_ungated_transfer);
+        assert_eq!(
+            object_core.transfer_events().key(),
+            &EventKey::new(OBJECT_INIT_GUID_CREATION_NUM, store_address)
+        );
+        assert_eq!(
+            object_core.guid_creation_num,
+            OBJECT_INIT_GUID_CREATION_NUM + 1
+        );
+        assert_eq!(
+            group
+                .group
+                .contains_key(&ConcurrentFungibleBalanceResource::struct_tag()),
+            state_store
+                .get_features()
+                .unwrap()
+                .is_enabled(FeatureFlag::DEFAULT_TO_CONCURRENT_FUNGIBLE_BALANCE)
+        );
+
+        // Updates the existing store.
+        state_store
+            .set_fungible_balance(owner, metadata, 7)
+            .unwrap();
+        assert_eq!(
+            state_store.get_fungible_balance(owner, metadata).unwrap(),
+            Some(7)
+        );
+
+        // Stores are only created for fungible assets with primary stores.
+        let not_metadata = AccountAddress::from_hex_literal("0xbeef").unwrap();
+        assert!(state_store
+            .set_fungible_balance(owner, not_metadata, 100)
+            .is_err());
+        assert_eq!(
+            state_store
+                .get_fungible_balance(owner, not_metadata)
+                .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_set_fungible_balance_of_account_with_concurrent_balance() {
+        let state_store = InMemoryStateStore::new();
+        let data = AccountData::with_account_and_fungible_store(Account::new(), 1_000, 0, true);
+        state_store.add_account_data(&data).unwrap();
+        assert_eq!(
+            state_store
+                .get_fungible_balance(*data.address(), AccountAddress::TEN)
+                .unwrap(),
+            Some(1_000)
+        );
+
+        state_store
+            .set_apt_fungible_balance(*data.address(), 42)
+            .unwrap();
+        assert_eq!(
+            state_store
+                .get_fungible_balance(*data.address(), AccountAddress::TEN)
+                .unwrap(),
+            Some(42)
+        );
+    }
+
     proptest! {
         /// Verifies that [`DeltaStateStore`] maintains the same behavior as if all writes
         /// were directly applied to the base storage.
@@ -70,14 +70,16 @@ use aptos_types::{
     randomness::Randomness,
     state_store::{StateView, TStateView},
     transaction::{
-        authenticator::{AbstractionAuthData, AnySignature, AuthenticationProof},
+        authenticator::{
+            AbstractionAuthData, AccountAuthenticator, AnySignature, AuthenticationProof,
+        },
         block_epilogue::{BlockEpiloguePayload, FeeDistribution},
         signature_verified_transaction::SignatureVerifiedTransaction,
         BlockOutput, EntryFunction, ExecutionError, ExecutionStatus, ModuleBundle,
-        MultisigTransactionPayload, ReplayProtector, Script, SignedTransaction, Transaction,
-        TransactionArgument, TransactionExecutableRef, TransactionExtraConfig, TransactionOutput,
-        TransactionPayload, TransactionStatus, VMValidatorResult, ViewFunctionOutput,
-        WriteSetPayload,
+        MultisigTransactionPayload, RawTransaction, ReplayProtector, Script, SignedTransaction,
+        Transaction, TransactionArgument, TransactionExecutableRef, TransactionExtraConfig,
+        TransactionOutput, TransactionPayload, TransactionStatus, VMValidatorResult,
+        ViewFunctionOutput, WriteSetPayload,
     },
     vm::module_metadata::{
         get_compilation_metadata, get_metadata, get_randomness_annotation_for_entry_function,
@@ -3123,9 +3125,16 @@ impl VMValidator for AptosVM {
 }
 
 // Ensure encapsulation of AptosVM APIs by using a wrapper.
-pub struct AptosSimulationVM;
+pub struct AptosSimulationVM(AptosVM);
 
 impl AptosSimulationVM {
+    /// Creates a new VM instance in simulation mode.
+    pub fn new(env: &AptosEnvironment, state_view: &impl StateView) -> Self {
+        let mut vm = AptosVM::new(env, state_view);
+        vm.is_simulation = true;
+        Self(vm)
+    }
+
     /// Simulates a signed transaction (i.e., executes it without performing
     /// signature verification) on a newly created VM instance.
     /// *Precondition:* the transaction must **not** have a valid signature.
@@ -3139,8 +3148,7 @@ impl AptosSimulationVM {
         );
 
         let env = AptosEnvironment::new(state_view);
-        let mut vm = AptosVM::new(&env, state_view);
-        vm.is_simulation = true;
+        let vm = Self::new(&env, state_view);
 
         let log_context = AdapterLogSchema::new(state_view.id(), 0);
 
@@ -3148,12 +3156,62 @@ impl AptosSimulationVM {
         let code_storage = state_view.as_aptos_code_storage(&env);
 
         let (vm_status, vm_output) =
-            vm.execute_user_transaction(&resolver, &code_storage, transaction, &log_context);
+            vm.0.execute_user_transaction(&resolver, &code_storage, transaction, &log_context);
         let txn_output = vm_output
             .try_materialize_into_transaction_output(&resolver)
             .expect("Materializing aggregator V1 deltas should never fail");
         (vm_status, txn_output)
     }
+
+    /// Simulates a transaction on behalf of its sender, without the sender's keys: the
+    /// transaction carries no authenticator, and the authentication key of the sender is not
+    /// checked by the prologue.
+    ///
+    /// Impersonation relies on the VM being in simulation mode, which is only possible through
+    /// [`AptosSimulationVM`]. The VMs used to validate and execute blocks are never in simulation
+    /// mode, so impersonated transactions are rejected by validators and fullnodes. The prologue
+    /// of the simulated state must support simulation, i.e., the
+    /// `TRANSACTION_SIMULATION_ENHANCEMENT` feature must be enabled.
+    pub fn create_vm_and_simulate_impersonated_transaction(
+        raw_txn: RawTransaction,
+        state_view: &impl StateView,
+    ) -> (VMStatus, TransactionOutput) {
+        let transaction = SignedTransaction::new_single_sender(
+            raw_txn,
+            AccountAuthenticator::NoAccountAuthenticator,
+        );
+        Self::create_vm_and_simulate_signed_transaction(&transaction, state_view)
+    }
+
+    /// Simulates a signed transaction using a customized version of the production gas meter,
+    /// e.g., to profile its gas usage. See
+    /// [`AptosVM::execute_user_transaction_with_modified_gas_meter`].
+    /// *Precondition:* the transaction must **not** have a valid signature.
+    pub fn simulate_user_transaction_with_modified_gas_meter<'a, G, F>(
+        &self,
+        resolver: &'a impl AptosMoveResolver,
+        code_storage: &'a (impl AptosCodeStorage + BlockSynchronizationKillSwitch),
+        transaction: &SignedTransaction,
+        log_context: &AdapterLogSchema,
+        modify_gas_meter: F,
+    ) -> Result<(VMStatus, VMOutput, G), VMStatus>
+    where
+        F: FnOnce(ProdGasMeter<'a, NoopBlockSynchronizationKillSwitch>) -> G,
+        G: AptosGasMeter,
+    {
+        assert_err!(
+            transaction.verify_signature(),
+            "Simulated transaction should not have a valid signature"
+        );
+
+        self.0.execute_user_transaction_with_modified_gas_meter(
+            resolver,
+            code_storage,
+            transaction,
+            log_context,
+            modify_gas_meter,
+        )
+    }
 }
 
 fn create_account_if_does_not_exist(
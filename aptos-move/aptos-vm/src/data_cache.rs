@@ -38,6 +38,7 @@ use move_core_types::{
     account_address::AccountAddress, language_storage::StructTag, metadata::Metadata,
     value::MoveTypeLayout,
 };
+use move_vm_runtime::execution_trace::{self, TraceEvent};
 use move_vm_types::{
     delayed_values::delayed_field_id::DelayedFieldID,
     resolver::{resource_size, ResourceResolver},
@@ -112,7 +113,15 @@ impl<'e, E: ExecutorView> StorageAdapter<'e, E> {
 
             let first_access = self.accessed_groups.borrow_mut().insert(key.clone());
             let group_size = if first_access {
-                self.resource_group_view.resource_group_size(&key)?.get()
+                let group_size = self.resource_group_view.resource_group_size(&key)?.get();
+                if execution_trace::is_recording() {
+                    execution_trace::record(TraceEvent::ResourceGroupRead {
+                        address: *address,
+                        group: resource_group.to_canonical_string(),
+                        bytes: group_size,
+                    });
+                }
+                group_size
             } else {
                 0
             };
@@ -3,6 +3,7 @@
 
 use crate::{
     code_cache_global::GlobalModuleCache,
+    conflict_report::{self, ConflictKind},
     types::InputOutputKey,
     view::{GroupReadResult, LatestView, ReadResult},
 };
@@ -940,6 +941,11 @@ where
                 | Err(DeltaApplicationFailure)
                 | Err(Uninitialized) => false,
             } {
+                conflict_report::record_validation_failure(
+                    idx_to_validate,
+                    key,
+                    ConflictKind::Resource,
+                );
                 return false;
             }
         }
@@ -1103,35 +1109,48 @@ where
                 ret &= group_map.validate_group_size(key, idx_to_validate, size);
             }
 
-            ret && group.inner_reads.iter().all(|(tag, r)| {
-                match group_map.fetch_tagged_data_no_record(key, tag, idx_to_validate) {
-                    Ok((version, v)) => {
-                        matches!(
-                            self.data_read_comparator.compare_data_reads(
-                                &DataRead::from_value_with_layout(version, v),
-                                r,
-                            ),
-                            DataReadComparison::Contains
-                        )
-                    },
-                    Err(TagNotFound) => {
-                        let sentinel_deletion =
-                            Arc::<T::Value>::new(TransactionWrite::from_state_value(None));
-                        assert!(sentinel_deletion.is_deletion());
-                        matches!(
-                            self.data_read_comparator.compare_data_reads(
-                                &DataRead::Versioned(Err(StorageVersion), sentinel_deletion, None),
-                                r,
-                            ),
-                            DataReadComparison::Contains
-                        )
-                    },
-                    Err(Dependency(_)) => false,
-                    Err(Uninitialized) => {
-                        unreachable!("May not be uninitialized if captured for validation");
-                    },
-                }
-            })
+            let valid = ret
+                && group.inner_reads.iter().all(|(tag, r)| {
+                    match group_map.fetch_tagged_data_no_record(key, tag, idx_to_validate) {
+                        Ok((version, v)) => {
+                            matches!(
+                                self.data_read_comparator.compare_data_reads(
+                                    &DataRead::from_value_with_layout(version, v),
+                                    r,
+                                ),
+                                DataReadComparison::Contains
+                            )
+                        },
+                        Err(TagNotFound) => {
+                            let sentinel_deletion =
+                                Arc::<T::Value>::new(TransactionWrite::from_state_value(None));
+                            assert!(sentinel_deletion.is_deletion());
+                            matches!(
+                                self.data_read_comparator.compare_data_reads(
+                                    &DataRead::Versioned(
+                                        Err(StorageVersion),
+                                        sentinel_deletion,
+                                        None
+                                    ),
+                                    r,
+                                ),
+                                DataReadComparison::Contains
+                            )
+                        },
+                        Err(Dependency(_)) => false,
+                        Err(Uninitialized) => {
+                            unreachable!("May not be uninitialized if captured for validation");
+                        },
+                    }
+                });
+            if !valid {
+                conflict_report::record_validation_failure(
+                    idx_to_validate,
+                    key,
+                    ConflictKind::ResourceGroup,
+                );
+            }
+            valid
         })
     }
 
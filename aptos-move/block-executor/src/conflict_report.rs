@@ -0,0 +1,420 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Optional per-block reports of the conflicts detected during parallel execution: which state
+//! keys and resource groups caused validation failures (or, in BlockSTMv2, invalidated reads),
+//! how many incarnations each transaction needed, and which transactions waited on each other.
+//!
+//! Reports are only collected after [enable_conflict_reports] is called, and are meant for tooling
+//! (benchmarks, replay) that takes the reports of executed blocks. Every block has its own
+//! report, and every worker records into its own (thread-local) buffer, which is merged into the
+//! block's report once the worker finishes. When disabled, the executor only pays for a single
+//! atomic load at every recording point.
+
+use aptos_mvhashmap::types::{Incarnation, TxnIndex};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt::{self, Debug, Display},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+/// The number of keys and transactions shown when displaying a report.
+const NUM_DISPLAYED_ENTRIES: usize = 10;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// The reports of the blocks that finished executing, until they are taken.
+static FINISHED_REPORTS: Lazy<Mutex<Vec<BlockConflictReport>>> = Lazy::new(|| Mutex::new(vec![]));
+
+thread_local! {
+    /// The conflicts recorded by the worker running on the current thread (if any).
+    static WORKER_BUFFER: RefCell<Option<ConflictReportBuilder>> = const { RefCell::new(None) };
+}
+
+/// Starts collecting conflict reports for all blocks executed in parallel.
+pub fn enable_conflict_reports() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Stops collecting conflict reports. Reports of already executed blocks can still be taken.
+pub fn disable_conflict_reports() {
+    ENABLED.store(false, Ordering::Relaxed);
+}
+
+/// Returns the reports of all blocks executed in parallel since the last call.
+pub fn take_conflict_reports() -> Vec<BlockConflictReport> {
+    std::mem::take(&mut *FINISHED_REPORTS.lock())
+}
+
+#[inline]
+pub(crate) fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+fn with_worker_buffer(f: impl FnOnce(&mut ConflictReportBuilder)) {
+    if is_enabled() {
+        WORKER_BUFFER.with(|buffer| {
+            if let Some(builder) = buffer.borrow_mut().as_mut() {
+                f(builder);
+            }
+        });
+    }
+}
+
+/// Collects the report of a single block executed in parallel, and finishes it when dropped
+/// (i.e., after all of the block's workers finished).
+pub(crate) struct BlockConflictRecorder {
+    num_txns: usize,
+    /// The conflicts of the workers that finished (None if reports are disabled).
+    report: Option<Mutex<ConflictReportBuilder>>,
+}
+
+impl BlockConflictRecorder {
+    pub(crate) fn new(num_txns: usize) -> Self {
+        Self {
+            num_txns,
+            report: is_enabled().then(|| Mutex::new(ConflictReportBuilder::new(num_txns))),
+        }
+    }
+
+    /// Starts recording the conflicts of a worker (on the current thread) into a buffer of its
+    /// own. The buffer is merged into the block's report once the returned guard is dropped.
+    pub(crate) fn start_worker(&self) -> WorkerConflictBuffer<'_> {
+        let previous = self.report.as_ref().map(|_| {
+            WORKER_BUFFER.with(|buffer| {
+                buffer
+                    .borrow_mut()
+                    .replace(ConflictReportBuilder::new(self.num_txns))
+            })
+        });
+        WorkerConflictBuffer {
+            recorder: self,
+            previous,
+        }
+    }
+}
+
+impl Drop for BlockConflictRecorder {
+    fn drop(&mut self) {
+        if let Some(report) = self.report.take() {
+            FINISHED_REPORTS.lock().push(report.into_inner().finish());
+        }
+    }
+}
+
+/// The buffer of a worker, merged into the block's report when dropped.
+pub(crate) struct WorkerConflictBuffer<'a> {
+    recorder: &'a BlockConflictRecorder,
+    /// The buffer of an enclosing worker on the same thread (if recording), restored on drop.
+    previous: Option<Option<ConflictReportBuilder>>,
+}
+
+impl Drop for WorkerConflictBuffer<'_> {
+    fn drop(&mut self) {
+        if let (Some(report), Some(previous)) = (&self.recorder.report, self.previous.take()) {
+            let buffer =
+                WORKER_BUFFER.with(|buffer| std::mem::replace(&mut *buffer.borrow_mut(), previous));
+            if let Some(buffer) = buffer {
+                report.lock().merge(buffer);
+            }
+        }
+    }
+}
+
+/// Records that the given incarnation of the transaction started executing.
+pub(crate) fn record_execution(txn_idx: TxnIndex, incarnation: Incarnation) {
+    with_worker_buffer(|builder| builder.record_execution(txn_idx, incarnation));
+}
+
+/// Records that the transaction failed validation due to its read of the key.
+pub(crate) fn record_validation_failure(txn_idx: TxnIndex, key: &impl Debug, kind: ConflictKind) {
+    with_worker_buffer(|builder| builder.record_conflict(txn_idx, key, kind));
+}
+
+/// Records that a write of the key (by the writer transaction) invalidated reads of the given
+/// transactions, making them depend on the writer.
+pub(crate) fn record_invalidations(
+    writer_idx: TxnIndex,
+    key: &impl Debug,
+    kind: ConflictKind,
+    invalidated: &BTreeSet<(TxnIndex, Incarnation)>,
+) {
+    if invalidated.is_empty() {
+        return;
+    }
+    with_worker_buffer(|builder| {
+        for (txn_idx, _) in invalidated {
+            builder.record_conflict(*txn_idx, key, kind);
+            builder.record_dependency(*txn_idx, writer_idx);
+        }
+    });
+}
+
+/// Records that the transaction had to wait for the dependency to finish re-executing.
+pub(crate) fn record_dependency(txn_idx: TxnIndex, dep_idx: TxnIndex) {
+    with_worker_buffer(|builder| builder.record_dependency(txn_idx, dep_idx));
+}
+
+/// The kind of state causing a conflict.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum ConflictKind {
+    Resource,
+    ResourceGroup,
+}
+
+/// The conflicts caused by a single key.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct KeyConflicts {
+    pub key: String,
+    pub kind: ConflictKind,
+    /// The number of validation failures (or invalidations) caused by the key.
+    pub count: usize,
+    /// The transactions which failed validation due to the key.
+    pub txns: BTreeSet<TxnIndex>,
+}
+
+/// The conflicts detected while executing a block in parallel.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BlockConflictReport {
+    /// The number of incarnations executed for every transaction in the block.
+    pub incarnations: Vec<u32>,
+    /// The keys which caused conflicts, from the most to the least conflicting.
+    pub conflicts: Vec<KeyConflicts>,
+    /// Maps a (transaction, dependency) edge to the number of times the transaction had to
+    /// wait on (or was invalidated by) the dependency.
+    pub dependencies: BTreeMap<(TxnIndex, TxnIndex), usize>,
+}
+
+impl BlockConflictReport {
+    pub fn num_txns(&self) -> usize {
+        self.incarnations.len()
+    }
+
+    /// Returns the number of executions beyond the first incarnation of every transaction.
+    pub fn num_re_executions(&self) -> usize {
+        self.incarnations
+            .iter()
+            .map(|incarnations| incarnations.saturating_sub(1) as usize)
+            .sum()
+    }
+
+    /// Renders the dependency graph in the Graphviz DOT format, where an edge points from a
+    /// transaction to its dependency (labelled with the number of times it was encountered).
+    pub fn dependency_graph_dot(&self) -> String {
+        let mut dot = String::from("digraph dependencies {\n");
+        for ((txn_idx, dep_idx), count) in &self.dependencies {
+            dot.push_str(&format!(
+                "    {} -> {} [label=\"{}\"];\n",
+                txn_idx, dep_idx, count
+            ));
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+impl Display for BlockConflictReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} transactions, {} re-executions, {} conflicting keys, {} dependency edges",
+            self.num_txns(),
+            self.num_re_executions(),
+            self.conflicts.len(),
+            self.dependencies.len()
+        )?;
+
+        if !self.conflicts.is_empty() {
+            writeln!(f, "Most conflicting keys (conflicts, transactions, key):")?;
+            for conflict in self.conflicts.iter().take(NUM_DISPLAYED_ENTRIES) {
+                let kind = match conflict.kind {
+                    ConflictKind::Resource => "",
+                    ConflictKind::ResourceGroup => " (resource group)",
+                };
+                writeln!(
+                    f,
+                    "    {} {} {}{}",
+                    conflict.count,
+                    conflict.txns.len(),
+                    conflict.key,
+                    kind
+                )?;
+            }
+        }
+
+        let mut txns = self
+            .incarnations
+            .iter()
+            .enumerate()
+            .filter(|(_, incarnations)| **incarnations > 1)
+            .collect::<Vec<_>>();
+        if !txns.is_empty() {
+            txns.sort_by(|(idx1, inc1), (idx2, inc2)| inc2.cmp(inc1).then(idx1.cmp(idx2)));
+            writeln!(f, "Most re-executed transactions (incarnations, index):")?;
+            for (txn_idx, incarnations) in txns.into_iter().take(NUM_DISPLAYED_ENTRIES) {
+                writeln!(f, "    {} {}", incarnations, txn_idx)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Accumulates the conflicts of a block.
+struct ConflictReportBuilder {
+    incarnations: Vec<u32>,
+    conflicts: HashMap<(String, ConflictKind), (usize, BTreeSet<TxnIndex>)>,
+    dependencies: BTreeMap<(TxnIndex, TxnIndex), usize>,
+}
+
+impl ConflictReportBuilder {
+    fn new(num_txns: usize) -> Self {
+        Self {
+            incarnations: vec![0; num_txns],
+            conflicts: HashMap::new(),
+            dependencies: BTreeMap::new(),
+        }
+    }
+
+    fn record_execution(&mut self, txn_idx: TxnIndex, incarnation: Incarnation) {
+        if let Some(incarnations) = self.incarnations.get_mut(txn_idx as usize) {
+            *incarnations = (*incarnations).max(incarnation + 1);
+        }
+    }
+
+    fn record_conflict(&mut self, txn_idx: TxnIndex, key: &impl Debug, kind: ConflictKind) {
+        let (count, txns) = self
+            .conflicts
+            .entry((format!("{:?}", key), kind))
+            .or_default();
+        *count += 1;
+        txns.insert(txn_idx);
+    }
+
+    fn record_dependency(&mut self, txn_idx: TxnIndex, dep_idx: TxnIndex) {
+        *self.dependencies.entry((txn_idx, dep_idx)).or_default() += 1;
+    }
+
+    fn merge(&mut self, other: ConflictReportBuilder) {
+        for (txn_idx, incarnations) in other.incarnations.into_iter().enumerate() {
+            if let Some(merged) = self.incarnations.get_mut(txn_idx) {
+                *merged = (*merged).max(incarnations);
+            }
+        }
+        for (key, (count, txns)) in other.conflicts {
+            let (merged_count, merged_txns) = self.conflicts.entry(key).or_default();
+            *merged_count += count;
+            merged_txns.extend(txns);
+        }
+        for (edge, count) in other.dependencies {
+            *self.dependencies.entry(edge).or_default() += count;
+        }
+    }
+
+    fn finish(self) -> BlockConflictReport {
+        let mut conflicts = self
+            .conflicts
+            .into_iter()
+            .map(|((key, kind), (count, txns))| KeyConflicts {
+                key,
+                kind,
+                count,
+                txns,
+            })
+            .collect::<Vec<_>>();
+        conflicts.sort_by(|c1, c2| c2.count.cmp(&c1.count).then(c1.key.cmp(&c2.key)));
+
+        BlockConflictReport {
+            incarnations: self.incarnations,
+            conflicts,
+            dependencies: self.dependencies,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_worker_buffers() {
+        enable_conflict_reports();
+        take_conflict_reports();
+
+        // Record the conflicts of a block on two workers
+        let recorder = BlockConflictRecorder::new(3);
+        std::thread::scope(|s| {
+            for txn_idx in [1, 2] {
+                let recorder = &recorder;
+                s.spawn(move || {
+                    let _buffer = recorder.start_worker();
+                    record_execution(txn_idx, 1);
+                    record_validation_failure(txn_idx, &"hot", ConflictKind::Resource);
+                    record_dependency(txn_idx, 0);
+                });
+            }
+        });
+
+        // Verify nothing is recorded outside of the workers
+        record_execution(0, 5);
+        drop(recorder);
+
+        let reports = take_conflict_reports();
+        disable_conflict_reports();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].incarnations, vec![0, 2, 2]);
+        assert_eq!(reports[0].conflicts, vec![KeyConflicts {
+            key: "\"hot\"".to_string(),
+            kind: ConflictKind::Resource,
+            count: 2,
+            txns: BTreeSet::from([1, 2]),
+        }]);
+        assert_eq!(
+            reports[0].dependencies,
+            BTreeMap::from([((1, 0), 1), ((2, 0), 1)])
+        );
+    }
+
+    #[test]
+    fn test_report_builder() {
+        let mut builder = ConflictReportBuilder::new(3);
+        for (txn_idx, incarnation) in [(0, 0), (1, 0), (2, 0), (2, 1), (1, 1), (2, 2)] {
+            builder.record_execution(txn_idx, incarnation);
+        }
+        builder.record_conflict(2, &"hot", ConflictKind::Resource);
+        builder.record_conflict(1, &"hot", ConflictKind::Resource);
+        builder.record_conflict(2, &"hot", ConflictKind::Resource);
+        builder.record_conflict(2, &"group", ConflictKind::ResourceGroup);
+        builder.record_dependency(2, 1);
+        builder.record_dependency(2, 1);
+        builder.record_dependency(1, 0);
+
+        let report = builder.finish();
+        assert_eq!(report.incarnations, vec![1, 2, 3]);
+        assert_eq!(report.num_re_executions(), 3);
+        assert_eq!(report.conflicts, vec![
+            KeyConflicts {
+                key: "\"hot\"".to_string(),
+                kind: ConflictKind::Resource,
+                count: 3,
+                txns: BTreeSet::from([1, 2]),
+            },
+            KeyConflicts {
+                key: "\"group\"".to_string(),
+                kind: ConflictKind::ResourceGroup,
+                count: 1,
+                txns: BTreeSet::from([2]),
+            },
+        ]);
+        assert_eq!(
+            report.dependencies,
+            BTreeMap::from([((1, 0), 1), ((2, 1), 2)])
+        );
+        assert_eq!(
+            report.dependency_graph_dot(),
+            "digraph dependencies {\n    1 -> 0 [label=\"1\"];\n    2 -> 1 [label=\"2\"];\n}\n"
+        );
+    }
+}
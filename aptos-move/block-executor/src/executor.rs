@@ -6,6 +6,7 @@ use crate::{
     captured_reads::CapturedReads,
     code_cache_global::GlobalModuleCache,
     code_cache_global_manager::AptosModuleCacheManagerGuard,
+    conflict_report::{self, BlockConflictRecorder, ConflictKind},
     counters::{
         self, BLOCK_EXECUTOR_INNER_EXECUTE_BLOCK, PARALLEL_EXECUTION_SECONDS,
         RAYON_EXECUTION_SECONDS, TASK_EXECUTE_SECONDS, TASK_VALIDATE_SECONDS, VM_INIT_SECONDS,
@@ -198,16 +199,21 @@ where
                                 None,
                             ),
                         )?;
-                        abort_manager.invalidate_dependencies(
-                            versioned_cache.group_data().write_v2(
-                                group_key,
-                                idx_to_execute,
-                                incarnation,
-                                group_ops.into_iter(),
-                                group_size,
-                                prev_tags,
-                            )?,
+                        let invalidated = versioned_cache.group_data().write_v2(
+                            group_key,
+                            idx_to_execute,
+                            incarnation,
+                            group_ops.into_iter(),
+                            group_size,
+                            prev_tags,
                         )?;
+                        conflict_report::record_invalidations(
+                            idx_to_execute,
+                            group_key_ref,
+                            ConflictKind::ResourceGroup,
+                            &invalidated,
+                        );
+                        abort_manager.invalidate_dependencies(invalidated)?;
                     },
                     None => {
                         // Clean up the write from previous incarnation.
@@ -243,14 +249,24 @@ where
                     None,
                 ),
             )?;
-            abort_manager.invalidate_dependencies(versioned_cache.group_data().write_v2(
+            let reported_key = conflict_report::is_enabled().then(|| group_key.clone());
+            let invalidated = versioned_cache.group_data().write_v2(
                 group_key,
                 idx_to_execute,
                 incarnation,
                 group_ops.into_iter(),
                 group_size,
                 HashSet::new(), // No previous tags since this is a new group write
-            )?)?;
+            )?;
+            if let Some(group_key) = reported_key {
+                conflict_report::record_invalidations(
+                    idx_to_execute,
+                    &group_key,
+                    ConflictKind::ResourceGroup,
+                    &invalidated,
+                );
+            }
+            abort_manager.invalidate_dependencies(invalidated)?;
         }
 
         Ok(())
@@ -339,6 +355,7 @@ where
         scheduler: &SchedulerV2,
     ) -> Result<(), PanicError> {
         let _timer = TASK_EXECUTE_SECONDS.start_timer();
+        conflict_report::record_execution(idx_to_execute, incarnation);
 
         // TODO(BlockSTMv2): proper integration w. execution pooling for performance.
         let txn = signature_verified_block.get_txn(idx_to_execute);
@@ -393,13 +410,23 @@ where
             resource_write_set = output.resource_write_set();
             for (key, value, maybe_layout) in resource_write_set.clone().into_iter() {
                 prev_modified_resource_keys.remove(&key);
-                abort_manager.invalidate_dependencies(versioned_cache.data().write_v2::<false>(
+                let reported_key = conflict_report::is_enabled().then(|| key.clone());
+                let invalidated = versioned_cache.data().write_v2::<false>(
                     key,
                     idx_to_execute,
                     incarnation,
                     value,
                     maybe_layout,
-                ))?;
+                );
+                if let Some(key) = reported_key {
+                    conflict_report::record_invalidations(
+                        idx_to_execute,
+                        &key,
+                        ConflictKind::Resource,
+                        &invalidated,
+                    );
+                }
+                abort_manager.invalidate_dependencies(invalidated)?;
             }
 
             // Apply aggregator v1 writes and deltas, using versioned data's V1 (write/add_delta) APIs.
@@ -481,6 +508,7 @@ where
         parallel_state: ParallelState<T>,
     ) -> Result<bool, PanicOr<ParallelBlockExecutionError>> {
         let _timer = TASK_EXECUTE_SECONDS.start_timer();
+        conflict_report::record_execution(idx_to_execute, incarnation);
 
         // VM execution.
         let sync_view = LatestView::new(
@@ -1727,6 +1755,7 @@ where
         if num_txns == 0 {
             return Ok(BlockOutput::new(vec![], None));
         }
+        let conflict_recorder = BlockConflictRecorder::new(num_txns);
 
         let num_workers = self.config.local.concurrency_level.min(num_txns / 2).max(2) as u32;
         let final_results = ExplicitSyncWrapper::new(Vec::with_capacity(num_txns));
@@ -1767,6 +1796,7 @@ where
         self.executor_thread_pool.scope(|s| {
             for worker_id in &worker_ids {
                 s.spawn(|_| {
+                    let _conflict_buffer = conflict_recorder.start_worker();
                     if let Err(err) = self.worker_loop_v2(
                         signature_verified_block,
                         module_cache_manager_guard.environment(),
@@ -1830,6 +1860,7 @@ where
         if num_txns == 0 {
             return Ok(BlockOutput::new(vec![], None));
         }
+        let conflict_recorder = BlockConflictRecorder::new(num_txns);
 
         let num_workers = self.config.local.concurrency_level.min(num_txns / 2).max(2);
         let block_limit_processor = ExplicitSyncWrapper::new(BlockGasLimitProcessor::new(
@@ -1864,6 +1895,7 @@ where
         self.executor_thread_pool.scope(|s| {
             for _ in 0..num_workers {
                 s.spawn(|_| {
+                    let _conflict_buffer = conflict_recorder.start_worker();
                     if let Err(err) = self.worker_loop(
                         module_cache_manager_guard.environment(),
                         signature_verified_block,
@@ -142,6 +142,7 @@ pub mod code_cache_global;
 pub mod code_cache_global_manager;
 mod cold_validation;
 pub(crate) mod combinatorial_tests;
+pub mod conflict_report;
 pub mod counters;
 pub mod errors;
 pub mod executor;
@@ -16,7 +16,7 @@ use crate::{
         UnsyncReadSet,
     },
     code_cache_global::GlobalModuleCache,
-    counters,
+    conflict_report, counters,
     scheduler::{DependencyResult, DependencyStatus, TWaitForDependency},
     scheduler_wrapper::SchedulerWrapper,
     value_exchange::TemporaryValueToIdentifierMapping,
@@ -491,6 +491,7 @@ fn wait_for_dependency(
     txn_idx: TxnIndex,
     dep_idx: TxnIndex,
 ) -> Result<bool, PanicError> {
+    conflict_report::record_dependency(txn_idx, dep_idx);
     match wait_for.wait_for_dependency(txn_idx, dep_idx)? {
         DependencyResult::Dependency(dep_condition) => {
             let _timer = counters::DEPENDENCY_WAIT_SECONDS.start_timer();
@@ -96,6 +96,9 @@ pub struct BuildOptions {
     pub docgen_options: Option<DocgenOptions>,
     #[clap(long)]
     pub skip_fetch_latest_git_deps: bool,
+    /// Fail if the dependencies do not resolve to the versions pinned in Move.lock
+    #[clap(long)]
+    pub locked: bool,
     #[clap(long)]
     pub bytecode_version: Option<u32>,
     #[clap(long, value_parser = clap::value_parser!(CompilerVersion))]
@@ -130,6 +133,7 @@ impl Default for BuildOptions {
             // This is false by default, because it could accidentally pull new dependencies
             // while in a test (and cause some havoc)
             skip_fetch_latest_git_deps: false,
+            locked: false,
             bytecode_version: None,
             compiler_version: None,
             language_version: None,
@@ -209,6 +213,8 @@ pub fn build_model(
         force_recompilation: false,
         fetch_deps_only: false,
         skip_fetch_latest_git_deps: true,
+        locked: false,
+        update_deps: false,
         compiler_config: CompilerConfig {
             bytecode_version,
             compiler_version,
@@ -259,6 +265,8 @@ impl BuiltPackage {
             force_recompilation: false,
             fetch_deps_only: false,
             skip_fetch_latest_git_deps: options.skip_fetch_latest_git_deps,
+            locked: options.locked,
+            update_deps: false,
             compiler_config: CompilerConfig {
                 bytecode_version,
                 compiler_version,
@@ -25,7 +25,10 @@ use move_core_types::{
 // ===========================================================================================
 // Public Data Structures and Constants
 pub use move_table_extension::{TableHandle, TableInfo, TableResolver};
-use move_vm_runtime::native_functions::{LoaderContext, NativeFunctionTable};
+use move_vm_runtime::{
+    execution_trace,
+    native_functions::{LoaderContext, NativeFunctionTable},
+};
 use move_vm_types::{
     loaded_data::runtime_types::Type,
     value_serde::{FunctionValueExtension, ValueSerDeContext},
@@ -243,6 +246,11 @@ impl Table {
                             None
                         },
                     )?;
+                execution_trace::record_table_read(
+                    self.handle.0,
+                    entry.key(),
+                    data.as_ref().map(|val_bytes| val_bytes.len() as u64),
+                );
 
                 let (gv, loaded) = match data {
                     Some(val_bytes) => {
@@ -3,12 +3,17 @@
 
 use crate::{
     commands::init_logger_and_metrics,
+    execution::execute_workload,
     runner::{BenchmarkRunner, ReplayBlock},
     state_view::ReadSet,
     workload::TransactionBlock,
 };
 use anyhow::{anyhow, bail};
+use aptos_block_executor::conflict_report::{
+    disable_conflict_reports, enable_conflict_reports, take_conflict_reports,
+};
 use aptos_logger::Level;
+use aptos_vm::aptos_vm::AptosVMBlockExecutor;
 use aptos_vm_environment::prod_configs::set_paranoid_type_checks;
 use clap::Parser;
 use std::path::PathBuf;
@@ -69,6 +74,16 @@ pub struct BenchmarkCommand {
         help = "If false, Move VM runs in paranoid mode, if true, paranoid mode is not used"
     )]
     disable_paranoid_mode: bool,
+
+    #[clap(
+        long,
+        default_value_t = false,
+        help = "If true, before benchmarking, executes every block once using the highest \
+                concurrency level and prints the conflicts detected by Block-STM: the state keys \
+                and resource groups causing re-executions, the most re-executed transactions, and \
+                the number of dependencies between transactions"
+    )]
+    report_conflicts: bool,
 }
 
 impl BenchmarkCommand {
@@ -118,6 +133,18 @@ impl BenchmarkCommand {
             .collect::<Vec<_>>();
 
         set_paranoid_type_checks(!self.disable_paranoid_mode);
+        if self.report_conflicts {
+            let concurrency_level = *self
+                .concurrency_levels
+                .iter()
+                .max()
+                .expect("At least one concurrency level is provided");
+            if concurrency_level <= 1 {
+                bail!("Reporting conflicts requires a concurrency level greater than 1");
+            }
+            report_conflicts(&blocks, concurrency_level);
+        }
+
         BenchmarkRunner::new(
             self.concurrency_levels,
             self.num_repeats,
@@ -129,6 +156,30 @@ impl BenchmarkCommand {
     }
 }
 
+/// Executes every block in parallel, and prints the conflicts detected during the execution.
+fn report_conflicts(blocks: &[ReplayBlock], concurrency_level: usize) {
+    let executor = AptosVMBlockExecutor::new();
+
+    enable_conflict_reports();
+    for block in blocks {
+        execute_workload(&executor, &block.workload, &block.inputs, concurrency_level);
+
+        let begin = block
+            .workload
+            .transaction_slice_metadata
+            .begin_version()
+            .expect("Transaction metadata must be a chunk");
+        for report in take_conflict_reports() {
+            println!(
+                "Conflicts for block starting at version {} (transaction indices are relative \
+                 to the block):\n{}",
+                begin, report
+            );
+        }
+    }
+    disable_conflict_reports();
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
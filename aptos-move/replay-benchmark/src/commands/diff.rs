@@ -3,7 +3,7 @@
 
 use crate::{
     commands::init_logger_and_metrics,
-    diff::TransactionDiffBuilder,
+    diff::{DiffReport, OutputSummary, TransactionDiffBuilder, TransactionDiffReport},
     execution::execute_workload,
     state_view::ReadSet,
     workload::{TransactionBlock, Workload},
@@ -44,6 +44,13 @@ pub struct DiffCommand {
         help = "If true, when comparing output diffs changes related to gas usage are ignored"
     )]
     allow_different_gas_usage: bool,
+
+    #[clap(
+        long,
+        help = "If set, a per-transaction report of differences in status, gas used, events and \
+                writes is saved to this file (as JSON)"
+    )]
+    report_file: Option<String>,
 }
 
 impl DiffCommand {
@@ -111,6 +118,7 @@ impl DiffCommand {
 
         let diff_builder = TransactionDiffBuilder::new(self.allow_different_gas_usage);
         let mut diffs = Vec::with_capacity(outputs.len());
+        let mut reports = vec![];
 
         println!(
             "block, {} (gas), {} (gas)",
@@ -131,7 +139,21 @@ impl DiffCommand {
                 block_gas_used += output.gas_used();
                 other_block_gas_used += other_output.gas_used();
 
+                let summaries = self.report_file.is_some().then(|| {
+                    (
+                        OutputSummary::new(&output),
+                        OutputSummary::new(&other_output),
+                    )
+                });
                 let diff = diff_builder.build_from_outputs(output, other_output, fee_payer);
+                if let Some((summary, other_summary)) = summaries {
+                    reports.push(TransactionDiffReport::new(
+                        version,
+                        summary,
+                        other_summary,
+                        &diff,
+                    ));
+                }
                 if !diff.is_empty() {
                     diffs.push((version, diff));
                 }
@@ -145,6 +167,18 @@ impl DiffCommand {
             diff.println();
         }
 
+        if let Some(report_file) = &self.report_file {
+            let report = DiffReport::new(
+                self.inputs_file.clone(),
+                self.other_inputs_file.clone(),
+                reports,
+            );
+            let bytes = serde_json::to_vec_pretty(&report)
+                .map_err(|err| anyhow!("Error when serializing diff report: {:?}", err))?;
+            fs::write(PathBuf::from(report_file), &bytes).await?;
+            println!("Saved diff report to {}", report_file);
+        }
+
         Ok(())
     }
 
@@ -0,0 +1,54 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_move_debugger::execution_trace::{find_first_divergence, load_trace};
+use clap::Parser;
+use std::path::PathBuf;
+
+#[derive(Parser)]
+#[command(
+    about = "Compares execution traces of the same transaction (e.g., executed with different gas \
+             schedules, feature flags or VM versions), and prints the first divergence"
+)]
+pub struct DiffTracesCommand {
+    #[clap(long, help = "File where the trace is saved")]
+    trace_file: String,
+
+    #[clap(long, help = "File where the other trace is saved")]
+    other_trace_file: String,
+
+    #[clap(
+        long,
+        default_value_t = false,
+        help = "If true, the gas charged for each instruction is also compared"
+    )]
+    compare_gas: bool,
+}
+
+impl DiffTracesCommand {
+    pub async fn diff_traces(self) -> anyhow::Result<()> {
+        let trace = load_trace(&PathBuf::from(&self.trace_file))?;
+        let other_trace = load_trace(&PathBuf::from(&self.other_trace_file))?;
+
+        println!(
+            "left: {}, right: {}",
+            self.trace_file, self.other_trace_file
+        );
+        match find_first_divergence(&trace, &other_trace, self.compare_gas) {
+            Some(divergence) => println!("{}", divergence),
+            None => println!("Traces are identical ({} events)", trace.len()),
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verify_tool() {
+        use clap::CommandFactory;
+        DiffTracesCommand::command().debug_assert();
+    }
+}
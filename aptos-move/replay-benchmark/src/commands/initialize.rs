@@ -2,14 +2,12 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    commands::{build_debugger, init_logger_and_metrics, RestAPI},
+    commands::{build_debugger, init_logger_and_metrics, OverrideArgs, RestAPI},
     generator::InputOutputDiffGenerator,
-    overrides::OverrideConfig,
     workload::TransactionBlock,
 };
 use anyhow::anyhow;
 use aptos_logger::Level;
-use aptos_types::on_chain_config::FeatureFlag;
 use clap::Parser;
 use std::path::PathBuf;
 use tokio::fs;
@@ -29,39 +27,8 @@ pub struct InitializeCommand {
     #[clap(long, help = "Path to the file where the input states will be saved")]
     inputs_file: String,
 
-    #[clap(
-        long,
-        num_args = 1..,
-        value_delimiter = ' ',
-        help = "List of space-separated feature flags to enable, in capital letters. For example, \
-                GAS_PAYER_ENABLED or EMIT_FEE_STATEMENT. For the full list of feature flags, see \
-                aptos-core/types/src/on_chain_config/aptos_features.rs"
-    )]
-    enable_features: Vec<FeatureFlag>,
-
-    #[clap(
-        long,
-        num_args = 1..,
-        value_delimiter = ' ',
-        help = "List of space-separated feature flags to disable, in capital letters. For \
-                example, GAS_PAYER_ENABLED or EMIT_FEE_STATEMENT. For the full list of feature \
-                flags, see aptos-core/types/src/on_chain_config/aptos_features.rs"
-    )]
-    disable_features: Vec<FeatureFlag>,
-
-    #[clap(
-        long,
-        help = "If set, overrides the gas feature version used by the gas schedule"
-    )]
-    gas_feature_version: Option<u64>,
-
-    #[clap(
-        long,
-        num_args = 1..,
-        value_delimiter = ' ',
-        help = "List of space-separated paths to compiled / built packages with Move code"
-    )]
-    override_packages: Vec<String>,
+    #[clap(flatten)]
+    override_args: OverrideArgs,
 }
 
 impl InitializeCommand {
@@ -77,16 +44,10 @@ impl InitializeCommand {
         })?;
 
         // TODO:
-        //   1. Override gas schedule, to track the costs of charging gas or tracking limits.
-        //   2. BlockExecutorConfigFromOnchain to experiment with different block cutting based
+        //   1. BlockExecutorConfigFromOnchain to experiment with different block cutting based
         //      on gas limits?.
-        //   3. Build options for package overrides.
-        let override_config = OverrideConfig::new(
-            self.enable_features,
-            self.disable_features,
-            self.gas_feature_version,
-            self.override_packages,
-        )?;
+        //   2. Build options for package overrides.
+        let override_config = self.override_args.into_override_config()?;
 
         let debugger = build_debugger(self.rest_api.rest_endpoint, self.rest_api.api_key)?;
         let inputs =
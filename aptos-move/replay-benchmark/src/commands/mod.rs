@@ -1,21 +1,28 @@
 // Copyright (c) Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::overrides::{OverrideConfig, Overrides};
 use aptos_logger::{Level, Logger};
 use aptos_move_debugger::aptos_debugger::AptosDebugger;
 use aptos_push_metrics::MetricsPusher;
 use aptos_rest_client::{AptosBaseUrl, Client};
+use aptos_types::on_chain_config::FeatureFlag;
 pub use benchmark::BenchmarkCommand;
 use clap::Parser;
 pub use diff::DiffCommand;
+pub use diff_traces::DiffTracesCommand;
 pub use download::DownloadCommand;
 pub use initialize::InitializeCommand;
+use std::path::PathBuf;
+pub use trace::TraceCommand;
 use url::Url;
 
 mod benchmark;
 mod diff;
+mod diff_traces;
 mod download;
 mod initialize;
+mod trace;
 
 pub(crate) fn init_logger_and_metrics(log_level: Level) {
     let mut logger = Logger::new();
@@ -53,3 +60,64 @@ pub struct RestAPI {
     )]
     api_key: Option<String>,
 }
+
+#[derive(Parser)]
+pub struct OverrideArgs {
+    #[clap(
+        long,
+        help = "Path to a YAML file with state overrides. Overrides specified via other flags are \
+                applied in addition to the ones in the file. See the README for the format"
+    )]
+    overrides_file: Option<PathBuf>,
+
+    #[clap(
+        long,
+        num_args = 1..,
+        value_delimiter = ' ',
+        help = "List of space-separated feature flags to enable, in capital letters. For example, \
+                GAS_PAYER_ENABLED or EMIT_FEE_STATEMENT. For the full list of feature flags, see \
+                aptos-core/types/src/on_chain_config/aptos_features.rs"
+    )]
+    enable_features: Vec<FeatureFlag>,
+
+    #[clap(
+        long,
+        num_args = 1..,
+        value_delimiter = ' ',
+        help = "List of space-separated feature flags to disable, in capital letters. For \
+                example, GAS_PAYER_ENABLED or EMIT_FEE_STATEMENT. For the full list of feature \
+                flags, see aptos-core/types/src/on_chain_config/aptos_features.rs"
+    )]
+    disable_features: Vec<FeatureFlag>,
+
+    #[clap(
+        long,
+        help = "If set, overrides the gas feature version used by the gas schedule"
+    )]
+    gas_feature_version: Option<u64>,
+
+    #[clap(
+        long,
+        num_args = 1..,
+        value_delimiter = ' ',
+        help = "List of space-separated paths to compiled / built packages with Move code"
+    )]
+    override_packages: Vec<String>,
+}
+
+impl OverrideArgs {
+    /// Merges the overrides from the file (if any) with the ones specified via flags.
+    pub(crate) fn into_override_config(self) -> anyhow::Result<OverrideConfig> {
+        let mut overrides = match &self.overrides_file {
+            Some(path) => Overrides::from_file(path)?,
+            None => Overrides::default(),
+        };
+        overrides.enable_features.extend(self.enable_features);
+        overrides.disable_features.extend(self.disable_features);
+        if self.gas_feature_version.is_some() {
+            overrides.gas_feature_version = self.gas_feature_version;
+        }
+        overrides.override_packages.extend(self.override_packages);
+        OverrideConfig::new(overrides)
+    }
+}
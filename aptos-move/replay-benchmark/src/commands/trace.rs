@@ -0,0 +1,90 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    commands::{build_debugger, init_logger_and_metrics, OverrideArgs, RestAPI},
+    state_view::ReadSetCapturingStateView,
+};
+use anyhow::anyhow;
+use aptos_logger::Level;
+use aptos_move_debugger::{aptos_debugger::AptosDebugger, execution_trace::save_trace};
+use aptos_types::transaction::Version;
+use clap::Parser;
+use std::path::PathBuf;
+
+#[derive(Parser)]
+#[command(
+    about = "Replays a single user transaction, possibly on top of an overridden state, and saves \
+             its execution trace locally"
+)]
+pub struct TraceCommand {
+    #[clap(long, default_value_t = Level::Error)]
+    log_level: Level,
+
+    #[clap(flatten)]
+    rest_api: RestAPI,
+
+    #[clap(long, help = "Version of the transaction to trace")]
+    version: Version,
+
+    #[clap(
+        long,
+        help = "Path to the file where the trace will be saved (as JSON lines)"
+    )]
+    trace_file: String,
+
+    #[clap(flatten)]
+    override_args: OverrideArgs,
+}
+
+impl TraceCommand {
+    pub async fn trace_transaction(self) -> anyhow::Result<()> {
+        init_logger_and_metrics(self.log_level);
+
+        let override_config = self.override_args.into_override_config()?;
+
+        let debugger = build_debugger(self.rest_api.rest_endpoint, self.rest_api.api_key)?;
+        let (txn, _) = debugger
+            .get_committed_transaction_at_version(self.version)
+            .await?;
+        let txn = txn
+            .try_as_signed_user_txn()
+            .ok_or_else(|| anyhow!("Transaction {} is not a user transaction", self.version))?
+            .clone();
+
+        // State is fetched using blocking calls, so execute outside of the async runtime.
+        let version = self.version;
+        let trace_file = PathBuf::from(&self.trace_file);
+        tokio::task::spawn_blocking(move || {
+            let state_view = debugger.state_view_at_version(version);
+            let state_override = override_config.get_state_override(&state_view);
+            let state_view = ReadSetCapturingStateView::new(&state_view, state_override);
+
+            let (status, output, trace) =
+                AptosDebugger::execute_transaction_with_trace(&state_view, txn)?;
+            save_trace(&trace_file, &trace)?;
+            println!(
+                "Transaction {} executed with status {:?} and used {} gas units. Saved {} trace \
+                 events to {}",
+                version,
+                status,
+                output.gas_used(),
+                trace.len(),
+                trace_file.display()
+            );
+            Ok(())
+        })
+        .await?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verify_tool() {
+        use clap::CommandFactory;
+        TraceCommand::command().debug_assert();
+    }
+}
@@ -6,7 +6,7 @@ use aptos_types::{
     contract_event::ContractEvent,
     fee_statement::FeeStatement,
     state_store::state_key::StateKey,
-    transaction::{ExecutionStatus, TransactionOutput},
+    transaction::{ExecutionStatus, TransactionOutput, Version},
     write_set::{WriteOp, WriteSet, TOTAL_SUPPLY_STATE_KEY},
     AptosCoinType,
 };
@@ -14,6 +14,7 @@ use claims::{assert_ok, assert_some};
 use move_core_types::{
     account_address::AccountAddress, language_storage::TypeTag, move_resource::MoveStructType,
 };
+use serde::Serialize;
 use std::collections::BTreeMap;
 
 /// Different parts of [TransactionOutput] that can be different:
@@ -142,6 +143,125 @@ impl TransactionDiff {
     }
 }
 
+/// Status and gas used of a transaction output, as saved in a [DiffReport].
+#[derive(Serialize)]
+pub(crate) struct OutputSummary {
+    status: String,
+    gas_used: u64,
+}
+
+impl OutputSummary {
+    pub(crate) fn new(output: &TransactionOutput) -> Self {
+        Self {
+            status: format!("{:?}", output.status()),
+            gas_used: output.gas_used(),
+        }
+    }
+}
+
+/// A difference in a single event or write, as saved in a [DiffReport]. Values are missing if
+/// the event is not emitted, or if the state is not written.
+#[derive(Serialize)]
+struct ChangeReport {
+    key: String,
+    left: Option<String>,
+    right: Option<String>,
+}
+
+/// Differences in outputs of a single transaction, as saved in a [DiffReport].
+#[derive(Serialize)]
+pub(crate) struct TransactionDiffReport {
+    version: Version,
+    left: OutputSummary,
+    right: OutputSummary,
+    status_changed: bool,
+    event_changes: Vec<ChangeReport>,
+    write_set_changes: Vec<ChangeReport>,
+}
+
+impl TransactionDiffReport {
+    pub(crate) fn new(
+        version: Version,
+        left: OutputSummary,
+        right: OutputSummary,
+        diff: &TransactionDiff,
+    ) -> Self {
+        let mut report = Self {
+            version,
+            left,
+            right,
+            status_changed: false,
+            event_changes: vec![],
+            write_set_changes: vec![],
+        };
+        for diff in &diff.diffs {
+            match diff {
+                // Already part of the output summaries.
+                Diff::GasUsed { .. } => {},
+                Diff::ExecutionStatus { .. } => report.status_changed = true,
+                Diff::Event { left, right } => {
+                    let event = left.as_ref().or(right.as_ref()).expect("Event must exist");
+                    report.event_changes.push(ChangeReport {
+                        key: event.type_tag().to_canonical_string(),
+                        left: left.as_ref().map(|e| hex::encode(e.event_data())),
+                        right: right.as_ref().map(|e| hex::encode(e.event_data())),
+                    });
+                },
+                Diff::WriteSet {
+                    state_key,
+                    left,
+                    right,
+                } => report.write_set_changes.push(ChangeReport {
+                    key: format!("{:?}", state_key),
+                    left: left.as_ref().map(|op| format!("{:?}", op)),
+                    right: right.as_ref().map(|op| format!("{:?}", op)),
+                }),
+            }
+        }
+        report
+    }
+
+    pub(crate) fn has_changes(&self) -> bool {
+        self.status_changed
+            || self.left.gas_used != self.right.gas_used
+            || !self.event_changes.is_empty()
+            || !self.write_set_changes.is_empty()
+    }
+}
+
+/// Machine-readable report of the differences in outputs of all transactions, e.g., between the
+/// baseline and the overridden state.
+#[derive(Serialize)]
+pub(crate) struct DiffReport {
+    left_inputs_file: String,
+    right_inputs_file: String,
+    num_transactions: usize,
+    num_transactions_with_changes: usize,
+    num_status_changes: usize,
+    left_total_gas_used: u64,
+    right_total_gas_used: u64,
+    transactions: Vec<TransactionDiffReport>,
+}
+
+impl DiffReport {
+    pub(crate) fn new(
+        left_inputs_file: String,
+        right_inputs_file: String,
+        transactions: Vec<TransactionDiffReport>,
+    ) -> Self {
+        Self {
+            left_inputs_file,
+            right_inputs_file,
+            num_transactions: transactions.len(),
+            num_transactions_with_changes: transactions.iter().filter(|t| t.has_changes()).count(),
+            num_status_changes: transactions.iter().filter(|t| t.status_changed).count(),
+            left_total_gas_used: transactions.iter().map(|t| t.left.gas_used).sum(),
+            right_total_gas_used: transactions.iter().map(|t| t.right.gas_used).sum(),
+            transactions,
+        }
+    }
+}
+
 /// Builds [TransactionDiff]s for transaction outputs. The builder can be configured to ignore the
 /// differences in outputs sometimes.
 pub(crate) struct TransactionDiffBuilder {
@@ -346,6 +466,58 @@ mod tests {
         assert!(diff.diffs.is_empty());
     }
 
+    #[test]
+    fn test_diff_report() {
+        let state_key = StateKey::raw(b"key");
+        let output_1 = TransactionOutput::new(
+            WriteSetMut::new(vec![(
+                state_key.clone(),
+                WriteOp::legacy_modification(vec![0].into()),
+            )])
+            .freeze()
+            .unwrap(),
+            vec![],
+            1,
+            TransactionStatus::Keep(ExecutionStatus::Success),
+            TransactionAuxiliaryData::None,
+        );
+        let output_2 = TransactionOutput::new(
+            WriteSet::new(vec![]).unwrap(),
+            vec![],
+            2,
+            TransactionStatus::Keep(ExecutionStatus::OutOfGas),
+            TransactionAuxiliaryData::None,
+        );
+
+        let left = OutputSummary::new(&output_1);
+        let right = OutputSummary::new(&output_2);
+        let diff =
+            TransactionDiffBuilder::new(true).build_from_outputs(output_1.clone(), output_2, None);
+        let report = TransactionDiffReport::new(10, left, right, &diff);
+        assert!(report.has_changes());
+        assert!(report.status_changed);
+        assert_eq!(report.right.gas_used, 2);
+        assert_eq!(report.write_set_changes.len(), 1);
+        assert!(report.write_set_changes[0].right.is_none());
+
+        let left = OutputSummary::new(&output_1);
+        let right = OutputSummary::new(&output_1);
+        let diff =
+            TransactionDiffBuilder::new(false).build_from_outputs(output_1.clone(), output_1, None);
+        let unchanged_report = TransactionDiffReport::new(11, left, right, &diff);
+        assert!(!unchanged_report.has_changes());
+
+        let report = DiffReport::new("left".to_string(), "right".to_string(), vec![
+            report,
+            unchanged_report,
+        ]);
+        assert_eq!(report.num_transactions, 2);
+        assert_eq!(report.num_transactions_with_changes, 1);
+        assert_eq!(report.num_status_changes, 1);
+        assert_eq!(report.left_total_gas_used, 2);
+        assert_eq!(report.right_total_gas_used, 3);
+    }
+
     #[test]
     fn test_diff_status() {
         let output_1 = TransactionOutput::new(
@@ -2,7 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use aptos_replay_benchmark::commands::{
-    BenchmarkCommand, DiffCommand, DownloadCommand, InitializeCommand,
+    BenchmarkCommand, DiffCommand, DiffTracesCommand, DownloadCommand, InitializeCommand,
+    TraceCommand,
 };
 use clap::Parser;
 
@@ -13,6 +14,8 @@ pub enum Command {
     Initialize(InitializeCommand),
     Diff(DiffCommand),
     Benchmark(BenchmarkCommand),
+    Trace(TraceCommand),
+    DiffTraces(DiffTracesCommand),
 }
 
 #[tokio::main]
@@ -23,6 +26,8 @@ async fn main() -> anyhow::Result<()> {
         Command::Initialize(command) => command.initialize_inputs().await,
         Command::Diff(command) => command.diff_outputs().await,
         Command::Benchmark(command) => command.benchmark().await,
+        Command::Trace(command) => command.trace_transaction().await,
+        Command::DiffTraces(command) => command.diff_traces().await,
     }
 }
 
@@ -7,9 +7,14 @@
 //!   1. enabling feature flags,
 //!   2. disabling feature flags,
 //!   3. overriding gas feature version,
-//!   4. changing modules (bytecode, metadata, etc.) and package information.
+//!   4. overriding gas schedule parameters,
+//!   5. replacing any on-chain config (or other resource) with BCS-encoded bytes,
+//!   6. changing modules (bytecode, metadata, etc.) and package information.
+//!
+//! Overrides can be specified via command line flags, or declaratively in a YAML file, see
+//! [Overrides].
 
-use anyhow::bail;
+use anyhow::{anyhow, bail};
 use aptos_framework::{natives::code::PackageRegistry, BuildOptions, BuiltPackage};
 use aptos_gas_schedule::LATEST_GAS_FEATURE_VERSION;
 use aptos_logger::{error, warn};
@@ -17,12 +22,94 @@ use aptos_types::{
     on_chain_config::{FeatureFlag, Features, GasScheduleV2, OnChainConfig},
     state_store::{state_key::StateKey, state_value::StateValue, StateView},
 };
-use serde::Serialize;
+use move_core_types::{account_address::AccountAddress, language_storage::StructTag};
+use serde::{Deserialize, Deserializer, Serialize};
 use std::{
-    collections::{BTreeSet, HashMap},
-    path::PathBuf,
+    collections::{BTreeMap, BTreeSet, HashMap},
+    path::{Path, PathBuf},
+    str::FromStr,
 };
 
+/// Declarative specification of state overrides, e.g., loaded from a YAML file:
+///
+/// ```yaml
+/// enable_features: [ENABLE_CALL_TREE_AND_INSTRUCTION_VM_CACHE]
+/// disable_features: [EMIT_FEE_STATEMENT]
+/// gas_feature_version: 30
+/// gas_parameters:
+///   txn.max_execution_gas: 4000000000
+/// resources:
+///   - struct_tag: 0x1::version::Version
+///     bcs: "0x2a00000000000000"
+/// override_packages: [../../framework/aptos-framework]
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Overrides {
+    /// Feature flags to enable.
+    #[serde(deserialize_with = "deserialize_feature_flags")]
+    pub enable_features: Vec<FeatureFlag>,
+    /// Feature flags to disable.
+    #[serde(deserialize_with = "deserialize_feature_flags")]
+    pub disable_features: Vec<FeatureFlag>,
+    /// Gas feature version to use.
+    pub gas_feature_version: Option<u64>,
+    /// Gas schedule parameters to set, by their on-chain names.
+    pub gas_parameters: BTreeMap<String, u64>,
+    /// Resources (e.g., on-chain configs) to replace.
+    pub resources: Vec<ResourceOverride>,
+    /// Paths to packages with Move code. If loaded from a file, relative paths are relative to
+    /// the file's directory.
+    pub override_packages: Vec<String>,
+}
+
+impl Overrides {
+    /// Loads overrides from a YAML file.
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|err| anyhow!("Failed to read overrides file {}: {}", path.display(), err))?;
+        let mut overrides: Self = serde_yaml::from_str(&contents)
+            .map_err(|err| anyhow!("Failed to parse overrides file {}: {}", path.display(), err))?;
+
+        let base_dir = path.parent().unwrap_or_else(|| Path::new("."));
+        for package_path in overrides.override_packages.iter_mut() {
+            if Path::new(package_path).is_relative() {
+                *package_path = base_dir.join(&*package_path).display().to_string();
+            }
+        }
+        Ok(overrides)
+    }
+}
+
+/// Replaces a resource with the specified BCS-encoded bytes.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ResourceOverride {
+    /// Address of the resource, 0x1 by default (where all on-chain configs are stored).
+    #[serde(default = "default_resource_address")]
+    pub address: AccountAddress,
+    /// Type of the resource, e.g., 0x1::consensus_config::ConsensusConfig.
+    pub struct_tag: String,
+    /// Hex-encoded BCS bytes of the new value.
+    pub bcs: String,
+}
+
+fn default_resource_address() -> AccountAddress {
+    AccountAddress::ONE
+}
+
+fn deserialize_feature_flags<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<FeatureFlag>, D::Error> {
+    Vec::<String>::deserialize(deserializer)?
+        .into_iter()
+        .map(|name| {
+            FeatureFlag::from_str(&name)
+                .map_err(|_| serde::de::Error::custom(format!("Unknown feature flag {}", name)))
+        })
+        .collect()
+}
+
 /// Stores information about compiled Move packages and the build options used to create them. Used
 /// by the override configuration to shadow existing on-chain modules with modules defined in these
 /// packages.
@@ -53,20 +140,56 @@ pub struct OverrideConfig {
     additional_disabled_features: Vec<FeatureFlag>,
     /// Gas feature version to use. Invariant: must be at most the latest version.
     gas_feature_version: Option<u64>,
+    /// Gas schedule parameters to set.
+    gas_parameters: BTreeMap<String, u64>,
+    /// New BCS-encoded values of resources. Invariant: does not contain features or gas schedule
+    /// if these are overridden by other means.
+    resources: Vec<(StateKey, Vec<u8>)>,
     /// Information about overridden packages.
     package_override: PackageOverride,
 }
 
 impl OverrideConfig {
-    pub fn new(
-        additional_enabled_features: Vec<FeatureFlag>,
-        additional_disabled_features: Vec<FeatureFlag>,
-        gas_feature_version: Option<u64>,
-        override_packages: Vec<String>,
-    ) -> anyhow::Result<Self> {
+    pub fn new(overrides: Overrides) -> anyhow::Result<Self> {
+        let Overrides {
+            enable_features: additional_enabled_features,
+            disable_features: additional_disabled_features,
+            gas_feature_version,
+            gas_parameters,
+            resources,
+            override_packages,
+        } = overrides;
+
         let build_options = BuildOptions::move_2();
         let package_override = PackageOverride::new(override_packages, build_options)?;
 
+        let resources = resources
+            .into_iter()
+            .map(|resource| {
+                let struct_tag = StructTag::from_str(&resource.struct_tag).map_err(|err| {
+                    anyhow!("Invalid struct tag {}: {}", resource.struct_tag, err)
+                })?;
+                let state_key = StateKey::resource(&resource.address, &struct_tag)?;
+                let bytes = hex::decode(resource.bcs.trim_start_matches("0x")).map_err(|err| {
+                    anyhow!("Invalid hex-encoded bytes for {}: {}", struct_tag, err)
+                })?;
+                Ok((state_key, bytes))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let overrides_features =
+            !additional_enabled_features.is_empty() || !additional_disabled_features.is_empty();
+        let overrides_gas_schedule = gas_feature_version.is_some() || !gas_parameters.is_empty();
+        for (state_key, _) in &resources {
+            if (overrides_features && *state_key == config_state_key::<Features>())
+                || (overrides_gas_schedule && *state_key == config_state_key::<GasScheduleV2>())
+            {
+                bail!(
+                    "Resource {:?} cannot be replaced when it is also overridden by other means",
+                    state_key
+                );
+            }
+        }
+
         if !additional_enabled_features
             .iter()
             .all(|f| !additional_disabled_features.contains(f))
@@ -84,6 +207,8 @@ impl OverrideConfig {
             additional_enabled_features,
             additional_disabled_features,
             gas_feature_version,
+            gas_parameters,
+            resources,
             package_override,
         })
     }
@@ -116,18 +241,42 @@ impl OverrideConfig {
             state_override.insert(features_state_key, features_state_value);
         }
 
-        // Gas feature override.
-        if let Some(gas_feature_version) = self.gas_feature_version {
+        // Gas feature version and parameters override.
+        if self.gas_feature_version.is_some() || !self.gas_parameters.is_empty() {
             // Only support V2 gas schedule which has gas feature versions. Otherwise, V1 has 0
             // version at all times, and most likely it has been so long ago we will not replay
             // these transactions.
             let (gas_schedule_state_key, gas_schedule_state_value) =
                 config_override::<GasScheduleV2, _>(state_view, |gas_schedule| {
-                    gas_schedule.feature_version = gas_feature_version;
+                    if let Some(gas_feature_version) = self.gas_feature_version {
+                        gas_schedule.feature_version = gas_feature_version;
+                    }
+                    for (name, value) in &self.gas_parameters {
+                        match gas_schedule.entries.iter_mut().find(|(n, _)| n == name) {
+                            Some((_, old_value)) => *old_value = *value,
+                            None => {
+                                // Could be a new parameter, e.g., added by a framework upgrade.
+                                warn!("Gas parameter {} does not exist, adding it", name);
+                                gas_schedule.entries.push((name.clone(), *value));
+                            },
+                        }
+                    }
                 });
             state_override.insert(gas_schedule_state_key, gas_schedule_state_value);
         }
 
+        // Replace resources.
+        for (state_key, bytes) in &self.resources {
+            let state_value = state_view
+                .get_state_value(state_key)
+                .unwrap_or_else(|err| panic!("Failed to fetch {:?}: {:?}", state_key, err));
+            let state_value = match state_value {
+                Some(state_value) => state_value.map_bytes(|_| Ok(bytes.clone().into())).unwrap(),
+                None => StateValue::new_legacy(bytes.clone().into()),
+            };
+            state_override.insert(state_key.clone(), state_value);
+        }
+
         // Override packages.
         let mut overridden_package_registries = HashMap::new();
         for package in &self.package_override.packages {
@@ -280,3 +429,61 @@ fn config_override<T: OnChainConfig + Serialize, F: FnOnce(&mut T)>(
     let new_state_value = state_value.map_bytes(|_| Ok(config_bytes.into())).unwrap();
     (state_key, new_state_value)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_overrides() {
+        let overrides: Overrides = serde_yaml::from_str(
+            r#"
+enable_features: [EMIT_FEE_STATEMENT]
+gas_feature_version: 30
+gas_parameters:
+  txn.max_execution_gas: 100
+resources:
+  - struct_tag: 0x1::version::Version
+    bcs: "0x2a00000000000000"
+"#,
+        )
+        .unwrap();
+        assert_eq!(overrides.enable_features, vec![
+            FeatureFlag::EMIT_FEE_STATEMENT
+        ]);
+        assert!(overrides.disable_features.is_empty());
+        assert_eq!(overrides.gas_feature_version, Some(30));
+        assert_eq!(
+            overrides.gas_parameters.get("txn.max_execution_gas"),
+            Some(&100)
+        );
+        assert_eq!(overrides.resources[0].address, AccountAddress::ONE);
+
+        let config = OverrideConfig::new(overrides).unwrap();
+        assert_eq!(config.resources[0].1, 42u64.to_le_bytes().to_vec());
+
+        assert!(serde_yaml::from_str::<Overrides>("enable_features: [NOT_A_FEATURE]").is_err());
+        assert!(serde_yaml::from_str::<Overrides>("unknown_field: 1").is_err());
+    }
+
+    #[test]
+    fn test_overlapping_overrides() {
+        let overrides = Overrides {
+            enable_features: vec![FeatureFlag::EMIT_FEE_STATEMENT],
+            resources: vec![ResourceOverride {
+                address: AccountAddress::ONE,
+                struct_tag: "0x1::features::Features".to_string(),
+                bcs: "0x00".to_string(),
+            }],
+            ..Overrides::default()
+        };
+        assert!(OverrideConfig::new(overrides).is_err());
+
+        let overrides = Overrides {
+            enable_features: vec![FeatureFlag::EMIT_FEE_STATEMENT],
+            disable_features: vec![FeatureFlag::EMIT_FEE_STATEMENT],
+            ..Overrides::default()
+        };
+        assert!(OverrideConfig::new(overrides).is_err());
+    }
+}
@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::network::ApplicationNetworkInterfaces;
+use aptos_backup_cli::state_sync_source::BackupStorageDataSource;
 use aptos_config::config::{NodeConfig, StateSyncConfig};
 use aptos_consensus_notifications::ConsensusNotifier;
 use aptos_data_client::{client::AptosDataClient, poller};
@@ -21,6 +22,7 @@ use aptos_network::application::{
     storage::PeersAndMetadata,
 };
 use aptos_state_sync_driver::{
+    backup_source::BackupDataSource,
     driver_factory::{DriverFactory, StateSyncRuntimes},
     metadata_storage::PersistentMetadataStorage,
 };
@@ -180,6 +182,9 @@ pub fn start_state_sync_and_get_notification_handles(
         storage_service_listener,
     )?;
 
+    // Open the backup to bootstrap from (if one is configured)
+    let backup_source = open_bootstrap_backup(node_config)?;
+
     // Create the state sync driver factory
     let state_sync = DriverFactory::create_and_spawn_driver(
         true,
@@ -195,6 +200,7 @@ pub fn start_state_sync_and_get_notification_handles(
         aptos_data_client.clone(),
         streaming_service_client,
         TimeService::real(),
+        backup_source,
     );
 
     // Create a new state sync runtime handle
@@ -213,6 +219,26 @@ pub fn start_state_sync_and_get_notification_handles(
     ))
 }
 
+/// Opens the backup that state sync bootstraps from, if one is configured
+fn open_bootstrap_backup(
+    node_config: &NodeConfig,
+) -> anyhow::Result<Option<Arc<dyn BackupDataSource>>> {
+    let bootstrap_backup = match &node_config.storage.bootstrap_backup {
+        Some(bootstrap_backup) => bootstrap_backup,
+        None => return Ok(None),
+    };
+
+    // Loading the backup metadata requires a tokio runtime
+    let default_metadata_cache_dir = node_config.storage.dir().join("bootstrap_backup_metadata");
+    let runtime = aptos_runtimes::spawn_named_runtime("bootstrapbkp".into(), None);
+    let backup_source = runtime.block_on(BackupStorageDataSource::open(
+        bootstrap_backup,
+        &default_metadata_cache_dir,
+    ))?;
+
+    Ok(Some(Arc::new(backup_source)))
+}
+
 /// Sets up the data streaming service runtime
 fn setup_data_streaming_service(
     state_sync_config: StateSyncConfig,
@@ -80,6 +80,9 @@ pub struct MempoolConfig {
     pub system_transaction_timeout_secs: u64,
     /// Interval to garbage collect and remove transactions that have expired from the Mempool.
     pub system_transaction_gc_interval_ms: u64,
+    /// Interval to update the gas unit price percentiles of the transactions ready to be included
+    /// in a block, which are used for gas estimation.
+    pub backlog_gas_price_update_interval_ms: u64,
     /// Gas unit price buckets for broadcasting to upstream nodes.
     ///
     /// Overriding this won't make much of a difference if the upstream nodes don't match.
@@ -126,6 +129,7 @@ impl Default for MempoolConfig {
             shared_mempool_failover_delay_ms: 500,
             system_transaction_timeout_secs: 600,
             system_transaction_gc_interval_ms: 60_000,
+            backlog_gas_price_update_interval_ms: 1_000,
             broadcast_buckets: DEFAULT_BUCKETS.to_vec(),
             eager_expire_threshold_ms: Some(15_000),
             eager_expire_time_ms: 6_000,
@@ -181,6 +181,8 @@ pub struct StorageServiceConfig {
     pub min_time_to_ignore_peers_secs: u64,
     /// The interval (ms) to refresh the request moderator state
     pub request_moderator_refresh_interval_ms: u64,
+    /// The request quota config for the storage service
+    pub request_quota_config: StorageRequestQuotaConfig,
     /// The interval (ms) to refresh the storage summary
     pub storage_summary_refresh_interval_ms: u64,
 }
@@ -203,11 +205,49 @@ impl Default for StorageServiceConfig {
             max_transaction_output_chunk_size: MAX_TRANSACTION_OUTPUT_CHUNK_SIZE,
             min_time_to_ignore_peers_secs: 300, // 5 minutes
             request_moderator_refresh_interval_ms: 1000, // 1 second
+            request_quota_config: StorageRequestQuotaConfig::default(),
             storage_summary_refresh_interval_ms: 100, // Optimal for <= 10 blocks per second
         }
     }
 }
 
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StorageRequestQuotaConfig {
+    /// Whether or not to enforce cost-based request quotas for peers. This
+    /// should only be enabled once all clients understand the
+    /// `RequestQuotaExceeded` error (older clients fail to deserialize it).
+    pub enable_request_quotas: bool,
+    /// The cost (in bytes) charged for each estimated storage read
+    pub io_operation_cost_bytes: u64,
+    /// The maximum number of cost units (bytes) each peer can burst
+    pub max_peer_burst_bytes: u64,
+    /// The maximum number of cost units (bytes) the public network can burst
+    pub max_public_network_burst_bytes: u64,
+    /// The rate (bytes per second) at which each peer's quota is refilled
+    pub peer_refill_bytes_per_sec: u64,
+    /// The multiplier applied to the quotas of prioritized peers (i.e.,
+    /// validators and VFNs). Prioritized peers are also exempt from the
+    /// public network quota.
+    pub prioritized_peer_quota_multiplier: u64,
+    /// The rate (bytes per second) at which the public network quota is refilled
+    pub public_network_refill_bytes_per_sec: u64,
+}
+
+impl Default for StorageRequestQuotaConfig {
+    fn default() -> Self {
+        Self {
+            enable_request_quotas: false,
+            io_operation_cost_bytes: 1024,            // 1 KiB
+            max_peer_burst_bytes: 1024 * 1024 * 1024, // 1 GiB
+            max_public_network_burst_bytes: 4 * 1024 * 1024 * 1024, // 4 GiB
+            peer_refill_bytes_per_sec: 100 * 1024 * 1024, // 100 MiB per second
+            prioritized_peer_quota_multiplier: 4,
+            public_network_refill_bytes_per_sec: 400 * 1024 * 1024, // 400 MiB per second
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
 #[serde(default, deny_unknown_fields)]
 pub struct DataStreamingServiceConfig {
@@ -251,6 +291,9 @@ pub struct DataStreamingServiceConfig {
 
     /// The interval (milliseconds) at which to check the progress of each stream.
     pub progress_check_interval_ms: u64,
+
+    /// The work stealing config for the data streaming service
+    pub work_stealing: WorkStealingConfig,
 }
 
 impl Default for DataStreamingServiceConfig {
@@ -268,6 +311,7 @@ impl Default for DataStreamingServiceConfig {
             max_request_retry: 5,
             max_subscription_stream_lag_secs: 10, // 10 seconds
             progress_check_interval_ms: 50,
+            work_stealing: WorkStealingConfig::default(),
         }
     }
 }
@@ -315,6 +359,35 @@ impl Default for DynamicPrefetchingConfig {
     }
 }
 
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WorkStealingConfig {
+    /// Whether or not to enable work stealing (i.e., re-requesting the
+    /// state values of slow in-flight requests from other peers)
+    pub enable_work_stealing: bool,
+
+    /// Maximum number of stolen requests that can be in-flight at any given time
+    pub max_in_flight_stolen_requests: u64,
+
+    /// The minimum duration (ms) a request must be in-flight before it can be stolen
+    pub min_steal_duration_ms: u64,
+
+    /// The multiple of the average response time after which an in-flight
+    /// request is considered slow (and can be stolen)
+    pub slow_response_multiplier: u64,
+}
+
+impl Default for WorkStealingConfig {
+    fn default() -> Self {
+        Self {
+            enable_work_stealing: false,
+            max_in_flight_stolen_requests: 3,
+            min_steal_duration_ms: 2000, // 2 seconds
+            slow_response_multiplier: 3,
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
 #[serde(default, deny_unknown_fields)]
 pub struct AptosDataPollerConfig {
@@ -348,6 +421,29 @@ impl Default for AptosDataPollerConfig {
     }
 }
 
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AptosAdaptiveChunkingConfig {
+    /// Whether or not to adapt the state value chunk sizes
+    /// requested from each peer to the peer's observed throughput
+    pub enable_adaptive_chunking: bool,
+    /// The minimum number of state values to request from a peer
+    pub min_state_chunk_size: u64,
+    /// The target time (ms) within which each peer should respond to
+    /// state value requests (including the network latency to the peer)
+    pub target_response_time_ms: u64,
+}
+
+impl Default for AptosAdaptiveChunkingConfig {
+    fn default() -> Self {
+        Self {
+            enable_adaptive_chunking: false,
+            min_state_chunk_size: 500,
+            target_response_time_ms: 2000, // 2 seconds
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
 #[serde(default, deny_unknown_fields)]
 pub struct AptosDataMultiFetchConfig {
@@ -402,6 +498,8 @@ impl Default for AptosLatencyFilteringConfig {
 #[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
 #[serde(default, deny_unknown_fields)]
 pub struct AptosDataClientConfig {
+    /// The adaptive chunking config for the data client
+    pub adaptive_chunking_config: AptosAdaptiveChunkingConfig,
     /// Whether transaction data v2 is enabled
     pub enable_transaction_data_v2: bool,
     /// The aptos data poller config for the data client
@@ -449,6 +547,7 @@ pub struct AptosDataClientConfig {
 impl Default for AptosDataClientConfig {
     fn default() -> Self {
         Self {
+            adaptive_chunking_config: AptosAdaptiveChunkingConfig::default(),
             enable_transaction_data_v2: true,
             data_poller_config: AptosDataPollerConfig::default(),
             data_multi_fetch_config: AptosDataMultiFetchConfig::default(),
@@ -210,6 +210,34 @@ pub struct StorageConfig {
     pub ensure_rlimit_nofile: u64,
     /// panic if failed to ensure `ulimit -n`
     pub assert_rlimit_nofile: bool,
+    /// A backup (as written by the backup coordinator) to bootstrap state sync from. Epoch
+    /// ending ledger infos and the state snapshot are read from it instead of the peers,
+    /// and verified against the waypoint in the same way.
+    pub bootstrap_backup: Option<BootstrapBackupConfig>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BootstrapBackupConfig {
+    /// Read the backup from a directory on the local file system
+    pub local_fs_dir: Option<PathBuf>,
+    /// Read the backup through the command adapter described by this config file
+    pub command_adapter_config: Option<PathBuf>,
+    /// Where to cache the backup metadata across restarts, defaults to a directory under the db
+    pub metadata_cache_dir: Option<PathBuf>,
+    /// The number of metadata files to download concurrently
+    pub concurrent_downloads: usize,
+}
+
+impl Default for BootstrapBackupConfig {
+    fn default() -> Self {
+        Self {
+            local_fs_dir: None,
+            command_adapter_config: None,
+            metadata_cache_dir: None,
+            concurrent_downloads: 16,
+        }
+    }
 }
 
 pub const NO_OP_STORAGE_PRUNER_CONFIG: PrunerConfig = PrunerConfig {
@@ -359,6 +387,7 @@ impl Default for StorageConfig {
             max_num_nodes_per_lru_cache_shard: DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
             ensure_rlimit_nofile: 0,
             assert_rlimit_nofile: false,
+            bootstrap_backup: None,
         }
     }
 }
@@ -655,6 +684,18 @@ impl ConfigSanitizer for StorageConfig {
             }
         }
 
+        if let Some(bootstrap_backup) = config.bootstrap_backup.as_ref() {
+            if bootstrap_backup.local_fs_dir.is_some()
+                == bootstrap_backup.command_adapter_config.is_some()
+            {
+                return Err(Error::ConfigSanitizerFailed(
+                    sanitizer_name,
+                    "Exactly one of local_fs_dir and command_adapter_config must be set for the bootstrap backup."
+                        .to_string(),
+                ));
+            }
+        }
+
         Ok(())
     }
 }
@@ -662,7 +703,8 @@ impl ConfigSanitizer for StorageConfig {
 #[cfg(test)]
 mod test {
     use crate::config::{
-        config_optimizer::ConfigOptimizer, NodeConfig, NodeType, PrunerConfig, ShardPathConfig,
+        config_optimizer::ConfigOptimizer, config_sanitizer::ConfigSanitizer,
+        BootstrapBackupConfig, NodeConfig, NodeType, PrunerConfig, ShardPathConfig,
         ShardedDbPathConfig, StorageConfig,
     };
     use aptos_types::chain_id::ChainId;
@@ -780,4 +822,27 @@ mod test {
         assert_eq!(node_config.storage.ensure_rlimit_nofile, 999_999);
         assert!(node_config.storage.assert_rlimit_nofile);
     }
+
+    #[test]
+    fn test_sanitize_bootstrap_backup() {
+        // A backup without any storage is rejected
+        let mut node_config = NodeConfig::default();
+        node_config.storage.bootstrap_backup = Some(BootstrapBackupConfig::default());
+        assert!(StorageConfig::sanitize(&node_config, NodeType::PublicFullnode, None).is_err());
+
+        // As is a backup with both storages
+        node_config.storage.bootstrap_backup = Some(BootstrapBackupConfig {
+            local_fs_dir: Some("/backup".into()),
+            command_adapter_config: Some("/backup.yaml".into()),
+            ..Default::default()
+        });
+        assert!(StorageConfig::sanitize(&node_config, NodeType::PublicFullnode, None).is_err());
+
+        // A single storage is fine
+        node_config.storage.bootstrap_backup = Some(BootstrapBackupConfig {
+            local_fs_dir: Some("/backup".into()),
+            ..Default::default()
+        });
+        StorageConfig::sanitize(&node_config, NodeType::PublicFullnode, None).unwrap();
+    }
 }
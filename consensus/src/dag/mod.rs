@@ -17,6 +17,7 @@ mod health;
 mod observability;
 mod order_rule;
 mod rb_handler;
+mod replay;
 mod round_state;
 mod storage;
 #[cfg(test)]
@@ -24,9 +25,12 @@ mod tests;
 mod types;
 
 pub use adapter::{ProofNotifier, StorageAdapter};
+pub use anchor_election::RoundRobinAnchorElection;
 pub use bootstrap::DagBootstrapper;
 pub use commit_signer::DagCommitSigner;
 pub use dag_network::{RpcHandler, RpcWithFallback, TDAGNetworkSender};
+pub use observability::visualizer::RenderFormat;
+pub use replay::{DagReplayResult, DagReplayer};
 #[cfg(test)]
 pub use types::Extensions;
 pub use types::{CertifiedNode, DAGMessage, DAGNetworkMessage, DAGRpcResult, Node, NodeId, Vote};
@@ -4,3 +4,4 @@
 pub mod counters;
 pub mod logging;
 pub mod tracing;
+pub mod visualizer;
@@ -0,0 +1,288 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::dag::{replay::DagReplayResult, NodeId};
+use aptos_consensus_types::common::{Author, Round};
+use aptos_short_hex_str::AsShortHexStr;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Write,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum RenderFormat {
+    Dot,
+    Html,
+}
+
+impl RenderFormat {
+    pub fn render(&self, result: &DagReplayResult) -> String {
+        match self {
+            RenderFormat::Dot => render_dot(result),
+            RenderFormat::Html => render_html(result),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum NodeKind {
+    OrderedAnchor,
+    SkippedAnchor,
+    Ordered,
+    Unordered,
+}
+
+impl NodeKind {
+    fn color(&self) -> &'static str {
+        match self {
+            NodeKind::OrderedAnchor => "gold",
+            NodeKind::SkippedAnchor => "tomato",
+            NodeKind::Ordered => "lightblue",
+            NodeKind::Unordered => "white",
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            NodeKind::OrderedAnchor => "ordered anchor",
+            NodeKind::SkippedAnchor => "skipped anchor",
+            NodeKind::Ordered => "ordered",
+            NodeKind::Unordered => "unordered",
+        }
+    }
+}
+
+/// Per node classification shared between the renderers.
+struct RenderContext {
+    order_index: HashMap<NodeId, usize>,
+    ordered_anchors: HashMap<NodeId, usize>,
+    skipped_anchors: BTreeMap<Round, Author>,
+}
+
+impl RenderContext {
+    fn new(result: &DagReplayResult) -> Self {
+        Self {
+            order_index: result.order_index(),
+            ordered_anchors: result.ordered_anchors(),
+            skipped_anchors: result.skipped_anchors(),
+        }
+    }
+
+    fn kind(&self, id: &NodeId) -> NodeKind {
+        if self.ordered_anchors.contains_key(id) {
+            NodeKind::OrderedAnchor
+        } else if self.skipped_anchors.get(&id.round()) == Some(id.author()) {
+            NodeKind::SkippedAnchor
+        } else if self.order_index.contains_key(id) {
+            NodeKind::Ordered
+        } else {
+            NodeKind::Unordered
+        }
+    }
+
+    fn round_note(&self, round: Round, result: &DagReplayResult) -> Option<String> {
+        let author = self.skipped_anchors.get(&round)?;
+        let present = result
+            .nodes
+            .iter()
+            .any(|node| node.round() == round && node.author() == author);
+        let status = if present { "skipped" } else { "missing" };
+        Some(format!("anchor {} {}", author.short_str(), status))
+    }
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn dot_id(id: &NodeId) -> String {
+    format!("r{}_{}", id.round(), id.author().short_str())
+}
+
+/// Renders the replayed DAG in Graphviz dot format, one cluster per round.
+pub fn render_dot(result: &DagReplayResult) -> String {
+    let mut out = String::new();
+    write_dot(result, &mut out).expect("writing to a String never fails");
+    out
+}
+
+fn write_dot(result: &DagReplayResult, out: &mut String) -> std::fmt::Result {
+    let ctx = RenderContext::new(result);
+    let mut by_round: BTreeMap<Round, Vec<_>> = BTreeMap::new();
+    for node in &result.nodes {
+        by_round.entry(node.round()).or_default().push(node);
+    }
+
+    writeln!(out, "digraph dag_epoch_{} {{", result.epoch)?;
+    writeln!(out, "  rankdir=BT;")?;
+    writeln!(out, "  node [shape=box, style=filled];")?;
+    for (round, nodes) in &by_round {
+        let mut label = format!("round {}", round);
+        if let Some(note) = ctx.round_note(*round, result) {
+            write!(label, " ({})", note)?;
+        }
+        writeln!(out, "  subgraph cluster_round_{} {{", round)?;
+        writeln!(out, "    label=\"{}\";", label)?;
+        if ctx.skipped_anchors.contains_key(round) {
+            writeln!(out, "    color=red;")?;
+        }
+        for node in nodes {
+            let id = node.id();
+            let kind = ctx.kind(&id);
+            let mut node_label = format!("{}\\n{}", round, id.author().short_str());
+            if let Some(idx) = ctx.order_index.get(&id) {
+                write!(node_label, "\\n#{}", idx)?;
+            }
+            writeln!(
+                out,
+                "    \"{}\" [label=\"{}\", fillcolor={}];",
+                dot_id(&id),
+                node_label,
+                kind.color()
+            )?;
+        }
+        writeln!(out, "  }}")?;
+    }
+    for node in &result.nodes {
+        for parent in node.parents_metadata() {
+            writeln!(
+                out,
+                "  \"{}\" -> \"{}\";",
+                dot_id(&node.id()),
+                dot_id(parent)
+            )?;
+        }
+    }
+    writeln!(out, "}}")
+}
+
+/// Renders the replayed DAG as a self-contained HTML page: a round by validator grid followed
+/// by the committed order and any nodes that were rejected during replay.
+pub fn render_html(result: &DagReplayResult) -> String {
+    let mut out = String::new();
+    write_html(result, &mut out).expect("writing to a String never fails");
+    out
+}
+
+fn write_html(result: &DagReplayResult, out: &mut String) -> std::fmt::Result {
+    let ctx = RenderContext::new(result);
+    let nodes: HashMap<NodeId, _> = result.nodes.iter().map(|node| (node.id(), node)).collect();
+    let highest_round = result
+        .nodes
+        .iter()
+        .map(|node| node.round())
+        .max()
+        .unwrap_or(result.start_round);
+
+    writeln!(
+        out,
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>DAG epoch {}</title>",
+        result.epoch
+    )?;
+    writeln!(
+        out,
+        "<style>table{{border-collapse:collapse}}td,th{{border:1px solid #999;padding:2px 6px;\
+         font-family:monospace;font-size:12px}}tr.skipped th{{background:tomato}}</style>"
+    )?;
+    writeln!(out, "</head><body><h1>DAG epoch {}</h1>", result.epoch)?;
+    writeln!(
+        out,
+        "<p>{} nodes, {} anchors ordered, {} anchors skipped, {} nodes rejected.</p>",
+        result.nodes.len(),
+        result.ordered.len(),
+        ctx.skipped_anchors.len(),
+        result.rejected.len()
+    )?;
+    write!(out, "<p>")?;
+    for kind in [
+        NodeKind::OrderedAnchor,
+        NodeKind::SkippedAnchor,
+        NodeKind::Ordered,
+        NodeKind::Unordered,
+    ] {
+        write!(
+            out,
+            "<span style=\"background:{}\">{}</span> ",
+            kind.color(),
+            kind.label()
+        )?;
+    }
+    writeln!(out, "</p>")?;
+
+    writeln!(out, "<table><tr><th>round</th>")?;
+    for author in &result.validators {
+        write!(out, "<th>{}</th>", author.short_str())?;
+    }
+    writeln!(out, "<th>note</th></tr>")?;
+    for round in (result.start_round..=highest_round).rev() {
+        let note = ctx.round_note(round, result);
+        let class = if note.is_some() {
+            " class=\"skipped\""
+        } else {
+            ""
+        };
+        write!(out, "<tr{}><th>{}</th>", class, round)?;
+        for author in &result.validators {
+            let id = NodeId::new(result.epoch, round, *author);
+            match nodes.get(&id) {
+                Some(node) => {
+                    let kind = ctx.kind(&id);
+                    let anchor_marker = if result.anchors.get(&round) == Some(author) {
+                        "&#9875;"
+                    } else {
+                        ""
+                    };
+                    let order = ctx
+                        .order_index
+                        .get(&id)
+                        .map(|idx| format!(" #{}", idx))
+                        .unwrap_or_default();
+                    write!(
+                        out,
+                        "<td style=\"background:{}\" title=\"{} parents\">{}{}</td>",
+                        kind.color(),
+                        node.parents().len(),
+                        anchor_marker,
+                        order
+                    )?;
+                },
+                None => write!(out, "<td></td>")?,
+            }
+        }
+        writeln!(out, "<td>{}</td></tr>", note.unwrap_or_default())?;
+    }
+    writeln!(out, "</table>")?;
+
+    writeln!(out, "<h2>Committed order</h2><ol start=\"0\">")?;
+    for batch in &result.ordered {
+        write!(
+            out,
+            "<li>anchor round {} by {}: {} nodes",
+            batch.anchor.round(),
+            batch.anchor.author().short_str(),
+            batch.nodes.len()
+        )?;
+        if !batch.skipped_anchors.is_empty() {
+            let skipped: Vec<_> = batch
+                .skipped_anchors
+                .iter()
+                .map(|(round, author)| format!("{}:{}", round, author.short_str()))
+                .collect();
+            write!(out, ", skipped anchors [{}]", skipped.join(", "))?;
+        }
+        writeln!(out, "</li>")?;
+    }
+    writeln!(out, "</ol>")?;
+
+    if !result.rejected.is_empty() {
+        writeln!(out, "<h2>Rejected nodes</h2><ul>")?;
+        for (id, reason) in &result.rejected {
+            writeln!(out, "<li>{}: {}</li>", id, escape_html(reason))?;
+        }
+        writeln!(out, "</ul>")?;
+    }
+    writeln!(out, "</body></html>")
+}
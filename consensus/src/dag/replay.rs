@@ -0,0 +1,302 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{
+    adapter::OrderedNotifier,
+    anchor_election::{AnchorElection, RoundRobinAnchorElection},
+    dag_store::DagStore,
+    order_rule::OrderRule,
+    storage::{CommitEvent, DAGStorage},
+    types::Vote,
+    CertifiedNode, Node, NodeId,
+};
+use crate::payload_manager::DirectMempoolPayloadManager;
+use anyhow::{bail, ensure};
+use aptos_consensus_types::common::{Author, Round};
+use aptos_crypto::HashValue;
+use aptos_infallible::Mutex;
+use aptos_types::{
+    epoch_state::EpochState,
+    ledger_info::LedgerInfoWithSignatures,
+    on_chain_config::{AnchorElectionMode, DagConsensusConfigV1},
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
+
+/// A batch of nodes ordered by a single anchor, in the order they are sent to execution.
+#[derive(Clone, Debug)]
+pub struct OrderedBatch {
+    pub anchor: NodeId,
+    pub nodes: Vec<NodeId>,
+    /// Anchors (round, author) that were skipped by this anchor.
+    pub skipped_anchors: Vec<(Round, Author)>,
+}
+
+/// Everything the replay learned about the persisted DAG, used by the visualizer.
+pub struct DagReplayResult {
+    pub epoch: u64,
+    pub validators: Vec<Author>,
+    pub start_round: Round,
+    pub nodes: Vec<Arc<CertifiedNode>>,
+    /// Nodes that were loaded from storage but couldn't be inserted into the DAG.
+    pub rejected: Vec<(NodeId, String)>,
+    pub ordered: Vec<OrderedBatch>,
+    /// The elected anchor for every anchor round replayed.
+    pub anchors: BTreeMap<Round, Author>,
+}
+
+impl DagReplayResult {
+    /// Position of each node in the committed order.
+    pub fn order_index(&self) -> HashMap<NodeId, usize> {
+        self.ordered
+            .iter()
+            .flat_map(|batch| batch.nodes.iter())
+            .enumerate()
+            .map(|(idx, node_id)| (node_id.clone(), idx))
+            .collect()
+    }
+
+    pub fn ordered_anchors(&self) -> HashMap<NodeId, usize> {
+        self.ordered
+            .iter()
+            .enumerate()
+            .map(|(idx, batch)| (batch.anchor.clone(), idx))
+            .collect()
+    }
+
+    /// Rounds whose anchor was skipped when a later anchor got ordered.
+    pub fn skipped_anchors(&self) -> BTreeMap<Round, Author> {
+        self.ordered
+            .iter()
+            .flat_map(|batch| batch.skipped_anchors.iter().cloned())
+            .collect()
+    }
+}
+
+/// Records the ordered nodes instead of sending them to execution.
+#[derive(Default)]
+struct RecordingNotifier {
+    ordered: Mutex<Vec<OrderedBatch>>,
+}
+
+impl OrderedNotifier for RecordingNotifier {
+    fn send_ordered_nodes(
+        &self,
+        ordered_nodes: Vec<Arc<CertifiedNode>>,
+        failed_author: Vec<(Round, Author)>,
+    ) {
+        let anchor = ordered_nodes
+            .last()
+            .expect("ordered nodes must not be empty")
+            .id();
+        self.ordered.lock().push(OrderedBatch {
+            anchor,
+            nodes: ordered_nodes.iter().map(|node| node.id()).collect(),
+            skipped_anchors: failed_author,
+        });
+    }
+}
+
+/// The replay never writes back to the consensus db.
+struct ReplayStorage;
+
+impl DAGStorage for ReplayStorage {
+    fn save_pending_node(&self, _node: &Node) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn get_pending_node(&self) -> anyhow::Result<Option<Node>> {
+        Ok(None)
+    }
+
+    fn delete_pending_node(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn save_vote(&self, _node_id: &NodeId, _vote: &Vote) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn get_votes(&self) -> anyhow::Result<Vec<(NodeId, Vote)>> {
+        Ok(vec![])
+    }
+
+    fn delete_votes(&self, _node_ids: Vec<NodeId>) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn save_certified_node(&self, _node: &CertifiedNode) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn get_certified_nodes(&self) -> anyhow::Result<Vec<(HashValue, CertifiedNode)>> {
+        Ok(vec![])
+    }
+
+    fn delete_certified_nodes(&self, _digests: Vec<HashValue>) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn get_latest_k_committed_events(&self, _k: u64) -> anyhow::Result<Vec<CommitEvent>> {
+        Ok(vec![])
+    }
+
+    fn get_latest_ledger_info(&self) -> anyhow::Result<LedgerInfoWithSignatures> {
+        bail!("ledger info is not available during replay")
+    }
+
+    fn get_epoch_to_proposers(&self) -> HashMap<u64, Vec<Author>> {
+        HashMap::new()
+    }
+}
+
+/// Replays the order rule over persisted certified nodes without any network or execution.
+///
+/// Nodes are inserted in (round, validator index) order, the order rule is deterministic
+/// regardless of the insertion order so the result matches what the validator ordered given
+/// the same anchor election. Anchors are on odd rounds, as the first anchor round of an epoch
+/// is 1.
+pub struct DagReplayer {
+    epoch_state: Arc<EpochState>,
+    anchor_election: Arc<dyn AnchorElection>,
+    window_size: u64,
+    verify_signatures: bool,
+}
+
+impl DagReplayer {
+    pub fn new(
+        epoch_state: Arc<EpochState>,
+        anchor_election: Arc<dyn AnchorElection>,
+        window_size: u64,
+    ) -> Self {
+        Self {
+            epoch_state,
+            anchor_election,
+            window_size,
+            verify_signatures: false,
+        }
+    }
+
+    /// Uses the anchor election and ordering window of the epoch's on-chain DAG config.
+    /// Leader reputation isn't supported, as the anchors it elects depend on the commit history
+    /// before the start round and on the proposers of the previous epochs, which aren't
+    /// available offline.
+    pub fn from_onchain_config(
+        epoch_state: Arc<EpochState>,
+        config: &DagConsensusConfigV1,
+    ) -> anyhow::Result<Self> {
+        let anchor_election: Arc<dyn AnchorElection> = match &config.anchor_election_mode {
+            AnchorElectionMode::RoundRobin => Arc::new(RoundRobinAnchorElection::new(
+                epoch_state.verifier.get_ordered_account_addresses(),
+            )),
+            AnchorElectionMode::LeaderReputation(_) => bail!(
+                "Epoch {} uses leader reputation anchor election, which can't be replayed \
+                 without the commit history.",
+                epoch_state.epoch
+            ),
+        };
+        let window_size = config.dag_ordering_causal_history_window as u64;
+        Ok(Self::new(epoch_state, anchor_election, window_size))
+    }
+
+    pub fn with_signature_verification(mut self, verify_signatures: bool) -> Self {
+        self.verify_signatures = verify_signatures;
+        self
+    }
+
+    /// Replays `nodes` starting with `start_round` as the lowest unordered anchor round, which
+    /// must be odd. If `start_round` is None, the lowest odd round among the nodes is used.
+    pub fn replay(
+        &self,
+        nodes: Vec<CertifiedNode>,
+        start_round: Option<Round>,
+    ) -> anyhow::Result<DagReplayResult> {
+        if let Some(round) = start_round {
+            ensure!(round % 2 == 1, "Anchor round {} is not odd.", round);
+        }
+        let validators = self.epoch_state.verifier.get_ordered_account_addresses();
+        let author_to_index = self.epoch_state.verifier.address_to_validator_index();
+        let mut rejected = vec![];
+        let mut nodes: Vec<_> = nodes
+            .into_iter()
+            .filter(|node| {
+                if node.epoch() != self.epoch_state.epoch {
+                    return false;
+                }
+                if self.verify_signatures {
+                    if let Err(e) = node.verify(&self.epoch_state.verifier) {
+                        rejected.push((node.id(), e.to_string()));
+                        return false;
+                    }
+                }
+                true
+            })
+            .collect();
+        nodes.sort_by_key(|node| {
+            (
+                node.round(),
+                author_to_index
+                    .get(node.author())
+                    .copied()
+                    .unwrap_or(usize::MAX),
+            )
+        });
+
+        let start_round = start_round
+            .or_else(|| nodes.first().map(|node| node.round() | 1))
+            .unwrap_or(1);
+        let dag = Arc::new(DagStore::new_empty(
+            self.epoch_state.clone(),
+            Arc::new(ReplayStorage),
+            Arc::new(DirectMempoolPayloadManager::new()),
+            start_round,
+            self.window_size,
+        ));
+        let notifier = Arc::new(RecordingNotifier::default());
+        let mut order_rule = OrderRule::new(
+            self.epoch_state.clone(),
+            start_round,
+            dag.clone(),
+            self.anchor_election.clone(),
+            notifier.clone(),
+            self.window_size,
+            None,
+        );
+
+        let mut inserted = vec![];
+        for node in nodes {
+            if node.round() < start_round {
+                continue;
+            }
+            let id = node.id();
+            let metadata = node.metadata().clone();
+            match dag.add_node(node) {
+                Ok(()) => {
+                    order_rule.process_new_node(&metadata);
+                    if let Some(node) = dag.read().get_node(&metadata) {
+                        inserted.push(node);
+                    }
+                },
+                Err(e) => rejected.push((id, e.to_string())),
+            }
+        }
+
+        let highest_round = dag.read().highest_round();
+        let anchors = (start_round..=highest_round)
+            .step_by(2)
+            .map(|round| (round, self.anchor_election.get_anchor(round)))
+            .collect();
+        let ordered = std::mem::take(&mut *notifier.ordered.lock());
+        Ok(DagReplayResult {
+            epoch: self.epoch_state.epoch,
+            validators,
+            start_round,
+            nodes: inserted,
+            rejected,
+            ordered,
+            anchors,
+        })
+    }
+}
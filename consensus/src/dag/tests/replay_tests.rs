@@ -0,0 +1,138 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::dag::{
+    anchor_election::RoundRobinAnchorElection,
+    observability::visualizer::{render_dot, render_html},
+    replay::DagReplayer,
+    tests::helpers::{generate_dag_nodes, TEST_DAG_WINDOW},
+};
+use aptos_types::{
+    epoch_state::EpochState,
+    on_chain_config::{AnchorElectionMode, DagConsensusConfigV1},
+    validator_verifier::random_validator_verifier,
+};
+use std::sync::Arc;
+
+#[test]
+fn test_replay_flags_skipped_anchor() {
+    // The anchor of round 1 (validator 0) is missing, so the anchor of round 3 skips it.
+    let dag = vec![
+        vec![None, Some(vec![]), Some(vec![]), Some(vec![])],
+        vec![
+            Some(vec![true, true, true]),
+            Some(vec![true, true, true]),
+            Some(vec![true, true, true]),
+            Some(vec![true, true, true]),
+        ],
+        vec![
+            Some(vec![true, true, true, false]),
+            Some(vec![true, true, true, false]),
+            Some(vec![true, true, true, false]),
+            Some(vec![true, true, true, false]),
+        ],
+        vec![
+            Some(vec![true, true, true, false]),
+            Some(vec![true, true, true, false]),
+            Some(vec![true, true, true, false]),
+            Some(vec![true, true, true, false]),
+        ],
+    ];
+    let (_, validator_verifier) = random_validator_verifier(4, None, false);
+    let validators = validator_verifier.get_ordered_account_addresses();
+    let nodes: Vec<_> = generate_dag_nodes(&dag, &validators)
+        .into_iter()
+        .flatten()
+        .flatten()
+        // the replay shouldn't depend on the order nodes are read from storage
+        .rev()
+        .collect();
+    let epoch_state = Arc::new(EpochState {
+        epoch: 1,
+        verifier: validator_verifier.into(),
+    });
+    let anchor_election = Arc::new(RoundRobinAnchorElection::new(validators.clone()));
+
+    let result = DagReplayer::new(epoch_state, anchor_election, TEST_DAG_WINDOW)
+        .replay(nodes, None)
+        .unwrap();
+
+    assert_eq!(result.start_round, 1);
+    assert_eq!(result.nodes.len(), 15);
+    assert!(result.rejected.is_empty());
+    assert_eq!(result.ordered.len(), 1);
+    let batch = &result.ordered[0];
+    assert_eq!(batch.anchor.round(), 3);
+    assert_eq!(*batch.anchor.author(), validators[1]);
+    assert_eq!(batch.skipped_anchors, vec![(1, validators[0])]);
+    // 3 nodes in round 1, the 3 parents in round 2 and the anchor itself
+    assert_eq!(batch.nodes.len(), 7);
+    assert_eq!(result.order_index().get(&batch.anchor), Some(&6));
+
+    let dot = render_dot(&result);
+    assert!(dot.contains("label=\"round 1 (anchor"));
+    assert!(dot.contains("missing)\";\n    color=red;"));
+    let html = render_html(&result);
+    assert!(html.contains("1 anchors ordered, 1 anchors skipped, 0 nodes rejected"));
+}
+
+#[test]
+fn test_replay_uses_onchain_config_and_odd_anchor_rounds() {
+    let dag = vec![
+        vec![Some(vec![]), Some(vec![]), Some(vec![]), Some(vec![])],
+        vec![
+            Some(vec![true, true, true, true]),
+            Some(vec![true, true, true, true]),
+            Some(vec![true, true, true, true]),
+            Some(vec![true, true, true, true]),
+        ],
+        vec![
+            Some(vec![true, true, true, true]),
+            Some(vec![true, true, true, true]),
+            Some(vec![true, true, true, true]),
+            Some(vec![true, true, true, true]),
+        ],
+    ];
+    let (_, validator_verifier) = random_validator_verifier(4, None, false);
+    let validators = validator_verifier.get_ordered_account_addresses();
+    let nodes: Vec<_> = generate_dag_nodes(&dag, &validators)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .collect();
+    let epoch_state = Arc::new(EpochState {
+        epoch: 1,
+        verifier: validator_verifier.into(),
+    });
+    let config = DagConsensusConfigV1 {
+        dag_ordering_causal_history_window: TEST_DAG_WINDOW as usize,
+        anchor_election_mode: AnchorElectionMode::RoundRobin,
+    };
+    let replayer = DagReplayer::from_onchain_config(epoch_state, &config).unwrap();
+
+    assert!(replayer.replay(nodes.clone(), Some(2)).is_err());
+
+    let result = replayer.replay(nodes, None).unwrap();
+    assert_eq!(result.anchors.keys().copied().collect::<Vec<_>>(), vec![
+        1, 3
+    ]);
+    assert_eq!(result.ordered.len(), 1);
+    assert_eq!(result.ordered[0].anchor.round(), 1);
+    assert_eq!(*result.ordered[0].anchor.author(), validators[0]);
+}
+
+#[test]
+fn test_replay_rejects_leader_reputation() {
+    let (_, validator_verifier) = random_validator_verifier(4, None, false);
+    let epoch_state = Arc::new(EpochState {
+        epoch: 1,
+        verifier: validator_verifier.into(),
+    });
+    assert!(matches!(
+        DagConsensusConfigV1::default().anchor_election_mode,
+        AnchorElectionMode::LeaderReputation(_)
+    ));
+    assert!(
+        DagReplayer::from_onchain_config(epoch_state, &DagConsensusConfigV1::default()).is_err()
+    );
+}
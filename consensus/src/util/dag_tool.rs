@@ -0,0 +1,90 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+pub use crate::dag::RenderFormat;
+use crate::{
+    consensusdb::{CertifiedNodeSchema, ConsensusDB},
+    dag::{DagReplayResult, DagReplayer},
+};
+use anyhow::{ensure, Result};
+use aptos_consensus_types::common::Round;
+use aptos_types::{epoch_state::EpochState, on_chain_config::DagConsensusConfigV1};
+use clap::Parser;
+use std::{path::PathBuf, sync::Arc};
+
+#[derive(Parser)]
+#[clap(about = "Replay the DAG order rule over nodes in consensus db and render the DAG.")]
+pub struct Command {
+    #[clap(long, value_parser)]
+    pub db_dir: PathBuf,
+
+    /// Epoch to replay, defaults to the highest epoch found in consensus db.
+    #[clap(long)]
+    pub epoch: Option<u64>,
+
+    /// Lowest unordered anchor round, must be odd. Defaults to the lowest odd round found in
+    /// consensus db.
+    #[clap(long)]
+    pub start_round: Option<Round>,
+
+    /// Reject nodes whose certificate doesn't verify against the epoch's validator set.
+    #[clap(long)]
+    pub verify_signatures: bool,
+
+    #[clap(long, value_enum, default_value_t = RenderFormat::Html)]
+    pub format: RenderFormat,
+
+    #[clap(long, value_parser)]
+    pub output: PathBuf,
+}
+
+impl Command {
+    /// Consensus db doesn't keep the validator set or the on-chain config, so the caller
+    /// provides the epoch state and the epoch's DAG consensus config. Epochs using leader
+    /// reputation anchor election are rejected.
+    pub fn run(
+        &self,
+        epoch_state: Arc<EpochState>,
+        dag_config: &DagConsensusConfigV1,
+    ) -> Result<()> {
+        let result = self.replay(epoch_state, dag_config)?;
+        std::fs::write(&self.output, self.format.render(&result))?;
+        println!(
+            "Replayed {} nodes of epoch {}: {} anchors ordered, {} anchors skipped, {} nodes rejected. Output written to {:?}.",
+            result.nodes.len(),
+            result.epoch,
+            result.ordered.len(),
+            result.skipped_anchors().len(),
+            result.rejected.len(),
+            self.output,
+        );
+        Ok(())
+    }
+
+    pub fn replay(
+        &self,
+        epoch_state: Arc<EpochState>,
+        dag_config: &DagConsensusConfigV1,
+    ) -> Result<DagReplayResult> {
+        let replayer = DagReplayer::from_onchain_config(epoch_state.clone(), dag_config)?
+            .with_signature_verification(self.verify_signatures);
+        let consensus_db = ConsensusDB::new(self.db_dir.clone());
+        let nodes: Vec<_> = consensus_db
+            .get_all::<CertifiedNodeSchema>()?
+            .into_iter()
+            .map(|(_, node)| node)
+            .collect();
+        let epoch = self
+            .epoch
+            .or_else(|| nodes.iter().map(|node| node.epoch()).max())
+            .unwrap_or(epoch_state.epoch);
+        ensure!(
+            epoch == epoch_state.epoch,
+            "Epoch state is for epoch {}, but replaying epoch {}.",
+            epoch_state.epoch,
+            epoch
+        );
+
+        replayer.replay(nodes, self.start_round)
+    }
+}
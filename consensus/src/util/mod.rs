@@ -8,6 +8,7 @@ use aptos_types::{
     validator_txn::ValidatorTransaction,
 };
 
+pub mod dag_tool;
 pub mod db_tool;
 #[cfg(any(test, feature = "fuzzing"))]
 pub mod mock_time_service;
@@ -23,6 +23,7 @@ mod index;
 mod json_encoder;
 mod metrics;
 mod peer_information;
+mod state_sync_progress;
 mod system_information;
 pub mod utils;
 
@@ -38,6 +39,7 @@ pub const INDEX_PATH: &str = "/";
 pub const JSON_METRICS_PATH: &str = "/json_metrics";
 pub const METRICS_PATH: &str = "/metrics";
 pub const PEER_INFORMATION_PATH: &str = "/peer_information";
+pub const STATE_SYNC_PROGRESS_PATH: &str = "/state_sync_progress";
 pub const SYSTEM_INFORMATION_PATH: &str = "/system_information";
 
 // Useful string constants
@@ -153,6 +155,11 @@ async fn serve_requests(
                 peers_and_metadata,
             )
         },
+        STATE_SYNC_PROGRESS_PATH => {
+            // /state_sync_progress
+            // Exposes the progress of the state snapshot sync
+            state_sync_progress::handle_state_sync_progress_request()
+        },
         SYSTEM_INFORMATION_PATH => {
             // /system_information
             // Exposes the system and build information
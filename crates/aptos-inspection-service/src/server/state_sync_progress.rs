@@ -0,0 +1,70 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::server::{utils, utils::CONTENT_TYPE_TEXT};
+use hyper::{Body, StatusCode};
+use std::collections::HashMap;
+
+// The metric name for the state snapshot sync progress gauges
+const SNAPSHOT_SYNC_PROGRESS_GAUGE: &str = "aptos_state_sync_snapshot_sync_progress";
+
+// The progress types (i.e., labels) of the state snapshot sync progress gauges
+const TARGET_VERSION: &str = "target_version";
+const SYNCED_STATE_VALUES: &str = "synced_state_values";
+const ESTIMATED_STATE_VALUES: &str = "estimated_state_values";
+const PROGRESS_BASIS_POINTS: &str = "progress_basis_points";
+const STATE_VALUES_PER_SECOND: &str = "state_values_per_second";
+const ETA_SECONDS: &str = "eta_seconds";
+
+/// Handles a new state sync progress request
+pub fn handle_state_sync_progress_request() -> (StatusCode, Body, String) {
+    let metrics = utils::get_all_metrics();
+    (
+        StatusCode::OK,
+        Body::from(get_state_sync_progress_response(&metrics)),
+        CONTENT_TYPE_TEXT.into(),
+    )
+}
+
+/// Returns a simple text response that describes the progress of
+/// the state snapshot sync (as read from the given metrics).
+fn get_state_sync_progress_response(metrics: &HashMap<String, String>) -> String {
+    // Fetch the progress values
+    let get_progress_value = |progress_type: &str| -> Option<u64> {
+        let metric_key = format!("{}{{type={}}}", SNAPSHOT_SYNC_PROGRESS_GAUGE, progress_type);
+        metrics
+            .get(&metric_key)
+            .and_then(|value| value.parse::<f64>().ok())
+            .map(|value| value as u64)
+    };
+    let target_version = match get_progress_value(TARGET_VERSION) {
+        Some(target_version) => target_version,
+        None => return "No state snapshot sync has started!".into(),
+    };
+    let synced_state_values = get_progress_value(SYNCED_STATE_VALUES).unwrap_or_default();
+    let estimated_state_values = get_progress_value(ESTIMATED_STATE_VALUES).unwrap_or_default();
+    let progress_basis_points = get_progress_value(PROGRESS_BASIS_POINTS).unwrap_or_default();
+    let state_values_per_second = get_progress_value(STATE_VALUES_PER_SECOND).unwrap_or_default();
+    let eta_seconds = get_progress_value(ETA_SECONDS).unwrap_or_default();
+
+    // Create the response
+    let mut progress_response: Vec<String> = Vec::new();
+    progress_response.push("State snapshot sync progress:".into());
+    progress_response.push(format!("\t- Target version: {}", target_version));
+    progress_response.push(format!(
+        "\t- Progress: {}.{:02}%",
+        progress_basis_points / 100,
+        progress_basis_points % 100
+    ));
+    progress_response.push(format!(
+        "\t- Synced state values: {} (estimated total: {})",
+        synced_state_values, estimated_state_values
+    ));
+    progress_response.push(format!(
+        "\t- State values per second: {}",
+        state_values_per_second
+    ));
+    progress_response.push(format!("\t- Estimated time remaining: {}s", eta_seconds));
+
+    progress_response.join("\n") // Separate each entry with a newline
+}
@@ -9,7 +9,8 @@ use crate::{
         system_information::SYS_INFO_DISABLED_MESSAGE, utils::get_all_metrics,
     },
     CONFIGURATION_PATH, FORGE_METRICS_PATH, IDENTITY_INFORMATION_PATH, INDEX_PATH,
-    JSON_METRICS_PATH, METRICS_PATH, PEER_INFORMATION_PATH, SYSTEM_INFORMATION_PATH,
+    JSON_METRICS_PATH, METRICS_PATH, PEER_INFORMATION_PATH, STATE_SYNC_PROGRESS_PATH,
+    SYSTEM_INFORMATION_PATH,
 };
 use aptos_config::config::{AptosDataClientConfig, BaseConfig, Identity, NodeConfig};
 use aptos_data_client::client::AptosDataClient;
@@ -21,7 +22,10 @@ use assert_approx_eq::assert_approx_eq;
 use futures::executor::block_on;
 use hyper::{body, Body, Method, Request, Response, StatusCode};
 use once_cell::sync::Lazy;
-use prometheus::{proto::MetricFamily, register_int_counter, Counter, IntCounter, Opts, Registry};
+use prometheus::{
+    proto::MetricFamily, register_int_counter, register_int_gauge_vec, Counter, IntCounter,
+    IntGaugeVec, Opts, Registry,
+};
 use rusty_fork::rusty_fork_test;
 use std::{collections::HashMap, io::read_to_string, string::String, sync::Arc};
 
@@ -31,6 +35,17 @@ const INT_COUNTER_NAME: &str = "INT_COUNTER";
 static INT_COUNTER: Lazy<IntCounter> =
     Lazy::new(|| register_int_counter!(INT_COUNTER_NAME, "An integer counter").unwrap());
 
+// The state snapshot sync progress gauges are registered by state sync
+// (which doesn't run in this test context), so we add them for tests.
+static SNAPSHOT_SYNC_PROGRESS: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "aptos_state_sync_snapshot_sync_progress",
+        "Gauges for tracking the progress of the state snapshot sync",
+        &["type"]
+    )
+    .unwrap()
+});
+
 #[tokio::test]
 async fn test_inspect_configuration() {
     // Create a validator config
@@ -92,6 +107,7 @@ async fn test_inspect_index() {
     assert!(response_body_string.contains(JSON_METRICS_PATH));
     assert!(response_body_string.contains(METRICS_PATH));
     assert!(response_body_string.contains(PEER_INFORMATION_PATH));
+    assert!(response_body_string.contains(STATE_SYNC_PROGRESS_PATH));
     assert!(response_body_string.contains(SYSTEM_INFORMATION_PATH));
 }
 
@@ -159,6 +175,46 @@ async fn test_inspect_metrics() {
     assert!(response_body_string.contains(INT_COUNTER_NAME));
 }
 
+#[tokio::test]
+async fn test_inspect_state_sync_progress() {
+    // Create a full node config
+    let config = NodeConfig::get_default_vfn_config();
+
+    // Ping the state sync progress endpoint before any snapshot sync has started
+    let mut response = send_get_request_to_path(&config, STATE_SYNC_PROGRESS_PATH).await;
+    let response_body = body::to_bytes(response.body_mut()).await.unwrap();
+    let response_body_string = read_to_string(response_body.as_ref()).unwrap();
+
+    // Verify that the response notes that no snapshot sync has started
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response_body_string.contains("No state snapshot sync has started"));
+
+    // Update the snapshot sync progress and ping the endpoint again
+    for (progress_type, value) in [
+        ("target_version", 1000),
+        ("synced_state_values", 1234),
+        ("estimated_state_values", 10000),
+        ("progress_basis_points", 1234),
+        ("state_values_per_second", 100),
+        ("eta_seconds", 88),
+    ] {
+        SNAPSHOT_SYNC_PROGRESS
+            .with_label_values(&[progress_type])
+            .set(value);
+    }
+    let mut response = send_get_request_to_path(&config, STATE_SYNC_PROGRESS_PATH).await;
+    let response_body = body::to_bytes(response.body_mut()).await.unwrap();
+    let response_body_string = read_to_string(response_body.as_ref()).unwrap();
+
+    // Verify that the response contains the expected progress
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response_body_string.contains("Target version: 1000"));
+    assert!(response_body_string.contains("Progress: 12.34%"));
+    assert!(response_body_string.contains("Synced state values: 1234 (estimated total: 10000)"));
+    assert!(response_body_string.contains("State values per second: 100"));
+    assert!(response_body_string.contains("Estimated time remaining: 88s"));
+}
+
 #[tokio::test]
 async fn test_inspect_system_information() {
     // Create a validator node config
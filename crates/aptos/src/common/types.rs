@@ -43,12 +43,15 @@ use aptos_sdk::{
     transaction_builder::TransactionFactory,
     types::{HardwareWalletAccount, HardwareWalletType, LocalAccount, TransactionSigner},
 };
+use aptos_transaction_simulation::SimulationStateStore;
+use aptos_transaction_simulation_session::Session;
 use aptos_types::{
+    account_config::AccountResource,
     chain_id::ChainId,
     transaction::{
-        authenticator::AuthenticationKey, EntryFunction, MultisigTransactionPayload,
-        ReplayProtector, Script, SignedTransaction, TransactionArgument, TransactionPayload,
-        TransactionStatus,
+        authenticator::{AccountAuthenticator, AuthenticationKey},
+        EntryFunction, MultisigTransactionPayload, ReplayProtector, Script, SignedTransaction,
+        TransactionArgument, TransactionPayload, TransactionStatus,
     },
 };
 use aptos_vm_types::output::VMOutput;
@@ -74,7 +77,7 @@ use std::{
     convert::TryFrom,
     fmt::{Debug, Display, Formatter},
     fs::OpenOptions,
-    path::PathBuf,
+    path::{Path, PathBuf},
     str::FromStr,
     time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
@@ -84,6 +87,10 @@ pub const USER_AGENT: &str = concat!("aptos-cli/", env!("CARGO_PKG_VERSION"));
 pub const US_IN_SECS: u64 = 1_000_000;
 pub const ACCEPTED_CLOCK_SKEW_US: u64 = 5 * US_IN_SECS;
 pub const DEFAULT_EXPIRATION_SECS: u64 = 30;
+// Gas parameters of local simulations.
+// TODO(Gas): get the following from the chain
+const DEFAULT_GAS_UNIT_PRICE: u64 = 100;
+const DEFAULT_MAX_GAS: u64 = 2_000_000;
 pub const DEFAULT_PROFILE: &str = "default";
 pub const GIT_IGNORE: &str = ".gitignore";
 
@@ -1220,6 +1227,13 @@ pub struct MovePackageOptions {
     #[clap(long)]
     pub(crate) skip_fetch_latest_git_deps: bool,
 
+    /// Fail if the dependencies do not resolve to the versions pinned in Move.lock
+    ///
+    /// Without this flag, dependencies which are not pinned in Move.lock resolve to their
+    /// latest versions.  Move.lock is only written by `aptos move update-deps`.
+    #[clap(long)]
+    pub(crate) locked: bool,
+
     /// Do not complain about unknown attributes in Move code.
     #[clap(long)]
     pub skip_attribute_checks: bool,
@@ -1292,6 +1306,7 @@ impl MovePackageOptions {
             named_addresses: Default::default(),
             override_std: None,
             skip_fetch_latest_git_deps: true,
+            locked: false,
             bytecode_version: None,
             compiler_version: Some(CompilerVersion::latest_stable()),
             language_version: Some(LanguageVersion::latest_stable()),
@@ -1799,6 +1814,20 @@ pub struct TransactionOptions {
     #[clap(long)]
     pub(crate) profile_gas: bool,
 
+    /// If this option is set, run the transaction in the local simulation session at this path
+    /// instead of submitting it, updating the state of the session.
+    ///
+    /// Sessions are created with `aptos move sim init`.
+    #[clap(long, value_parser)]
+    pub(crate) session: Option<PathBuf>,
+
+    /// If this option is set, run the transaction in the session as this account, without its
+    /// key
+    ///
+    /// The authentication key of the account is not checked. Only available with `--session`.
+    #[clap(long, requires = "session", value_parser = crate::common::types::load_account_arg)]
+    pub(crate) impersonate: Option<AccountAddress>,
+
     /// Replay protection mechanism to use when generating the transaction.
     ///
     /// When "nonce" is chosen, the transaction will be an orderless transaction and contains a replay protection nonce.
@@ -2090,10 +2119,6 @@ impl TransactionOptions {
         let client = self.rest_client()?;
 
         // Fetch the chain states required for the simulation
-        // TODO(Gas): get the following from the chain
-        const DEFAULT_GAS_UNIT_PRICE: u64 = 100;
-        const DEFAULT_MAX_GAS: u64 = 2_000_000;
-
         let (sender_key, sender_address) = self.get_key_and_address()?;
         let gas_unit_price = self
             .gas_options
@@ -2152,6 +2177,87 @@ impl TransactionOptions {
         Ok(summary)
     }
 
+    /// Runs a transaction in a local simulation session, the sequence number, chain id and time
+    /// are taken from the state of the session.
+    pub async fn simulate_in_session(
+        &self,
+        session_path: &Path,
+        payload: TransactionPayload,
+    ) -> CliTypedResult<TransactionSummary> {
+        println!();
+        println!(
+            "Running transaction in session {}...",
+            session_path.display()
+        );
+
+        let mut session = Session::load(session_path)?;
+        let state_store = session.state_store();
+
+        let sender_address = match self.impersonate {
+            Some(address) => address,
+            None => self.sender_address()?,
+        };
+        let sequence_number = state_store
+            .get_resource::<AccountResource>(sender_address)?
+            .map_or(0, |account| account.sequence_number);
+        let chain_id = state_store.get_chain_id()?;
+        let now_secs = state_store.get_timestamp_micros()? / US_IN_SECS;
+
+        let gas_unit_price = self
+            .gas_options
+            .gas_unit_price
+            .unwrap_or(DEFAULT_GAS_UNIT_PRICE);
+        let max_gas = self.gas_options.max_gas.unwrap_or(DEFAULT_MAX_GAS);
+
+        let transaction_builder = TransactionFactory::new(chain_id)
+            .with_gas_unit_price(gas_unit_price)
+            .with_max_gas_amount(max_gas)
+            .payload(payload)
+            .expiration_timestamp_secs(now_secs + self.gas_options.expiration_secs);
+
+        let (hash, (vm_status, txn_output)) = if self.impersonate.is_some() {
+            let raw_txn = transaction_builder
+                .sender(sender_address)
+                .sequence_number(sequence_number)
+                .build();
+            // The hash of the transaction as simulated, i.e., without any authenticator.
+            let hash = SignedTransaction::new_single_sender(
+                raw_txn.clone(),
+                AccountAuthenticator::NoAccountAuthenticator,
+            )
+            .committed_hash();
+            (hash, session.execute_impersonated_transaction(raw_txn)?)
+        } else {
+            let (sender_key, _) = self.get_key_and_address()?;
+            let sender_account =
+                &mut LocalAccount::new(sender_address, sender_key, sequence_number);
+            let transaction = sender_account.sign_with_transaction_builder(transaction_builder);
+            (
+                transaction.committed_hash(),
+                session.execute_transaction(transaction)?,
+            )
+        };
+
+        let success = match txn_output.status() {
+            TransactionStatus::Keep(exec_status) => Some(exec_status.is_success()),
+            TransactionStatus::Discard(_) | TransactionStatus::Retry => None,
+        };
+
+        Ok(TransactionSummary {
+            transaction_hash: hash.into(),
+            gas_used: Some(txn_output.gas_used()),
+            gas_unit_price: Some(gas_unit_price),
+            pending: None,
+            sender: Some(sender_address),
+            sequence_number: Some(sequence_number),
+            replay_protector: None,
+            success,
+            timestamp_us: None,
+            version: None, // The transaction is not comitted so there is no version.
+            vm_status: Some(vm_status.to_string()),
+        })
+    }
+
     /// Simulates a transaction locally.
     pub async fn simulate_locally(
         &self,
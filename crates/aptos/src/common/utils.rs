@@ -509,8 +509,21 @@ pub async fn profile_or_submit(
         ));
     }
 
-    // Profile gas if needed.
-    if txn_options_ref.profile_gas {
+    if txn_options_ref.session.is_some()
+        && (txn_options_ref.profile_gas || txn_options_ref.benchmark || txn_options_ref.local)
+    {
+        return Err(CliError::UnexpectedError(
+            "Cannot run a transaction in a session and simulate it locally at the same time."
+                .to_string(),
+        ));
+    }
+
+    // Run in a session, profile gas or simulate locally if needed.
+    if let Some(session_path) = &txn_options_ref.session {
+        txn_options_ref
+            .simulate_in_session(session_path, payload)
+            .await
+    } else if txn_options_ref.profile_gas {
         txn_options_ref.profile_gas(payload).await
     } else if txn_options_ref.benchmark {
         txn_options_ref.benchmark_locally(payload).await
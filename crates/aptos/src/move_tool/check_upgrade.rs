@@ -0,0 +1,542 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::{
+        types::{
+            load_account_arg, CliCommand, CliError, CliTypedResult, MovePackageOptions,
+            ProfileOptions, RestOptions,
+        },
+        utils::get_feature_flag,
+    },
+    move_tool::{fix_bytecode_version, CachedPackageRegistry, IncludedArtifacts},
+};
+use aptos_api_types::AptosErrorCode;
+use aptos_framework::{natives::code::UpgradePolicy, BuildOptions, BuiltPackage};
+use aptos_rest_client::{error::RestError, Client};
+use aptos_types::{
+    account_address::AccountAddress,
+    chain_id::ChainId,
+    on_chain_config::{FeatureFlag, TimedFeatureFlag, TimedFeaturesBuilder},
+};
+use async_trait::async_trait;
+use clap::Parser;
+use legacy_move_compiler::compiled_unit::{CompiledUnit, NamedCompiledModule};
+use move_binary_format::{
+    access::ModuleAccess,
+    compatibility::{Compatibility, Incompatibility},
+    file_format::{FunctionDefinitionIndex, StructDefinitionIndex, TableIndex},
+    CompiledModule,
+};
+use move_ir_types::location::Loc;
+use reqwest::StatusCode;
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    fmt,
+    path::{Path, PathBuf},
+};
+
+/// Checks whether a package can be published as an upgrade of its on-chain version
+///
+/// Builds the package locally and compares it with the version in the `PackageRegistry` of the
+/// account. Every change which the chain would reject, under the upgrade policy of the
+/// package, is reported with its location in the local sources.
+#[derive(Parser)]
+pub struct CheckUpgrade {
+    /// Address of the account containing the package
+    #[clap(long, value_parser = load_account_arg)]
+    pub(crate) account: AccountAddress,
+
+    #[clap(flatten)]
+    pub(crate) move_options: MovePackageOptions,
+    #[clap(flatten)]
+    pub(crate) rest_options: RestOptions,
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+}
+
+/// The result of a successful upgrade check
+#[derive(Debug, Serialize)]
+pub struct UpgradeCheck {
+    /// Whether the package can be published as an upgrade
+    pub message: &'static str,
+    /// The changes which the upgrade policy of the published package allows
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub policy: Option<String>,
+}
+
+impl UpgradeCheck {
+    fn not_published() -> Self {
+        Self {
+            message: "Package is not published yet, any version can be published",
+            policy: None,
+        }
+    }
+}
+
+#[async_trait]
+impl CliCommand<UpgradeCheck> for CheckUpgrade {
+    fn command_name(&self) -> &'static str {
+        "CheckUpgrade"
+    }
+
+    async fn execute(self) -> CliTypedResult<UpgradeCheck> {
+        let package_path = self.move_options.get_package_path()?;
+        let build_options = BuildOptions {
+            install_dir: self.move_options.output_dir.clone(),
+            bytecode_version: fix_bytecode_version(
+                self.move_options.bytecode_version,
+                self.move_options.language_version,
+            ),
+            ..IncludedArtifacts::None.build_options(&self.move_options)?
+        };
+        let pack = BuiltPackage::build(package_path.clone(), build_options)
+            .map_err(|e| CliError::MoveCompilationError(format!("{:#}", e)))?;
+        let new_policy = pack.extract_metadata()?.upgrade_policy;
+
+        let url = self.rest_options.url(&self.profile_options)?;
+        let registry = match CachedPackageRegistry::create(url.clone(), self.account, true).await {
+            Ok(registry) => registry,
+            Err(err) if is_not_found(&err) => return Ok(UpgradeCheck::not_published()),
+            Err(err) => return Err(err.into()),
+        };
+        if !registry.package_names().contains(&pack.name()) {
+            return Ok(UpgradeCheck::not_published());
+        }
+        let old_package = registry.get_package(pack.name()).await?;
+        let old_policy = old_package.upgrade_policy();
+        let mut old_modules = BTreeMap::new();
+        for name in old_package.module_names() {
+            if let Some(bytecode) = registry.get_bytecode(name).await? {
+                let module = CompiledModule::deserialize(bytecode).map_err(|err| {
+                    CliError::UnexpectedError(format!(
+                        "Failed to deserialize on-chain module `{}`: {}",
+                        name, err
+                    ))
+                })?;
+                old_modules.insert(name.to_string(), module);
+            }
+        }
+
+        let client = Client::new(url);
+        let treat_friend_as_private =
+            get_feature_flag(&client, FeatureFlag::TREAT_FRIEND_AS_PRIVATE).await?;
+        let treat_entry_as_public = is_entry_compatibility_enabled(&client).await?;
+        let policy = explain_policy(
+            old_policy,
+            new_policy,
+            treat_friend_as_private,
+            treat_entry_as_public,
+        );
+
+        let mut issues = check_policy(old_policy, new_policy);
+        if old_policy != UpgradePolicy::immutable() {
+            let compatibility = Compatibility::new(
+                /* check_struct_layout */ true,
+                /* check_friend_linking */ !treat_friend_as_private,
+                treat_entry_as_public,
+                /* function_type_compat_bug */ false,
+            );
+            issues.extend(check_modules(
+                &old_modules,
+                &pack,
+                &package_path,
+                &compatibility,
+            ));
+        }
+        if issues.is_empty() {
+            return Ok(UpgradeCheck {
+                message: "Package upgrade is compatible",
+                policy: Some(policy),
+            });
+        }
+        Err(CliError::UnexpectedError(format!(
+            "Package upgrade is not compatible: found {} incompatible change(s)\n{}\n{}",
+            issues.len(),
+            issues
+                .iter()
+                .map(|issue| issue.to_string())
+                .collect::<Vec<_>>()
+                .join("\n"),
+            policy
+        )))
+    }
+}
+
+/// Returns true if `TimedFeatureFlag::EntryCompatibility` is enabled on the network at the current
+/// ledger timestamp, i.e., `entry` functions must be kept on upgrades like public ones.
+async fn is_entry_compatibility_enabled(client: &Client) -> CliTypedResult<bool> {
+    let state = client
+        .get_ledger_information()
+        .await
+        .map_err(|err| CliError::ApiError(err.to_string()))?
+        .into_inner();
+    Ok(
+        TimedFeaturesBuilder::new(ChainId::new(state.chain_id), state.timestamp_usecs)
+            .build()
+            .is_enabled(TimedFeatureFlag::EntryCompatibility),
+    )
+}
+
+/// Returns true if the request for the `PackageRegistry` failed because the account or the
+/// resource does not exist, i.e., no package was published to the account yet.
+fn is_not_found(err: &anyhow::Error) -> bool {
+    match err.downcast_ref::<RestError>() {
+        Some(RestError::Api(response)) => {
+            response.status_code == StatusCode::NOT_FOUND
+                || matches!(
+                    response.error.error_code,
+                    AptosErrorCode::AccountNotFound | AptosErrorCode::ResourceNotFound
+                )
+        },
+        Some(RestError::Http(status_code, _)) => *status_code == StatusCode::NOT_FOUND,
+        _ => false,
+    }
+}
+
+/// A change which the chain would reject when publishing the upgrade.
+struct UpgradeIssue {
+    /// The location of the change in the local sources, as `path:line:column`
+    location: Option<String>,
+    /// The name of the module the change is in
+    module: Option<String>,
+    message: String,
+}
+
+impl fmt::Display for UpgradeIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(location) = &self.location {
+            write!(f, "{}: ", location)?;
+        }
+        if let Some(module) = &self.module {
+            write!(f, "module `{}`: ", module)?;
+        }
+        f.write_str(&self.message)
+    }
+}
+
+/// Describes which changes are allowed when upgrading a package published under `old_policy`.
+fn explain_policy(
+    old_policy: UpgradePolicy,
+    new_policy: UpgradePolicy,
+    treat_friend_as_private: bool,
+    treat_entry_as_public: bool,
+) -> String {
+    if old_policy == UpgradePolicy::immutable() {
+        return "The package is published with upgrade policy `immutable`, and cannot be upgraded."
+            .to_string();
+    }
+    let friends = if treat_friend_as_private {
+        "`public(friend)` functions which are not `entry` functions can be changed or removed \
+        like private ones, and friend declarations can be removed."
+    } else {
+        "`public(friend)` functions must be kept like public ones, and friend declarations \
+        cannot be removed."
+    };
+    let functions = if treat_entry_as_public {
+        "public and entry functions"
+    } else {
+        "public functions"
+    };
+    format!(
+        "The package is published with upgrade policy `{}`, and upgraded with policy `{}`. \
+        The policy can only be kept or strengthened. An upgrade can add modules, structs, \
+        functions, struct abilities and friend declarations, and make functions more visible. \
+        Modules cannot be removed, and {} as well as structs must be kept with their signatures, \
+        type parameters and layouts. {}",
+        old_policy, new_policy, functions, friends
+    )
+}
+
+fn check_policy(old_policy: UpgradePolicy, new_policy: UpgradePolicy) -> Vec<UpgradeIssue> {
+    let mut messages = vec![];
+    if old_policy == UpgradePolicy::immutable() {
+        messages.push("packages with upgrade policy `immutable` cannot be upgraded".to_string());
+    }
+    if new_policy.policy < old_policy.policy {
+        messages.push(format!(
+            "the upgrade policy cannot be weakened from `{}` to `{}`",
+            old_policy, new_policy
+        ));
+    }
+    if new_policy == UpgradePolicy::arbitrary() {
+        messages.push("packages cannot be published with upgrade policy `arbitrary`".to_string());
+    }
+    messages
+        .into_iter()
+        .map(|message| UpgradeIssue {
+            location: None,
+            module: None,
+            message,
+        })
+        .collect()
+}
+
+/// Checks the modules of the local package against the on-chain `old_modules`.
+fn check_modules(
+    old_modules: &BTreeMap<String, CompiledModule>,
+    pack: &BuiltPackage,
+    package_path: &Path,
+    compatibility: &Compatibility,
+) -> Vec<UpgradeIssue> {
+    let new_modules = pack
+        .package
+        .root_modules()
+        .filter_map(|unit| match &unit.unit {
+            CompiledUnit::Module(module) => {
+                Some((module.name.to_string(), (module, &unit.source_path)))
+            },
+            CompiledUnit::Script(_) => None,
+        })
+        .collect::<BTreeMap<_, _>>();
+    let mut issues = vec![];
+    for (name, old_module) in old_modules {
+        let Some((new_module, source_path)) = new_modules.get(name) else {
+            issues.push(UpgradeIssue {
+                location: None,
+                module: Some(name.clone()),
+                message: "removed module, modules of a package cannot be removed".to_string(),
+            });
+            continue;
+        };
+        for incompatibility in compatibility.incompatibilities(old_module, &new_module.module) {
+            issues.push(UpgradeIssue {
+                location: source_location(new_module, source_path, package_path, &incompatibility),
+                module: Some(name.clone()),
+                message: incompatibility.to_string(),
+            });
+        }
+    }
+    issues
+}
+
+/// Returns the location of the definition which `incompatibility` is about, or of the module if
+/// the definition was removed.
+fn source_location(
+    module: &NamedCompiledModule,
+    source_path: &Path,
+    package_path: &Path,
+    incompatibility: &Incompatibility,
+) -> Option<String> {
+    let compiled = &module.module;
+    let loc = match incompatibility {
+        Incompatibility::StructAbilitiesRemoved { name, .. }
+        | Incompatibility::StructTypeParametersChanged { name }
+        | Incompatibility::StructLayoutChanged { name } => {
+            let index = compiled.struct_defs().iter().position(|def| {
+                compiled.identifier_at(compiled.struct_handle_at(def.struct_handle).name)
+                    == name.as_ident_str()
+            })?;
+            module
+                .source_map
+                .get_struct_source_map(StructDefinitionIndex(index as TableIndex))
+                .ok()?
+                .definition_location
+        },
+        Incompatibility::FunctionChanged { name, .. } => {
+            let index = compiled.function_defs().iter().position(|def| {
+                compiled.identifier_at(compiled.function_handle_at(def.function).name)
+                    == name.as_ident_str()
+            })?;
+            module
+                .source_map
+                .get_function_source_map(FunctionDefinitionIndex(index as TableIndex))
+                .ok()?
+                .definition_location
+        },
+        _ => module.source_map.definition_location,
+    };
+    format_location(source_path, package_path, loc)
+}
+
+/// Formats `loc` in the file at `source_path` as `path:line:column`.
+fn format_location(source_path: &Path, package_path: &Path, loc: Loc) -> Option<String> {
+    let source = std::fs::read_to_string(source_path).ok()?;
+    let prefix = source.get(..loc.start() as usize)?;
+    let line = prefix.matches('\n').count() + 1;
+    let column = prefix.len() - prefix.rfind('\n').map_or(0, |pos| pos + 1) + 1;
+    let path = source_path
+        .strip_prefix(package_path)
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| source_path.to_path_buf());
+    Some(format!("{}:{}:{}", path.display(), line, column))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use move_binary_format::compatibility::FunctionChange;
+    use move_core_types::identifier::Identifier;
+    use std::{collections::BTreeSet, fs};
+    use tempfile::TempDir;
+
+    const OLD_SOURCE: &str = "module 0xcafe::m {
+    struct S has key {
+        value: u64,
+    }
+
+    public fun f(x: u64): u64 {
+        x
+    }
+
+    public fun g() {}
+}
+";
+
+    // Changes the layout of `S` and the parameters of `f`, and removes `g`
+    const NEW_SOURCE: &str = "module 0xcafe::m {
+    struct S has key {
+        value: u128,
+    }
+
+    public fun f(x: u64, _y: u64): u64 {
+        x
+    }
+}
+";
+
+    const OTHER_SOURCE: &str = "module 0xcafe::other {}\n";
+
+    /// Builds a package with the given `(file name, source)` pairs in a new directory. Returns
+    /// the directory, which is removed when dropped, and the canonical path of the package.
+    fn build(sources: &[(&str, &str)]) -> (TempDir, PathBuf, BuiltPackage) {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path().canonicalize().unwrap();
+        fs::create_dir_all(dir.join("sources")).unwrap();
+        fs::write(
+            dir.join("Move.toml"),
+            "[package]\nname = \"Upgrade\"\nversion = \"0.0.0\"\n",
+        )
+        .unwrap();
+        for (file_name, source) in sources {
+            fs::write(dir.join("sources").join(file_name), source).unwrap();
+        }
+        let pack = BuiltPackage::build(dir.clone(), BuildOptions::move_2()).unwrap();
+        (temp_dir, dir, pack)
+    }
+
+    fn modules(pack: &BuiltPackage) -> BTreeMap<String, CompiledModule> {
+        pack.package
+            .root_modules()
+            .filter_map(|unit| match &unit.unit {
+                CompiledUnit::Module(module) => {
+                    Some((module.name.to_string(), module.module.clone()))
+                },
+                CompiledUnit::Script(_) => None,
+            })
+            .collect()
+    }
+
+    fn compatibility() -> Compatibility {
+        Compatibility::new(true, true, true, false)
+    }
+
+    fn messages(issues: &[UpgradeIssue]) -> Vec<String> {
+        issues.iter().map(|issue| issue.to_string()).collect()
+    }
+
+    #[test]
+    fn policy_can_be_kept_or_strengthened() {
+        assert!(check_policy(UpgradePolicy::compat(), UpgradePolicy::compat()).is_empty());
+        assert!(check_policy(UpgradePolicy::compat(), UpgradePolicy::immutable()).is_empty());
+    }
+
+    #[test]
+    fn policy_cannot_be_weakened_or_arbitrary() {
+        assert_eq!(
+            messages(&check_policy(
+                UpgradePolicy::immutable(),
+                UpgradePolicy::compat()
+            )),
+            vec![
+                "packages with upgrade policy `immutable` cannot be upgraded",
+                "the upgrade policy cannot be weakened from `immutable` to `compatible`",
+            ]
+        );
+        assert_eq!(
+            messages(&check_policy(
+                UpgradePolicy::compat(),
+                UpgradePolicy::arbitrary()
+            )),
+            vec![
+                "the upgrade policy cannot be weakened from `compatible` to `arbitrary`",
+                "packages cannot be published with upgrade policy `arbitrary`",
+            ]
+        );
+    }
+
+    #[test]
+    fn removed_module() {
+        let (_old_dir, _, old) = build(&[("m.move", OLD_SOURCE), ("other.move", OTHER_SOURCE)]);
+        let (_new_dir, path, new) = build(&[("m.move", OLD_SOURCE)]);
+        let issues = check_modules(&modules(&old), &new, &path, &compatibility());
+        assert_eq!(messages(&issues), vec![
+            "module `other`: removed module, modules of a package cannot be removed"
+        ]);
+    }
+
+    #[test]
+    fn unchanged_modules() {
+        let (_old_dir, _, old) = build(&[("m.move", OLD_SOURCE)]);
+        let (_new_dir, path, new) = build(&[("m.move", OLD_SOURCE)]);
+        assert!(check_modules(&modules(&old), &new, &path, &compatibility()).is_empty());
+    }
+
+    #[test]
+    fn changed_definitions_are_located() {
+        let (_old_dir, _, old) = build(&[("m.move", OLD_SOURCE)]);
+        let (_new_dir, path, new) = build(&[("m.move", NEW_SOURCE)]);
+        let issues = check_modules(&modules(&old), &new, &path, &compatibility());
+        assert!(issues
+            .iter()
+            .all(|issue| issue.module.as_deref() == Some("m")));
+        // The struct and the changed function are located at their definitions, the removed
+        // function at the module
+        let locations = issues
+            .iter()
+            .map(|issue| issue.location.clone().unwrap())
+            .collect::<BTreeSet<_>>();
+        assert_eq!(
+            locations,
+            BTreeSet::from([
+                "sources/m.move:1:1".to_string(),
+                "sources/m.move:2:5".to_string(),
+                "sources/m.move:6:16".to_string(),
+            ])
+        );
+    }
+
+    #[test]
+    fn source_locations() {
+        let (_dir, path, pack) = build(&[("m.move", NEW_SOURCE)]);
+        let unit = pack.package.root_modules().next().unwrap();
+        let CompiledUnit::Module(module) = &unit.unit else {
+            panic!("expected a module")
+        };
+        let locate = |incompatibility: Incompatibility| {
+            source_location(module, &unit.source_path, &path, &incompatibility)
+        };
+        let name = |name: &str| Identifier::new(name).unwrap();
+        assert_eq!(
+            locate(Incompatibility::StructLayoutChanged { name: name("S") }).as_deref(),
+            Some("sources/m.move:2:5")
+        );
+        assert_eq!(
+            locate(Incompatibility::StructRemoved { name: name("T") }).as_deref(),
+            Some("sources/m.move:1:1")
+        );
+        assert_eq!(
+            locate(Incompatibility::FunctionChanged {
+                name: name("f"),
+                change: FunctionChange::ParameterTypes,
+            })
+            .as_deref(),
+            Some("sources/m.move:6:16")
+        );
+        assert_eq!(
+            locate(Incompatibility::FunctionRemoved { name: name("g") }).as_deref(),
+            Some("sources/m.move:1:1")
+        );
+    }
+}
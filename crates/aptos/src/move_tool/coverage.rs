@@ -7,17 +7,19 @@ use crate::{
 };
 use aptos_framework::extended_checks;
 use async_trait::async_trait;
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use legacy_move_compiler::compiled_unit::{CompiledUnit, NamedCompiledModule};
 use move_coverage::{
     coverage_map::CoverageMap,
     format_csv_summary, format_human_summary,
+    report::CoverageReport,
     source_coverage::{ColorChoice, SourceCoverageBuilder, TextIndicator},
     summary::summarize_inst_cov,
 };
 use move_disassembler::disassembler::Disassembler;
 use move_model::metadata::{CompilerVersion, LanguageVersion};
 use move_package::{compilation::compiled_package::CompiledPackage, BuildConfig, CompilerConfig};
+use std::{fs::File, io::BufWriter, path::PathBuf};
 
 /// Display a coverage summary for all modules in a package
 ///
@@ -166,6 +168,75 @@ impl CliCommand<()> for BytecodeCoverage {
     }
 }
 
+/// The format coverage is exported in
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum CoverageFormat {
+    /// An lcov tracefile
+    Lcov,
+    /// Cobertura XML
+    Cobertura,
+}
+
+/// Export line and branch coverage for CI tooling
+///
+/// Writes the coverage of all modules in the package as an lcov tracefile or as Cobertura XML,
+/// as consumed by Codecov, GitLab and similar tools.
+#[derive(Debug, Parser)]
+pub struct ExportCoverage {
+    /// The format to export coverage in
+    #[clap(long, value_enum, default_value_t = CoverageFormat::Lcov)]
+    pub format: CoverageFormat,
+
+    /// The file to write coverage to
+    ///
+    /// Defaults to `lcov.info` for lcov and to `cobertura.xml` for Cobertura, in the package
+    /// directory.
+    #[clap(long, value_parser)]
+    pub output_file: Option<PathBuf>,
+
+    #[clap(flatten)]
+    pub move_options: MovePackageOptions,
+}
+
+#[async_trait]
+impl CliCommand<String> for ExportCoverage {
+    fn command_name(&self) -> &'static str {
+        "ExportCoverage"
+    }
+
+    async fn execute(self) -> CliTypedResult<String> {
+        let package_path = self.move_options.get_package_path()?;
+        let (coverage_map, package) = compile_coverage(self.move_options)?;
+        let exec_map = coverage_map.to_unified_exec_map();
+        let mut report = CoverageReport::default();
+        for unit in package.root_modules() {
+            if let CompiledUnit::Module(NamedCompiledModule {
+                module, source_map, ..
+            }) = &unit.unit
+            {
+                report
+                    .add_module(module, source_map, &unit.source_path, &exec_map)
+                    .map_err(|err| CliError::UnexpectedError(err.to_string()))?;
+            }
+        }
+
+        let output_file = self.output_file.unwrap_or_else(|| match self.format {
+            CoverageFormat::Lcov => package_path.join("lcov.info"),
+            CoverageFormat::Cobertura => package_path.join("cobertura.xml"),
+        });
+        let mut writer = BufWriter::new(
+            File::create(&output_file)
+                .map_err(|err| CliError::IO(output_file.display().to_string(), err))?,
+        );
+        match self.format {
+            CoverageFormat::Lcov => report.write_lcov(&mut writer),
+            CoverageFormat::Cobertura => report.write_cobertura(&package_path, &mut writer),
+        }
+        .map_err(|err| CliError::IO(output_file.display().to_string(), err))?;
+        Ok(format!("Wrote coverage to {}", output_file.display()))
+    }
+}
+
 fn compile_coverage(
     move_options: MovePackageOptions,
 ) -> CliTypedResult<(CoverageMap, CompiledPackage)> {
@@ -176,6 +247,7 @@ fn compile_coverage(
         full_model_generation: !move_options.skip_checks_on_test_code,
         install_dir: move_options.output_dir.clone(),
         skip_fetch_latest_git_deps: move_options.skip_fetch_latest_git_deps,
+        locked: move_options.locked,
         compiler_config: CompilerConfig {
             known_attributes: extended_checks::get_all_attribute_names().clone(),
             skip_attribute_checks: move_options.skip_attribute_checks,
@@ -215,6 +287,7 @@ pub enum CoveragePackage {
     Summary(SummaryCoverage),
     Source(SourceCoverage),
     Bytecode(BytecodeCoverage),
+    Export(ExportCoverage),
 }
 
 impl CoveragePackage {
@@ -223,6 +296,7 @@ impl CoveragePackage {
             Self::Summary(tool) => tool.execute_serialized_success().await,
             Self::Source(tool) => tool.execute_serialized_success().await,
             Self::Bytecode(tool) => tool.execute_serialized_success().await,
+            Self::Export(tool) => tool.execute_serialized().await,
         }
     }
 }
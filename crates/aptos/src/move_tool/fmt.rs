@@ -1,18 +1,16 @@
 // Copyright (c) Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{
-    common::{
-        types::{CliCommand, CliError, CliTypedResult},
-        utils::dir_default_to_current,
-    },
-    update::get_movefmt_path,
+use crate::common::{
+    types::{CliCommand, CliError, CliTypedResult},
+    utils::dir_default_to_current,
 };
 use async_trait::async_trait;
 use clap::{Args, Parser};
-use move_command_line_common::files::find_move_filenames;
+use move_command_line_common::{files::find_move_filenames, testing::format_diff_no_color};
+use move_fmt::{format_source, FormatConfig};
 use move_package::source_package::layout::SourcePackageLayout;
-use std::{collections::BTreeMap, fs, path::PathBuf, process::Command};
+use std::{collections::BTreeMap, fs, path::PathBuf};
 
 /// Format the Move source code.
 #[derive(Debug, Parser)]
@@ -23,16 +21,21 @@ pub struct Fmt {
 
 #[derive(clap::ValueEnum, Clone, Copy, Debug, Eq, PartialEq, PartialOrd)]
 pub enum EmitMode {
+    /// Overwrite the files with the formatted code.
     Overwrite,
+    /// Write the formatted code to a new file next to the original, with the extension
+    /// `.fmt.out`.
     NewFile,
+    /// Print the formatted code.
     StdOut,
+    /// Print the difference between the original and the formatted code.
     Diff,
 }
 
 #[derive(Debug, Args)]
 pub struct FmtCommand {
     /// How to generate and show the result after reformatting.
-    /// Warning: if not specified or set in the config file, files will by default be overwritten.
+    /// Warning: if not specified, files will by default be overwritten.
     #[clap(long, value_enum)]
     emit_mode: Option<EmitMode>,
 
@@ -52,7 +55,8 @@ pub struct FmtCommand {
 
     /// Set options from command line. These settings take
     /// priority over movefmt.toml.
-    /// Config options can be found at https://github.com/movebit/movefmt/blob/develop/doc/how_to_use.md
+    /// The options are `max_width` (default 90) and `indent_size` (default 4),
+    /// e.g. `--config max_width=100,indent_size=2`. Other options are ignored with a warning.
     #[clap(long, value_parser = crate::common::utils::parse_map::<String, String>, default_value = "")]
     pub(crate) config: BTreeMap<String, String>,
 
@@ -78,42 +82,9 @@ impl CliCommand<String> for Fmt {
 
 impl FmtCommand {
     async fn execute(self) -> CliTypedResult<String> {
-        let exe = get_movefmt_path()?;
         let package_opt = self.package_path;
-        let config_path_opt = self.config_path;
         let files_opt = self.file_path;
-        let config_map = self.config;
-        let verbose_flag = self.verbose;
-        let quiet_flag = self.quiet;
-        let create_cmd = || {
-            let mut cmd = Command::new(exe.as_path());
-            if let Some(emit_mode) = self.emit_mode {
-                let emit_mode = match emit_mode {
-                    EmitMode::Overwrite => "overwrite",
-                    EmitMode::NewFile => "new_file",
-                    EmitMode::StdOut => "stdout",
-                    EmitMode::Diff => "diff",
-                };
-                cmd.arg(format!("--emit={}", emit_mode));
-            }
-            if let Some(config_path) = config_path_opt.clone() {
-                cmd.arg(format!("--config-path={}", config_path.as_path().display()));
-            }
-            if verbose_flag {
-                cmd.arg("-v");
-            } else if quiet_flag {
-                cmd.arg("-q");
-            }
-            if !config_map.is_empty() {
-                let mut config_map_str_vec = vec![];
-                for (key, value) in &config_map {
-                    config_map_str_vec.push(format!("{}={}", key, value));
-                }
-                cmd.arg(format!("--config={}", config_map_str_vec.join(",")));
-            }
-            cmd
-        };
-        let to_cli_error = |e| CliError::IO(exe.display().to_string(), e);
+        let config = Self::load_config(self.config_path, &self.config)?;
 
         // Get the list of files to format
         let files_to_format = if let Some(files) = files_opt {
@@ -200,29 +171,33 @@ impl FmtCommand {
         }
 
         // Format all the files
+        let emit_mode = self.emit_mode.unwrap_or(EmitMode::Overwrite);
         for file in &files_to_format {
-            let mut cur_cmd = create_cmd();
-            cur_cmd.arg(format!("--file-path={}", file.display()));
-            let out = cur_cmd.output().map_err(to_cli_error)?;
-            if !out.status.success() {
-                return Err(CliError::UnexpectedError(format!(
-                    "Formatter exited with status {}: {}",
-                    out.status,
-                    String::from_utf8(out.stderr).unwrap_or_default()
-                )));
-            } else {
+            let source = fs::read_to_string(file)
+                .map_err(|err| CliError::IO(file.display().to_string(), err))?;
+            let formatted = format_source(&file.display().to_string(), &source, &config)
+                .map_err(|err| CliError::UnexpectedError(format!("{:#}", err)))?;
+            if !self.quiet && (self.verbose || formatted != source) {
                 eprintln!("Formatting file: {}", file.display());
-                match String::from_utf8(out.stdout) {
-                    Ok(output) => {
-                        eprint!("{}", output);
-                    },
-                    Err(err) => {
-                        return Err(CliError::UnexpectedError(format!(
-                            "Output generated by formatter is not valid utf8: {}",
-                            err
-                        )));
-                    },
-                }
+            }
+            match emit_mode {
+                EmitMode::Overwrite => {
+                    if formatted != source {
+                        fs::write(file, formatted)
+                            .map_err(|err| CliError::IO(file.display().to_string(), err))?;
+                    }
+                },
+                EmitMode::NewFile => {
+                    let new_file = file.with_extension("fmt.out");
+                    fs::write(&new_file, formatted)
+                        .map_err(|err| CliError::IO(new_file.display().to_string(), err))?;
+                },
+                EmitMode::StdOut => eprint!("{}", formatted),
+                EmitMode::Diff => {
+                    if formatted != source {
+                        eprint!("{}", format_diff_no_color(&source, &formatted));
+                    }
+                },
             }
         }
 
@@ -231,4 +206,28 @@ impl FmtCommand {
             files_to_format.len()
         ))
     }
+
+    /// Reads the configuration file, if any, and applies the options given on the command
+    /// line on top of it.
+    fn load_config(
+        config_path: Option<PathBuf>,
+        options: &BTreeMap<String, String>,
+    ) -> CliTypedResult<FormatConfig> {
+        let mut config = FormatConfig::default();
+        let config_path = match config_path {
+            Some(path) => Some(path),
+            None => FormatConfig::find_config_file(&dir_default_to_current(None)?),
+        };
+        if let Some(path) = config_path {
+            config.load(&path).map_err(|err| {
+                CliError::ConfigLoadError(path.display().to_string(), format!("{:#}", err))
+            })?;
+        }
+        for (key, value) in options {
+            config
+                .set(key, value)
+                .map_err(|err| CliError::CommandArgumentError(err.to_string()))?;
+        }
+        Ok(config)
+    }
 }
@@ -21,10 +21,12 @@ use crate::{
     governance::CompileScriptFunction,
     move_tool::{
         bytecode::{Decompile, Disassemble},
+        check_upgrade::CheckUpgrade,
         coverage::SummaryCoverage,
         fmt::Fmt,
         lint::LintPackage,
         manifest::{Dependency, ManifestNamedAddress, MovePackageManifest, PackageInfo},
+        unit_test_gas::AptosGasUnitTestFactory,
     },
     CliCommand, CliResult,
 };
@@ -64,8 +66,11 @@ use move_cli::{self, base::test::UnitTestResult};
 use move_command_line_common::{address::NumericalAddress, env::MOVE_HOME};
 use move_core_types::{identifier::Identifier, language_storage::ModuleId, u256::U256};
 use move_model::metadata::{CompilerVersion, LanguageVersion};
-use move_package::{source_package::layout::SourcePackageLayout, BuildConfig, CompilerConfig};
-use move_unit_test::UnitTestingConfig;
+use move_package::{
+    resolution::lock_file::LockFile, source_package::layout::SourcePackageLayout, BuildConfig,
+    CompilerConfig,
+};
+use move_unit_test::{gas_snapshot::GAS_SNAPSHOT_FILE_NAME, UnitTestingConfig};
 pub use package_hooks::*;
 use rand::SeedableRng;
 use serde::{Deserialize, Serialize};
@@ -81,13 +86,16 @@ use tokio::task;
 use url::Url;
 pub mod aptos_debug_natives;
 mod bytecode;
+mod check_upgrade;
 pub mod coverage;
 mod fmt;
 mod lint;
 mod manifest;
 pub mod package_hooks;
 mod show;
+mod sim;
 pub mod stored_package;
+pub mod unit_test_gas;
 
 const HELLO_BLOCKCHAIN_EXAMPLE: &str = include_str!(
     "../../../../aptos-move/move-examples/hello_blockchain/sources/hello_blockchain.move"
@@ -101,6 +109,7 @@ const HELLO_BLOCKCHAIN_EXAMPLE: &str = include_str!(
 #[derive(Subcommand)]
 pub enum MoveTool {
     BuildPublishPayload(BuildPublishPayload),
+    CheckUpgrade(CheckUpgrade),
     Clean(CleanPackage),
     ClearStagingArea(ClearStagingArea),
     #[clap(alias = "build")]
@@ -130,7 +139,10 @@ pub enum MoveTool {
     Simulate(Simulate),
     #[clap(subcommand, hide = true)]
     Show(show::ShowTool),
+    #[clap(subcommand)]
+    Sim(sim::Sim),
     Test(TestPackage),
+    UpdateDeps(UpdateDeps),
     VerifyPackage(VerifyPackage),
     View(ViewFunction),
     Replay(Replay),
@@ -141,6 +153,7 @@ impl MoveTool {
     pub async fn execute(self) -> CliResult {
         match self {
             MoveTool::BuildPublishPayload(tool) => tool.execute_serialized().await,
+            MoveTool::CheckUpgrade(tool) => tool.execute_serialized().await,
             MoveTool::Clean(tool) => tool.execute_serialized().await,
             MoveTool::ClearStagingArea(tool) => tool.execute_serialized().await,
             MoveTool::Compile(tool) => tool.execute_serialized().await,
@@ -167,7 +180,9 @@ impl MoveTool {
             MoveTool::RunScript(tool) => tool.execute_serialized().await,
             MoveTool::Simulate(tool) => tool.execute_serialized().await,
             MoveTool::Show(tool) => tool.execute_serialized().await,
+            MoveTool::Sim(tool) => tool.execute().await,
             MoveTool::Test(tool) => tool.execute_serialized().await,
+            MoveTool::UpdateDeps(tool) => tool.execute_serialized().await,
             MoveTool::VerifyPackage(tool) => tool.execute_serialized().await,
             MoveTool::View(tool) => tool.execute_serialized().await,
             MoveTool::Replay(tool) => tool.execute_serialized().await,
@@ -538,6 +553,60 @@ pub struct TestPackage {
     /// Dump storage state on failure.
     #[clap(long = "dump")]
     pub dump_state: bool,
+
+    /// Wait for a Debug Adapter Protocol client (e.g., an editor) to connect on this
+    /// address (e.g., `127.0.0.1:4711`) and debug the tests at the source level.
+    ///
+    /// Tests run on a single thread while debugging.
+    #[clap(long)]
+    pub debug_adapter_address: Option<String>,
+
+    /// The number of inputs to run each test with generated parameters with
+    ///
+    /// Parameters of a `#[test]` function marked with `#[fuzz]` which are not assigned in the
+    /// attribute, and are of a boolean, integer, address, signer or vector type, get generated
+    /// values.
+    #[clap(long, default_value_t = move_unit_test::property::DEFAULT_ITERATIONS)]
+    pub fuzz_iterations: u64,
+
+    /// The seed for the inputs of tests with generated parameters
+    ///
+    /// The seed is reported when a test fails. The default seed makes runs reproducible.
+    #[clap(long, default_value_t = move_unit_test::property::DEFAULT_SEED)]
+    pub fuzz_seed: u64,
+
+    /// Steer the inputs of tests with generated parameters by instruction coverage
+    ///
+    /// Inputs which cover new control flow in the VM are mutated to derive further inputs.
+    #[clap(long)]
+    pub coverage_guided: bool,
+
+    /// Meter tests with the Aptos gas schedule instead of counting instructions
+    ///
+    /// The gas used by a test is the execution and IO gas of a transaction running the same
+    /// code, without storage fees. Tests with a `#[max_gas = N]` attribute fail if they use
+    /// more gas than `N`, and fail without this option since their gas is not metered. The bound
+    /// applies to the whole test; bounding the gas of a region of code in a test is not
+    /// supported.
+    #[clap(long)]
+    pub gas: bool,
+
+    /// Write the gas used by each passing test to the `.gas-snapshot` file of the package
+    ///
+    /// Changes to the gas used since the file was last written are reported. Implies `--gas`.
+    #[clap(long)]
+    pub gas_snapshot: bool,
+
+    /// Check the gas used by each test against the `.gas-snapshot` file of the package
+    ///
+    /// Fails if a test uses more gas than recorded, beyond `--gas-tolerance`. The file is
+    /// not updated. Implies `--gas`.
+    #[clap(long, conflicts_with = "gas_snapshot")]
+    pub gas_snapshot_check: bool,
+
+    /// Percentage by which a test may exceed the gas recorded in the snapshot
+    #[clap(long, default_value_t = 0.0)]
+    pub gas_tolerance: f64,
 }
 
 pub(crate) fn fix_bytecode_version(
@@ -570,6 +639,7 @@ impl CliCommand<&'static str> for TestPackage {
             full_model_generation: !self.move_options.skip_checks_on_test_code,
             install_dir: self.move_options.output_dir.clone(),
             skip_fetch_latest_git_deps: self.move_options.skip_fetch_latest_git_deps,
+            locked: self.move_options.locked,
             compiler_config: CompilerConfig {
                 known_attributes: known_attributes.clone(),
                 skip_attribute_checks: self.move_options.skip_attribute_checks,
@@ -591,38 +661,67 @@ impl CliCommand<&'static str> for TestPackage {
         };
 
         let path = self.move_options.get_package_path()?;
-        let result = move_cli::base::test::run_move_unit_tests(
-            path.as_path(),
-            config.clone(),
-            UnitTestingConfig {
-                filter: self.filter.clone(),
-                report_stacktrace_on_abort: true,
-                report_storage_on_error: self.dump_state,
-                ignore_compile_warnings: self.ignore_compile_warnings,
-                named_address_values: self
-                    .move_options
-                    .named_addresses
-                    .iter()
-                    .map(|(name, addr_wrap)| {
-                        (
-                            name.clone(),
-                            NumericalAddress::from_account_address(addr_wrap.account_address),
-                        )
-                    })
-                    .collect(),
-                ..UnitTestingConfig::default()
-            },
-            // TODO(Gas): we may want to switch to non-zero costs in the future
-            aptos_debug_natives::aptos_debug_natives(
-                NativeGasParameters::zeros(),
-                MiscGasParameters::zeros(),
-            ),
-            aptos_test_feature_flags_genesis(),
-            None,
-            None,
-            self.compute_coverage,
-            &mut std::io::stdout(),
-        )
+        let use_aptos_gas = self.gas || self.gas_snapshot || self.gas_snapshot_check;
+        let unit_test_config = UnitTestingConfig {
+            filter: self.filter.clone(),
+            report_stacktrace_on_abort: true,
+            report_storage_on_error: self.dump_state,
+            ignore_compile_warnings: self.ignore_compile_warnings,
+            debug_adapter_address: self.debug_adapter_address.clone(),
+            fuzz_iterations: self.fuzz_iterations,
+            fuzz_seed: self.fuzz_seed,
+            coverage_guided: self.coverage_guided,
+            gas_snapshot: (self.gas_snapshot || self.gas_snapshot_check)
+                .then(|| path.join(GAS_SNAPSHOT_FILE_NAME)),
+            check_gas_snapshot: self.gas_snapshot_check,
+            gas_tolerance: self.gas_tolerance,
+            meters_gas: use_aptos_gas,
+            named_address_values: self
+                .move_options
+                .named_addresses
+                .iter()
+                .map(|(name, addr_wrap)| {
+                    (
+                        name.clone(),
+                        NumericalAddress::from_account_address(addr_wrap.account_address),
+                    )
+                })
+                .collect(),
+            ..UnitTestingConfig::default()
+        };
+        let result = if use_aptos_gas {
+            let factory = AptosGasUnitTestFactory::new();
+            let natives = aptos_debug_natives::aptos_debug_natives(
+                factory.gas_params().natives.clone(),
+                factory.gas_params().vm.misc.clone(),
+            );
+            move_cli::base::test::run_move_unit_tests_with_factory(
+                path.as_path(),
+                config.clone(),
+                unit_test_config,
+                natives,
+                aptos_test_feature_flags_genesis(),
+                self.compute_coverage,
+                &mut std::io::stdout(),
+                factory,
+            )
+        } else {
+            move_cli::base::test::run_move_unit_tests(
+                path.as_path(),
+                config.clone(),
+                unit_test_config,
+                // TODO(Gas): we may want to switch to non-zero costs in the future
+                aptos_debug_natives::aptos_debug_natives(
+                    NativeGasParameters::zeros(),
+                    MiscGasParameters::zeros(),
+                ),
+                aptos_test_feature_flags_genesis(),
+                None,
+                None,
+                self.compute_coverage,
+                &mut std::io::stdout(),
+            )
+        }
         .map_err(|err| CliError::UnexpectedError(format!("Failed to run tests: {:#}", err)))?;
 
         // Print coverage summary if --coverage is set
@@ -732,6 +831,7 @@ impl CliCommand<&'static str> for DocumentPackage {
             override_std: move_options.override_std.clone(),
             docgen_options: Some(docgen_options),
             skip_fetch_latest_git_deps: move_options.skip_fetch_latest_git_deps,
+            locked: move_options.locked,
             bytecode_version: fix_bytecode_version(
                 move_options.bytecode_version,
                 move_options.language_version,
@@ -908,6 +1008,7 @@ impl IncludedArtifacts {
     ) -> CliTypedResult<BuildOptions> {
         let dev = move_options.dev;
         let skip_fetch_latest_git_deps = move_options.skip_fetch_latest_git_deps;
+        let locked = move_options.locked;
         let named_addresses = move_options.named_addresses();
         let override_std = move_options.override_std.clone();
         let bytecode_version =
@@ -930,6 +1031,7 @@ impl IncludedArtifacts {
             named_addresses,
             override_std,
             skip_fetch_latest_git_deps,
+            locked,
             bytecode_version,
             compiler_version,
             language_version,
@@ -2121,6 +2223,47 @@ impl CliCommand<&'static str> for CleanPackage {
     }
 }
 
+/// Updates the git and on-chain dependencies of a package to their latest versions
+///
+/// The versions the dependencies resolve to are pinned in the package's Move.lock, including
+/// the ones of dev dependencies.  Builds use the pinned versions until they are updated again.
+#[derive(Parser)]
+pub struct UpdateDeps {
+    #[clap(flatten)]
+    pub(crate) move_options: MovePackageOptions,
+}
+
+#[async_trait]
+impl CliCommand<Vec<String>> for UpdateDeps {
+    fn command_name(&self) -> &'static str {
+        "UpdateDeps"
+    }
+
+    async fn execute(self) -> CliTypedResult<Vec<String>> {
+        let package_path = self.move_options.get_package_path()?;
+        let config = BuildConfig {
+            dev_mode: true,
+            additional_named_addresses: self.move_options.named_addresses(),
+            override_std: self.move_options.override_std.clone(),
+            update_deps: true,
+            ..BuildConfig::default()
+        };
+        let resolved_graph = config
+            .resolution_graph_for_package(&package_path, &mut std::io::stderr())
+            .map_err(|err| {
+                CliError::UnexpectedError(format!("Failed to update dependencies: {:#}", err))
+            })?;
+        let lock_file = LockFile::read(&resolved_graph.root_package_path)
+            .map_err(|err| CliError::UnexpectedError(format!("{:#}", err)))?
+            .unwrap_or_default();
+        Ok(lock_file
+            .packages
+            .iter()
+            .map(|package| format!("{}: {}", package.name, package))
+            .collect())
+    }
+}
+
 /// Run a Move function
 #[derive(Parser)]
 pub struct RunFunction {
@@ -2275,6 +2418,17 @@ pub struct Replay {
     pub(crate) node_api_key: Option<String>,
 }
 
+impl ReplayNetworkSelection {
+    pub(crate) fn rest_endpoint(&self) -> &str {
+        match self {
+            Self::Mainnet => "https://fullnode.mainnet.aptoslabs.com",
+            Self::Testnet => "https://fullnode.testnet.aptoslabs.com",
+            Self::Devnet => "https://fullnode.devnet.aptoslabs.com",
+            Self::RestEndpoint(url) => url,
+        }
+    }
+}
+
 impl FromStr for ReplayNetworkSelection {
     type Err = CliError;
 
@@ -2295,20 +2449,13 @@ impl CliCommand<TransactionSummary> for Replay {
     }
 
     async fn execute(self) -> CliTypedResult<TransactionSummary> {
-        use ReplayNetworkSelection::*;
-
         if self.profile_gas && self.benchmark {
             return Err(CliError::UnexpectedError(
                 "Cannot perform benchmarking and gas profiling at the same time.".to_string(),
             ));
         }
 
-        let rest_endpoint = match &self.network {
-            Mainnet => "https://fullnode.mainnet.aptoslabs.com",
-            Testnet => "https://fullnode.testnet.aptoslabs.com",
-            Devnet => "https://fullnode.devnet.aptoslabs.com",
-            RestEndpoint(url) => url,
-        };
+        let rest_endpoint = self.network.rest_endpoint();
 
         // Build the client
         let client = Client::builder(AptosBaseUrl::Custom(
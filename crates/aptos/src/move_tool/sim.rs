@@ -0,0 +1,288 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::types::{CliCommand, CliError, CliResult, CliTypedResult, US_IN_SECS},
+    move_tool::ReplayNetworkSelection,
+};
+use aptos_rest_client::{AptosBaseUrl, Client};
+use aptos_transaction_simulation_session::Session;
+use async_trait::async_trait;
+use clap::{Parser, Subcommand};
+use move_core_types::{account_address::AccountAddress, language_storage::StructTag};
+use std::{path::PathBuf, time::Duration};
+use url::Url;
+
+/// Local simulation sessions
+///
+/// A session starts from a local genesis or from a fork of a network, and applies transactions,
+/// time advances and resource edits on top of it. Nothing is ever submitted to the network.
+/// Transactions are run in a session by passing `--session <PATH>` to the commands that submit
+/// transactions, e.g., `aptos move run` or `aptos move publish`, and `--impersonate <ADDRESS>`
+/// runs them as any account without its key.
+#[derive(Subcommand)]
+pub enum Sim {
+    Init(InitSession),
+    AdvanceTime(AdvanceTime),
+    WarpTime(WarpTime),
+    SetBalance(SetBalance),
+    SetResource(SetResource),
+    RemoveResource(RemoveResource),
+    ViewResource(ViewResource),
+}
+
+impl Sim {
+    pub async fn execute(self) -> CliResult {
+        match self {
+            Sim::Init(tool) => tool.execute_serialized_success().await,
+            Sim::AdvanceTime(tool) => tool.execute_serialized().await,
+            Sim::WarpTime(tool) => tool.execute_serialized_success().await,
+            Sim::SetBalance(tool) => tool.execute_serialized_success().await,
+            Sim::SetResource(tool) => tool.execute_serialized_success().await,
+            Sim::RemoveResource(tool) => tool.execute_serialized_success().await,
+            Sim::ViewResource(tool) => tool.execute_serialized().await,
+        }
+    }
+}
+
+/// Create a new simulation session
+#[derive(Parser)]
+pub struct InitSession {
+    /// Directory of the session, must not contain a session already
+    #[clap(long, value_parser)]
+    path: PathBuf,
+
+    /// The network to fork, the session starts from a local genesis if not set
+    ///
+    /// Possible values:
+    ///     mainnet, testnet, devnet, <REST_ENDPOINT_URL>
+    #[clap(long)]
+    network: Option<ReplayNetworkSelection>,
+
+    /// The version of the network to fork, defaults to the latest version
+    ///
+    /// The session starts from the state right after the transaction at this version.
+    #[clap(long, requires = "network")]
+    network_version: Option<u64>,
+
+    /// Key to use for ratelimiting purposes with the node API. This value will be used
+    /// as `Authorization: Bearer <key>`
+    #[clap(long, requires = "network")]
+    node_api_key: Option<String>,
+}
+
+#[async_trait]
+impl CliCommand<()> for InitSession {
+    fn command_name(&self) -> &'static str {
+        "InitSimulationSession"
+    }
+
+    async fn execute(self) -> CliTypedResult<()> {
+        let network = match self.network {
+            Some(network) => network,
+            None => {
+                Session::init(&self.path)?;
+                return Ok(());
+            },
+        };
+
+        let rest_endpoint = network.rest_endpoint();
+        let node_url = Url::parse(rest_endpoint)
+            .map_err(|_err| CliError::UnableToParse("url", rest_endpoint.to_string()))?;
+        let network_version = match self.network_version {
+            Some(network_version) => network_version,
+            None => {
+                let client = Client::builder(AptosBaseUrl::Custom(node_url.clone()));
+                let client = match &self.node_api_key {
+                    Some(api_key) => client.api_key(api_key)?.build(),
+                    None => client.build(),
+                };
+                client.get_ledger_information().await?.into_inner().version
+            },
+        };
+
+        Session::init_with_remote_state(&self.path, node_url, network_version, self.node_api_key)?;
+        Ok(())
+    }
+}
+
+/// Advance the on-chain time of a session
+///
+/// Returns the new on-chain time in microseconds.
+#[derive(Parser)]
+pub struct AdvanceTime {
+    /// Directory of the session
+    #[clap(long, value_parser)]
+    session: PathBuf,
+
+    /// Number of seconds to advance the time by
+    #[clap(long)]
+    secs: u64,
+}
+
+#[async_trait]
+impl CliCommand<u64> for AdvanceTime {
+    fn command_name(&self) -> &'static str {
+        "AdvanceSimulationTime"
+    }
+
+    async fn execute(self) -> CliTypedResult<u64> {
+        let mut session = Session::load(&self.session)?;
+        Ok(session.advance_time(Duration::from_secs(self.secs))?)
+    }
+}
+
+/// Set the on-chain time of a session, which may be in the past
+#[derive(Parser)]
+pub struct WarpTime {
+    /// Directory of the session
+    #[clap(long, value_parser)]
+    session: PathBuf,
+
+    /// The new on-chain time, in seconds since the Unix epoch
+    #[clap(long)]
+    timestamp_secs: u64,
+}
+
+#[async_trait]
+impl CliCommand<()> for WarpTime {
+    fn command_name(&self) -> &'static str {
+        "WarpSimulationTime"
+    }
+
+    async fn execute(self) -> CliTypedResult<()> {
+        let timestamp_micros = self
+            .timestamp_secs
+            .checked_mul(US_IN_SECS)
+            .ok_or_else(|| CliError::CommandArgumentError("Timestamp is too large".to_string()))?;
+        let mut session = Session::load(&self.session)?;
+        Ok(session.warp_time(timestamp_micros)?)
+    }
+}
+
+/// Set the fungible asset balance of an account in a session
+///
+/// The primary fungible store of the account is created if it does not exist. The supply of the
+/// fungible asset is not updated.
+#[derive(Parser)]
+pub struct SetBalance {
+    /// Directory of the session
+    #[clap(long, value_parser)]
+    session: PathBuf,
+
+    /// Address of the account
+    #[clap(long, value_parser = crate::common::types::load_account_arg)]
+    account: AccountAddress,
+
+    /// The new balance, in the smallest unit of the fungible asset (e.g., Octas for APT)
+    #[clap(long)]
+    amount: u64,
+
+    /// Address of the metadata object of the fungible asset, defaults to APT
+    #[clap(long, value_parser = crate::common::types::load_account_arg, default_value = "0xa")]
+    metadata_address: AccountAddress,
+}
+
+#[async_trait]
+impl CliCommand<()> for SetBalance {
+    fn command_name(&self) -> &'static str {
+        "SetSimulationBalance"
+    }
+
+    async fn execute(self) -> CliTypedResult<()> {
+        let mut session = Session::load(&self.session)?;
+        Ok(session.set_fungible_balance(self.account, self.metadata_address, self.amount)?)
+    }
+}
+
+/// Overwrite or create a resource in a session
+#[derive(Parser)]
+pub struct SetResource {
+    /// Directory of the session
+    #[clap(long, value_parser)]
+    session: PathBuf,
+
+    /// Address of the account holding the resource
+    #[clap(long, value_parser = crate::common::types::load_account_arg)]
+    account: AccountAddress,
+
+    /// Type of the resource, e.g., `0x1::account::Account`
+    #[clap(long)]
+    resource_type: StructTag,
+
+    /// Hex encoded BCS value of the resource
+    #[clap(long)]
+    bcs_hex: String,
+}
+
+#[async_trait]
+impl CliCommand<()> for SetResource {
+    fn command_name(&self) -> &'static str {
+        "SetSimulationResource"
+    }
+
+    async fn execute(self) -> CliTypedResult<()> {
+        let blob = hex::decode(self.bcs_hex.trim_start_matches("0x"))
+            .map_err(|err| CliError::UnableToParse("bcs-hex", err.to_string()))?;
+        let mut session = Session::load(&self.session)?;
+        session.set_resource(self.account, &self.resource_type, blob)?;
+        Ok(())
+    }
+}
+
+/// Remove a resource from a session
+#[derive(Parser)]
+pub struct RemoveResource {
+    /// Directory of the session
+    #[clap(long, value_parser)]
+    session: PathBuf,
+
+    /// Address of the account holding the resource
+    #[clap(long, value_parser = crate::common::types::load_account_arg)]
+    account: AccountAddress,
+
+    /// Type of the resource, e.g., `0x1::account::Account`
+    #[clap(long)]
+    resource_type: StructTag,
+}
+
+#[async_trait]
+impl CliCommand<()> for RemoveResource {
+    fn command_name(&self) -> &'static str {
+        "RemoveSimulationResource"
+    }
+
+    async fn execute(self) -> CliTypedResult<()> {
+        let mut session = Session::load(&self.session)?;
+        session.remove_resource(self.account, &self.resource_type)?;
+        Ok(())
+    }
+}
+
+/// View a resource in a session
+#[derive(Parser)]
+pub struct ViewResource {
+    /// Directory of the session
+    #[clap(long, value_parser)]
+    session: PathBuf,
+
+    /// Address of the account holding the resource
+    #[clap(long, value_parser = crate::common::types::load_account_arg)]
+    account: AccountAddress,
+
+    /// Type of the resource, e.g., `0x1::account::Account`
+    #[clap(long)]
+    resource_type: StructTag,
+}
+
+#[async_trait]
+impl CliCommand<Option<serde_json::Value>> for ViewResource {
+    fn command_name(&self) -> &'static str {
+        "ViewSimulationResource"
+    }
+
+    async fn execute(self) -> CliTypedResult<Option<serde_json::Value>> {
+        let session = Session::load(&self.session)?;
+        Ok(session.view_resource(self.account, &self.resource_type)?)
+    }
+}
@@ -0,0 +1,64 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_gas_meter::{AptosGasMeter, StandardGasAlgebra, StandardGasMeter};
+use aptos_gas_schedule::{AptosGasParameters, LATEST_GAS_FEATURE_VERSION};
+use aptos_vm_types::{resolver::NoopBlockSynchronizationKillSwitch, storage::StorageGasParameters};
+use move_core_types::effects::ChangeSet;
+use move_unit_test::test_reporter::{TestRunInfo, UnitTestFactory};
+use move_vm_runtime::native_extensions::NativeContextExtensions;
+
+/// Meters unit tests with the latest Aptos gas schedule, so the gas reported for a test is the
+/// execution and IO gas a transaction running the same code would be charged. Storage fees are
+/// not included.
+pub struct AptosGasUnitTestFactory {
+    gas_params: AptosGasParameters,
+    storage_gas_params: StorageGasParameters,
+}
+
+impl AptosGasUnitTestFactory {
+    pub fn new() -> Self {
+        Self {
+            gas_params: AptosGasParameters::initial(),
+            storage_gas_params: StorageGasParameters::latest(),
+        }
+    }
+
+    pub fn gas_params(&self) -> &AptosGasParameters {
+        &self.gas_params
+    }
+}
+
+impl Default for AptosGasUnitTestFactory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UnitTestFactory for AptosGasUnitTestFactory {
+    type GasMeter =
+        StandardGasMeter<StandardGasAlgebra<'static, NoopBlockSynchronizationKillSwitch>>;
+
+    fn new_gas_meter(&self) -> Self::GasMeter {
+        // A test can use as much gas as the largest transaction
+        StandardGasMeter::new(StandardGasAlgebra::new(
+            LATEST_GAS_FEATURE_VERSION,
+            self.gas_params.vm.clone(),
+            self.storage_gas_params.clone(),
+            false,
+            self.gas_params.vm.txn.maximum_number_of_gas_units,
+            &NoopBlockSynchronizationKillSwitch {},
+        ))
+    }
+
+    fn finalize_test_run_info(
+        &self,
+        _: &ChangeSet,
+        _: &mut NativeContextExtensions,
+        gas_meter: Self::GasMeter,
+        mut test_run_info: TestRunInfo,
+    ) -> TestRunInfo {
+        test_run_info.gas_used = (gas_meter.execution_gas_used() + gas_meter.io_gas_used()).into();
+        test_run_info
+    }
+}
@@ -858,6 +858,14 @@ impl CliTestFramework {
             ignore_compile_warnings: false,
             compute_coverage: false,
             dump_state: false,
+            debug_adapter_address: None,
+            fuzz_iterations: move_unit_test::property::DEFAULT_ITERATIONS,
+            fuzz_seed: move_unit_test::property::DEFAULT_SEED,
+            coverage_guided: false,
+            gas: false,
+            gas_snapshot: false,
+            gas_snapshot_check: false,
+            gas_tolerance: 0.0,
         }
         .execute()
         .await
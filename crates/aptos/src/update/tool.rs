@@ -5,8 +5,7 @@ use super::{aptos::AptosUpdateTool, revela::RevelaUpdateTool};
 use crate::{
     common::types::{CliCommand, CliResult},
     update::{
-        move_mutation_test::MutationTestUpdaterTool, movefmt::FormatterUpdateTool,
-        prover_dependencies::ProverDependencyInstaller,
+        move_mutation_test::MutationTestUpdaterTool, prover_dependencies::ProverDependencyInstaller,
     },
 };
 use clap::Subcommand;
@@ -16,7 +15,6 @@ use clap::Subcommand;
 pub enum UpdateTool {
     Aptos(AptosUpdateTool),
     Revela(RevelaUpdateTool),
-    Movefmt(FormatterUpdateTool),
     MoveMutationTest(MutationTestUpdaterTool),
     ProverDependencies(ProverDependencyInstaller),
 }
@@ -26,7 +24,6 @@ impl UpdateTool {
         match self {
             UpdateTool::Aptos(tool) => tool.execute_serialized().await,
             UpdateTool::Revela(tool) => tool.execute_serialized().await,
-            UpdateTool::Movefmt(tool) => tool.execute_serialized().await,
             UpdateTool::MoveMutationTest(tool) => tool.execute_serialized().await,
             UpdateTool::ProverDependencies(tool) => tool.execute_serialized().await,
         }
@@ -2,6 +2,7 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use aptos_block_executor::conflict_report::enable_conflict_reports;
 use aptos_block_partitioner::{
     pre_partition::{
         connected_component::config::ConnectedComponentPartitionerConfig,
@@ -27,7 +28,7 @@ use aptos_executor_benchmark::{
     pipeline::PipelineConfig,
     BenchmarkWorkload,
 };
-use aptos_executor_service::remote_executor_client;
+use aptos_executor_service::remote_executor_client::{self, RemoteExecutorConfig};
 use aptos_experimental_ptx_executor::PtxBlockExecutor;
 #[cfg(target_os = "linux")]
 use aptos_experimental_runtimes::thread_manager::{ThreadConfigStrategy, ThreadManagerBuilder};
@@ -44,7 +45,7 @@ use once_cell::sync::Lazy;
 use std::{
     net::SocketAddr,
     path::PathBuf,
-    time::{SystemTime, UNIX_EPOCH},
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 #[cfg(unix)]
@@ -147,6 +148,12 @@ pub struct PipelineOpt {
     /// Number of worker threads signature verification will use.
     #[clap(long, default_value = "8")]
     num_sig_verify_threads: usize,
+    /// Speculatively execute each block while its parent is being executed, and reuse the output
+    /// if the parent didn't change any of the state it read.
+    /// Useful to measure the gain of cross-block pipelining for workloads with low cross-block
+    /// contention.
+    #[clap(long)]
+    speculate_next_block: bool,
     /// Sharding configuration.
     #[clap(flatten)]
     sharding_opt: ShardingOpt,
@@ -165,6 +172,7 @@ impl PipelineOpt {
             num_generator_workers: self.num_generator_workers,
             partitioner_config: self.sharding_opt.partitioner_config(),
             num_sig_verify_threads: self.num_sig_verify_threads,
+            speculate_next_block: self.speculate_next_block,
             print_transactions: false,
         }
     }
@@ -183,6 +191,12 @@ struct ShardingOpt {
     remote_executor_addresses: Option<Vec<SocketAddr>>,
     #[clap(long)]
     coordinator_address: Option<SocketAddr>,
+    /// Maximum time to wait for the remote shards to execute a block.
+    #[clap(long, default_value = "60")]
+    remote_execution_timeout_secs: u64,
+    /// Fail instead of executing a block locally when the remote shards fail to execute it.
+    #[clap(long)]
+    disable_remote_execution_fallback: bool,
     #[clap(long, default_value = "4")]
     max_partitioning_rounds: usize,
     #[clap(long, default_value = "0.90")]
@@ -323,6 +337,11 @@ struct Opt {
 
     #[clap(long)]
     skip_paranoid_checks: bool,
+
+    /// Print a report of the conflicts detected during parallel execution of every block
+    /// (conflicting state keys, re-executed transactions and dependencies).
+    #[clap(long)]
+    print_conflict_reports: bool,
 }
 
 impl Opt {
@@ -616,6 +635,16 @@ fn main() {
         remote_executor_client::set_coordinator_address(
             opt.pipeline_opt.sharding_opt.coordinator_address.unwrap(),
         );
+        remote_executor_client::set_remote_executor_config(RemoteExecutorConfig {
+            execution_timeout: Duration::from_secs(
+                opt.pipeline_opt.sharding_opt.remote_execution_timeout_secs,
+            ),
+            fallback_to_local: !opt
+                .pipeline_opt
+                .sharding_opt
+                .disable_remote_execution_fallback,
+            ..RemoteExecutorConfig::default()
+        });
         // it does not matter because shards are on remote node, but for sake of correctness lets
         // set it
         execution_threads_per_shard = execution_threads;
@@ -624,6 +653,9 @@ fn main() {
     if opt.skip_paranoid_checks {
         set_paranoid_type_checks(false);
     }
+    if opt.print_conflict_reports {
+        enable_conflict_reports();
+    }
     AptosVM::set_num_shards_once(execution_shards);
     AptosVM::set_concurrency_level_once(execution_threads_per_shard);
     NativeConfig::set_concurrency_level_once(execution_threads_per_shard);
@@ -48,6 +48,8 @@ pub struct PipelineConfig {
     pub partitioner_config: PartitionerV2Config,
     #[derivative(Default(value = "8"))]
     pub num_sig_verify_threads: usize,
+    /// Speculatively execute the next block (if already prepared) while executing the current one.
+    pub speculate_next_block: bool,
 
     pub print_transactions: bool,
 }
@@ -148,6 +150,7 @@ where
         );
 
         let print_transactions = config.print_transactions;
+        let speculate_next_block = config.speculate_next_block;
         let staged_result = Arc::new(Mutex::new(Vec::new()));
         let staged_result_clone = staged_result.clone();
 
@@ -186,7 +189,15 @@ where
                 let mut stage_executed = 0;
                 let mut stage_txn_occurences: HashMap<String, usize> = HashMap::new();
 
-                while let Ok(msg) = executable_block_receiver.recv() {
+                let mut next_msg: Option<ExecuteBlockMessage> = None;
+                loop {
+                    let msg = match next_msg.take() {
+                        Some(msg) => msg,
+                        None => match executable_block_receiver.recv() {
+                            Ok(msg) => msg,
+                            Err(_) => break,
+                        },
+                    };
                     let ExecuteBlockMessage {
                         current_block_start_time,
                         partition_time,
@@ -213,7 +224,16 @@ where
                     info!("Received block of size {:?} to execute", block_size);
                     executed += block_size;
                     stage_executed += block_size;
-                    exe.execute_block(current_block_start_time, partition_time, block, stage_index);
+                    if speculate_next_block {
+                        next_msg = executable_block_receiver.try_recv().ok();
+                    }
+                    exe.execute_block(
+                        current_block_start_time,
+                        partition_time,
+                        block,
+                        next_msg.as_ref().map(|msg| &msg.block),
+                        stage_index,
+                    );
                     info!("Finished executing block");
 
                     // Empty blocks indicate the end of a stage.
@@ -3,12 +3,14 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{metrics::TIMER, pipeline::LedgerUpdateMessage};
+use aptos_block_executor::conflict_report::take_conflict_reports;
 use aptos_crypto::hash::HashValue;
 use aptos_executor::block_executor::BlockExecutor;
 use aptos_executor_types::BlockExecutorTrait;
 use aptos_logger::info;
 use aptos_types::block_executor::{
-    config::BlockExecutorConfigFromOnchain, partitioner::ExecutableBlock,
+    config::BlockExecutorConfigFromOnchain,
+    partitioner::{ExecutableBlock, ExecutableTransactions},
 };
 use aptos_vm::VMBlockExecutor;
 use std::{
@@ -45,11 +47,14 @@ where
         }
     }
 
+    /// Executes the block. If the next block is given, it is concurrently executed speculatively,
+    /// on top of the parent of the block.
     pub fn execute_block(
         &mut self,
         current_block_start_time: Instant,
         partition_time: Duration,
         executable_block: ExecutableBlock,
+        next_block: Option<&ExecutableBlock>,
         stage: usize,
     ) {
         let execution_start_time = Instant::now();
@@ -64,13 +69,33 @@ where
         let num_input_txns = executable_block.transactions.num_transactions();
         {
             let _timer = TIMER.with_label_values(&["execute"]).start_timer();
-            self.executor
-                .execute_and_update_state(
-                    executable_block,
-                    self.parent_block_id,
-                    BENCHMARKS_BLOCK_EXECUTOR_ONCHAIN_CONFIG,
-                )
-                .unwrap();
+            let executor = &self.executor;
+            let parent_block_id = self.parent_block_id;
+            std::thread::scope(|s| {
+                if let Some(next_block) = next_block.and_then(clone_unsharded_block) {
+                    s.spawn(move || {
+                        executor
+                            .execute_speculatively(
+                                next_block,
+                                block_id,
+                                parent_block_id,
+                                BENCHMARKS_BLOCK_EXECUTOR_ONCHAIN_CONFIG,
+                            )
+                            .unwrap()
+                    });
+                }
+                executor
+                    .execute_and_update_state(
+                        executable_block,
+                        parent_block_id,
+                        BENCHMARKS_BLOCK_EXECUTOR_ONCHAIN_CONFIG,
+                    )
+                    .unwrap();
+            });
+        }
+        // Only collected if enabled.
+        for report in take_conflict_reports() {
+            println!("Conflict report for block {}:\n{}", block_id, report);
         }
         let msg = LedgerUpdateMessage {
             current_block_start_time,
@@ -87,3 +112,15 @@ where
         self.num_blocks_processed += 1;
     }
 }
+
+/// Speculative execution is only supported for unsharded blocks.
+fn clone_unsharded_block(block: &ExecutableBlock) -> Option<ExecutableBlock> {
+    match &block.transactions {
+        ExecutableTransactions::Unsharded(txns) => Some(ExecutableBlock::new(
+            block.block_id,
+            ExecutableTransactions::Unsharded(txns.clone()),
+            block.auxiliary_info.clone(),
+        )),
+        ExecutableTransactions::Sharded(_) => None,
+    }
+}
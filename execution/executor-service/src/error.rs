@@ -2,6 +2,7 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use aptos_types::block_executor::partitioner::ShardId;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
@@ -12,6 +13,25 @@ pub enum Error {
     InternalError(String),
     #[error("Serialization error: {0}")]
     SerializationError(String),
+    #[error("Shard {0} is unhealthy: {1}")]
+    ShardUnhealthy(ShardId, String),
+    #[error("Timed out waiting for the result of shard {0}")]
+    ShardTimeout(ShardId),
+    #[error("Shard {0} disconnected")]
+    ShardDisconnected(ShardId),
+}
+
+impl Error {
+    /// A short name of the error, used as a metric label.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::InternalError(_) => "internal_error",
+            Self::SerializationError(_) => "serialization_error",
+            Self::ShardUnhealthy(..) => "shard_unhealthy",
+            Self::ShardTimeout(_) => "shard_timeout",
+            Self::ShardDisconnected(_) => "shard_disconnected",
+        }
+    }
 }
 
 impl From<bcs::Error> for Error {
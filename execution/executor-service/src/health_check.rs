@@ -0,0 +1,249 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Health checks of the remote executor shards. The coordinator periodically pings every shard,
+//! and a shard responds with the request it is currently executing (if any). A shard is ready to
+//! execute a block if it responded recently and is not stuck executing a block the coordinator
+//! gave up on (e.g., a shard that waits for cross shard messages from a crashed shard).
+
+use crate::{
+    error::Error, metrics::REMOTE_EXECUTOR_SHARD_HEALTHY,
+    remote_cordinator_client::RemoteCoordinatorClient, ShardHealthRequest, ShardHealthResponse,
+};
+use aptos_infallible::Mutex;
+use aptos_logger::{info, warn};
+use aptos_secure_net::network_controller::{Message, NetworkController};
+use aptos_types::block_executor::partitioner::ShardId;
+use crossbeam_channel::{Receiver, Sender};
+use std::{
+    collections::HashSet,
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+const HEALTH_CHECK_REQUEST_TYPE: &str = "health_check_request";
+const HEALTH_CHECK_RESPONSE_TYPE: &str = "health_check_response";
+
+/// Responds to the health checks of the coordinator, on the shard.
+pub struct HealthCheckResponder {
+    shard_id: ShardId,
+    request_rx: Receiver<Message>,
+    response_tx: Sender<Message>,
+    coordinator_client: Arc<RemoteCoordinatorClient>,
+}
+
+impl HealthCheckResponder {
+    pub fn new(
+        shard_id: ShardId,
+        controller: &mut NetworkController,
+        coordinator_address: SocketAddr,
+        coordinator_client: Arc<RemoteCoordinatorClient>,
+    ) -> Self {
+        let request_rx = controller.create_inbound_channel(HEALTH_CHECK_REQUEST_TYPE.to_string());
+        let response_tx = controller
+            .create_outbound_channel(coordinator_address, HEALTH_CHECK_RESPONSE_TYPE.to_string());
+        Self {
+            shard_id,
+            request_rx,
+            response_tx,
+            coordinator_client,
+        }
+    }
+
+    pub fn start(&self) {
+        while let Ok(message) = self.request_rx.recv() {
+            let request: ShardHealthRequest = match bcs::from_bytes(&message.data) {
+                Ok(request) => request,
+                Err(e) => {
+                    warn!(
+                        "Shard {} dropping health check request: {}",
+                        self.shard_id, e
+                    );
+                    continue;
+                },
+            };
+            let response = ShardHealthResponse {
+                shard_id: self.shard_id,
+                seq: request.seq,
+                executing_request_id: self.coordinator_client.executing_request_id(),
+            };
+            let message = Message::new(bcs::to_bytes(&response).unwrap());
+            if self.response_tx.send(message).is_err() {
+                break;
+            }
+        }
+        info!("Shard {} health check responder stopped", self.shard_id);
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+struct ShardHealth {
+    last_response: Option<Instant>,
+    executing_request_id: Option<u64>,
+}
+
+/// Tracks the health of the remote shards, on the coordinator.
+pub struct ShardHealthMonitor {
+    started_at: Instant,
+    health_check_timeout: Duration,
+    shard_health: Arc<Vec<Mutex<ShardHealth>>>,
+    // Requests the coordinator gave up on. Expected to be rare, so they are never pruned.
+    abandoned_request_ids: Mutex<HashSet<u64>>,
+    stopped: Arc<AtomicBool>,
+    _join_handle: Option<thread::JoinHandle<()>>,
+}
+
+impl ShardHealthMonitor {
+    /// Creates the channels to the shards. The health checks start once the controller is
+    /// started.
+    pub fn new(
+        controller: &mut NetworkController,
+        remote_shard_addresses: &[SocketAddr],
+        health_check_interval: Duration,
+        health_check_timeout: Duration,
+    ) -> Self {
+        let request_txs = remote_shard_addresses
+            .iter()
+            .map(|address| {
+                controller.create_outbound_channel(*address, HEALTH_CHECK_REQUEST_TYPE.to_string())
+            })
+            .collect::<Vec<_>>();
+        let response_rx = controller.create_inbound_channel(HEALTH_CHECK_RESPONSE_TYPE.to_string());
+
+        let shard_health = Arc::new(
+            (0..remote_shard_addresses.len())
+                .map(|_| Mutex::new(ShardHealth::default()))
+                .collect::<Vec<_>>(),
+        );
+        let stopped = Arc::new(AtomicBool::new(false));
+
+        let shard_health_clone = shard_health.clone();
+        let stopped_clone = stopped.clone();
+        let join_handle = thread::Builder::new()
+            .name("remote-shard-health-monitor".to_string())
+            .spawn(move || {
+                Self::run(
+                    request_txs,
+                    response_rx,
+                    shard_health_clone,
+                    stopped_clone,
+                    health_check_interval,
+                )
+            })
+            .unwrap();
+
+        Self {
+            started_at: Instant::now(),
+            health_check_timeout,
+            shard_health,
+            abandoned_request_ids: Mutex::new(HashSet::new()),
+            stopped,
+            _join_handle: Some(join_handle),
+        }
+    }
+
+    fn run(
+        request_txs: Vec<Sender<Message>>,
+        response_rx: Receiver<Message>,
+        shard_health: Arc<Vec<Mutex<ShardHealth>>>,
+        stopped: Arc<AtomicBool>,
+        health_check_interval: Duration,
+    ) {
+        let mut seq = 0;
+        while !stopped.load(Ordering::Relaxed) {
+            seq += 1;
+            let request = Message::new(bcs::to_bytes(&ShardHealthRequest { seq }).unwrap());
+            for request_tx in request_txs.iter() {
+                if request_tx.send(request.clone()).is_err() {
+                    return;
+                }
+            }
+
+            // Responses to earlier requests are accepted as well, a slow response still means
+            // that the shard is alive.
+            let deadline = Instant::now() + health_check_interval;
+            while let Ok(message) = response_rx.recv_deadline(deadline) {
+                let response: ShardHealthResponse = match bcs::from_bytes(&message.data) {
+                    Ok(response) => response,
+                    Err(e) => {
+                        warn!("Dropping shard health check response: {}", e);
+                        continue;
+                    },
+                };
+                if let Some(health) = shard_health.get(response.shard_id) {
+                    *health.lock() = ShardHealth {
+                        last_response: Some(Instant::now()),
+                        executing_request_id: response.executing_request_id,
+                    };
+                }
+            }
+        }
+    }
+
+    /// Returns an error if any of the shards is not ready to execute a new block.
+    pub fn check_ready(&self) -> Result<(), Error> {
+        let now = Instant::now();
+        let abandoned_request_ids = self.abandoned_request_ids.lock();
+        let mut result = Ok(());
+        for (shard_id, health) in self.shard_health.iter().enumerate() {
+            let health = *health.lock();
+            let shard_result = match health.last_response {
+                Some(last_response) if now - last_response > self.health_check_timeout => {
+                    Err(Error::ShardUnhealthy(
+                        shard_id,
+                        format!("no health check response for {:?}", now - last_response),
+                    ))
+                },
+                // Give the shards some time to respond to the first health check.
+                None if now - self.started_at > self.health_check_timeout => Err(
+                    Error::ShardUnhealthy(shard_id, "never responded to health checks".to_string()),
+                ),
+                _ => match health.executing_request_id {
+                    Some(request_id) if abandoned_request_ids.contains(&request_id) => {
+                        Err(Error::ShardUnhealthy(
+                            shard_id,
+                            format!("stuck executing abandoned request {}", request_id),
+                        ))
+                    },
+                    _ => Ok(()),
+                },
+            };
+            REMOTE_EXECUTOR_SHARD_HEALTHY
+                .with_label_values(&[&shard_id.to_string()])
+                .set(shard_result.is_ok() as i64);
+            if result.is_ok() {
+                result = shard_result;
+            }
+        }
+        result
+    }
+
+    /// Returns whether all the shards responded to health checks, and are ready.
+    pub fn is_ready(&self) -> bool {
+        self.shard_health
+            .iter()
+            .all(|health| health.lock().last_response.is_some())
+            && self.check_ready().is_ok()
+    }
+
+    /// Marks the request as abandoned by the coordinator, shards still executing it are not ready
+    /// for new blocks.
+    pub fn abandon_request(&self, request_id: u64) {
+        self.abandoned_request_ids.lock().insert(request_id);
+    }
+
+    pub fn shutdown(&self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Drop for ShardHealthMonitor {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
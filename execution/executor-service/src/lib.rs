@@ -12,6 +12,7 @@ use aptos_types::{
 use serde::{Deserialize, Serialize};
 
 mod error;
+mod health_check;
 pub mod local_executor_helper;
 mod metrics;
 pub mod process_executor_service;
@@ -21,8 +22,8 @@ pub mod remote_executor_client;
 pub mod remote_executor_service;
 mod remote_state_view;
 mod remote_state_view_service;
-#[cfg(test)]
-mod test_utils;
+#[cfg(any(test, feature = "testing"))]
+pub mod test_utils;
 #[cfg(test)]
 mod tests;
 #[cfg(test)]
@@ -30,12 +31,14 @@ mod thread_executor_service;
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct RemoteExecutionResult {
+    /// The id of the [ExecuteBlockCommand] this is the result of.
+    pub request_id: u64,
     pub inner: Result<Vec<Vec<TransactionOutput>>, VMStatus>,
 }
 
 impl RemoteExecutionResult {
-    pub fn new(inner: Result<Vec<Vec<TransactionOutput>>, VMStatus>) -> Self {
-        Self { inner }
+    pub fn new(request_id: u64, inner: Result<Vec<Vec<TransactionOutput>>, VMStatus>) -> Self {
+        Self { request_id, inner }
     }
 }
 
@@ -46,6 +49,9 @@ pub enum RemoteExecutionRequest {
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct ExecuteBlockCommand {
+    /// Identifies the command, so that results of commands the coordinator gave up on (e.g., on a
+    /// timeout) are not mistaken for the results of later ones.
+    pub(crate) request_id: u64,
     pub(crate) sub_blocks: SubBlocksForShard<AnalyzedTransaction>,
     pub(crate) concurrency_level: usize,
     pub(crate) onchain_config: BlockExecutorConfigFromOnchain,
@@ -89,3 +95,17 @@ impl RemoteKVResponse {
         Self { inner }
     }
 }
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ShardHealthRequest {
+    pub(crate) seq: u64,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ShardHealthResponse {
+    pub(crate) shard_id: ShardId,
+    /// The sequence number of the [ShardHealthRequest] this responds to.
+    pub(crate) seq: u64,
+    /// The id of the [ExecuteBlockCommand] the shard is executing, if any.
+    pub(crate) executing_request_id: Option<u64>,
+}
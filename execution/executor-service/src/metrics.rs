@@ -2,8 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use aptos_metrics_core::{
-    exponential_buckets, register_histogram_vec, register_int_counter_vec, HistogramVec,
-    IntCounterVec,
+    exponential_buckets, register_histogram_vec, register_int_counter_vec, register_int_gauge_vec,
+    HistogramVec, IntCounterVec, IntGaugeVec,
 };
 use once_cell::sync::Lazy;
 
@@ -44,3 +44,29 @@ pub static REMOTE_EXECUTOR_REMOTE_KV_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
     )
     .unwrap()
 });
+
+pub static REMOTE_EXECUTOR_SHARD_HEALTHY: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        // metric name
+        "remote_executor_shard_healthy",
+        // metric description
+        "Whether the remote shard responded to the last health checks (1) or not (0), as seen by \
+         the coordinator",
+        // metric labels (dimensions)
+        &["shard_id"],
+    )
+    .unwrap()
+});
+
+pub static REMOTE_EXECUTOR_LOCAL_FALLBACK_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        // metric name
+        "remote_executor_local_fallback_count",
+        // metric description
+        "The number of blocks executed locally by the coordinator because remote execution \
+         failed, by the reason of the failure",
+        // metric labels (dimensions)
+        &["reason"],
+    )
+    .unwrap()
+});
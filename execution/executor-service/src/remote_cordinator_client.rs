@@ -4,6 +4,7 @@ use crate::{
     metrics::REMOTE_EXECUTOR_TIMER, remote_state_view::RemoteStateViewClient, ExecuteBlockCommand,
     RemoteExecutionRequest, RemoteExecutionResult,
 };
+use aptos_infallible::Mutex;
 use aptos_secure_net::network_controller::{Message, NetworkController};
 use aptos_types::{
     block_executor::partitioner::ShardId, state_store::state_key::StateKey,
@@ -21,6 +22,8 @@ pub struct RemoteCoordinatorClient {
     command_rx: Receiver<Message>,
     result_tx: Sender<Message>,
     shard_id: ShardId,
+    // The id of the command being executed, reported to the coordinator in health checks.
+    executing_request_id: Mutex<Option<u64>>,
 }
 
 impl RemoteCoordinatorClient {
@@ -43,9 +46,14 @@ impl RemoteCoordinatorClient {
             command_rx,
             result_tx,
             shard_id,
+            executing_request_id: Mutex::new(None),
         }
     }
 
+    pub fn executing_request_id(&self) -> Option<u64> {
+        *self.executing_request_id.lock()
+    }
+
     // Extract all the state keys from the execute block command. It is possible that there are duplicate state keys.
     // We are not de-duplicating them here to avoid the overhead of deduplication. The state view server will deduplicate
     // the state keys.
@@ -91,6 +99,7 @@ impl CoordinatorClient<RemoteStateViewClient> for RemoteCoordinatorClient {
 
                 match request {
                     RemoteExecutionRequest::ExecuteBlock(command) => {
+                        *self.executing_request_id.lock() = Some(command.request_id);
                         let init_prefetch_timer = REMOTE_EXECUTOR_TIMER
                             .with_label_values(&[&self.shard_id.to_string(), "init_prefetch"])
                             .start_timer();
@@ -113,7 +122,12 @@ impl CoordinatorClient<RemoteStateViewClient> for RemoteCoordinatorClient {
     }
 
     fn send_execution_result(&self, result: Result<Vec<Vec<TransactionOutput>>, VMStatus>) {
-        let remote_execution_result = RemoteExecutionResult::new(result);
+        let request_id = self
+            .executing_request_id
+            .lock()
+            .take()
+            .expect("Must be executing a command.");
+        let remote_execution_result = RemoteExecutionResult::new(request_id, result);
         let output_message = bcs::to_bytes(&remote_execution_result).unwrap();
         self.result_tx.send(Message::new(output_message)).unwrap();
     }
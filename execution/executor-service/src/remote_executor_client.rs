@@ -1,11 +1,11 @@
 // Copyright © Aptos Foundation
-// Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 use crate::{
+    error::Error, health_check::ShardHealthMonitor, metrics::REMOTE_EXECUTOR_LOCAL_FALLBACK_COUNT,
     remote_state_view_service::RemoteStateViewService, ExecuteBlockCommand, RemoteExecutionRequest,
     RemoteExecutionResult,
 };
-use aptos_logger::{info, trace};
+use aptos_logger::{info, trace, warn};
 use aptos_secure_net::network_controller::{Message, NetworkController};
 use aptos_storage_interface::state_store::state_view::cached_state_view::CachedStateView;
 use aptos_types::{
@@ -14,24 +14,30 @@ use aptos_types::{
     },
     state_store::StateView,
     transaction::TransactionOutput,
-    vm_status::VMStatus,
+    vm_status::{StatusCode, VMStatus},
 };
 use aptos_vm::sharded_block_executor::{
     executor_client::{ExecutorClient, ShardedExecutionOutput},
-    ShardedBlockExecutor,
+    local_executor_shard::{LocalExecutorClient, LocalExecutorService},
+    sharded_aggregator_service, ShardedBlockExecutor,
 };
 use crossbeam_channel::{Receiver, Sender};
 use once_cell::sync::{Lazy, OnceCell};
 use std::{
     net::{IpAddr, Ipv4Addr, SocketAddr},
-    sync::{Arc, Mutex},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
     thread,
+    time::{Duration, Instant},
 };
 
 pub static COORDINATOR_PORT: u16 = 52200;
 
 static REMOTE_ADDRESSES: OnceCell<Vec<SocketAddr>> = OnceCell::new();
 static COORDINATOR_ADDRESS: OnceCell<SocketAddr> = OnceCell::new();
+static REMOTE_EXECUTOR_CONFIG: OnceCell<RemoteExecutorConfig> = OnceCell::new();
 
 pub fn set_remote_addresses(addresses: Vec<SocketAddr>) {
     REMOTE_ADDRESSES.set(addresses).ok();
@@ -55,6 +61,14 @@ pub fn get_coordinator_address() -> SocketAddr {
     }
 }
 
+pub fn set_remote_executor_config(config: RemoteExecutorConfig) {
+    REMOTE_EXECUTOR_CONFIG.set(config).ok();
+}
+
+pub fn get_remote_executor_config() -> RemoteExecutorConfig {
+    REMOTE_EXECUTOR_CONFIG.get().cloned().unwrap_or_default()
+}
+
 pub static REMOTE_SHARDED_BLOCK_EXECUTOR: Lazy<
     Arc<
         aptos_infallible::Mutex<
@@ -68,10 +82,41 @@ pub static REMOTE_SHARDED_BLOCK_EXECUTOR: Lazy<
             get_coordinator_address(),
             get_remote_addresses(),
             None,
+            get_remote_executor_config(),
         ),
     ))
 });
 
+/// Fault handling of the remote execution.
+#[derive(Clone, Debug)]
+pub struct RemoteExecutorConfig {
+    /// The maximum time to wait for the results of a block from all the shards.
+    pub execution_timeout: Duration,
+    /// How often the shards are health checked.
+    pub health_check_interval: Duration,
+    /// A shard that did not respond to health checks for this long is considered unhealthy.
+    pub health_check_timeout: Duration,
+    /// If set, a block that could not be executed by the shards (e.g., because a shard is
+    /// unhealthy or timed out) is executed by local shards on the coordinator instead. Otherwise
+    /// an error is returned.
+    pub fallback_to_local: bool,
+    /// If set, messages to shards which cannot be delivered (e.g., because the shard crashed)
+    /// are dropped, and the block times out instead of the coordinator panicking.
+    pub drop_undeliverable_messages: bool,
+}
+
+impl Default for RemoteExecutorConfig {
+    fn default() -> Self {
+        Self {
+            execution_timeout: Duration::from_secs(60),
+            health_check_interval: Duration::from_secs(1),
+            health_check_timeout: Duration::from_secs(5),
+            fallback_to_local: true,
+            drop_undeliverable_messages: true,
+        }
+    }
+}
+
 #[allow(dead_code)]
 pub struct RemoteExecutorClient<S: StateView + Sync + Send + 'static> {
     // The network controller used to create channels to send and receive messages. We want the
@@ -85,6 +130,12 @@ pub struct RemoteExecutorClient<S: StateView + Sync + Send + 'static> {
     result_rxs: Vec<Receiver<Message>>,
     // Thread pool used to pre-fetch the state values for the block in parallel and create an in-memory state view.
     thread_pool: Arc<rayon::ThreadPool>,
+    health_monitor: ShardHealthMonitor,
+    next_request_id: AtomicU64,
+    config: RemoteExecutorConfig,
+    num_threads: usize,
+    // Created on the first fallback to local execution.
+    local_executor_client: OnceCell<LocalExecutorClient<S>>,
 
     phantom: std::marker::PhantomData<S>,
     _join_handle: Option<thread::JoinHandle<()>>,
@@ -93,9 +144,23 @@ pub struct RemoteExecutorClient<S: StateView + Sync + Send + 'static> {
 #[allow(dead_code)]
 impl<S: StateView + Sync + Send + 'static> RemoteExecutorClient<S> {
     pub fn new(
+        remote_shard_addresses: Vec<SocketAddr>,
+        controller: NetworkController,
+        num_threads: Option<usize>,
+    ) -> Self {
+        Self::new_with_config(
+            remote_shard_addresses,
+            controller,
+            num_threads,
+            RemoteExecutorConfig::default(),
+        )
+    }
+
+    pub fn new_with_config(
         remote_shard_addresses: Vec<SocketAddr>,
         mut controller: NetworkController,
         num_threads: Option<usize>,
+        config: RemoteExecutorConfig,
     ) -> Self {
         let num_threads = num_threads.unwrap_or_else(num_cpus::get);
         let thread_pool = Arc::new(
@@ -104,6 +169,7 @@ impl<S: StateView + Sync + Send + 'static> RemoteExecutorClient<S> {
                 .build()
                 .unwrap(),
         );
+        controller.set_drop_undeliverable_messages(config.drop_undeliverable_messages);
         let controller_mut_ref = &mut controller;
         let (command_txs, result_rxs) = remote_shard_addresses
             .iter()
@@ -119,6 +185,13 @@ impl<S: StateView + Sync + Send + 'static> RemoteExecutorClient<S> {
             })
             .unzip();
 
+        let health_monitor = ShardHealthMonitor::new(
+            controller_mut_ref,
+            &remote_shard_addresses,
+            config.health_check_interval,
+            config.health_check_timeout,
+        );
+
         let state_view_service = Arc::new(RemoteStateViewService::new(
             controller_mut_ref,
             remote_shard_addresses,
@@ -141,6 +214,11 @@ impl<S: StateView + Sync + Send + 'static> RemoteExecutorClient<S> {
             command_txs: Arc::new(command_txs),
             result_rxs,
             thread_pool,
+            health_monitor,
+            next_request_id: AtomicU64::new(0),
+            config,
+            num_threads,
+            local_executor_client: OnceCell::new(),
             phantom: std::marker::PhantomData,
         }
     }
@@ -149,8 +227,9 @@ impl<S: StateView + Sync + Send + 'static> RemoteExecutorClient<S> {
         coordinator_address: SocketAddr,
         remote_shard_addresses: Vec<SocketAddr>,
         num_threads: Option<usize>,
+        config: RemoteExecutorConfig,
     ) -> ShardedBlockExecutor<S, RemoteExecutorClient<S>> {
-        ShardedBlockExecutor::new(RemoteExecutorClient::new(
+        ShardedBlockExecutor::new(RemoteExecutorClient::new_with_config(
             remote_shard_addresses,
             NetworkController::new(
                 "remote-executor-coordinator".to_string(),
@@ -158,61 +237,210 @@ impl<S: StateView + Sync + Send + 'static> RemoteExecutorClient<S> {
                 5000,
             ),
             num_threads,
+            config,
         ))
     }
 
-    fn get_output_from_shards(&self) -> Result<Vec<Vec<Vec<TransactionOutput>>>, VMStatus> {
-        trace!("RemoteExecutorClient Waiting for results");
-        let mut results = vec![];
-        for rx in self.result_rxs.iter() {
-            let received_bytes = rx.recv().unwrap().to_bytes();
-            let result: RemoteExecutionResult = bcs::from_bytes(&received_bytes).unwrap();
-            results.push(result.inner?);
+    /// Blocks until all the shards respond to health checks and are ready to execute blocks.
+    /// Returns false if they are not ready within the timeout.
+    pub fn wait_for_shards(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        while !self.health_monitor.is_ready() {
+            if Instant::now() >= deadline {
+                return false;
+            }
+            thread::sleep(self.config.health_check_interval);
         }
-        Ok(results)
+        true
     }
-}
 
-impl<S: StateView + Sync + Send + 'static> ExecutorClient<S> for RemoteExecutorClient<S> {
-    fn num_shards(&self) -> usize {
-        self.command_txs.len()
+    // The outer error means the shards failed to execute the block (e.g., a shard crashed), the
+    // inner error is an execution error returned by a shard.
+    fn get_output_from_shards(
+        &self,
+        request_id: u64,
+    ) -> Result<Result<Vec<Vec<Vec<TransactionOutput>>>, VMStatus>, Error> {
+        trace!("RemoteExecutorClient Waiting for results");
+        let deadline = Instant::now() + self.config.execution_timeout;
+        let mut results = vec![];
+        for (shard_id, rx) in self.result_rxs.iter().enumerate() {
+            let result = loop {
+                let received_bytes = rx
+                    .recv_deadline(deadline)
+                    .map_err(|e| {
+                        if e.is_timeout() {
+                            Error::ShardTimeout(shard_id)
+                        } else {
+                            Error::ShardDisconnected(shard_id)
+                        }
+                    })?
+                    .to_bytes();
+                let result: RemoteExecutionResult = bcs::from_bytes(&received_bytes)?;
+                if result.request_id == request_id {
+                    break result;
+                }
+                warn!(
+                    "Dropping result of abandoned request {} from shard {}",
+                    result.request_id, shard_id
+                );
+            };
+            match result.inner {
+                Ok(result) => results.push(result),
+                Err(e) => return Ok(Err(e)),
+            }
+        }
+        Ok(Ok(results))
     }
 
-    fn execute_block(
+    // The transactions are returned back (they are only serialized to be sent), so that the
+    // block can be executed locally if remote execution fails without cloning it upfront.
+    #[allow(clippy::type_complexity)]
+    fn execute_block_remotely(
         &self,
+        request_id: u64,
         state_view: Arc<S>,
         transactions: PartitionedTransactions,
         concurrency_level_per_shard: usize,
         onchain_config: BlockExecutorConfigFromOnchain,
-    ) -> Result<ShardedExecutionOutput, VMStatus> {
+    ) -> (
+        PartitionedTransactions,
+        Result<Result<Vec<Vec<Vec<TransactionOutput>>>, VMStatus>, Error>,
+    ) {
+        if let Err(error) = self.health_monitor.check_ready() {
+            return (transactions, Err(error));
+        }
+
         trace!("RemoteExecutorClient Sending block to shards");
         self.state_view_service.set_state_view(state_view);
-        let (sub_blocks, global_txns) = transactions.into();
+        let (sharded_txns, global_txns) = transactions.into();
         if !global_txns.is_empty() {
             panic!("Global transactions are not supported yet");
         }
-        for (shard_id, sub_blocks) in sub_blocks.into_iter().enumerate() {
-            let senders = self.command_txs.clone();
+        let mut result = Ok(());
+        let mut sent_sharded_txns = Vec::with_capacity(sharded_txns.len());
+        for (shard_id, sub_blocks) in sharded_txns.into_iter().enumerate() {
+            if result.is_err() {
+                sent_sharded_txns.push(sub_blocks);
+                continue;
+            }
             let execution_request = RemoteExecutionRequest::ExecuteBlock(ExecuteBlockCommand {
+                request_id,
                 sub_blocks,
                 concurrency_level: concurrency_level_per_shard,
                 onchain_config: onchain_config.clone(),
             });
 
-            senders[shard_id]
-                .lock()
-                .unwrap()
-                .send(Message::new(bcs::to_bytes(&execution_request).unwrap()))
-                .unwrap();
+            result = bcs::to_bytes(&execution_request)
+                .map_err(Error::from)
+                .and_then(|bytes| {
+                    self.command_txs[shard_id]
+                        .lock()
+                        .unwrap()
+                        .send(Message::new(bytes))
+                        .map_err(|_| Error::ShardDisconnected(shard_id))
+                });
+            let RemoteExecutionRequest::ExecuteBlock(command) = execution_request;
+            sent_sharded_txns.push(command.sub_blocks);
         }
 
-        let execution_results = self.get_output_from_shards()?;
+        let execution_results = result.and_then(|()| self.get_output_from_shards(request_id));
 
         self.state_view_service.drop_state_view();
-        Ok(ShardedExecutionOutput::new(execution_results, vec![]))
+        (
+            PartitionedTransactions::new(sent_sharded_txns, global_txns),
+            execution_results,
+        )
+    }
+
+    fn execute_block_locally(
+        &self,
+        state_view: Arc<S>,
+        transactions: PartitionedTransactions,
+        concurrency_level_per_shard: usize,
+        onchain_config: BlockExecutorConfigFromOnchain,
+    ) -> Result<ShardedExecutionOutput, VMStatus> {
+        let num_shards = self.num_shards();
+        self.local_executor_client
+            .get_or_init(|| {
+                info!("Creating local executor shards for fallback from remote execution");
+                LocalExecutorService::setup_local_executor_shards(
+                    num_shards,
+                    Some((self.num_threads / num_shards).max(1)),
+                )
+            })
+            .execute_block(
+                state_view,
+                transactions,
+                concurrency_level_per_shard,
+                onchain_config,
+            )
+    }
+}
+
+impl<S: StateView + Sync + Send + 'static> ExecutorClient<S> for RemoteExecutorClient<S> {
+    fn num_shards(&self) -> usize {
+        self.command_txs.len()
+    }
+
+    fn execute_block(
+        &self,
+        state_view: Arc<S>,
+        transactions: PartitionedTransactions,
+        concurrency_level_per_shard: usize,
+        onchain_config: BlockExecutorConfigFromOnchain,
+    ) -> Result<ShardedExecutionOutput, VMStatus> {
+        let request_id = self.next_request_id.fetch_add(1, Ordering::Relaxed);
+
+        let (transactions, execution_results) = self.execute_block_remotely(
+            request_id,
+            state_view.clone(),
+            transactions,
+            concurrency_level_per_shard,
+            onchain_config.clone(),
+        );
+        let error = match execution_results {
+            Ok(execution_results) => {
+                let mut sharded_output = execution_results?;
+                // Same as local execution, the total supply is tracked in the shards against a
+                // base value, and updated here.
+                sharded_aggregator_service::aggregate_and_update_total_supply(
+                    &mut sharded_output,
+                    &mut [],
+                    state_view.as_ref(),
+                    self.thread_pool.clone(),
+                );
+                return Ok(ShardedExecutionOutput::new(sharded_output, vec![]));
+            },
+            Err(error) => error,
+        };
+
+        // The shards might still be executing the block (or be stuck, e.g. waiting for cross
+        // shard messages from a crashed shard), they are not used until they are done with it.
+        self.health_monitor.abandon_request(request_id);
+        if self.config.fallback_to_local {
+            REMOTE_EXECUTOR_LOCAL_FALLBACK_COUNT
+                .with_label_values(&[error.name()])
+                .inc();
+            warn!(
+                "Remote execution of request {} failed: {}, executing the block locally",
+                request_id, error
+            );
+            self.execute_block_locally(
+                state_view,
+                transactions,
+                concurrency_level_per_shard,
+                onchain_config,
+            )
+        } else {
+            Err(VMStatus::error(
+                StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR,
+                Some(format!("Remote execution failed: {}", error)),
+            ))
+        }
     }
 
     fn shutdown(&mut self) {
+        self.health_monitor.shutdown();
         self.network_controller.shutdown();
     }
 }
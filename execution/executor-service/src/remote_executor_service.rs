@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    remote_cordinator_client::RemoteCoordinatorClient,
+    health_check::HealthCheckResponder, remote_cordinator_client::RemoteCoordinatorClient,
     remote_cross_shard_client::RemoteCrossShardClient, remote_state_view::RemoteStateViewClient,
 };
 use aptos_secure_net::network_controller::NetworkController;
@@ -16,6 +16,7 @@ pub struct ExecutorService {
     shard_id: ShardId,
     controller: NetworkController,
     executor_service: Arc<ShardedExecutorService<RemoteStateViewClient>>,
+    health_check_responder: Arc<HealthCheckResponder>,
 }
 
 impl ExecutorService {
@@ -34,6 +35,12 @@ impl ExecutorService {
             &mut controller,
             coordinator_address,
         ));
+        let health_check_responder = Arc::new(HealthCheckResponder::new(
+            shard_id,
+            &mut controller,
+            coordinator_address,
+            coordinator_client.clone(),
+        ));
         let cross_shard_client = Arc::new(RemoteCrossShardClient::new(
             &mut controller,
             remote_shard_addresses,
@@ -51,6 +58,7 @@ impl ExecutorService {
             shard_id,
             controller,
             executor_service,
+            health_check_responder,
         }
     }
 
@@ -64,6 +72,14 @@ impl ExecutorService {
                 executor_service_clone.start();
             })
             .expect("Failed to spawn thread");
+
+        let health_check_responder_clone = self.health_check_responder.clone();
+        thread::Builder::new()
+            .name(format!("HealthCheckResponder-{}", self.shard_id))
+            .spawn(move || {
+                health_check_responder_clone.start();
+            })
+            .expect("Failed to spawn thread");
     }
 
     pub fn shutdown(&mut self) {
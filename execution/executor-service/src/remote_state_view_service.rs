@@ -10,7 +10,7 @@ use std::{
 
 extern crate itertools;
 use crate::metrics::REMOTE_EXECUTOR_TIMER;
-use aptos_logger::trace;
+use aptos_logger::{trace, warn};
 use aptos_types::state_store::{StateView, TStateView};
 use itertools::Itertools;
 
@@ -92,16 +92,23 @@ impl<S: StateView + Sync + Send + 'static> RemoteStateViewService<S> {
             shard_id,
             state_keys.len()
         );
+        // The state view is dropped once the coordinator is done with a block, e.g. if it gave up
+        // waiting for a shard. A late request of that shard is dropped.
+        let state_view = match state_view.read().unwrap().clone() {
+            Some(state_view) => state_view,
+            None => {
+                warn!(
+                    "remote state view service - dropping request for shard {} with {} keys, no block is being executed",
+                    shard_id,
+                    state_keys.len()
+                );
+                return;
+            },
+        };
         let resp = state_keys
             .into_iter()
             .map(|state_key| {
-                let state_value = state_view
-                    .read()
-                    .unwrap()
-                    .as_ref()
-                    .unwrap()
-                    .get_state_value(&state_key)
-                    .unwrap();
+                let state_value = state_view.get_state_value(&state_key).unwrap();
                 (state_key, state_value)
             })
             .collect_vec();
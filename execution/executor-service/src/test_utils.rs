@@ -120,6 +120,15 @@ pub fn compare_txn_outputs(
 
 pub fn test_sharded_block_executor_no_conflict<E: ExecutorClient<InMemoryStateStore>>(
     mut sharded_block_executor: ShardedBlockExecutor<InMemoryStateStore, E>,
+) {
+    check_sharded_block_executor_no_conflict(&sharded_block_executor);
+    sharded_block_executor.shutdown();
+}
+
+/// Executes a block of non-conflicting transactions, and checks that the outputs are the same as
+/// those of the unsharded executor.
+pub fn check_sharded_block_executor_no_conflict<E: ExecutorClient<InMemoryStateStore>>(
+    sharded_block_executor: &ShardedBlockExecutor<InMemoryStateStore, E>,
 ) {
     let num_txns = 400;
     let num_shards = sharded_block_executor.num_shards();
@@ -153,12 +162,21 @@ pub fn test_sharded_block_executor_no_conflict<E: ExecutorClient<InMemoryStateSt
         .execute_block_no_limit(&txn_provider, &state_store)
         .unwrap();
     compare_txn_outputs(unsharded_txn_output, sharded_txn_output);
-    sharded_block_executor.shutdown();
 }
 
 pub fn sharded_block_executor_with_conflict<E: ExecutorClient<InMemoryStateStore>>(
     mut sharded_block_executor: ShardedBlockExecutor<InMemoryStateStore, E>,
     concurrency: usize,
+) {
+    check_sharded_block_executor_with_conflict(&sharded_block_executor, concurrency);
+    sharded_block_executor.shutdown();
+}
+
+/// Executes a block of conflicting transactions, and checks that the outputs are the same as
+/// those of the unsharded executor.
+pub fn check_sharded_block_executor_with_conflict<E: ExecutorClient<InMemoryStateStore>>(
+    sharded_block_executor: &ShardedBlockExecutor<InMemoryStateStore, E>,
+    concurrency: usize,
 ) {
     let num_txns = 800;
     let num_shards = sharded_block_executor.num_shards();
@@ -209,5 +227,4 @@ pub fn sharded_block_executor_with_conflict<E: ExecutorClient<InMemoryStateStore
         .execute_block_no_limit(&txn_provider, &state_store)
         .unwrap();
     compare_txn_outputs(unsharded_txn_output, sharded_txn_output);
-    sharded_block_executor.shutdown();
 }
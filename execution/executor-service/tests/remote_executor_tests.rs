@@ -0,0 +1,120 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Runs the remote executor shards as separate processes on localhost, and checks that the
+//! outputs of sharded execution are the same as those of the unsharded executor, including when
+//! a shard crashes.
+
+use aptos_config::utils;
+use aptos_executor_service::{
+    remote_executor_client::{RemoteExecutorClient, RemoteExecutorConfig},
+    test_utils,
+};
+use aptos_secure_net::network_controller::NetworkController;
+use aptos_transaction_simulation::InMemoryStateStore;
+use aptos_vm::sharded_block_executor::ShardedBlockExecutor;
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    process::{Child, Command},
+    time::Duration,
+};
+
+const NUM_SHARDS: usize = 4;
+
+struct ShardProcess(Child);
+
+impl ShardProcess {
+    fn spawn(
+        shard_id: usize,
+        coordinator_address: SocketAddr,
+        remote_shard_addresses: &[SocketAddr],
+    ) -> Self {
+        let child = Command::new(env!("CARGO_BIN_EXE_aptos-executor-service"))
+            .arg("--num-executor-threads")
+            .arg("2")
+            .arg("--shard-id")
+            .arg(shard_id.to_string())
+            .arg("--num-shards")
+            .arg(remote_shard_addresses.len().to_string())
+            .arg("--coordinator-address")
+            .arg(coordinator_address.to_string())
+            .arg("--remote-executor-addresses")
+            .args(
+                remote_shard_addresses
+                    .iter()
+                    .map(|address| address.to_string()),
+            )
+            .spawn()
+            .expect("Failed to spawn executor service");
+        Self(child)
+    }
+
+    fn kill(&mut self) {
+        self.0.kill().ok();
+        self.0.wait().ok();
+    }
+}
+
+impl Drop for ShardProcess {
+    fn drop(&mut self) {
+        self.kill();
+    }
+}
+
+fn local_address() -> SocketAddr {
+    SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), utils::get_available_port())
+}
+
+fn create_process_remote_executor_shards() -> (
+    ShardedBlockExecutor<InMemoryStateStore, RemoteExecutorClient<InMemoryStateStore>>,
+    Vec<ShardProcess>,
+) {
+    let coordinator_address = local_address();
+    let remote_shard_addresses = (0..NUM_SHARDS).map(|_| local_address()).collect::<Vec<_>>();
+    let shard_processes = (0..NUM_SHARDS)
+        .map(|shard_id| ShardProcess::spawn(shard_id, coordinator_address, &remote_shard_addresses))
+        .collect::<Vec<_>>();
+
+    let config = RemoteExecutorConfig {
+        execution_timeout: Duration::from_secs(10),
+        health_check_interval: Duration::from_millis(100),
+        health_check_timeout: Duration::from_secs(1),
+        fallback_to_local: true,
+        drop_undeliverable_messages: true,
+    };
+    let controller = NetworkController::new(
+        "remote-executor-coordinator".to_string(),
+        coordinator_address,
+        5000,
+    );
+    let executor_client =
+        RemoteExecutorClient::new_with_config(remote_shard_addresses, controller, None, config);
+    assert!(
+        executor_client.wait_for_shards(Duration::from_secs(60)),
+        "Executor shards did not start"
+    );
+    (ShardedBlockExecutor::new(executor_client), shard_processes)
+}
+
+#[test]
+fn test_process_sharded_block_executor_matches_unsharded() {
+    let (mut sharded_block_executor, _shard_processes) = create_process_remote_executor_shards();
+
+    test_utils::check_sharded_block_executor_no_conflict(&sharded_block_executor);
+    test_utils::check_sharded_block_executor_with_conflict(&sharded_block_executor, 2);
+
+    sharded_block_executor.shutdown();
+}
+
+#[test]
+fn test_process_sharded_block_executor_shard_crash() {
+    let (mut sharded_block_executor, mut shard_processes) = create_process_remote_executor_shards();
+    test_utils::check_sharded_block_executor_with_conflict(&sharded_block_executor, 2);
+
+    // The block is executed locally once a shard is down, the outputs must stay the same.
+    shard_processes[2].kill();
+    test_utils::check_sharded_block_executor_with_conflict(&sharded_block_executor, 2);
+    test_utils::check_sharded_block_executor_no_conflict(&sharded_block_executor);
+
+    sharded_block_executor.shutdown();
+}
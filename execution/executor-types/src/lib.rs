@@ -152,6 +152,18 @@ pub trait BlockExecutorTrait: Send + Sync {
         parent_block_id: HashValue,
     ) -> ExecutorResult<StateComputeResult>;
 
+    /// Speculatively executes a block whose parent is not executed yet (e.g., because it is
+    /// being executed concurrently), on top of the parent's parent. When the block is executed
+    /// via `execute_and_update_state`, the speculative output is reused if the parent didn't
+    /// change any of the state read by the block.
+    fn execute_speculatively(
+        &self,
+        block: ExecutableBlock,
+        parent_block_id: HashValue,
+        base_block_id: HashValue,
+        onchain_config: BlockExecutorConfigFromOnchain,
+    ) -> ExecutorResult<()>;
+
     #[cfg(any(test, feature = "fuzzing"))]
     fn commit_blocks(
         &self,
@@ -34,7 +34,8 @@ use aptos_storage_interface::{
 };
 use aptos_types::{
     block_executor::{
-        config::BlockExecutorConfigFromOnchain, partitioner::ExecutableBlock,
+        config::BlockExecutorConfigFromOnchain,
+        partitioner::{ExecutableBlock, ExecutableTransactions},
         transaction_slice_metadata::TransactionSliceMetadata,
     },
     ledger_info::LedgerInfoWithSignatures,
@@ -43,9 +44,11 @@ use aptos_types::{
 use aptos_vm::VMBlockExecutor;
 use block_tree::BlockTree;
 use fail::fail_point;
+use speculation::{SpeculativeOutput, SpeculativeOutputs};
 use std::sync::Arc;
 
 pub mod block_tree;
+mod speculation;
 
 pub struct BlockExecutor<V> {
     pub db: DbReaderWriter,
@@ -109,6 +112,23 @@ where
             .execute_and_update_state(block, parent_block_id, onchain_config)
     }
 
+    fn execute_speculatively(
+        &self,
+        block: ExecutableBlock,
+        parent_block_id: HashValue,
+        base_block_id: HashValue,
+        onchain_config: BlockExecutorConfigFromOnchain,
+    ) -> ExecutorResult<()> {
+        let _guard = CONCURRENCY_GAUGE.concurrency_with(&["block", "execute_speculatively"]);
+
+        self.maybe_initialize()?;
+        self.inner
+            .read()
+            .as_ref()
+            .expect("BlockExecutor is not reset")
+            .execute_speculatively(block, parent_block_id, base_block_id, onchain_config)
+    }
+
     fn ledger_update(
         &self,
         block_id: HashValue,
@@ -154,6 +174,7 @@ struct BlockExecutorInner<V> {
     db: DbReaderWriter,
     block_tree: BlockTree,
     block_executor: V,
+    speculative_outputs: SpeculativeOutputs,
 }
 
 impl<V> BlockExecutorInner<V>
@@ -166,6 +187,7 @@ where
             db,
             block_tree,
             block_executor: V::new(),
+            speculative_outputs: SpeculativeOutputs::default(),
         })
     }
 }
@@ -212,6 +234,31 @@ where
                     "reconfig_descendant_block_received"
                 );
                 parent_output.execution_output.reconfig_suffix()
+            } else if let Some(speculative_output) = self.speculative_outputs.take_valid(
+                &self.block_tree,
+                parent_block_id,
+                block_id,
+                &parent_output.execution_output,
+            )? {
+                info!(
+                    LogSchema::new(LogEntry::BlockExecutor).block_id(block_id),
+                    "reusing_speculative_output"
+                );
+                let state_view = {
+                    let _timer = OTHER_TIMERS.timer_with(&["get_state_view"]);
+                    CachedStateView::new(
+                        StateViewId::BlockExecution { block_id },
+                        Arc::clone(&self.db.reader),
+                        parent_output.result_state().latest().clone(),
+                    )?
+                };
+
+                let _timer = OTHER_TIMERS.timer_with(&["reuse_speculative_output"]);
+                speculative_output.into_execution_output(
+                    parent_output.result_state(),
+                    state_view,
+                    true, // is_block
+                )?
             } else {
                 let state_view = {
                     let _timer = OTHER_TIMERS.timer_with(&["get_state_view"]);
@@ -247,6 +294,78 @@ where
         Ok(())
     }
 
+    fn execute_speculatively(
+        &self,
+        block: ExecutableBlock,
+        parent_block_id: HashValue,
+        base_block_id: HashValue,
+        onchain_config: BlockExecutorConfigFromOnchain,
+    ) -> ExecutorResult<()> {
+        let _timer = OTHER_TIMERS.timer_with(&["execute_speculatively"]);
+        let ExecutableBlock {
+            block_id,
+            transactions,
+            auxiliary_info,
+        } = block;
+        let transactions = match transactions {
+            ExecutableTransactions::Unsharded(transactions) => transactions,
+            // Sharded execution is not supported, the block is only executed once its parent is.
+            ExecutableTransactions::Sharded(_) => return Ok(()),
+        };
+        let mut block_vec = self
+            .block_tree
+            .get_blocks_opt(&[parent_block_id, base_block_id])?;
+        let base_block = block_vec
+            .pop()
+            .expect("Must exist.")
+            .ok_or(ExecutorError::BlockNotFound(base_block_id))?;
+        if block_vec.pop().expect("Must exist.").is_some() {
+            // The parent is already executed, so there is no need to speculate.
+            return Ok(());
+        }
+        let base_output = &base_block.output;
+        if base_block_id != self.committed_block_id() && base_output.has_reconfiguration() {
+            // Both the parent and the block are reconfiguration suffixes.
+            return Ok(());
+        }
+        info!(
+            LogSchema::new(LogEntry::BlockExecutor).block_id(block_id),
+            "execute_speculatively"
+        );
+
+        let state_view = CachedStateView::new(
+            StateViewId::BlockExecution { block_id },
+            Arc::clone(&self.db.reader),
+            base_output.result_state().latest().clone(),
+        )?;
+        // A new VM executor is used for every speculative execution, so that no code is cached
+        // from a state other than the base block's: code is read through the state view, and so
+        // validated as any other read. This also leaves the module cache of the executor used
+        // for the non-speculative execution intact.
+        let (transactions, transaction_outputs, auxiliary_info) =
+            DoGetExecutionOutput::execute_unsharded(
+                &V::new(),
+                transactions,
+                auxiliary_info,
+                &state_view,
+                onchain_config,
+                TransactionSliceMetadata::block(parent_block_id, block_id),
+            )?;
+
+        self.speculative_outputs.insert(
+            parent_block_id,
+            block_id,
+            SpeculativeOutput::new(
+                base_block_id,
+                transactions,
+                transaction_outputs,
+                auxiliary_info,
+                state_view.memorized_reads(),
+            ),
+        );
+        Ok(())
+    }
+
     fn ledger_update(
         &self,
         block_id: HashValue,
@@ -376,6 +495,7 @@ where
             .commit_ledger(target_version, Some(&ledger_info_with_sigs), None)?;
 
         self.block_tree.prune(ledger_info_with_sigs.ledger_info())?;
+        self.speculative_outputs.prune(&self.block_tree)?;
 
         Ok(())
     }
@@ -0,0 +1,206 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Speculative execution of a block before its parent is executed (e.g., while the parent is
+//! still being executed), on top of the result of the parent's parent, the base block.
+//!
+//! Similar to versioned reads in Block-STM, a speculative output is tagged with the block it was
+//! executed on, and records every key read from the state of that block. Once the parent is
+//! executed, the output is valid if the parent was executed on top of the base block and did not
+//! write any of the keys read. Otherwise the output is discarded and the block is executed
+//! again, so speculation never changes the result of execution.
+//!
+//! Unlike Block-STM, the block is not executed on top of the (partial) outputs of the parent in
+//! a multi-versioned data structure, and a conflict does not re-execute only the affected
+//! transactions: the parent is executed by a separate call into the VM, whose outputs only become
+//! available once the whole parent is executed, and a block is validated as a whole against them.
+//!
+//! Keys made hot by the parent are not conflicts, as they don't change any value read. The block
+//! epilogue of the speculative output records the slots of the keys it makes hot, which are read
+//! again from the state of the parent when the output is reused (see
+//! [DoGetExecutionOutput::by_speculative_execution]).
+
+use super::block_tree::{Block, BlockTree};
+use crate::{
+    logging::{LogEntry, LogSchema},
+    metrics::SPECULATIVE_BLOCK_EXECUTIONS,
+    workflow::do_get_execution_output::DoGetExecutionOutput,
+};
+use anyhow::Result;
+use aptos_crypto::HashValue;
+use aptos_executor_types::execution_output::ExecutionOutput;
+use aptos_experimental_runtimes::thread_manager::THREAD_MANAGER;
+use aptos_infallible::Mutex;
+use aptos_logger::prelude::*;
+use aptos_storage_interface::state_store::{
+    state::LedgerState,
+    state_view::cached_state_view::{CachedStateView, ShardedStateCache},
+};
+use aptos_types::{
+    state_store::{state_key::StateKey, TStateView},
+    transaction::{AuxiliaryInfo, Transaction, TransactionOutput},
+};
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+/// The raw output of a block executed on top of the base block.
+pub(crate) struct SpeculativeOutput {
+    base_block_id: HashValue,
+    transactions: Vec<Transaction>,
+    transaction_outputs: Vec<TransactionOutput>,
+    auxiliary_info: Vec<AuxiliaryInfo>,
+    /// All keys read from the state of the base block.
+    reads: HashSet<StateKey>,
+}
+
+impl SpeculativeOutput {
+    pub fn new(
+        base_block_id: HashValue,
+        transactions: Vec<Transaction>,
+        transaction_outputs: Vec<TransactionOutput>,
+        auxiliary_info: Vec<AuxiliaryInfo>,
+        reads: &ShardedStateCache,
+    ) -> Self {
+        let reads = reads
+            .shards
+            .iter()
+            .flat_map(|shard| shard.iter().map(|entry| entry.key().clone()))
+            .collect();
+        Self {
+            base_block_id,
+            transactions,
+            transaction_outputs,
+            auxiliary_info,
+            reads,
+        }
+    }
+
+    /// Checks whether the output is valid on top of the executed parent, and otherwise returns
+    /// the reason it has to be discarded.
+    fn validate(
+        &self,
+        base_block: Option<&Block>,
+        parent_block_id: HashValue,
+        parent_output: &ExecutionOutput,
+    ) -> Result<(), &'static str> {
+        // The parent must have been executed on top of the state the output was executed on.
+        match base_block {
+            Some(base_block) if base_block.ensure_has_child(parent_block_id).is_ok() => {},
+            _ => return Err("stale"),
+        }
+        // The block becomes a reconfiguration suffix, with no transactions to commit.
+        if parent_output.next_epoch_state.is_some() {
+            return Err("reconfig");
+        }
+        let has_conflict = parent_output
+            .to_commit
+            .transaction_outputs
+            .iter()
+            .flat_map(|output| output.write_set().write_op_iter())
+            .any(|(key, _)| self.reads.contains(key));
+        if has_conflict {
+            return Err("conflict");
+        }
+        Ok(())
+    }
+
+    /// Parses the output into the [ExecutionOutput] of the block on top of the parent.
+    pub fn into_execution_output(
+        self,
+        parent_state: &LedgerState,
+        state_view: CachedStateView,
+        is_block: bool,
+    ) -> Result<ExecutionOutput> {
+        // None of the keys were changed by the parent, but reading them again memorizes them
+        // (with their hotness in the parent state), same as when executing the block.
+        THREAD_MANAGER.get_io_pool().install(|| {
+            self.reads
+                .par_iter()
+                .try_for_each(|key| state_view.get_state_slot(key).map(|_| ()))
+        })?;
+
+        DoGetExecutionOutput::by_speculative_execution(
+            self.transactions,
+            self.transaction_outputs,
+            self.auxiliary_info,
+            parent_state,
+            state_view,
+            is_block,
+        )
+    }
+}
+
+/// Speculative outputs, keyed by the ids of the parent and the block.
+#[derive(Default)]
+pub(crate) struct SpeculativeOutputs {
+    outputs: Mutex<HashMap<(HashValue, HashValue), SpeculativeOutput>>,
+}
+
+impl SpeculativeOutputs {
+    pub fn insert(
+        &self,
+        parent_block_id: HashValue,
+        block_id: HashValue,
+        output: SpeculativeOutput,
+    ) {
+        self.outputs
+            .lock()
+            .insert((parent_block_id, block_id), output);
+    }
+
+    /// Removes the output of the block (if any), and returns it if it's valid on top of the
+    /// executed parent.
+    pub fn take_valid(
+        &self,
+        block_tree: &BlockTree,
+        parent_block_id: HashValue,
+        block_id: HashValue,
+        parent_output: &ExecutionOutput,
+    ) -> Result<Option<SpeculativeOutput>> {
+        let output = match self.outputs.lock().remove(&(parent_block_id, block_id)) {
+            Some(output) => output,
+            None => return Ok(None),
+        };
+        let base_block = block_tree
+            .get_blocks_opt(&[output.base_block_id])?
+            .pop()
+            .expect("Must exist.");
+
+        match output.validate(base_block.as_deref(), parent_block_id, parent_output) {
+            Ok(()) => {
+                SPECULATIVE_BLOCK_EXECUTIONS
+                    .with_label_values(&["reused"])
+                    .inc();
+                Ok(Some(output))
+            },
+            Err(outcome) => {
+                info!(
+                    LogSchema::new(LogEntry::BlockExecutor).block_id(block_id),
+                    outcome = outcome,
+                    "Discarded speculative output."
+                );
+                SPECULATIVE_BLOCK_EXECUTIONS
+                    .with_label_values(&[outcome])
+                    .inc();
+                Ok(None)
+            },
+        }
+    }
+
+    /// Drops the outputs executed on top of blocks which are no longer in the tree.
+    pub fn prune(&self, block_tree: &BlockTree) -> Result<()> {
+        let mut outputs = self.outputs.lock();
+        let base_block_ids = outputs
+            .values()
+            .map(|output| output.base_block_id)
+            .collect::<Vec<_>>();
+        let base_blocks = block_tree.get_blocks_opt(&base_block_ids)?;
+        let pruned = base_block_ids
+            .into_iter()
+            .zip(base_blocks)
+            .filter_map(|(id, block)| block.is_none().then_some(id))
+            .collect::<HashSet<_>>();
+        outputs.retain(|_, output| !pruned.contains(&output.base_block_id));
+        Ok(())
+    }
+}
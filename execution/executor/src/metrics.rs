@@ -162,6 +162,17 @@ pub static TRANSACTIONS_SAVED: Lazy<Histogram> = Lazy::new(|| {
 // EXECUTED TRANSACTION STATS COUNTERS
 //////////////////////////////////////
 
+/// Count of blocks executed speculatively on top of their unexecuted parent, by whether the
+/// speculative output was reused once the parent was executed.
+pub static SPECULATIVE_BLOCK_EXECUTIONS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_executor_speculative_block_executions",
+        "Count of blocks executed speculatively. outcome is reused, conflict, stale or reconfig",
+        &["outcome"]
+    )
+    .unwrap()
+});
+
 /// Count of the executed transactions since last restart.
 pub static PROCESSED_TXNS_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
     register_int_counter_vec!(
@@ -22,10 +22,10 @@ use aptos_types::{
     on_chain_config::{ConfigurationResource, ValidatorSet},
     state_store::{state_key::StateKey, StateView},
     transaction::{
-        signature_verified_transaction::SignatureVerifiedTransaction, BlockEndInfo, BlockOutput,
-        ChangeSet, ExecutionStatus, RawTransaction, Script, SignedTransaction, Transaction,
-        TransactionArgument, TransactionAuxiliaryData, TransactionExecutableRef, TransactionOutput,
-        TransactionStatus, WriteSetPayload,
+        signature_verified_transaction::SignatureVerifiedTransaction, BlockEndInfo,
+        BlockEndInfoExt, BlockOutput, ChangeSet, ExecutionStatus, FeeDistribution, RawTransaction,
+        Script, SignedTransaction, Transaction, TransactionArgument, TransactionAuxiliaryData,
+        TransactionExecutableRef, TransactionOutput, TransactionStatus, WriteSetPayload,
     },
     vm_status::{StatusCode, VMStatus},
     write_set::{WriteOp, WriteSet, WriteSetMut},
@@ -36,7 +36,10 @@ use aptos_vm::{
 };
 use move_core_types::language_storage::TypeTag;
 use once_cell::sync::Lazy;
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    sync::Arc,
+};
 
 #[derive(Debug)]
 enum MockVMTransaction {
@@ -76,6 +79,9 @@ impl VMBlockExecutor for MockVM {
         // output_cache is used to store the output of transactions so they are visible to later
         // transactions.
         let mut output_cache = HashMap::new();
+        // storage_reads is used to store the keys read from the storage, which are made hot by
+        // the block epilogue unless they are written by the block.
+        let mut storage_reads = BTreeSet::new();
         let mut outputs = vec![];
 
         let mut skip_rest = false;
@@ -134,9 +140,11 @@ impl VMBlockExecutor for MockVM {
 
             match decode_transaction(txn.try_as_signed_user_txn().unwrap()) {
                 MockVMTransaction::Mint { sender, amount } => {
-                    let old_balance = read_balance(&output_cache, state_view, sender);
+                    let old_balance =
+                        read_balance(&output_cache, &mut storage_reads, state_view, sender);
                     let new_balance = old_balance + amount;
-                    let old_seqnum = read_seqnum(&output_cache, state_view, sender);
+                    let old_seqnum =
+                        read_seqnum(&output_cache, &mut storage_reads, state_view, sender);
                     let new_seqnum = old_seqnum + 1;
 
                     output_cache.insert(balance_ap(sender), new_balance);
@@ -157,8 +165,10 @@ impl VMBlockExecutor for MockVM {
                     recipient,
                     amount,
                 } => {
-                    let sender_old_balance = read_balance(&output_cache, state_view, sender);
-                    let recipient_old_balance = read_balance(&output_cache, state_view, recipient);
+                    let sender_old_balance =
+                        read_balance(&output_cache, &mut storage_reads, state_view, sender);
+                    let recipient_old_balance =
+                        read_balance(&output_cache, &mut storage_reads, state_view, recipient);
                     if sender_old_balance < amount {
                         outputs.push(TransactionOutput::new(
                             WriteSet::default(),
@@ -170,7 +180,8 @@ impl VMBlockExecutor for MockVM {
                         continue;
                     }
 
-                    let sender_old_seqnum = read_seqnum(&output_cache, state_view, sender);
+                    let sender_old_seqnum =
+                        read_seqnum(&output_cache, &mut storage_reads, state_view, sender);
                     let sender_new_seqnum = sender_old_seqnum + 1;
                     let sender_new_balance = sender_old_balance - amount;
                     let recipient_new_balance = recipient_old_balance + amount;
@@ -201,9 +212,21 @@ impl VMBlockExecutor for MockVM {
         let mut block_epilogue_txn = None;
         if !skip_rest {
             if let Some(block_id) = transaction_slice_metadata.append_state_checkpoint_to_block() {
-                block_epilogue_txn = Some(Transaction::block_epilogue_v0(
+                let to_make_hot = storage_reads
+                    .into_iter()
+                    .filter(|access_path| !output_cache.contains_key(access_path))
+                    .map(|access_path| {
+                        let state_key = StateKey::raw(&access_path);
+                        let slot = state_view
+                            .get_state_slot(&state_key)
+                            .expect("Failed to query storage.");
+                        (state_key, slot)
+                    })
+                    .collect();
+                block_epilogue_txn = Some(Transaction::block_epilogue_v1(
                     block_id,
-                    BlockEndInfo::new_empty(),
+                    BlockEndInfoExt::new(BlockEndInfo::new_empty(), to_make_hot),
+                    FeeDistribution::new(BTreeMap::new()),
                 ));
                 outputs.push(TransactionOutput::new_empty_success());
             }
@@ -227,25 +250,35 @@ impl VMBlockExecutor for MockVM {
 
 fn read_balance(
     output_cache: &HashMap<Vec<u8>, u64>,
+    storage_reads: &mut BTreeSet<Vec<u8>>,
     state_view: &impl StateView,
     account: AccountAddress,
 ) -> u64 {
     let balance_access_path = balance_ap(account);
     match output_cache.get(&balance_access_path) {
         Some(balance) => *balance,
-        None => read_balance_from_storage(state_view, &balance_access_path),
+        None => {
+            let balance = read_balance_from_storage(state_view, &balance_access_path);
+            storage_reads.insert(balance_access_path);
+            balance
+        },
     }
 }
 
 fn read_seqnum(
     output_cache: &HashMap<Vec<u8>, u64>,
+    storage_reads: &mut BTreeSet<Vec<u8>>,
     state_view: &impl StateView,
     account: AccountAddress,
 ) -> u64 {
     let seqnum_access_path = seqnum_ap(account);
     match output_cache.get(&seqnum_access_path) {
         Some(seqnum) => *seqnum,
-        None => read_seqnum_from_storage(state_view, &seqnum_access_path),
+        None => {
+            let seqnum = read_seqnum_from_storage(state_view, &seqnum_access_path);
+            storage_reads.insert(seqnum_access_path);
+            seqnum
+        },
     }
 }
 
@@ -5,12 +5,14 @@
 use crate::{
     block_executor::BlockExecutor,
     db_bootstrapper::{generate_waypoint, maybe_bootstrap},
+    metrics::SPECULATIVE_BLOCK_EXECUTIONS,
     workflow::{do_get_execution_output::DoGetExecutionOutput, ApplyExecutionOutput},
 };
 use aptos_crypto::{ed25519::Ed25519PrivateKey, HashValue, PrivateKey, SigningKey, Uniform};
 use aptos_db::AptosDB;
 use aptos_executor_types::{
-    BlockExecutorTrait, ChunkExecutorTrait, TransactionReplayer, VerifyExecutionMode,
+    state_compute_result::StateComputeResult, BlockExecutorTrait, ChunkExecutorTrait,
+    TransactionReplayer, VerifyExecutionMode,
 };
 use aptos_storage_interface::{
     state_store::state_view::cached_state_view::CachedStateView, DbReaderWriter, LedgerSummary,
@@ -33,8 +35,8 @@ use aptos_types::{
         signature_verified_transaction::{
             into_signature_verified_block, SignatureVerifiedTransaction,
         },
-        AuxiliaryInfo, BlockEndInfo, ExecutionStatus, PersistedAuxiliaryInfo, RawTransaction,
-        Script, SignedTransaction, Transaction, TransactionAuxiliaryData,
+        AuxiliaryInfo, BlockEndInfoExt, ExecutionStatus, FeeDistribution, PersistedAuxiliaryInfo,
+        RawTransaction, Script, SignedTransaction, Transaction, TransactionAuxiliaryData,
         TransactionListWithProofV2, TransactionOutput, TransactionPayload, TransactionStatus,
         Version,
     },
@@ -47,7 +49,7 @@ use mock_vm::{
     MockVM, DISCARD_STATUS, KEEP_STATUS,
 };
 use proptest::prelude::*;
-use std::iter::once;
+use std::{collections::BTreeMap, iter::once};
 
 mod chunk_executor_tests;
 #[cfg(test)]
@@ -338,6 +340,101 @@ fn test_executor_execute_same_block_multiple_times() {
     );
 }
 
+/// Executes two blocks in order, optionally executing the second one speculatively before the
+/// first, and returns the result of the second block.
+fn execute_two_blocks(
+    block1_txns: Vec<Transaction>,
+    block2_txns: Vec<Transaction>,
+    speculate: bool,
+) -> StateComputeResult {
+    let executor = TestExecutor::new();
+    let parent_block_id = executor.committed_block_id();
+    let block1_id = gen_block_id(1);
+    let block2_id = gen_block_id(2);
+
+    if speculate {
+        executor
+            .execute_speculatively(
+                (block2_id, block(block2_txns.clone())).into(),
+                block1_id,
+                parent_block_id,
+                TEST_BLOCK_EXECUTOR_ONCHAIN_CONFIG,
+            )
+            .unwrap();
+    }
+    executor
+        .execute_block(
+            (block1_id, block(block1_txns)).into(),
+            parent_block_id,
+            TEST_BLOCK_EXECUTOR_ONCHAIN_CONFIG,
+        )
+        .unwrap();
+    executor
+        .execute_block(
+            (block2_id, block(block2_txns)).into(),
+            block1_id,
+            TEST_BLOCK_EXECUTOR_ONCHAIN_CONFIG,
+        )
+        .unwrap()
+}
+
+#[test]
+#[cfg_attr(feature = "consensus-only-perf-test", ignore)]
+fn test_executor_speculative_execution() {
+    let block1_txns = (0..10)
+        .map(|i| encode_mint_transaction(gen_address(i), 100))
+        .chain(once(
+            // Discarded for insufficient balance, so the balances are read but not written, and
+            // made hot by the block epilogue.
+            encode_transfer_transaction(gen_address(30), gen_address(31), 50),
+        ))
+        .collect::<Vec<_>>();
+    // Doesn't read any state written by the first block, so the speculative output is reused.
+    let independent_txns = (10..20)
+        .map(|i| encode_mint_transaction(gen_address(i), 100))
+        .collect::<Vec<_>>();
+    // Reads a balance the first block made hot but did not write, so the speculative output is
+    // reused, but the slot made hot by its epilogue has to come from the state after the first
+    // block.
+    let hot_read_txns = vec![encode_transfer_transaction(
+        gen_address(30),
+        gen_address(32),
+        50,
+    )];
+    // Spends the balances minted by the first block, so the speculative output is discarded.
+    let dependent_txns = (0..10)
+        .map(|i| encode_transfer_transaction(gen_address(i), gen_address(i + 10), 50))
+        .collect::<Vec<_>>();
+
+    for (block2_txns, outcome) in [
+        (independent_txns, "reused"),
+        (hot_read_txns, "reused"),
+        (dependent_txns, "conflict"),
+    ] {
+        let count = SPECULATIVE_BLOCK_EXECUTIONS
+            .with_label_values(&[outcome])
+            .get();
+        let speculative = execute_two_blocks(block1_txns.clone(), block2_txns.clone(), true);
+        let expected = execute_two_blocks(block1_txns.clone(), block2_txns, false);
+        assert_eq!(speculative.root_hash(), expected.root_hash());
+        // The block epilogue, including the slots to make hot, which are not hashed.
+        assert_eq!(
+            speculative.transactions_to_commit().last(),
+            expected.transactions_to_commit().last(),
+        );
+        assert_eq!(
+            speculative.as_chunk_to_commit().transaction_outputs.last(),
+            expected.as_chunk_to_commit().transaction_outputs.last(),
+        );
+        assert_eq!(
+            SPECULATIVE_BLOCK_EXECUTIONS
+                .with_label_values(&[outcome])
+                .get(),
+            count + 1
+        );
+    }
+}
+
 fn create_blocks_and_chunks(
     block_ranges: Vec<std::ops::RangeInclusive<Version>>,
     chunk_ranges: Vec<std::ops::RangeInclusive<Version>>,
@@ -893,9 +990,9 @@ proptest! {
         let expected_root_hash = run_transactions_naive({
             let mut txns = vec![];
             txns.extend(block_a.txns.iter().cloned());
-            txns.push(SignatureVerifiedTransaction::Valid(Transaction::block_epilogue_v0(block_a.id, BlockEndInfo::new_empty())));
+            txns.push(SignatureVerifiedTransaction::Valid(Transaction::block_epilogue_v1(block_a.id, BlockEndInfoExt::new_empty(), FeeDistribution::new(BTreeMap::new()))));
             txns.extend(block_b.txns.iter().cloned());
-            txns.push(SignatureVerifiedTransaction::Valid(Transaction::block_epilogue_v0(block_b.id, BlockEndInfo::new_empty())));
+            txns.push(SignatureVerifiedTransaction::Valid(Transaction::block_epilogue_v1(block_b.id, BlockEndInfoExt::new_empty(), FeeDistribution::new(BTreeMap::new()))));
             txns
         }, TEST_BLOCK_EXECUTOR_ONCHAIN_CONFIG);
 
@@ -109,15 +109,50 @@ impl DoGetExecutionOutput {
         onchain_config: BlockExecutorConfigFromOnchain,
         transaction_slice_metadata: TransactionSliceMetadata,
     ) -> Result<ExecutionOutput> {
+        let (transactions, mut transaction_outputs, auxiliary_info) = Self::execute_unsharded::<V>(
+            executor,
+            transactions,
+            auxiliary_info,
+            &state_view,
+            onchain_config,
+            transaction_slice_metadata,
+        )?;
+        Self::add_block_epilogue_hotness(&transactions, &mut transaction_outputs);
+
+        Parser::parse(
+            state_view.next_version(),
+            transactions,
+            transaction_outputs,
+            auxiliary_info,
+            parent_state,
+            state_view,
+            false, // prime_state_cache
+            transaction_slice_metadata
+                .append_state_checkpoint_to_block()
+                .is_some(),
+        )
+    }
+
+    /// Executes the block using the [VMBlockExecutor], and returns the transactions (including
+    /// the block epilogue, if any), their raw outputs and auxiliary info, without parsing them
+    /// into an [ExecutionOutput].
+    pub fn execute_unsharded<V: VMBlockExecutor>(
+        executor: &V,
+        transactions: Vec<SignatureVerifiedTransaction>,
+        auxiliary_info: Vec<AuxiliaryInfo>,
+        state_view: &CachedStateView,
+        onchain_config: BlockExecutorConfigFromOnchain,
+        transaction_slice_metadata: TransactionSliceMetadata,
+    ) -> Result<(Vec<Transaction>, Vec<TransactionOutput>, Vec<AuxiliaryInfo>)> {
         let txn_provider = DefaultTxnProvider::new(transactions, auxiliary_info);
         let block_output = Self::execute_block::<V>(
             executor,
             &txn_provider,
-            &state_view,
+            state_view,
             onchain_config,
             transaction_slice_metadata,
         )?;
-        let (mut transaction_outputs, block_epilogue_txn) = block_output.into_inner();
+        let (transaction_outputs, block_epilogue_txn) = block_output.into_inner();
         let (transactions, mut auxiliary_info) = txn_provider.into_inner();
         let mut transactions = transactions
             .into_iter()
@@ -128,18 +163,79 @@ impl DoGetExecutionOutput {
             // TODO(grao): Double check if we want to put anything into AuxiliaryInfo here.
             auxiliary_info.push(AuxiliaryInfo::new_empty());
         }
+        Ok((transactions, transaction_outputs, auxiliary_info))
+    }
 
-        // Manually create hotness write sets for block epilogue transaction(s), based on the block
-        // end info saved. Note that even if we are re-executing transactions during a state sync,
-        // the block end info is not re-computed and has to come from the previous execution.
-        //
-        // If the input transactions are from a normal block, the last one should be the epilogue.
-        // If they are from a chunk (i.e. we are re-executing transactions during state sync), then
-        // there could be zero or more block epilogue transactions, and we need to handle all of
-        // them.
-        //
-        // TODO(HotState): it might be better to do this in AptosVM::execute_single_transaction,
-        // but we need to figure out how to properly construct `VMOutput` from block end info.
+    /// Parses the raw outputs of a block executed speculatively (see
+    /// [DoGetExecutionOutput::execute_unsharded]) on top of a different state than
+    /// `parent_state`, after the caller verified that none of the state read by the block was
+    /// changed in between. The `state_view` must be on top of `parent_state`.
+    pub fn by_speculative_execution(
+        mut transactions: Vec<Transaction>,
+        mut transaction_outputs: Vec<TransactionOutput>,
+        auxiliary_info: Vec<AuxiliaryInfo>,
+        parent_state: &LedgerState,
+        state_view: CachedStateView,
+        is_block: bool,
+    ) -> Result<ExecutionOutput> {
+        // The block end info was computed on the speculative state, where the parent had not
+        // made any keys hot yet. The set of keys to make hot only depends on the block itself,
+        // but their slots are re-read from the actual parent state, so that the epilogue is the
+        // same as if the block was executed on top of the parent.
+        for transaction in transactions.iter_mut() {
+            if let Transaction::BlockEpilogue(payload) = transaction {
+                for (key, slot) in payload
+                    .try_get_slots_to_make_hot_mut()
+                    .into_iter()
+                    .flatten()
+                {
+                    *slot = state_view.get_state_slot(key)?;
+                }
+            }
+        }
+        Self::add_block_epilogue_hotness(&transactions, &mut transaction_outputs);
+
+        let out = Parser::parse(
+            state_view.next_version(),
+            transactions,
+            transaction_outputs,
+            auxiliary_info,
+            parent_state,
+            state_view,
+            false, // prime_state_cache
+            is_block,
+        )?;
+
+        let ret = out.clone();
+        THREAD_MANAGER.get_background_pool().spawn(move || {
+            let _timer = OTHER_TIMERS.timer_with(&["async_update_counters__by_speculation"]);
+            for x in [&out.to_commit, &out.to_retry, &out.to_discard] {
+                metrics::update_counters_for_processed_chunk(
+                    &x.transactions,
+                    &x.transaction_outputs,
+                    "speculation",
+                )
+            }
+        });
+
+        Ok(ret)
+    }
+
+    /// Manually creates hotness write sets for block epilogue transaction(s), based on the block
+    /// end info saved. Note that even if we are re-executing transactions during a state sync,
+    /// the block end info is not re-computed and has to come from the previous execution.
+    ///
+    /// If the input transactions are from a normal block, the last one should be the epilogue.
+    /// If they are from a chunk (i.e. we are re-executing transactions during state sync), then
+    /// there could be zero or more block epilogue transactions, and we need to handle all of
+    /// them.
+    ///
+    /// TODO(HotState): it might be better to do this in AptosVM::execute_single_transaction,
+    /// but we need to figure out how to properly construct `VMOutput` from block end info.
+    fn add_block_epilogue_hotness(
+        transactions: &[Transaction],
+        transaction_outputs: &mut [TransactionOutput],
+    ) {
         for (transaction, output) in transactions.iter().zip_eq(transaction_outputs.iter_mut()) {
             if let Transaction::BlockEpilogue(payload) = transaction {
                 assert!(output.status().is_kept(), "Block epilogue must be kept");
@@ -154,19 +250,6 @@ impl DoGetExecutionOutput {
                 );
             }
         }
-
-        Parser::parse(
-            state_view.next_version(),
-            transactions,
-            transaction_outputs,
-            auxiliary_info,
-            parent_state,
-            state_view,
-            false, // prime_state_cache
-            transaction_slice_metadata
-                .append_state_checkpoint_to_block()
-                .is_some(),
-        )
     }
 
     pub fn by_transaction_execution_sharded<V: VMBlockExecutor>(
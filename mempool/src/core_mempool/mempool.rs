@@ -38,6 +38,10 @@ pub struct Mempool {
     pub(crate) transactions: TransactionStore,
 
     pub system_transaction_timeout: Duration,
+
+    // The number of transactions ready to be included in a block, and their gas ranking scores at
+    // every percentile (from 0 to 100), updated periodically.
+    backlog_gas_price_percentiles: (u64, Vec<u64>),
 }
 
 impl Mempool {
@@ -47,6 +51,7 @@ impl Mempool {
             system_transaction_timeout: Duration::from_secs(
                 config.mempool.system_transaction_timeout_secs,
             ),
+            backlog_gas_price_percentiles: (0, vec![]),
         }
     }
 
@@ -661,4 +666,48 @@ impl Mempool {
     pub fn get_parking_lot_addresses(&self) -> Vec<(AccountAddress, u64)> {
         self.transactions.get_parking_lot_addresses()
     }
+
+    /// Recomputes the number of transactions that are ready to be included in a block, and their
+    /// gas ranking scores (i.e., gas unit prices) at every percentile. Ready transactions include
+    /// the ones already pulled by consensus but not committed yet. This iterates over all of
+    /// them, so it is done periodically rather than on every request.
+    pub fn update_backlog_gas_price_percentiles(&mut self) {
+        let num_txns = self.transactions.iter_queue().len();
+        let mut prices = vec![];
+        if num_txns > 0 {
+            // The queue is ordered from the highest to the lowest score, so the p-th percentile is
+            // at position (100 - p)% from the front, and the percentiles are found from the
+            // highest to the lowest.
+            let mut percentiles = (0..=100usize)
+                .rev()
+                .map(|percentile| (num_txns - 1 - (num_txns - 1) * percentile / 100))
+                .peekable();
+            for (position, key) in self.transactions.iter_queue().enumerate() {
+                while percentiles.next_if_eq(&position).is_some() {
+                    prices.push(key.gas_ranking_score);
+                }
+                if percentiles.peek().is_none() {
+                    break;
+                }
+            }
+            prices.reverse();
+        }
+        self.backlog_gas_price_percentiles = (num_txns as u64, prices);
+    }
+
+    /// Returns the number of transactions that were ready to be included in a block, and their gas
+    /// ranking scores at the given percentiles, as of the last
+    /// [Mempool::update_backlog_gas_price_percentiles]. The percentiles are empty if there were no
+    /// ready transactions.
+    pub fn get_backlog_gas_price_percentiles(&self, percentiles: &[u8]) -> (u64, Vec<u64>) {
+        let (num_txns, prices) = &self.backlog_gas_price_percentiles;
+        if prices.is_empty() {
+            return (0, vec![]);
+        }
+        let prices = percentiles
+            .iter()
+            .map(|percentile| prices[(*percentile).min(100) as usize])
+            .collect();
+        (*num_txns, prices)
+    }
 }
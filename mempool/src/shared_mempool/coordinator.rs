@@ -222,6 +222,15 @@ async fn handle_client_request<NetworkClient, TransactionValidator>(
                 .spawn(tasks::process_parking_lot_addresses(smp.clone(), callback))
                 .await;
         },
+        MempoolClientRequest::GetBacklogGasPricePercentiles(percentiles, callback) => {
+            bounded_executor
+                .spawn(tasks::process_backlog_gas_price_percentiles(
+                    smp.clone(),
+                    percentiles,
+                    callback,
+                ))
+                .await;
+        },
     }
 }
 
@@ -460,6 +469,18 @@ pub(crate) async fn gc_coordinator(mempool: Arc<Mutex<CoreMempool>>, gc_interval
     ));
 }
 
+/// Periodically updates the gas unit price percentiles of the transactions ready to be included
+/// in a block, which are requested for gas estimation.
+pub(crate) async fn backlog_gas_price_job(
+    mempool: Arc<Mutex<CoreMempool>>,
+    update_interval_ms: u64,
+) {
+    let mut interval = IntervalStream::new(interval(Duration::from_millis(update_interval_ms)));
+    while let Some(_interval) = interval.next().await {
+        mempool.lock().update_backlog_gas_price_percentiles();
+    }
+}
+
 /// Periodically logs a snapshot of transactions in core mempool.
 /// In the future we may want an interactive way to directly query mempool's internal state.
 /// For now, we will rely on this periodic snapshot to observe the internal state.
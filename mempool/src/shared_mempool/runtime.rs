@@ -6,7 +6,7 @@ use crate::{
     core_mempool::CoreMempool,
     network::MempoolSyncMsg,
     shared_mempool::{
-        coordinator::{coordinator, gc_coordinator, snapshot_job},
+        coordinator::{backlog_gas_price_job, coordinator, gc_coordinator, snapshot_job},
         types::{MempoolEventsReceiver, SharedMempool, SharedMempoolNotification},
     },
     QuorumStoreRequest,
@@ -32,6 +32,8 @@ use tokio::runtime::{Handle, Runtime};
 ///   - outbound_sync_task (task that periodically broadcasts transactions to peers).
 ///   - inbound_network_task (task that handles inbound mempool messages and network events).
 ///   - gc_task (task that performs GC of all expired transactions by SystemTTL).
+///   - backlog_gas_price_task (task that periodically updates the gas unit prices of the
+///     transactions ready for consensus).
 pub(crate) fn start_shared_mempool<TransactionValidator, ConfigProvider>(
     executor: &Handle,
     config: &NodeConfig,
@@ -81,6 +83,11 @@ pub(crate) fn start_shared_mempool<TransactionValidator, ConfigProvider>(
         config.mempool.system_transaction_gc_interval_ms,
     ));
 
+    executor.spawn(backlog_gas_price_job(
+        mempool.clone(),
+        config.mempool.backlog_gas_price_update_interval_ms,
+    ));
+
     if aptos_logger::enabled!(Level::Trace) {
         executor.spawn(snapshot_job(
             mempool,
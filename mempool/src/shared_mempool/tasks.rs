@@ -184,6 +184,29 @@ pub(crate) async fn process_parking_lot_addresses<NetworkClient, TransactionVali
     }
 }
 
+/// Processes a request for the gas unit prices of the transactions ready for consensus.
+pub(crate) async fn process_backlog_gas_price_percentiles<NetworkClient, TransactionValidator>(
+    smp: SharedMempool<NetworkClient, TransactionValidator>,
+    percentiles: Vec<u8>,
+    callback: oneshot::Sender<(u64, Vec<u64>)>,
+) where
+    NetworkClient: NetworkClientInterface<MempoolSyncMsg>,
+    TransactionValidator: TransactionValidation + 'static,
+{
+    let backlog = smp
+        .mempool
+        .lock()
+        .get_backlog_gas_price_percentiles(&percentiles);
+
+    if callback.send(backlog).is_err() {
+        warn!(LogSchema::event_log(
+            LogEntry::JsonRpc,
+            LogEvent::CallbackFail
+        ));
+        counters::CLIENT_CALLBACK_FAIL.inc();
+    }
+}
+
 /// Processes get transaction by hash request by client.
 pub(crate) async fn process_client_get_transaction<NetworkClient, TransactionValidator>(
     smp: SharedMempool<NetworkClient, TransactionValidator>,
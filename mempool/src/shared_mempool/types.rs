@@ -246,6 +246,10 @@ pub enum MempoolClientRequest {
     /// Retrieves all addresses with transactions in the mempool's parking lot and
     /// the number of transactions for each address
     GetAddressesFromParkingLot(oneshot::Sender<Vec<(AccountAddress, u64)>>),
+    /// Retrieves the number of transactions that are ready to be included in a block (including
+    /// the ones already pulled by consensus but not committed yet), and their gas unit prices at
+    /// the given percentiles, as last updated periodically
+    GetBacklogGasPricePercentiles(Vec<u8>, oneshot::Sender<(u64, Vec<u64>)>),
 }
 
 pub type MempoolClientSender = mpsc::Sender<MempoolClientRequest>;
@@ -1131,6 +1131,32 @@ fn signed_txn_to_mempool_transaction(txn: SignedTransaction) -> MempoolTransacti
     )
 }
 
+#[test]
+fn test_backlog_gas_price_percentiles() {
+    let (mut pool, _) = setup_mempool();
+    pool.update_backlog_gas_price_percentiles();
+    assert_eq!(pool.get_backlog_gas_price_percentiles(&[50]), (0, vec![]));
+
+    let mut txns: Vec<_> = (0..10)
+        .map(|i| TestTransaction::new(0, ReplayProtector::SequenceNumber(i), i + 1))
+        .collect();
+    // Transactions in the parking lot are not part of the backlog.
+    txns.push(TestTransaction::new(
+        1,
+        ReplayProtector::SequenceNumber(5),
+        100,
+    ));
+    add_txns_to_mempool(&mut pool, txns);
+
+    // The percentiles are only updated periodically.
+    assert_eq!(pool.get_backlog_gas_price_percentiles(&[50]), (0, vec![]));
+    pool.update_backlog_gas_price_percentiles();
+    assert_eq!(
+        pool.get_backlog_gas_price_percentiles(&[90, 0, 50, 100]),
+        (10, vec![9, 1, 5, 10])
+    );
+}
+
 #[test]
 fn test_parking_lot_eviction() {
     let mut config = NodeConfig::generate_random_config();
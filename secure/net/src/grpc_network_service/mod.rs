@@ -1,7 +1,10 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::network_controller::{metrics::NETWORK_HANDLER_TIMER, Message, MessageType};
+use crate::network_controller::{
+    metrics::{NETWORK_HANDLER_TIMER, UNDELIVERABLE_MESSAGES},
+    Message, MessageType,
+};
 use aptos_logger::{error, info};
 use aptos_protos::remote_executor::v1::{
     network_message_service_client::NetworkMessageServiceClient,
@@ -13,6 +16,7 @@ use std::{
     collections::HashMap,
     net::SocketAddr,
     sync::{Arc, Mutex},
+    time::Duration,
 };
 use tokio::{runtime::Runtime, sync::oneshot};
 use tonic::{
@@ -21,6 +25,9 @@ use tonic::{
 };
 
 const MAX_MESSAGE_SIZE: usize = 1024 * 1024 * 80;
+// Bounds the time the (single) outbound task can be blocked on an unreachable remote node.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
 
 pub struct GRPCNetworkMessageServiceServerWrapper {
     inbound_handlers: Arc<Mutex<HashMap<MessageType, Sender<Message>>>>,
@@ -118,14 +125,18 @@ impl NetworkMessageService for GRPCNetworkMessageServiceServerWrapper {
 pub struct GRPCNetworkMessageServiceClientWrapper {
     remote_addr: String,
     remote_channel: NetworkMessageServiceClient<Channel>,
+    drop_undeliverable_messages: bool,
 }
 
 impl GRPCNetworkMessageServiceClientWrapper {
-    pub fn new(rt: &Runtime, remote_addr: SocketAddr) -> Self {
+    /// If `drop_undeliverable_messages` is set, messages which cannot be sent to the remote node
+    /// (e.g., because it crashed) are dropped, otherwise sending them panics.
+    pub fn new(rt: &Runtime, remote_addr: SocketAddr, drop_undeliverable_messages: bool) -> Self {
         Self {
             remote_addr: remote_addr.to_string(),
             remote_channel: rt
                 .block_on(async { Self::get_channel(format!("http://{}", remote_addr)).await }),
+            drop_undeliverable_messages,
         }
     }
 
@@ -133,6 +144,8 @@ impl GRPCNetworkMessageServiceClientWrapper {
         info!("Trying to connect to remote server at {:?}", remote_addr);
         let conn = tonic::transport::Endpoint::new(remote_addr)
             .unwrap()
+            .connect_timeout(CONNECT_TIMEOUT)
+            .timeout(REQUEST_TIMEOUT)
             .connect_lazy();
         NetworkMessageServiceClient::new(conn).max_decoding_message_size(MAX_MESSAGE_SIZE)
     }
@@ -148,14 +161,26 @@ impl GRPCNetworkMessageServiceClientWrapper {
             message_type: mt.get_type(),
         });
         // TODO: Retry with exponential backoff on failures
-        match self.remote_channel.simple_msg_exchange(request).await {
-            Ok(_) => {},
-            Err(e) => {
+        if let Err(e) = self.remote_channel.simple_msg_exchange(request).await {
+            // If dropped, the receiver has to handle lost messages (e.g., with timeouts).
+            // Panicking stops the outbound task, and with it all the messages to the other
+            // remote nodes.
+            if !self.drop_undeliverable_messages {
                 panic!(
                     "Error '{}' sending message to {} on node {:?}",
                     e, self.remote_addr, sender_addr
                 );
-            },
+            }
+            UNDELIVERABLE_MESSAGES
+                .with_label_values(&[&self.remote_addr, &mt.get_type()])
+                .inc();
+            error!(
+                "Error '{}' sending message of type {:?} to {} on node {:?}, dropping it",
+                e,
+                mt.get_type(),
+                self.remote_addr,
+                sender_addr
+            );
         }
     }
 }
@@ -190,7 +215,7 @@ fn basic_test() {
         server_shutdown_rx,
     );
 
-    let mut grpc_client = GRPCNetworkMessageServiceClientWrapper::new(&rt, server_addr);
+    let mut grpc_client = GRPCNetworkMessageServiceClientWrapper::new(&rt, server_addr, false);
 
     let client_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), utils::get_available_port());
     let test_message_content = "test1".as_bytes().to_vec();
@@ -1,7 +1,10 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use aptos_metrics_core::{exponential_buckets, register_histogram_vec, HistogramVec};
+use aptos_metrics_core::{
+    exponential_buckets, register_histogram_vec, register_int_counter_vec, HistogramVec,
+    IntCounterVec,
+};
 use once_cell::sync::Lazy;
 
 pub static NETWORK_HANDLER_TIMER: Lazy<HistogramVec> = Lazy::new(|| {
@@ -18,3 +21,15 @@ pub static NETWORK_HANDLER_TIMER: Lazy<HistogramVec> = Lazy::new(|| {
     )
     .unwrap()
 });
+
+pub static UNDELIVERABLE_MESSAGES: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        // metric name
+        "network_undeliverable_messages",
+        // metric description
+        "The number of messages which could not be sent to remote nodes, and were dropped",
+        // metric labels (dimensions)
+        &["remote_addr", "message_type"],
+    )
+    .unwrap()
+});
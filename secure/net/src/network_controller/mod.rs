@@ -111,6 +111,14 @@ impl NetworkController {
         }
     }
 
+    /// If set, messages which cannot be sent to a remote node (e.g., because it crashed) are
+    /// logged and dropped, instead of panicking the task sending messages to all remote nodes.
+    /// Disabled by default, and has to be set before the network controller is started.
+    pub fn set_drop_undeliverable_messages(&mut self, drop_undeliverable_messages: bool) {
+        self.outbound_handler
+            .set_drop_undeliverable_messages(drop_undeliverable_messages);
+    }
+
     pub fn create_outbound_channel(
         &mut self,
         remote_peer_addr: SocketAddr,
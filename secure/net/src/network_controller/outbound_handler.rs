@@ -24,6 +24,7 @@ pub struct OutboundHandler {
     // Used to route outgoing messages to correct network client with the correct message type
     handlers: Vec<(Receiver<Message>, SocketAddr, MessageType)>,
     inbound_handler: Arc<Mutex<InboundHandler>>,
+    drop_undeliverable_messages: bool,
 }
 
 impl OutboundHandler {
@@ -38,9 +39,14 @@ impl OutboundHandler {
             address: listen_addr,
             handlers: Vec::new(),
             inbound_handler,
+            drop_undeliverable_messages: false,
         }
     }
 
+    pub fn set_drop_undeliverable_messages(&mut self, drop_undeliverable_messages: bool) {
+        self.drop_undeliverable_messages = drop_undeliverable_messages;
+    }
+
     pub fn register_handler(
         &mut self,
         message_type: String,
@@ -71,7 +77,11 @@ impl OutboundHandler {
         self.remote_addresses.iter().for_each(|remote_addr| {
             grpc_clients.insert(
                 *remote_addr,
-                GRPCNetworkMessageServiceClientWrapper::new(rt, *remote_addr),
+                GRPCNetworkMessageServiceClientWrapper::new(
+                    rt,
+                    *remote_addr,
+                    self.drop_undeliverable_messages,
+                ),
             );
         });
 
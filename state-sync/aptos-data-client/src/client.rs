@@ -43,9 +43,9 @@ use aptos_storage_service_types::{
         TransactionsOrOutputsWithProofRequest, TransactionsWithProofRequest,
     },
     responses::{StorageServerSummary, StorageServiceResponse, TransactionOrOutputListWithProofV2},
-    Epoch, StorageServiceMessage,
+    Epoch, StorageServiceError, StorageServiceMessage,
 };
-use aptos_time_service::TimeService;
+use aptos_time_service::{TimeService, TimeServiceTrait};
 use aptos_types::{
     epoch_change::EpochChangeProof,
     ledger_info::LedgerInfoWithSignatures,
@@ -57,8 +57,9 @@ use async_trait::async_trait;
 use futures::{stream::FuturesUnordered, StreamExt};
 use maplit::hashset;
 use std::{
+    any::Any,
     cmp::min,
-    collections::{BTreeMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     fmt,
     ops::Deref,
     sync::Arc,
@@ -99,6 +100,9 @@ pub struct AptosDataClient {
     active_subscription_state: Arc<Mutex<Option<SubscriptionState>>>,
     /// All of the data-client specific data we have on each network peer.
     peer_states: Arc<PeerStates>,
+    /// The peers currently servicing each in-flight state value request. Used
+    /// to send a resent (i.e., stolen) request to different peers.
+    in_flight_state_value_requests: Arc<Mutex<HashMap<DataRequest, HashSet<PeerNetworkId>>>>,
     /// A cached, aggregate data summary of all unbanned peers' data summaries.
     global_summary_cache: Arc<ArcSwap<GlobalDataSummary>>,
     /// Used for generating the next request/response id.
@@ -127,6 +131,7 @@ impl AptosDataClient {
             storage_service_client: storage_service_client.clone(),
             active_subscription_state: Arc::new(Mutex::new(None)),
             peer_states: Arc::new(PeerStates::new(data_client_config.clone())),
+            in_flight_state_value_requests: Arc::new(Mutex::new(HashMap::new())),
             global_summary_cache: Arc::new(ArcSwap::from(Arc::new(GlobalDataSummary::empty()))),
             response_id_generator: Arc::new(U64IdGenerator::new()),
             time_service: time_service.clone(),
@@ -279,6 +284,10 @@ impl AptosDataClient {
             serviceable_peers_by_priorities.push(peers);
         }
 
+        // Avoid the peers already servicing the same request (e.g., if the
+        // request is being resent because those peers are slow to respond)
+        self.remove_peers_servicing_request(request, &mut serviceable_peers_by_priorities);
+
         // If the request is a subscription request, select a single
         // peer (as we can only subscribe to a single peer at a time).
         if request.data_request.is_subscription_request() {
@@ -559,6 +568,34 @@ impl AptosDataClient {
             .collect()
     }
 
+    /// Removes the peers that are currently servicing an identical state value
+    /// request from the given serviceable peers. If no other peers can service
+    /// the request, the serviceable peers are left unchanged.
+    fn remove_peers_servicing_request(
+        &self,
+        request: &StorageServiceRequest,
+        serviceable_peers_by_priorities: &mut [HashSet<PeerNetworkId>],
+    ) {
+        let in_flight_peers = match self
+            .in_flight_state_value_requests
+            .lock()
+            .get(&request.data_request)
+        {
+            Some(in_flight_peers) => in_flight_peers.clone(),
+            None => return, // The request isn't in-flight
+        };
+
+        let has_other_peers = serviceable_peers_by_priorities
+            .iter()
+            .flatten()
+            .any(|peer| !in_flight_peers.contains(peer));
+        if has_other_peers {
+            for peers in serviceable_peers_by_priorities.iter_mut() {
+                peers.retain(|peer| !in_flight_peers.contains(peer));
+            }
+        }
+    }
+
     /// Returns all peers connected to us
     fn get_all_connected_peers(&self) -> crate::error::Result<HashSet<PeerNetworkId>, Error> {
         let connected_peers = self.storage_service_client.get_available_peers()?;
@@ -659,8 +696,10 @@ impl AptosDataClient {
         for peer in peers {
             // Send the request to the peer
             let aptos_data_client = self.clone();
-            let request = request.clone();
+            let in_flight_request = self.track_in_flight_request(peer, &request);
+            let request = self.adapt_request_for_peer(peer, &request);
             let sent_request = tokio::spawn(async move {
+                let _in_flight_request = in_flight_request; // Dropped when the task ends
                 aptos_data_client
                     .send_request_to_peer_and_decode(peer, request, request_timeout_ms)
                     .await
@@ -701,6 +740,87 @@ impl AptosDataClient {
         )))
     }
 
+    /// Tracks the given state value request as in-flight to the specified peer,
+    /// until the returned guard is dropped. Other requests are not tracked.
+    pub(crate) fn track_in_flight_request(
+        &self,
+        peer: PeerNetworkId,
+        request: &StorageServiceRequest,
+    ) -> Option<InFlightRequestGuard> {
+        if !matches!(
+            request.data_request,
+            DataRequest::GetStateValuesWithProof(_)
+        ) {
+            return None;
+        }
+
+        self.in_flight_state_value_requests
+            .lock()
+            .entry(request.data_request.clone())
+            .or_default()
+            .insert(peer);
+        Some(InFlightRequestGuard {
+            in_flight_requests: self.in_flight_state_value_requests.clone(),
+            data_request: request.data_request.clone(),
+            peer,
+        })
+    }
+
+    /// Adapts the given request to the specified peer. For state value requests,
+    /// this reduces the number of requested state values to what the peer can
+    /// serve within the target response time (based on the peer's observed
+    /// throughput and latency). Note: the data streaming service will request
+    /// any missing state values if the response is smaller than expected.
+    fn adapt_request_for_peer(
+        &self,
+        peer: PeerNetworkId,
+        request: &StorageServiceRequest,
+    ) -> StorageServiceRequest {
+        // Only adapt state value requests (if adaptive chunking is enabled)
+        if !self
+            .data_client_config
+            .adaptive_chunking_config
+            .enable_adaptive_chunking
+        {
+            return request.clone();
+        }
+        let state_values_request = match &request.data_request {
+            DataRequest::GetStateValuesWithProof(state_values_request) => state_values_request,
+            _ => return request.clone(),
+        };
+
+        // Calculate the adaptive chunk size for the peer
+        let requested_chunk_size = state_values_request
+            .end_index
+            .saturating_sub(state_values_request.start_index)
+            .saturating_add(1);
+        let adaptive_chunk_size = self.peer_states.get_adaptive_state_chunk_size(
+            &peer,
+            requested_chunk_size,
+            self.get_latency_for_peer(peer),
+        );
+        if adaptive_chunk_size >= requested_chunk_size {
+            return request.clone(); // The peer can serve the entire request
+        }
+
+        // Reduce the number of requested state values
+        let end_index = state_values_request
+            .start_index
+            .saturating_add(adaptive_chunk_size)
+            .saturating_sub(1);
+        let data_request = DataRequest::GetStateValuesWithProof(StateValuesWithProofRequest {
+            end_index,
+            ..state_values_request.clone()
+        });
+        StorageServiceRequest::new(data_request, request.use_compression)
+    }
+
+    /// Returns the measured latency for the given peer (if one exists)
+    fn get_latency_for_peer(&self, peer: PeerNetworkId) -> Option<Duration> {
+        utils::get_latency_for_peer(&self.get_peers_and_metadata(), peer)
+            .map(Duration::from_secs_f64)
+    }
+
     /// Sends a request to a specific peer and decodes the response
     pub async fn send_request_to_peer_and_decode<T, E>(
         &self,
@@ -716,6 +836,7 @@ impl AptosDataClient {
         let timer = start_request_timer(&metrics::REQUEST_LATENCIES, &request.get_label(), peer);
 
         // Get the response from the peer
+        let request_start_time = self.time_service.now();
         let response = self
             .send_request_to_peer(peer, request.clone(), request_timeout_ms)
             .await;
@@ -749,7 +870,8 @@ impl AptosDataClient {
 
         // Try to convert the storage service enum into the exact variant we're expecting.
         // We do this using spawn_blocking because it involves serde and compression.
-        tokio::task::spawn_blocking(move || {
+        let response_time = self.time_service.now().duration_since(request_start_time);
+        let response = tokio::task::spawn_blocking(move || {
             match T::try_from(storage_response) {
                 Ok(new_payload) => Ok(Response::new(context, new_payload)),
                 // If the variant doesn't match what we're expecting, report the issue
@@ -762,7 +884,12 @@ impl AptosDataClient {
             }
         })
         .await
-        .map_err(|error| Error::UnexpectedErrorEncountered(error.to_string()))?
+        .map_err(|error| Error::UnexpectedErrorEncountered(error.to_string()))??;
+
+        // Update the state value throughput using the served data
+        self.update_state_value_throughput(peer, &response.payload, response_time);
+
+        Ok(response)
     }
 
     /// Sends a request to a specific peer
@@ -841,6 +968,15 @@ impl AptosDataClient {
                         },
                         _ => Error::UnexpectedErrorEncountered(rpc_error.to_string()),
                     },
+                    aptos_storage_service_client::Error::StorageServiceError(
+                        StorageServiceError::RequestQuotaExceeded {
+                            retry_after_ms,
+                            message,
+                        },
+                    ) => Error::RequestQuotaExceeded {
+                        retry_after_ms,
+                        message,
+                    },
                     aptos_storage_service_client::Error::StorageServiceError(err) => {
                         Error::UnexpectedErrorEncountered(err.to_string())
                     },
@@ -862,7 +998,17 @@ impl AptosDataClient {
                     peer,
                 );
 
-                self.notify_bad_response(id, peer, &request, ErrorType::NotUseful);
+                // If the peer is throttling our requests, avoid the peer until the
+                // retry time has elapsed. Otherwise, penalize the peer's score.
+                if let Error::RequestQuotaExceeded { retry_after_ms, .. } = &client_error {
+                    self.peer_states.throttle_peer(
+                        peer,
+                        Duration::from_millis(*retry_after_ms),
+                        self.time_service.now(),
+                    );
+                } else {
+                    self.notify_bad_response(id, peer, &request, ErrorType::NotUseful);
+                }
                 Err(client_error)
             },
         }
@@ -896,6 +1042,26 @@ impl AptosDataClient {
             .await
     }
 
+    /// Updates the state value throughput of the peer (if the payload is a
+    /// state value chunk), using the number of state values in the payload.
+    fn update_state_value_throughput<T: 'static>(
+        &self,
+        peer: PeerNetworkId,
+        payload: &T,
+        response_time: Duration,
+    ) {
+        if let Some(state_value_chunk) =
+            (payload as &dyn Any).downcast_ref::<StateValueChunkWithProof>()
+        {
+            self.peer_states.update_state_value_throughput(
+                peer,
+                state_value_chunk.raw_values.len() as u64,
+                response_time,
+                self.get_latency_for_peer(peer),
+            );
+        }
+    }
+
     /// Updates the metrics for the responses received via the data client
     fn update_received_response_metrics(
         &self,
@@ -926,6 +1092,26 @@ impl AptosDataClient {
     }
 }
 
+/// Removes a request from the in-flight state value requests when dropped
+/// (i.e., when the request completes or its task is aborted).
+pub(crate) struct InFlightRequestGuard {
+    in_flight_requests: Arc<Mutex<HashMap<DataRequest, HashSet<PeerNetworkId>>>>,
+    data_request: DataRequest,
+    peer: PeerNetworkId,
+}
+
+impl Drop for InFlightRequestGuard {
+    fn drop(&mut self) {
+        let mut in_flight_requests = self.in_flight_requests.lock();
+        if let Some(peers) = in_flight_requests.get_mut(&self.data_request) {
+            peers.remove(&self.peer);
+            if peers.is_empty() {
+                in_flight_requests.remove(&self.data_request);
+            }
+        }
+    }
+}
+
 #[async_trait]
 impl AptosDataClientInterface for AptosDataClient {
     fn get_global_data_summary(&self) -> GlobalDataSummary {
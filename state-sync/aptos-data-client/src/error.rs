@@ -19,6 +19,11 @@ pub enum Error {
     InvalidResponse(String),
     #[error("No connected peers: {0}")]
     NoConnectedPeers(String),
+    #[error("The peer's request quota was exceeded! Retry after {retry_after_ms} ms: {message}")]
+    RequestQuotaExceeded {
+        retry_after_ms: u64,
+        message: String,
+    },
     #[error("The subscription stream is lagging behind the data advertisements: {0}")]
     SubscriptionStreamIsLagging(String),
     #[error("Timed out waiting for a response: {0}")]
@@ -36,6 +41,7 @@ impl Error {
             Self::InvalidRequest(_) => "invalid_request",
             Self::InvalidResponse(_) => "invalid_response",
             Self::NoConnectedPeers(_) => "no_connected_peers",
+            Self::RequestQuotaExceeded { .. } => "request_quota_exceeded",
             Self::SubscriptionStreamIsLagging(_) => "subscription_stream_is_lagging",
             Self::TimeoutWaitingForResponse(_) => "timeout_waiting_for_response",
             Self::UnexpectedErrorEncountered(_) => "unexpected_error_encountered",
@@ -15,13 +15,13 @@ use aptos_logger::prelude::*;
 use aptos_storage_service_types::{
     requests::StorageServiceRequest, responses::StorageServerSummary,
 };
-use aptos_time_service::TimeService;
+use aptos_time_service::{TimeService, TimeServiceTrait};
 use dashmap::DashMap;
 use std::{
     cmp::min,
     collections::{BTreeMap, HashSet},
     sync::Arc,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 // Useful constants
@@ -42,6 +42,17 @@ const MALICIOUS_MULTIPLIER: f64 = 0.8;
 /// Ignore a peer when their score dips below this threshold.
 const IGNORE_PEER_THRESHOLD: f64 = 25.0;
 
+/// The maximum duration (secs) for which a peer can ask us to stop sending requests
+const MAX_THROTTLE_DURATION_SECS: u64 = 60;
+
+/// The weight given to new observations when updating the state value throughput
+const STATE_VALUE_THROUGHPUT_WEIGHT: f64 = 0.25;
+/// The minimum transfer time (ms) used to calculate the state value throughput
+const MIN_TRANSFER_TIME_MS: u64 = 1;
+/// The minimum fraction of the target response time that is always
+/// used for transferring state values (regardless of peer latency).
+const MIN_TRANSFER_TIME_FRACTION: f64 = 0.25;
+
 pub enum ErrorType {
     /// A response or error that's not actively malicious but also doesn't help
     /// us make progress, e.g., timeouts, remote errors, invalid data, etc...
@@ -79,6 +90,15 @@ pub struct PeerState {
 
     /// For now, a simplified port of the original state-sync v1 scoring system.
     score: f64,
+
+    /// The observed rate (state values per second) at which the peer serves state
+    /// values, or `None` if the peer hasn't served any state values yet.
+    state_value_throughput: Option<f64>,
+
+    /// The time until which we should not send requests to this peer (e.g.,
+    /// because we exceeded the peer's request quota), or `None` if the peer
+    /// is not throttling us.
+    throttled_until: Option<Instant>,
 }
 
 impl PeerState {
@@ -89,6 +109,8 @@ impl PeerState {
             sent_requests_by_type: Arc::new(DashMap::new()),
             storage_summary: None,
             score: STARTING_SCORE,
+            state_value_throughput: None,
+            throttled_until: None,
         }
     }
 }
@@ -115,6 +137,11 @@ impl PeerState {
         self.score
     }
 
+    /// Returns the observed state value throughput for the peer
+    pub fn get_state_value_throughput(&self) -> Option<f64> {
+        self.state_value_throughput
+    }
+
     /// Returns the storage summary for the peer
     pub fn get_storage_summary(&self) -> Option<StorageServerSummary> {
         self.storage_summary.clone()
@@ -159,6 +186,21 @@ impl PeerState {
         self.score <= IGNORE_PEER_THRESHOLD
     }
 
+    /// Returns true iff the peer is currently throttling our requests
+    pub fn is_throttled(&self, time_now: Instant) -> bool {
+        self.throttled_until
+            .is_some_and(|throttled_until| time_now < throttled_until)
+    }
+
+    /// Marks the peer as throttling our requests for the given duration
+    fn throttle(&mut self, throttle_duration: Duration, time_now: Instant) {
+        let throttle_duration = min(
+            throttle_duration,
+            Duration::from_secs(MAX_THROTTLE_DURATION_SECS),
+        );
+        self.throttled_until = Some(time_now + throttle_duration);
+    }
+
     /// Updates the score of the peer according to a successful operation
     fn update_score_success(&mut self) {
         self.score = f64::min(self.score + SUCCESSFUL_RESPONSE_DELTA, MAX_SCORE);
@@ -177,6 +219,21 @@ impl PeerState {
     fn update_storage_summary(&mut self, storage_summary: StorageServerSummary) {
         self.storage_summary = Some(storage_summary);
     }
+
+    /// Updates the state value throughput for the peer using an
+    /// exponentially weighted moving average of the observations.
+    fn update_state_value_throughput(&mut self, num_state_values: u64, transfer_time: Duration) {
+        let transfer_time = transfer_time.max(Duration::from_millis(MIN_TRANSFER_TIME_MS));
+        let observed_throughput = num_state_values as f64 / transfer_time.as_secs_f64();
+        let new_throughput = match self.state_value_throughput {
+            Some(throughput) => {
+                throughput * (1.0 - STATE_VALUE_THROUGHPUT_WEIGHT)
+                    + observed_throughput * STATE_VALUE_THROUGHPUT_WEIGHT
+            },
+            None => observed_throughput,
+        };
+        self.state_value_throughput = Some(new_throughput);
+    }
 }
 
 /// Contains all of the unbanned peers' most recent [`StorageServerSummary`] data
@@ -214,6 +271,11 @@ impl PeerStates {
 
         // Check if the peer can service the request
         if let Some(peer_state) = self.peer_to_state.get(peer) {
+            // If the peer is throttling our requests, avoid it for now
+            if peer_state.is_throttled(time_service.now()) {
+                return false;
+            }
+
             return match peer_state.get_storage_summary_if_not_ignored() {
                 Some(storage_summary) => {
                     storage_summary.can_service(&self.data_client_config, time_service, request)
@@ -321,6 +383,78 @@ impl PeerStates {
         }
     }
 
+    /// Marks the peer as throttling our requests for the given duration
+    /// (e.g., because we exceeded the peer's request quota). Note: the
+    /// peer's score is not updated, as the peer is only protecting itself.
+    pub fn throttle_peer(
+        &self,
+        peer: PeerNetworkId,
+        throttle_duration: Duration,
+        time_now: Instant,
+    ) {
+        if let Some(mut entry) = self.peer_to_state.get_mut(&peer) {
+            entry.throttle(throttle_duration, time_now);
+        }
+    }
+
+    /// Returns the number of state values that should be requested from the given
+    /// peer, such that the peer can respond within the target response time. This
+    /// is based on the observed state value throughput and the latency of the peer.
+    /// If the throughput is unknown, the requested chunk size is returned.
+    pub fn get_adaptive_state_chunk_size(
+        &self,
+        peer: &PeerNetworkId,
+        requested_chunk_size: u64,
+        peer_latency: Option<Duration>,
+    ) -> u64 {
+        // If adaptive chunking is disabled, use the requested chunk size
+        let adaptive_chunking_config = self.data_client_config.adaptive_chunking_config;
+        if !adaptive_chunking_config.enable_adaptive_chunking {
+            return requested_chunk_size;
+        }
+
+        // Get the observed throughput of the peer
+        let state_value_throughput = match self
+            .peer_to_state
+            .get(peer)
+            .and_then(|peer_state| peer_state.get_state_value_throughput())
+        {
+            Some(state_value_throughput) => state_value_throughput,
+            None => return requested_chunk_size, // We haven't observed the peer yet
+        };
+
+        // Calculate the time available for transferring state values
+        let target_response_time =
+            Duration::from_millis(adaptive_chunking_config.target_response_time_ms);
+        let transfer_time = target_response_time
+            .saturating_sub(peer_latency.unwrap_or_default())
+            .max(target_response_time.mul_f64(MIN_TRANSFER_TIME_FRACTION));
+
+        // Calculate the chunk size and bound it by the min and requested sizes
+        let adaptive_chunk_size = (state_value_throughput * transfer_time.as_secs_f64()) as u64;
+        let min_chunk_size = min(
+            adaptive_chunking_config.min_state_chunk_size,
+            requested_chunk_size,
+        );
+        adaptive_chunk_size.clamp(min_chunk_size, requested_chunk_size)
+    }
+
+    /// Updates the state value throughput of the given peer using the number
+    /// of state values served by the peer and the time taken to serve them.
+    pub fn update_state_value_throughput(
+        &self,
+        peer: PeerNetworkId,
+        num_state_values: u64,
+        response_time: Duration,
+        peer_latency: Option<Duration>,
+    ) {
+        if let Some(mut entry) = self.peer_to_state.get_mut(&peer) {
+            // Update the throughput using the time spent transferring the data
+            let transfer_time = response_time.saturating_sub(peer_latency.unwrap_or_default());
+            entry.update_state_value_throughput(num_state_values, transfer_time);
+        }
+    }
+
     /// Updates the storage summary for the given peer
     pub fn update_summary(&self, peer: PeerNetworkId, storage_summary: StorageServerSummary) {
         self.peer_to_state
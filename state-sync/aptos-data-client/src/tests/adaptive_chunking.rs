@@ -0,0 +1,175 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{peer_states::PeerStates, tests::utils};
+use aptos_config::{
+    config::{AptosAdaptiveChunkingConfig, AptosDataClientConfig},
+    network_id::PeerNetworkId,
+};
+use std::{sync::Arc, time::Duration};
+
+#[test]
+fn test_adaptive_chunk_size_disabled() {
+    // Create the peer states with adaptive chunking disabled
+    let data_client_config = AptosDataClientConfig {
+        adaptive_chunking_config: AptosAdaptiveChunkingConfig {
+            enable_adaptive_chunking: false,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let (peer_states, peer) = create_peer_states_with_peer(data_client_config);
+
+    // Update the throughput of the peer (100 state values per second)
+    update_state_value_throughput(&peer_states, peer, 100, Duration::from_secs(1), None);
+
+    // Verify the requested chunk size is always returned
+    for requested_chunk_size in [1, 100, 1000, 10_000] {
+        let chunk_size =
+            peer_states.get_adaptive_state_chunk_size(&peer, requested_chunk_size, None);
+        assert_eq!(chunk_size, requested_chunk_size);
+    }
+}
+
+#[test]
+fn test_adaptive_chunk_size_latency() {
+    // Create the peer states with a target response time of 2 seconds
+    let data_client_config = AptosDataClientConfig {
+        adaptive_chunking_config: AptosAdaptiveChunkingConfig {
+            enable_adaptive_chunking: true,
+            min_state_chunk_size: 100,
+            target_response_time_ms: 2000,
+        },
+        ..Default::default()
+    };
+    let (peer_states, peer) = create_peer_states_with_peer(data_client_config);
+
+    // Update the throughput of the peer (1000 state values per second)
+    update_state_value_throughput(&peer_states, peer, 1000, Duration::from_secs(1), None);
+
+    // Verify the chunk size accounts for the latency of the peer
+    let requested_chunk_size = 10_000;
+    for (peer_latency_ms, expected_chunk_size) in [(0, 2000), (500, 1500), (1000, 1000)] {
+        let chunk_size = peer_states.get_adaptive_state_chunk_size(
+            &peer,
+            requested_chunk_size,
+            Some(Duration::from_millis(peer_latency_ms)),
+        );
+        assert_eq!(chunk_size, expected_chunk_size);
+    }
+
+    // Verify that a quarter of the target response time is always used for the transfer
+    let chunk_size = peer_states.get_adaptive_state_chunk_size(
+        &peer,
+        requested_chunk_size,
+        Some(Duration::from_secs(10)),
+    );
+    assert_eq!(chunk_size, 500);
+}
+
+#[test]
+fn test_adaptive_chunk_size_throughput() {
+    // Create the peer states with a target response time of 1 second
+    let min_state_chunk_size = 100;
+    let data_client_config = AptosDataClientConfig {
+        adaptive_chunking_config: AptosAdaptiveChunkingConfig {
+            enable_adaptive_chunking: true,
+            min_state_chunk_size,
+            target_response_time_ms: 1000,
+        },
+        ..Default::default()
+    };
+    let (peer_states, peer) = create_peer_states_with_peer(data_client_config);
+
+    // Verify the requested chunk size is returned for peers without a known throughput
+    let requested_chunk_size = 800;
+    let chunk_size = peer_states.get_adaptive_state_chunk_size(&peer, requested_chunk_size, None);
+    assert_eq!(chunk_size, requested_chunk_size);
+    let unknown_peer = PeerNetworkId::random();
+    let chunk_size =
+        peer_states.get_adaptive_state_chunk_size(&unknown_peer, requested_chunk_size, None);
+    assert_eq!(chunk_size, requested_chunk_size);
+
+    // Update the throughput of the peer (400 state values per second)
+    update_state_value_throughput(&peer_states, peer, 200, Duration::from_millis(500), None);
+    assert_eq!(get_state_value_throughput(&peer_states, peer), 400.0);
+
+    // Verify the chunk size is bounded by the throughput
+    let chunk_size = peer_states.get_adaptive_state_chunk_size(&peer, requested_chunk_size, None);
+    assert_eq!(chunk_size, 400);
+
+    // Verify the chunk size never exceeds the requested chunk size
+    let chunk_size = peer_states.get_adaptive_state_chunk_size(&peer, 300, None);
+    assert_eq!(chunk_size, 300);
+
+    // Update the throughput of the peer (10 state values per second)
+    for _ in 0..100 {
+        update_state_value_throughput(&peer_states, peer, 10, Duration::from_secs(1), None);
+    }
+
+    // Verify the chunk size never drops below the min chunk size
+    let chunk_size = peer_states.get_adaptive_state_chunk_size(&peer, requested_chunk_size, None);
+    assert_eq!(chunk_size, min_state_chunk_size);
+    let chunk_size = peer_states.get_adaptive_state_chunk_size(&peer, 50, None);
+    assert_eq!(chunk_size, 50);
+}
+
+#[test]
+fn test_update_state_value_throughput() {
+    // Create the peer states
+    let (peer_states, peer) = create_peer_states_with_peer(AptosDataClientConfig::default());
+
+    // Verify the throughput is unknown
+    assert!(peer_states
+        .get_peer_to_states()
+        .get(&peer)
+        .unwrap()
+        .get_state_value_throughput()
+        .is_none());
+
+    // Update the throughput and verify the peer latency is ignored
+    update_state_value_throughput(
+        &peer_states,
+        peer,
+        500,
+        Duration::from_secs(2),
+        Some(Duration::from_secs(1)),
+    );
+    assert_eq!(get_state_value_throughput(&peer_states, peer), 500.0);
+
+    // Update the throughput again and verify the moving average is used
+    update_state_value_throughput(&peer_states, peer, 900, Duration::from_secs(1), None);
+    assert_eq!(get_state_value_throughput(&peer_states, peer), 600.0);
+}
+
+/// Creates a new peer states and adds a single peer (with a storage summary)
+fn create_peer_states_with_peer(
+    data_client_config: AptosDataClientConfig,
+) -> (PeerStates, PeerNetworkId) {
+    let peer_states = PeerStates::new(Arc::new(data_client_config));
+    let peer = PeerNetworkId::random();
+    peer_states.update_summary(peer, utils::create_storage_summary(100));
+    (peer_states, peer)
+}
+
+/// Returns the state value throughput of the given peer
+fn get_state_value_throughput(peer_states: &PeerStates, peer: PeerNetworkId) -> f64 {
+    peer_states
+        .get_peer_to_states()
+        .get(&peer)
+        .unwrap()
+        .get_state_value_throughput()
+        .unwrap()
+}
+
+/// Updates the state value throughput of the peer using a response
+/// containing the given number of state values.
+fn update_state_value_throughput(
+    peer_states: &PeerStates,
+    peer: PeerNetworkId,
+    num_state_values: u64,
+    response_time: Duration,
+    peer_latency: Option<Duration>,
+) {
+    peer_states.update_state_value_throughput(peer, num_state_values, response_time, peer_latency);
+}
@@ -10,8 +10,12 @@ use aptos_config::{
     config::{AptosDataClientConfig, AptosDataMultiFetchConfig},
     network_id::NetworkId,
 };
-use aptos_storage_service_types::requests::{
-    DataRequest, StorageServiceRequest, TransactionOutputsWithProofRequest,
+use aptos_storage_service_types::{
+    requests::{
+        DataRequest, StateValuesWithProofRequest, StorageServiceRequest,
+        TransactionOutputsWithProofRequest,
+    },
+    responses::CompleteDataRange,
 };
 use aptos_time_service::TimeServiceTrait;
 use maplit::hashset;
@@ -1491,3 +1495,78 @@ fn verify_num_selected_peers(
     let selected_peers = client.choose_peers_for_request(storage_request).unwrap();
     assert_eq!(selected_peers.len(), num_expected_peers);
 }
+
+#[tokio::test]
+async fn multi_fetch_avoids_peers_servicing_request() {
+    // Create a data client config with multi-fetch disabled
+    let data_client_config = AptosDataClientConfig {
+        data_multi_fetch_config: AptosDataMultiFetchConfig {
+            enable_multi_fetch: false,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    // Create the mock network and client
+    let (mut mock_network, _, client, _) = MockNetwork::new(None, Some(data_client_config), None);
+
+    // Add several high priority peers that can service state value requests
+    let known_version = 1000;
+    let peers = utils::add_several_peers(&mut mock_network, 3, PeerPriority::HighPriority);
+    for peer in peers.iter() {
+        let mut storage_summary = utils::create_storage_summary(known_version);
+        storage_summary.data_summary.states =
+            Some(CompleteDataRange::new(0, known_version).unwrap());
+        client.update_peer_storage_summary(*peer, storage_summary);
+    }
+
+    // Create a state values request
+    let storage_request = StorageServiceRequest::new(
+        DataRequest::GetStateValuesWithProof(StateValuesWithProofRequest {
+            version: known_version,
+            start_index: 0,
+            end_index: 999,
+        }),
+        true,
+    );
+
+    // Mark the request as in-flight to each peer and verify the
+    // request is never sent to the peers already servicing it.
+    let mut in_flight_peers = HashSet::new();
+    let mut in_flight_requests = vec![];
+    for peer in peers.iter().take(peers.len() - 1) {
+        in_flight_requests.push(client.track_in_flight_request(*peer, &storage_request));
+        in_flight_peers.insert(*peer);
+
+        let selected_peers = utils::select_peers_multiple_times(&client, 1, &storage_request);
+        for selected_peer in selected_peers.keys() {
+            assert!(!in_flight_peers.contains(selected_peer));
+        }
+    }
+
+    // Mark the request as in-flight to the last peer and verify
+    // that all peers can be selected again (there are no others).
+    let last_peer = *peers.difference(&in_flight_peers).next().unwrap();
+    in_flight_requests.push(client.track_in_flight_request(last_peer, &storage_request));
+    let selected_peers = utils::select_peers_multiple_times(&client, 1, &storage_request);
+    assert_eq!(
+        selected_peers.keys().copied().collect::<HashSet<_>>(),
+        peers
+    );
+
+    // Complete the requests and verify that all peers can be selected
+    in_flight_requests.clear();
+    let selected_peers = utils::select_peers_multiple_times(&client, 1, &storage_request);
+    assert_eq!(
+        selected_peers.keys().copied().collect::<HashSet<_>>(),
+        peers
+    );
+
+    // Verify that other requests are never tracked as in-flight
+    let server_version_request =
+        StorageServiceRequest::new(DataRequest::GetServerProtocolVersion, true);
+    let peer = *peers.iter().next().unwrap();
+    assert!(client
+        .track_in_flight_request(peer, &server_version_request)
+        .is_none());
+}
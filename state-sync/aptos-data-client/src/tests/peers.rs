@@ -20,11 +20,15 @@ use aptos_storage_service_types::{
     responses::{CompleteDataRange, DataResponse, StorageServerSummary, StorageServiceResponse},
     StorageServiceError,
 };
+use aptos_time_service::TimeServiceTrait;
 use aptos_types::transaction::{TransactionListWithProof, TransactionListWithProofV2};
 use claims::{assert_err, assert_matches, assert_ok};
 use maplit::hashset;
 use rand::{rngs::OsRng, Rng};
-use std::{collections::HashSet, time::Duration};
+use std::{
+    collections::HashSet,
+    time::{Duration, Instant},
+};
 
 #[tokio::test]
 async fn all_bad_peers_with_invalid_responses() {
@@ -679,6 +683,57 @@ async fn single_good_peer_across_priorities() {
     }
 }
 
+#[tokio::test]
+async fn throttled_peer_is_avoided() {
+    // Ensure the properties hold for all peer priorities
+    for peer_priority in PeerPriority::get_all_ordered_priorities() {
+        // Create a base config for a validator
+        let base_config = utils::create_validator_base_config();
+
+        // Create the mock network and client
+        let data_client_config = AptosDataClientConfig::default();
+        let (mut mock_network, time_service, client, _) =
+            MockNetwork::new(Some(base_config), Some(data_client_config), None);
+
+        // Add a single peer that advertises transactions 0 -> 100
+        let (peer, network_id) = utils::add_peer_to_network(peer_priority, &mut mock_network);
+        let max_transaction_version = 100;
+        client.update_peer_storage_summary(
+            peer,
+            utils::create_storage_summary(max_transaction_version),
+        );
+        client.update_global_summary_cache().unwrap();
+
+        // Spawn a handler for the peer to respond with request quota errors
+        let retry_after_ms = 10_000;
+        tokio::spawn(async move {
+            while let Some(network_request) = mock_network.next_request(network_id).await {
+                network_request.response_sender.send(Err(
+                    StorageServiceError::RequestQuotaExceeded {
+                        retry_after_ms,
+                        message: "Too many requests!".into(),
+                    },
+                ));
+            }
+        });
+
+        // Send a request to the peer and verify the request fails
+        let peer_score = get_peer_score(&client, &peer);
+        verify_transactions_response(&data_client_config, &client, max_transaction_version, true)
+            .await;
+
+        // Verify the peer is now throttled, but its score was not penalized
+        assert!(is_peer_throttled(&client, &peer, time_service.now()));
+        assert_eq!(get_peer_score(&client, &peer), peer_score);
+
+        // Elapse enough time for the peer to stop throttling our requests
+        time_service.advance_ms_async(retry_after_ms).await;
+
+        // Verify the peer is no longer throttled
+        assert!(!is_peer_throttled(&client, &peer, time_service.now()));
+    }
+}
+
 /// Emulates network latencies by sleeping for some amount of time.
 /// If no duration is specified, the sleep duration is randomly chosen.
 async fn emulate_network_latencies(sleep_duration_ms: Option<u64>) {
@@ -711,6 +766,26 @@ async fn poll_peers(
     }
 }
 
+/// Returns the current score of the given peer
+fn get_peer_score(client: &AptosDataClient, peer: &PeerNetworkId) -> f64 {
+    client
+        .get_peer_states()
+        .get_peer_to_states()
+        .get(peer)
+        .unwrap()
+        .get_score()
+}
+
+/// Returns true iff the given peer is currently throttling our requests
+fn is_peer_throttled(client: &AptosDataClient, peer: &PeerNetworkId, time_now: Instant) -> bool {
+    client
+        .get_peer_states()
+        .get_peer_to_states()
+        .get(peer)
+        .unwrap()
+        .is_throttled(time_now)
+}
+
 /// Sends an error response to the specified network request
 fn send_error_response(network_request: NetworkRequest) {
     network_request
@@ -207,7 +207,7 @@ pub fn extend_with_random_peers(
 }
 
 /// Gets the latency for the specified peer from the peer monitoring metadata
-fn get_latency_for_peer(
+pub(crate) fn get_latency_for_peer(
     peers_and_metadata: &Arc<PeersAndMetadata>,
     peer: PeerNetworkId,
 ) -> Option<f64> {
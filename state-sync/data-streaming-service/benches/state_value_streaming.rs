@@ -0,0 +1,460 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+#![allow(clippy::unwrap_used)]
+
+//! Benchmarks the time taken to stream all state values from a simulated
+//! network of storage service peers, where some peers are much slower
+//! than others. This compares static chunk sizes with adaptive chunk
+//! sizes (per peer) and work stealing (of slow in-flight requests).
+
+use aptos_config::{
+    config::{
+        AptosAdaptiveChunkingConfig, AptosDataClientConfig, DataStreamingServiceConfig,
+        DynamicPrefetchingConfig, WorkStealingConfig,
+    },
+    network_id::PeerNetworkId,
+};
+use aptos_crypto::HashValue;
+use aptos_data_client::{
+    error,
+    global_summary::{AdvertisedData, GlobalDataSummary, OptimalChunkSizes},
+    interface::{
+        AptosDataClientInterface, Response, ResponseCallback, ResponseContext, ResponseError,
+        SubscriptionRequestMetadata,
+    },
+    peer_states::PeerStates,
+};
+use aptos_data_streaming_service::{
+    data_notification::DataPayload,
+    streaming_client::{
+        new_streaming_service_client_listener_pair, DataStreamingClient, StreamingServiceClient,
+    },
+    streaming_service::DataStreamingService,
+};
+use aptos_storage_service_types::{
+    responses::{
+        CompleteDataRange, DataSummary, ProtocolMetadata, StorageServerSummary,
+        TransactionOrOutputListWithProofV2,
+    },
+    Epoch,
+};
+use aptos_time_service::TimeService;
+use aptos_types::{
+    ledger_info::LedgerInfoWithSignatures,
+    proof::SparseMerkleRangeProof,
+    state_store::{
+        state_key::StateKey,
+        state_value::{StateValue, StateValueChunkWithProof},
+    },
+    transaction::{TransactionListWithProofV2, TransactionOutputListWithProofV2, Version},
+};
+use async_trait::async_trait;
+use criterion::{criterion_group, criterion_main, Criterion};
+use futures::StreamExt;
+use std::{
+    cmp::min,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+// Useful benchmark constants
+const MAX_STATE_CHUNK_SIZE: u64 = 1000;
+const NUM_STATE_VALUES: u64 = 20_000;
+const STATE_VERSION: Version = 1000;
+
+/// A simulated storage service peer with a fixed latency and throughput
+#[derive(Clone, Copy, Debug)]
+struct SimulatedPeer {
+    peer_network_id: PeerNetworkId,
+    latency: Duration,
+    state_values_per_second: u64,
+}
+
+impl SimulatedPeer {
+    fn new(latency_ms: u64, state_values_per_second: u64) -> Self {
+        Self {
+            peer_network_id: PeerNetworkId::random(),
+            latency: Duration::from_millis(latency_ms),
+            state_values_per_second,
+        }
+    }
+
+    /// Returns the time taken by the peer to serve the given number of state values
+    fn get_response_time(&self, num_state_values: u64) -> Duration {
+        let transfer_time =
+            Duration::from_secs_f64(num_state_values as f64 / self.state_values_per_second as f64);
+        self.latency + transfer_time
+    }
+}
+
+/// A simulated storage service server (i.e., a set of peers) that serves state
+/// values to the data streaming service. Requests are sent to the peers in a
+/// round-robin fashion, and (if enabled) the number of state values requested
+/// from each peer is adapted using the peer states of the data client.
+#[derive(Clone)]
+struct SimulatedStorageServer {
+    next_peer_index: Arc<AtomicUsize>,
+    peer_states: Arc<PeerStates>,
+    peers: Vec<SimulatedPeer>,
+}
+
+impl SimulatedStorageServer {
+    fn new(data_client_config: AptosDataClientConfig, peers: Vec<SimulatedPeer>) -> Self {
+        // Create the peer states and add a storage summary for each peer
+        let peer_states = Arc::new(PeerStates::new(Arc::new(data_client_config)));
+        for peer in &peers {
+            peer_states.update_summary(peer.peer_network_id, create_storage_summary());
+        }
+
+        Self {
+            next_peer_index: Arc::new(AtomicUsize::new(0)),
+            peer_states,
+            peers,
+        }
+    }
+
+    /// Returns the next peer to service a request
+    fn get_next_peer(&self) -> SimulatedPeer {
+        let peer_index = self.next_peer_index.fetch_add(1, Ordering::Relaxed);
+        self.peers[peer_index % self.peers.len()]
+    }
+}
+
+#[async_trait]
+impl AptosDataClientInterface for SimulatedStorageServer {
+    fn get_global_data_summary(&self) -> GlobalDataSummary {
+        let advertised_data = AdvertisedData {
+            states: vec![CompleteDataRange::new(0, STATE_VERSION).unwrap()],
+            ..AdvertisedData::empty()
+        };
+        let optimal_chunk_sizes = OptimalChunkSizes {
+            state_chunk_size: MAX_STATE_CHUNK_SIZE,
+            ..OptimalChunkSizes::empty()
+        };
+        GlobalDataSummary {
+            advertised_data,
+            optimal_chunk_sizes,
+        }
+    }
+
+    async fn get_epoch_ending_ledger_infos(
+        &self,
+        _start_epoch: Epoch,
+        _expected_end_epoch: Epoch,
+        _request_timeout_ms: u64,
+    ) -> error::Result<Response<Vec<LedgerInfoWithSignatures>>> {
+        unsupported_request("get_epoch_ending_ledger_infos")
+    }
+
+    async fn get_new_transaction_outputs_with_proof(
+        &self,
+        _known_version: Version,
+        _known_epoch: Epoch,
+        _request_timeout_ms: u64,
+    ) -> error::Result<Response<(TransactionOutputListWithProofV2, LedgerInfoWithSignatures)>> {
+        unsupported_request("get_new_transaction_outputs_with_proof")
+    }
+
+    async fn get_new_transactions_with_proof(
+        &self,
+        _known_version: Version,
+        _known_epoch: Epoch,
+        _include_events: bool,
+        _request_timeout_ms: u64,
+    ) -> error::Result<Response<(TransactionListWithProofV2, LedgerInfoWithSignatures)>> {
+        unsupported_request("get_new_transactions_with_proof")
+    }
+
+    async fn get_new_transactions_or_outputs_with_proof(
+        &self,
+        _known_version: Version,
+        _known_epoch: Epoch,
+        _include_events: bool,
+        _request_timeout_ms: u64,
+    ) -> error::Result<Response<(TransactionOrOutputListWithProofV2, LedgerInfoWithSignatures)>>
+    {
+        unsupported_request("get_new_transactions_or_outputs_with_proof")
+    }
+
+    async fn get_number_of_states(
+        &self,
+        _version: Version,
+        _request_timeout_ms: u64,
+    ) -> error::Result<Response<u64>> {
+        Ok(create_response(NUM_STATE_VALUES))
+    }
+
+    async fn get_state_values_with_proof(
+        &self,
+        _version: u64,
+        start_index: u64,
+        end_index: u64,
+        _request_timeout_ms: u64,
+    ) -> error::Result<Response<StateValueChunkWithProof>> {
+        // Identify the peer and the number of state values to request from the peer
+        let peer = self.get_next_peer();
+        let requested_chunk_size = end_index - start_index + 1;
+        let chunk_size = self.peer_states.get_adaptive_state_chunk_size(
+            &peer.peer_network_id,
+            requested_chunk_size,
+            Some(peer.latency),
+        );
+        let num_state_values = min(chunk_size, MAX_STATE_CHUNK_SIZE);
+
+        // Emulate the time taken by the peer to serve the request
+        let response_time = peer.get_response_time(num_state_values);
+        tokio::time::sleep(response_time).await;
+
+        // Update the throughput of the peer
+        let end_index = start_index + num_state_values - 1;
+        self.peer_states.update_state_value_throughput(
+            peer.peer_network_id,
+            num_state_values,
+            response_time,
+            Some(peer.latency),
+        );
+
+        // Create the state value chunk
+        let raw_values = (start_index..=end_index)
+            .map(|_| (StateKey::raw(&[]), StateValue::new_legacy(vec![].into())))
+            .collect();
+        let state_value_chunk_with_proof = StateValueChunkWithProof {
+            first_index: start_index,
+            last_index: end_index,
+            first_key: HashValue::zero(),
+            last_key: HashValue::zero(),
+            raw_values,
+            proof: SparseMerkleRangeProof::new(vec![]),
+            root_hash: HashValue::zero(),
+        };
+        Ok(create_response(state_value_chunk_with_proof))
+    }
+
+    async fn get_transaction_outputs_with_proof(
+        &self,
+        _proof_version: Version,
+        _start_version: Version,
+        _end_version: Version,
+        _request_timeout_ms: u64,
+    ) -> error::Result<Response<TransactionOutputListWithProofV2>> {
+        unsupported_request("get_transaction_outputs_with_proof")
+    }
+
+    async fn get_transactions_with_proof(
+        &self,
+        _proof_version: Version,
+        _start_version: Version,
+        _end_version: Version,
+        _include_events: bool,
+        _request_timeout_ms: u64,
+    ) -> error::Result<Response<TransactionListWithProofV2>> {
+        unsupported_request("get_transactions_with_proof")
+    }
+
+    async fn get_transactions_or_outputs_with_proof(
+        &self,
+        _proof_version: Version,
+        _start_version: Version,
+        _end_version: Version,
+        _include_events: bool,
+        _request_timeout_ms: u64,
+    ) -> error::Result<Response<TransactionOrOutputListWithProofV2>> {
+        unsupported_request("get_transactions_or_outputs_with_proof")
+    }
+
+    async fn subscribe_to_transaction_outputs_with_proof(
+        &self,
+        _subscription_request_metadata: SubscriptionRequestMetadata,
+        _request_timeout_ms: u64,
+    ) -> error::Result<Response<(TransactionOutputListWithProofV2, LedgerInfoWithSignatures)>> {
+        unsupported_request("subscribe_to_transaction_outputs_with_proof")
+    }
+
+    async fn subscribe_to_transactions_with_proof(
+        &self,
+        _subscription_request_metadata: SubscriptionRequestMetadata,
+        _include_events: bool,
+        _request_timeout_ms: u64,
+    ) -> error::Result<Response<(TransactionListWithProofV2, LedgerInfoWithSignatures)>> {
+        unsupported_request("subscribe_to_transactions_with_proof")
+    }
+
+    async fn subscribe_to_transactions_or_outputs_with_proof(
+        &self,
+        _subscription_request_metadata: SubscriptionRequestMetadata,
+        _include_events: bool,
+        _request_timeout_ms: u64,
+    ) -> error::Result<Response<(TransactionOrOutputListWithProofV2, LedgerInfoWithSignatures)>>
+    {
+        unsupported_request("subscribe_to_transactions_or_outputs_with_proof")
+    }
+}
+
+/// Returns an error for a request that the simulated peers don't serve
+/// (only state value requests are expected by the benchmark).
+fn unsupported_request<T>(request: &str) -> error::Result<T> {
+    Err(error::Error::UnexpectedErrorEncountered(format!(
+        "The simulated storage server does not serve {} requests!",
+        request
+    )))
+}
+
+/// A response callback that ignores all feedback
+#[derive(Debug)]
+struct NoopResponseCallback;
+
+impl ResponseCallback for NoopResponseCallback {
+    fn notify_bad_response(&self, _error: ResponseError) {}
+}
+
+/// Creates a data client response for the given payload
+fn create_response<T>(payload: T) -> Response<T> {
+    let context = ResponseContext::new(0, Box::new(NoopResponseCallback));
+    Response::new(context, payload)
+}
+
+/// Creates a storage summary for a simulated peer
+fn create_storage_summary() -> StorageServerSummary {
+    StorageServerSummary {
+        protocol_metadata: ProtocolMetadata {
+            max_epoch_chunk_size: MAX_STATE_CHUNK_SIZE,
+            max_state_chunk_size: MAX_STATE_CHUNK_SIZE,
+            max_transaction_chunk_size: MAX_STATE_CHUNK_SIZE,
+            max_transaction_output_chunk_size: MAX_STATE_CHUNK_SIZE,
+        },
+        data_summary: DataSummary {
+            states: Some(CompleteDataRange::new(0, STATE_VERSION).unwrap()),
+            ..Default::default()
+        },
+    }
+}
+
+/// Creates the simulated peers (two fast peers and a single slow peer)
+fn create_simulated_peers() -> Vec<SimulatedPeer> {
+    vec![
+        SimulatedPeer::new(10, 50_000),
+        SimulatedPeer::new(10, 50_000),
+        SimulatedPeer::new(100, 2_000),
+    ]
+}
+
+/// Streams all state values from the simulated peers using the given
+/// configs, and returns the number of state values received.
+async fn stream_all_state_values(
+    data_client_config: AptosDataClientConfig,
+    streaming_service_config: DataStreamingServiceConfig,
+) -> u64 {
+    // Create the simulated storage server
+    let simulated_storage_server =
+        SimulatedStorageServer::new(data_client_config, create_simulated_peers());
+
+    // Create and start the data streaming service
+    let (streaming_client, streaming_service_listener) =
+        new_streaming_service_client_listener_pair();
+    let streaming_service = DataStreamingService::new(
+        data_client_config,
+        streaming_service_config,
+        simulated_storage_server,
+        streaming_service_listener,
+        TimeService::real(),
+    );
+    let streaming_service_handle = tokio::spawn(streaming_service.start_service());
+
+    // Stream all state values
+    let num_state_values = receive_all_state_values(&streaming_client).await;
+
+    // Stop the streaming service
+    streaming_service_handle.abort();
+
+    num_state_values
+}
+
+/// Receives all state values along a new stream, and returns the number of values
+async fn receive_all_state_values(streaming_client: &StreamingServiceClient) -> u64 {
+    let mut stream_listener = streaming_client
+        .get_all_state_values(STATE_VERSION, None)
+        .await
+        .unwrap();
+
+    let mut num_state_values = 0;
+    loop {
+        let data_notification = stream_listener.select_next_some().await;
+        match data_notification.data_payload {
+            DataPayload::StateValuesWithProof(state_value_chunk_with_proof) => {
+                num_state_values += state_value_chunk_with_proof.raw_values.len() as u64;
+            },
+            DataPayload::EndOfStream => return num_state_values,
+            data_payload => panic!("Unexpected data payload: {:?}", data_payload),
+        }
+    }
+}
+
+/// Creates the data client and streaming service configs for the benchmark
+fn create_configs(
+    enable_adaptive_chunking: bool,
+    enable_work_stealing: bool,
+) -> (AptosDataClientConfig, DataStreamingServiceConfig) {
+    let data_client_config = AptosDataClientConfig {
+        adaptive_chunking_config: AptosAdaptiveChunkingConfig {
+            enable_adaptive_chunking,
+            min_state_chunk_size: 50,
+            target_response_time_ms: 100,
+        },
+        ..Default::default()
+    };
+    let streaming_service_config = DataStreamingServiceConfig {
+        dynamic_prefetching: DynamicPrefetchingConfig {
+            enable_dynamic_prefetching: false,
+            ..Default::default()
+        },
+        max_concurrent_state_requests: 6,
+        progress_check_interval_ms: 10,
+        work_stealing: WorkStealingConfig {
+            enable_work_stealing,
+            min_steal_duration_ms: 100,
+            slow_response_multiplier: 3,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    (data_client_config, streaming_service_config)
+}
+
+fn bench_state_value_streaming(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+
+    let mut group = c.benchmark_group("state_value_streaming");
+    group.sample_size(10);
+    for (name, enable_adaptive_chunking, enable_work_stealing) in [
+        ("static_chunks", false, false),
+        ("adaptive_chunks", true, false),
+        ("adaptive_chunks_and_work_stealing", true, true),
+    ] {
+        let (data_client_config, streaming_service_config) =
+            create_configs(enable_adaptive_chunking, enable_work_stealing);
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                let num_state_values = runtime.block_on(stream_all_state_values(
+                    data_client_config,
+                    streaming_service_config,
+                ));
+                assert_eq!(num_state_values, NUM_STATE_VALUES);
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    name = state_value_streaming_benches;
+    config = Criterion::default();
+    targets = bench_state_value_streaming
+);
+criterion_main!(state_value_streaming_benches);
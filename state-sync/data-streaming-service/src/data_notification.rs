@@ -11,7 +11,7 @@ use aptos_types::{
 };
 use std::{
     fmt::{Debug, Formatter},
-    time::Instant,
+    time::{Duration, Instant},
 };
 
 /// A unique ID used to identify each notification.
@@ -106,6 +106,11 @@ impl DataClientRequest {
             )
     }
 
+    /// Returns true iff the request is a state values request
+    pub fn is_state_values_request(&self) -> bool {
+        matches!(self, DataClientRequest::StateValuesWithProof(_))
+    }
+
     /// Returns true iff the request is a subscription request
     pub fn is_subscription_request(&self) -> bool {
         matches!(self, DataClientRequest::SubscribeTransactionsWithProof(_))
@@ -221,9 +226,17 @@ pub struct TransactionsOrOutputsWithProofRequest {
 
 /// A pending client response where data has been requested from the
 /// network and will be available in `client_response` when received.
+///
+/// Note: the same request may be sent multiple times (e.g., if the original
+/// request was slow and has been stolen). In this case, the first successful
+/// response is used, and errors are only used once all requests have completed.
 pub struct PendingClientResponse {
     pub client_request: DataClientRequest,
     pub client_response: Option<Result<Response<ResponsePayload>, aptos_data_client::error::Error>>,
+    pub num_in_flight_requests: u64, // The number of requests still waiting on a response
+    pub request_sent_time: Option<Instant>, // The time the request was first sent
+    pub response_time: Option<Duration>, // The time taken to receive the client response
+    pub stolen: bool,                // Whether the request has been stolen (i.e., resent)
 }
 
 impl PendingClientResponse {
@@ -231,7 +244,46 @@ impl PendingClientResponse {
         Self {
             client_request,
             client_response: None,
+            num_in_flight_requests: 0,
+            request_sent_time: None,
+            response_time: None,
+            stolen: false,
+        }
+    }
+
+    /// Records that a request was sent to the network at the given time
+    pub fn record_sent_request(&mut self, sent_time: Instant) {
+        self.num_in_flight_requests = self.num_in_flight_requests.saturating_add(1);
+        if self.request_sent_time.is_none() {
+            self.request_sent_time = Some(sent_time);
+        }
+    }
+
+    /// Records the given client response (and the time taken to receive it).
+    /// Returns true iff the response was saved (i.e., it is the response that
+    /// will be processed by the stream).
+    pub fn record_client_response(
+        &mut self,
+        client_response: Result<Response<ResponsePayload>, aptos_data_client::error::Error>,
+        response_time: Duration,
+    ) -> bool {
+        self.num_in_flight_requests = self.num_in_flight_requests.saturating_sub(1);
+
+        // If a response has already been saved, ignore the new one
+        if self.client_response.is_some() {
+            return false;
         }
+
+        // If the response is an error, but other requests are still
+        // in-flight, ignore the error and wait for the other responses.
+        if client_response.is_err() && self.num_in_flight_requests > 0 {
+            return false;
+        }
+
+        // Otherwise, save the response
+        self.client_response = Some(client_response);
+        self.response_time = Some(response_time);
+        true
     }
 
     #[cfg(test)]
@@ -241,8 +293,8 @@ impl PendingClientResponse {
         client_response: Result<Response<ResponsePayload>, aptos_data_client::error::Error>,
     ) -> Self {
         Self {
-            client_request,
             client_response: Some(client_response),
+            ..Self::new(client_request)
         }
     }
 }
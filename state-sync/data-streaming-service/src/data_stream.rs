@@ -21,6 +21,7 @@ use crate::{
     stream_engine::{DataStreamEngine, StreamEngine},
     streaming_client::{NotificationFeedback, StreamRequest},
     streaming_service::StreamUpdateNotification,
+    work_stealing::WorkStealingState,
 };
 use aptos_channels::aptos_channel;
 use aptos_config::config::{AptosDataClientConfig, DataStreamingServiceConfig};
@@ -123,6 +124,9 @@ pub struct DataStream<T> {
 
     // The dynamic prefetching state (if enabled)
     dynamic_prefetching_state: DynamicPrefetchingState,
+
+    // The work stealing state (if enabled)
+    work_stealing_state: WorkStealingState,
 }
 
 impl<T: AptosDataClientInterface + Send + Clone + 'static> DataStream<T> {
@@ -149,6 +153,9 @@ impl<T: AptosDataClientInterface + Send + Clone + 'static> DataStream<T> {
         let dynamic_prefetching_state =
             DynamicPrefetchingState::new(data_stream_config, time_service.clone());
 
+        // Create the work stealing state
+        let work_stealing_state = WorkStealingState::new(data_stream_config);
+
         // Create a new data stream
         let data_stream = Self {
             data_client_config,
@@ -168,6 +175,7 @@ impl<T: AptosDataClientInterface + Send + Clone + 'static> DataStream<T> {
             subscription_stream_lag: None,
             time_service,
             dynamic_prefetching_state,
+            work_stealing_state,
         };
 
         Ok((data_stream, data_stream_listener))
@@ -386,12 +394,104 @@ impl<T: AptosDataClientInterface + Send + Clone + 'static> DataStream<T> {
             pending_client_response.clone(),
             request_timeout_ms,
             self.stream_update_notifier.clone(),
+            self.time_service.clone(),
         );
         self.spawned_tasks.push(join_handle);
 
         pending_client_response
     }
 
+    /// Identifies any slow in-flight state value requests and steals them
+    /// (i.e., resends them to the network) so that the data can be fetched
+    /// from other peers (the data client avoids the peers already servicing
+    /// the same request). The first successful response will be used. This
+    /// avoids a single slow peer stalling the entire stream.
+    fn steal_slow_requests(&mut self) -> Result<(), Error> {
+        // If work stealing is disabled, there's nothing to do
+        if !self.work_stealing_state.is_work_stealing_enabled() {
+            return Ok(());
+        }
+
+        // Identify the slow in-flight requests (ordered from oldest to newest)
+        let sent_data_requests: Vec<PendingClientResponse> =
+            self.get_sent_data_requests()?.iter().cloned().collect();
+        let time_now = self.time_service.now();
+        let mut num_in_flight_stolen_requests = 0;
+        let mut slow_requests = vec![];
+        for sent_data_request in sent_data_requests {
+            let pending_response = sent_data_request.lock();
+            if pending_response.client_response.is_some()
+                || !pending_response.client_request.is_state_values_request()
+            {
+                continue; // The request has completed or can't be stolen
+            }
+
+            if pending_response.stolen {
+                num_in_flight_stolen_requests += 1;
+            } else if let Some(request_sent_time) = pending_response.request_sent_time {
+                let in_flight_duration = time_now.duration_since(request_sent_time);
+                if self.work_stealing_state.is_request_slow(in_flight_duration) {
+                    slow_requests.push(sent_data_request.clone());
+                }
+            }
+        }
+
+        // Calculate the timeout for the stolen requests. Given that the original
+        // requests are slow, we treat the stolen requests as retries (i.e., the
+        // timeout is doubled, but bounded by the max timeout).
+        let request_timeout_ms = min(
+            self.data_client_config.max_response_timeout_ms,
+            self.data_client_config
+                .response_timeout_ms
+                .saturating_mul(2),
+        );
+
+        // Steal as many slow requests as the config allows
+        let max_requests_to_steal = self
+            .work_stealing_state
+            .get_max_in_flight_stolen_requests()
+            .saturating_sub(num_in_flight_stolen_requests);
+        for pending_client_response in slow_requests
+            .into_iter()
+            .take(max_requests_to_steal as usize)
+        {
+            // Mark the request as stolen
+            let data_client_request = {
+                let mut pending_response = pending_client_response.lock();
+                pending_response.stolen = true;
+                pending_response.client_request.clone()
+            };
+
+            // Update the stolen request counter and log the request
+            increment_counter(
+                &metrics::STOLEN_DATA_REQUESTS,
+                data_client_request.get_label(),
+            );
+            debug!(
+                (LogSchema::new(LogEntry::StealDataRequest)
+                    .stream_id(self.data_stream_id)
+                    .message(&format!(
+                        "Stealing slow data request: {:?}",
+                        data_client_request
+                    )))
+            );
+
+            // Resend the request to the network (using the same pending response)
+            let join_handle = spawn_request_task(
+                self.data_stream_id,
+                data_client_request,
+                self.aptos_data_client.clone(),
+                pending_client_response,
+                request_timeout_ms,
+                self.stream_update_notifier.clone(),
+                self.time_service.clone(),
+            );
+            self.spawned_tasks.push(join_handle);
+        }
+
+        Ok(())
+    }
+
     // TODO(joshlind): this function shouldn't be blocking when trying to send.
     // If there are multiple streams, a single blocked stream could cause them
     // all to block. This is acceptable for now (because there is only ever
@@ -462,6 +562,7 @@ impl<T: AptosDataClientInterface + Send + Clone + 'static> DataStream<T> {
                 Error::UnexpectedErrorEncountered("The client response should be ready!".into())
             })?;
             let client_request = &pending_response.lock().client_request.clone();
+            let response_time = pending_response.lock().response_time;
 
             // Process the client response
             match client_response {
@@ -511,6 +612,13 @@ impl<T: AptosDataClientInterface + Send + Clone + 'static> DataStream<T> {
                                 .increase_max_concurrent_requests();
                         }
 
+                        // If the request is for state values, update the work stealing state
+                        if client_request.is_state_values_request() {
+                            if let Some(response_time) = response_time {
+                                self.work_stealing_state.record_response_time(response_time);
+                            }
+                        }
+
                         // If we're head of line blocked, we should return early
                         if head_of_line_blocked {
                             break;
@@ -540,6 +648,9 @@ impl<T: AptosDataClientInterface + Send + Clone + 'static> DataStream<T> {
             }
         }
 
+        // Steal any slow requests to avoid stalling the stream on slow peers
+        self.steal_slow_requests()?;
+
         // Create and send further client requests to the network
         // to ensure we're maximizing the number of concurrent requests.
         self.create_and_send_client_requests(&global_data_summary)
@@ -1402,6 +1513,7 @@ fn spawn_request_task<T: AptosDataClientInterface + Send + Clone + 'static>(
     pending_response: PendingClientResponse,
     request_timeout_ms: u64,
     stream_update_notifier: aptos_channel::Sender<(), StreamUpdateNotification>,
+    time_service: TimeService,
 ) -> JoinHandle<()> {
     // Update the requests sent counter
     increment_counter(
@@ -1409,6 +1521,12 @@ fn spawn_request_task<T: AptosDataClientInterface + Send + Clone + 'static>(
         data_client_request.get_label(),
     );
 
+    // Record the sent request
+    let request_start_time = time_service.now();
+    pending_response
+        .lock()
+        .record_sent_request(request_start_time);
+
     // Spawn the request
     tokio::spawn(async move {
         // Time the request (the timer will stop when it's dropped)
@@ -1498,8 +1616,16 @@ fn spawn_request_task<T: AptosDataClientInterface + Send + Clone + 'static>(
             },
         }
 
-        // Save the response
-        pending_response.lock().client_response = Some(client_response);
+        // Save the response. If the response isn't saved (e.g., because
+        // the request was stolen and another response was already
+        // received), there's no need to notify the stream.
+        let response_time = time_service.now().duration_since(request_start_time);
+        let response_saved = pending_response
+            .lock()
+            .record_client_response(client_response, response_time);
+        if !response_saved {
+            return;
+        }
 
         // Send a notification via the stream update notifier
         let stream_update_notification = StreamUpdateNotification::new(data_stream_id);
@@ -1760,6 +1886,7 @@ mod test {
             pending_client_response.clone(),
             1000,
             stream_update_notifier.clone(),
+            TimeService::mock(),
         );
 
         // Wait for the request to complete
@@ -13,6 +13,7 @@ mod metrics;
 mod stream_engine;
 pub mod streaming_client;
 pub mod streaming_service;
+mod work_stealing;
 
 #[cfg(test)]
 mod tests;
@@ -43,6 +43,7 @@ pub enum LogEntry {
     RespondToStreamRequest,
     RetryDataRequest,
     SendDataRequests,
+    StealDataRequest,
     StreamNotification,
     TerminateStream,
 }
@@ -123,6 +123,16 @@ pub static SENT_DATA_REQUESTS_FOR_MISSING_DATA: Lazy<IntCounterVec> = Lazy::new(
     .unwrap()
 });
 
+/// Counter for tracking slow data requests that were stolen (i.e., resent)
+pub static STOLEN_DATA_REQUESTS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_data_streaming_service_stolen_data_requests",
+        "Counters related to slow data requests that were stolen (i.e., resent)",
+        &["request_type"]
+    )
+    .unwrap()
+});
+
 /// Counter for tracking data requests that were retried (including
 /// the new timeouts).
 pub static RETRIED_DATA_REQUESTS: Lazy<IntCounterVec> = Lazy::new(|| {
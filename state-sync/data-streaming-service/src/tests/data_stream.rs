@@ -32,7 +32,7 @@ use crate::{
 };
 use aptos_channels::{aptos_channel, message_queues::QueueStyle};
 use aptos_config::config::{
-    AptosDataClientConfig, DataStreamingServiceConfig, DynamicPrefetchingConfig,
+    AptosDataClientConfig, DataStreamingServiceConfig, DynamicPrefetchingConfig, WorkStealingConfig,
 };
 use aptos_data_client::{
     global_summary::{AdvertisedData, GlobalDataSummary, OptimalChunkSizes},
@@ -495,6 +495,125 @@ async fn test_state_stream_out_of_order_responses_dynamic() {
     );
 }
 
+#[tokio::test]
+async fn test_state_stream_work_stealing() {
+    // Create a state value data stream with work stealing enabled
+    let max_concurrent_state_requests = 6;
+    let max_in_flight_stolen_requests = 2;
+    let min_steal_duration_ms = 1000;
+    let streaming_service_config = DataStreamingServiceConfig {
+        dynamic_prefetching: DynamicPrefetchingConfig {
+            enable_dynamic_prefetching: false,
+            ..Default::default()
+        },
+        max_concurrent_requests: 1,
+        max_concurrent_state_requests,
+        work_stealing: WorkStealingConfig {
+            enable_work_stealing: true,
+            max_in_flight_stolen_requests,
+            min_steal_duration_ms,
+            slow_response_multiplier: 3,
+        },
+        ..Default::default()
+    };
+    let (mut data_stream, mut stream_listener, time_service) =
+        create_state_value_stream_with_time_service(streaming_service_config);
+
+    // Initialize the data stream and mark all state value requests as pending
+    let global_data_summary = create_global_data_summary(1);
+    initialize_state_value_requests_as_pending(
+        &mut data_stream,
+        &global_data_summary,
+        max_concurrent_state_requests,
+    )
+    .await;
+
+    // Elapse some time and verify no requests are stolen (the average response time is unknown)
+    let time_service = time_service.into_mock();
+    time_service.advance(Duration::from_millis(min_steal_duration_ms * 10));
+    process_data_responses(&mut data_stream, &global_data_summary).await;
+    verify_stolen_requests(&mut data_stream, &[]);
+
+    // Set a response (with a known response time) for the first request and process it
+    set_state_value_response_in_queue(&mut data_stream, 0, 0, 0);
+    set_response_time_in_queue(&mut data_stream, 0, Duration::from_millis(100));
+    process_data_responses(&mut data_stream, &global_data_summary).await;
+
+    // Verify that only the oldest slow requests were stolen (the new request is not stolen)
+    verify_num_sent_requests(&mut data_stream, max_concurrent_state_requests);
+    verify_stolen_requests(&mut data_stream, &[0, 1]);
+
+    // Verify a notification was sent for the first response
+    let data_notification = get_data_notification(&mut stream_listener).await.unwrap();
+    assert_matches!(
+        data_notification.data_payload,
+        DataPayload::StateValuesWithProof(_)
+    );
+
+    // Wait for the stolen requests to be serviced by the data client and process them
+    for index in 0..max_in_flight_stolen_requests {
+        wait_for_data_client_to_respond(&mut data_stream, index as usize).await;
+    }
+    process_data_responses(&mut data_stream, &global_data_summary).await;
+
+    // Verify notifications were sent for the stolen requests
+    for _ in 0..max_in_flight_stolen_requests {
+        let data_notification = get_data_notification(&mut stream_listener).await.unwrap();
+        assert_matches!(
+            data_notification.data_payload,
+            DataPayload::StateValuesWithProof(_)
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_state_stream_work_stealing_disabled() {
+    // Create a state value data stream with work stealing disabled
+    let max_concurrent_state_requests = 6;
+    let streaming_service_config = DataStreamingServiceConfig {
+        dynamic_prefetching: DynamicPrefetchingConfig {
+            enable_dynamic_prefetching: false,
+            ..Default::default()
+        },
+        max_concurrent_requests: 1,
+        max_concurrent_state_requests,
+        work_stealing: WorkStealingConfig {
+            enable_work_stealing: false,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let (mut data_stream, mut stream_listener, time_service) =
+        create_state_value_stream_with_time_service(streaming_service_config);
+
+    // Initialize the data stream and mark all state value requests as pending
+    let global_data_summary = create_global_data_summary(1);
+    initialize_state_value_requests_as_pending(
+        &mut data_stream,
+        &global_data_summary,
+        max_concurrent_state_requests,
+    )
+    .await;
+
+    // Set a response (with a known response time) for the first request and process it
+    set_state_value_response_in_queue(&mut data_stream, 0, 0, 0);
+    set_response_time_in_queue(&mut data_stream, 0, Duration::from_millis(100));
+    process_data_responses(&mut data_stream, &global_data_summary).await;
+    let data_notification = get_data_notification(&mut stream_listener).await.unwrap();
+    assert_matches!(
+        data_notification.data_payload,
+        DataPayload::StateValuesWithProof(_)
+    );
+
+    // Elapse a large amount of time and verify no requests are ever stolen
+    let time_service = time_service.into_mock();
+    for _ in 0..10 {
+        time_service.advance(Duration::from_secs(100));
+        process_data_responses(&mut data_stream, &global_data_summary).await;
+        verify_stolen_requests(&mut data_stream, &[]);
+    }
+}
+
 #[tokio::test]
 async fn test_stream_max_pending_requests() {
     // Create an epoch ending data stream with dynamic prefetching disabled
@@ -3327,6 +3446,26 @@ fn create_state_value_stream(
     (data_stream, data_stream_listener)
 }
 
+/// Creates a state value stream (at the min advertised version) and
+/// returns the time service used by the stream.
+fn create_state_value_stream_with_time_service(
+    streaming_service_config: DataStreamingServiceConfig,
+) -> (
+    DataStream<MockAptosDataClient>,
+    DataStreamListener,
+    TimeService,
+) {
+    let stream_request = StreamRequest::GetAllStates(GetAllStatesRequest {
+        version: MIN_ADVERTISED_STATES,
+        start_index: 0,
+    });
+    create_data_stream(
+        AptosDataClientConfig::default(),
+        streaming_service_config,
+        stream_request,
+    )
+}
+
 /// Creates an epoch ending stream starting at `start_epoch`
 fn create_epoch_ending_stream(
     data_client_config: AptosDataClientConfig,
@@ -3708,6 +3847,17 @@ fn set_state_value_response_in_queue(
     pending_response.lock().client_response = client_response;
 }
 
+/// Sets the response time of the client response at the index in the pending queue
+fn set_response_time_in_queue(
+    data_stream: &mut DataStream<MockAptosDataClient>,
+    index: usize,
+    response_time: Duration,
+) {
+    let (sent_requests, _) = data_stream.get_sent_requests_and_notifications();
+    let pending_response = sent_requests.as_mut().unwrap().get_mut(index).unwrap();
+    pending_response.lock().response_time = Some(response_time);
+}
+
 /// Sets the client response at the index in the pending
 /// queue to contain new data.
 fn set_new_data_response_in_queue(
@@ -3933,6 +4083,27 @@ fn initialize_data_requests(
         .unwrap();
 }
 
+/// Initializes the data requests for the given state value stream, and
+/// marks all state value requests as pending (after the data client has
+/// responded to them). This emulates slow in-flight requests.
+async fn initialize_state_value_requests_as_pending(
+    data_stream: &mut DataStream<MockAptosDataClient>,
+    global_data_summary: &GlobalDataSummary,
+    max_concurrent_state_requests: u64,
+) {
+    // Initialize the data stream and set a response for the number of state values
+    initialize_data_requests(data_stream, global_data_summary);
+    set_num_state_values_response_in_queue(data_stream, 0);
+    process_data_responses(data_stream, global_data_summary).await;
+    verify_num_sent_requests(data_stream, max_concurrent_state_requests);
+
+    // Wait for the data client to respond to the requests, and mark them as pending
+    for index in 0..max_concurrent_state_requests as usize {
+        wait_for_data_client_to_respond(data_stream, index).await;
+        set_pending_response_in_queue(data_stream, index);
+    }
+}
+
 /// Helper function to process data responses on the given data stream
 async fn process_data_responses(
     data_stream: &mut DataStream<MockAptosDataClient>,
@@ -3993,6 +4164,21 @@ fn verify_num_sent_requests(
     assert_eq!(num_sent_requests, expected_length);
 }
 
+/// Verifies that only the requests at the given indices (in the
+/// pending queue) have been stolen.
+fn verify_stolen_requests(
+    data_stream: &mut DataStream<MockAptosDataClient>,
+    stolen_indices: &[usize],
+) {
+    let (sent_requests, _) = data_stream.get_sent_requests_and_notifications();
+    for (index, pending_response) in sent_requests.as_ref().unwrap().iter().enumerate() {
+        assert_eq!(
+            pending_response.lock().stolen,
+            stolen_indices.contains(&index)
+        );
+    }
+}
+
 /// Verifies that a single pending optimistic fetch exists and
 /// that it is for the correct data.
 fn verify_pending_optimistic_fetch(
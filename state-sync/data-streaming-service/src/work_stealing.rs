@@ -0,0 +1,168 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_config::config::{DataStreamingServiceConfig, WorkStealingConfig};
+use std::{cmp::max, time::Duration};
+
+// The weight given to new observations when updating the average response time
+const RESPONSE_TIME_WEIGHT: f64 = 0.2;
+
+/// A simple container for the work stealing state. This tracks the average
+/// response time of state value requests, and identifies in-flight requests
+/// that are slow (e.g., because they were sent to slow peers) and should
+/// be stolen (i.e., resent to the network).
+#[derive(Debug)]
+pub struct WorkStealingState {
+    // The work stealing config
+    work_stealing_config: WorkStealingConfig,
+
+    // The average response time of completed requests (if any)
+    average_response_time: Option<Duration>,
+}
+
+impl WorkStealingState {
+    pub fn new(data_streaming_service_config: DataStreamingServiceConfig) -> Self {
+        Self {
+            work_stealing_config: data_streaming_service_config.work_stealing,
+            average_response_time: None,
+        }
+    }
+
+    /// Returns true iff work stealing is enabled
+    pub fn is_work_stealing_enabled(&self) -> bool {
+        self.work_stealing_config.enable_work_stealing
+    }
+
+    /// Returns the maximum number of stolen requests that can be in-flight
+    pub fn get_max_in_flight_stolen_requests(&self) -> u64 {
+        self.work_stealing_config.max_in_flight_stolen_requests
+    }
+
+    /// Returns the average response time of completed requests (if any)
+    pub fn get_average_response_time(&self) -> Option<Duration> {
+        self.average_response_time
+    }
+
+    /// Returns true iff a request that has been in-flight for the given
+    /// duration is slow, and should be stolen. Requests are only considered
+    /// slow once the average response time is known.
+    pub fn is_request_slow(&self, in_flight_duration: Duration) -> bool {
+        // If work stealing is disabled, no requests are slow
+        if !self.is_work_stealing_enabled() {
+            return false;
+        }
+
+        // If we don't know the average response time, we can't identify slow requests
+        let average_response_time = match self.average_response_time {
+            Some(average_response_time) => average_response_time,
+            None => return false,
+        };
+
+        // Calculate the slow request threshold
+        let min_steal_duration =
+            Duration::from_millis(self.work_stealing_config.min_steal_duration_ms);
+        let slow_response_time = average_response_time
+            .saturating_mul(self.work_stealing_config.slow_response_multiplier as u32);
+        let slow_request_threshold = max(min_steal_duration, slow_response_time);
+
+        in_flight_duration > slow_request_threshold
+    }
+
+    /// Updates the average response time using the given response time.
+    /// This is typically called after a successful response is received.
+    pub fn record_response_time(&mut self, response_time: Duration) {
+        let average_response_time = match self.average_response_time {
+            Some(average_response_time) => average_response_time
+                .mul_f64(1.0 - RESPONSE_TIME_WEIGHT)
+                .saturating_add(response_time.mul_f64(RESPONSE_TIME_WEIGHT)),
+            None => response_time,
+        };
+        self.average_response_time = Some(average_response_time);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_record_response_time() {
+        // Create the work stealing state
+        let mut work_stealing_state = WorkStealingState::new(DataStreamingServiceConfig::default());
+        assert!(work_stealing_state.get_average_response_time().is_none());
+
+        // Record a response time and verify it is used as the average
+        work_stealing_state.record_response_time(Duration::from_millis(1000));
+        assert_eq!(
+            work_stealing_state.get_average_response_time(),
+            Some(Duration::from_millis(1000))
+        );
+
+        // Record another response time and verify the moving average is updated
+        work_stealing_state.record_response_time(Duration::from_millis(2000));
+        let average_response_time_ms = work_stealing_state
+            .get_average_response_time()
+            .unwrap()
+            .as_secs_f64()
+            * 1000.0;
+        assert!((average_response_time_ms - 1200.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_is_request_slow() {
+        // Create the work stealing state
+        let data_streaming_service_config = DataStreamingServiceConfig {
+            work_stealing: WorkStealingConfig {
+                enable_work_stealing: true,
+                min_steal_duration_ms: 1000,
+                slow_response_multiplier: 3,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut work_stealing_state = WorkStealingState::new(data_streaming_service_config);
+
+        // Verify no requests are slow if the average response time is unknown
+        for in_flight_duration_secs in [0, 1, 10, 100] {
+            assert!(
+                !work_stealing_state.is_request_slow(Duration::from_secs(in_flight_duration_secs))
+            );
+        }
+
+        // Record a small response time and verify the min steal duration is used
+        work_stealing_state.record_response_time(Duration::from_millis(100));
+        assert!(!work_stealing_state.is_request_slow(Duration::from_millis(500)));
+        assert!(!work_stealing_state.is_request_slow(Duration::from_millis(1000)));
+        assert!(work_stealing_state.is_request_slow(Duration::from_millis(1001)));
+
+        // Create a new work stealing state with a large response time
+        let mut work_stealing_state = WorkStealingState::new(data_streaming_service_config);
+        work_stealing_state.record_response_time(Duration::from_millis(2000));
+
+        // Verify the slow response multiplier is used
+        assert!(!work_stealing_state.is_request_slow(Duration::from_millis(2000)));
+        assert!(!work_stealing_state.is_request_slow(Duration::from_millis(6000)));
+        assert!(work_stealing_state.is_request_slow(Duration::from_millis(6001)));
+    }
+
+    #[test]
+    fn test_work_stealing_disabled() {
+        // Create the work stealing state with work stealing disabled
+        let data_streaming_service_config = DataStreamingServiceConfig {
+            work_stealing: WorkStealingConfig {
+                enable_work_stealing: false,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut work_stealing_state = WorkStealingState::new(data_streaming_service_config);
+
+        // Record a response time and verify no requests are ever slow
+        work_stealing_state.record_response_time(Duration::from_millis(10));
+        for in_flight_duration_secs in [0, 1, 10, 100, 1000] {
+            assert!(
+                !work_stealing_state.is_request_slow(Duration::from_secs(in_flight_duration_secs))
+            );
+        }
+    }
+}
@@ -0,0 +1,170 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::logging::{LogEntry, LogSchema};
+use aptos_data_streaming_service::{
+    data_notification::{DataNotification, DataPayload, NotificationId},
+    data_stream::{DataStreamId, DataStreamListener},
+};
+use aptos_logger::prelude::*;
+use aptos_types::{
+    ledger_info::LedgerInfoWithSignatures,
+    state_store::state_value::StateValueChunkWithProof,
+    transaction::{TransactionOutputListWithProofV2, Version},
+};
+use async_trait::async_trait;
+use futures::{channel::mpsc, SinkExt};
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+// The maximum number of notifications buffered for a backup stream
+const BACKUP_STREAM_BUFFER_SIZE: usize = 10;
+
+// The maximum number of epoch ending ledger infos per notification
+const MAX_EPOCH_CHUNK_SIZE: usize = 200;
+
+// Backup streams are never registered with the data streaming service, so
+// their ids are handed out from the top of the id space to avoid confusion
+// with streaming service ids in the logs.
+static NEXT_BACKUP_STREAM_ID: AtomicU64 = AtomicU64::new(u64::MAX);
+
+/// A source of bootstrapping data read from a backup (e.g., one written by the
+/// backup coordinator to cloud storage). Data returned by the source is never
+/// trusted: the bootstrapper verifies it exactly like data fetched from peers.
+#[async_trait]
+pub trait BackupDataSource: Send + Sync {
+    /// Returns all epoch ending ledger infos in the backup, starting at `start_epoch`
+    async fn get_epoch_ending_ledger_infos(
+        &self,
+        start_epoch: u64,
+    ) -> anyhow::Result<Vec<LedgerInfoWithSignatures>>;
+
+    /// Returns the versions of all state snapshots in the backup. Snapshots
+    /// are always taken at epoch ending versions.
+    async fn get_state_snapshot_versions(&self) -> anyhow::Result<Vec<Version>>;
+
+    /// Returns the output of the transaction at `version`, proven against the
+    /// epoch ending ledger info at `version`.
+    async fn get_transaction_output(
+        &self,
+        version: Version,
+    ) -> anyhow::Result<TransactionOutputListWithProofV2>;
+
+    /// Returns the chunk of the state snapshot at `version` that contains
+    /// `start_index`, with all state values before `start_index` removed.
+    async fn get_state_value_chunk(
+        &self,
+        version: Version,
+        start_index: u64,
+    ) -> anyhow::Result<StateValueChunkWithProof>;
+}
+
+/// Returns a stream of all epoch ending ledger infos in the backup, starting at `start_epoch`
+pub(crate) fn stream_epoch_ending_ledger_infos(
+    backup_source: Arc<dyn BackupDataSource>,
+    start_epoch: u64,
+) -> DataStreamListener {
+    spawn_backup_stream(move |mut sender| async move {
+        let epoch_ending_ledger_infos = backup_source
+            .get_epoch_ending_ledger_infos(start_epoch)
+            .await?;
+        for chunk in epoch_ending_ledger_infos.chunks(MAX_EPOCH_CHUNK_SIZE) {
+            sender
+                .send(DataPayload::EpochEndingLedgerInfos(chunk.to_vec()))
+                .await?;
+        }
+        Ok(())
+    })
+}
+
+/// Returns a stream containing the output of the transaction at `version`
+pub(crate) fn stream_transaction_output(
+    backup_source: Arc<dyn BackupDataSource>,
+    version: Version,
+) -> DataStreamListener {
+    spawn_backup_stream(move |mut sender| async move {
+        let transaction_output = backup_source.get_transaction_output(version).await?;
+        sender
+            .send(DataPayload::TransactionOutputsWithProof(transaction_output))
+            .await
+    })
+}
+
+/// Returns a stream of the state snapshot at `version`, starting at `start_index`
+pub(crate) fn stream_state_values(
+    backup_source: Arc<dyn BackupDataSource>,
+    version: Version,
+    start_index: u64,
+) -> DataStreamListener {
+    spawn_backup_stream(move |mut sender| async move {
+        let mut next_index = start_index;
+        loop {
+            let state_value_chunk = backup_source
+                .get_state_value_chunk(version, next_index)
+                .await?;
+            let is_last_chunk = state_value_chunk.is_last_chunk();
+            next_index = state_value_chunk.last_index + 1;
+            sender
+                .send(DataPayload::StateValuesWithProof(state_value_chunk))
+                .await?;
+            if is_last_chunk {
+                return Ok(());
+            }
+        }
+    })
+}
+
+/// Assigns notification ids to the payloads sent through a backup stream
+struct BackupStreamSender {
+    next_notification_id: NotificationId,
+    notification_sender: mpsc::Sender<DataNotification>,
+}
+
+impl BackupStreamSender {
+    async fn send(&mut self, data_payload: DataPayload) -> anyhow::Result<()> {
+        let notification_id = self.next_notification_id;
+        self.next_notification_id += 1;
+        self.notification_sender
+            .send(DataNotification::new(notification_id, data_payload))
+            .await?;
+        Ok(())
+    }
+}
+
+/// Spawns a task that produces the payloads of a backup stream. The stream
+/// always ends with an end of stream notification (even if reading the backup
+/// fails), so that the bootstrapper resets it and decides what to do next.
+fn spawn_backup_stream<F, Fut>(produce_payloads: F) -> DataStreamListener
+where
+    F: FnOnce(BackupStreamSender) -> Fut + Send + 'static,
+    Fut: std::future::Future<Output = anyhow::Result<()>> + Send + 'static,
+{
+    let data_stream_id: DataStreamId = NEXT_BACKUP_STREAM_ID.fetch_sub(1, Ordering::Relaxed);
+    let (mut notification_sender, notification_receiver) = mpsc::channel(BACKUP_STREAM_BUFFER_SIZE);
+
+    tokio::spawn(async move {
+        let sender = BackupStreamSender {
+            next_notification_id: 0,
+            notification_sender: notification_sender.clone(),
+        };
+        if let Err(error) = produce_payloads(sender).await {
+            warn!(LogSchema::new(LogEntry::Bootstrapper).message(&format!(
+                "Failed to read data from the backup for stream {:?}! Error: {:?}",
+                data_stream_id, error
+            )));
+        }
+
+        // Notification ids are only used for feedback to the streaming
+        // service, which never sees backup streams.
+        let _ = notification_sender
+            .send(DataNotification::new(
+                NotificationId::MAX,
+                DataPayload::EndOfStream,
+            ))
+            .await;
+    });
+
+    DataStreamListener::new(data_stream_id, notification_receiver)
+}
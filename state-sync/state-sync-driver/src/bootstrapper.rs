@@ -3,6 +3,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    backup_source,
+    backup_source::BackupDataSource,
     driver::DriverConfiguration,
     error::Error,
     logging::{LogEntry, LogSchema},
@@ -14,7 +16,8 @@ use crate::{
     utils::{OutputFallbackHandler, SpeculativeStreamState, PENDING_DATA_LOG_FREQ_SECS},
 };
 use aptos_config::config::BootstrappingMode;
-use aptos_data_client::global_summary::GlobalDataSummary;
+use aptos_crypto::{hash::CryptoHash, HashValue};
+use aptos_data_client::global_summary::{AdvertisedData, GlobalDataSummary};
 use aptos_data_streaming_service::{
     data_notification::{DataNotification, DataPayload, NotificationId},
     data_stream::DataStreamListener,
@@ -253,6 +256,10 @@ pub(crate) struct StateValueSyncer {
     // processed -- i.e., sent to the storage synchronizer).
     next_state_index_to_process: u64,
 
+    // The key hash of the last persisted state value (if the snapshot sync is being
+    // resumed). The first state value received after resuming must match this hash.
+    resumed_state_key_hash: Option<HashValue>,
+
     // The transaction output (inc. info and proof) for the version we're syncing
     transaction_output_to_sync: Option<TransactionOutputListWithProofV2>,
 }
@@ -263,6 +270,7 @@ impl StateValueSyncer {
             initialized_state_snapshot_receiver: false,
             ledger_info_to_sync: None,
             next_state_index_to_process: 0,
+            resumed_state_key_hash: None,
             transaction_output_to_sync: None,
         }
     }
@@ -284,6 +292,11 @@ impl StateValueSyncer {
     pub fn update_next_state_index_to_process(&mut self, next_state_index_to_process: u64) {
         self.next_state_index_to_process = next_state_index_to_process;
     }
+
+    /// Updates the key hash of the state value that the resumed snapshot sync must start with
+    pub fn update_resumed_state_key_hash(&mut self, resumed_state_key_hash: Option<HashValue>) {
+        self.resumed_state_key_hash = resumed_state_key_hash;
+    }
 }
 
 /// A simple component that manages the bootstrapping of the node
@@ -291,6 +304,12 @@ pub struct Bootstrapper<MetadataStorage, StorageSyncer, StreamingClient> {
     // The currently active data stream (provided by the data streaming service)
     active_data_stream: Option<DataStreamListener>,
 
+    // If the active data stream is read from the backup (instead of the streaming service)
+    active_data_stream_from_backup: bool,
+
+    // If the epoch ending ledger infos in the backup have already been fetched
+    fetched_backup_epoch_endings: bool,
+
     // The channel used to notify a listener of successful bootstrapping
     bootstrap_notifier_channel: Option<oneshot::Sender<Result<(), Error>>>,
 
@@ -347,6 +366,8 @@ impl<
         Self {
             state_value_syncer: StateValueSyncer::new(),
             active_data_stream: None,
+            active_data_stream_from_backup: false,
+            fetched_backup_epoch_endings: false,
             bootstrap_notifier_channel: None,
             bootstrapped: false,
             driver_configuration,
@@ -458,8 +479,14 @@ impl<
         // Reset the chunk executor to flush any invalid state currently held in-memory
         self.storage_synchronizer.reset_chunk_executor()?;
 
-        // Always fetch the new epoch ending ledger infos first
+        // Always fetch the new epoch ending ledger infos first. If a backup
+        // is configured, fetch everything it holds before asking the peers.
         if self.should_fetch_epoch_ending_ledger_infos() {
+            if self.driver_configuration.backup_source.is_some()
+                && !self.fetched_backup_epoch_endings
+            {
+                return self.fetch_backup_epoch_ending_ledger_infos();
+            }
             return self
                 .fetch_epoch_ending_ledger_infos(global_data_summary)
                 .await;
@@ -501,6 +528,7 @@ impl<
         if self.get_bootstrapping_mode().is_fast_sync() {
             // We're fast syncing
             self.fetch_missing_state_snapshot_data(
+                global_data_summary,
                 highest_synced_version,
                 highest_known_ledger_info,
             )
@@ -515,6 +543,7 @@ impl<
     /// Fetches all missing state snapshot data in order to bootstrap the node
     async fn fetch_missing_state_snapshot_data(
         &mut self,
+        global_data_summary: &GlobalDataSummary,
         highest_synced_version: Version,
         highest_known_ledger_info: LedgerInfoWithSignatures,
     ) -> Result<(), Error> {
@@ -538,15 +567,50 @@ impl<
                             target
                         )))
                     }
+                } else if self
+                    .should_switch_snapshot_sync_target(
+                        global_data_summary,
+                        &target,
+                        &highest_known_ledger_info,
+                    )
+                    .await?
+                {
+                    // The previous target can no longer be synced. Switch to the highest
+                    // known ledger info (continuing from the state persisted for the
+                    // previous target).
+                    info!(LogSchema::new(LogEntry::Bootstrapper).message(&format!(
+                        "Switching the snapshot sync target! Previous target: {:?}, new target: {:?}",
+                        target, highest_known_ledger_info
+                    )));
+                    self.metadata_storage
+                        .switch_snapshot_sync_target(&highest_known_ledger_info)?;
+                    self.fetch_missing_state_values(highest_known_ledger_info, true)
+                        .await
                 } else {
                     // Continue snapshot syncing to the target
                     self.fetch_missing_state_values(target, true).await
                 }
+            } else if let Some(backup_target) = self.select_backup_snapshot_target().await? {
+                // No snapshot sync has started, but the backup holds a snapshot we can sync to
+                info!(LogSchema::new(LogEntry::Bootstrapper).message(&format!(
+                    "Fast syncing to the state snapshot in the backup! Target: {:?}",
+                    backup_target
+                )));
+                self.fetch_missing_state_values(backup_target, false).await
             } else {
                 // No snapshot sync has started. Start a new sync for the highest known ledger info.
                 self.fetch_missing_state_values(highest_known_ledger_info, false)
                     .await
             }
+        } else if self.synced_backup_snapshot(highest_synced_version).await? {
+            // The state snapshot was synced from the backup, which is expected to be older
+            // than the latest state. Let the continuous syncer catch up with the peers.
+            info!(LogSchema::new(LogEntry::Bootstrapper).message(&format!(
+                "The state snapshot from the backup has been synced at version {}! \
+                The peers will provide the remaining data.",
+                highest_synced_version
+            )));
+            self.bootstrapping_complete().await
         } else {
             // This node has already synced some state. Ensure the node is not too far behind.
             let highest_known_ledger_version = highest_known_ledger_info.ledger_info().version();
@@ -686,16 +750,34 @@ impl<
 
         // Fetch the data that we're missing
         let target_ledger_info_version = target_ledger_info.ledger_info().version();
+        let backup_source = self
+            .get_backup_source_for_snapshot(target_ledger_info_version)
+            .await?;
         let data_stream = if self.state_value_syncer.transaction_output_to_sync.is_none() {
             // Fetch the transaction info first, before the states
-            self.streaming_client
-                .get_all_transaction_outputs(
-                    target_ledger_info_version,
-                    target_ledger_info_version,
-                    target_ledger_info_version,
-                )
-                .await?
+            if let Some(backup_source) = backup_source {
+                self.active_data_stream_from_backup = true;
+                backup_source::stream_transaction_output(backup_source, target_ledger_info_version)
+            } else {
+                self.streaming_client
+                    .get_all_transaction_outputs(
+                        target_ledger_info_version,
+                        target_ledger_info_version,
+                        target_ledger_info_version,
+                    )
+                    .await?
+            }
         } else {
+            // Identify the key hash that the resumed snapshot sync must start with (if any)
+            let resumed_state_key_hash = if existing_snapshot_progress {
+                self.metadata_storage
+                    .get_last_persisted_state_key_hash(&target_ledger_info)?
+            } else {
+                None
+            };
+            self.state_value_syncer
+                .update_resumed_state_key_hash(resumed_state_key_hash);
+
             // Identify the next state index to fetch
             let next_state_index_to_process = if existing_snapshot_progress {
                 // The state snapshot receiver requires that after each reboot we
@@ -718,12 +800,21 @@ impl<
             // Fetch the missing state values
             self.state_value_syncer
                 .update_next_state_index_to_process(next_state_index_to_process);
-            self.streaming_client
-                .get_all_state_values(
+            if let Some(backup_source) = backup_source {
+                self.active_data_stream_from_backup = true;
+                backup_source::stream_state_values(
+                    backup_source,
                     target_ledger_info_version,
-                    Some(next_state_index_to_process),
+                    next_state_index_to_process,
                 )
-                .await?
+            } else {
+                self.streaming_client
+                    .get_all_state_values(
+                        target_ledger_info_version,
+                        Some(next_state_index_to_process),
+                    )
+                    .await?
+            }
         };
         self.active_data_stream = Some(data_stream);
 
@@ -810,6 +901,161 @@ impl<
         Ok(())
     }
 
+    /// Fetches all epoch ending ledger infos held by the backup (from the
+    /// current epoch onwards). The backup is only read once: anything newer
+    /// is fetched from the peers by `fetch_epoch_ending_ledger_infos()`.
+    fn fetch_backup_epoch_ending_ledger_infos(&mut self) -> Result<(), Error> {
+        let backup_source = self.get_backup_source()?;
+        let next_epoch = self
+            .get_highest_known_ledger_info()?
+            .ledger_info()
+            .next_block_epoch();
+        info!(LogSchema::new(LogEntry::Bootstrapper).message(&format!(
+            "Fetching the epoch ending ledger infos from the backup, starting at epoch: {:?}",
+            next_epoch
+        )));
+
+        self.fetched_backup_epoch_endings = true;
+        self.active_data_stream_from_backup = true;
+        self.active_data_stream = Some(backup_source::stream_epoch_ending_ledger_infos(
+            backup_source,
+            next_epoch,
+        ));
+
+        Ok(())
+    }
+
+    /// Returns the highest verified epoch ending ledger info that has a state
+    /// snapshot in the backup (if any). Snapshots older than the waypoint are
+    /// ignored, as is everything if no backup is configured.
+    async fn select_backup_snapshot_target(
+        &self,
+    ) -> Result<Option<LedgerInfoWithSignatures>, Error> {
+        let backup_source = match &self.driver_configuration.backup_source {
+            Some(backup_source) => backup_source.clone(),
+            None => return Ok(None),
+        };
+
+        let waypoint_version = self.driver_configuration.waypoint.version();
+        let mut snapshot_versions =
+            backup_source
+                .get_state_snapshot_versions()
+                .await
+                .map_err(|error| {
+                    Error::StorageError(format!(
+                        "Failed to get the state snapshot versions from the backup! Error: {:?}",
+                        error
+                    ))
+                })?;
+        snapshot_versions.sort_unstable();
+        Ok(snapshot_versions
+            .into_iter()
+            .rev()
+            .filter(|version| *version >= waypoint_version)
+            .find_map(|version| {
+                self.verified_epoch_states
+                    .get_epoch_ending_ledger_info(version)
+            }))
+    }
+
+    /// Returns true iff the incomplete snapshot sync for the given target should be
+    /// abandoned in favour of the highest known ledger info. This only happens when
+    /// the snapshot sync hasn't yet started for this run (e.g., after a restart), the
+    /// highest known ledger info is newer, and the state at the previous target is
+    /// no longer advertised by any peers (or held by the backup).
+    async fn should_switch_snapshot_sync_target(
+        &self,
+        global_data_summary: &GlobalDataSummary,
+        target: &LedgerInfoWithSignatures,
+        highest_known_ledger_info: &LedgerInfoWithSignatures,
+    ) -> Result<bool, Error> {
+        // Never switch the target once the snapshot sync is underway
+        if self.state_value_syncer.ledger_info_to_sync.is_some() {
+            return Ok(false);
+        }
+
+        // Only switch if the highest known ledger info is newer
+        let target_version = target.ledger_info().version();
+        if highest_known_ledger_info.ledger_info().version() <= target_version {
+            return Ok(false);
+        }
+
+        // Only switch if no peers advertise the target state (and we've
+        // heard from peers that advertise some state), and the backup
+        // doesn't hold the target state.
+        let advertised_states = &global_data_summary.advertised_data.states;
+        if advertised_states.is_empty()
+            || AdvertisedData::contains_range(target_version, target_version, advertised_states)
+        {
+            return Ok(false);
+        }
+        Ok(self
+            .get_backup_source_for_snapshot(target_version)
+            .await?
+            .is_none())
+    }
+
+    /// Returns the backup source iff the backup holds a state snapshot at the given version
+    async fn get_backup_source_for_snapshot(
+        &self,
+        version: Version,
+    ) -> Result<Option<Arc<dyn BackupDataSource>>, Error> {
+        let backup_source = match &self.driver_configuration.backup_source {
+            Some(backup_source) => backup_source.clone(),
+            None => return Ok(None),
+        };
+
+        let snapshot_versions =
+            backup_source
+                .get_state_snapshot_versions()
+                .await
+                .map_err(|error| {
+                    Error::StorageError(format!(
+                        "Failed to get the state snapshot versions from the backup! Error: {:?}",
+                        error
+                    ))
+                })?;
+        if snapshot_versions.contains(&version) {
+            Ok(Some(backup_source))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Returns true iff the node completed a fast sync to a state snapshot
+    /// at the given version, and the snapshot was read from the backup (i.e.,
+    /// the backup holds a snapshot at that version). Snapshots synced from
+    /// the peers are handled like any other existing state.
+    async fn synced_backup_snapshot(&self, highest_synced_version: Version) -> Result<bool, Error> {
+        if self.driver_configuration.backup_source.is_none() {
+            return Ok(false);
+        }
+
+        let target = match self.metadata_storage.previous_snapshot_sync_target()? {
+            Some(target) => target,
+            None => return Ok(false),
+        };
+        let target_version = target.ledger_info().version();
+        if target_version != highest_synced_version
+            || !self.metadata_storage.is_snapshot_sync_complete(&target)?
+        {
+            return Ok(false);
+        }
+
+        Ok(self
+            .get_backup_source_for_snapshot(target_version)
+            .await?
+            .is_some())
+    }
+
+    /// Returns the configured backup source
+    fn get_backup_source(&self) -> Result<Arc<dyn BackupDataSource>, Error> {
+        self.driver_configuration
+            .backup_source
+            .clone()
+            .ok_or_else(|| Error::UnexpectedError("No backup source is configured!".into()))
+    }
+
     /// Fetches all epoch ending ledger infos (from the current epoch to the
     /// maximum that can be found by the data streaming service).
     async fn fetch_epoch_ending_ledger_infos(
@@ -959,6 +1205,40 @@ impl<
         Ok(())
     }
 
+    /// Verifies that the first state value (after resuming a snapshot sync)
+    /// matches the last state value persisted before the snapshot sync stopped.
+    async fn verify_resumed_state_key(
+        &mut self,
+        notification_id: NotificationId,
+        state_value_chunk_with_proof: &StateValueChunkWithProof,
+    ) -> Result<(), Error> {
+        // Only the first chunk after resuming needs to be verified
+        let expected_key_hash = match self.state_value_syncer.resumed_state_key_hash.take() {
+            Some(expected_key_hash) => expected_key_hash,
+            None => return Ok(()),
+        };
+
+        // Verify the key hash of the first state value
+        let first_key_hash = state_value_chunk_with_proof
+            .raw_values
+            .first()
+            .map(|(state_key, _)| CryptoHash::hash(state_key));
+        if first_key_hash != Some(expected_key_hash) {
+            self.reset_active_stream(Some(NotificationAndFeedback::new(
+                notification_id,
+                NotificationFeedback::InvalidPayloadData,
+            )))
+            .await?;
+            return Err(Error::VerificationError(format!(
+                "The first state value does not match the last persisted state value! \
+                Expected key hash: {:?}, received: {:?}",
+                expected_key_hash, first_key_hash
+            )));
+        }
+
+        Ok(())
+    }
+
     /// Process a single state value chunk with proof payload
     async fn process_state_values_payload(
         &mut self,
@@ -989,7 +1269,13 @@ impl<
             let epoch_change_proofs = if version_to_sync == GENESIS_TRANSACTION_VERSION {
                 vec![ledger_info_to_sync.clone()] // Sync to genesis
             } else {
-                self.verified_epoch_states.all_epoch_ending_ledger_infos() // Sync beyond genesis
+                // Sync beyond genesis. A snapshot from the backup may be older than the
+                // highest known epoch, so only the epochs up to the snapshot are committed.
+                self.verified_epoch_states
+                    .all_epoch_ending_ledger_infos()
+                    .into_iter()
+                    .filter(|ledger_info| ledger_info.ledger_info().version() <= version_to_sync)
+                    .collect()
             };
 
             // Initialize the state value synchronizer
@@ -1005,6 +1291,10 @@ impl<
         self.verify_states_values_indices(notification_id, &state_value_chunk_with_proof)
             .await?;
 
+        // Verify the state values payload resumes from the last persisted state key
+        self.verify_resumed_state_key(notification_id, &state_value_chunk_with_proof)
+            .await?;
+
         // Verify the chunk root hash matches the expected root hash
         let first_transaction_info = transaction_output_to_sync
             .get_output_list_with_proof()
@@ -1542,16 +1832,20 @@ impl<
         notification_and_feedback: Option<NotificationAndFeedback>,
     ) -> Result<(), Error> {
         if let Some(active_data_stream) = &self.active_data_stream {
-            let data_stream_id = active_data_stream.data_stream_id;
-            utils::terminate_stream_with_feedback(
-                &mut self.streaming_client,
-                data_stream_id,
-                notification_and_feedback,
-            )
-            .await?;
+            // Backup streams are not known to the streaming service
+            if !self.active_data_stream_from_backup {
+                let data_stream_id = active_data_stream.data_stream_id;
+                utils::terminate_stream_with_feedback(
+                    &mut self.streaming_client,
+                    data_stream_id,
+                    notification_and_feedback,
+                )
+                .await?;
+            }
         }
 
         self.active_data_stream = None;
+        self.active_data_stream_from_backup = false;
         self.speculative_stream_state = None;
         Ok(())
     }
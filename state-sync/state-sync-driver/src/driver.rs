@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    backup_source::BackupDataSource,
     bootstrapper::Bootstrapper,
     continuous_syncer::ContinuousSyncer,
     driver_client::{ClientNotificationListener, DriverNotification},
@@ -63,6 +64,9 @@ pub struct DriverConfiguration {
 
     // The trusted waypoint for the node
     pub waypoint: Waypoint,
+
+    // The backup to bootstrap from (if any)
+    pub backup_source: Option<Arc<dyn BackupDataSource>>,
 }
 
 impl DriverConfiguration {
@@ -71,12 +75,14 @@ impl DriverConfiguration {
         consensus_observer_config: ConsensusObserverConfig,
         role: RoleType,
         waypoint: Waypoint,
+        backup_source: Option<Arc<dyn BackupDataSource>>,
     ) -> Self {
         Self {
             config,
             consensus_observer_config,
             role,
             waypoint,
+            backup_source,
         }
     }
 }
@@ -4,6 +4,7 @@
 
 #![forbid(unsafe_code)]
 
+pub mod backup_source;
 mod bootstrapper;
 mod continuous_syncer;
 mod driver;
@@ -14,6 +15,7 @@ mod logging;
 pub mod metadata_storage;
 pub mod metrics;
 mod notification_handlers;
+mod snapshot_progress;
 mod storage_synchronizer;
 mod utils;
 
@@ -6,6 +6,7 @@ use crate::{
     metadata_storage::database_schema::{MetadataKey, MetadataSchema, MetadataValue},
 };
 use anyhow::{anyhow, Result};
+use aptos_crypto::HashValue;
 use aptos_logger::prelude::*;
 use aptos_schemadb::{
     batch::SchemaBatch,
@@ -13,7 +14,7 @@ use aptos_schemadb::{
     schema::{KeyCodec, ValueCodec},
     ColumnFamilyName, Options, DB,
 };
-use aptos_types::ledger_info::LedgerInfoWithSignatures;
+use aptos_types::{ledger_info::LedgerInfoWithSignatures, transaction::Version};
 use serde::{Deserialize, Serialize};
 use std::{path::Path, sync::Arc, time::Instant};
 
@@ -36,16 +37,48 @@ pub trait MetadataStorageInterface {
         target_ledger_info: &LedgerInfoWithSignatures,
     ) -> Result<u64, Error>;
 
+    /// Gets the hash of the last persisted state key for the snapshot sync at the
+    /// specified version. Returns None if no state has been persisted for the target
+    /// yet (or the progress was recorded before state keys were persisted).
+    fn get_last_persisted_state_key_hash(
+        &self,
+        target_ledger_info: &LedgerInfoWithSignatures,
+    ) -> Result<Option<HashValue>, Error>;
+
+    /// Gets the version of the abandoned state snapshot sync that the snapshot sync at
+    /// the specified target continues. Returns None if the snapshot sync doesn't
+    /// continue an abandoned one.
+    fn get_resumed_snapshot_version(
+        &self,
+        target_ledger_info: &LedgerInfoWithSignatures,
+    ) -> Result<Option<Version>, Error>;
+
     /// Returns the target ledger info of any state snapshot sync that has previously
     /// started. If no snapshot sync started, None is returned.
     fn previous_snapshot_sync_target(&self) -> Result<Option<LedgerInfoWithSignatures>, Error>;
 
-    /// Updates the last persisted state value index for the state snapshot
-    /// sync at the specified target ledger info.
+    /// Abandons the incomplete state snapshot sync that has previously started, and
+    /// starts a new one at the specified target. The new snapshot sync continues from
+    /// the last state value persisted for the abandoned target.
+    fn switch_snapshot_sync_target(
+        &self,
+        target_ledger_info: &LedgerInfoWithSignatures,
+    ) -> Result<(), Error>;
+
+    /// Restarts the state snapshot sync at the specified target from the first
+    /// state value, i.e., without continuing any abandoned snapshot sync.
+    fn restart_snapshot_sync(
+        &self,
+        target_ledger_info: &LedgerInfoWithSignatures,
+    ) -> Result<(), Error>;
+
+    /// Updates the last persisted state value index (and the hash of the corresponding
+    /// state key) for the state snapshot sync at the specified target ledger info.
     fn update_last_persisted_state_value_index(
         &self,
         target_ledger_info: &LedgerInfoWithSignatures,
         last_persisted_state_value_index: u64,
+        last_persisted_state_key_hash: HashValue,
         snapshot_sync_completed: bool,
     ) -> Result<(), Error>;
 }
@@ -107,10 +140,13 @@ impl PersistentMetadataStorage {
                     ))
                 })?;
         match maybe_metadata_value {
-            Some(metadata_value) => {
-                let MetadataValue::StateSnapshotSync(snapshot_progress) = metadata_value;
+            Some(MetadataValue::StateSnapshotSync(snapshot_progress)) => {
                 Ok(Some(snapshot_progress))
             },
+            Some(metadata_value) => Err(Error::StorageError(format!(
+                "Unexpected metadata value found for key: {:?}. Value: {:?}",
+                metadata_key, metadata_value
+            ))),
             None => Ok(None),
         }
     }
@@ -138,21 +174,71 @@ impl PersistentMetadataStorage {
         }
     }
 
-    /// Write the key value pair to the database
-    fn commit_key_value(
+    /// Returns the existing snapshot checkpoint, if it belongs to the specified target.
+    /// Returns None if no checkpoint is found for the target.
+    fn get_snapshot_checkpoint_at_target(
         &self,
-        metadata_key: MetadataKey,
-        metadata_value: MetadataValue,
+        target_ledger_info: &LedgerInfoWithSignatures,
+    ) -> Result<Option<StateSnapshotCheckpoint>, Error> {
+        let metadata_key = MetadataKey::StateSnapshotSyncCheckpoint;
+        let maybe_metadata_value =
+            self.database
+                .get::<MetadataSchema>(&metadata_key)
+                .map_err(|error| {
+                    Error::StorageError(format!(
+                        "Failed to read metadata value for key: {:?}. Error: {:?}",
+                        metadata_key, error
+                    ))
+                })?;
+        match maybe_metadata_value {
+            Some(MetadataValue::StateSnapshotSyncCheckpoint(snapshot_checkpoint))
+                if snapshot_checkpoint.target_version
+                    == target_ledger_info.ledger_info().version() =>
+            {
+                Ok(Some(snapshot_checkpoint))
+            },
+            Some(MetadataValue::StateSnapshotSyncCheckpoint(_)) | None => Ok(None),
+            Some(metadata_value) => Err(Error::StorageError(format!(
+                "Unexpected metadata value found for key: {:?}. Value: {:?}",
+                metadata_key, metadata_value
+            ))),
+        }
+    }
+
+    /// Write the snapshot progress and checkpoint to the database
+    fn commit_snapshot_progress(
+        &self,
+        snapshot_progress: StateSnapshotProgress,
+        snapshot_checkpoint: StateSnapshotCheckpoint,
+    ) -> Result<(), Error> {
+        self.commit_key_values(vec![
+            (
+                MetadataKey::StateSnapshotSync,
+                MetadataValue::StateSnapshotSync(snapshot_progress),
+            ),
+            (
+                MetadataKey::StateSnapshotSyncCheckpoint,
+                MetadataValue::StateSnapshotSyncCheckpoint(snapshot_checkpoint),
+            ),
+        ])
+    }
+
+    /// Write the key value pairs to the database (atomically)
+    fn commit_key_values(
+        &self,
+        key_values: Vec<(MetadataKey, MetadataValue)>,
     ) -> Result<(), Error> {
         // Create the schema batch
         let mut batch = SchemaBatch::new();
-        batch
-            .put::<MetadataSchema>(&metadata_key, &metadata_value)
-            .map_err(|error| {
-                Error::StorageError(format!(
-                    "Failed to batch put the metadata key and value. Key: {:?}, Value: {:?}. Error: {:?}", metadata_key, metadata_value, error
-                ))
-            })?;
+        for (metadata_key, metadata_value) in key_values {
+            batch
+                .put::<MetadataSchema>(&metadata_key, &metadata_value)
+                .map_err(|error| {
+                    Error::StorageError(format!(
+                        "Failed to batch put the metadata key and value. Key: {:?}, Value: {:?}. Error: {:?}", metadata_key, metadata_value, error
+                    ))
+                })?;
+        }
 
         // Write the schema batch to the database
         self.database.write_schemas(batch).map_err(|error| {
@@ -192,16 +278,102 @@ impl MetadataStorageInterface for PersistentMetadataStorage {
         Ok(snapshot_progress.last_persisted_state_value_index)
     }
 
+    fn get_last_persisted_state_key_hash(
+        &self,
+        target: &LedgerInfoWithSignatures,
+    ) -> Result<Option<HashValue>, Error> {
+        Ok(self
+            .get_snapshot_checkpoint_at_target(target)?
+            .and_then(|snapshot_checkpoint| snapshot_checkpoint.last_persisted_state_key_hash))
+    }
+
+    fn get_resumed_snapshot_version(
+        &self,
+        target: &LedgerInfoWithSignatures,
+    ) -> Result<Option<Version>, Error> {
+        Ok(self
+            .get_snapshot_checkpoint_at_target(target)?
+            .and_then(|snapshot_checkpoint| snapshot_checkpoint.resumed_snapshot_version))
+    }
+
     fn previous_snapshot_sync_target(&self) -> Result<Option<LedgerInfoWithSignatures>, Error> {
         Ok(self
             .get_snapshot_progress()?
             .map(|snapshot_progress| snapshot_progress.target_ledger_info))
     }
 
+    fn switch_snapshot_sync_target(
+        &self,
+        target_ledger_info: &LedgerInfoWithSignatures,
+    ) -> Result<(), Error> {
+        // Ensure that the previous snapshot sync exists and is incomplete
+        let snapshot_progress = self.get_snapshot_progress()?.ok_or_else(|| {
+            Error::StorageError(
+                "Failed to switch the snapshot sync target! No state snapshot progress was found!"
+                    .into(),
+            )
+        })?;
+        if snapshot_progress.snapshot_sync_completed {
+            return Err(Error::StorageError(format!(
+                "Failed to switch the snapshot sync target! The snapshot sync for the previous \
+                target has already completed. Previous target: {:?}",
+                snapshot_progress.target_ledger_info
+            )));
+        }
+
+        // Continue from the last state value persisted for the previous target. If no
+        // state was persisted for it, keep continuing whatever the previous target did.
+        let previous_target = &snapshot_progress.target_ledger_info;
+        let previous_checkpoint = self.get_snapshot_checkpoint_at_target(previous_target)?;
+        let resumed_snapshot_version = match previous_checkpoint {
+            Some(StateSnapshotCheckpoint {
+                last_persisted_state_key_hash: None,
+                resumed_snapshot_version,
+                ..
+            }) => resumed_snapshot_version,
+            None if snapshot_progress.last_persisted_state_value_index == 0 => None,
+            _ => Some(previous_target.ledger_info().version()),
+        };
+
+        // Start the snapshot sync for the new target
+        self.commit_snapshot_progress(
+            StateSnapshotProgress {
+                target_ledger_info: target_ledger_info.clone(),
+                last_persisted_state_value_index: snapshot_progress
+                    .last_persisted_state_value_index,
+                snapshot_sync_completed: false,
+            },
+            StateSnapshotCheckpoint {
+                target_version: target_ledger_info.ledger_info().version(),
+                last_persisted_state_key_hash: None,
+                resumed_snapshot_version,
+            },
+        )
+    }
+
+    fn restart_snapshot_sync(
+        &self,
+        target_ledger_info: &LedgerInfoWithSignatures,
+    ) -> Result<(), Error> {
+        self.commit_snapshot_progress(
+            StateSnapshotProgress {
+                target_ledger_info: target_ledger_info.clone(),
+                last_persisted_state_value_index: 0,
+                snapshot_sync_completed: false,
+            },
+            StateSnapshotCheckpoint {
+                target_version: target_ledger_info.ledger_info().version(),
+                last_persisted_state_key_hash: None,
+                resumed_snapshot_version: None,
+            },
+        )
+    }
+
     fn update_last_persisted_state_value_index(
         &self,
         target_ledger_info: &LedgerInfoWithSignatures,
         last_persisted_state_value_index: u64,
+        last_persisted_state_key_hash: HashValue,
         snapshot_sync_completed: bool,
     ) -> Result<(), Error> {
         // Ensure that if any previous snapshot progress exists, it has the same target
@@ -214,16 +386,24 @@ impl MetadataStorageInterface for PersistentMetadataStorage {
             }
         }
 
-        // Create the key/value pair
-        let metadata_key = MetadataKey::StateSnapshotSync;
-        let metadata_value = MetadataValue::StateSnapshotSync(StateSnapshotProgress {
-            last_persisted_state_value_index,
-            snapshot_sync_completed,
-            target_ledger_info: target_ledger_info.clone(),
-        });
+        // Preserve the resumed snapshot version of the target (if any)
+        let resumed_snapshot_version = self
+            .get_snapshot_checkpoint_at_target(target_ledger_info)?
+            .and_then(|snapshot_checkpoint| snapshot_checkpoint.resumed_snapshot_version);
 
-        // Insert the new key/value pair
-        self.commit_key_value(metadata_key, metadata_value)
+        // Insert the new progress and checkpoint
+        self.commit_snapshot_progress(
+            StateSnapshotProgress {
+                last_persisted_state_value_index,
+                snapshot_sync_completed,
+                target_ledger_info: target_ledger_info.clone(),
+            },
+            StateSnapshotCheckpoint {
+                target_version: target_ledger_info.ledger_info().version(),
+                last_persisted_state_key_hash: Some(last_persisted_state_key_hash),
+                resumed_snapshot_version,
+            },
+        )
     }
 }
 
@@ -235,6 +415,15 @@ pub struct StateSnapshotProgress {
     pub snapshot_sync_completed: bool,
 }
 
+/// Additional progress of a state snapshot sync, required to resume it. This is kept
+/// separate from `StateSnapshotProgress` so that existing progress can still be read.
+#[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct StateSnapshotCheckpoint {
+    pub target_version: Version,
+    pub last_persisted_state_key_hash: Option<HashValue>,
+    pub resumed_snapshot_version: Option<Version>,
+}
+
 /// The raw schema format used by the database
 pub mod database_schema {
     use super::*;
@@ -252,7 +441,8 @@ pub mod database_schema {
     #[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
     #[repr(u8)]
     pub enum MetadataKey {
-        StateSnapshotSync, // A state snapshot sync that was started
+        StateSnapshotSync,           // A state snapshot sync that was started
+        StateSnapshotSyncCheckpoint, // The checkpoint of the state snapshot sync
     }
 
     /// A metadata value that can be inserted into the database
@@ -260,6 +450,7 @@ pub mod database_schema {
     #[repr(u8)]
     pub enum MetadataValue {
         StateSnapshotSync(StateSnapshotProgress), // A state snapshot sync progress marker
+        StateSnapshotSyncCheckpoint(StateSnapshotCheckpoint), // A state snapshot sync checkpoint
     }
 
     impl KeyCodec<MetadataSchema> for MetadataKey {
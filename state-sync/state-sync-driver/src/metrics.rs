@@ -90,6 +90,30 @@ impl StorageSynchronizerOperations {
     }
 }
 
+/// An enum of state snapshot sync progress indicators. Each
+/// of these is a metric label to track.
+pub enum SnapshotSyncProgress {
+    TargetVersion,        // The target version of the snapshot sync
+    SyncedStateValues,    // The number of state values synced for the target
+    EstimatedStateValues, // The estimated total number of state values at the target
+    ProgressBasisPoints,  // The estimated progress (in basis points, i.e., 10,000 is complete)
+    StateValuesPerSecond, // The rate at which state values are being synced
+    EtaSeconds,           // The estimated number of seconds until the snapshot sync completes
+}
+
+impl SnapshotSyncProgress {
+    pub fn get_label(&self) -> &'static str {
+        match self {
+            SnapshotSyncProgress::TargetVersion => "target_version",
+            SnapshotSyncProgress::SyncedStateValues => "synced_state_values",
+            SnapshotSyncProgress::EstimatedStateValues => "estimated_state_values",
+            SnapshotSyncProgress::ProgressBasisPoints => "progress_basis_points",
+            SnapshotSyncProgress::StateValuesPerSecond => "state_values_per_second",
+            SnapshotSyncProgress::EtaSeconds => "eta_seconds",
+        }
+    }
+}
+
 /// Histogram buckets for tracking chunk sizes
 const CHUNK_SIZE_BUCKETS: &[f64] = &[
     1.0, 2.0, 4.0, 5.0, 10.0, 25.0, 50.0, 75.0, 100.0, 250.0, 500.0, 750.0, 1000.0, 2500.0, 5000.0,
@@ -167,6 +191,16 @@ pub static EXECUTING_COMPONENT: Lazy<IntCounterVec> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Gauges for tracking the progress of the state snapshot sync
+pub static SNAPSHOT_SYNC_PROGRESS: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "aptos_state_sync_snapshot_sync_progress",
+        "Gauges for tracking the progress of the state snapshot sync",
+        &["type"]
+    )
+    .unwrap()
+});
+
 /// Counter for tracking sizes of data chunks sent to the storage synchronizer
 pub static STORAGE_SYNCHRONIZER_CHUNK_SIZES: Lazy<HistogramVec> = Lazy::new(|| {
     let histogram_opts = histogram_opts!(
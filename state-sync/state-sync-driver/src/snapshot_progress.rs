@@ -0,0 +1,130 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::metrics::{self, SnapshotSyncProgress};
+use aptos_crypto::HashValue;
+use aptos_types::transaction::Version;
+use std::time::Instant;
+
+/// The number of basis points that represent a completed snapshot sync
+pub const COMPLETED_BASIS_POINTS: u64 = 10_000;
+
+/// A simple tracker for the progress of a state snapshot sync. Given that state
+/// values are synced in key hash order (and key hashes are uniformly distributed),
+/// the progress is estimated using the position of the last synced key hash in
+/// the key space. This avoids having to know the total number of state values
+/// at the target (which is not known until the snapshot sync completes).
+pub struct SnapshotSyncProgressTracker {
+    // The number of synced state values (and time) when the tracker first
+    // observed progress. This is used to calculate the sync rate.
+    baseline: Option<(u64, Instant)>,
+}
+
+impl SnapshotSyncProgressTracker {
+    pub fn new(target_version: Version) -> Self {
+        metrics::set_gauge(
+            &metrics::SNAPSHOT_SYNC_PROGRESS,
+            SnapshotSyncProgress::TargetVersion.get_label(),
+            target_version,
+        );
+
+        Self { baseline: None }
+    }
+
+    /// Updates the progress using the index and key hash of the last persisted state value
+    pub fn update_progress(
+        &mut self,
+        last_persisted_index: u64,
+        last_persisted_key_hash: HashValue,
+    ) {
+        let synced_state_values = last_persisted_index.saturating_add(1);
+        let now = Instant::now();
+
+        // Estimate the total number of state values and the progress
+        let keyspace_fraction = estimate_keyspace_fraction(last_persisted_key_hash);
+        let estimated_state_values =
+            ((synced_state_values as f64 / keyspace_fraction) as u64).max(synced_state_values);
+        let progress_basis_points = ((keyspace_fraction * COMPLETED_BASIS_POINTS as f64) as u64)
+            .min(COMPLETED_BASIS_POINTS - 1); // We're only done once the last chunk is committed
+
+        // Calculate the sync rate and the estimated time to completion
+        let (baseline_state_values, baseline_time) =
+            *self.baseline.get_or_insert((synced_state_values, now));
+        let elapsed_secs = now.duration_since(baseline_time).as_secs_f64();
+        let state_values_per_second = if elapsed_secs > 0.0 {
+            synced_state_values.saturating_sub(baseline_state_values) as f64 / elapsed_secs
+        } else {
+            0.0
+        };
+        let eta_seconds = if state_values_per_second > 0.0 {
+            (estimated_state_values.saturating_sub(synced_state_values) as f64
+                / state_values_per_second) as u64
+        } else {
+            0
+        };
+
+        // Update the progress metrics
+        self.set_progress_gauges(
+            synced_state_values,
+            estimated_state_values,
+            progress_basis_points,
+            state_values_per_second as u64,
+            eta_seconds,
+        );
+    }
+
+    /// Marks the snapshot sync as complete
+    pub fn complete(&mut self, last_persisted_index: u64) {
+        let synced_state_values = last_persisted_index.saturating_add(1);
+        self.set_progress_gauges(
+            synced_state_values,
+            synced_state_values,
+            COMPLETED_BASIS_POINTS,
+            0,
+            0,
+        );
+    }
+
+    /// Sets the progress gauges to the given values
+    fn set_progress_gauges(
+        &self,
+        synced_state_values: u64,
+        estimated_state_values: u64,
+        progress_basis_points: u64,
+        state_values_per_second: u64,
+        eta_seconds: u64,
+    ) {
+        for (progress_type, value) in [
+            (SnapshotSyncProgress::SyncedStateValues, synced_state_values),
+            (
+                SnapshotSyncProgress::EstimatedStateValues,
+                estimated_state_values,
+            ),
+            (
+                SnapshotSyncProgress::ProgressBasisPoints,
+                progress_basis_points,
+            ),
+            (
+                SnapshotSyncProgress::StateValuesPerSecond,
+                state_values_per_second,
+            ),
+            (SnapshotSyncProgress::EtaSeconds, eta_seconds),
+        ] {
+            metrics::set_gauge(
+                &metrics::SNAPSHOT_SYNC_PROGRESS,
+                progress_type.get_label(),
+                value,
+            );
+        }
+    }
+}
+
+/// Estimates the fraction of the key space (in (0, 1]) that lies at or
+/// below the given key hash. Only the most significant 8 bytes are used,
+/// which is more than enough precision for progress estimation.
+pub fn estimate_keyspace_fraction(key_hash: HashValue) -> f64 {
+    let mut prefix_bytes = [0u8; 8];
+    prefix_bytes.copy_from_slice(&key_hash.as_ref()[..8]);
+    let prefix = u64::from_be_bytes(prefix_bytes);
+    (prefix as f64 + 1.0) / (u64::MAX as f64 + 1.0)
+}
@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    bootstrapper::GENESIS_TRANSACTION_VERSION,
     error::Error,
     logging::{LogEntry, LogSchema},
     metadata_storage::MetadataStorageInterface,
@@ -11,9 +12,11 @@ use crate::{
         CommitNotification, CommittedTransactions, ErrorNotification, MempoolNotificationHandler,
         StorageServiceNotificationHandler,
     },
+    snapshot_progress::SnapshotSyncProgressTracker,
     utils,
 };
 use aptos_config::config::StateSyncDriverConfig;
+use aptos_crypto::{hash::CryptoHash, HashValue};
 use aptos_data_streaming_service::data_notification::NotificationId;
 use aptos_event_notifications::EventSubscriptionService;
 use aptos_executor_types::{ChunkCommitNotification, ChunkExecutorTrait};
@@ -854,11 +857,39 @@ fn spawn_state_snapshot_receiver<
             .ensure_state_checkpoint_hash()
             .expect("Must be at state checkpoint.");
 
+        // Identify any abandoned snapshot sync that this snapshot sync continues
+        let resumed_snapshot_version = metadata_storage
+            .get_resumed_snapshot_version(&target_ledger_info)
+            .expect("Failed to get the resumed snapshot version!");
+
         // Create the snapshot receiver
-        let mut state_snapshot_receiver = storage
-            .writer
-            .get_state_snapshot_receiver(version, expected_root_hash)
-            .expect("Failed to initialize the state snapshot receiver!");
+        let mut state_snapshot_receiver = match resumed_snapshot_version {
+            Some(resumed_snapshot_version) => {
+                info!(
+                    LogSchema::new(LogEntry::StorageSynchronizer).message(&format!(
+                        "Continuing the snapshot sync abandoned at version: {:?} at version: {:?}",
+                        resumed_snapshot_version, version
+                    ))
+                );
+                storage.writer.get_state_snapshot_receiver_resuming(
+                    version,
+                    expected_root_hash,
+                    resumed_snapshot_version,
+                )
+            },
+            None => storage
+                .writer
+                .get_state_snapshot_receiver(version, expected_root_hash),
+        }
+        .expect("Failed to initialize the state snapshot receiver!");
+
+        // The abandoned snapshot sync can only be continued if the state it persisted
+        // is unchanged at the target. This is verified by the first committed chunk.
+        let mut resuming_snapshot_sync = resumed_snapshot_version.is_some();
+        let mut restarted_snapshot_sync = false;
+
+        // Create the snapshot sync progress tracker
+        let mut progress_tracker = SnapshotSyncProgressTracker::new(version);
 
         // Handle state value chunks
         while let Some(storage_data_chunk) = state_snapshot_listener.next().await {
@@ -871,9 +902,24 @@ fn spawn_state_snapshot_receiver<
             // Commit the state value chunk
             match storage_data_chunk {
                 StorageDataChunk::States(notification_id, states_with_proof) => {
+                    // If the snapshot sync was restarted, drop the chunks streamed before
+                    // the restart (the restarted stream starts at the first state value).
+                    if restarted_snapshot_sync {
+                        if states_with_proof.first_index != 0 {
+                            decrement_pending_data_chunks(pending_data_chunks.clone());
+                            continue;
+                        }
+                        restarted_snapshot_sync = false;
+                    }
+
                     // Commit the state value chunk
                     let all_states_synced = states_with_proof.is_last_chunk();
                     let last_committed_state_index = states_with_proof.last_index;
+                    let last_committed_state_key_hash = states_with_proof
+                        .raw_values
+                        .last()
+                        .map(|(state_key, _)| CryptoHash::hash(state_key))
+                        .unwrap_or(states_with_proof.last_key);
                     let num_state_values = states_with_proof.raw_values.len();
 
                     let result = state_snapshot_receiver.add_chunk(
@@ -884,6 +930,8 @@ fn spawn_state_snapshot_receiver<
                     // Handle the commit result
                     match result {
                         Ok(()) => {
+                            resuming_snapshot_sync = false;
+
                             // Update the logs and metrics
                             info!(
                                 LogSchema::new(LogEntry::StorageSynchronizer).message(&format!(
@@ -908,12 +956,19 @@ fn spawn_state_snapshot_receiver<
                             );
 
                             if !all_states_synced {
+                                // Update the snapshot sync progress
+                                progress_tracker.update_progress(
+                                    last_committed_state_index,
+                                    last_committed_state_key_hash,
+                                );
+
                                 // Update the metadata storage with the last committed state index
                                 if let Err(error) = metadata_storage
                                     .clone()
                                     .update_last_persisted_state_value_index(
                                         &target_ledger_info,
                                         last_committed_state_index,
+                                        last_committed_state_key_hash,
                                         all_states_synced,
                                     )
                                 {
@@ -941,6 +996,7 @@ fn spawn_state_snapshot_receiver<
                                 version,
                                 &target_ledger_info,
                                 last_committed_state_index,
+                                last_committed_state_key_hash,
                             )
                             .await
                             {
@@ -950,13 +1006,41 @@ fn spawn_state_snapshot_receiver<
                                     error,
                                 )
                                 .await;
+                            } else {
+                                progress_tracker.complete(last_committed_state_index);
                             }
                             decrement_pending_data_chunks(pending_data_chunks.clone());
                             return; // There's nothing left to do!
                         },
                         Err(error) => {
-                            let error =
+                            let mut error =
                                 format!("Failed to commit state value chunk! Error: {:?}", error);
+
+                            // If the abandoned snapshot sync can't be continued, discard it
+                            // and restart the snapshot sync from the first state value.
+                            if resuming_snapshot_sync {
+                                warn!(LogSchema::new(LogEntry::StorageSynchronizer).message(&format!(
+                                    "Failed to continue the abandoned snapshot sync! Restarting the snapshot sync at version: {:?}. Error: {:?}",
+                                    version, error
+                                )));
+                                match restart_state_snapshot_sync(
+                                    &metadata_storage,
+                                    &storage,
+                                    &target_ledger_info,
+                                    expected_root_hash,
+                                ) {
+                                    Ok(receiver) => {
+                                        state_snapshot_receiver = receiver;
+                                        resuming_snapshot_sync = false;
+                                        restarted_snapshot_sync = true;
+                                    },
+                                    Err(restart_error) => {
+                                        error =
+                                            format!("{} Restart error: {}", error, restart_error);
+                                    },
+                                }
+                            }
+
                             send_storage_synchronizer_error(
                                 error_notification_sender.clone(),
                                 notification_id,
@@ -981,6 +1065,39 @@ fn spawn_state_snapshot_receiver<
     spawn(runtime, receiver)
 }
 
+/// Discards the state persisted by the unfinished snapshot sync at the given target
+/// (and by any abandoned snapshot sync that it continued), and restarts the snapshot
+/// sync from the first state value. Returns the new state snapshot receiver.
+fn restart_state_snapshot_sync<MetadataStorage: MetadataStorageInterface>(
+    metadata_storage: &MetadataStorage,
+    storage: &DbReaderWriter,
+    target_ledger_info: &LedgerInfoWithSignatures,
+    expected_root_hash: HashValue,
+) -> Result<Box<dyn StateSnapshotReceiver<StateKey, StateValue>>, String> {
+    let version = target_ledger_info.ledger_info().version();
+    storage
+        .writer
+        .discard_unfinished_state_snapshots(GENESIS_TRANSACTION_VERSION + 1, version + 1)
+        .map_err(|error| {
+            format!(
+                "Failed to discard the unfinished state snapshots! Error: {:?}",
+                error
+            )
+        })?;
+    metadata_storage
+        .restart_snapshot_sync(target_ledger_info)
+        .map_err(|error| format!("Failed to restart the snapshot sync! Error: {:?}", error))?;
+    storage
+        .writer
+        .get_state_snapshot_receiver(version, expected_root_hash)
+        .map_err(|error| {
+            format!(
+                "Failed to initialize the state snapshot receiver! Error: {:?}",
+                error
+            )
+        })
+}
+
 /// Spawns a dedicated task that applies the given output chunk. We use
 /// `spawn_blocking` so that the heavy synchronous function doesn't
 /// block the async thread.
@@ -1119,6 +1236,7 @@ async fn finalize_storage_and_send_commit<
     version: Version,
     target_ledger_info: &LedgerInfoWithSignatures,
     last_committed_state_index: u64,
+    last_committed_state_key_hash: HashValue,
 ) -> Result<(), String> {
     // Finalize the state snapshot
     state_snapshot_receiver.finish_box().map_err(|error| {
@@ -1142,6 +1260,7 @@ async fn finalize_storage_and_send_commit<
     metadata_storage.update_last_persisted_state_value_index(
             target_ledger_info,
             last_committed_state_index,
+            last_committed_state_key_hash,
             true,
         ).map_err(|error| {
         format!("All states have synced, but failed to update the metadata storage at version {:?}! Error: {:?}", version, error)
@@ -8,7 +8,8 @@ use crate::{
     tests::{
         mocks::{
             create_mock_db_reader, create_mock_streaming_client, create_ready_storage_synchronizer,
-            MockMetadataStorage, MockStorageSynchronizer, MockStreamingClient,
+            MockBackupDataSource, MockMetadataStorage, MockStorageSynchronizer,
+            MockStreamingClient,
         },
         utils::{
             create_data_stream_listener, create_empty_epoch_state, create_epoch_ending_ledger_info,
@@ -26,6 +27,7 @@ use aptos_data_streaming_service::{
     data_notification::{DataNotification, DataPayload, NotificationId},
     streaming_client::{NotificationAndFeedback, NotificationFeedback},
 };
+use aptos_storage_service_types::responses::CompleteDataRange;
 use aptos_time_service::TimeService;
 use aptos_types::{
     transaction::{TransactionOutputListWithProofV2, Version},
@@ -989,6 +991,9 @@ async fn test_snapshot_sync_epoch_change() {
     metadata_storage
         .expect_get_last_persisted_state_value_index()
         .returning(move |_| Ok(last_persisted_index_clone));
+    metadata_storage
+        .expect_get_last_persisted_state_key_hash()
+        .returning(|_| Ok(None));
 
     // Create the bootstrapper
     let mut bootstrapper = create_bootstrapper_with_storage(
@@ -1111,6 +1116,9 @@ async fn test_snapshot_sync_epoch_change_genesis_restart() {
     metadata_storage
         .expect_get_last_persisted_state_value_index()
         .returning(move |_| Ok(last_persisted_index_clone));
+    metadata_storage
+        .expect_get_last_persisted_state_key_hash()
+        .returning(|_| Ok(None));
 
     // Create the bootstrapper
     let mut bootstrapper = create_bootstrapper_with_storage(
@@ -1199,6 +1207,9 @@ async fn test_snapshot_sync_existing_state() {
     metadata_storage
         .expect_get_last_persisted_state_value_index()
         .returning(move |_| Ok(last_persisted_index_clone));
+    metadata_storage
+        .expect_get_last_persisted_state_key_hash()
+        .returning(|_| Ok(None));
 
     // Create the bootstrapper
     let mut bootstrapper = create_bootstrapper_with_storage(
@@ -1246,6 +1257,81 @@ async fn test_snapshot_sync_existing_state() {
         .unwrap();
 }
 
+#[tokio::test]
+async fn test_snapshot_sync_switch_target() {
+    // Create test data
+    let synced_version = GENESIS_TRANSACTION_VERSION; // Genesis is the highest synced
+    let previous_target_version = 1000;
+    let previous_target_ledger_info =
+        create_random_epoch_ending_ledger_info(previous_target_version, 1);
+    let highest_version = 1000000;
+    let highest_ledger_info = create_random_epoch_ending_ledger_info(highest_version, 2);
+    let last_persisted_index = 4567;
+
+    // Create a driver configuration with a genesis waypoint and state syncing
+    let mut driver_configuration = create_full_node_driver_configuration();
+    driver_configuration.config.bootstrapping_mode = BootstrappingMode::DownloadLatestStates;
+
+    // Create the mock streaming client (the new target should continue from the
+    // last state value persisted for the previous target)
+    let mut mock_streaming_client = create_mock_streaming_client();
+    let (_notification_sender, data_stream_listener) = create_data_stream_listener();
+    mock_streaming_client
+        .expect_get_all_state_values()
+        .times(1)
+        .with(eq(highest_version), eq(Some(last_persisted_index)))
+        .return_once(move |_, _| Ok(data_stream_listener));
+
+    // Create the mock metadata storage and expect the target to be switched
+    let mut metadata_storage = MockMetadataStorage::new();
+    metadata_storage
+        .expect_previous_snapshot_sync_target()
+        .returning(move || Ok(Some(previous_target_ledger_info.clone())));
+    metadata_storage
+        .expect_is_snapshot_sync_complete()
+        .returning(|_| Ok(false));
+    metadata_storage
+        .expect_switch_snapshot_sync_target()
+        .times(1)
+        .withf(move |target| target.ledger_info().version() == highest_version)
+        .returning(|_| Ok(()));
+    metadata_storage
+        .expect_get_last_persisted_state_value_index()
+        .returning(move |_| Ok(last_persisted_index));
+    metadata_storage
+        .expect_get_last_persisted_state_key_hash()
+        .returning(|_| Ok(None));
+
+    // Create the bootstrapper
+    let mut bootstrapper = create_bootstrapper_with_storage(
+        driver_configuration,
+        mock_streaming_client,
+        metadata_storage,
+        None,
+        synced_version,
+        true,
+    );
+
+    // Insert an epoch ending ledger info into the verified states of the bootstrapper
+    manipulate_verified_epoch_states(&mut bootstrapper, true, true, Some(highest_version));
+
+    // Manually insert a transaction output to sync
+    bootstrapper
+        .get_state_value_syncer()
+        .set_transaction_output_to_sync(create_output_list_with_proof());
+
+    // Create a global data summary where the previous target states are no longer advertised
+    let mut global_data_summary = create_global_summary(2);
+    global_data_summary.advertised_data.synced_ledger_infos = vec![highest_ledger_info.clone()];
+    global_data_summary.advertised_data.states =
+        vec![CompleteDataRange::new(highest_version - 100, highest_version).unwrap()];
+
+    // Drive progress to start the state value stream for the new target
+    drive_progress(&mut bootstrapper, &global_data_summary, false)
+        .await
+        .unwrap();
+}
+
 #[tokio::test]
 async fn test_snapshot_sync_fresh_state() {
     // Create test data
@@ -1450,6 +1536,115 @@ async fn test_snapshot_sync_lag_panic() {
         .unwrap();
 }
 
+#[tokio::test]
+async fn test_snapshot_sync_backup_resume() {
+    // Create test data (the node restarts after syncing the snapshot in the backup)
+    let backup_snapshot_version = 1000;
+    let highest_version = 1000000;
+    let backup_ledger_info = create_random_epoch_ending_ledger_info(backup_snapshot_version, 1);
+    let highest_ledger_info = create_random_epoch_ending_ledger_info(highest_version, 1);
+
+    // Create a driver configuration with a backup holding the synced snapshot. The
+    // node is too far behind the peers to skip a new snapshot sync.
+    let mut driver_configuration = create_full_node_driver_configuration();
+    driver_configuration.config.bootstrapping_mode = BootstrappingMode::DownloadLatestStates;
+    driver_configuration
+        .config
+        .num_versions_to_skip_snapshot_sync = 100;
+    driver_configuration.backup_source = Some(Arc::new(create_backup_source(vec![
+        backup_snapshot_version,
+    ])));
+
+    // Create the mock streaming client
+    let mock_streaming_client = create_mock_streaming_client();
+
+    // Create the mock metadata storage with a completed snapshot sync
+    let mut metadata_storage = MockMetadataStorage::new();
+    metadata_storage
+        .expect_previous_snapshot_sync_target()
+        .returning(move || Ok(Some(backup_ledger_info.clone())));
+    metadata_storage
+        .expect_is_snapshot_sync_complete()
+        .returning(|_| Ok(true));
+
+    // Create the bootstrapper
+    let mut bootstrapper = create_bootstrapper_with_storage(
+        driver_configuration,
+        mock_streaming_client,
+        metadata_storage,
+        None,
+        backup_snapshot_version,
+        true,
+    );
+
+    // Insert an epoch ending ledger info into the verified states of the bootstrapper
+    manipulate_verified_epoch_states(&mut bootstrapper, true, true, Some(highest_version));
+
+    // Create a global data summary
+    let mut global_data_summary = create_global_summary(1);
+    global_data_summary.advertised_data.synced_ledger_infos = vec![highest_ledger_info.clone()];
+
+    // Drive progress and verify the peers are left to provide the remaining data
+    drive_progress(&mut bootstrapper, &global_data_summary, false)
+        .await
+        .unwrap();
+    assert!(bootstrapper.is_bootstrapped());
+}
+
+#[tokio::test]
+#[should_panic(
+    expected = "You are currently 999000 versions behind the latest snapshot version (1000000)"
+)]
+async fn test_snapshot_sync_peer_snapshot_with_backup() {
+    // Create test data (the synced snapshot was provided by the peers, not the backup)
+    let synced_version = 1000;
+    let highest_version = 1000000;
+    let synced_ledger_info = create_random_epoch_ending_ledger_info(synced_version, 1);
+    let highest_ledger_info = create_random_epoch_ending_ledger_info(highest_version, 1);
+
+    // Create a driver configuration with a backup that doesn't hold the synced snapshot
+    let mut driver_configuration = create_full_node_driver_configuration();
+    driver_configuration.config.bootstrapping_mode = BootstrappingMode::DownloadLatestStates;
+    driver_configuration
+        .config
+        .num_versions_to_skip_snapshot_sync = 100;
+    driver_configuration.backup_source = Some(Arc::new(create_backup_source(vec![500])));
+
+    // Create the mock streaming client
+    let mock_streaming_client = create_mock_streaming_client();
+
+    // Create the mock metadata storage with a completed snapshot sync
+    let mut metadata_storage = MockMetadataStorage::new();
+    metadata_storage
+        .expect_previous_snapshot_sync_target()
+        .returning(move || Ok(Some(synced_ledger_info.clone())));
+    metadata_storage
+        .expect_is_snapshot_sync_complete()
+        .returning(|_| Ok(true));
+
+    // Create the bootstrapper
+    let mut bootstrapper = create_bootstrapper_with_storage(
+        driver_configuration,
+        mock_streaming_client,
+        metadata_storage,
+        None,
+        synced_version,
+        true,
+    );
+
+    // Insert an epoch ending ledger info into the verified states of the bootstrapper
+    manipulate_verified_epoch_states(&mut bootstrapper, true, true, Some(highest_version));
+
+    // Create a global data summary
+    let mut global_data_summary = create_global_summary(1);
+    global_data_summary.advertised_data.synced_ledger_infos = vec![highest_ledger_info.clone()];
+
+    // Drive progress to panic the node (the backup doesn't excuse the lag)
+    drive_progress(&mut bootstrapper, &global_data_summary, false)
+        .await
+        .unwrap();
+}
+
 #[tokio::test]
 async fn test_waypoint_mismatch() {
     // Create a waypoint
@@ -1583,6 +1778,15 @@ async fn test_waypoint_satisfiable() {
     assert_matches!(error, Error::UnsatisfiableWaypoint(_));
 }
 
+/// Creates a mock backup source holding state snapshots at the given versions
+fn create_backup_source(snapshot_versions: Vec<Version>) -> MockBackupDataSource {
+    let mut backup_source = MockBackupDataSource::new();
+    backup_source
+        .expect_get_state_snapshot_versions()
+        .returning(move || Ok(snapshot_versions.clone()));
+    backup_source
+}
+
 /// Creates a bootstrapper for testing
 fn create_bootstrapper(
     driver_configuration: DriverConfiguration,
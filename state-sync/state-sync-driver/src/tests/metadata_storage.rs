@@ -4,10 +4,12 @@
 use crate::{
     metadata_storage::{
         database_schema::{MetadataKey, MetadataSchema, MetadataValue},
-        MetadataStorageInterface, PersistentMetadataStorage, StateSnapshotProgress,
+        MetadataStorageInterface, PersistentMetadataStorage, StateSnapshotCheckpoint,
+        StateSnapshotProgress,
     },
     tests::utils::{create_epoch_ending_ledger_info, create_ledger_info_at_version},
 };
+use aptos_crypto::HashValue;
 use aptos_schemadb::schema::fuzzing::assert_encode_decode;
 use aptos_temppath::TempPath;
 use claims::{assert_err, assert_none};
@@ -29,6 +31,7 @@ fn test_create_then_open() {
         .update_last_persisted_state_value_index(
             &target_ledger_info,
             last_persisted_state_value,
+            HashValue::random(),
             snapshot_sync_completed,
         )
         .unwrap();
@@ -62,6 +65,7 @@ fn test_create_then_open() {
         .update_last_persisted_state_value_index(
             &target_ledger_info,
             last_persisted_state_value,
+            HashValue::random(),
             snapshot_sync_completed,
         )
         .unwrap();
@@ -99,6 +103,14 @@ fn test_metadata_schema_encode_decode() {
             snapshot_sync_completed: false,
         }),
     );
+    assert_encode_decode::<MetadataSchema>(
+        &MetadataKey::StateSnapshotSyncCheckpoint,
+        &MetadataValue::StateSnapshotSyncCheckpoint(StateSnapshotCheckpoint {
+            target_version: 1234,
+            last_persisted_state_key_hash: Some(HashValue::random()),
+            resumed_snapshot_version: Some(1000),
+        }),
+    );
 }
 
 #[test]
@@ -117,11 +129,13 @@ fn test_multiple_reads_and_writes() {
     for index in 0..100 {
         // Insert a new state value entry for the target
         let last_persisted_state_value = 50000 + index;
+        let last_persisted_state_key_hash = HashValue::random();
         let snapshot_sync_completed = false;
         metadata_storage
             .update_last_persisted_state_value_index(
                 &target_ledger_info,
                 last_persisted_state_value,
+                last_persisted_state_key_hash,
                 snapshot_sync_completed,
             )
             .unwrap();
@@ -137,6 +151,12 @@ fn test_multiple_reads_and_writes() {
                 .get_last_persisted_state_value_index(&target_ledger_info)
                 .unwrap()
         );
+        assert_eq!(
+            Some(last_persisted_state_key_hash),
+            metadata_storage
+                .get_last_persisted_state_key_hash(&target_ledger_info)
+                .unwrap()
+        );
         assert_eq!(
             snapshot_sync_completed,
             metadata_storage
@@ -158,12 +178,140 @@ fn test_writes_to_different_targets() {
     // Write a new progress entry into the storage
     let target_ledger_info = create_ledger_info_at_version(100);
     metadata_storage
-        .update_last_persisted_state_value_index(&target_ledger_info, 10101, false)
+        .update_last_persisted_state_value_index(
+            &target_ledger_info,
+            10101,
+            HashValue::random(),
+            false,
+        )
         .unwrap();
 
     // Write another progress entry with a different target and verify that it fails
     let target_ledger_info = create_ledger_info_at_version(200);
     metadata_storage
-        .update_last_persisted_state_value_index(&target_ledger_info, 10101, false)
+        .update_last_persisted_state_value_index(
+            &target_ledger_info,
+            10101,
+            HashValue::random(),
+            false,
+        )
+        .unwrap_err();
+}
+
+#[test]
+fn test_switch_snapshot_sync_target() {
+    // Create a new metadata storage
+    let tmp_dir = TempPath::new();
+    let metadata_storage = PersistentMetadataStorage::new(tmp_dir.path());
+
+    // Verify that switching fails if no snapshot sync has started
+    let first_target = create_ledger_info_at_version(100);
+    metadata_storage
+        .switch_snapshot_sync_target(&first_target)
+        .unwrap_err();
+
+    // Persist some progress for the first target
+    metadata_storage
+        .update_last_persisted_state_value_index(&first_target, 10101, HashValue::random(), false)
+        .unwrap();
+    assert_none!(metadata_storage
+        .get_resumed_snapshot_version(&first_target)
+        .unwrap());
+
+    // Switch to a second target and verify it continues from the progress of the first
+    let second_target = create_ledger_info_at_version(200);
+    metadata_storage
+        .switch_snapshot_sync_target(&second_target)
+        .unwrap();
+    assert_eq!(
+        Some(second_target.clone()),
+        metadata_storage.previous_snapshot_sync_target().unwrap()
+    );
+    assert_eq!(
+        10101,
+        metadata_storage
+            .get_last_persisted_state_value_index(&second_target)
+            .unwrap()
+    );
+    assert_none!(metadata_storage
+        .get_last_persisted_state_key_hash(&second_target)
+        .unwrap());
+    assert_eq!(
+        Some(100),
+        metadata_storage
+            .get_resumed_snapshot_version(&second_target)
+            .unwrap()
+    );
+
+    // Switch to a third target before any progress is made, and verify
+    // that it still continues from the progress of the first target.
+    let third_target = create_ledger_info_at_version(300);
+    metadata_storage
+        .switch_snapshot_sync_target(&third_target)
+        .unwrap();
+    assert_eq!(
+        10101,
+        metadata_storage
+            .get_last_persisted_state_value_index(&third_target)
+            .unwrap()
+    );
+    assert_eq!(
+        Some(100),
+        metadata_storage
+            .get_resumed_snapshot_version(&third_target)
+            .unwrap()
+    );
+
+    // Persist progress for the third target and verify the resumed version is kept
+    let last_persisted_state_key_hash = HashValue::random();
+    metadata_storage
+        .update_last_persisted_state_value_index(
+            &third_target,
+            10500,
+            last_persisted_state_key_hash,
+            false,
+        )
+        .unwrap();
+    assert_eq!(
+        Some(last_persisted_state_key_hash),
+        metadata_storage
+            .get_last_persisted_state_key_hash(&third_target)
+            .unwrap()
+    );
+    assert_eq!(
+        Some(100),
+        metadata_storage
+            .get_resumed_snapshot_version(&third_target)
+            .unwrap()
+    );
+
+    // Restart the snapshot sync for the third target and verify it starts from scratch
+    metadata_storage
+        .restart_snapshot_sync(&third_target)
+        .unwrap();
+    assert_eq!(
+        0,
+        metadata_storage
+            .get_last_persisted_state_value_index(&third_target)
+            .unwrap()
+    );
+    assert_none!(metadata_storage
+        .get_last_persisted_state_key_hash(&third_target)
+        .unwrap());
+    assert_none!(metadata_storage
+        .get_resumed_snapshot_version(&third_target)
+        .unwrap());
+
+    // Complete the snapshot sync for the third target
+    metadata_storage
+        .update_last_persisted_state_value_index(&third_target, 500, HashValue::random(), true)
+        .unwrap();
+    assert_none!(metadata_storage
+        .get_resumed_snapshot_version(&third_target)
+        .unwrap());
+
+    // Verify that a completed snapshot sync can't be switched
+    metadata_storage
+        .switch_snapshot_sync_target(&create_ledger_info_at_version(400))
         .unwrap_err();
 }
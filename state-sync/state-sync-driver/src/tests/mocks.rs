@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    backup_source::BackupDataSource,
     error::Error,
     metadata_storage::MetadataStorageInterface,
     storage_synchronizer::{NotificationMetadata, StorageSynchronizerInterface},
@@ -346,12 +347,33 @@ mock! {
             target_ledger_info: &LedgerInfoWithSignatures,
         ) -> Result<u64, Error>;
 
+        fn get_last_persisted_state_key_hash(
+            &self,
+            target_ledger_info: &LedgerInfoWithSignatures,
+        ) -> Result<Option<HashValue>, Error>;
+
+        fn get_resumed_snapshot_version(
+            &self,
+            target_ledger_info: &LedgerInfoWithSignatures,
+        ) -> Result<Option<Version>, Error>;
+
         fn previous_snapshot_sync_target(&self) -> Result<Option<LedgerInfoWithSignatures>, Error>;
 
+        fn switch_snapshot_sync_target(
+            &self,
+            target_ledger_info: &LedgerInfoWithSignatures,
+        ) -> Result<(), Error>;
+
+        fn restart_snapshot_sync(
+            &self,
+            target_ledger_info: &LedgerInfoWithSignatures,
+        ) -> Result<(), Error>;
+
         fn update_last_persisted_state_value_index(
             &self,
             target_ledger_info: &LedgerInfoWithSignatures,
             last_persisted_state_value_index: u64,
+            last_persisted_state_key_hash: HashValue,
             snapshot_sync_completed: bool,
         ) -> Result<(), Error>;
     }
@@ -361,6 +383,31 @@ mock! {
     }
 }
 
+// This automatically creates a MockBackupDataSource.
+mock! {
+    pub BackupDataSource {}
+    #[async_trait]
+    impl BackupDataSource for BackupDataSource {
+        async fn get_epoch_ending_ledger_infos(
+            &self,
+            start_epoch: u64,
+        ) -> AnyhowResult<Vec<LedgerInfoWithSignatures>>;
+
+        async fn get_state_snapshot_versions(&self) -> AnyhowResult<Vec<Version>>;
+
+        async fn get_transaction_output(
+            &self,
+            version: Version,
+        ) -> AnyhowResult<TransactionOutputListWithProofV2>;
+
+        async fn get_state_value_chunk(
+            &self,
+            version: Version,
+            start_index: u64,
+        ) -> AnyhowResult<StateValueChunkWithProof>;
+    }
+}
+
 // This automatically creates a MockSnapshotReceiver.
 mock! {
     pub SnapshotReceiver {}
@@ -104,6 +104,7 @@ pub fn create_full_node_driver_configuration() -> DriverConfiguration {
         consensus_observer_config,
         role,
         waypoint,
+        backup_source: None,
     }
 }
 
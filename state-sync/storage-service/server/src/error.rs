@@ -8,6 +8,11 @@ use thiserror::Error;
 pub enum Error {
     #[error("Invalid request received: {0}")]
     InvalidRequest(String),
+    #[error("Request quota exceeded! Retry after {retry_after_ms} ms: {message}")]
+    RequestQuotaExceeded {
+        retry_after_ms: u64,
+        message: String,
+    },
     #[error("Storage error encountered: {0}")]
     StorageErrorEncountered(String),
     #[error("Too many invalid requests: {0}")]
@@ -21,6 +26,7 @@ impl Error {
     pub fn get_label(&self) -> &'static str {
         match self {
             Error::InvalidRequest(_) => "invalid_request",
+            Error::RequestQuotaExceeded { .. } => "request_quota_exceeded",
             Error::StorageErrorEncountered(_) => "storage_error",
             Error::TooManyInvalidRequests(_) => "too_many_invalid_requests",
             Error::UnexpectedErrorEncountered(_) => "unexpected_error",
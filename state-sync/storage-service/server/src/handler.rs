@@ -195,6 +195,13 @@ impl<T: StorageReaderInterface> Handler<T> {
         // Transform the request error into a storage service error (for the client)
         process_result.map_err(|error| match error {
             Error::InvalidRequest(error) => StorageServiceError::InvalidRequest(error),
+            Error::RequestQuotaExceeded {
+                retry_after_ms,
+                message,
+            } => StorageServiceError::RequestQuotaExceeded {
+                retry_after_ms,
+                message,
+            },
             Error::TooManyInvalidRequests(error) => {
                 StorageServiceError::TooManyInvalidRequests(error)
             },
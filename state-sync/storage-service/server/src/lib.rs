@@ -42,6 +42,7 @@ pub mod metrics;
 mod moderator;
 pub mod network;
 mod optimistic_fetch;
+mod request_quotas;
 pub mod storage;
 mod subscription;
 mod utils;
@@ -1,7 +1,9 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{error::Error, logging::LogEntry, metrics, utils, LogSchema};
+use crate::{
+    error::Error, logging::LogEntry, metrics, request_quotas::RequestQuotas, utils, LogSchema,
+};
 use aptos_config::{
     config::{AptosDataClientConfig, StorageServiceConfig},
     network_id::{NetworkId, PeerNetworkId},
@@ -101,11 +103,13 @@ impl UnhealthyPeerState {
 /// The request moderator is responsible for validating inbound storage
 /// requests and ensuring that only valid (and satisfiable) requests are processed.
 /// If a peer sends too many invalid requests, the moderator will mark the peer as
-/// "unhealthy" and will ignore requests from that peer for some time.
+/// "unhealthy" and will ignore requests from that peer for some time. The moderator
+/// also enforces cost-based request quotas, to prevent peers from overloading storage.
 pub struct RequestModerator {
     aptos_data_client_config: AptosDataClientConfig,
     cached_storage_server_summary: Arc<ArcSwap<StorageServerSummary>>,
     peers_and_metadata: Arc<PeersAndMetadata>,
+    request_quotas: RequestQuotas,
     storage_service_config: StorageServiceConfig,
     time_service: TimeService,
     unhealthy_peer_states: Arc<DashMap<PeerNetworkId, UnhealthyPeerState>>,
@@ -124,6 +128,7 @@ impl RequestModerator {
             cached_storage_server_summary,
             unhealthy_peer_states: Arc::new(DashMap::new()),
             peers_and_metadata,
+            request_quotas: RequestQuotas::new(storage_service_config, time_service.clone()),
             storage_service_config,
             time_service,
         }
@@ -184,6 +189,11 @@ impl RequestModerator {
                 )));
             }
 
+            // Charge the request against the peer's quota (this
+            // will fail if the peer has exhausted its quota).
+            self.request_quotas
+                .charge_request(peer_network_id, request)?;
+
             Ok(()) // The request is valid
         };
         utils::execute_and_time_duration(
@@ -227,6 +237,11 @@ impl RequestModerator {
                 }
             });
 
+        // Remove the request quotas of disconnected peers
+        self.request_quotas.retain_peer_quotas(|peer_network_id| {
+            connected_peers_and_metadata.contains_key(peer_network_id)
+        });
+
         // Update the number of ignored peers
         metrics::set_gauge(
             &metrics::IGNORED_PEER_COUNT,
@@ -244,6 +259,12 @@ impl RequestModerator {
     ) -> Arc<DashMap<PeerNetworkId, UnhealthyPeerState>> {
         self.unhealthy_peer_states.clone()
     }
+
+    #[cfg(test)]
+    /// Returns a reference to the request quotas for testing
+    pub(crate) fn get_request_quotas(&self) -> &RequestQuotas {
+        &self.request_quotas
+    }
 }
 
 #[cfg(test)]
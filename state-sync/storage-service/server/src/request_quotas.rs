@@ -0,0 +1,606 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::error::Error;
+use aptos_config::{
+    config::{StorageRequestQuotaConfig, StorageServiceConfig},
+    network_id::PeerNetworkId,
+};
+use aptos_infallible::Mutex;
+use aptos_storage_service_types::requests::{
+    DataRequest, StorageServiceRequest, TransactionDataRequestType,
+};
+use aptos_time_service::{TimeService, TimeServiceTrait};
+use dashmap::DashMap;
+use std::{
+    cmp::{max, min},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+// The estimated sizes (bytes) of the data items served by the storage service
+const ESTIMATED_EPOCH_ENDING_LEDGER_INFO_BYTES: u64 = 10 * 1024; // Includes the signatures
+const ESTIMATED_EVENTS_BYTES: u64 = 1024; // The events emitted by a single transaction
+const ESTIMATED_METADATA_RESPONSE_BYTES: u64 = 1024; // Summaries, versions and state counts
+const ESTIMATED_STATE_VALUE_BYTES: u64 = 512;
+const ESTIMATED_TRANSACTION_BYTES: u64 = 1024;
+const ESTIMATED_TRANSACTION_OUTPUT_BYTES: u64 = 4 * 1024; // Includes the write set and events
+
+// The estimated number of storage reads required to serve each data item
+const IO_OPERATIONS_PER_EPOCH_ENDING_LEDGER_INFO: u64 = 1;
+const IO_OPERATIONS_PER_EVENTS: u64 = 1;
+const IO_OPERATIONS_PER_STATE_VALUE: u64 = 2; // The value and the proof nodes
+const IO_OPERATIONS_PER_TRANSACTION: u64 = 2; // The transaction and the transaction info
+const IO_OPERATIONS_PER_TRANSACTION_OUTPUT: u64 = 3; // The write set, events and transaction info
+
+/// The estimated cost of serving a single storage service request
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RequestCost {
+    estimated_bytes: u64,         // The estimated number of bytes in the response
+    estimated_io_operations: u64, // The estimated number of storage reads
+}
+
+impl RequestCost {
+    pub fn new(estimated_bytes: u64, estimated_io_operations: u64) -> Self {
+        Self {
+            estimated_bytes,
+            estimated_io_operations,
+        }
+    }
+
+    /// Estimates the cost of serving the given request. Note: the estimates
+    /// are bounded by the chunk and network limits of the storage service.
+    pub fn estimate(
+        storage_service_config: &StorageServiceConfig,
+        request: &StorageServiceRequest,
+    ) -> Self {
+        let max_bytes = storage_service_config.max_network_chunk_bytes;
+        let max_bytes_v2 = storage_service_config.max_network_chunk_bytes_v2;
+
+        match &request.data_request {
+            DataRequest::GetEpochEndingLedgerInfos(request) => {
+                let num_ledger_infos = min(
+                    get_num_items_in_range(request.start_epoch, request.expected_end_epoch),
+                    storage_service_config.max_epoch_chunk_size,
+                );
+                Self::estimate_for_items(
+                    num_ledger_infos,
+                    ESTIMATED_EPOCH_ENDING_LEDGER_INFO_BYTES,
+                    IO_OPERATIONS_PER_EPOCH_ENDING_LEDGER_INFO,
+                    max_bytes,
+                )
+            },
+            DataRequest::GetNumberOfStatesAtVersion(_) => {
+                Self::new(ESTIMATED_METADATA_RESPONSE_BYTES, 1)
+            },
+            DataRequest::GetServerProtocolVersion | DataRequest::GetStorageServerSummary => {
+                Self::new(ESTIMATED_METADATA_RESPONSE_BYTES, 0) // These are served from memory
+            },
+            DataRequest::GetStateValuesWithProof(request) => {
+                let num_state_values = min(
+                    get_num_items_in_range(request.start_index, request.end_index),
+                    storage_service_config.max_state_chunk_size,
+                );
+                Self::estimate_for_items(
+                    num_state_values,
+                    ESTIMATED_STATE_VALUE_BYTES,
+                    IO_OPERATIONS_PER_STATE_VALUE,
+                    max_bytes,
+                )
+            },
+            DataRequest::GetTransactionsWithProof(request) => Self::estimate_for_transactions(
+                storage_service_config,
+                get_num_items_in_range(request.start_version, request.end_version),
+                request.include_events,
+                max_bytes,
+            ),
+            DataRequest::GetNewTransactionsWithProof(request) => Self::estimate_for_transactions(
+                storage_service_config,
+                u64::MAX, // The number of new transactions is unknown
+                request.include_events,
+                max_bytes,
+            ),
+            DataRequest::SubscribeTransactionsWithProof(request) => {
+                Self::estimate_for_transactions(
+                    storage_service_config,
+                    u64::MAX, // The number of new transactions is unknown
+                    request.include_events,
+                    max_bytes,
+                )
+            },
+            DataRequest::GetTransactionOutputsWithProof(request) => {
+                Self::estimate_for_transaction_outputs(
+                    storage_service_config,
+                    get_num_items_in_range(request.start_version, request.end_version),
+                    max_bytes,
+                )
+            },
+            DataRequest::GetTransactionsOrOutputsWithProof(request) => {
+                // Outputs are served first, so we assume the worst case
+                Self::estimate_for_transaction_outputs(
+                    storage_service_config,
+                    get_num_items_in_range(request.start_version, request.end_version),
+                    max_bytes,
+                )
+            },
+            DataRequest::GetNewTransactionOutputsWithProof(_)
+            | DataRequest::GetNewTransactionsOrOutputsWithProof(_)
+            | DataRequest::SubscribeTransactionOutputsWithProof(_)
+            | DataRequest::SubscribeTransactionsOrOutputsWithProof(_) => {
+                Self::estimate_for_transaction_outputs(
+                    storage_service_config,
+                    u64::MAX, // The number of new outputs is unknown
+                    max_bytes,
+                )
+            },
+
+            // Transaction data v2 requests (transactions with auxiliary data)
+            DataRequest::GetTransactionDataWithProof(request) => {
+                Self::estimate_for_transaction_data(
+                    storage_service_config,
+                    &request.transaction_data_request_type,
+                    get_num_items_in_range(request.start_version, request.end_version),
+                    min(request.max_response_bytes, max_bytes_v2),
+                )
+            },
+            DataRequest::GetNewTransactionDataWithProof(request) => {
+                Self::estimate_for_transaction_data(
+                    storage_service_config,
+                    &request.transaction_data_request_type,
+                    u64::MAX, // The amount of new data is unknown
+                    min(request.max_response_bytes, max_bytes_v2),
+                )
+            },
+            DataRequest::SubscribeTransactionDataWithProof(request) => {
+                Self::estimate_for_transaction_data(
+                    storage_service_config,
+                    &request.transaction_data_request_type,
+                    u64::MAX, // The amount of new data is unknown
+                    min(request.max_response_bytes, max_bytes_v2),
+                )
+            },
+        }
+    }
+
+    /// Estimates the cost of serving the given number of data items
+    fn estimate_for_items(
+        num_items: u64,
+        bytes_per_item: u64,
+        io_operations_per_item: u64,
+        max_bytes: u64,
+    ) -> Self {
+        let estimated_bytes = min(num_items.saturating_mul(bytes_per_item), max_bytes);
+        let estimated_io_operations = num_items.saturating_mul(io_operations_per_item);
+        Self::new(estimated_bytes, estimated_io_operations)
+    }
+
+    /// Estimates the cost of serving the given number of transactions
+    fn estimate_for_transactions(
+        storage_service_config: &StorageServiceConfig,
+        num_transactions: u64,
+        include_events: bool,
+        max_bytes: u64,
+    ) -> Self {
+        let num_transactions = min(
+            num_transactions,
+            storage_service_config.max_transaction_chunk_size,
+        );
+        let (bytes_per_transaction, io_operations_per_transaction) = if include_events {
+            (
+                ESTIMATED_TRANSACTION_BYTES + ESTIMATED_EVENTS_BYTES,
+                IO_OPERATIONS_PER_TRANSACTION + IO_OPERATIONS_PER_EVENTS,
+            )
+        } else {
+            (ESTIMATED_TRANSACTION_BYTES, IO_OPERATIONS_PER_TRANSACTION)
+        };
+        Self::estimate_for_items(
+            num_transactions,
+            bytes_per_transaction,
+            io_operations_per_transaction,
+            max_bytes,
+        )
+    }
+
+    /// Estimates the cost of serving the given number of transaction outputs
+    fn estimate_for_transaction_outputs(
+        storage_service_config: &StorageServiceConfig,
+        num_outputs: u64,
+        max_bytes: u64,
+    ) -> Self {
+        let num_outputs = min(
+            num_outputs,
+            storage_service_config.max_transaction_output_chunk_size,
+        );
+        Self::estimate_for_items(
+            num_outputs,
+            ESTIMATED_TRANSACTION_OUTPUT_BYTES,
+            IO_OPERATIONS_PER_TRANSACTION_OUTPUT,
+            max_bytes,
+        )
+    }
+
+    /// Estimates the cost of serving the given amount of transaction data (v2)
+    fn estimate_for_transaction_data(
+        storage_service_config: &StorageServiceConfig,
+        transaction_data_request_type: &TransactionDataRequestType,
+        num_items: u64,
+        max_bytes: u64,
+    ) -> Self {
+        match transaction_data_request_type {
+            TransactionDataRequestType::TransactionData(transaction_data) => {
+                Self::estimate_for_transactions(
+                    storage_service_config,
+                    num_items,
+                    transaction_data.include_events,
+                    max_bytes,
+                )
+            },
+            TransactionDataRequestType::TransactionOutputData
+            | TransactionDataRequestType::TransactionOrOutputData(_) => {
+                Self::estimate_for_transaction_outputs(storage_service_config, num_items, max_bytes)
+            },
+        }
+    }
+
+    #[cfg(test)]
+    /// Returns the estimated number of bytes in the response
+    pub fn get_estimated_bytes(&self) -> u64 {
+        self.estimated_bytes
+    }
+
+    #[cfg(test)]
+    /// Returns the estimated number of storage reads
+    pub fn get_estimated_io_operations(&self) -> u64 {
+        self.estimated_io_operations
+    }
+
+    /// Returns the total cost (in bytes), where each storage
+    /// read is charged at the given cost.
+    pub fn get_total_cost(&self, io_operation_cost_bytes: u64) -> u64 {
+        self.estimated_io_operations
+            .saturating_mul(io_operation_cost_bytes)
+            .saturating_add(self.estimated_bytes)
+    }
+}
+
+/// Returns the number of items in the given (inclusive) range
+fn get_num_items_in_range(start: u64, end: u64) -> u64 {
+    end.saturating_sub(start).saturating_add(1)
+}
+
+/// A simple token bucket that refills continuously at a fixed rate
+#[derive(Clone, Debug)]
+pub struct TokenBucket {
+    available_tokens: f64,     // The number of tokens currently available
+    capacity: u64,             // The max number of tokens in the bucket
+    last_refill_time: Instant, // The time at which the bucket was last refilled
+    refill_rate_per_sec: u64,  // The number of tokens added to the bucket per second
+}
+
+impl TokenBucket {
+    /// Creates a new (full) token bucket
+    pub fn new(capacity: u64, refill_rate_per_sec: u64, time_now: Instant) -> Self {
+        Self {
+            available_tokens: capacity as f64,
+            capacity,
+            last_refill_time: time_now,
+            refill_rate_per_sec,
+        }
+    }
+
+    #[cfg(test)]
+    /// Returns the number of tokens currently available
+    pub fn get_available_tokens(&self) -> u64 {
+        self.available_tokens as u64
+    }
+
+    /// Returns the time to wait until the given number of tokens is
+    /// available (zero if the tokens are available now). Note: costs
+    /// that exceed the capacity are bounded by the capacity (otherwise
+    /// they could never be satisfied).
+    pub fn get_time_until_available(&mut self, num_tokens: u64, time_now: Instant) -> Duration {
+        self.refill(time_now);
+
+        let num_tokens = min(num_tokens, self.capacity) as f64;
+        if self.available_tokens >= num_tokens {
+            return Duration::ZERO;
+        }
+
+        let missing_tokens = num_tokens - self.available_tokens;
+        let refill_rate_per_sec = max(self.refill_rate_per_sec, 1) as f64;
+        Duration::from_secs_f64(missing_tokens / refill_rate_per_sec)
+    }
+
+    /// Removes the given number of tokens from the bucket. The
+    /// caller must first ensure that the tokens are available.
+    pub fn consume(&mut self, num_tokens: u64) {
+        let num_tokens = min(num_tokens, self.capacity) as f64;
+        self.available_tokens = (self.available_tokens - num_tokens).max(0.0);
+    }
+
+    /// Refills the bucket according to the time elapsed since the last refill
+    fn refill(&mut self, time_now: Instant) {
+        let elapsed_secs = time_now
+            .saturating_duration_since(self.last_refill_time)
+            .as_secs_f64();
+        let refilled_tokens = elapsed_secs * self.refill_rate_per_sec as f64;
+        self.available_tokens = (self.available_tokens + refilled_tokens).min(self.capacity as f64);
+        self.last_refill_time = max(self.last_refill_time, time_now);
+    }
+}
+
+/// The request quotas enforce cost-based limits on the storage requests
+/// served to each peer, as well as to the public network as a whole. This
+/// prevents greedy (but otherwise well-behaved) peers from saturating the
+/// node's disk. Validators and VFNs are prioritized, i.e., they are given
+/// larger quotas and are exempt from the public network quota.
+pub struct RequestQuotas {
+    peer_quotas: Arc<DashMap<PeerNetworkId, TokenBucket>>,
+    public_network_quota: Arc<Mutex<TokenBucket>>,
+    storage_service_config: StorageServiceConfig,
+    time_service: TimeService,
+}
+
+impl RequestQuotas {
+    pub fn new(storage_service_config: StorageServiceConfig, time_service: TimeService) -> Self {
+        let quota_config = storage_service_config.request_quota_config;
+        let public_network_quota = TokenBucket::new(
+            quota_config.max_public_network_burst_bytes,
+            quota_config.public_network_refill_bytes_per_sec,
+            time_service.now(),
+        );
+
+        Self {
+            peer_quotas: Arc::new(DashMap::new()),
+            public_network_quota: Arc::new(Mutex::new(public_network_quota)),
+            storage_service_config,
+            time_service,
+        }
+    }
+
+    /// Charges the estimated cost of the given request against the quotas of
+    /// the peer (and network). If the quotas are exhausted, nothing is charged
+    /// and an error is returned that specifies when the peer can retry.
+    pub fn charge_request(
+        &self,
+        peer_network_id: &PeerNetworkId,
+        request: &StorageServiceRequest,
+    ) -> Result<(), Error> {
+        // If request quotas are disabled, there's nothing to do
+        let quota_config = self.get_quota_config();
+        if !quota_config.enable_request_quotas {
+            return Ok(());
+        }
+
+        // Estimate the cost of the request
+        let request_cost = RequestCost::estimate(&self.storage_service_config, request);
+        let total_cost = request_cost.get_total_cost(quota_config.io_operation_cost_bytes);
+
+        // Identify the time until the peer's quota can cover the request
+        let time_now = self.time_service.now();
+        let is_prioritized_peer = is_prioritized_peer(peer_network_id);
+        let mut peer_quota = self
+            .peer_quotas
+            .entry(*peer_network_id)
+            .or_insert_with(|| create_peer_quota(&quota_config, is_prioritized_peer, time_now));
+        let mut time_until_available = peer_quota.get_time_until_available(total_cost, time_now);
+
+        // Identify the time until the network quota can cover the request (if required)
+        let mut public_network_quota = if is_prioritized_peer {
+            None
+        } else {
+            let mut public_network_quota = self.public_network_quota.lock();
+            time_until_available = max(
+                time_until_available,
+                public_network_quota.get_time_until_available(total_cost, time_now),
+            );
+            Some(public_network_quota)
+        };
+
+        // If the quotas are exhausted, return an error
+        if !time_until_available.is_zero() {
+            let retry_after_ms = max(time_until_available.as_secs_f64() * 1000.0, 1.0).ceil();
+            return Err(Error::RequestQuotaExceeded {
+                retry_after_ms: retry_after_ms as u64,
+                message: format!(
+                    "The request quota is exhausted! Request: {:?}, estimated cost: {:?}",
+                    request, request_cost
+                ),
+            });
+        }
+
+        // Otherwise, charge the request against the quotas
+        peer_quota.consume(total_cost);
+        if let Some(public_network_quota) = public_network_quota.as_mut() {
+            public_network_quota.consume(total_cost);
+        }
+
+        Ok(())
+    }
+
+    /// Removes the quotas of all peers that don't satisfy the given predicate
+    pub fn retain_peer_quotas<F: Fn(&PeerNetworkId) -> bool>(&self, should_retain: F) {
+        self.peer_quotas
+            .retain(|peer_network_id, _| should_retain(peer_network_id));
+    }
+
+    /// Returns the request quota config
+    fn get_quota_config(&self) -> StorageRequestQuotaConfig {
+        self.storage_service_config.request_quota_config
+    }
+
+    #[cfg(test)]
+    /// Returns a copy of the peer quotas for testing
+    pub(crate) fn get_peer_quotas(&self) -> Arc<DashMap<PeerNetworkId, TokenBucket>> {
+        self.peer_quotas.clone()
+    }
+}
+
+/// Creates a new quota for a peer (prioritized peers are given larger quotas)
+fn create_peer_quota(
+    quota_config: &StorageRequestQuotaConfig,
+    is_prioritized_peer: bool,
+    time_now: Instant,
+) -> TokenBucket {
+    let quota_multiplier = if is_prioritized_peer {
+        quota_config.prioritized_peer_quota_multiplier
+    } else {
+        1
+    };
+    TokenBucket::new(
+        quota_config
+            .max_peer_burst_bytes
+            .saturating_mul(quota_multiplier),
+        quota_config
+            .peer_refill_bytes_per_sec
+            .saturating_mul(quota_multiplier),
+        time_now,
+    )
+}
+
+/// Returns true iff the peer should be prioritized (i.e., the peer
+/// is a validator or a VFN, and not on the public network).
+fn is_prioritized_peer(peer_network_id: &PeerNetworkId) -> bool {
+    !peer_network_id.network_id().is_public_network()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_storage_service_types::requests::{
+        StateValuesWithProofRequest, TransactionOutputsWithProofRequest,
+    };
+
+    #[test]
+    fn test_request_cost_estimates() {
+        // Create a storage service config for testing
+        let storage_service_config = StorageServiceConfig {
+            max_network_chunk_bytes: 10 * 1024 * 1024,
+            max_state_chunk_size: 1000,
+            max_transaction_output_chunk_size: 500,
+            ..Default::default()
+        };
+
+        // Verify the cost of a small state value request
+        let request = create_state_values_request(0, 99);
+        let request_cost = RequestCost::estimate(&storage_service_config, &request);
+        assert_eq!(
+            request_cost.get_estimated_bytes(),
+            100 * ESTIMATED_STATE_VALUE_BYTES
+        );
+        assert_eq!(
+            request_cost.get_estimated_io_operations(),
+            100 * IO_OPERATIONS_PER_STATE_VALUE
+        );
+
+        // Verify the cost of a state value request is bounded by the chunk size
+        let request = create_state_values_request(0, 1_000_000);
+        let request_cost = RequestCost::estimate(&storage_service_config, &request);
+        assert_eq!(
+            request_cost.get_estimated_io_operations(),
+            1000 * IO_OPERATIONS_PER_STATE_VALUE
+        );
+
+        // Verify the cost of an output request is bounded by the chunk size
+        let request = create_transaction_outputs_request(100, u64::MAX);
+        let request_cost = RequestCost::estimate(&storage_service_config, &request);
+        assert_eq!(
+            request_cost.get_estimated_bytes(),
+            500 * ESTIMATED_TRANSACTION_OUTPUT_BYTES
+        );
+        assert_eq!(
+            request_cost.get_estimated_io_operations(),
+            500 * IO_OPERATIONS_PER_TRANSACTION_OUTPUT
+        );
+
+        // Verify the estimated bytes are bounded by the network chunk size
+        let storage_service_config = StorageServiceConfig {
+            max_network_chunk_bytes: 1024,
+            ..storage_service_config
+        };
+        let request_cost = RequestCost::estimate(&storage_service_config, &request);
+        assert_eq!(request_cost.get_estimated_bytes(), 1024);
+
+        // Verify the total cost of the request
+        assert_eq!(
+            request_cost.get_total_cost(10),
+            1024 + 500 * IO_OPERATIONS_PER_TRANSACTION_OUTPUT * 10
+        );
+    }
+
+    #[test]
+    fn test_token_bucket() {
+        // Create a token bucket
+        let time_service = TimeService::mock();
+        let mut token_bucket = TokenBucket::new(1000, 100, time_service.now());
+
+        // Verify the tokens are available and consume them
+        assert_eq!(
+            token_bucket.get_time_until_available(600, time_service.now()),
+            Duration::ZERO
+        );
+        token_bucket.consume(600);
+        assert_eq!(token_bucket.get_available_tokens(), 400);
+
+        // Verify the time until enough tokens are available
+        assert_eq!(
+            token_bucket.get_time_until_available(600, time_service.now()),
+            Duration::from_secs(2)
+        );
+
+        // Elapse some time and verify the bucket is refilled
+        time_service
+            .clone()
+            .into_mock()
+            .advance(Duration::from_secs(2));
+        assert_eq!(
+            token_bucket.get_time_until_available(600, time_service.now()),
+            Duration::ZERO
+        );
+        assert_eq!(token_bucket.get_available_tokens(), 600);
+
+        // Elapse a lot of time and verify the bucket never exceeds the capacity
+        time_service
+            .clone()
+            .into_mock()
+            .advance(Duration::from_secs(100));
+        token_bucket.get_time_until_available(0, time_service.now());
+        assert_eq!(token_bucket.get_available_tokens(), 1000);
+
+        // Verify that costs larger than the capacity are bounded by the capacity
+        assert_eq!(
+            token_bucket.get_time_until_available(5000, time_service.now()),
+            Duration::ZERO
+        );
+        token_bucket.consume(5000);
+        assert_eq!(token_bucket.get_available_tokens(), 0);
+        assert_eq!(
+            token_bucket.get_time_until_available(5000, time_service.now()),
+            Duration::from_secs(10)
+        );
+    }
+
+    /// Creates a state values request for the given index range
+    fn create_state_values_request(start_index: u64, end_index: u64) -> StorageServiceRequest {
+        let data_request = DataRequest::GetStateValuesWithProof(StateValuesWithProofRequest {
+            version: 0,
+            start_index,
+            end_index,
+        });
+        StorageServiceRequest::new(data_request, false)
+    }
+
+    /// Creates a transaction outputs request for the given version range
+    fn create_transaction_outputs_request(
+        start_version: u64,
+        end_version: u64,
+    ) -> StorageServiceRequest {
+        let data_request =
+            DataRequest::GetTransactionOutputsWithProof(TransactionOutputsWithProofRequest {
+                proof_version: end_version,
+                start_version,
+                end_version,
+            });
+        StorageServiceRequest::new(data_request, false)
+    }
+}
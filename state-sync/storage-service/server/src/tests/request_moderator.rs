@@ -6,7 +6,7 @@ use crate::{
     tests::{mock::MockClient, utils},
 };
 use aptos_config::{
-    config::{PeerRole, StorageServiceConfig},
+    config::{PeerRole, StorageRequestQuotaConfig, StorageServiceConfig},
     network_id::{NetworkId, PeerNetworkId},
 };
 use aptos_netcore::transport::ConnectionOrigin;
@@ -353,6 +353,161 @@ async fn test_request_moderator_peer_garbage_collect() {
     assert_eq!(unhealthy_peer_states.len(), 1);
 }
 
+#[tokio::test]
+async fn test_request_moderator_quotas() {
+    // Create a storage service config with small request quotas
+    let metadata_request_cost = 1024; // The estimated cost of a storage summary request
+    let storage_service_config = StorageServiceConfig {
+        request_quota_config: StorageRequestQuotaConfig {
+            enable_request_quotas: true,
+            max_peer_burst_bytes: 3 * metadata_request_cost,
+            max_public_network_burst_bytes: 5 * metadata_request_cost,
+            peer_refill_bytes_per_sec: metadata_request_cost,
+            prioritized_peer_quota_multiplier: 2,
+            public_network_refill_bytes_per_sec: metadata_request_cost,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    // Create the storage client and server
+    let (mut mock_client, service, _, time_service, _) =
+        MockClient::new(None, Some(storage_service_config));
+    tokio::spawn(service.start());
+
+    // Process several requests from a PFN and verify the peer quota is enforced
+    let pfn_peer_network_id_1 = PeerNetworkId::new(NetworkId::Public, PeerId::random());
+    for _ in 0..3 {
+        send_storage_summary_request(&mut mock_client, pfn_peer_network_id_1)
+            .await
+            .unwrap();
+    }
+    let response = send_storage_summary_request(&mut mock_client, pfn_peer_network_id_1).await;
+    assert_matches!(
+        response.unwrap_err(),
+        StorageServiceError::RequestQuotaExceeded {
+            retry_after_ms: 1000,
+            ..
+        }
+    );
+
+    // Process several requests from another PFN and verify the network quota is enforced
+    let pfn_peer_network_id_2 = PeerNetworkId::new(NetworkId::Public, PeerId::random());
+    for _ in 0..2 {
+        send_storage_summary_request(&mut mock_client, pfn_peer_network_id_2)
+            .await
+            .unwrap();
+    }
+    let response = send_storage_summary_request(&mut mock_client, pfn_peer_network_id_2).await;
+    assert_matches!(
+        response.unwrap_err(),
+        StorageServiceError::RequestQuotaExceeded { .. }
+    );
+
+    // Process several requests from a VFN and verify the peer is prioritized
+    let vfn_peer_network_id = PeerNetworkId::new(NetworkId::Vfn, PeerId::random());
+    for _ in 0..6 {
+        send_storage_summary_request(&mut mock_client, vfn_peer_network_id)
+            .await
+            .unwrap();
+    }
+    let response = send_storage_summary_request(&mut mock_client, vfn_peer_network_id).await;
+    assert_matches!(
+        response.unwrap_err(),
+        StorageServiceError::RequestQuotaExceeded { .. }
+    );
+
+    // Elapse enough time for the quotas to refill a single request
+    time_service.advance_secs_async(1).await;
+
+    // Verify the first PFN can send another request (but the network quota is exhausted again)
+    send_storage_summary_request(&mut mock_client, pfn_peer_network_id_1)
+        .await
+        .unwrap();
+    let response = send_storage_summary_request(&mut mock_client, pfn_peer_network_id_2).await;
+    assert_matches!(
+        response.unwrap_err(),
+        StorageServiceError::RequestQuotaExceeded { .. }
+    );
+}
+
+#[tokio::test]
+async fn test_request_moderator_quotas_disabled() {
+    // Create a storage service config with request quotas disabled
+    let storage_service_config = StorageServiceConfig {
+        request_quota_config: StorageRequestQuotaConfig {
+            enable_request_quotas: false,
+            max_peer_burst_bytes: 0,
+            max_public_network_burst_bytes: 0,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    // Create the storage client and server
+    let (mut mock_client, service, _, _, _) = MockClient::new(None, Some(storage_service_config));
+    tokio::spawn(service.start());
+
+    // Process many requests from a PFN and verify they are all served
+    let pfn_peer_network_id = PeerNetworkId::new(NetworkId::Public, PeerId::random());
+    for _ in 0..10 {
+        send_storage_summary_request(&mut mock_client, pfn_peer_network_id)
+            .await
+            .unwrap();
+    }
+}
+
+#[tokio::test]
+async fn test_request_moderator_quotas_garbage_collect() {
+    // Create the storage client and server
+    let (mut mock_client, service, _, time_service, peers_and_metadata) =
+        MockClient::new(None, None);
+    let request_moderator = service.get_request_moderator();
+    let peer_quotas = request_moderator.get_request_quotas().get_peer_quotas();
+    tokio::spawn(service.start());
+
+    // Connect a new peer
+    let peer_network_id = PeerNetworkId::new(NetworkId::Public, PeerId::random());
+    peers_and_metadata
+        .insert_connection_metadata(
+            peer_network_id,
+            create_connection_metadata(peer_network_id.peer_id(), 0),
+        )
+        .unwrap();
+
+    // Send a request from the peer and verify the peer quota is tracked
+    send_storage_summary_request(&mut mock_client, peer_network_id)
+        .await
+        .unwrap();
+    assert!(peer_quotas.contains_key(&peer_network_id));
+
+    // Disconnect the peer
+    peers_and_metadata
+        .remove_peer_metadata(peer_network_id, ConnectionId::from(0))
+        .unwrap();
+
+    // Wait for the request moderator to garbage collect the peer quota
+    let garbage_collect = async move {
+        loop {
+            // Elapse enough time to force the moderator to refresh peer states
+            advance_moderator_refresh_time(&time_service).await;
+
+            // Check if the peer quota is still being tracked
+            if !peer_quotas.contains_key(&peer_network_id) {
+                return; // The peer quota has been garbage collected
+            }
+
+            // Wait before retrying
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    };
+    utils::spawn_with_timeout(
+        garbage_collect,
+        "Timed-out while waiting for the request moderator to garbage collect the peer quota",
+    )
+    .await;
+}
+
 /// Advances the given timer by the amount of time it takes to refresh the moderator
 async fn advance_moderator_refresh_time(mock_time: &MockTimeService) {
     let default_storage_config = StorageServiceConfig::default();
@@ -404,6 +559,22 @@ async fn send_invalid_transaction_request(
     mock_client.wait_for_response(receiver).await
 }
 
+/// Sends a request to get the storage server summary from the given peer
+async fn send_storage_summary_request(
+    mock_client: &mut MockClient,
+    peer_network_id: PeerNetworkId,
+) -> Result<StorageServiceResponse, StorageServiceError> {
+    let request = StorageServiceRequest::new(DataRequest::GetStorageServerSummary, true);
+    let receiver = mock_client
+        .send_request(
+            request,
+            Some(peer_network_id.peer_id()),
+            Some(peer_network_id.network_id()),
+        )
+        .await;
+    mock_client.wait_for_response(receiver).await
+}
+
 /// Waits for the request moderator to garbage collect the peer state
 async fn wait_for_request_moderator_to_garbage_collect(
     unhealthy_peer_states: Arc<DashMap<PeerNetworkId, UnhealthyPeerState>>,
@@ -35,6 +35,11 @@ pub enum StorageServiceError {
     InvalidRequest(String),
     #[error("Too many invalid requests! Back off required: {0}")]
     TooManyInvalidRequests(String),
+    #[error("Request quota exceeded! Retry after {retry_after_ms} ms: {message}")]
+    RequestQuotaExceeded {
+        retry_after_ms: u64,
+        message: String,
+    },
 }
 
 /// A single storage service message sent or received over AptosNet.
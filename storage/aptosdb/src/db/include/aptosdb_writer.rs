@@ -86,6 +86,32 @@ impl DbWriter for AptosDB {
         })
     }
 
+    fn get_state_snapshot_receiver_resuming(
+        &self,
+        version: Version,
+        expected_root_hash: HashValue,
+        previous_version: Version,
+    ) -> Result<Box<dyn StateSnapshotReceiver<StateKey, StateValue>>> {
+        gauged_api("get_state_snapshot_receiver_resuming", || {
+            self.state_store.get_snapshot_receiver_resuming(
+                version,
+                expected_root_hash,
+                previous_version,
+            )
+        })
+    }
+
+    fn discard_unfinished_state_snapshots(
+        &self,
+        start_version: Version,
+        end_version: Version,
+    ) -> Result<()> {
+        gauged_api("discard_unfinished_state_snapshots", || {
+            self.state_store
+                .discard_unfinished_snapshots(start_version, end_version)
+        })
+    }
+
     fn finalize_state_snapshot(
         &self,
         version: Version,
@@ -149,6 +149,26 @@ impl DbWriter for FastSyncStorageWrapper {
             .get_state_snapshot_receiver(version, expected_root_hash)
     }
 
+    fn get_state_snapshot_receiver_resuming(
+        &self,
+        version: Version,
+        expected_root_hash: HashValue,
+        previous_version: Version,
+    ) -> Result<Box<dyn StateSnapshotReceiver<StateKey, StateValue>>> {
+        *self.fast_sync_status.write() = FastSyncStatus::STARTED;
+        self.get_aptos_db_write_ref()
+            .get_state_snapshot_receiver_resuming(version, expected_root_hash, previous_version)
+    }
+
+    fn discard_unfinished_state_snapshots(
+        &self,
+        start_version: Version,
+        end_version: Version,
+    ) -> Result<()> {
+        self.get_aptos_db_write_ref()
+            .discard_unfinished_state_snapshots(start_version, end_version)
+    }
+
     fn finalize_state_snapshot(
         &self,
         version: Version,
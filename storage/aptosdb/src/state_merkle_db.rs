@@ -846,4 +846,21 @@ impl TreeWriter<StateKey> for StateMerkleDb {
         })?;
         self.commit_no_progress(top_level_batch, jmt_shard_batches)
     }
+
+    fn delete_node_batch(&self, node_keys: &[NodeKey]) -> Result<()> {
+        let _timer = OTHER_TIMERS_SECONDS
+            .with_label_values(&["tree_writer_delete_batch"])
+            .start_timer();
+        let mut top_level_batch = SchemaBatch::new();
+        let mut jmt_shard_batches: Vec<SchemaBatch> = Vec::with_capacity(NUM_STATE_SHARDS);
+        jmt_shard_batches.resize_with(NUM_STATE_SHARDS, SchemaBatch::new);
+        node_keys.iter().try_for_each(|node_key| {
+            if let Some(shard_id) = node_key.get_shard_id() {
+                jmt_shard_batches[shard_id].delete::<JellyfishMerkleNodeSchema>(node_key)
+            } else {
+                top_level_batch.delete::<JellyfishMerkleNodeSchema>(node_key)
+            }
+        })?;
+        self.commit_no_progress(top_level_batch, jmt_shard_batches)
+    }
 }
@@ -7,7 +7,7 @@ use aptos_crypto::{hash::CryptoHash, HashValue};
 use aptos_db_indexer_schemas::metadata::StateSnapshotProgress;
 use aptos_infallible::Mutex;
 use aptos_jellyfish_merkle::{restore::JellyfishMerkleRestore, Key, TreeReader, TreeWriter, Value};
-use aptos_storage_interface::{Result, StateSnapshotReceiver};
+use aptos_storage_interface::{db_ensure as ensure, AptosDbError, Result, StateSnapshotReceiver};
 use aptos_types::{
     proof::SparseMerkleRangeProof, state_store::state_storage_usage::StateStorageUsage,
     transaction::Version,
@@ -43,6 +43,9 @@ pub trait StateValueWriter<K, V>: Send + Sync {
     fn kv_finish(&self, version: Version, usage: StateStorageUsage) -> Result<()>;
 
     fn get_progress(&self, version: Version) -> Result<Option<StateSnapshotProgress>>;
+
+    /// Gets the latest value of the key at or before the version.
+    fn get_value(&self, key: &K, version: Version) -> Result<Option<V>>;
 }
 
 #[derive(Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
@@ -77,11 +80,32 @@ impl FromStr for StateSnapshotRestoreMode {
 struct StateValueRestore<K, V> {
     version: Version,
     db: Arc<dyn StateValueWriter<K, V>>,
+    /// The version and progress of the abandoned restore this restore continues, if any.
+    resumed: Option<(Version, StateSnapshotProgress)>,
 }
 
 impl<K: Key + CryptoHash + Eq + Hash, V: Value> StateValueRestore<K, V> {
     pub fn new<D: 'static + StateValueWriter<K, V>>(db: Arc<D>, version: Version) -> Self {
-        Self { version, db }
+        Self {
+            version,
+            db,
+            resumed: None,
+        }
+    }
+
+    pub fn new_resuming<D: 'static + StateValueWriter<K, V>>(
+        db: Arc<D>,
+        version: Version,
+        previous_version: Version,
+    ) -> Result<Self> {
+        let resumed = db
+            .get_progress(previous_version)?
+            .map(|progress| (previous_version, progress));
+        Ok(Self {
+            version,
+            db,
+            resumed,
+        })
     }
 
     pub fn add_chunk(&mut self, mut chunk: Vec<(K, V)>) -> Result<()> {
@@ -103,7 +127,10 @@ impl<K: Key + CryptoHash + Eq + Hash, V: Value> StateValueRestore<K, V> {
         }
 
         // save
-        let mut usage = progress_opt.map_or(StateStorageUsage::zero(), |p| p.usage);
+        let mut usage = match progress_opt {
+            Some(progress) => progress.usage,
+            None => self.resumed_usage(&chunk)?,
+        };
         let (last_key, _last_value) = chunk.last().unwrap();
         let last_key_hash = CryptoHash::hash(last_key);
 
@@ -125,6 +152,32 @@ impl<K: Key + CryptoHash + Eq + Hash, V: Value> StateValueRestore<K, V> {
         )
     }
 
+    /// Returns the usage of the abandoned restore being continued (if any), excluding its last
+    /// state. Unlike the tree, the abandoned restore has persisted the value of its last state,
+    /// which may have changed since. So the first chunk must start with that state, which is
+    /// written again.
+    fn resumed_usage(&self, chunk: &[(K, V)]) -> Result<StateStorageUsage> {
+        let (previous_version, progress) = match &self.resumed {
+            Some(resumed) => resumed,
+            None => return Ok(StateStorageUsage::zero()),
+        };
+        let (first_key, _first_value) = chunk.first().expect("Chunk must not be empty.");
+        ensure!(
+            CryptoHash::hash(first_key) == progress.key_hash,
+            "The first state does not continue the restore abandoned at version {}. \
+             Expected key hash: {}, found: {}",
+            previous_version,
+            progress.key_hash,
+            CryptoHash::hash(first_key),
+        );
+
+        let mut usage = progress.usage;
+        if let Some(previous_value) = self.db.get_value(first_key, *previous_version)? {
+            usage.remove_item(first_key.key_size() + previous_value.value_size());
+        }
+        Ok(usage)
+    }
+
     pub fn finish(self) -> Result<()> {
         let progress = self.db.get_progress(self.version)?;
         self.db.kv_finish(
@@ -171,6 +224,40 @@ impl<K: Key + CryptoHash + Hash + Eq, V: Value> StateSnapshotRestore<K, V> {
         })
     }
 
+    /// Same as `new`, but continues the restore abandoned at `previous_version` (e.g., because a
+    /// newer snapshot is restored instead) if nothing has been restored at `version` yet. The
+    /// first chunk must start with the last state the abandoned restore received, and fails if
+    /// anything restored at `previous_version` differs at `version`, in which case the caller is
+    /// expected to discard both restores and start over.
+    pub fn new_resuming<
+        T: 'static + TreeReader<K> + TreeWriter<K>,
+        S: 'static + StateValueWriter<K, V>,
+    >(
+        tree_store: &Arc<T>,
+        value_store: &Arc<S>,
+        version: Version,
+        expected_root_hash: HashValue,
+        async_commit: bool,
+        restore_mode: StateSnapshotRestoreMode,
+        previous_version: Version,
+    ) -> Result<Self> {
+        Ok(Self {
+            tree_restore: Arc::new(Mutex::new(Some(JellyfishMerkleRestore::new_resuming(
+                Arc::clone(tree_store),
+                version,
+                expected_root_hash,
+                async_commit,
+                previous_version,
+            )?))),
+            kv_restore: Arc::new(Mutex::new(Some(StateValueRestore::new_resuming(
+                Arc::clone(value_store),
+                version,
+                previous_version,
+            )?))),
+            restore_mode,
+        })
+    }
+
     pub fn new_overwrite<T: 'static + TreeWriter<K>, S: 'static + StateValueWriter<K, V>>(
         tree_store: &Arc<T>,
         value_store: &Arc<S>,
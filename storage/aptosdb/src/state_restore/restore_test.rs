@@ -56,11 +56,18 @@ where
     }
 
     fn calculate_usage(&self, version: Version) -> StateStorageUsage {
+        // The latest value of each key at or before the version
+        let latest_values: BTreeMap<_, _> = self
+            .kv_store
+            .read()
+            .iter()
+            .filter(|((_k, ver), _v)| *ver <= version)
+            .map(|((k, _ver), v)| (k.clone(), v.clone()))
+            .collect();
+
         let mut usage = StateStorageUsage::zero();
-        for ((k, ver), v) in self.kv_store.read().iter() {
-            if *ver == version {
-                usage.add_item(k.key_size() + v.value_size());
-            }
+        for (k, v) in latest_values {
+            usage.add_item(k.key_size() + v.value_size());
         }
         usage
     }
@@ -96,6 +103,15 @@ where
     fn get_progress(&self, version: Version) -> Result<Option<StateSnapshotProgress>> {
         Ok(self.progress_store.read().get(&version).cloned())
     }
+
+    fn get_value(&self, key: &K, version: Version) -> Result<Option<V>> {
+        Ok(self
+            .kv_store
+            .read()
+            .range((key.clone(), 0)..=(key.clone(), version))
+            .next_back()
+            .map(|(_, value)| value.clone()))
+    }
 }
 
 impl<K, V> TreeReader<K> for MockSnapshotStore<K, V>
@@ -120,6 +136,10 @@ where
     fn write_node_batch(&self, node_batch: &NodeBatch<K>) -> Result<()> {
         self.tree_store.write_node_batch(node_batch)
     }
+
+    fn delete_node_batch(&self, node_keys: &[NodeKey]) -> Result<()> {
+        self.tree_store.delete_node_batch(node_keys)
+    }
 }
 
 fn init_mock_store<V>(kvs: &BTreeMap<V, V>) -> (MockSnapshotStore<V, V>, Version)
@@ -216,6 +236,40 @@ proptest! {
         assert_success(&restore_db, expected_root_hash, &all, version);
     }
 
+    #[test]
+    fn test_restore_resuming_abandoned_restore(
+        (all, num_abandoned_chunks, changed_index) in arb_btree_map(20)
+            .prop_flat_map(|btree| {
+                let len = btree.len();
+                (Just(btree), 1..=(len - 1) / 10, 0..len)
+            })
+    ) {
+        // The abandoned snapshot only differs from the new one in the value of a single key
+        let mut abandoned = all.clone();
+        let (_, (_, changed_value)) = abandoned.iter_mut().nth(changed_index).unwrap();
+        *changed_value = ValueBlob::from(HashValue::random().to_vec());
+
+        let restore_db = Arc::new(MockSnapshotStore::default());
+        restore_in_chunks(&abandoned, 100, &restore_db, None, 0, Some(num_abandoned_chunks)).unwrap();
+
+        // Like state sync, continue from the last state received by the abandoned restore
+        let last_abandoned_index = num_abandoned_chunks * 10 - 1;
+        let result = restore_in_chunks(&all, 200, &restore_db, Some(100), last_abandoned_index, None);
+        if changed_index < last_abandoned_index {
+            // The tree restored at the abandoned version doesn't match the new snapshot
+            prop_assert!(result.is_err());
+        } else {
+            assert_success(&restore_db, result.unwrap(), &all, 200);
+
+            // No node of the abandoned restore is left behind
+            let tree = JellyfishMerkleTree::new(restore_db.as_ref());
+            prop_assert_eq!(
+                restore_db.tree_store.num_nodes(),
+                tree.get_all_nodes_referenced(200).unwrap().len()
+            );
+        }
+    }
+
     #[test]
     fn test_overwrite(
         btree in arb_btree_map(1),
@@ -303,3 +357,67 @@ fn restore_without_interruption<V>(
 
     assert_success(target_db, expected_root_hash, btree, target_version);
 }
+
+/// Restores the states of `btree` from `first_index` on at `target_version` in chunks of 10
+/// states, continuing the restore abandoned at `previous_version` (if any). The restore is
+/// abandoned after `max_num_chunks` chunks (if any), otherwise it is finished. Returns the expected
+/// root hash.
+fn restore_in_chunks<V>(
+    btree: &BTreeMap<HashValue, (V, V)>,
+    target_version: Version,
+    target_db: &Arc<MockSnapshotStore<V, V>>,
+    previous_version: Option<Version>,
+    first_index: usize,
+    max_num_chunks: Option<usize>,
+) -> Result<HashValue>
+where
+    V: TestKey + TestValue,
+{
+    let (db, source_version) = init_mock_store(
+        &btree
+            .iter()
+            .map(|(_, (k, v))| (k.clone(), v.clone()))
+            .collect(),
+    );
+    let tree = JellyfishMerkleTree::new(&db);
+    let expected_root_hash = tree.get_root_hash(source_version)?;
+
+    let mut restore = match previous_version {
+        Some(previous_version) => StateSnapshotRestore::new_resuming(
+            target_db,
+            target_db,
+            target_version,
+            expected_root_hash,
+            true, /* async_commit */
+            StateSnapshotRestoreMode::Default,
+            previous_version,
+        )?,
+        None => StateSnapshotRestore::new(
+            target_db,
+            target_db,
+            target_version,
+            expected_root_hash,
+            true, /* async_commit */
+            StateSnapshotRestoreMode::Default,
+        )?,
+    };
+    let states: Vec<_> = btree.iter().skip(first_index).collect();
+    for (i, chunk) in states.chunks(10).enumerate() {
+        if max_num_chunks == Some(i) {
+            restore.wait_for_async_commit()?;
+            return Ok(expected_root_hash);
+        }
+        let (last_hashed_key, _) = chunk.last().unwrap();
+        let proof = tree.get_range_proof(**last_hashed_key, source_version)?;
+        restore.add_chunk(
+            chunk
+                .iter()
+                .map(|(_, (k, v))| (k.clone(), v.clone()))
+                .collect(),
+            proof,
+        )?;
+    }
+    Box::new(restore).finish()?;
+
+    Ok(expected_root_hash)
+}
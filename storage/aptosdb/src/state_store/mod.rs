@@ -10,6 +10,7 @@ use crate::{
     pruner::{StateKvPrunerManager, StateMerklePrunerManager},
     schema::{
         db_metadata::{DbMetadataKey, DbMetadataSchema, DbMetadataValue},
+        jellyfish_merkle_node::JellyfishMerkleNodeSchema,
         stale_node_index::StaleNodeIndexSchema,
         stale_node_index_cross_epoch::StaleNodeIndexCrossEpochSchema,
         stale_state_value_index::StaleStateValueIndexSchema,
@@ -41,10 +42,14 @@ use aptos_db_indexer_schemas::{
     schema::indexer_metadata::InternalIndexerMetadataSchema,
 };
 use aptos_infallible::Mutex;
-use aptos_jellyfish_merkle::iterator::JellyfishMerkleIterator;
+use aptos_jellyfish_merkle::{iterator::JellyfishMerkleIterator, node_type::NodeKey};
 use aptos_logger::info;
 use aptos_metrics_core::TimerHelper;
-use aptos_schemadb::batch::{NativeBatch, SchemaBatch, WriteBatch};
+use aptos_schemadb::{
+    batch::{NativeBatch, SchemaBatch, WriteBatch},
+    schema::Schema,
+    DB,
+};
 use aptos_scratchpad::SparseMerkleTree;
 use aptos_storage_interface::{
     db_ensure as ensure, db_other_bail as bail,
@@ -1063,6 +1068,95 @@ impl StateStore {
         )?))
     }
 
+    pub fn get_snapshot_receiver_resuming(
+        self: &Arc<Self>,
+        version: Version,
+        expected_root_hash: HashValue,
+        previous_version: Version,
+    ) -> Result<Box<dyn StateSnapshotReceiver<StateKey, StateValue>>> {
+        Ok(Box::new(StateSnapshotRestore::new_resuming(
+            &self.state_merkle_db,
+            self,
+            version,
+            expected_root_hash,
+            false, /* async_commit */
+            StateSnapshotRestoreMode::Default,
+            previous_version,
+        )?))
+    }
+
+    /// Deletes the tree nodes, state values and restore progress written by unfinished state
+    /// snapshot restores at the versions in [`start_version`, `end_version`). There must be no
+    /// other state at these versions.
+    pub fn discard_unfinished_snapshots(
+        &self,
+        start_version: Version,
+        end_version: Version,
+    ) -> Result<()> {
+        let _timer = OTHER_TIMERS_SECONDS.timer_with(&["discard_unfinished_snapshots"]);
+        let versions = start_version..end_version;
+
+        let state_merkle_dbs = std::iter::once(self.state_merkle_db.metadata_db()).chain(
+            (0..self.state_merkle_db.hack_num_real_shards())
+                .map(|shard_id| self.state_merkle_db.db_shard(shard_id)),
+        );
+        for db in state_merkle_dbs {
+            let mut iter = db.iter::<JellyfishMerkleNodeSchema>()?;
+            iter.seek(&NodeKey::new_empty_path(start_version))?;
+            let node_keys = iter
+                .map_ok(|(node_key, _node)| node_key)
+                .take_while(|node_key| {
+                    node_key
+                        .as_ref()
+                        .map_or(true, |node_key| versions.contains(&node_key.version()))
+                });
+            Self::delete_in_batches::<JellyfishMerkleNodeSchema>(db, node_keys)?;
+        }
+
+        for shard_id in 0..self.state_kv_db.hack_num_real_shards() {
+            let db = self.state_kv_db.db_shard(shard_id);
+            if self.state_kv_db.enabled_sharding() {
+                let mut iter = db.iter::<StateValueByKeyHashSchema>()?;
+                iter.seek_to_first();
+                let keys = iter
+                    .map_ok(|(key, _value)| key)
+                    .filter_ok(|(_key_hash, version)| versions.contains(version));
+                Self::delete_in_batches::<StateValueByKeyHashSchema>(db, keys)?;
+            } else {
+                let mut iter = db.iter::<StateValueSchema>()?;
+                iter.seek_to_first();
+                let keys = iter
+                    .map_ok(|(key, _value)| key)
+                    .filter_ok(|(_state_key, version)| versions.contains(version));
+                Self::delete_in_batches::<StateValueSchema>(db, keys)?;
+            }
+        }
+
+        let db = self.state_kv_db.metadata_db();
+        let mut iter = db.iter::<DbMetadataSchema>()?;
+        iter.seek_to_first();
+        let keys = iter.map_ok(|(key, _value)| key).filter_ok(|key| {
+            matches!(key, DbMetadataKey::StateSnapshotKvRestoreProgress(version) if versions.contains(version))
+        });
+        Self::delete_in_batches::<DbMetadataSchema>(db, keys)
+    }
+
+    fn delete_in_batches<S: Schema>(
+        db: &DB,
+        keys: impl Iterator<Item = Result<S::Key>>,
+    ) -> Result<()> {
+        const MAX_DELETES_PER_BATCH: usize = 10_000;
+
+        for chunk in &keys.chunks(MAX_DELETES_PER_BATCH) {
+            let mut batch = SchemaBatch::new();
+            for key in chunk {
+                batch.delete::<S>(&key?)?;
+            }
+            db.write_schemas(batch)?;
+        }
+        Ok(())
+    }
+
     #[cfg(test)]
     pub fn get_all_jmt_nodes_referenced(
         &self,
@@ -1214,6 +1308,13 @@ impl StateValueWriter<StateKey, StateValue> for StateStore {
         Ok(())
     }
 
+    fn get_value(&self, key: &StateKey, version: Version) -> Result<Option<StateValue>> {
+        Ok(self
+            .state_kv_db
+            .get_state_value_with_version_by_version(key, version)?
+            .map(|(_version, value)| value))
+    }
+
     fn get_progress(&self, version: Version) -> Result<Option<StateSnapshotProgress>> {
         let main_db_progress = self
             .state_kv_db
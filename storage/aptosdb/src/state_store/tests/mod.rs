@@ -422,6 +422,70 @@ proptest! {
         );
     }
 
+    #[test]
+    fn test_discard_unfinished_snapshots(
+        (input, batch1_size) in hash_map(any::<StateKey>(), any::<StateValue>(), 2..1000)
+            .prop_flat_map(|input| {
+                let len = input.len();
+                (Just(input), 1..len)
+            })
+    ) {
+        let tmp_dir1 = TempPath::new();
+        let db1 = AptosDB::new_for_test(&tmp_dir1);
+        let store1 = &db1.state_store;
+        init_store(store1, input.clone().into_iter());
+        let version1 = (input.len() - 1) as Version;
+        let root_hash1 = store1.get_root_hash(version1).unwrap();
+
+        // Delete the leftmost key at the next version, so that the restore abandoned at the first
+        // version can't be continued.
+        let leftmost_key = input.keys().min_by_key(|key| key.hash()).unwrap().clone();
+        let version2 = version1 + 1;
+        update_store(store1, std::iter::once((leftmost_key.clone(), None)), version2);
+        let root_hash2 = store1.get_root_hash(version2).unwrap();
+
+        let tmp_dir2 = TempPath::new();
+        let db2 = AptosDB::new_for_test(&tmp_dir2);
+        let store2 = &db2.state_store;
+
+        let mut restore = store2.get_snapshot_receiver(version1, root_hash1).unwrap();
+        let chunk = store1.get_value_chunk_with_proof(version1, 0, batch1_size).unwrap();
+        restore.add_chunk(chunk.raw_values, chunk.proof).unwrap();
+
+        let mut restore = store2
+            .get_snapshot_receiver_resuming(version2, root_hash2, version1)
+            .unwrap();
+        let chunk = store1
+            .get_value_chunk_with_proof(version2, batch1_size - 1, input.len() - batch1_size)
+            .unwrap();
+        prop_assert!(restore.add_chunk(chunk.raw_values, chunk.proof).is_err());
+
+        // Nothing of either restore remains once they are discarded.
+        store2.discard_unfinished_snapshots(0, version2 + 1).unwrap();
+        prop_assert!(store2.get_all_jmt_nodes().unwrap().is_empty());
+        prop_assert_eq!(
+            store2.get_state_value_by_version(&leftmost_key, version2).unwrap(),
+            None
+        );
+
+        let mut restore = store2.get_snapshot_receiver(version2, root_hash2).unwrap();
+        let chunk = store1.get_value_chunk_with_proof(version2, 0, input.len() - 1).unwrap();
+        restore.add_chunk(chunk.raw_values, chunk.proof).unwrap();
+        restore.finish_box().unwrap();
+
+        prop_assert_eq!(store2.get_root_hash(version2).unwrap(), root_hash2);
+        prop_assert_eq!(store2.get_value_count(version2).unwrap(), input.len() - 1);
+        prop_assert_eq!(
+            store2.get_state_value_by_version(&leftmost_key, version2).unwrap(),
+            None
+        );
+        let mut all_nodes = store2.get_all_jmt_nodes().unwrap();
+        let mut referenced_nodes = store2.get_all_jmt_nodes_referenced(version2).unwrap();
+        all_nodes.sort_unstable();
+        referenced_nodes.sort_unstable();
+        prop_assert_eq!(all_nodes, referenced_nodes);
+    }
+
     #[test]
     fn test_get_rightmost_leaf_with_sharding(
         (input, batch1_size) in hash_map(any::<StateKey>(), any::<StateValue>(), 2..1000)
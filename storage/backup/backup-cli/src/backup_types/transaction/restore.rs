@@ -87,7 +87,7 @@ pub struct TransactionRestoreController {
     inner: TransactionRestoreBatchController,
 }
 
-struct LoadedChunk {
+pub(crate) struct LoadedChunk {
     pub manifest: TransactionChunk,
     pub txns: Vec<Transaction>,
     pub persisted_aux_info: Vec<PersistedAuxiliaryInfo>,
@@ -98,7 +98,7 @@ struct LoadedChunk {
 }
 
 impl LoadedChunk {
-    async fn load(
+    pub(crate) async fn load(
         manifest: TransactionChunk,
         storage: &Arc<dyn BackupStorage>,
         epoch_history: Option<&Arc<EpochHistory>>,
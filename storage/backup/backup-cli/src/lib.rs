@@ -8,5 +8,6 @@ pub mod backup_types;
 pub mod coordinators;
 pub mod metadata;
 pub mod metrics;
+pub mod state_sync_source;
 pub mod storage;
 pub mod utils;
@@ -0,0 +1,335 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Serves the data required to bootstrap state sync out of a backup, see
+//! `aptos_state_sync_driver::backup_source`.
+
+use crate::{
+    backup_types::{
+        epoch_ending::manifest::EpochEndingBackup,
+        state_snapshot::manifest::StateSnapshotBackup,
+        transaction::{manifest::TransactionBackup, restore::LoadedChunk},
+    },
+    metadata::{
+        cache::{sync_and_load, MetadataCacheOpt},
+        view::MetadataView,
+    },
+    storage::{
+        command_adapter::{config::CommandAdapterConfig, CommandAdapter},
+        local_fs::LocalFs,
+        BackupStorage, FileHandleRef,
+    },
+    utils::{read_record_bytes::ReadRecordBytes, storage_ext::BackupStorageExt},
+};
+use anyhow::{anyhow, bail, ensure, Result};
+use aptos_config::config::BootstrapBackupConfig;
+use aptos_crypto::hash::CryptoHash;
+use aptos_infallible::Mutex;
+use aptos_logger::prelude::*;
+use aptos_state_sync_driver::backup_source::BackupDataSource;
+use aptos_types::{
+    ledger_info::LedgerInfoWithSignatures,
+    proof::{
+        SparseMerkleRangeProof, TransactionAccumulatorProof, TransactionAccumulatorRangeProof,
+        TransactionInfoListWithProof, TransactionInfoWithProof,
+    },
+    state_store::{
+        state_key::StateKey,
+        state_value::{StateValue, StateValueChunkWithProof},
+    },
+    transaction::{
+        TransactionAuxiliaryData, TransactionOutput, TransactionOutputListWithAuxiliaryInfos,
+        TransactionOutputListWithProof, TransactionOutputListWithProofV2, Version,
+    },
+};
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use std::{collections::HashMap, path::Path, sync::Arc};
+
+/// A `BackupDataSource` reading from a `BackupStorage`. Nothing read here is verified beyond
+/// what's needed to interpret it, the state sync bootstrapper verifies everything against its
+/// waypoint.
+pub struct BackupStorageDataSource {
+    storage: Arc<dyn BackupStorage>,
+    metadata_view: MetadataView,
+    state_snapshot_manifests: Mutex<HashMap<Version, Arc<StateSnapshotBackup>>>,
+}
+
+impl BackupStorageDataSource {
+    pub fn new(storage: Arc<dyn BackupStorage>, metadata_view: MetadataView) -> Self {
+        Self {
+            storage,
+            metadata_view,
+            state_snapshot_manifests: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Opens the backup storage in `config` and loads its metadata, caching it under
+    /// `default_metadata_cache_dir` unless the config says otherwise.
+    pub async fn open(
+        config: &BootstrapBackupConfig,
+        default_metadata_cache_dir: &Path,
+    ) -> Result<Self> {
+        let storage: Arc<dyn BackupStorage> =
+            match (&config.local_fs_dir, &config.command_adapter_config) {
+                (Some(dir), None) => Arc::new(LocalFs::new(dir.clone())),
+                (None, Some(command_adapter_config)) => Arc::new(CommandAdapter::new(
+                    CommandAdapterConfig::load_from_file(command_adapter_config).await?,
+                )),
+                _ => bail!(
+                    "Exactly one of local_fs_dir and command_adapter_config is expected for the \
+                    bootstrap backup."
+                ),
+            };
+        let metadata_cache_dir = config
+            .metadata_cache_dir
+            .clone()
+            .unwrap_or_else(|| default_metadata_cache_dir.to_path_buf());
+        let metadata_view = sync_and_load(
+            &MetadataCacheOpt::new(Some(metadata_cache_dir)),
+            storage.clone(),
+            config.concurrent_downloads,
+        )
+        .await?;
+        info!(
+            num_state_snapshots = metadata_view.all_state_snapshots().len(),
+            "Backup for bootstrapping state sync loaded."
+        );
+
+        Ok(Self::new(storage, metadata_view))
+    }
+
+    async fn load_state_snapshot_manifest(
+        &self,
+        version: Version,
+    ) -> Result<Arc<StateSnapshotBackup>> {
+        if let Some(manifest) = self.state_snapshot_manifests.lock().get(&version) {
+            return Ok(manifest.clone());
+        }
+
+        let meta = self.metadata_view.expect_state_snapshot(version)?;
+        let manifest: Arc<StateSnapshotBackup> =
+            Arc::new(self.storage.load_json_file(&meta.manifest).await?);
+        self.state_snapshot_manifests
+            .lock()
+            .insert(version, manifest.clone());
+        Ok(manifest)
+    }
+
+    async fn read_records<T: DeserializeOwned>(
+        &self,
+        file_handle: &FileHandleRef,
+    ) -> Result<Vec<T>> {
+        let mut file = self.storage.open_for_read(file_handle).await?;
+        let mut records = vec![];
+
+        while let Some(record_bytes) = file.read_record_bytes().await? {
+            records.push(bcs::from_bytes(&record_bytes)?);
+        }
+
+        Ok(records)
+    }
+}
+
+#[async_trait]
+impl BackupDataSource for BackupStorageDataSource {
+    async fn get_epoch_ending_ledger_infos(
+        &self,
+        start_epoch: u64,
+    ) -> Result<Vec<LedgerInfoWithSignatures>> {
+        let mut ledger_infos = vec![];
+        for backup in self
+            .metadata_view
+            .select_epoch_ending_backups(Version::MAX)?
+        {
+            if backup.last_epoch < start_epoch {
+                continue;
+            }
+            let manifest: EpochEndingBackup = self.storage.load_json_file(&backup.manifest).await?;
+            manifest.verify()?;
+            for chunk in manifest.chunks {
+                if chunk.last_epoch < start_epoch {
+                    continue;
+                }
+                let chunk_ledger_infos: Vec<LedgerInfoWithSignatures> =
+                    self.read_records(&chunk.ledger_infos).await?;
+                ledger_infos.extend(
+                    chunk_ledger_infos
+                        .into_iter()
+                        .filter(|ledger_info| ledger_info.ledger_info().epoch() >= start_epoch),
+                );
+            }
+        }
+
+        Ok(ledger_infos)
+    }
+
+    async fn get_state_snapshot_versions(&self) -> Result<Vec<Version>> {
+        Ok(self
+            .metadata_view
+            .all_state_snapshots()
+            .iter()
+            .map(|snapshot| snapshot.version)
+            .collect())
+    }
+
+    async fn get_transaction_output(
+        &self,
+        version: Version,
+    ) -> Result<TransactionOutputListWithProofV2> {
+        // The state snapshot proves the transaction info against the epoch ending ledger info.
+        let snapshot = self.load_state_snapshot_manifest(version).await?;
+        let (txn_info_with_proof, _): (TransactionInfoWithProof, LedgerInfoWithSignatures) =
+            self.storage.load_bcs_file(&snapshot.proof).await?;
+
+        // The output itself is only found in the transaction backups.
+        let backup = self
+            .metadata_view
+            .select_transaction_backups(version, version)?
+            .into_iter()
+            .find(|backup| backup.first_version <= version && version <= backup.last_version)
+            .ok_or_else(|| anyhow!("Transaction backup not found at version {}", version))?;
+        let manifest: TransactionBackup = self.storage.load_json_file(&backup.manifest).await?;
+        let chunk = manifest
+            .chunks
+            .into_iter()
+            .find(|chunk| chunk.first_version <= version && version <= chunk.last_version)
+            .ok_or_else(|| anyhow!("Transaction chunk not found at version {}", version))?;
+        let first_version = chunk.first_version;
+        let mut loaded_chunk = LoadedChunk::load(chunk, &self.storage, None).await?;
+
+        let idx = (version - first_version) as usize;
+        let txn = loaded_chunk.txns.swap_remove(idx);
+        let persisted_aux_info = loaded_chunk.persisted_aux_info.swap_remove(idx);
+        let txn_info = loaded_chunk.txn_infos.swap_remove(idx);
+        let events = loaded_chunk.event_vecs.swap_remove(idx);
+        let write_set = loaded_chunk.write_sets.swap_remove(idx);
+        let txn_output = TransactionOutput::new(
+            write_set,
+            events,
+            txn_info.gas_used(),
+            txn_info.status().clone().into(),
+            TransactionAuxiliaryData::default(),
+        );
+        let proof = TransactionInfoListWithProof::new(
+            single_leaf_range_proof(
+                txn_info_with_proof.ledger_info_to_transaction_info_proof(),
+                version,
+            ),
+            vec![txn_info],
+        );
+
+        Ok(TransactionOutputListWithProofV2::new(
+            TransactionOutputListWithAuxiliaryInfos::new(
+                TransactionOutputListWithProof::new(vec![(txn, txn_output)], Some(version), proof),
+                vec![persisted_aux_info],
+            ),
+        ))
+    }
+
+    async fn get_state_value_chunk(
+        &self,
+        version: Version,
+        start_index: u64,
+    ) -> Result<StateValueChunkWithProof> {
+        let manifest = self.load_state_snapshot_manifest(version).await?;
+        let chunk = manifest
+            .chunks
+            .iter()
+            .find(|chunk| chunk.last_idx as u64 >= start_index)
+            .ok_or_else(|| {
+                anyhow!(
+                    "State index {} is beyond the snapshot at version {}",
+                    start_index,
+                    version
+                )
+            })?;
+        ensure!(
+            chunk.first_idx as u64 <= start_index,
+            "State snapshot chunks are not continuous, expecting index {}, got {}.",
+            start_index,
+            chunk.first_idx,
+        );
+
+        let mut raw_values: Vec<(StateKey, StateValue)> = self.read_records(&chunk.blobs).await?;
+        ensure!(
+            raw_values.len() == chunk.last_idx - chunk.first_idx + 1,
+            "Number of items in chunk doesn't match that in manifest. first_idx: {}, last_idx: {}, items in chunk: {}",
+            chunk.first_idx,
+            chunk.last_idx,
+            raw_values.len(),
+        );
+        let proof: SparseMerkleRangeProof = self.storage.load_bcs_file(&chunk.proof).await?;
+
+        // The range proof only covers what's to the right of the last key, so dropping the
+        // values that were already synced keeps it valid.
+        let raw_values = raw_values.split_off((start_index - chunk.first_idx as u64) as usize);
+        let first_key = raw_values
+            .first()
+            .map(|(state_key, _)| state_key.hash())
+            .ok_or_else(|| anyhow!("State snapshot chunk is empty"))?;
+
+        Ok(StateValueChunkWithProof {
+            first_index: start_index,
+            last_index: chunk.last_idx as u64,
+            first_key,
+            last_key: chunk.last_key,
+            raw_values,
+            proof,
+            root_hash: manifest.root_hash,
+        })
+    }
+}
+
+/// Converts the proof of a single leaf into a range proof of the range containing only that leaf.
+fn single_leaf_range_proof(
+    proof: &TransactionAccumulatorProof,
+    leaf_index: u64,
+) -> TransactionAccumulatorRangeProof {
+    let mut left_siblings = vec![];
+    let mut right_siblings = vec![];
+    // Siblings in the proof are ordered from the leaf to the root.
+    for (level, sibling) in proof.siblings().iter().enumerate() {
+        if (leaf_index >> level) & 1 == 1 {
+            left_siblings.push(*sibling);
+        } else {
+            right_siblings.push(*sibling);
+        }
+    }
+    // While in the range proof, siblings near the root come first.
+    left_siblings.reverse();
+    right_siblings.reverse();
+    TransactionAccumulatorRangeProof::new(left_siblings, right_siblings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::single_leaf_range_proof;
+    use aptos_crypto::{
+        hash::{CryptoHash, TransactionAccumulatorHasher},
+        HashValue,
+    };
+    use aptos_types::proof::{MerkleTreeInternalNode, TransactionAccumulatorProof};
+
+    fn parent(left: HashValue, right: HashValue) -> HashValue {
+        MerkleTreeInternalNode::<TransactionAccumulatorHasher>::new(left, right).hash()
+    }
+
+    #[test]
+    fn test_single_leaf_range_proof() {
+        let leaves: Vec<_> = (0..4).map(|_| HashValue::random()).collect();
+        let internal_nodes = [parent(leaves[0], leaves[1]), parent(leaves[2], leaves[3])];
+        let root_hash = parent(internal_nodes[0], internal_nodes[1]);
+
+        for (leaf_index, leaf) in leaves.iter().enumerate() {
+            let proof = TransactionAccumulatorProof::new(vec![
+                leaves[leaf_index ^ 1],
+                internal_nodes[(leaf_index / 2) ^ 1],
+            ]);
+            proof.verify(root_hash, *leaf, leaf_index as u64).unwrap();
+            single_leaf_range_proof(&proof, leaf_index as u64)
+                .verify(root_hash, Some(leaf_index as u64), &[*leaf])
+                .unwrap();
+        }
+    }
+}
@@ -27,7 +27,7 @@ use aptos_db::{
 use aptos_db_indexer_schemas::metadata::StateSnapshotProgress;
 use aptos_indexer_grpc_table_info::internal_indexer_db_service::InternalIndexerDBService;
 use aptos_infallible::duration_since_epoch;
-use aptos_jellyfish_merkle::{NodeBatch, TreeWriter};
+use aptos_jellyfish_merkle::{node_type::NodeKey, NodeBatch, TreeWriter};
 use aptos_logger::info;
 use aptos_storage_interface::{AptosDbError, Result};
 use aptos_types::{
@@ -180,6 +180,10 @@ impl TreeWriter<StateKey> for MockStore {
     fn write_node_batch(&self, _node_batch: &NodeBatch<StateKey>) -> Result<()> {
         Ok(())
     }
+
+    fn delete_node_batch(&self, _node_keys: &[NodeKey]) -> Result<()> {
+        Ok(())
+    }
 }
 
 impl StateValueWriter<StateKey, StateValue> for MockStore {
@@ -199,6 +203,10 @@ impl StateValueWriter<StateKey, StateValue> for MockStore {
     fn get_progress(&self, _version: Version) -> Result<Option<StateSnapshotProgress>> {
         Ok(None)
     }
+
+    fn get_value(&self, _key: &StateKey, _version: Version) -> Result<Option<StateValue>> {
+        Ok(None)
+    }
 }
 
 impl RestoreRunMode {
@@ -140,6 +140,9 @@ pub trait TreeReader<K> {
 pub trait TreeWriter<K>: Send + Sync {
     /// Writes a node batch into storage.
     fn write_node_batch(&self, node_batch: &HashMap<NodeKey, Node<K>>) -> Result<()>;
+
+    /// Deletes the nodes from storage.
+    fn delete_node_batch(&self, node_keys: &[NodeKey]) -> Result<()>;
 }
 
 pub trait Key: Clone + Serialize + DeserializeOwned + Send + Sync + 'static {
@@ -66,6 +66,14 @@ where
         }
         Ok(())
     }
+
+    fn delete_node_batch(&self, node_keys: &[NodeKey]) -> Result<()> {
+        let mut locked = self.data.write();
+        for node_key in node_keys {
+            locked.0.remove(node_key);
+        }
+        Ok(())
+    }
 }
 
 impl<K> MockTreeStore<K>
@@ -181,6 +181,11 @@ pub struct JellyfishMerkleRestore<K> {
 
     async_commit: bool,
     async_commit_result: Option<Receiver<Result<()>>>,
+
+    /// The nodes of an abandoned restore at an older version that this restore continues (see
+    /// `new_resuming`). They are deleted when the first chunk is written, as by then they have
+    /// been copied to this version.
+    abandoned_nodes: Vec<NodeKey>,
 }
 
 impl<K> JellyfishMerkleRestore<K>
@@ -232,9 +237,100 @@ where
             finished,
             async_commit,
             async_commit_result: None,
+            abandoned_nodes: vec![],
         })
     }
 
+    /// Same as `new`, but if nothing has been restored at `version` yet, continues the restore
+    /// abandoned at `previous_version` (e.g., because a newer snapshot is restored instead). The
+    /// partial nodes of the abandoned restore are recovered as if they had been restored at
+    /// `version`, so the first chunk must start right after the rightmost leaf persisted at
+    /// `previous_version`, and it only passes verification if the abandoned restore matches the
+    /// tree at `version`. Once it does, the nodes at `previous_version` that the tree at `version`
+    /// no longer references are deleted.
+    pub fn new_resuming<D: 'static + TreeReader<K> + TreeWriter<K>>(
+        store: Arc<D>,
+        version: Version,
+        expected_root_hash: HashValue,
+        async_commit: bool,
+        previous_version: Version,
+    ) -> Result<Self> {
+        ensure!(
+            previous_version < version,
+            "Only a restore at an older version can be continued. Previous version: {}, version: {}",
+            previous_version,
+            version,
+        );
+        let mut restore = Self::new(
+            Arc::clone(&store),
+            version,
+            expected_root_hash,
+            async_commit,
+        )?;
+        if restore.finished || restore.previous_leaf.is_some() {
+            info!(
+                "Restore at version {} already started, not resuming.",
+                version
+            );
+            return Ok(restore);
+        }
+
+        // A finished restore has no partial nodes left to continue from. The first chunk will
+        // fail verification in that case.
+        if store
+            .get_node_option(&NodeKey::new_empty_path(previous_version), "restore")?
+            .is_some()
+        {
+            return Ok(restore);
+        }
+        if let Some((rightmost_leaf_node_key, leaf_node)) =
+            store.get_rightmost_leaf(previous_version)?
+        {
+            let mut partial_nodes = Self::recover_partial_nodes(
+                store.as_ref(),
+                previous_version,
+                rightmost_leaf_node_key.clone(),
+            )?;
+
+            // The children of partial nodes are copied to `version`, as the partial nodes will
+            // refer to them at that version once frozen. Their descendants are left as they are.
+            for partial_node in partial_nodes.iter_mut() {
+                let previous_node_key = std::mem::replace(
+                    &mut partial_node.node_key,
+                    NodeKey::new(version, partial_node.node_key.nibble_path().clone()),
+                );
+                for (index, child_info) in partial_node.children.iter().enumerate() {
+                    let nibble = (index as u8).into();
+                    let previous_child_node_key =
+                        previous_node_key.gen_child_node_key(previous_version, nibble);
+                    match child_info {
+                        // Partial nodes have not been persisted.
+                        None | Some(ChildInfo::Internal { hash: None, .. }) => continue,
+                        // The rightmost leaf is frozen again once its position is known.
+                        Some(ChildInfo::Leaf(_))
+                            if previous_child_node_key == rightmost_leaf_node_key => {},
+                        Some(_) => {
+                            restore.frozen_nodes.insert(
+                                partial_node.node_key.gen_child_node_key(version, nibble),
+                                store.get_node_with_tag(&previous_child_node_key, "restore")?,
+                            );
+                        },
+                    }
+                    restore.abandoned_nodes.push(previous_child_node_key);
+                }
+            }
+
+            info!(
+                "Resuming the restore abandoned at version {} for version {}.",
+                previous_version, version
+            );
+            restore.partial_nodes = partial_nodes;
+            restore.previous_leaf = Some(leaf_node);
+        }
+
+        Ok(restore)
+    }
+
     pub fn new_overwrite<D: 'static + TreeWriter<K>>(
         store: Arc<D>,
         version: Version,
@@ -251,6 +347,7 @@ where
             finished: false,
             async_commit: false,
             async_commit_result: None,
+            abandoned_nodes: vec![],
         })
     }
 
@@ -399,20 +496,35 @@ where
 
             let mut frozen_nodes = HashMap::new();
             std::mem::swap(&mut frozen_nodes, &mut self.frozen_nodes);
+            let abandoned_nodes = std::mem::take(&mut self.abandoned_nodes);
             let store = self.store.clone();
 
             IO_POOL.spawn(move || {
-                let res = store.write_node_batch(&frozen_nodes);
+                let res = Self::write_frozen_nodes(store.as_ref(), &frozen_nodes, &abandoned_nodes);
                 tx.send(res).unwrap();
             });
         } else {
-            self.store.write_node_batch(&self.frozen_nodes)?;
+            let abandoned_nodes = std::mem::take(&mut self.abandoned_nodes);
+            Self::write_frozen_nodes(self.store.as_ref(), &self.frozen_nodes, &abandoned_nodes)?;
             self.frozen_nodes.clear();
         }
 
         Ok(())
     }
 
+    /// Writes the frozen nodes, and then deletes the abandoned nodes they replace (if any).
+    fn write_frozen_nodes(
+        store: &dyn TreeWriter<K>,
+        frozen_nodes: &HashMap<NodeKey, Node<K>>,
+        abandoned_nodes: &[NodeKey],
+    ) -> Result<()> {
+        store.write_node_batch(frozen_nodes)?;
+        if !abandoned_nodes.is_empty() {
+            store.delete_node_batch(abandoned_nodes)?;
+        }
+        Ok(())
+    }
+
     /// Restores one state.
     fn add_one(&mut self, new_key: &K, new_value_hash: HashValue) {
         let new_hashed_key = new_key.hash();
@@ -557,8 +669,9 @@ where
     /// Freezes the previously added leaf node. It should always be the rightmost leaf node on the
     /// lowest level, inserted in the previous `add_one` call.
     fn freeze_previous_leaf(&mut self) {
-        // If this is the very first key, there is no previous leaf to freeze.
-        if self.num_keys_received == 0 {
+        // If this is the very first key, there is no previous leaf to freeze. After a restart, the
+        // previous leaf has been frozen already, unless it was restored at an abandoned version.
+        if self.num_keys_received == 0 && self.abandoned_nodes.is_empty() {
             return;
         }
 
@@ -750,6 +863,10 @@ where
     /// otherwise we can not freeze the rightmost leaf and its ancestors.
     pub fn finish_impl(mut self) -> Result<()> {
         self.wait_for_async_commit()?;
+        ensure!(
+            self.abandoned_nodes.is_empty(),
+            "The abandoned restore being continued has not been verified by any chunk.",
+        );
         // Deal with the special case when the entire tree has a single leaf or null node.
         if self.partial_nodes.len() == 1 {
             let mut num_children = 0;
@@ -550,6 +550,30 @@ pub trait DbWriter: Send + Sync {
         unimplemented!()
     }
 
+    /// Same as `get_state_snapshot_receiver`, but the receiver continues the unfinished restore
+    /// of an older state snapshot at `previous_version` (e.g., abandoned for a newer target),
+    /// unless the restore at `version` has already started. The first chunk must start with the
+    /// last state received by the previous receiver, and fails if anything restored at
+    /// `previous_version` differs at `version`.
+    fn get_state_snapshot_receiver_resuming(
+        &self,
+        version: Version,
+        expected_root_hash: HashValue,
+        previous_version: Version,
+    ) -> Result<Box<dyn StateSnapshotReceiver<StateKey, StateValue>>> {
+        unimplemented!()
+    }
+
+    /// Deletes everything restored by unfinished state snapshot receivers at the versions in
+    /// [`start_version`, `end_version`), e.g., when a receiver failed to continue an older one.
+    fn discard_unfinished_state_snapshots(
+        &self,
+        start_version: Version,
+        end_version: Version,
+    ) -> Result<()> {
+        unimplemented!()
+    }
+
     /// Finalizes a state snapshot that has already been restored to the database through
     /// a state snapshot receiver. This is required to bootstrap the transaction accumulator,
     /// populate transaction information, save the epoch ending ledger infos and delete genesis.
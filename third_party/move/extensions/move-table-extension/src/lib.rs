@@ -19,7 +19,7 @@ use move_core_types::{
     vm_status::StatusCode,
 };
 use move_vm_runtime::{
-    native_functions,
+    execution_trace, native_functions,
     native_functions::{LoaderContext, NativeContext, NativeFunction, NativeFunctionTable},
 };
 use move_vm_types::{
@@ -252,10 +252,15 @@ impl Table {
     ) -> PartialVMResult<(&mut GlobalValue, Option<Option<NumBytes>>)> {
         Ok(match self.content.entry(key) {
             Entry::Vacant(entry) => {
-                let (gv, loaded) = match table_context
+                let val_bytes = table_context
                     .resolver
-                    .resolve_table_entry_bytes_with_layout(&self.handle, entry.key(), None)?
-                {
+                    .resolve_table_entry_bytes_with_layout(&self.handle, entry.key(), None)?;
+                execution_trace::record_table_read(
+                    self.handle.0,
+                    entry.key(),
+                    val_bytes.as_ref().map(|val_bytes| val_bytes.len() as u64),
+                );
+                let (gv, loaded) = match val_bytes {
                     Some(val_bytes) => {
                         let val =
                             deserialize(function_value_extension, &val_bytes, &self.value_layout)?;
@@ -15,8 +15,11 @@ use crate::{
     },
     CompiledModule,
 };
-use move_core_types::{ability::AbilitySet, vm_status::StatusCode};
-use std::collections::BTreeSet;
+use move_core_types::{
+    ability::AbilitySet, account_address::AccountAddress, identifier::Identifier,
+    language_storage::ModuleId, vm_status::StatusCode,
+};
+use std::{collections::BTreeSet, fmt};
 
 /// The result of a linking and layout compatibility check. Here is what the different combinations. NOTE that if `check_struct_layout` is false, type safety over a series of upgrades cannot be guaranteed.
 /// mean:
@@ -52,6 +55,98 @@ impl Default for Compatibility {
     }
 }
 
+/// An incompatible change of a module, found by `Compatibility::incompatibilities`.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum Incompatibility {
+    ModuleAddressChanged {
+        new_address: AccountAddress,
+    },
+    ModuleNameChanged {
+        new_name: Identifier,
+    },
+    StructRemoved {
+        name: Identifier,
+    },
+    StructAbilitiesRemoved {
+        name: Identifier,
+        removed: AbilitySet,
+    },
+    StructTypeParametersChanged {
+        name: Identifier,
+    },
+    StructLayoutChanged {
+        name: Identifier,
+    },
+    FunctionRemoved {
+        name: Identifier,
+    },
+    FunctionChanged {
+        name: Identifier,
+        change: FunctionChange,
+    },
+    FriendsRemoved {
+        friends: Vec<ModuleId>,
+    },
+}
+
+/// An incompatible change of a function which is kept.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum FunctionChange {
+    Visibility,
+    EntryRemoved,
+    AttributesRemoved,
+    ParameterTypes,
+    ReturnType,
+    TypeParameters,
+}
+
+impl fmt::Display for Incompatibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ModuleAddressChanged { new_address } => {
+                write!(f, "module address changed to `{}`", new_address)
+            },
+            Self::ModuleNameChanged { new_name } => {
+                write!(f, "module name changed to `{}`", new_name)
+            },
+            Self::StructRemoved { name } => write!(f, "removed struct `{}`", name),
+            Self::StructAbilitiesRemoved { name, removed } => {
+                write!(f, "removed abilities `{}` from struct `{}`", removed, name)
+            },
+            Self::StructTypeParametersChanged { name } => {
+                write!(f, "changed type parameters of struct `{}`", name)
+            },
+            Self::StructLayoutChanged { name } => write!(f, "changed layout of struct `{}`", name),
+            Self::FunctionRemoved { name } => write!(f, "removed function `{}`", name),
+            Self::FunctionChanged { name, change } => {
+                write!(f, "{} of function `{}`", change, name)
+            },
+            Self::FriendsRemoved { friends } => write!(
+                f,
+                "removed friend declaration {}",
+                friends
+                    .iter()
+                    .map(|id| format!("`{}`", id))
+                    .collect::<Vec<_>>()
+                    .join(" and ")
+            ),
+        }
+    }
+}
+
+impl fmt::Display for FunctionChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Visibility => "changed visibility",
+            Self::EntryRemoved => "removed `entry` modifier",
+            Self::AttributesRemoved => "removed required attributes",
+            Self::ParameterTypes => "changed parameter types",
+            Self::ReturnType => "changed return type",
+            Self::TypeParameters => "changed type parameters",
+        })
+    }
+}
+
 impl Compatibility {
     pub fn full_check() -> Self {
         Self::default()
@@ -90,23 +185,62 @@ impl Compatibility {
     }
 
     /// Check compatibility for `new_module` relative to old module `old_module`.
-    #[allow(clippy::nonminimal_bool)] // simplification is more unreadable
     pub fn check(
         &self,
         old_module: &CompiledModule,
         new_module: &CompiledModule,
     ) -> PartialVMResult<()> {
+        let errors = self.collect_incompatibilities(old_module, new_module, false);
+        if !errors.is_empty() {
+            Err(
+                PartialVMError::new(StatusCode::BACKWARD_INCOMPATIBLE_MODULE_UPDATE).with_message(
+                    format!(
+                        "Module update failure: new module not compatible with \
+                        existing module in `{}`: {}",
+                        old_module.self_id(),
+                        errors
+                            .iter()
+                            .map(|error| error.to_string())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                ),
+            )
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns all incompatibilities of `new_module` relative to old module `old_module`.
+    pub fn incompatibilities(
+        &self,
+        old_module: &CompiledModule,
+        new_module: &CompiledModule,
+    ) -> Vec<Incompatibility> {
+        self.collect_incompatibilities(old_module, new_module, true)
+    }
+
+    /// Collects the incompatibilities of `new_module` relative to old module `old_module`. Unless
+    /// `all_removed_structs` is set, the structs are no longer checked after the first removed one.
+    #[allow(clippy::nonminimal_bool)] // simplification is more unreadable
+    fn collect_incompatibilities(
+        &self,
+        old_module: &CompiledModule,
+        new_module: &CompiledModule,
+        all_removed_structs: bool,
+    ) -> Vec<Incompatibility> {
         let mut errors = vec![];
 
         // module's name and address are unchanged
         if old_module.address() != new_module.address() {
-            errors.push(format!(
-                "module address changed to `{}`",
-                new_module.address()
-            ));
+            errors.push(Incompatibility::ModuleAddressChanged {
+                new_address: *new_module.address(),
+            });
         }
         if old_module.name() != new_module.name() {
-            errors.push(format!("module name changed to `{}`", new_module.name()));
+            errors.push(Incompatibility::ModuleNameChanged {
+                new_name: new_module.name().to_owned(),
+            });
         }
 
         let old_view = ModuleView::new(old_module);
@@ -120,30 +254,35 @@ impl Compatibility {
                     // Struct not present in new . Existing modules that depend on this struct will fail to link with the new version of the module.
                     // Also, struct layout cannot be guaranteed transitively, because after
                     // removing the struct, it could be re-added later with a different layout.
-                    errors.push(format!("removed struct `{}`", old_struct.name()));
+                    errors.push(Incompatibility::StructRemoved {
+                        name: old_struct.name().to_owned(),
+                    });
+                    if all_removed_structs {
+                        continue;
+                    }
                     break;
                 },
             };
 
             if !self.struct_abilities_compatible(old_struct.abilities(), new_struct.abilities()) {
-                errors.push(format!(
-                    "removed abilities `{}` from struct `{}`",
-                    old_struct.abilities().setminus(new_struct.abilities()),
-                    old_struct.name()
-                ));
+                errors.push(Incompatibility::StructAbilitiesRemoved {
+                    name: old_struct.name().to_owned(),
+                    removed: old_struct.abilities().setminus(new_struct.abilities()),
+                });
             }
             if !self.struct_type_parameters_compatible(
                 old_struct.type_parameters(),
                 new_struct.type_parameters(),
             ) {
-                errors.push(format!(
-                    "changed type parameters of struct `{}`",
-                    old_struct.name()
-                ));
+                errors.push(Incompatibility::StructTypeParametersChanged {
+                    name: old_struct.name().to_owned(),
+                });
             }
             // Layout of old and new struct need to be compatible
             if self.check_struct_layout && !self.struct_layout_compatible(&old_struct, new_struct) {
-                errors.push(format!("changed layout of struct `{}`", old_struct.name()));
+                errors.push(Incompatibility::StructLayoutChanged {
+                    name: old_struct.name().to_owned(),
+                });
             }
         }
 
@@ -190,7 +329,9 @@ impl Compatibility {
                     // any Entry functions to be deleted, when self.treat_entry_as_public is
                     // set (treats entry as public)
                     {
-                        errors.push(format!("removed function `{}`", old_func.name()));
+                        errors.push(Incompatibility::FunctionRemoved {
+                            name: old_func.name().to_owned(),
+                        });
                     }
                     continue;
                 },
@@ -229,36 +370,39 @@ impl Compatibility {
                 };
             let is_attribute_compatible =
                 FunctionAttribute::is_compatible_with(old_func.attributes(), new_func.attributes());
-            let error_msg = if !is_vis_compatible {
-                Some("changed visibility")
+            let change = if !is_vis_compatible {
+                Some(FunctionChange::Visibility)
             } else if !is_entry_compatible {
-                Some("removed `entry` modifier")
+                Some(FunctionChange::EntryRemoved)
             } else if !is_attribute_compatible {
-                Some("removed required attributes")
+                Some(FunctionChange::AttributesRemoved)
             } else if !self.signature_compatible(
                 old_module,
                 old_func.parameters(),
                 new_module,
                 new_func.parameters(),
             ) {
-                Some("changed parameter types")
+                Some(FunctionChange::ParameterTypes)
             } else if !self.signature_compatible(
                 old_module,
                 old_func.return_type(),
                 new_module,
                 new_func.return_type(),
             ) {
-                Some("changed return type")
+                Some(FunctionChange::ReturnType)
             } else if !self.fun_type_parameters_compatible(
                 old_func.type_parameters(),
                 new_func.type_parameters(),
             ) {
-                Some("changed type parameters")
+                Some(FunctionChange::TypeParameters)
             } else {
                 None
             };
-            if let Some(msg) = error_msg {
-                errors.push(format!("{} of function `{}`", msg, old_func.name()));
+            if let Some(change) = change {
+                errors.push(Incompatibility::FunctionChanged {
+                    name: old_func.name().to_owned(),
+                    change,
+                });
             }
         }
 
@@ -273,31 +417,16 @@ impl Compatibility {
             let new_friend_module_ids: BTreeSet<_> =
                 new_module.immediate_friends().iter().cloned().collect();
             if !old_friend_module_ids.is_subset(&new_friend_module_ids) {
-                errors.push(format!(
-                    "removed friend declaration {}",
-                    old_friend_module_ids
+                errors.push(Incompatibility::FriendsRemoved {
+                    friends: old_friend_module_ids
                         .difference(&new_friend_module_ids)
-                        .map(|id| format!("`{}`", id))
-                        .collect::<Vec<_>>()
-                        .join(" and ")
-                ))
+                        .cloned()
+                        .collect(),
+                })
             }
         }
 
-        if !errors.is_empty() {
-            Err(
-                PartialVMError::new(StatusCode::BACKWARD_INCOMPATIBLE_MODULE_UPDATE).with_message(
-                    format!(
-                        "Module update failure: new module not compatible with \
-                        existing module in `{}`: {}",
-                        old_view.id(),
-                        errors.join(", ")
-                    ),
-                ),
-            )
-        } else {
-            Ok(())
-        }
+        errors
     }
 
     // When upgrading, the new abilities must be a superset of the old abilities.
@@ -1,8 +1,13 @@
 // Copyright (c) The Move Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{compatibility::Compatibility, file_format::*};
-use move_core_types::{account_address::AccountAddress, identifier::Identifier};
+use crate::{
+    compatibility::{Compatibility, FunctionChange, Incompatibility},
+    file_format::*,
+};
+use move_core_types::{
+    ability::AbilitySet, account_address::AccountAddress, identifier::Identifier,
+};
 use std::convert::TryFrom;
 
 #[allow(deprecated)]
@@ -120,3 +125,74 @@ fn deprecated_add_script_visibility() {
         .check(&friend_module, &script_module)
         .is_err());
 }
+
+#[test]
+fn incompatibilities_of_functions() {
+    let public_module = mk_module(Visibility::Public as u8);
+    let private_module = mk_module(Visibility::Private as u8);
+    let name = Identifier::new("fn").unwrap();
+    assert_eq!(
+        Compatibility::full_check().incompatibilities(&public_module, &private_module),
+        vec![Incompatibility::FunctionChanged {
+            name: name.clone(),
+            change: FunctionChange::Visibility
+        }]
+    );
+    let error = Compatibility::full_check()
+        .check(&public_module, &private_module)
+        .unwrap_err();
+    assert!(error
+        .message()
+        .unwrap()
+        .ends_with("::M`: changed visibility of function `fn`"));
+
+    let mut removed_module = public_module.clone();
+    removed_module.function_defs.clear();
+    assert_eq!(
+        Compatibility::full_check().incompatibilities(&public_module, &removed_module),
+        vec![Incompatibility::FunctionRemoved { name }]
+    );
+    // Private functions can be removed
+    assert!(Compatibility::full_check()
+        .incompatibilities(&private_module, &removed_module)
+        .is_empty());
+}
+
+#[test]
+fn incompatibilities_of_removed_structs() {
+    let mut old_module = mk_module(Visibility::Public as u8);
+    for name in ["S1", "S2"] {
+        old_module.identifiers.push(Identifier::new(name).unwrap());
+        old_module.struct_handles.push(StructHandle {
+            module: ModuleHandleIndex(0),
+            name: IdentifierIndex((old_module.identifiers.len() - 1) as u16),
+            abilities: AbilitySet::EMPTY,
+            type_parameters: vec![],
+        });
+        old_module.struct_defs.push(StructDefinition {
+            struct_handle: StructHandleIndex((old_module.struct_handles.len() - 1) as u16),
+            field_information: StructFieldInformation::Declared(vec![]),
+        });
+    }
+    let new_module = mk_module(Visibility::Public as u8);
+
+    assert_eq!(
+        Compatibility::full_check().incompatibilities(&old_module, &new_module),
+        vec![
+            Incompatibility::StructRemoved {
+                name: Identifier::new("S1").unwrap()
+            },
+            Incompatibility::StructRemoved {
+                name: Identifier::new("S2").unwrap()
+            },
+        ]
+    );
+    // The check stops at the first removed struct
+    let error = Compatibility::full_check()
+        .check(&old_module, &new_module)
+        .unwrap_err();
+    assert!(error
+        .message()
+        .unwrap()
+        .ends_with("::M`: removed struct `S1`"));
+}
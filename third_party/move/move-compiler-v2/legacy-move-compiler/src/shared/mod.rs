@@ -662,6 +662,10 @@ pub mod known_attributes {
         Test,
         // This test is expected to fail
         ExpectedFailure,
+        // This test fails if it uses more gas than the given bound
+        MaxGas,
+        // This test is run with generated values for its parameters not assigned in #[test]
+        Fuzz,
     }
 
     #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -721,6 +725,8 @@ pub mod known_attributes {
                 TestingAttribute::EXPECTED_FAILURE => {
                     Self::Testing(TestingAttribute::ExpectedFailure)
                 },
+                TestingAttribute::MAX_GAS => Self::Testing(TestingAttribute::MaxGas),
+                TestingAttribute::FUZZ => Self::Testing(TestingAttribute::Fuzz),
                 VerificationAttribute::VERIFY_ONLY => {
                     Self::Verification(VerificationAttribute::VerifyOnly)
                 },
@@ -781,12 +787,19 @@ pub mod known_attributes {
 
     impl TestingAttribute {
         pub const ABORT_CODE_NAME: &'static str = "abort_code";
-        const ALL_ATTRIBUTE_NAMES: [&'static str; 3] =
-            [Self::TEST, Self::TEST_ONLY, Self::EXPECTED_FAILURE];
+        const ALL_ATTRIBUTE_NAMES: [&'static str; 5] = [
+            Self::TEST,
+            Self::TEST_ONLY,
+            Self::EXPECTED_FAILURE,
+            Self::MAX_GAS,
+            Self::FUZZ,
+        ];
         pub const ARITHMETIC_ERROR_NAME: &'static str = "arithmetic_error";
         pub const ERROR_LOCATION: &'static str = "location";
         pub const EXPECTED_FAILURE: &'static str = "expected_failure";
+        pub const FUZZ: &'static str = "fuzz";
         pub const MAJOR_STATUS_NAME: &'static str = "major_status";
+        pub const MAX_GAS: &'static str = "max_gas";
         pub const MINOR_STATUS_NAME: &'static str = "minor_status";
         pub const OUT_OF_GAS_NAME: &'static str = "out_of_gas";
         pub const TEST: &'static str = "test";
@@ -815,6 +828,8 @@ pub mod known_attributes {
                 Self::Test => Self::TEST,
                 Self::TestOnly => Self::TEST_ONLY,
                 Self::ExpectedFailure => Self::EXPECTED_FAILURE,
+                Self::MaxGas => Self::MAX_GAS,
+                Self::Fuzz => Self::FUZZ,
             }
         }
 
@@ -835,10 +850,16 @@ pub mod known_attributes {
                 Lazy::new(|| IntoIterator::into_iter([AttributePosition::Function]).collect());
             static EXPECTED_FAILURE_POSITIONS: Lazy<BTreeSet<AttributePosition>> =
                 Lazy::new(|| IntoIterator::into_iter([AttributePosition::Function]).collect());
+            static MAX_GAS_POSITIONS: Lazy<BTreeSet<AttributePosition>> =
+                Lazy::new(|| IntoIterator::into_iter([AttributePosition::Function]).collect());
+            static FUZZ_POSITIONS: Lazy<BTreeSet<AttributePosition>> =
+                Lazy::new(|| IntoIterator::into_iter([AttributePosition::Function]).collect());
             match self {
                 TestingAttribute::TestOnly => &TEST_ONLY_POSITIONS,
                 TestingAttribute::Test => &TEST_POSITIONS,
                 TestingAttribute::ExpectedFailure => &EXPECTED_FAILURE_POSITIONS,
+                TestingAttribute::MaxGas => &MAX_GAS_POSITIONS,
+                TestingAttribute::Fuzz => &FUZZ_POSITIONS,
             }
         }
     }
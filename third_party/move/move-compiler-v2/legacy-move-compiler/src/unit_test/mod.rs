@@ -9,8 +9,11 @@ use crate::{
 };
 use move_binary_format::CompiledModule;
 use move_core_types::{
-    account_address::AccountAddress, identifier::Identifier, language_storage::ModuleId,
-    value::MoveValue, vm_status::StatusCode,
+    account_address::AccountAddress,
+    identifier::Identifier,
+    language_storage::ModuleId,
+    value::{MoveTypeLayout, MoveValue},
+    vm_status::StatusCode,
 };
 use std::{collections::BTreeMap, fmt};
 
@@ -45,7 +48,20 @@ pub struct ModuleTestPlan {
 pub struct TestCase {
     pub test_name: TestName,
     pub arguments: Vec<MoveValue>,
+    // parameters not assigned in the test attribute, whose values are generated for each run of
+    // the test (making it a property-based test). `arguments` holds the remaining parameters.
+    pub generated_parameters: Vec<GeneratedParameter>,
     pub expected_failure: Option<ExpectedFailure>,
+    // upper bound on the gas a passing run of the test may use, set with `#[max_gas = N]`
+    pub max_gas: Option<u64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct GeneratedParameter {
+    // position of the parameter in the signature of the test function
+    pub position: usize,
+    pub name: String,
+    pub layout: MoveTypeLayout,
 }
 
 #[derive(Debug, Clone)]
@@ -15,11 +15,14 @@ use crate::options::Options;
 use codespan_reporting::diagnostic::Severity;
 use legacy_move_compiler::{
     shared::known_attributes::{AttributeKind, TestingAttribute},
-    unit_test::{ExpectedFailure, ExpectedMoveError, ModuleTestPlan, TestCase},
+    unit_test::{ExpectedFailure, ExpectedMoveError, GeneratedParameter, ModuleTestPlan, TestCase},
 };
 use move_command_line_common::{address::NumericalAddress, parser::NumberFormat};
 use move_core_types::{
-    identifier::Identifier, language_storage::ModuleId, value::MoveValue, vm_status::StatusCode,
+    identifier::Identifier,
+    language_storage::ModuleId,
+    value::{MoveTypeLayout, MoveValue},
+    vm_status::StatusCode,
 };
 use move_model::{
     ast::{Address, Attribute, AttributeValue, ModuleName, Value},
@@ -117,9 +120,13 @@ fn build_test_info(
     let expected_failure_name = env.symbol_pool().make(TestingAttribute::EXPECTED_FAILURE);
     let test_name = env.symbol_pool().make(TestingAttribute::TEST);
     let test_only_name = env.symbol_pool().make(TestingAttribute::TEST_ONLY);
+    let max_gas_name = env.symbol_pool().make(TestingAttribute::MAX_GAS);
+    let fuzz_name = env.symbol_pool().make(TestingAttribute::FUZZ);
 
     let test_attribute_opt = attrs.iter().find(|a| a.name() == test_name);
     let abort_attribute_opt = attrs.iter().find(|a| a.name() == expected_failure_name);
+    let max_gas_attribute_opt = attrs.iter().find(|a| a.name() == max_gas_name);
+    let fuzz_attribute_opt = attrs.iter().find(|a| a.name() == fuzz_name);
 
     let test_attribute = match test_attribute_opt {
         None => {
@@ -132,6 +139,25 @@ fn build_test_info(
                 let abort_loc = env.get_node_loc(abort_id);
                 env.error_with_labels(&fn_id_loc, fn_msg, vec![(abort_loc, abort_msg.to_string())]);
             }
+            // neither can gas bounds
+            if let Some(max_gas_attribute) = max_gas_attribute_opt {
+                let fn_msg = "Only functions defined as a test with #[test] can also have a \
+                              #[max_gas] attribute";
+                let max_gas_msg = "Attributed as #[max_gas] here";
+                let max_gas_loc = env.get_node_loc(max_gas_attribute.node_id());
+                env.error_with_labels(&fn_id_loc, fn_msg, vec![(
+                    max_gas_loc,
+                    max_gas_msg.to_string(),
+                )]);
+            }
+            // nor can generated parameters
+            if let Some(fuzz_attribute) = fuzz_attribute_opt {
+                let fn_msg = "Only functions defined as a test with #[test] can also have a \
+                              #[fuzz] attribute";
+                let fuzz_msg = "Attributed as #[fuzz] here";
+                let fuzz_loc = env.get_node_loc(fuzz_attribute.node_id());
+                env.error_with_labels(&fn_id_loc, fn_msg, vec![(fuzz_loc, fuzz_msg.to_string())]);
+            }
             return None;
         },
         Some(test_attribute) => test_attribute,
@@ -158,9 +184,12 @@ fn build_test_info(
     }
 
     let test_annotation_params = parse_test_attribute(env, test_attribute, 0);
+    let fuzz = fuzz_attribute_opt
+        .is_some_and(|fuzz_attribute| check_fuzz_attribute(env, fuzz_attribute).is_some());
 
     let mut arguments = Vec::new();
-    for param in function.get_parameters_ref() {
+    let mut generated_parameters = Vec::new();
+    for (position, param) in function.get_parameters_ref().iter().enumerate() {
         let Parameter(var, ty, var_loc) = &param;
 
         match test_annotation_params.get(var) {
@@ -185,6 +214,15 @@ fn build_test_info(
                 },
             },
             Some(value) => arguments.push(value.clone()),
+            // Parameters of a #[fuzz] test which are not assigned get generated values, if they
+            // are of a type values can be generated for
+            None if fuzz && generated_parameter_layout(ty).is_some() => {
+                generated_parameters.push(GeneratedParameter {
+                    position,
+                    name: var.display(env.symbol_pool()).to_string(),
+                    layout: generated_parameter_layout(ty).unwrap(),
+                })
+            },
             None => {
                 let missing_param_msg = "Missing test parameter assignment in test. Expected a \
                                          parameter to be assigned in this attribute";
@@ -205,10 +243,44 @@ fn build_test_info(
         Some(abort_attribute) => parse_failure_attribute(env, current_module, abort_attribute),
     };
 
+    let max_gas = max_gas_attribute_opt.and_then(|max_gas_attribute| {
+        parse_max_gas_attribute(env, current_module, max_gas_attribute)
+    });
+
     Some(TestCase {
         test_name: fn_name_str.to_string(),
         arguments,
+        generated_parameters,
         expected_failure,
+        max_gas,
+    })
+}
+
+/// Returns the layout of values generated for a test parameter of type `ty`, if values of that
+/// type can be generated: booleans, integers, addresses, signers and vectors of these (except
+/// for signers).
+fn generated_parameter_layout(ty: &Type) -> Option<MoveTypeLayout> {
+    Some(match ty {
+        Type::Primitive(prim) => match prim {
+            PrimitiveType::Bool => MoveTypeLayout::Bool,
+            PrimitiveType::U8 => MoveTypeLayout::U8,
+            PrimitiveType::U16 => MoveTypeLayout::U16,
+            PrimitiveType::U32 => MoveTypeLayout::U32,
+            PrimitiveType::U64 => MoveTypeLayout::U64,
+            PrimitiveType::U128 => MoveTypeLayout::U128,
+            PrimitiveType::U256 => MoveTypeLayout::U256,
+            PrimitiveType::Address => MoveTypeLayout::Address,
+            PrimitiveType::Signer => MoveTypeLayout::Signer,
+            PrimitiveType::Num | PrimitiveType::Range | PrimitiveType::EventStore => return None,
+        },
+        Type::Reference(_, inner) if **inner == Type::Primitive(PrimitiveType::Signer) => {
+            MoveTypeLayout::Signer
+        },
+        Type::Vector(elem) => match generated_parameter_layout(elem)? {
+            MoveTypeLayout::Signer => return None,
+            layout => MoveTypeLayout::Vector(Box::new(layout)),
+        },
+        _ => return None,
     })
 }
 
@@ -467,6 +539,45 @@ fn parse_failure_attribute(
     }
 }
 
+/// Checks that the #[fuzz] attribute has no value or parameters
+fn check_fuzz_attribute(env: &GlobalEnv, fuzz_attr: &Attribute) -> Option<()> {
+    match fuzz_attr {
+        Attribute::Apply(_id, _sym, attrs) if attrs.is_empty() => Some(()),
+        Attribute::Apply(id, _, _) | Attribute::Assign(id, _, _) => {
+            let loc = env.get_node_loc(*id);
+            let msg = format!(
+                "Expected no value or parameters for attribute `{}`",
+                TestingAttribute::FUZZ
+            );
+            env.error(&loc, &msg);
+            None
+        },
+    }
+}
+
+fn parse_max_gas_attribute(
+    env: &GlobalEnv,
+    current_module: &ModuleName,
+    max_gas_attr: &Attribute,
+) -> Option<u64> {
+    match max_gas_attr {
+        Attribute::Assign(_id, _sym, value) => {
+            let (_, _, max_gas) =
+                convert_constant_value_u64_constant_or_value(env, current_module, value)?;
+            Some(max_gas)
+        },
+        Attribute::Apply(id, _sym, _attrs) => {
+            let loc = env.get_node_loc(*id);
+            let msg = format!(
+                "Expected assigned value, e.g. `{}=...`, for gas bound attribute",
+                TestingAttribute::MAX_GAS
+            );
+            env.error(&loc, &msg);
+            None
+        },
+    }
+}
+
 fn check_attribute_unassigned(env: &GlobalEnv, kind: &str, attr: Attribute) -> Option<()> {
     match attr {
         Attribute::Apply(id, sym, vec) => {
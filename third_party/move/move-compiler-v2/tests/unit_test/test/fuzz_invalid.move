@@ -0,0 +1,22 @@
+// parameters only get generated values in #[test] functions marked with #[fuzz]
+module 0x1::A {
+    struct S has drop {}
+
+    // not assigned, and not marked with #[fuzz]
+    #[test]
+    fun not_fuzz(_x: u64) { }
+
+    // #[fuzz] on a function which is not a test
+    #[fuzz]
+    fun not_test(_x: u64) { }
+
+    // no values can be generated for a struct
+    #[test]
+    #[fuzz]
+    fun struct_param(_s: S) { }
+
+    // #[fuzz] takes no value
+    #[test]
+    #[fuzz = 10]
+    fun fuzz_with_value(_x: u64) { }
+}
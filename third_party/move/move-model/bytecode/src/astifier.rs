@@ -89,7 +89,7 @@ use move_model::{
     exp_rewriter::ExpRewriterFunctions,
     model::{GlobalEnv, Loc, NodeId, QualifiedInstId, StructId},
     symbol::Symbol,
-    ty::{ReferenceKind, Type},
+    ty::{PrimitiveType, ReferenceKind, Type},
 };
 use std::{
     cmp::Ordering,
@@ -189,6 +189,8 @@ struct Context<'a> {
     /// Back edges, defined by the code offset where the branch or jump is found, and the label
     /// of the loop header.
     back_edges: BTreeSet<(CodeOffset, Label)>,
+    /// Names of the locals which are not parameters, for those a name could be inferred for.
+    local_names: BTreeMap<TempIndex, Symbol>,
 }
 
 /// Mutable state of the ast generator.
@@ -233,9 +235,59 @@ impl<'a> Context<'a> {
             loop_labels: Default::default(),
             after_loop_labels: Default::default(),
             back_edges: Default::default(),
+            local_names: Self::infer_local_names(target),
         }
     }
 
+    /// Infers names for the locals of the function. The name of a local is taken from the
+    /// source map if it has one, otherwise derived from the type of the local, as in
+    /// `coin` for a local of type `Coin<T>`. Locals without a name are later named after
+    /// their index, as in `_t3`.
+    fn infer_local_names(target: &FunctionTarget) -> BTreeMap<TempIndex, Symbol> {
+        let env = target.global_env();
+        let mut taken: BTreeSet<String> = target
+            .get_parameters()
+            .map(|temp| {
+                target
+                    .get_local_name(temp)
+                    .display(env.symbol_pool())
+                    .to_string()
+            })
+            .collect();
+        let mut names = BTreeMap::new();
+        for temp in target.get_non_parameter_locals() {
+            let source_name = if temp < target.get_user_local_count() {
+                Some(
+                    target
+                        .get_local_name(temp)
+                        .display(env.symbol_pool())
+                        .to_string(),
+                )
+                .filter(|name| is_inferable_name(name))
+            } else {
+                None
+            };
+            let Some(base_name) =
+                source_name.or_else(|| name_from_type(env, target.get_local_type(temp)))
+            else {
+                continue;
+            };
+            let name = (0..)
+                .map(|i| {
+                    if i == 0 {
+                        base_name.clone()
+                    } else {
+                        format!("{}_{}", base_name, i)
+                    }
+                })
+                .find(|name| !taken.contains(name) && !RESERVED_NAMES.contains(&name.as_str()))
+                .expect("unique name");
+            names.insert(temp, env.symbol_pool().make(&name));
+            taken.insert(name);
+        }
+        names
+    }
+
     /// Cleans up the bytecode. To make the algorithm work, the following requirements
     /// have to be met:
     /// 1. There must be no directly adjacent blocks B1 -> B2 such that B1 has no
@@ -497,6 +549,90 @@ impl<'a> Context<'a> {
     }
 }
 
+// -------------------------------------------------------------------------------------------
+// Local Names
+
+/// Names which cannot be used for locals, as they are keywords or reserved by the language.
+const RESERVED_NAMES: &[&str] = &[
+    "abort",
+    "acquires",
+    "as",
+    "break",
+    "const",
+    "continue",
+    "copy",
+    "else",
+    "enum",
+    "false",
+    "for",
+    "friend",
+    "fun",
+    "has",
+    "if",
+    "invariant",
+    "let",
+    "loop",
+    "match",
+    "module",
+    "move",
+    "mut",
+    "native",
+    "phantom",
+    "public",
+    "return",
+    "script",
+    "spec",
+    "struct",
+    "true",
+    "use",
+    "while",
+];
+
+/// Checks whether a name from the source map can be used for a decompiled local. This
+/// excludes temporaries introduced by the compiler, as well as names of the form `_t<n>`
+/// used for locals without a name.
+fn is_inferable_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !name
+            .strip_prefix("_t")
+            .is_some_and(|index| index.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Derives a name for a local from its type, if the type is descriptive enough.
+fn name_from_type(env: &GlobalEnv, ty: &Type) -> Option<String> {
+    match ty.skip_reference() {
+        Type::Struct(mid, sid, _) => {
+            let struct_env = env.get_module(*mid).into_struct(*sid);
+            let name = struct_env.get_name().display(env.symbol_pool()).to_string();
+            // Convert to snake case, keeping acronyms together, as in `NFTStore` => `nft_store`
+            let chars = name.chars().collect_vec();
+            let mut snake_case = String::new();
+            for (i, c) in chars.iter().enumerate() {
+                if c.is_ascii_uppercase() && i > 0 {
+                    let prev = chars[i - 1];
+                    let next_is_lower = chars.get(i + 1).is_some_and(|c| c.is_ascii_lowercase());
+                    if prev.is_ascii_lowercase()
+                        || prev.is_ascii_digit()
+                        || (prev.is_ascii_uppercase() && next_is_lower)
+                    {
+                        snake_case.push('_')
+                    }
+                }
+                snake_case.push(c.to_ascii_lowercase())
+            }
+            Some(snake_case).filter(|name| is_inferable_name(name))
+        },
+        Type::Vector(_) => Some("v".to_string()),
+        Type::Primitive(PrimitiveType::Address) => Some("addr".to_string()),
+        Type::Primitive(PrimitiveType::Signer) => Some("account".to_string()),
+        _ => None,
+    }
+}
+
 // -------------------------------------------------------------------------------------------
 // Generator Core Logic
 
@@ -1307,6 +1443,8 @@ impl Generator {
     fn make_var_decl(&mut self, ctx: &Context, temp: TempIndex) -> (NodeId, Symbol) {
         let name = if temp < ctx.target.get_parameter_count() {
             ctx.target.get_local_name(temp)
+        } else if let Some(name) = ctx.local_names.get(&temp) {
+            *name
         } else {
             ctx.env().symbol_pool().make(&format!("_t{}", temp))
         };
@@ -2140,7 +2278,7 @@ impl AssignTransformer<'_> {
             let stm_usage = self.usage[&stm.node_id()].clone();
             match stm.as_ref() {
                 // Check whether an assignment can be eliminated
-                ExpData::Assign(id, Pattern::Var(_, var), rhs)
+                ExpData::Assign(id, Pattern::Var(pat_id, var), rhs)
                 if
                 // Cannot be read outside this block
                 after_block_usage.read_count(*var) == 0 &&
@@ -2158,6 +2296,15 @@ impl AssignTransformer<'_> {
                         if read_cnt == 0 && Self::safe_to_eliminate(&rhs_unfolded) {
                             continue;
                         }
+                        // If the result is not used but the RHS is not safe to eliminate, keep
+                        // the RHS, assigning its result to the wildcard
+                        if read_cnt == 0 {
+                            let rhs = self.rewrite_exp(rhs_unfolded);
+                            new_stms.push(
+                                ExpData::Assign(*id, Pattern::Wildcard(*pat_id), rhs).into_exp(),
+                            );
+                            continue;
+                        }
                         // If the RHS is safe to simplify and the result is used at least once, we can substitute the var with RHS
                         if read_cnt != 0 && self.safe_to_simplify(var, rhs, stms.get(idx + 1..).unwrap_or(&[]), &substitution) {
                             substitution.insert(*var, self.rewrite_exp(rhs_unfolded));
@@ -2212,9 +2359,11 @@ impl AssignTransformer<'_> {
         // If there are no statements after the assignment, or
         // if the target var is used immediately after its assignment, we can safely simplify it.
         // While this seems to be hacky, it is very effective in reducing compiler-introduced temp vars.
+        let rhs_vars = self.builder.unfold(substitution, rhs.clone()).free_vars();
         if stmts.first().map_or(true, |first_exp| {
             Self::used_immediately(
                 target_var,
+                &rhs_vars,
                 &self.builder.unfold(substitution, first_exp.clone()),
             )
         }) {
@@ -2265,29 +2414,31 @@ impl AssignTransformer<'_> {
     }
 
     /// Check if the target variable is the first variable accessed during execution of the expression.
-    fn used_immediately(target_var: &Symbol, exp: &Exp) -> bool {
+    /// `rhs_vars` are the free variables of the expression to be substituted for the target.
+    fn used_immediately(target_var: &Symbol, rhs_vars: &BTreeSet<Symbol>, exp: &Exp) -> bool {
         match exp.as_ref() {
             ExpData::LocalVar(_, var) => *var == *target_var,
-            ExpData::IfElse(_, cond, _, _) => Self::used_immediately(target_var, cond),
-            ExpData::Assign(_, _, rhs) => Self::used_immediately(target_var, rhs),
+            ExpData::IfElse(_, cond, _, _) => Self::used_immediately(target_var, rhs_vars, cond),
+            ExpData::Assign(_, _, rhs) => Self::used_immediately(target_var, rhs_vars, rhs),
             ExpData::Sequence(_, stmts) => stmts
                 .first()
-                .map_or(false, |e| Self::used_immediately(target_var, e)),
+                .map_or(false, |e| Self::used_immediately(target_var, rhs_vars, e)),
             ExpData::Block(_, _, bind, body) => {
                 if let Some(bind) = bind {
                     // If there is a binding, we check the bind
-                    Self::used_immediately(target_var, bind)
+                    Self::used_immediately(target_var, rhs_vars, bind)
                 } else {
                     // Otherwise, we check the body
-                    Self::used_immediately(target_var, body)
+                    Self::used_immediately(target_var, rhs_vars, body)
                 }
             },
-            ExpData::Match(_, target, _) => Self::used_immediately(target_var, target),
-            ExpData::Return(_, ret) => Self::used_immediately(target_var, ret),
-            ExpData::Mutate(_, _, rhs) => Self::used_immediately(target_var, rhs),
+            ExpData::Match(_, target, _) => Self::used_immediately(target_var, rhs_vars, target),
+            ExpData::Return(_, ret) => Self::used_immediately(target_var, rhs_vars, ret),
+            ExpData::Mutate(_, _, rhs) => Self::used_immediately(target_var, rhs_vars, rhs),
             ExpData::Call(_, op, args) => match op {
                 // Operations with exactly one argument
-                Operation::Freeze(_)
+                Operation::Abort
+                | Operation::Freeze(_)
                 | Operation::Borrow(_)
                 | Operation::Deref
                 | Operation::Not
@@ -2296,7 +2447,7 @@ impl AssignTransformer<'_> {
                 | Operation::SelectVariants(_, _, _)
                 | Operation::TestVariants(_, _, _) => args
                     .first()
-                    .map_or(false, |e| Self::used_immediately(target_var, e)),
+                    .map_or(false, |e| Self::used_immediately(target_var, rhs_vars, e)),
                 // Operations with two arguments whose order does not matter
                 Operation::Add
                 | Operation::Copy
@@ -2319,14 +2470,20 @@ impl AssignTransformer<'_> {
                 | Operation::Le
                 | Operation::Ge => args
                     .iter()
-                    .any(|arg| Self::used_immediately(target_var, arg)),
+                    .any(|arg| Self::used_immediately(target_var, rhs_vars, arg)),
                 // Operations with or without arguments
+                // Leading arguments which are values or variables not used by the RHS can
+                // be evaluated before the RHS without changing the result
                 Operation::MoveFunction(_, _) | Operation::Pack(_, _, _) | Operation::Tuple => args
-                    .first()
-                    .map_or(false, |e| Self::used_immediately(target_var, e)),
+                    .iter()
+                    .find(|arg| match arg.as_ref() {
+                        ExpData::Value(..) => false,
+                        ExpData::LocalVar(_, var) => var == target_var || rhs_vars.contains(var),
+                        _ => true,
+                    })
+                    .map_or(false, |e| Self::used_immediately(target_var, rhs_vars, e)),
                 // [TODO] handle global resource operators after issue #17010 is fixed
-                Operation::Abort
-                | Operation::Closure(..)
+                Operation::Closure(..)
                 | Operation::Vector
                 | Operation::Exists(..)
                 | Operation::BorrowGlobal(..)
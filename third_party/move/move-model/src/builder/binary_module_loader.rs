@@ -140,7 +140,11 @@ impl<'a> BinaryModuleLoader<'a> {
                 },
                 Some(module_env) => {
                     if handle.module_id() == module.id() {
-                        module_id = Some(module_env.get_id());
+                        // The module may have been added before as a dependency of another
+                        // loaded module, so attach the source map now.
+                        let id = module_env.get_id();
+                        env.module_data[id.to_usize()].source_map = Some(source_map.clone());
+                        module_id = Some(id)
                     }
                 },
             }
@@ -412,9 +416,17 @@ impl<'a> BinaryModuleLoader<'a> {
 
         let result_type = Type::tuple(handle_view.return_().0.iter().map(|s| self.ty(s)).collect());
 
-        // Convert access specifiers from file format to AST format
-        // [Optional TODO] fetch `acquires` from the definition view, if available
-        let access_specifiers = self.access_specifiers(&handle_view, &module_id, &params);
+        // Convert access specifiers from file format to AST format, and add the legacy
+        // `acquires` from the definition view, if available
+        let mut access_specifiers = self.access_specifiers(&handle_view, &module_id, &params);
+        if let Some((_, def_view)) = &def_view {
+            let acquires = self.acquires(def_view.acquired_resources(), &module_id, &loc);
+            if !acquires.is_empty() {
+                access_specifiers
+                    .get_or_insert_with(Vec::new)
+                    .extend(acquires)
+            }
+        }
 
         let (visibility, is_native, kind) = if let Some((_, def_view)) = def_view {
             (
@@ -479,6 +491,13 @@ impl<'a> BinaryModuleLoader<'a> {
                 || !params_logical_equal(&params, &fun_data.params)
                 || result_type != fun_data.result_type);
 
+        if has_def {
+            // Information which is only known from the definition, and has been defaulted
+            // if the function was added before from a handle.
+            fun_data.visibility = visibility;
+            fun_data.is_native = is_native;
+            fun_data.kind = kind;
+        }
         if new || has_def {
             // Update if the entry is new, or there is information which is exclusive
             // to definition, like locations.
@@ -621,6 +640,33 @@ impl<'a> BinaryModuleLoader<'a> {
         Some(access_specifiers)
     }
 
+    /// Converts the resources acquired by a function definition into legacy `acquires`
+    /// access specifiers. Acquired resources are always declared in the loaded module.
+    fn acquires(
+        &self,
+        acquired: &[StructDefinitionIndex],
+        module_id: &ModuleId,
+        loc: &Loc,
+    ) -> Vec<ASTAccessSpecifier> {
+        acquired
+            .iter()
+            .map(|def_idx| {
+                let struct_view = self.module.structs().nth(def_idx.into_index()).unwrap();
+                let struct_id = StructId::new(self.sym(struct_view.name().as_str()));
+                ASTAccessSpecifier {
+                    loc: loc.clone(),
+                    kind: ASTAccessSpecifierKind::LegacyAcquires,
+                    negated: false,
+                    resource: (
+                        loc.clone(),
+                        ASTResourceSpecifier::Resource(module_id.qualified_inst(struct_id, vec![])),
+                    ),
+                    address: (loc.clone(), ASTAddressSpecifier::Any),
+                }
+            })
+            .collect()
+    }
+
     fn ty(&self, sign: &SignatureToken) -> Type {
         let resolver = |module_name, struct_sym| {
             let struct_id = StructId::new(struct_sym);
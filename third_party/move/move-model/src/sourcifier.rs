@@ -11,15 +11,18 @@ use crate::{
     emit, emitln,
     exp_builder::ExpBuilder,
     model::{
-        FieldEnv, FunId, FunctionEnv, GlobalEnv, ModuleId, NodeId, Parameter, QualifiedId,
-        QualifiedInstId, StructId, TypeParameter, Visibility,
+        FieldEnv, FieldId, FunId, FunctionEnv, GlobalEnv, ModuleEnv, ModuleId, NodeId, Parameter,
+        QualifiedId, QualifiedInstId, StructId, TypeParameter, Visibility,
     },
     symbol::Symbol,
     ty::{PrimitiveType, ReferenceKind, Type, TypeDisplayContext},
 };
 use itertools::Itertools;
 use move_core_types::ability::AbilitySet;
-use std::collections::{BTreeMap, BTreeSet};
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, BTreeSet},
+};
 //
 // ========================================================================================
 //
@@ -31,8 +34,13 @@ pub struct Sourcifier<'a> {
     writer: CodeWriter,
     // whether to amend the displayed results to be recompilable (e.g., remove `__` from lambda names)
     amend: bool,
+    // for each module printed so far, the structs whose dummy field is declared by the user
+    declared_dummy_fields: RefCell<BTreeMap<ModuleId, BTreeSet<StructId>>>,
 }
 
+/// The name of the field the compiler introduces for empty structs.
+const DUMMY_FIELD_NAME: &str = "dummy_field";
+
 impl<'a> Sourcifier<'a> {
     /// Creates a new sourcifier.
     pub fn new(env: &'a GlobalEnv, amend: bool) -> Self {
@@ -41,6 +49,7 @@ impl<'a> Sourcifier<'a> {
             // Location not used, but required by the constructor
             writer: CodeWriter::new(env.unknown_loc()),
             amend,
+            declared_dummy_fields: RefCell::new(BTreeMap::new()),
         }
     }
 
@@ -66,7 +75,10 @@ impl<'a> Sourcifier<'a> {
             emitln!(self.writer, "module {} {{", module_env.get_full_name_str());
         }
         self.writer.indent();
-        for use_ in module_env.get_used_modules(false) {
+        for use_ in self
+            .with_importable_modules(module_env.get_type_display_ctx())
+            .used_modules
+        {
             emitln!(
                 self.writer,
                 "use {};",
@@ -132,7 +144,7 @@ impl<'a> Sourcifier<'a> {
             Self::amend_fun_name(self.env(), self.sym(fun_env.get_name()), self.amend),
             self.type_params(fun_env.get_type_parameters_ref())
         );
-        let tctx = fun_env.get_type_display_ctx();
+        let tctx = self.with_importable_modules(fun_env.get_type_display_ctx());
         let params = fun_env
             .get_parameters()
             .into_iter()
@@ -314,7 +326,7 @@ impl<'a> Sourcifier<'a> {
     /// Prints a struct (or enum) declaration.
     pub fn print_struct(&self, struct_id: QualifiedId<StructId>) {
         let struct_env = self.env().get_struct(struct_id);
-        let type_display_ctx = struct_env.get_type_display_ctx();
+        let type_display_ctx = self.with_importable_modules(struct_env.get_type_display_ctx());
         let ability_str = if !struct_env.get_abilities().is_empty() {
             format!(" has {}", self.abilities(", ", struct_env.get_abilities()))
         } else {
@@ -355,10 +367,6 @@ impl<'a> Sourcifier<'a> {
             );
             self.writer.indent();
             for fld in struct_env.get_fields() {
-                // TODO: currently we just filter the dummy field out, but the user might have
-                //   actually declared one. We could try heuristics on the code whether the
-                //   user intended to use the dummy field. (Its in a borrow field or in a
-                //   Unpack to an unused destination.)
                 if !self.is_dummy_field(&fld) {
                     emitln!(self.writer, "{},", self.field(&type_display_ctx, &fld))
                 }
@@ -373,9 +381,55 @@ impl<'a> Sourcifier<'a> {
         }
     }
 
-    /// Check for dummy field the compiler introduces for empty structs
+    /// Check for dummy field the compiler introduces for empty structs. A field named like the
+    /// dummy field is considered to be declared by the user if the code of the module selects
+    /// it, or packs it with a value other than `false`.
     fn is_dummy_field(&self, fld: &FieldEnv) -> bool {
-        fld.get_type().is_bool() && self.sym(fld.get_name()) == "dummy_field"
+        let struct_env = &fld.struct_env;
+        fld.get_type().is_bool()
+            && self.sym(fld.get_name()) == DUMMY_FIELD_NAME
+            && !struct_env.has_variants()
+            && struct_env.get_field_count() == 1
+            && !self
+                .declared_dummy_fields
+                .borrow_mut()
+                .entry(struct_env.module_env.get_id())
+                .or_insert_with(|| self.compute_declared_dummy_fields(&struct_env.module_env))
+                .contains(&struct_env.get_id())
+    }
+
+    /// Computes the structs of the module whose dummy field is used by the code of the module.
+    fn compute_declared_dummy_fields(&self, module_env: &ModuleEnv) -> BTreeSet<StructId> {
+        let mid = module_env.get_id();
+        let is_dummy_field = |fid: &FieldId| self.sym(fid.symbol()) == DUMMY_FIELD_NAME;
+        let mut result = BTreeSet::new();
+        for fun_env in module_env.get_functions() {
+            let Some(def) = fun_env.get_def() else {
+                continue;
+            };
+            def.visit_pre_order(&mut |e| {
+                if let ExpData::Call(_, oper, args) = e {
+                    match oper {
+                        Operation::Select(smid, sid, fid)
+                            if *smid == mid && is_dummy_field(fid) =>
+                        {
+                            result.insert(*sid);
+                        },
+                        Operation::Pack(smid, sid, None)
+                            if *smid == mid
+                                && args.first().is_some_and(|arg| {
+                                    !matches!(arg.as_ref(), ExpData::Value(_, Value::Bool(false)))
+                                }) =>
+                        {
+                            result.insert(*sid);
+                        },
+                        _ => {},
+                    }
+                }
+                true
+            });
+        }
+        result
     }
 
     fn field(&self, tctx: &TypeDisplayContext, field: &FieldEnv) -> String {
@@ -498,6 +552,32 @@ impl<'a> Sourcifier<'a> {
         emit!(self.writer, end)
     }
 
+    /// Restricts the used modules of the display context to those which can be imported by a
+    /// `use` declaration. Modules with the same name as the current module or another used
+    /// module are not imported, and referred to by their full name instead.
+    fn with_importable_modules<'b>(&self, tctx: TypeDisplayContext<'b>) -> TypeDisplayContext<'b> {
+        let env = self.env();
+        let name_of = |mid: &ModuleId| env.get_module(*mid).get_name().name();
+        let current_name = tctx.module_name.as_ref().map(|name| name.name());
+        let mut name_counts: BTreeMap<Symbol, usize> = BTreeMap::new();
+        for mid in &tctx.used_modules {
+            *name_counts.entry(name_of(mid)).or_default() += 1
+        }
+        let used_modules = tctx
+            .used_modules
+            .iter()
+            .filter(|mid| {
+                let name = name_of(mid);
+                name_counts[&name] == 1 && Some(name) != current_name
+            })
+            .cloned()
+            .collect();
+        TypeDisplayContext {
+            used_modules,
+            ..tctx
+        }
+    }
+
     fn module_qualifier(&self, tctx: &TypeDisplayContext, mid: ModuleId) -> String {
         let module_env = self.env().get_module(mid);
         if tctx.module_name.as_ref() == Some(module_env.get_name()) {
@@ -591,7 +671,7 @@ impl<'a> ExpSourcifier<'a> {
         exp: &Exp,
         amend: bool,
     ) -> Self {
-        let type_display_context = fun_env.get_type_display_ctx();
+        let type_display_context = parent.with_importable_modules(fun_env.get_type_display_ctx());
         let temp_names = (0..fun_env.get_parameter_count())
             .map(|i| (i, fun_env.get_local_name(i)))
             .collect();
@@ -728,6 +808,20 @@ impl<'a> ExpSourcifier<'a> {
                 }
             },
             IfElse(_, cond, if_exp, else_exp) => {
+                if let Some((cond, code, negate)) = Self::as_assert(cond, if_exp, else_exp) {
+                    // Print as the `assert!` macro, which expands to the same code
+                    emit!(self.wr(), "assert!(");
+                    if negate {
+                        emit!(self.wr(), "!");
+                        self.print_exp(Prio::Prefix, false, cond)
+                    } else {
+                        self.print_exp(Prio::General, false, cond)
+                    }
+                    emit!(self.wr(), ", ");
+                    self.print_exp(Prio::General, false, code);
+                    emit!(self.wr(), ")");
+                    return;
+                }
                 self.parenthesize(context_prio, Prio::General, || {
                     emit!(self.wr(), "if (");
                     self.print_exp(Prio::General, false, cond);
@@ -743,7 +837,16 @@ impl<'a> ExpSourcifier<'a> {
                             self.print_exp(Prio::General, is_result, if_exp)
                         }
                     } else {
-                        self.print_exp(Prio::General, is_result, if_exp);
+                        // Likewise, if the inner expression is an `if` without
+                        // `else`, the `else` would be attached to it.
+                        if matches!(if_exp.as_ref(),
+                            IfElse(_, _, _, inner_else) if inner_else.is_unit_exp())
+                        {
+                            self.parent
+                                .print_block(|| self.print_exp(Prio::General, is_result, if_exp))
+                        } else {
+                            self.print_exp(Prio::General, is_result, if_exp)
+                        }
                         emit!(self.wr(), " else ");
                         self.print_exp(Prio::General, is_result, else_exp);
                     }
@@ -792,7 +895,12 @@ impl<'a> ExpSourcifier<'a> {
                 };
                 let general_loop = || {
                     emit!(self.wr(), "loop ");
-                    print_loop_body(body)
+                    if body.is_loop_cont(Some(0), true) {
+                        // `loop continue` would be typed as a loop with an exit
+                        emit!(self.wr(), "()")
+                    } else {
+                        print_loop_body(body)
+                    }
                 };
 
                 // Detect while loop
@@ -906,6 +1014,31 @@ impl<'a> ExpSourcifier<'a> {
         }
     }
 
+    /// Determines whether a conditional is an `assert!(cond, code)`, either in the form
+    /// `if (cond) () else abort code` or `if (!cond) abort code`. Returns the condition, the
+    /// abort code, and whether the condition needs to be negated.
+    fn as_assert<'b>(
+        cond: &'b Exp,
+        if_exp: &'b Exp,
+        else_exp: &'b Exp,
+    ) -> Option<(&'b Exp, &'b Exp, bool)> {
+        let abort_code = |exp: &'b Exp| match exp.as_ref() {
+            ExpData::Call(_, Operation::Abort, args) if args.len() == 1 => Some(&args[0]),
+            _ => None,
+        };
+        if if_exp.is_unit_exp() {
+            Some((cond, abort_code(else_exp)?, false))
+        } else if else_exp.is_unit_exp() {
+            let code = abort_code(if_exp)?;
+            match cond.as_ref() {
+                ExpData::Call(_, Operation::Not, args) => Some((&args[0], code, false)),
+                _ => Some((cond, code, true)),
+            }
+        } else {
+            None
+        }
+    }
+
     fn print_exp_list(&self, open: &str, close: &str, exps: &[Exp]) {
         self.parent.print_list(open, ", ", close, exps.iter(), |e| {
             self.print_exp(Prio::General, false, e)
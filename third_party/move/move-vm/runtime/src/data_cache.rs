@@ -250,4 +250,28 @@ impl TransactionDataCache {
             PartialVMError::new(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR).with_message(msg);
         Err(err)
     }
+
+    /// Returns the printed values of all resources loaded into the data cache that currently
+    /// exist, together with their addresses and tags. Only used by debuggers.
+    #[cfg(any(debug_assertions, feature = "debugging"))]
+    pub(crate) fn debug_loaded_resources(&self) -> Vec<(AccountAddress, StructTag, String)> {
+        let mut resources = vec![];
+        for (addr, account_cache) in self.account_map.iter() {
+            for entry in account_cache.values() {
+                if !entry.value.exists().unwrap_or(false) {
+                    continue;
+                }
+                let mut buf = String::new();
+                let printed = entry
+                    .value
+                    .borrow_global()
+                    .and_then(|value| move_vm_types::values::debug::print_value(&mut buf, &value));
+                if printed.is_err() {
+                    buf = "<unavailable>".to_string();
+                }
+                resources.push((*addr, entry.struct_tag.clone(), buf));
+            }
+        }
+        resources
+    }
 }
@@ -2,8 +2,18 @@
 // Copyright (c) The Move Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{interpreter::InterpreterDebugInterface, LoadedFunction, RuntimeEnvironment};
-use move_binary_format::file_format::Bytecode;
+use crate::{
+    data_cache::TransactionDataCache, interpreter::InterpreterDebugInterface, LoadedFunction,
+    RuntimeEnvironment,
+};
+use move_binary_format::{
+    errors::PartialVMResult,
+    file_format::{Bytecode, CodeOffset, FunctionDefinitionIndex},
+};
+use move_core_types::{
+    account_address::AccountAddress,
+    language_storage::{ModuleId, StructTag},
+};
 use move_vm_types::values::{self, Locals};
 use std::{
     collections::BTreeSet,
@@ -11,6 +21,93 @@ use std::{
     str::FromStr,
 };
 
+/// A debugger that can be attached to the VM (see [crate::tracing::attach_debugger]). It is
+/// invoked before every instruction is executed, on the thread executing it, and may block
+/// that thread (e.g., while execution is paused at a breakpoint).
+pub trait Debugger: Send + Sync {
+    fn on_instruction(&self, state: &DebugState);
+}
+
+/// A view of the interpreter state right before an instruction is executed.
+pub struct DebugState<'a> {
+    function: &'a LoadedFunction,
+    locals: &'a Locals,
+    pc: CodeOffset,
+    instr: &'a Bytecode,
+    data_cache: &'a TransactionDataCache,
+    interpreter: &'a dyn InterpreterDebugInterface,
+}
+
+impl<'a> DebugState<'a> {
+    pub(crate) fn new(
+        function: &'a LoadedFunction,
+        locals: &'a Locals,
+        pc: CodeOffset,
+        instr: &'a Bytecode,
+        data_cache: &'a TransactionDataCache,
+        interpreter: &'a dyn InterpreterDebugInterface,
+    ) -> Self {
+        Self {
+            function,
+            locals,
+            pc,
+            instr,
+            data_cache,
+            interpreter,
+        }
+    }
+
+    /// Returns the module of the executing function (or None for scripts)
+    pub fn module_id(&self) -> Option<&ModuleId> {
+        self.function.module_id()
+    }
+
+    /// Returns the name of the executing function
+    pub fn function_name(&self) -> &str {
+        self.function.name()
+    }
+
+    /// Returns the definition index of the executing function in its module
+    pub fn function_index(&self) -> FunctionDefinitionIndex {
+        self.function.index()
+    }
+
+    /// Returns the offset of the instruction about to be executed
+    pub fn pc(&self) -> CodeOffset {
+        self.pc
+    }
+
+    /// Returns the instruction about to be executed
+    pub fn instruction(&self) -> &Bytecode {
+        self.instr
+    }
+
+    /// Returns the call stack, starting with the executing frame and followed by
+    /// its callers (i.e., the outermost frame is the last one).
+    pub fn call_stack(&self) -> Vec<(Option<ModuleId>, FunctionDefinitionIndex, CodeOffset)> {
+        let mut call_stack = vec![(self.module_id().cloned(), self.function_index(), self.pc)];
+        call_stack.extend(
+            self.interpreter
+                .get_stack_frames(usize::MAX)
+                .stack_trace()
+                .iter()
+                .cloned(),
+        );
+        call_stack
+    }
+
+    /// Returns the printed values of the locals of the executing frame, indexed by
+    /// local index. Uninitialized (or moved) locals are printed as "-".
+    pub fn locals(&self) -> PartialVMResult<Vec<String>> {
+        values::debug::locals_to_strings(self.locals)
+    }
+
+    /// Returns the printed values of the resources loaded by the transaction so far
+    pub fn loaded_resources(&self) -> Vec<(AccountAddress, StructTag, String)> {
+        self.data_cache.debug_loaded_resources()
+    }
+}
+
 #[derive(Debug)]
 enum DebugCommand {
     PrintStack,
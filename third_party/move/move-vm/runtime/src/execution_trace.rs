@@ -0,0 +1,214 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Structured execution traces. Unlike the tracing in [crate::tracing] (which is only compiled
+//! into debug builds), execution traces are available in all builds, and are recorded at runtime
+//! for the threads that start a [ExecutionTraceRecorder]. When no recorder is active, the VM only
+//! pays for a single atomic load per instruction.
+
+use move_binary_format::file_format::CodeOffset;
+use move_core_types::{account_address::AccountAddress, identifier::IdentStr};
+use serde::{Deserialize, Serialize};
+use std::{
+    cell::RefCell,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// The number of threads currently recording an execution trace
+static NUM_ACTIVE_RECORDERS: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    /// The events recorded on the current thread (if a recorder is active)
+    static RECORDED_EVENTS: RefCell<Option<Vec<TraceEvent>>> = const { RefCell::new(None) };
+}
+
+/// A single event of an execution trace
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum TraceEvent {
+    /// An instruction is about to be executed, with the given gas remaining
+    Instruction {
+        function: String,
+        pc: CodeOffset,
+        instruction: String,
+        gas_remaining: u64,
+    },
+    /// A resource was read from storage (i.e., its first access in the transaction)
+    ResourceRead {
+        address: AccountAddress,
+        resource: String,
+        bytes: u64,
+    },
+    /// A resource was (possibly) modified, i.e., moved to or from global
+    /// storage, or mutably borrowed.
+    ResourceWrite {
+        address: AccountAddress,
+        resource: String,
+    },
+    /// A resource group was read from storage (i.e., the first access to any of its
+    /// members in the transaction). Members are also recorded as resource reads.
+    ResourceGroupRead {
+        address: AccountAddress,
+        group: String,
+        bytes: u64,
+    },
+    /// A table entry was read from storage (i.e., its first access in the transaction).
+    /// The key is hex-encoded, and there are no bytes if the entry doesn't exist.
+    TableRead {
+        handle: AccountAddress,
+        key: String,
+        bytes: Option<u64>,
+    },
+    /// A module was read from storage (i.e., its first access in the transaction)
+    ModuleRead {
+        address: AccountAddress,
+        module: String,
+        bytes: u64,
+    },
+}
+
+/// Records the execution trace of everything executed by the VM on the current
+/// thread, until the recorder is finished (or dropped).
+pub struct ExecutionTraceRecorder {
+    // Recorders must be finished on the thread that started them
+    _not_send: std::marker::PhantomData<*const ()>,
+}
+
+impl ExecutionTraceRecorder {
+    /// Starts recording on the current thread. Any events recorded by a
+    /// previous recorder on this thread are discarded.
+    pub fn start() -> Self {
+        RECORDED_EVENTS.with(|events| {
+            if events.borrow_mut().replace(vec![]).is_none() {
+                NUM_ACTIVE_RECORDERS.fetch_add(1, Ordering::Relaxed);
+            }
+        });
+        Self {
+            _not_send: std::marker::PhantomData,
+        }
+    }
+
+    /// Stops recording and returns the recorded events
+    pub fn finish(self) -> Vec<TraceEvent> {
+        stop_recording()
+    }
+}
+
+impl Drop for ExecutionTraceRecorder {
+    fn drop(&mut self) {
+        stop_recording();
+    }
+}
+
+fn stop_recording() -> Vec<TraceEvent> {
+    RECORDED_EVENTS.with(|events| match events.borrow_mut().take() {
+        Some(events) => {
+            NUM_ACTIVE_RECORDERS.fetch_sub(1, Ordering::Relaxed);
+            events
+        },
+        None => vec![],
+    })
+}
+
+/// Returns true iff an execution trace is being recorded on the current thread
+#[inline]
+pub fn is_recording() -> bool {
+    NUM_ACTIVE_RECORDERS.load(Ordering::Relaxed) > 0
+        && RECORDED_EVENTS.with(|events| events.borrow().is_some())
+}
+
+/// Records the given event (if an execution trace is being recorded on the current thread).
+/// Adapters and native extensions use this to record the storage reads they perform.
+pub fn record(event: TraceEvent) {
+    RECORDED_EVENTS.with(|events| {
+        if let Some(events) = events.borrow_mut().as_mut() {
+            events.push(event);
+        }
+    });
+}
+
+/// Records a read of the table entry (if an execution trace is being recorded)
+pub fn record_table_read(handle: AccountAddress, key: &[u8], bytes: Option<u64>) {
+    if is_recording() {
+        record(TraceEvent::TableRead {
+            handle,
+            key: hex::encode(key),
+            bytes,
+        });
+    }
+}
+
+/// Records a read of the module (if an execution trace is being recorded)
+pub(crate) fn record_module_read(address: &AccountAddress, module: &IdentStr, bytes: u64) {
+    if is_recording() {
+        record(TraceEvent::ModuleRead {
+            address: *address,
+            module: module.to_string(),
+            bytes,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_instruction_event(pc: CodeOffset) -> TraceEvent {
+        TraceEvent::Instruction {
+            function: "0x1::m::f".to_string(),
+            pc,
+            instruction: "Ret".to_string(),
+            gas_remaining: 100,
+        }
+    }
+
+    #[test]
+    fn test_recorder() {
+        // Verify nothing is recorded without a recorder
+        assert!(!is_recording());
+        record(create_instruction_event(0));
+
+        // Record a few events and verify they're returned
+        let recorder = ExecutionTraceRecorder::start();
+        assert!(is_recording());
+        record(create_instruction_event(1));
+        record(create_instruction_event(2));
+        assert_eq!(recorder.finish(), vec![
+            create_instruction_event(1),
+            create_instruction_event(2)
+        ]);
+        assert!(!is_recording());
+
+        // Verify recording on other threads is independent
+        let recorder = ExecutionTraceRecorder::start();
+        std::thread::spawn(|| {
+            assert!(!is_recording());
+            record(create_instruction_event(3));
+        })
+        .join()
+        .unwrap();
+        assert!(recorder.finish().is_empty());
+
+        // Verify storage reads are recorded
+        let recorder = ExecutionTraceRecorder::start();
+        record_table_read(AccountAddress::ONE, &[0xAB, 0x01], None);
+        record_module_read(&AccountAddress::ONE, IdentStr::new("m").unwrap(), 10);
+        assert_eq!(recorder.finish(), vec![
+            TraceEvent::TableRead {
+                handle: AccountAddress::ONE,
+                key: "ab01".to_string(),
+                bytes: None,
+            },
+            TraceEvent::ModuleRead {
+                address: AccountAddress::ONE,
+                module: "m".to_string(),
+                bytes: 10,
+            },
+        ]);
+
+        // Verify dropping a recorder stops recording
+        let recorder = ExecutionTraceRecorder::start();
+        drop(recorder);
+        assert!(!is_recording());
+    }
+}
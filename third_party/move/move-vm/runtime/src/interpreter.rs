@@ -6,6 +6,7 @@ use crate::{
     access_control::AccessControlState,
     config::VMConfig,
     data_cache::{DataCacheEntry, TransactionDataCache},
+    execution_trace::{self, TraceEvent},
     frame::Frame,
     frame_type_cache::{
         AllRuntimeCaches, FrameTypeCache, NoRuntimeCaches, PerInstructionCache, RuntimeCacheTraits,
@@ -1125,9 +1126,35 @@ where
             entry.value().view(),
             bytes_loaded,
         )?;
+        if execution_trace::is_recording() {
+            execution_trace::record(TraceEvent::ResourceRead {
+                address: addr,
+                resource: self.resource_name(ty),
+                bytes: bytes_loaded.into(),
+            });
+        }
         Ok(entry)
     }
 
+    /// Returns the name of the resource type, used in execution traces
+    fn resource_name(&self, ty: &Type) -> String {
+        self.loader
+            .runtime_environment()
+            .ty_to_ty_tag(ty)
+            .map(|ty_tag| ty_tag.to_canonical_string())
+            .unwrap_or_else(|_| format!("{:?}", ty))
+    }
+
+    /// Records a (possible) write of the resource in the execution trace
+    fn record_resource_write(&self, addr: AccountAddress, ty: &Type) {
+        if execution_trace::is_recording() {
+            execution_trace::record(TraceEvent::ResourceWrite {
+                address: addr,
+                resource: self.resource_name(ty),
+            });
+        }
+    }
+
     /// Loads a resource from the data store and return the number of bytes read from the storage.
     fn load_resource<'c>(
         &self,
@@ -1196,6 +1223,9 @@ where
         self.operand_stack.push(res.map_err(|err| {
             err.with_message(format!("Failed to borrow global resource from {:?}", addr))
         })?)?;
+        if is_mut {
+            self.record_resource_write(addr, ty);
+        }
         Ok(())
     }
 
@@ -1298,6 +1328,7 @@ where
                     Some(&resource),
                 )?;
                 self.check_access(runtime_environment, AccessKind::Writes, ty, addr)?;
+                self.record_resource_write(addr, ty);
                 resource
             },
             Err(err) => {
@@ -1352,6 +1383,7 @@ where
                     true,
                 )?;
                 self.check_access(runtime_environment, AccessKind::Writes, ty, addr)?;
+                self.record_resource_write(addr, ty);
                 Ok(())
             },
             Err((err, resource)) => {
@@ -1793,8 +1825,17 @@ impl Frame {
                     self.pc,
                     instruction,
                     interpreter.loader.runtime_environment(),
+                    &*data_cache,
                     interpreter
                 );
+                if execution_trace::is_recording() {
+                    execution_trace::record(TraceEvent::Instruction {
+                        function: self.function.name_as_pretty_string(),
+                        pc: self.pc,
+                        instruction: format!("{:?}", instruction),
+                        gas_remaining: gas_meter.balance_internal().into(),
+                    });
+                }
 
                 fail_point!("move_vm::interpreter_loop", |_| {
                     Err(
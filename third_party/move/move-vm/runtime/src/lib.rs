@@ -8,6 +8,7 @@
 //! The core Move VM logic.
 
 pub mod data_cache;
+pub mod execution_trace;
 mod interpreter;
 mod loader;
 pub mod logging;
@@ -21,7 +22,7 @@ pub mod module_traversal;
 
 // Only include debugging functionality in debug builds
 #[cfg(any(debug_assertions, feature = "debugging"))]
-mod debug;
+pub mod debug;
 
 mod access_control;
 mod frame;
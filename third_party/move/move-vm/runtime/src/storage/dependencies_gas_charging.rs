@@ -1,7 +1,7 @@
 // Copyright (c) The Move Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{module_traversal::TraversalContext, ModuleStorage};
+use crate::{execution_trace, module_traversal::TraversalContext, ModuleStorage};
 use move_binary_format::{
     access::ModuleAccess,
     errors::{Location, VMResult},
@@ -87,6 +87,7 @@ where
                 NumBytes::new(size as u64),
             )
             .map_err(|err| err.finish(Location::Module(ModuleId::new(*addr, name.to_owned()))))?;
+        execution_trace::record_module_read(addr, name, size as u64);
 
         // Extend the lifetime of the module to the remainder of the function body
         // by storing it in an arena.
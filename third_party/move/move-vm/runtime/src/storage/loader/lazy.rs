@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    execution_trace,
     module_traversal::TraversalContext,
     storage::loader::traits::{
         FunctionDefinitionLoader, InstantiatedFunctionLoader, InstantiatedFunctionLoaderHelper,
@@ -71,6 +72,7 @@ where
                 name,
                 NumBytes::new(size as u64),
             )?;
+            execution_trace::record_module_read(addr, name, size as u64);
         }
         Ok(())
     }
@@ -3,9 +3,12 @@
 // SPDX-License-Identifier: Apache-2.0
 
 #[cfg(any(debug_assertions, feature = "debugging"))]
-use crate::debug::DebugContext;
+use crate::debug::{DebugContext, DebugState, Debugger};
 #[cfg(any(debug_assertions, feature = "debugging"))]
-use crate::{interpreter::InterpreterDebugInterface, loader::LoadedFunction, RuntimeEnvironment};
+use crate::{
+    data_cache::TransactionDataCache, interpreter::InterpreterDebugInterface,
+    loader::LoadedFunction, RuntimeEnvironment,
+};
 #[cfg(any(debug_assertions, feature = "debugging"))]
 use ::{
     move_binary_format::file_format::Bytecode,
@@ -15,7 +18,10 @@ use ::{
         env,
         fs::{File, OpenOptions},
         io::Write,
-        sync::Mutex,
+        sync::{
+            atomic::{AtomicBool, AtomicU64, Ordering},
+            Arc, Mutex, RwLock,
+        },
     },
 };
 
@@ -51,9 +57,39 @@ pub static LOGGING_FILE_WRITER: Lazy<Mutex<std::io::BufWriter<File>>> = Lazy::ne
     ))
 });
 
+#[cfg(any(debug_assertions, feature = "debugging"))]
+static NEXT_TRACE_THREAD_ID: AtomicU64 = AtomicU64::new(0);
+
+#[cfg(any(debug_assertions, feature = "debugging"))]
+thread_local! {
+    /// Identifies the thread in the trace, as the entries of concurrent executions interleave
+    static TRACE_THREAD_ID: u64 = NEXT_TRACE_THREAD_ID.fetch_add(1, Ordering::Relaxed);
+}
+
 #[cfg(any(debug_assertions, feature = "debugging"))]
 static DEBUG_CONTEXT: Lazy<Mutex<DebugContext>> = Lazy::new(|| Mutex::new(DebugContext::new()));
 
+#[cfg(any(debug_assertions, feature = "debugging"))]
+static DEBUGGER_ATTACHED: AtomicBool = AtomicBool::new(false);
+
+#[cfg(any(debug_assertions, feature = "debugging"))]
+static DEBUGGER: Lazy<RwLock<Option<Arc<dyn Debugger>>>> = Lazy::new(|| RwLock::new(None));
+
+/// Attaches the given debugger to the VM. The debugger is invoked before every
+/// instruction executed by any VM in this process, until it is detached.
+#[cfg(any(debug_assertions, feature = "debugging"))]
+pub fn attach_debugger(debugger: Arc<dyn Debugger>) {
+    *DEBUGGER.write().unwrap() = Some(debugger);
+    DEBUGGER_ATTACHED.store(true, Ordering::Release);
+}
+
+/// Detaches the currently attached debugger (if any)
+#[cfg(any(debug_assertions, feature = "debugging"))]
+pub fn detach_debugger() {
+    DEBUGGER_ATTACHED.store(false, Ordering::Release);
+    *DEBUGGER.write().unwrap() = None;
+}
+
 // Only include in debug builds
 #[cfg(any(debug_assertions, feature = "debugging"))]
 pub(crate) fn trace(
@@ -62,15 +98,32 @@ pub(crate) fn trace(
     pc: u16,
     instr: &Bytecode,
     runtime_environment: &RuntimeEnvironment,
+    data_cache: &TransactionDataCache,
     interpreter: &dyn InterpreterDebugInterface,
 ) {
+    if DEBUGGER_ATTACHED.load(Ordering::Acquire) {
+        // Clone the debugger so that it can be detached while execution is paused
+        let debugger = DEBUGGER.read().unwrap().clone();
+        if let Some(debugger) = debugger {
+            debugger.on_instruction(&DebugState::new(
+                function,
+                locals,
+                pc,
+                instr,
+                data_cache,
+                interpreter,
+            ));
+        }
+    }
     if *TRACING_ENABLED {
+        let thread_id = TRACE_THREAD_ID.with(|id| *id);
         let buf_writer = &mut *LOGGING_FILE_WRITER.lock().unwrap();
         buf_writer
             .write_fmt(format_args!(
-                "{},{}\n",
+                "{},{},{}\n",
                 function.name_as_pretty_string(),
                 pc,
+                thread_id,
             ))
             .unwrap();
         buf_writer.flush().unwrap();
@@ -89,9 +142,25 @@ pub(crate) fn trace(
 
 #[macro_export]
 macro_rules! trace {
-    ($function_desc:expr, $locals:expr, $pc:expr, $instr:tt, $resolver:expr, $interp:expr) => {
+    (
+        $function_desc:expr,
+        $locals:expr,
+        $pc:expr,
+        $instr:tt,
+        $resolver:expr,
+        $data_cache:expr,
+        $interp:expr
+    ) => {
         // Only include this code in debug releases
         #[cfg(any(debug_assertions, feature = "debugging"))]
-        $crate::tracing::trace(&$function_desc, $locals, $pc, &$instr, $resolver, $interp)
+        $crate::tracing::trace(
+            &$function_desc,
+            $locals,
+            $pc,
+            &$instr,
+            $resolver,
+            $data_cache,
+            $interp,
+        )
     };
 }
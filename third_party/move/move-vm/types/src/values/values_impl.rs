@@ -3794,6 +3794,18 @@ pub mod debug {
         Ok(())
     }
 
+    /// Returns the printed value of each local, indexed by local index. Used by
+    /// debuggers that display locals individually.
+    pub fn locals_to_strings(locals: &Locals) -> PartialVMResult<Vec<String>> {
+        let mut values = vec![];
+        for val in locals.0.borrow().iter() {
+            let mut buf = String::new();
+            print_value_impl(&mut buf, val)?;
+            values.push(buf);
+        }
+        Ok(values)
+    }
+
     pub fn print_value<B: Write>(buf: &mut B, val: &Value) -> PartialVMResult<()> {
         print_value_impl(buf, &val.0)
     }
@@ -7,8 +7,9 @@ use anyhow::{bail, Result};
 use clap::*;
 use codespan_reporting::term::{termcolor, termcolor::StandardStream};
 use legacy_move_compiler::{
+    compiled_unit::CompiledUnit,
     shared::{NumberFormat, NumericalAddress},
-    unit_test::TestPlan,
+    unit_test::{NamedOrBytecodeModule, TestPlan},
 };
 use move_command_line_common::files::{FileHash, MOVE_COVERAGE_MAP_EXTENSION};
 use move_compiler_v2::plan_builder as plan_builder_v2;
@@ -261,12 +262,21 @@ pub fn run_move_unit_tests_with_factory<W: Write + Send, F: UnitTestFactory + Se
     files.extend(dep_file_map);
     let test_plan = test_plan.unwrap();
     let no_tests = test_plan.is_empty();
-    let test_plan = TestPlan::new(
+    let mut test_plan = TestPlan::new(
         test_plan,
         files,
         units,
         compiled_package.bytecode_deps.into_values().collect(),
     );
+    // Dependencies loaded from the compilation cache are not compiled by the driver
+    for (_, unit) in compiled_package.deps_compiled_units {
+        if let CompiledUnit::Module(module) = unit.unit {
+            test_plan
+                .module_info
+                .entry(module.module.self_id())
+                .or_insert(NamedOrBytecodeModule::Named(module));
+        }
+    }
 
     let trace_path = pkg_path.join(".trace");
     let coverage_map_path = pkg_path
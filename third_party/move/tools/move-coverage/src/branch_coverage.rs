@@ -0,0 +1,267 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+use crate::coverage_map::ModuleCoverageMap;
+use move_binary_format::{
+    access::ModuleAccess,
+    file_format::{Bytecode, CodeOffset, FunctionDefinitionIndex},
+    CompiledModule,
+};
+use move_bytecode_source_map::source_map::SourceMap;
+use move_core_types::identifier::Identifier;
+use move_ir_types::location::Loc;
+use serde::Serialize;
+
+/// Coverage of a conditional branch instruction. Each outgoing edge of the instruction in the
+/// control flow graph of its function is an outcome of the branch.
+#[derive(Clone, Debug, Serialize)]
+pub struct BranchCoverage {
+    pub function_name: Identifier,
+    pub code_offset: CodeOffset,
+    /// The source location of the branch instruction, which is the location of the conditional
+    /// it was compiled from.
+    pub loc: Option<Loc>,
+    /// Whether the branch instruction was executed at all.
+    pub executed: bool,
+    /// The outcomes of the branch, in order of their target code offset.
+    pub outcomes: Vec<BranchOutcome>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct BranchOutcome {
+    pub target: CodeOffset,
+    /// The number of times the branch went to `target`.
+    pub count: u64,
+}
+
+impl BranchCoverage {
+    pub fn covered_outcomes(&self) -> usize {
+        self.outcomes
+            .iter()
+            .filter(|outcome| outcome.count > 0)
+            .count()
+    }
+}
+
+/// Computes the coverage of all conditional branches in `module`.
+///
+/// How often a branch jumped to its target comes from the jumps recorded in `module_map`.
+/// How often it fell through to the next instruction is the number of times the branch was
+/// executed, less the number of jumps taken.
+pub fn branch_coverage(
+    module: &CompiledModule,
+    source_map: &SourceMap,
+    module_map: Option<&ModuleCoverageMap>,
+) -> Vec<BranchCoverage> {
+    let mut branches = vec![];
+    for (function_def_idx, function_def) in module.function_defs().iter().enumerate() {
+        let code = match &function_def.code {
+            Some(code_unit) => &code_unit.code,
+            None => continue,
+        };
+        let fn_handle = module.function_handle_at(function_def.function);
+        let fn_name = module.identifier_at(fn_handle.name);
+        let function_coverage = module_map.and_then(|map| map.get_function_coverage(fn_name));
+        let jump_coverage = module_map.and_then(|map| map.get_jump_coverage(fn_name));
+
+        for (code_offset, instr) in code.iter().enumerate() {
+            let code_offset = code_offset as CodeOffset;
+            let successors = Bytecode::get_successors(code_offset, code);
+            if !instr.is_conditional_branch() || successors.len() < 2 {
+                continue;
+            }
+            let executions = function_coverage
+                .and_then(|coverage| coverage.get(&(code_offset as u64)))
+                .copied()
+                .unwrap_or(0);
+            let jumps = |target: CodeOffset| {
+                jump_coverage
+                    .and_then(|coverage| coverage.get(&(code_offset as u64, target as u64)))
+                    .copied()
+                    .unwrap_or(0)
+            };
+            let jumped: u64 = successors
+                .iter()
+                .filter(|target| **target != code_offset + 1)
+                .map(|target| jumps(*target))
+                .sum();
+            let outcomes = successors
+                .iter()
+                .map(|target| BranchOutcome {
+                    target: *target,
+                    count: if *target == code_offset + 1 {
+                        executions.saturating_sub(jumped)
+                    } else {
+                        jumps(*target)
+                    },
+                })
+                .collect();
+            branches.push(BranchCoverage {
+                function_name: fn_name.to_owned(),
+                code_offset,
+                loc: source_map
+                    .get_code_location(
+                        FunctionDefinitionIndex(function_def_idx as u16),
+                        code_offset,
+                    )
+                    .ok(),
+                executed: executions > 0,
+                outcomes,
+            });
+        }
+    }
+    branches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::branch_coverage;
+    use crate::coverage_map::ModuleCoverageMap;
+    use move_binary_format::{
+        file_format::{basic_test_module, Bytecode, FunctionDefinitionIndex},
+        CompiledModule,
+    };
+    use move_bytecode_source_map::source_map::SourceMap;
+    use move_command_line_common::files::FileHash;
+    use move_core_types::identifier::Identifier;
+    use move_ir_types::location::Loc;
+
+    /// A module with a function `foo` whose code is given
+    fn module(code: Vec<Bytecode>) -> CompiledModule {
+        let mut module = basic_test_module();
+        module.function_defs[0].code.as_mut().unwrap().code = code;
+        module
+    }
+
+    fn loc(start: u32, end: u32) -> Loc {
+        Loc::new(FileHash::new("module"), start, end)
+    }
+
+    /// A source map placing the code of `foo` at the given offsets at the given locations
+    fn source_map(locs: &[(u16, Loc)]) -> SourceMap {
+        let mut source_map = SourceMap::new(loc(0, 100), None);
+        source_map
+            .add_top_level_function_mapping(FunctionDefinitionIndex(0), loc(0, 100), false)
+            .unwrap();
+        for (code_offset, loc) in locs {
+            source_map
+                .add_code_mapping(FunctionDefinitionIndex(0), *code_offset, *loc)
+                .unwrap();
+        }
+        source_map
+    }
+
+    fn module_map(
+        module: &CompiledModule,
+        executions: &[(u64, u64)],
+        jumps: &[((u64, u64), u64)],
+    ) -> ModuleCoverageMap {
+        let foo = Identifier::new("foo").unwrap();
+        let mut module_map = ModuleCoverageMap::new(
+            *module.self_id().address(),
+            module.self_id().name().to_owned(),
+        );
+        for (code_offset, count) in executions {
+            module_map.insert_multi(foo.clone(), *code_offset, *count);
+        }
+        for ((from, to), count) in jumps {
+            module_map.insert_jump_multi(foo.clone(), *from, *to, *count);
+        }
+        module_map
+    }
+
+    #[test]
+    fn test_branch_outcomes() {
+        // 0: LdTrue, 1: BrTrue(4), 2: LdFalse, 3: Ret, 4: Ret
+        let module = module(vec![
+            Bytecode::LdTrue,
+            Bytecode::BrTrue(4),
+            Bytecode::LdFalse,
+            Bytecode::Ret,
+            Bytecode::Ret,
+        ]);
+        let source_map = source_map(&[(0, loc(10, 20)), (1, loc(20, 30)), (2, loc(30, 40))]);
+        // The branch was executed 5 times, and jumped twice
+        let module_map = module_map(&module, &[(1, 5), (2, 3), (4, 2)], &[((1, 4), 2)]);
+
+        let branches = branch_coverage(&module, &source_map, Some(&module_map));
+        assert_eq!(branches.len(), 1);
+        let branch = &branches[0];
+        assert_eq!(branch.function_name.as_str(), "foo");
+        assert_eq!(branch.code_offset, 1);
+        assert_eq!(branch.loc, Some(loc(20, 30)));
+        assert!(branch.executed);
+        let outcomes: Vec<_> = branch
+            .outcomes
+            .iter()
+            .map(|outcome| (outcome.target, outcome.count))
+            .collect();
+        assert_eq!(outcomes, vec![(2, 3), (4, 2)]);
+        assert_eq!(branch.covered_outcomes(), 2);
+    }
+
+    #[test]
+    fn test_branch_outcome_not_taken() {
+        // 0: LdFalse, 1: BrFalse(3), 2: Ret, 3: Ret
+        let module = module(vec![
+            Bytecode::LdFalse,
+            Bytecode::BrFalse(3),
+            Bytecode::Ret,
+            Bytecode::Ret,
+        ]);
+        let source_map = source_map(&[(0, loc(10, 20))]);
+        // The branch always jumped, and never fell through
+        let module_map = module_map(&module, &[(1, 4), (3, 4)], &[((1, 3), 4)]);
+
+        let branches = branch_coverage(&module, &source_map, Some(&module_map));
+        assert_eq!(branches.len(), 1);
+        let branch = &branches[0];
+        // The location of the branch is the one of the code before it
+        assert_eq!(branch.loc, Some(loc(10, 20)));
+        let outcomes: Vec<_> = branch
+            .outcomes
+            .iter()
+            .map(|outcome| (outcome.target, outcome.count))
+            .collect();
+        assert_eq!(outcomes, vec![(2, 0), (3, 4)]);
+        assert_eq!(branch.covered_outcomes(), 1);
+    }
+
+    #[test]
+    fn test_branch_not_executed() {
+        let module = module(vec![
+            Bytecode::LdTrue,
+            Bytecode::BrTrue(3),
+            Bytecode::Ret,
+            Bytecode::Ret,
+        ]);
+        let source_map = source_map(&[]);
+
+        let branches = branch_coverage(&module, &source_map, None);
+        assert_eq!(branches.len(), 1);
+        let branch = &branches[0];
+        assert_eq!(branch.loc, None);
+        assert!(!branch.executed);
+        assert!(branch.outcomes.iter().all(|outcome| outcome.count == 0));
+        assert_eq!(branch.covered_outcomes(), 0);
+    }
+
+    #[test]
+    fn test_only_conditional_branches() {
+        // An unconditional branch, and a conditional branch whose outcomes are the same code
+        // offset, have a single outcome and are not reported
+        let module = module(vec![
+            Bytecode::Branch(2),
+            Bytecode::Ret,
+            Bytecode::LdTrue,
+            Bytecode::BrTrue(4),
+            Bytecode::Ret,
+        ]);
+        let source_map = source_map(&[]);
+        let module_map = module_map(&module, &[(0, 1), (2, 1), (3, 1), (4, 1)], &[((0, 2), 1)]);
+
+        assert!(branch_coverage(&module, &source_map, Some(&module_map)).is_empty());
+    }
+}
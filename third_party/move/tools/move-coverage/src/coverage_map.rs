@@ -21,6 +21,12 @@ use std::{
 /// Map from code offset in a function to the number of times it was executed.
 pub type FunctionCoverage = BTreeMap<u64, u64>;
 
+/// Map from a jump in a function, given by the code offset it was taken at and the code offset
+/// it went to, to the number of times it was taken. Only transfers of control to other than the
+/// next code offset are recorded: how often execution fell through to the next code offset
+/// follows from the execution counts.
+pub type JumpCoverage = BTreeMap<(u64, u64), u64>;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CoverageMap {
     pub exec_maps: BTreeMap<String, ExecCoverageMap>,
@@ -31,6 +37,8 @@ pub struct ModuleCoverageMap {
     pub module_addr: AccountAddress,
     pub module_name: Identifier,
     pub function_maps: BTreeMap<Identifier, FunctionCoverage>,
+    #[serde(default)]
+    pub jump_maps: BTreeMap<Identifier, JumpCoverage>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -58,6 +66,56 @@ pub struct TraceMap {
     pub exec_maps: BTreeMap<String, Vec<TraceEntry>>,
 }
 
+/// The layout of a coverage map before jumps were recorded
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(Serialize))]
+struct LegacyCoverageMap {
+    exec_maps: BTreeMap<String, LegacyExecCoverageMap>,
+}
+
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(Serialize))]
+struct LegacyExecCoverageMap {
+    exec_id: String,
+    module_maps: BTreeMap<(AccountAddress, Identifier), LegacyModuleCoverageMap>,
+}
+
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(Serialize))]
+struct LegacyModuleCoverageMap {
+    module_addr: AccountAddress,
+    module_name: Identifier,
+    function_maps: BTreeMap<Identifier, FunctionCoverage>,
+}
+
+impl From<LegacyCoverageMap> for CoverageMap {
+    fn from(legacy: LegacyCoverageMap) -> Self {
+        let exec_maps = legacy
+            .exec_maps
+            .into_iter()
+            .map(|(exec_id, exec_map)| {
+                let module_maps = exec_map
+                    .module_maps
+                    .into_iter()
+                    .map(|(key, module_map)| {
+                        (key, ModuleCoverageMap {
+                            module_addr: module_map.module_addr,
+                            module_name: module_map.module_name,
+                            function_maps: module_map.function_maps,
+                            jump_maps: BTreeMap::new(),
+                        })
+                    })
+                    .collect();
+                (exec_id, ExecCoverageMap {
+                    exec_id: exec_map.exec_id,
+                    module_maps,
+                })
+            })
+            .collect();
+        CoverageMap { exec_maps }
+    }
+}
+
 impl CoverageMap {
     /// Takes in a file containing a raw VM trace, and returns an updated coverage map.
     pub fn update_coverage_from_trace_file<P: AsRef<Path> + std::fmt::Debug>(
@@ -66,6 +124,9 @@ impl CoverageMap {
     ) -> Result<Self> {
         let file = File::open(filename)
             .map_err(|e| format_err!("Unable to open coverage trace file {:?}: {}", filename, e))?;
+        // The previous entry of the trace of each thread, to find the jumps taken within a
+        // function. The entries of tests running concurrently interleave in the trace.
+        let mut previous: BTreeMap<String, (String, u64)> = BTreeMap::new();
         for line in BufReader::new(file).lines() {
             let line = line?;
             let mut splits = line.split(',');
@@ -73,6 +134,17 @@ impl CoverageMap {
             let exec_id = "dummy_exec_id";
             let context = splits.next().unwrap();
             let pc = splits.next().unwrap().parse::<u64>().unwrap();
+            // Traces of older VMs do not identify the thread
+            let thread_id = splits.next().unwrap_or_default();
+            let jumped_from =
+                match previous.insert(thread_id.to_string(), (context.to_string(), pc)) {
+                    Some((previous_context, previous_pc))
+                        if previous_context == context && previous_pc + 1 != pc =>
+                    {
+                        Some(previous_pc)
+                    },
+                    _ => None,
+                };
 
             let mut context_segs: Vec<_> = context.split("::").collect();
             let is_script = context_segs.len() == 2;
@@ -81,6 +153,16 @@ impl CoverageMap {
                 let module_name = Identifier::new(context_segs.pop().unwrap()).unwrap();
                 let module_addr =
                     AccountAddress::from_hex_literal(context_segs.pop().unwrap()).unwrap();
+                if let Some(from_pc) = jumped_from {
+                    self.insert_jump(
+                        exec_id,
+                        module_addr,
+                        module_name.clone(),
+                        func_name.clone(),
+                        from_pc,
+                        pc,
+                    );
+                }
                 self.insert(exec_id, module_addr, module_name, func_name, pc);
             } else {
                 // Don't count scripts (for now)
@@ -109,7 +191,14 @@ impl CoverageMap {
             .read_to_end(&mut bytes)
             .ok()
             .ok_or_else(|| format_err!("Unable to read coverage map"))?;
+        // Maps written before jumps were recorded have no jump maps. As BCS does not encode
+        // field names, missing fields are not defaulted, so these are read in their old layout.
         bcs::from_bytes(&bytes)
+            .or_else(|err| {
+                bcs::from_bytes::<LegacyCoverageMap>(&bytes)
+                    .map(CoverageMap::from)
+                    .map_err(|_| err)
+            })
             .with_context(|| format!("Deserializing coverage map from binary file {:?}", filename))
     }
 
@@ -129,6 +218,22 @@ impl CoverageMap {
         exec_entry.insert(module_addr, module_name, func_name, pc);
     }
 
+    pub fn insert_jump(
+        &mut self,
+        exec_id: &str,
+        module_addr: AccountAddress,
+        module_name: Identifier,
+        func_name: Identifier,
+        from_pc: u64,
+        to_pc: u64,
+    ) {
+        let exec_entry = self
+            .exec_maps
+            .entry(exec_id.to_owned())
+            .or_insert_with(|| ExecCoverageMap::new(exec_id.to_owned()));
+        exec_entry.insert_jump_multi(module_addr, module_name, func_name, from_pc, to_pc, 1);
+    }
+
     pub fn to_unified_exec_map(&self) -> ExecCoverageMap {
         let mut unified_map = ExecCoverageMap::new(String::new());
         for (_, exec_map) in self.exec_maps.iter() {
@@ -144,6 +249,18 @@ impl CoverageMap {
                         );
                     }
                 }
+                for (func_name, jump_map) in module_map.jump_maps.iter() {
+                    for ((from_pc, to_pc), count) in jump_map.iter() {
+                        unified_map.insert_jump_multi(
+                            *module_addr,
+                            module_name.clone(),
+                            func_name.clone(),
+                            *from_pc,
+                            *to_pc,
+                            *count,
+                        );
+                    }
+                }
             }
         }
         unified_map
@@ -156,6 +273,7 @@ impl ModuleCoverageMap {
             module_addr,
             module_name,
             function_maps: BTreeMap::new(),
+            jump_maps: BTreeMap::new(),
         }
     }
 
@@ -169,15 +287,34 @@ impl ModuleCoverageMap {
         self.insert_multi(func_name, pc, 1);
     }
 
+    pub fn insert_jump_multi(
+        &mut self,
+        func_name: Identifier,
+        from_pc: u64,
+        to_pc: u64,
+        count: u64,
+    ) {
+        let func_entry = self.jump_maps.entry(func_name).or_default();
+        let jump_entry = func_entry.entry((from_pc, to_pc)).or_insert(0);
+        *jump_entry += count;
+    }
+
     pub fn merge(&mut self, another: ModuleCoverageMap) {
         for (key, val) in another.function_maps {
             self.function_maps.entry(key).or_default().extend(val);
         }
+        for (key, val) in another.jump_maps {
+            self.jump_maps.entry(key).or_default().extend(val);
+        }
     }
 
     pub fn get_function_coverage(&self, func_name: &IdentStr) -> Option<&FunctionCoverage> {
         self.function_maps.get(func_name)
     }
+
+    pub fn get_jump_coverage(&self, func_name: &IdentStr) -> Option<&JumpCoverage> {
+        self.jump_maps.get(func_name)
+    }
 }
 
 impl ExecCoverageMap {
@@ -213,6 +350,22 @@ impl ExecCoverageMap {
         self.insert_multi(module_addr, module_name, func_name, pc, 1);
     }
 
+    pub fn insert_jump_multi(
+        &mut self,
+        module_addr: AccountAddress,
+        module_name: Identifier,
+        func_name: Identifier,
+        from_pc: u64,
+        to_pc: u64,
+        count: u64,
+    ) {
+        let module_entry = self
+            .module_maps
+            .entry((module_addr, module_name.clone()))
+            .or_insert_with(|| ModuleCoverageMap::new(module_addr, module_name));
+        module_entry.insert_jump_multi(func_name, from_pc, to_pc, count);
+    }
+
     pub fn into_coverage_map_with_modules(
         self,
         modules: BTreeMap<AccountAddress, BTreeMap<Identifier, (String, CompiledModule)>>,
@@ -348,3 +501,70 @@ pub fn output_map_to_file<M: Serialize, P: AsRef<Path> + std::fmt::Debug>(
     file.write_all(&bytes)?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{CoverageMap, LegacyCoverageMap, LegacyExecCoverageMap, LegacyModuleCoverageMap};
+    use move_core_types::{account_address::AccountAddress, identifier::Identifier};
+    use std::{collections::BTreeMap, io::Write};
+
+    fn coverage_map_of_trace(trace: &str) -> CoverageMap {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(trace.as_bytes()).unwrap();
+        CoverageMap::from_trace_file(&file.path()).unwrap()
+    }
+
+    #[test]
+    fn test_jumps_of_interleaved_threads() {
+        // Two threads execute `f` concurrently: thread 0 jumps from 1 to 3, while thread 1
+        // runs through the code in order
+        let coverage_map = coverage_map_of_trace(
+            "0x1::m::f,0,0\n\
+             0x1::m::f,0,1\n\
+             0x1::m::f,1,0\n\
+             0x1::m::f,1,1\n\
+             0x1::m::f,3,0\n\
+             0x1::m::f,2,1\n\
+             0x1::m::f,3,1\n",
+        );
+        let exec_map = coverage_map.to_unified_exec_map();
+        let module_map = exec_map.module_maps.values().next().unwrap();
+        let f = Identifier::new("f").unwrap();
+        assert_eq!(
+            module_map.get_function_coverage(&f),
+            Some(&BTreeMap::from([(0, 2), (1, 2), (2, 1), (3, 2)]))
+        );
+        assert_eq!(
+            module_map.get_jump_coverage(&f),
+            Some(&BTreeMap::from([((1, 3), 1)]))
+        );
+    }
+
+    #[test]
+    fn test_legacy_coverage_map() {
+        let module_name = Identifier::new("m").unwrap();
+        let f = Identifier::new("f").unwrap();
+        let module_map = LegacyModuleCoverageMap {
+            module_addr: AccountAddress::ONE,
+            module_name: module_name.clone(),
+            function_maps: BTreeMap::from([(f.clone(), BTreeMap::from([(0, 1), (1, 1)]))]),
+        };
+        let legacy = LegacyCoverageMap {
+            exec_maps: BTreeMap::from([("exec".to_string(), LegacyExecCoverageMap {
+                exec_id: "exec".to_string(),
+                module_maps: BTreeMap::from([((AccountAddress::ONE, module_name), module_map)]),
+            })]),
+        };
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(&bcs::to_bytes(&legacy).unwrap()).unwrap();
+
+        let coverage_map = CoverageMap::from_binary_file(&file.path()).unwrap();
+        let exec_map = coverage_map.to_unified_exec_map();
+        let module_map = exec_map.module_maps.values().next().unwrap();
+        assert_eq!(
+            module_map.get_function_coverage(&f),
+            Some(&BTreeMap::from([(0, 1), (1, 1)]))
+        );
+        assert!(module_map.jump_maps.is_empty());
+    }
+}
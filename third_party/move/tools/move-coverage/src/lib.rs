@@ -6,7 +6,9 @@ use crate::summary::ModuleSummary;
 use move_binary_format::CompiledModule;
 use std::io::Write;
 
+pub mod branch_coverage;
 pub mod coverage_map;
+pub mod report;
 pub mod source_coverage;
 pub mod summary;
 
@@ -0,0 +1,456 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+//! Export of coverage in the lcov tracefile and Cobertura XML formats understood by CI tooling.
+
+use crate::{branch_coverage::branch_coverage, coverage_map::ExecCoverageMap};
+use anyhow::{Context, Result};
+use move_binary_format::{
+    access::ModuleAccess,
+    file_format::{CodeOffset, FunctionDefinitionIndex},
+    CompiledModule,
+};
+use move_bytecode_source_map::source_map::SourceMap;
+use move_ir_types::location::Loc;
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Line-level coverage of a set of source files, with functions and branches.
+#[derive(Clone, Debug, Default)]
+pub struct CoverageReport {
+    pub files: BTreeMap<PathBuf, FileCoverage>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct FileCoverage {
+    /// The functions defined in the file, by qualified name.
+    pub functions: BTreeMap<String, FunctionHits>,
+    /// Execution counts of the lines with code, by line number, starting at 1.
+    pub lines: BTreeMap<u32, u64>,
+    /// The branches in the file, in order of their line.
+    pub branches: Vec<LineBranch>,
+}
+
+#[derive(Clone, Debug)]
+pub struct FunctionHits {
+    /// The line the function is declared at.
+    pub line: u32,
+    /// The number of times the function was called.
+    pub hits: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct LineBranch {
+    pub line: u32,
+    /// The number of times each outcome of the branch was taken, none if the branch was never
+    /// executed.
+    pub outcomes: Vec<Option<u64>>,
+}
+
+/// Byte offsets of the start of each line of a file.
+struct LineIndex(Vec<u32>);
+
+impl LineIndex {
+    fn new(contents: &str) -> Self {
+        let starts = std::iter::once(0)
+            .chain(contents.match_indices('\n').map(|(i, _)| i as u32 + 1))
+            .collect();
+        Self(starts)
+    }
+
+    /// The line, starting at 1, of the given byte offset.
+    fn line(&self, offset: u32) -> u32 {
+        self.0.partition_point(|start| *start <= offset) as u32
+    }
+}
+
+impl FileCoverage {
+    fn branches_covered(&self) -> usize {
+        self.branches
+            .iter()
+            .flat_map(|branch| branch.outcomes.iter())
+            .filter(|count| count.unwrap_or(0) > 0)
+            .count()
+    }
+
+    fn branches_valid(&self) -> usize {
+        self.branches
+            .iter()
+            .map(|branch| branch.outcomes.len())
+            .sum()
+    }
+
+    fn lines_covered(&self) -> usize {
+        self.lines.values().filter(|count| **count > 0).count()
+    }
+}
+
+impl CoverageReport {
+    /// Adds the coverage of a module compiled from the source file at `source_path`. Code the
+    /// source map attributes to other files, such as inlined functions, is left out.
+    pub fn add_module(
+        &mut self,
+        module: &CompiledModule,
+        source_map: &SourceMap,
+        source_path: &Path,
+        exec_map: &ExecCoverageMap,
+    ) -> Result<()> {
+        let contents = fs::read_to_string(source_path)
+            .with_context(|| format!("Unable to read source file {}", source_path.display()))?;
+        let lines = LineIndex::new(&contents);
+        let file_hash = source_map.definition_location.file_hash();
+        let line_of = |loc: &Loc| (loc.file_hash() == file_hash).then(|| lines.line(loc.start()));
+
+        let module_id = module.self_id();
+        let module_map = exec_map
+            .module_maps
+            .get(&(*module_id.address(), module_id.name().to_owned()));
+        let file = self.files.entry(source_path.to_path_buf()).or_default();
+
+        for (function_def_idx, function_def) in module.function_defs().iter().enumerate() {
+            let code_unit = match &function_def.code {
+                Some(code_unit) => code_unit,
+                None => continue,
+            };
+            let function_def_idx = FunctionDefinitionIndex(function_def_idx as u16);
+            let fn_handle = module.function_handle_at(function_def.function);
+            let fn_name = module.identifier_at(fn_handle.name);
+            let function_coverage = module_map.and_then(|map| map.get_function_coverage(fn_name));
+            let count_at = |code_offset: usize| {
+                function_coverage
+                    .and_then(|coverage| coverage.get(&(code_offset as u64)))
+                    .copied()
+                    .unwrap_or(0)
+            };
+
+            if let Some(line) = source_map
+                .get_function_source_map(function_def_idx)
+                .ok()
+                .and_then(|function_map| line_of(&function_map.definition_location))
+            {
+                file.functions
+                    .insert(format!("{}::{}", module_id.name(), fn_name), FunctionHits {
+                        line,
+                        hits: count_at(0),
+                    });
+            }
+            for code_offset in 0..code_unit.code.len() {
+                let line = match source_map
+                    .get_code_location(function_def_idx, code_offset as CodeOffset)
+                    .ok()
+                    .and_then(|loc| line_of(&loc))
+                {
+                    Some(line) => line,
+                    None => continue,
+                };
+                let count = file.lines.entry(line).or_insert(0);
+                *count = (*count).max(count_at(code_offset));
+            }
+        }
+
+        for branch in branch_coverage(module, source_map, module_map) {
+            if let Some(line) = branch.loc.as_ref().and_then(line_of) {
+                file.branches.push(LineBranch {
+                    line,
+                    outcomes: branch
+                        .outcomes
+                        .iter()
+                        .map(|outcome| branch.executed.then_some(outcome.count))
+                        .collect(),
+                });
+            }
+        }
+        file.branches.sort_by_key(|branch| branch.line);
+        Ok(())
+    }
+
+    /// Writes the report as an lcov tracefile.
+    pub fn write_lcov<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        for (path, file) in &self.files {
+            writeln!(w, "TN:")?;
+            writeln!(w, "SF:{}", path.display())?;
+            for (name, function) in &file.functions {
+                writeln!(w, "FN:{},{}", function.line, name)?;
+            }
+            for (name, function) in &file.functions {
+                writeln!(w, "FNDA:{},{}", function.hits, name)?;
+            }
+            writeln!(w, "FNF:{}", file.functions.len())?;
+            writeln!(
+                w,
+                "FNH:{}",
+                file.functions.values().filter(|f| f.hits > 0).count()
+            )?;
+            for (block, branch) in file.branches.iter().enumerate() {
+                for (outcome, count) in branch.outcomes.iter().enumerate() {
+                    match count {
+                        Some(count) => {
+                            writeln!(w, "BRDA:{},{},{},{}", branch.line, block, outcome, count)?
+                        },
+                        None => writeln!(w, "BRDA:{},{},{},-", branch.line, block, outcome)?,
+                    }
+                }
+            }
+            writeln!(w, "BRF:{}", file.branches_valid())?;
+            writeln!(w, "BRH:{}", file.branches_covered())?;
+            for (line, count) in &file.lines {
+                writeln!(w, "DA:{},{}", line, count)?;
+            }
+            writeln!(w, "LF:{}", file.lines.len())?;
+            writeln!(w, "LH:{}", file.lines_covered())?;
+            writeln!(w, "end_of_record")?;
+        }
+        Ok(())
+    }
+
+    /// Writes the report as Cobertura XML. Files are listed relative to `source_root`, with one
+    /// package per directory and one class per file.
+    pub fn write_cobertura<W: Write>(&self, source_root: &Path, w: &mut W) -> io::Result<()> {
+        let mut packages: BTreeMap<String, Vec<(String, &FileCoverage)>> = BTreeMap::new();
+        for (path, file) in &self.files {
+            let relative = path.strip_prefix(source_root).unwrap_or(path);
+            let package = relative
+                .parent()
+                .map(|dir| dir.display().to_string())
+                .unwrap_or_default();
+            packages
+                .entry(package)
+                .or_default()
+                .push((relative.display().to_string(), file));
+        }
+        let all_files = || packages.values().flatten().map(|(_, file)| *file);
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_millis())
+            .unwrap_or(0);
+
+        writeln!(w, r#"<?xml version="1.0" ?>"#)?;
+        writeln!(
+            w,
+            r#"<!DOCTYPE coverage SYSTEM "http://cobertura.sourceforge.net/xml/coverage-04.dtd">"#
+        )?;
+        writeln!(
+            w,
+            r#"<coverage {} complexity="0" version="1.9" timestamp="{}">"#,
+            rates(all_files()),
+            timestamp
+        )?;
+        writeln!(w, "  <sources>")?;
+        writeln!(
+            w,
+            "    <source>{}</source>",
+            escape_xml(&source_root.display().to_string())
+        )?;
+        writeln!(w, "  </sources>")?;
+        writeln!(w, "  <packages>")?;
+        for (package, files) in &packages {
+            writeln!(
+                w,
+                r#"    <package name="{}" {} complexity="0">"#,
+                escape_xml(package),
+                rates(files.iter().map(|(_, file)| *file))
+            )?;
+            writeln!(w, "      <classes>")?;
+            for (filename, file) in files {
+                let class_name = Path::new(filename)
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                writeln!(
+                    w,
+                    r#"        <class name="{}" filename="{}" {} complexity="0">"#,
+                    escape_xml(&class_name),
+                    escape_xml(filename),
+                    rates(std::iter::once(*file))
+                )?;
+                writeln!(w, "          <methods>")?;
+                for (name, function) in &file.functions {
+                    writeln!(
+                        w,
+                        r#"            <method name="{}" signature="" line-rate="{}" branch-rate="0" complexity="0">"#,
+                        escape_xml(name),
+                        if function.hits > 0 { "1" } else { "0" }
+                    )?;
+                    writeln!(w, "              <lines>")?;
+                    writeln!(
+                        w,
+                        r#"                <line number="{}" hits="{}"/>"#,
+                        function.line, function.hits
+                    )?;
+                    writeln!(w, "              </lines>")?;
+                    writeln!(w, "            </method>")?;
+                }
+                writeln!(w, "          </methods>")?;
+                writeln!(w, "          <lines>")?;
+                for (line, hits) in &file.lines {
+                    let (covered, valid) = file
+                        .branches
+                        .iter()
+                        .filter(|branch| branch.line == *line)
+                        .flat_map(|branch| branch.outcomes.iter())
+                        .fold((0, 0), |(covered, valid), count| {
+                            (covered + (count.unwrap_or(0) > 0) as usize, valid + 1)
+                        });
+                    if valid == 0 {
+                        writeln!(
+                            w,
+                            r#"            <line number="{}" hits="{}" branch="false"/>"#,
+                            line, hits
+                        )?;
+                    } else {
+                        writeln!(
+                            w,
+                            r#"            <line number="{}" hits="{}" branch="true" condition-coverage="{}% ({}/{})"/>"#,
+                            line,
+                            hits,
+                            covered * 100 / valid,
+                            covered,
+                            valid
+                        )?;
+                    }
+                }
+                writeln!(w, "          </lines>")?;
+                writeln!(w, "        </class>")?;
+            }
+            writeln!(w, "      </classes>")?;
+            writeln!(w, "    </package>")?;
+        }
+        writeln!(w, "  </packages>")?;
+        writeln!(w, "</coverage>")
+    }
+}
+
+/// The line and branch rate attributes of a Cobertura element covering `files`.
+fn rates<'a>(files: impl Iterator<Item = &'a FileCoverage>) -> String {
+    let (mut lines_covered, mut lines_valid, mut branches_covered, mut branches_valid) =
+        (0, 0, 0, 0);
+    for file in files {
+        lines_covered += file.lines_covered();
+        lines_valid += file.lines.len();
+        branches_covered += file.branches_covered();
+        branches_valid += file.branches_valid();
+    }
+    let rate = |covered: usize, valid: usize| {
+        if valid == 0 {
+            1.0
+        } else {
+            covered as f64 / valid as f64
+        }
+    };
+    format!(
+        r#"line-rate="{:.4}" branch-rate="{:.4}" lines-covered="{}" lines-valid="{}" branches-covered="{}" branches-valid="{}""#,
+        rate(lines_covered, lines_valid),
+        rate(branches_covered, branches_valid),
+        lines_covered,
+        lines_valid,
+        branches_covered,
+        branches_valid
+    )
+}
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CoverageReport, FileCoverage, FunctionHits, LineBranch, LineIndex};
+    use std::{collections::BTreeMap, path::PathBuf};
+
+    fn report() -> CoverageReport {
+        let file = FileCoverage {
+            functions: BTreeMap::from([
+                ("m::f".to_string(), FunctionHits { line: 2, hits: 3 }),
+                ("m::g".to_string(), FunctionHits { line: 8, hits: 0 }),
+            ]),
+            lines: BTreeMap::from([(3, 3), (4, 1), (5, 2), (9, 0)]),
+            branches: vec![
+                LineBranch {
+                    line: 3,
+                    outcomes: vec![Some(1), Some(2)],
+                },
+                LineBranch {
+                    line: 9,
+                    outcomes: vec![None, None],
+                },
+            ],
+        };
+        CoverageReport {
+            files: BTreeMap::from([(PathBuf::from("/pkg/sources/m.move"), file)]),
+        }
+    }
+
+    #[test]
+    fn test_line_index() {
+        let lines = LineIndex::new("ab\ncd\n\ne");
+        assert_eq!(lines.line(0), 1);
+        assert_eq!(lines.line(2), 1);
+        assert_eq!(lines.line(3), 2);
+        assert_eq!(lines.line(6), 3);
+        assert_eq!(lines.line(7), 4);
+    }
+
+    #[test]
+    fn test_lcov() {
+        let mut output = vec![];
+        report().write_lcov(&mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "TN:\n\
+             SF:/pkg/sources/m.move\n\
+             FN:2,m::f\n\
+             FN:8,m::g\n\
+             FNDA:3,m::f\n\
+             FNDA:0,m::g\n\
+             FNF:2\n\
+             FNH:1\n\
+             BRDA:3,0,0,1\n\
+             BRDA:3,0,1,2\n\
+             BRDA:9,1,0,-\n\
+             BRDA:9,1,1,-\n\
+             BRF:4\n\
+             BRH:2\n\
+             DA:3,3\n\
+             DA:4,1\n\
+             DA:5,2\n\
+             DA:9,0\n\
+             LF:4\n\
+             LH:3\n\
+             end_of_record\n"
+        );
+    }
+
+    #[test]
+    fn test_cobertura() {
+        let mut output = vec![];
+        report()
+            .write_cobertura(&PathBuf::from("/pkg"), &mut output)
+            .unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains(
+            r#"line-rate="0.7500" branch-rate="0.5000" lines-covered="3" lines-valid="4" branches-covered="2" branches-valid="4""#
+        ));
+        assert!(output.contains("<source>/pkg</source>"));
+        assert!(output.contains(r#"<package name="sources" "#));
+        assert!(output.contains(r#"<class name="m" filename="sources/m.move" "#));
+        assert!(output.contains(r#"<method name="m::f" signature="" line-rate="1""#));
+        assert!(output.contains(
+            r#"<line number="3" hits="3" branch="true" condition-coverage="100% (2/2)"/>"#
+        ));
+        assert!(output.contains(r#"<line number="4" hits="1" branch="false"/>"#));
+        assert!(output.contains(
+            r#"<line number="9" hits="0" branch="true" condition-coverage="0% (0/2)"/>"#
+        ));
+    }
+}
@@ -0,0 +1,610 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    protocol::{Request, SharedWriter},
+    source_resolver::{FileId, SourceLocation, SourceResolver},
+};
+use move_vm_runtime::debug::{DebugState, Debugger};
+use serde_json::{json, Value};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Mutex,
+    },
+};
+
+/// The VM is exposed to the client as a single thread
+pub const THREAD_ID: u64 = 1;
+
+/// The variable references of the scopes of the executing frame
+const LOCALS_REFERENCE: u64 = 1;
+const GLOBALS_REFERENCE: u64 = 2;
+
+/// The ways in which execution can be resumed after it was paused
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ResumeMode {
+    Continue,
+    StepIn,
+    StepOver,
+    StepOut,
+}
+
+/// The condition (other than breakpoints) under which execution stops next
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum StepMode {
+    /// Stop at the first line (used for `stopOnEntry`)
+    Entry,
+    /// Only stop at breakpoints
+    Continue,
+    /// Stop at the next line, in any frame
+    StepIn,
+    /// Stop at the next line in a frame at (or above) the given depth
+    StepOver(usize),
+    /// Stop at the next line in a frame above the given depth
+    StepOut(usize),
+}
+
+/// Commands sent to the (paused) VM thread by the server
+enum Command {
+    /// Handle a request that inspects the paused execution
+    Inspect(Request),
+    /// Resume execution
+    Resume(ResumeMode),
+}
+
+/// Tracks the lines executed by every frame, and decides where to stop
+struct ExecutionState {
+    step_mode: StepMode,
+    /// The last line executed by each frame of the call stack (outermost first)
+    frame_lines: Vec<SourceLocation>,
+    paused: bool,
+}
+
+impl ExecutionState {
+    fn new(stop_on_entry: bool) -> Self {
+        Self {
+            step_mode: if stop_on_entry {
+                StepMode::Entry
+            } else {
+                StepMode::Continue
+            },
+            frame_lines: vec![],
+            paused: false,
+        }
+    }
+
+    /// Records that the frame at the given depth (i.e., the number of frames on
+    /// the call stack) is executing the given location, and returns the reason
+    /// for stopping (if execution should stop). Execution only stops when a
+    /// frame enters a new line.
+    fn on_location(
+        &mut self,
+        depth: usize,
+        location: SourceLocation,
+        breakpoints: &BTreeMap<FileId, BTreeSet<u64>>,
+        pause_requested: bool,
+    ) -> Option<&'static str> {
+        // Drop the lines of returned frames, and update the line of this frame
+        self.frame_lines.truncate(depth);
+        let is_new_line = if self.frame_lines.len() == depth {
+            let frame_line = &mut self.frame_lines[depth - 1];
+            let is_new_line = *frame_line != location;
+            *frame_line = location;
+            is_new_line
+        } else {
+            // This is a new frame (possibly called by frames without source locations)
+            self.frame_lines.resize(depth, location);
+            true
+        };
+        if !is_new_line {
+            return None;
+        }
+
+        let breakpoint_hit = breakpoints
+            .get(&location.file)
+            .is_some_and(|lines| lines.contains(&location.line));
+        if pause_requested {
+            Some("pause")
+        } else if breakpoint_hit {
+            Some("breakpoint")
+        } else {
+            match self.step_mode {
+                StepMode::Entry => Some("entry"),
+                StepMode::Continue => None,
+                StepMode::StepIn => Some("step"),
+                StepMode::StepOver(stop_depth) => (depth <= stop_depth).then_some("step"),
+                StepMode::StepOut(stop_depth) => (depth < stop_depth).then_some("step"),
+            }
+        }
+    }
+
+    /// Resumes execution from the given depth
+    fn resume(&mut self, mode: ResumeMode, depth: usize) {
+        self.step_mode = match mode {
+            ResumeMode::Continue => StepMode::Continue,
+            ResumeMode::StepIn => StepMode::StepIn,
+            ResumeMode::StepOver => StepMode::StepOver(depth),
+            ResumeMode::StepOut => StepMode::StepOut(depth),
+        };
+    }
+}
+
+/// A debugger that is attached to the VM and controlled by a client over the
+/// Debug Adapter Protocol. When execution stops, the VM thread is blocked
+/// until the client resumes it, and serves the client's inspection requests
+/// (e.g., for the stack trace and variables) in the meantime.
+pub struct DapDebugger {
+    resolver: SourceResolver,
+    writer: SharedWriter,
+    state: Mutex<ExecutionState>,
+    breakpoints: Mutex<BTreeMap<FileId, BTreeSet<u64>>>,
+    /// The paths used by the client for source files found by their contents
+    file_aliases: Mutex<BTreeMap<FileId, PathBuf>>,
+    pause_requested: AtomicBool,
+    disconnected: AtomicBool,
+    command_sender: Mutex<Option<Sender<Command>>>,
+    command_receiver: Mutex<Receiver<Command>>,
+}
+
+impl DapDebugger {
+    pub fn new(resolver: SourceResolver, writer: SharedWriter) -> Self {
+        let (command_sender, command_receiver) = mpsc::channel();
+        Self {
+            resolver,
+            writer,
+            state: Mutex::new(ExecutionState::new(false)),
+            breakpoints: Mutex::new(BTreeMap::new()),
+            file_aliases: Mutex::new(BTreeMap::new()),
+            pause_requested: AtomicBool::new(false),
+            disconnected: AtomicBool::new(false),
+            command_sender: Mutex::new(Some(command_sender)),
+            command_receiver: Mutex::new(command_receiver),
+        }
+    }
+
+    /// Sets whether execution stops at the first line
+    pub fn set_stop_on_entry(&self, stop_on_entry: bool) {
+        *self.state.lock().unwrap() = ExecutionState::new(stop_on_entry);
+    }
+
+    /// Replaces the breakpoints in the given file, and returns the breakpoints
+    /// to report to the client. Breakpoints on lines without code are moved
+    /// to the next line with code (or reported as unverified).
+    pub fn set_breakpoints(&self, path: &Path, lines: &[u64]) -> Vec<Value> {
+        let Some(file) = self.resolver.find_file(path) else {
+            return lines
+                .iter()
+                .map(|line| {
+                    json!({
+                        "verified": false,
+                        "line": line,
+                        "message": "No compiled code found for this file",
+                    })
+                })
+                .collect();
+        };
+        self.file_aliases
+            .lock()
+            .unwrap()
+            .insert(file, path.to_path_buf());
+
+        let mut file_breakpoints = BTreeSet::new();
+        let reported_breakpoints = lines
+            .iter()
+            .map(
+                |line| match self.resolver.get_breakpoint_line(file, *line) {
+                    Some(breakpoint_line) => {
+                        file_breakpoints.insert(breakpoint_line);
+                        json!({ "verified": true, "line": breakpoint_line })
+                    },
+                    None => json!({
+                        "verified": false,
+                        "line": line,
+                        "message": "No code found at or after this line",
+                    }),
+                },
+            )
+            .collect();
+        self.breakpoints
+            .lock()
+            .unwrap()
+            .insert(file, file_breakpoints);
+        reported_breakpoints
+    }
+
+    /// Requests execution to stop at the next line
+    pub fn pause(&self) {
+        self.pause_requested.store(true, Ordering::Release);
+    }
+
+    /// Resumes paused execution. Returns false if execution is not paused.
+    pub fn resume(&self, mode: ResumeMode) -> bool {
+        self.send_command(Command::Resume(mode))
+    }
+
+    /// Forwards a request to the paused VM thread, which sends the response.
+    /// Returns false if execution is not paused.
+    pub fn inspect(&self, request: Request) -> bool {
+        self.send_command(Command::Inspect(request))
+    }
+
+    /// Detaches the client: all breakpoints are removed and execution runs to completion
+    pub fn disconnect(&self) {
+        self.disconnected.store(true, Ordering::Release);
+        self.breakpoints.lock().unwrap().clear();
+        self.command_sender.lock().unwrap().take();
+    }
+
+    /// Notifies the client that execution has completed
+    pub fn terminate(&self) {
+        if !self.disconnected.load(Ordering::Acquire) {
+            let _ = self
+                .writer
+                .lock()
+                .unwrap()
+                .send_event("terminated", json!({}));
+        }
+    }
+
+    fn send_command(&self, command: Command) -> bool {
+        let mut state = self.state.lock().unwrap();
+        if !state.paused {
+            return false;
+        }
+        if let Command::Resume(_) = command {
+            state.paused = false;
+        }
+        match self.command_sender.lock().unwrap().as_ref() {
+            Some(command_sender) => command_sender.send(command).is_ok(),
+            None => false,
+        }
+    }
+
+    /// Blocks the VM thread until execution is resumed, serving inspection
+    /// requests in the meantime.
+    fn wait_for_resume(&self, state: &DebugState, depth: usize, reason: &str) {
+        self.state.lock().unwrap().paused = true;
+        let _ = self.writer.lock().unwrap().send_event(
+            "stopped",
+            json!({
+                "reason": reason,
+                "threadId": THREAD_ID,
+                "allThreadsStopped": true,
+            }),
+        );
+
+        let command_receiver = self.command_receiver.lock().unwrap();
+        loop {
+            match command_receiver.recv() {
+                Ok(Command::Inspect(request)) => {
+                    let response = match request.command.as_str() {
+                        "stackTrace" => Ok(self.get_stack_trace(state)),
+                        "scopes" => Ok(self.get_scopes(&request)),
+                        "variables" => Ok(self.get_variables(state, &request)),
+                        "evaluate" => self.evaluate(state, &request),
+                        _ => Err(format!("Unsupported command: {}", request.command)),
+                    };
+                    let mut writer = self.writer.lock().unwrap();
+                    let _ = match response {
+                        Ok(body) => writer.send_response(&request, body),
+                        Err(message) => writer.send_error_response(&request, &message),
+                    };
+                },
+                Ok(Command::Resume(mode)) => {
+                    self.state.lock().unwrap().resume(mode, depth);
+                    return;
+                },
+                Err(_) => {
+                    // The client disconnected
+                    let mut execution_state = self.state.lock().unwrap();
+                    execution_state.paused = false;
+                    execution_state.resume(ResumeMode::Continue, depth);
+                    return;
+                },
+            }
+        }
+    }
+
+    /// Returns the source object of the given file, as expected by the client
+    fn get_source(&self, file: FileId) -> Value {
+        let path = self
+            .file_aliases
+            .lock()
+            .unwrap()
+            .get(&file)
+            .cloned()
+            .unwrap_or_else(|| self.resolver.get_file_path(file).to_path_buf());
+        json!({
+            "name": path.file_name().map(|name| name.to_string_lossy().to_string()),
+            "path": path,
+        })
+    }
+
+    fn get_stack_trace(&self, state: &DebugState) -> Value {
+        let stack_frames: Vec<_> = state
+            .call_stack()
+            .into_iter()
+            .enumerate()
+            .map(|(frame_id, (module_id, function_index, pc))| {
+                let Some(module_id) = module_id else {
+                    return json!({
+                        "id": frame_id,
+                        "name": "<script>",
+                        "line": 0,
+                        "column": 0,
+                        "presentationHint": "subtle",
+                    });
+                };
+                let function_name = self
+                    .resolver
+                    .get_function_name(&module_id, function_index)
+                    .unwrap_or_else(|| format!("<function {}>", function_index));
+                let name = format!("{}::{}", module_id.short_str_lossless(), function_name);
+                match self.resolver.get_location(&module_id, function_index, pc) {
+                    Some(location) => json!({
+                        "id": frame_id,
+                        "name": name,
+                        "source": self.get_source(location.file),
+                        "line": location.line,
+                        "column": 1,
+                    }),
+                    None => json!({
+                        "id": frame_id,
+                        "name": name,
+                        "line": 0,
+                        "column": 0,
+                        "presentationHint": "subtle",
+                    }),
+                }
+            })
+            .collect();
+        json!({
+            "totalFrames": stack_frames.len(),
+            "stackFrames": stack_frames,
+        })
+    }
+
+    fn get_scopes(&self, request: &Request) -> Value {
+        // Locals are only available for the executing frame
+        let globals = json!({
+            "name": "Globals",
+            "variablesReference": GLOBALS_REFERENCE,
+            "expensive": false,
+        });
+        let scopes = if request.arguments["frameId"].as_u64().unwrap_or(0) == 0 {
+            vec![
+                json!({
+                    "name": "Locals",
+                    "presentationHint": "locals",
+                    "variablesReference": LOCALS_REFERENCE,
+                    "expensive": false,
+                }),
+                globals,
+            ]
+        } else {
+            vec![globals]
+        };
+        json!({ "scopes": scopes })
+    }
+
+    fn get_variables(&self, state: &DebugState, request: &Request) -> Value {
+        let variables = match request.arguments["variablesReference"].as_u64() {
+            Some(LOCALS_REFERENCE) => self.get_locals(state),
+            Some(GLOBALS_REFERENCE) => get_resources(state),
+            _ => vec![],
+        };
+        let variables: Vec<_> = variables
+            .into_iter()
+            .map(|(name, value)| {
+                json!({
+                    "name": name,
+                    "value": value,
+                    "variablesReference": 0,
+                })
+            })
+            .collect();
+        json!({ "variables": variables })
+    }
+
+    /// Evaluates an expression, which is either the name of a local or a
+    /// `borrow_global<T>(@address)` expression for a loaded resource.
+    fn evaluate(&self, state: &DebugState, request: &Request) -> Result<Value, String> {
+        let expression = request.arguments["expression"]
+            .as_str()
+            .unwrap_or_default()
+            .trim();
+        self.get_locals(state)
+            .into_iter()
+            .chain(get_resources(state))
+            .find(|(name, _)| name == expression)
+            .map(|(_, value)| json!({ "result": value, "variablesReference": 0 }))
+            .ok_or_else(|| format!("Unknown local or resource: {}", expression))
+    }
+
+    /// Returns the names and values of the locals of the executing frame
+    fn get_locals(&self, state: &DebugState) -> Vec<(String, String)> {
+        let locals = state.locals().unwrap_or_default();
+        locals
+            .into_iter()
+            .enumerate()
+            .map(|(index, value)| {
+                let name = state
+                    .module_id()
+                    .and_then(|module_id| {
+                        self.resolver
+                            .get_local_name(module_id, state.function_index(), index)
+                    })
+                    .unwrap_or_else(|| format!("local_{}", index));
+                (name, value)
+            })
+            .collect()
+    }
+}
+
+/// Returns the names and values of the resources loaded by the transaction
+fn get_resources(state: &DebugState) -> Vec<(String, String)> {
+    state
+        .loaded_resources()
+        .into_iter()
+        .map(|(address, struct_tag, value)| {
+            let name = format!(
+                "borrow_global<{}>(@{})",
+                struct_tag.to_canonical_string(),
+                address.to_hex_literal()
+            );
+            (name, value)
+        })
+        .collect()
+}
+
+impl Debugger for DapDebugger {
+    fn on_instruction(&self, state: &DebugState) {
+        if self.disconnected.load(Ordering::Acquire) {
+            return;
+        }
+
+        // Instructions without a source location (e.g., in modules without
+        // source maps) are never stopped at.
+        let Some(module_id) = state.module_id() else {
+            return;
+        };
+        let Some(location) =
+            self.resolver
+                .get_location(module_id, state.function_index(), state.pc())
+        else {
+            return;
+        };
+
+        let depth = state.call_stack().len();
+        let stop_reason = {
+            let breakpoints = self.breakpoints.lock().unwrap();
+            self.state.lock().unwrap().on_location(
+                depth,
+                location,
+                &breakpoints,
+                self.pause_requested.load(Ordering::Acquire),
+            )
+        };
+        if let Some(reason) = stop_reason {
+            self.pause_requested.store(false, Ordering::Release);
+            self.wait_for_resume(state, depth, reason);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn location(line: u64) -> SourceLocation {
+        SourceLocation { file: 0, line }
+    }
+
+    #[test]
+    fn test_stop_on_entry() {
+        let no_breakpoints = BTreeMap::new();
+        let mut state = ExecutionState::new(true);
+        assert_eq!(
+            state.on_location(1, location(1), &no_breakpoints, false),
+            Some("entry")
+        );
+
+        // Continue and verify execution never stops without breakpoints
+        state.resume(ResumeMode::Continue, 1);
+        for line in 2..10 {
+            assert_eq!(
+                state.on_location(1, location(line), &no_breakpoints, false),
+                None
+            );
+        }
+
+        // Verify pause requests stop execution
+        assert_eq!(
+            state.on_location(1, location(10), &no_breakpoints, true),
+            Some("pause")
+        );
+    }
+
+    #[test]
+    fn test_breakpoints() {
+        let breakpoints = BTreeMap::from([(0, BTreeSet::from([3]))]);
+        let mut state = ExecutionState::new(false);
+        assert_eq!(state.on_location(1, location(2), &breakpoints, false), None);
+        assert_eq!(
+            state.on_location(1, location(3), &breakpoints, false),
+            Some("breakpoint")
+        );
+
+        // Verify execution doesn't stop again until a new line is entered
+        assert_eq!(state.on_location(1, location(3), &breakpoints, false), None);
+        assert_eq!(state.on_location(1, location(4), &breakpoints, false), None);
+        assert_eq!(
+            state.on_location(1, location(3), &breakpoints, false),
+            Some("breakpoint")
+        );
+
+        // Verify breakpoints in other files are ignored
+        let other_file_location = SourceLocation { file: 1, line: 3 };
+        assert_eq!(
+            state.on_location(2, other_file_location, &breakpoints, false),
+            None
+        );
+    }
+
+    #[test]
+    fn test_stepping() {
+        let no_breakpoints = BTreeMap::new();
+        let mut state = ExecutionState::new(true);
+        assert_eq!(
+            state.on_location(1, location(1), &no_breakpoints, false),
+            Some("entry")
+        );
+
+        // Step over a call and verify the callee is skipped
+        state.resume(ResumeMode::StepOver, 1);
+        assert_eq!(
+            state.on_location(2, location(10), &no_breakpoints, false),
+            None
+        );
+        assert_eq!(
+            state.on_location(2, location(11), &no_breakpoints, false),
+            None
+        );
+
+        // Verify returning to the line of the call doesn't stop execution
+        assert_eq!(
+            state.on_location(1, location(1), &no_breakpoints, false),
+            None
+        );
+        assert_eq!(
+            state.on_location(1, location(2), &no_breakpoints, false),
+            Some("step")
+        );
+
+        // Step into a call
+        state.resume(ResumeMode::StepIn, 1);
+        assert_eq!(
+            state.on_location(2, location(10), &no_breakpoints, false),
+            Some("step")
+        );
+
+        // Step out of the call and verify execution stops in the caller
+        state.resume(ResumeMode::StepOut, 2);
+        assert_eq!(
+            state.on_location(2, location(11), &no_breakpoints, false),
+            None
+        );
+        assert_eq!(
+            state.on_location(1, location(2), &no_breakpoints, false),
+            None
+        );
+        assert_eq!(
+            state.on_location(1, location(3), &no_breakpoints, false),
+            Some("step")
+        );
+    }
+}
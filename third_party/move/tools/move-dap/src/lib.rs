@@ -0,0 +1,49 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! # Move Debug Adapter
+//!
+//! A [Debug Adapter Protocol](https://microsoft.github.io/debug-adapter-protocol/) server
+//! for source-level debugging of Move code executed by the Move VM. Bytecode offsets are
+//! mapped to Move source lines using the source maps produced by the compiler, so clients
+//! (e.g., VS Code) can set breakpoints by file and line, step through code, and inspect
+//! locals and the global resources loaded by the executing transaction.
+//!
+//! The server listens on a TCP address, and clients connect to it as a debug server
+//! (e.g., using `debugServer` in a VS Code launch configuration). The debugger is attached
+//! to all VMs in the process, so embedders should execute on a single thread while a
+//! session is active.
+
+mod debugger;
+mod protocol;
+mod server;
+mod source_resolver;
+
+use anyhow::Result;
+pub use debugger::DapDebugger;
+use move_vm_runtime::tracing;
+pub use source_resolver::SourceResolver;
+use std::sync::Arc;
+
+/// An active debug session. The debugger is detached from the VM (and the client is
+/// notified that execution completed) when the session is dropped.
+pub struct DebugSession {
+    debugger: Arc<DapDebugger>,
+}
+
+impl DebugSession {
+    /// Waits for a client to connect on the given address and configure the session,
+    /// and then attaches the debugger to the VM.
+    pub fn start(address: &str, resolver: SourceResolver) -> Result<Self> {
+        let debugger = server::start_server(address, resolver)?;
+        tracing::attach_debugger(debugger.clone());
+        Ok(Self { debugger })
+    }
+}
+
+impl Drop for DebugSession {
+    fn drop(&mut self) {
+        tracing::detach_debugger();
+        self.debugger.terminate();
+    }
+}
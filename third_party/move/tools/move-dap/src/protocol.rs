@@ -0,0 +1,179 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Wire format of the Debug Adapter Protocol. Every message is a JSON object,
+//! preceded by a `Content-Length` header and an empty line.
+
+use anyhow::{anyhow, bail, Result};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::{
+    io::{BufRead, Write},
+    sync::{Arc, Mutex},
+};
+
+const CONTENT_LENGTH_HEADER: &str = "Content-Length:";
+
+/// A request sent by the client
+#[derive(Clone, Debug, Deserialize)]
+pub struct Request {
+    pub seq: u64,
+    pub command: String,
+    #[serde(default)]
+    pub arguments: Value,
+}
+
+/// Reads the next message from the given reader. Returns None if the
+/// connection was closed before a new message started.
+pub fn read_message<R: BufRead>(reader: &mut R) -> Result<Option<Value>> {
+    let mut content_length = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            if content_length.is_none() {
+                return Ok(None);
+            }
+            bail!("Connection closed while reading message headers");
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            // Headers are only terminated once a content length was received
+            if content_length.is_some() {
+                break;
+            }
+            continue;
+        }
+        if let Some(length) = line.strip_prefix(CONTENT_LENGTH_HEADER) {
+            content_length = Some(length.trim().parse::<usize>()?);
+        }
+    }
+
+    let content_length =
+        content_length.ok_or_else(|| anyhow!("Message is missing a content length"))?;
+    let mut content = vec![0; content_length];
+    reader.read_exact(&mut content)?;
+    Ok(Some(serde_json::from_slice(&content)?))
+}
+
+/// A message writer shared by the server and the debugger
+pub type SharedWriter = Arc<Mutex<MessageWriter<Box<dyn Write + Send>>>>;
+
+/// Writes responses and events to the client. Every message is assigned
+/// the next sequence number.
+pub struct MessageWriter<W> {
+    writer: W,
+    next_seq: u64,
+}
+
+impl<W: Write> MessageWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            next_seq: 1,
+        }
+    }
+
+    /// Sends a successful response to the given request
+    pub fn send_response(&mut self, request: &Request, body: Value) -> Result<()> {
+        self.send(json!({
+            "type": "response",
+            "request_seq": request.seq,
+            "success": true,
+            "command": request.command,
+            "body": body,
+        }))
+    }
+
+    /// Sends a failed response to the given request. The message is
+    /// displayed to the user by the client.
+    pub fn send_error_response(&mut self, request: &Request, message: &str) -> Result<()> {
+        self.send(json!({
+            "type": "response",
+            "request_seq": request.seq,
+            "success": false,
+            "command": request.command,
+            "message": message,
+        }))
+    }
+
+    /// Sends an event with the given body
+    pub fn send_event(&mut self, event: &str, body: Value) -> Result<()> {
+        self.send(json!({
+            "type": "event",
+            "event": event,
+            "body": body,
+        }))
+    }
+
+    fn send(&mut self, mut message: Value) -> Result<()> {
+        message["seq"] = json!(self.next_seq);
+        self.next_seq += 1;
+
+        let content = serde_json::to_vec(&message)?;
+        write!(
+            self.writer,
+            "{} {}\r\n\r\n",
+            CONTENT_LENGTH_HEADER,
+            content.len()
+        )?;
+        self.writer.write_all(&content)?;
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    #[cfg(test)]
+    pub(crate) fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_message_round_trip() {
+        // Write a response and an event
+        let request = Request {
+            seq: 7,
+            command: "threads".to_string(),
+            arguments: Value::Null,
+        };
+        let mut writer = MessageWriter::new(vec![]);
+        writer
+            .send_response(&request, json!({ "threads": [] }))
+            .unwrap();
+        writer
+            .send_event("stopped", json!({ "reason": "step" }))
+            .unwrap();
+
+        // Read the messages back and verify the sequence numbers
+        let mut reader = Cursor::new(writer.into_inner());
+        let response = read_message(&mut reader).unwrap().unwrap();
+        assert_eq!(response["seq"], 1);
+        assert_eq!(response["request_seq"], 7);
+        assert_eq!(response["command"], "threads");
+        assert_eq!(response["success"], true);
+        let event = read_message(&mut reader).unwrap().unwrap();
+        assert_eq!(event["seq"], 2);
+        assert_eq!(event["event"], "stopped");
+        assert_eq!(event["body"]["reason"], "step");
+
+        // Verify the end of the stream is detected
+        assert!(read_message(&mut reader).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_read_request() {
+        let content = r#"{"seq":1,"type":"request","command":"initialize"}"#;
+        let message = format!("Content-Length: {}\r\n\r\n{}", content.len(), content);
+        let message = read_message(&mut Cursor::new(message)).unwrap().unwrap();
+
+        // Verify missing arguments default to null
+        let request: Request = serde_json::from_value(message).unwrap();
+        assert_eq!(request.seq, 1);
+        assert_eq!(request.command, "initialize");
+        assert!(request.arguments.is_null());
+    }
+}
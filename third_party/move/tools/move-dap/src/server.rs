@@ -0,0 +1,254 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    debugger::{DapDebugger, ResumeMode, THREAD_ID},
+    protocol::{read_message, MessageWriter, Request, SharedWriter},
+    source_resolver::SourceResolver,
+};
+use anyhow::{anyhow, Result};
+use serde_json::{json, Value};
+use std::{
+    io::{BufRead, BufReader},
+    net::TcpListener,
+    path::Path,
+    sync::{
+        mpsc::{self, Sender},
+        Arc, Mutex,
+    },
+    thread,
+};
+
+/// Listens on the given address for a client, and returns the debugger once
+/// the client has configured the session (i.e., after the client launched or
+/// attached to the debuggee and finished setting breakpoints). The client's
+/// requests are served on a background thread.
+pub fn start_server(address: &str, resolver: SourceResolver) -> Result<Arc<DapDebugger>> {
+    let listener = TcpListener::bind(address)?;
+    eprintln!(
+        "Waiting for a debug adapter client to connect on {}",
+        listener.local_addr()?
+    );
+    let (stream, _) = listener.accept()?;
+
+    let writer: SharedWriter = Arc::new(Mutex::new(MessageWriter::new(Box::new(
+        stream.try_clone()?,
+    ))));
+    let debugger = Arc::new(DapDebugger::new(resolver, writer.clone()));
+    let (configured_sender, configured_receiver) = mpsc::channel();
+    let server_debugger = debugger.clone();
+    thread::spawn(move || {
+        serve_requests(
+            BufReader::new(stream),
+            writer,
+            server_debugger,
+            configured_sender,
+        )
+    });
+
+    configured_receiver.recv().map_err(|_| {
+        anyhow!("The debug adapter client disconnected before configuring the session")
+    })?;
+    Ok(debugger)
+}
+
+/// Serves the client's requests until it disconnects (or the connection is closed)
+fn serve_requests<R: BufRead>(
+    mut reader: R,
+    writer: SharedWriter,
+    debugger: Arc<DapDebugger>,
+    configured_sender: Sender<()>,
+) {
+    let mut configured_sender = Some(configured_sender);
+    let (mut launched, mut configuration_done) = (false, false);
+    while let Ok(Some(message)) = read_message(&mut reader) {
+        // Ignore anything that isn't a request (e.g., responses to reverse requests)
+        let Ok(request) = serde_json::from_value::<Request>(message) else {
+            continue;
+        };
+        let response = match request.command.as_str() {
+            "initialize" => {
+                let capabilities = json!({
+                    "supportsConfigurationDoneRequest": true,
+                    "supportsEvaluateForHovers": true,
+                });
+                let mut writer = writer.lock().unwrap();
+                let _ = writer.send_response(&request, capabilities);
+                let _ = writer.send_event("initialized", json!({}));
+                continue;
+            },
+            "launch" | "attach" => {
+                let stop_on_entry = request.arguments["stopOnEntry"].as_bool().unwrap_or(false);
+                debugger.set_stop_on_entry(stop_on_entry);
+                launched = true;
+                Ok(json!({}))
+            },
+            "setBreakpoints" => {
+                let path = request.arguments["source"]["path"]
+                    .as_str()
+                    .unwrap_or_default();
+                let lines: Vec<_> = request.arguments["breakpoints"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|breakpoint| breakpoint["line"].as_u64())
+                    .collect();
+                let breakpoints = debugger.set_breakpoints(Path::new(path), &lines);
+                Ok(json!({ "breakpoints": breakpoints }))
+            },
+            "setExceptionBreakpoints" => Ok(json!({})),
+            "configurationDone" => {
+                configuration_done = true;
+                Ok(json!({}))
+            },
+            "threads" => Ok(json!({
+                "threads": [{ "id": THREAD_ID, "name": "Move VM" }],
+            })),
+            "stackTrace" | "scopes" | "variables" | "evaluate" => {
+                // The paused VM thread responds to the request
+                if debugger.inspect(request.clone()) {
+                    continue;
+                }
+                Err("Execution is not paused")
+            },
+            "continue" | "next" | "stepIn" | "stepOut" => {
+                let mode = match request.command.as_str() {
+                    "continue" => ResumeMode::Continue,
+                    "next" => ResumeMode::StepOver,
+                    "stepIn" => ResumeMode::StepIn,
+                    _ => ResumeMode::StepOut,
+                };
+                if debugger.resume(mode) {
+                    Ok(json!({ "allThreadsContinued": true }))
+                } else {
+                    Err("Execution is not paused")
+                }
+            },
+            "pause" => {
+                debugger.pause();
+                Ok(json!({}))
+            },
+            "disconnect" | "terminate" => {
+                let _ = writer.lock().unwrap().send_response(&request, json!({}));
+                break;
+            },
+            _ => Err("Unsupported command"),
+        };
+        send_response(&writer, &request, response);
+
+        if launched && configuration_done {
+            if let Some(configured_sender) = configured_sender.take() {
+                let _ = configured_sender.send(());
+            }
+        }
+    }
+
+    // Let execution run to completion
+    debugger.disconnect();
+}
+
+fn send_response(writer: &SharedWriter, request: &Request, response: Result<Value, &str>) {
+    let mut writer = writer.lock().unwrap();
+    let _ = match response {
+        Ok(body) => writer.send_response(request, body),
+        Err(message) => writer.send_error_response(request, message),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{io::Write, net::TcpStream, time::Duration};
+
+    struct TestClient {
+        reader: BufReader<TcpStream>,
+        stream: TcpStream,
+        next_seq: u64,
+    }
+
+    impl TestClient {
+        fn connect(address: &str) -> Self {
+            // Retry until the server is listening
+            for _ in 0..100 {
+                if let Ok(stream) = TcpStream::connect(address) {
+                    return Self {
+                        reader: BufReader::new(stream.try_clone().unwrap()),
+                        stream,
+                        next_seq: 1,
+                    };
+                }
+                thread::sleep(Duration::from_millis(50));
+            }
+            panic!("Failed to connect to the debug adapter server");
+        }
+
+        /// Sends a request and returns the response (skipping any events)
+        fn send_request(&mut self, command: &str, arguments: Value) -> Value {
+            let seq = self.next_seq;
+            self.next_seq += 1;
+            let content = json!({
+                "seq": seq,
+                "type": "request",
+                "command": command,
+                "arguments": arguments,
+            })
+            .to_string();
+            write!(
+                self.stream,
+                "Content-Length: {}\r\n\r\n{}",
+                content.len(),
+                content
+            )
+            .unwrap();
+
+            loop {
+                let message = read_message(&mut self.reader).unwrap().unwrap();
+                if message["type"] == "response" {
+                    assert_eq!(message["request_seq"], seq);
+                    return message;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_session() {
+        // Start the server on a free port
+        let address = {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            listener.local_addr().unwrap().to_string()
+        };
+        let server_address = address.clone();
+        let server = thread::spawn(move || start_server(&server_address, SourceResolver::new()));
+
+        // Configure the session
+        let mut client = TestClient::connect(&address);
+        let response = client.send_request("initialize", json!({ "adapterID": "move" }));
+        assert_eq!(response["body"]["supportsConfigurationDoneRequest"], true);
+        let response = client.send_request(
+            "setBreakpoints",
+            json!({
+                "source": { "path": "/unknown/file.move" },
+                "breakpoints": [{ "line": 3 }],
+            }),
+        );
+        assert_eq!(response["body"]["breakpoints"][0]["verified"], false);
+        client.send_request("launch", json!({ "stopOnEntry": true }));
+        client.send_request("configurationDone", json!({}));
+        let debugger = server.join().unwrap().unwrap();
+
+        // Verify the VM thread is reported
+        let response = client.send_request("threads", json!({}));
+        assert_eq!(response["body"]["threads"][0]["id"], THREAD_ID);
+
+        // Verify execution can't be inspected or resumed while running
+        for command in ["stackTrace", "continue"] {
+            let response = client.send_request(command, json!({ "threadId": THREAD_ID }));
+            assert_eq!(response["success"], false);
+        }
+
+        // Disconnect and verify the debugger stops accepting commands
+        client.send_request("disconnect", json!({}));
+        assert!(!debugger.resume(ResumeMode::Continue));
+    }
+}
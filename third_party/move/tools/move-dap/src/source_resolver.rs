@@ -0,0 +1,267 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{Context, Result};
+use move_binary_format::{
+    access::ModuleAccess,
+    file_format::{CodeOffset, FunctionDefinitionIndex},
+    CompiledModule,
+};
+use move_bytecode_source_map::{source_map::SourceMap, utils::source_map_from_file};
+use move_command_line_common::files::{
+    extension_equals, find_filenames, FileHash, MOVE_COMPILED_EXTENSION, MOVE_EXTENSION,
+    SOURCE_MAP_EXTENSION,
+};
+use move_core_types::language_storage::ModuleId;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// The directories of a compiled package (i.e., `build/<package>`) that hold
+/// the bytecode, source maps and sources (see `CompiledPackageLayout`).
+const COMPILED_MODULES_DIR: &str = "bytecode_modules";
+const SOURCE_MAPS_DIR: &str = "source_maps";
+const SOURCES_DIR: &str = "sources";
+
+/// An index into the source files known to the resolver
+pub type FileId = usize;
+
+/// A line (1-based) in a source file
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct SourceLocation {
+    pub file: FileId,
+    pub line: u64,
+}
+
+/// A source file and the byte offsets at which its lines start
+struct SourceFile {
+    path: PathBuf,
+    line_starts: Vec<usize>,
+}
+
+impl SourceFile {
+    fn new(path: PathBuf, contents: &str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(contents.match_indices('\n').map(|(index, _)| index + 1))
+            .collect();
+        Self { path, line_starts }
+    }
+
+    /// Returns the (1-based) line containing the given byte offset
+    fn get_line(&self, byte_offset: usize) -> u64 {
+        self.line_starts
+            .partition_point(|line_start| *line_start <= byte_offset) as u64
+    }
+}
+
+/// A compiled module together with its source map
+struct ModuleSources {
+    module: CompiledModule,
+    source_map: SourceMap,
+}
+
+/// Maps bytecode offsets of loaded functions to lines in Move source files
+/// (and back), using the source maps produced by the compiler.
+#[derive(Default)]
+pub struct SourceResolver {
+    files: Vec<SourceFile>,
+    file_hashes: BTreeMap<FileHash, FileId>,
+    modules: BTreeMap<ModuleId, ModuleSources>,
+}
+
+impl SourceResolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a source file. Source maps identify files by the hash of their contents.
+    pub fn add_source_file(&mut self, path: &Path, contents: &str) {
+        let file_hash = FileHash::new(contents);
+        if self.file_hashes.contains_key(&file_hash) {
+            return;
+        }
+        let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        self.file_hashes.insert(file_hash, self.files.len());
+        self.files.push(SourceFile::new(path, contents));
+    }
+
+    /// Adds a compiled module and the source map produced for it
+    pub fn add_module(&mut self, module: CompiledModule, source_map: SourceMap) {
+        self.modules
+            .insert(module.self_id(), ModuleSources { module, source_map });
+    }
+
+    /// Adds all modules (and sources) of a compiled package, including its
+    /// dependencies. The given directory is the package's build output, i.e.,
+    /// `build/<package>`. Modules without a source map are skipped.
+    pub fn add_package_build_dir(&mut self, build_dir: &Path) -> Result<()> {
+        let sources_dir = build_dir.join(SOURCES_DIR);
+        for source_path in find_filenames(&[&sources_dir], |path| {
+            extension_equals(path, MOVE_EXTENSION)
+        })? {
+            let contents = fs::read_to_string(&source_path)
+                .with_context(|| format!("Failed to read source file {}", source_path))?;
+            self.add_source_file(Path::new(&source_path), &contents);
+        }
+
+        let modules_dir = build_dir.join(COMPILED_MODULES_DIR);
+        for module_path in find_filenames(&[&modules_dir], |path| {
+            extension_equals(path, MOVE_COMPILED_EXTENSION)
+        })? {
+            let module_path = PathBuf::from(module_path);
+            let source_map_path = build_dir
+                .join(SOURCE_MAPS_DIR)
+                .join(module_path.strip_prefix(&modules_dir)?)
+                .with_extension(SOURCE_MAP_EXTENSION);
+            if !source_map_path.is_file() {
+                continue;
+            }
+
+            let bytes = fs::read(&module_path)?;
+            let module = CompiledModule::deserialize(&bytes).with_context(|| {
+                format!("Failed to deserialize module {}", module_path.display())
+            })?;
+            let source_map = source_map_from_file(&source_map_path)?;
+            self.add_module(module, source_map);
+        }
+        Ok(())
+    }
+
+    /// Returns the source location of the instruction at the given offset
+    pub fn get_location(
+        &self,
+        module_id: &ModuleId,
+        function_index: FunctionDefinitionIndex,
+        pc: CodeOffset,
+    ) -> Option<SourceLocation> {
+        let loc = self
+            .modules
+            .get(module_id)?
+            .source_map
+            .get_code_location(function_index, pc)
+            .ok()?;
+        let file = *self.file_hashes.get(&loc.file_hash())?;
+        Some(SourceLocation {
+            file,
+            line: self.files[file].get_line(loc.start() as usize),
+        })
+    }
+
+    /// Returns the name of the given function
+    pub fn get_function_name(
+        &self,
+        module_id: &ModuleId,
+        function_index: FunctionDefinitionIndex,
+    ) -> Option<String> {
+        let module = &self.modules.get(module_id)?.module;
+        let function_def = module.function_defs().get(function_index.0 as usize)?;
+        let function_handle = module.function_handle_at(function_def.function);
+        Some(module.identifier_at(function_handle.name).to_string())
+    }
+
+    /// Returns the source name of the given parameter or local
+    pub fn get_local_name(
+        &self,
+        module_id: &ModuleId,
+        function_index: FunctionDefinitionIndex,
+        local_index: usize,
+    ) -> Option<String> {
+        self.modules
+            .get(module_id)?
+            .source_map
+            .get_parameter_or_local_name(function_index, local_index as u64)
+            .ok()
+            .map(|(name, _)| name)
+    }
+
+    /// Returns the path of the given source file
+    pub fn get_file_path(&self, file: FileId) -> &Path {
+        &self.files[file].path
+    }
+
+    /// Finds the source file at the given path. If the path is not known, the
+    /// file is identified by its contents (e.g., for the copies of the sources
+    /// stored in a package's build directory).
+    pub fn find_file(&self, path: &Path) -> Option<FileId> {
+        let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        if let Some(file) = self.files.iter().position(|file| file.path == path) {
+            return Some(file);
+        }
+        let contents = fs::read_to_string(&path).ok()?;
+        self.file_hashes.get(&FileHash::new(&contents)).copied()
+    }
+
+    /// Returns the first line (at or after the given line) of the file that
+    /// contains code, i.e., the line at which a breakpoint would be hit.
+    pub fn get_breakpoint_line(&self, file: FileId, line: u64) -> Option<u64> {
+        self.get_code_lines(file).range(line..).next().copied()
+    }
+
+    /// Returns all lines of the given file that instructions map to
+    fn get_code_lines(&self, file: FileId) -> BTreeSet<u64> {
+        let mut lines = BTreeSet::new();
+        for ModuleSources { module, source_map } in self.modules.values() {
+            for index in 0..module.function_defs().len() {
+                let function_index = FunctionDefinitionIndex::new(index as u16);
+                let Ok(function_source_map) = source_map.get_function_source_map(function_index)
+                else {
+                    continue;
+                };
+                for loc in function_source_map.code_map.values() {
+                    if self.file_hashes.get(&loc.file_hash()) == Some(&file) {
+                        lines.insert(self.files[file].get_line(loc.start() as usize));
+                    }
+                }
+            }
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_line() {
+        let file = SourceFile::new(
+            PathBuf::from("test.move"),
+            "module 0x1::m {\n\n    fun f() {}\n}",
+        );
+        assert_eq!(file.get_line(0), 1);
+        assert_eq!(file.get_line(15), 1);
+        assert_eq!(file.get_line(16), 2);
+        assert_eq!(file.get_line(17), 3);
+        assert_eq!(file.get_line(30), 4);
+    }
+
+    #[test]
+    fn test_find_file() {
+        // Create a source file and a copy of it in another directory
+        let temp_dir = tempfile::tempdir().unwrap();
+        let contents = "module 0x1::m {}\n";
+        let source_path = temp_dir.path().join("m.move");
+        fs::write(&source_path, contents).unwrap();
+        let copy_dir = temp_dir.path().join("build");
+        fs::create_dir(&copy_dir).unwrap();
+        let copy_path = copy_dir.join("m.move");
+        fs::write(&copy_path, contents).unwrap();
+
+        // Add the copy and verify both files are resolved to it
+        let mut resolver = SourceResolver::new();
+        resolver.add_source_file(&copy_path, contents);
+        let file = resolver.find_file(&copy_path).unwrap();
+        assert_eq!(resolver.find_file(&source_path), Some(file));
+        assert_eq!(
+            resolver.get_file_path(file),
+            fs::canonicalize(&copy_path).unwrap()
+        );
+
+        // Verify unknown files are not resolved
+        let other_path = temp_dir.path().join("other.move");
+        fs::write(&other_path, "module 0x1::other {}\n").unwrap();
+        assert_eq!(resolver.find_file(&other_path), None);
+    }
+}
@@ -17,6 +17,7 @@ use move_model::{
 use move_stackless_bytecode::{
     astifier,
     function_target_pipeline::{FunctionTargetsHolder, FunctionVariant},
+    stackless_bytecode::{Bytecode, Operation},
 };
 use std::{collections::BTreeMap, fs, io::Write, mem, path::Path, rc::Rc, vec};
 
@@ -222,7 +223,77 @@ impl Decompiler {
         // Create FunctionTargetsHolder with stackless bytecode for all functions in the module,
         let module_env = self.env.get_module(module_id);
         for func_env in module_env.get_functions() {
-            targets.add_target(&func_env)
+            targets.add_target(&func_env);
+            if let Some(data) =
+                targets.get_data_mut(&func_env.get_qualified_id(), &FunctionVariant::Baseline)
+            {
+                data.code = self.recover_vector_literals(mem::take(&mut data.code))
+            }
+        }
+    }
+
+    /// Recovers vector literals from stackless bytecode. The stackless bytecode generator
+    /// lowers `vector[a, ..]` into `vector::empty` followed by a `vector::push_back` per
+    /// element, which would otherwise be decompiled verbatim.
+    fn recover_vector_literals(&self, code: Vec<Bytecode>) -> Vec<Bytecode> {
+        let mut result = vec![];
+        let mut offset = 0;
+        while offset < code.len() {
+            if let Some((literal, len)) = self.match_vector_literal(&code[offset..]) {
+                result.push(literal);
+                offset += len
+            } else {
+                result.push(code[offset].clone());
+                offset += 1
+            }
+        }
+        result
+    }
+
+    /// Matches `t := vector::empty(); r := borrow_local(t); vector::push_back(r, a_1); ..`
+    /// at the start of `code`, returning the equivalent `t := vector(a_1, ..)` and the
+    /// number of instructions it replaces.
+    fn match_vector_literal(&self, code: &[Bytecode]) -> Option<(Bytecode, usize)> {
+        let [Bytecode::Call(attr_id, dests, empty, srcs, _), Bytecode::Call(_, refs, Operation::BorrowLoc, borrowed, _), rest @ ..] =
+            code
+        else {
+            return None;
+        };
+        if !self.is_vector_function(empty, "empty") || !srcs.is_empty() || borrowed != dests {
+            return None;
+        }
+        let elems = rest
+            .iter()
+            .map_while(|bc| match bc {
+                Bytecode::Call(_, push_dests, push, args, _)
+                    if push_dests.is_empty()
+                        && self.is_vector_function(push, "push_back")
+                        && args.len() == 2
+                        && args[0] == refs[0] =>
+                {
+                    Some(args[1])
+                },
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        if elems.is_empty() {
+            return None;
+        }
+        let len = 2 + elems.len();
+        Some((
+            Bytecode::Call(*attr_id, dests.clone(), Operation::Vector, elems, None),
+            len,
+        ))
+    }
+
+    fn is_vector_function(&self, op: &Operation, name: &str) -> bool {
+        match op {
+            Operation::Function(module_id, fun_id, _) => {
+                let module_env = self.env.get_module(*module_id);
+                module_env.is_std_vector()
+                    && module_env.get_function(*fun_id).get_name_str() == name
+            },
+            _ => false,
         }
     }
 
@@ -0,0 +1,1347 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Round-trip tests for the Move decompiler on the Aptos framework.
+//!
+//! Each framework package is compiled, and every module of it decompiled. The decompiled
+//! modules are then recompiled together, where modules whose decompiled source does not
+//! compile are replaced by their original source. Finally, the bytecode of the recompiled
+//! modules is compared with the original bytecode, normalized such that differences in the
+//! allocation of locals, the order of operands and the layout of basic blocks are ignored. The
+//! baseline of a package records for each module whether it round-trips to equivalent
+//! bytecode. The test fails if a module does not, unless the differing functions are listed
+//! in `ALLOWED_DIFFERENCES`.
+
+use codespan::{FileId, Files};
+use codespan_reporting::diagnostic::{Diagnostic, LabelStyle, Severity};
+use libtest_mimic::{Arguments, Trial};
+use move_binary_format::{
+    access::ModuleAccess,
+    file_format::{
+        Bytecode, FunctionDefinition, LocalIndex, SignatureToken, StructDefinitionIndex,
+        StructFieldInformation, StructHandleIndex,
+    },
+    CompiledModule,
+};
+use move_bytecode_source_map::source_map::SourceMap;
+use move_compiler_v2::{diagnostics::Emitter, logging, run_move_compiler, Experiment};
+use move_decompiler::Decompiler;
+use move_prover_test_utils::baseline_test;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Path, PathBuf},
+};
+use tempfile::tempdir;
+
+/// The framework packages to test, with the packages they depend on.
+const PACKAGES: &[(&str, &[&str])] = &[
+    ("move-stdlib", &[]),
+    ("aptos-stdlib", &["move-stdlib"]),
+    ("aptos-framework", &["move-stdlib", "aptos-stdlib"]),
+];
+
+/// The functions which are known to round-trip to bytecode differing from the original one,
+/// with the reason why the difference is benign. Any other difference fails the test.
+const ALLOWED_DIFFERENCES: &[(&str, &str, &str)] = &[
+    (
+        "0x1::simple_map",
+        "upsert",
+        "the first-iteration flag of the loop is set on entry instead of tested, and the \
+         length of the vector is computed once instead of twice",
+    ),
+    (
+        "0x1::big_ordered_map",
+        "remove_at",
+        "a loop exit condition is evaluated where it is tested instead of kept in a local",
+    ),
+    (
+        "0x1::smart_table",
+        "keys_paginated",
+        "the first-iteration flag of the inner loop is set on entry instead of tested, and the \
+         length of the bucket is computed once instead of twice",
+    ),
+    (
+        "0x1::smart_table",
+        "new_with_config",
+        "constant fields are packed directly instead of through locals",
+    ),
+    (
+        "0x1::ethereum_derivable_account",
+        "authenticate_auth_data",
+        "a match borrowing fields of several variants is split into a match per field",
+    ),
+    (
+        "0x1::multisig_account",
+        "failed_transaction_execution_cleanup",
+        "a negated boolean local is folded into the branch testing it",
+    ),
+    (
+        "0x1::multisig_account",
+        "successful_transaction_execution_cleanup",
+        "a negated boolean local is folded into the branch testing it",
+    ),
+    (
+        "0x1::permissioned_delegation",
+        "handle_address_by_key",
+        "an unused reference is dropped instead of stored in a local",
+    ),
+    (
+        "0x1::permissioned_delegation",
+        "permissioned_signer_by_key",
+        "an unused reference is dropped instead of stored in a local",
+    ),
+    (
+        "0x1::stake",
+        "next_validator_consensus_infos",
+        "a condition is evaluated where it is tested instead of kept in a local",
+    ),
+    (
+        "0x1::transaction_validation",
+        "unified_epilogue_v2",
+        "the address of a signer is computed once instead of once per use",
+    ),
+];
+
+const NAMED_ADDRESSES: &[&str] = &[
+    "std=0x1",
+    "aptos_std=0x1",
+    "aptos_framework=0x1",
+    "aptos_fungible_asset=0xA",
+    "aptos_token=0x3",
+    "core_resources=0xA550C18",
+    "vm=0x0",
+    "vm_reserved=0x0",
+];
+
+/// A compiled module, together with information about its source.
+struct Module {
+    module: CompiledModule,
+    source_map: SourceMap,
+    /// The source files of the module, including a separate specification file, if any.
+    source_paths: Vec<String>,
+    /// The names of the modules used by this one, including in specifications.
+    used_modules: BTreeSet<String>,
+    /// Whether the module declares inline functions or specification functions or
+    /// variables, which do not survive compilation to bytecode.
+    has_source_only_decls: bool,
+}
+
+/// An emitter which records the messages of errors by the file they are reported in.
+#[derive(Default)]
+struct ErrorCollector {
+    errors: BTreeMap<String, Vec<String>>,
+}
+
+impl Emitter for ErrorCollector {
+    fn emit(&mut self, source_files: &Files<String>, diag: &Diagnostic<FileId>) {
+        if diag.severity < Severity::Error {
+            return;
+        }
+        let file = diag
+            .labels
+            .iter()
+            .find(|label| label.style == LabelStyle::Primary)
+            .map(|label| {
+                source_files
+                    .name(label.file_id)
+                    .to_string_lossy()
+                    .to_string()
+            })
+            .unwrap_or_default();
+        self.errors
+            .entry(file)
+            .or_default()
+            .push(diag.message.clone());
+    }
+}
+
+fn framework_path(package: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../../../../aptos-move/framework")
+        .join(package)
+        .join("sources")
+}
+
+/// Compiles `sources` against the framework packages `deps`, returning the compiled modules by
+/// name, or the errors by file.
+fn compile(
+    sources: Vec<String>,
+    deps: &[&str],
+) -> Result<BTreeMap<String, Module>, BTreeMap<String, Vec<String>>> {
+    let mut options = move_compiler_v2::Options {
+        sources,
+        dependencies: deps
+            .iter()
+            .map(|dep| framework_path(dep).display().to_string())
+            .collect(),
+        named_address_mapping: NAMED_ADDRESSES.iter().map(|s| s.to_string()).collect(),
+        skip_attribute_checks: true,
+        ..Default::default()
+    };
+    options = options.set_experiment(Experiment::ATTACH_COMPILED_MODULE, true);
+    let mut errors = ErrorCollector::default();
+    let env = match run_move_compiler(&mut errors, options) {
+        Ok((env, _)) => env,
+        Err(err) => {
+            if errors.errors.is_empty() {
+                errors
+                    .errors
+                    .insert(String::new(), vec![format!("{:#}", err)]);
+            }
+            return Err(errors.errors);
+        },
+    };
+    Ok(env
+        .get_modules()
+        .filter(|module_env| module_env.is_target())
+        .filter_map(|module_env| {
+            let module = module_env.get_verified_module()?.clone();
+            let source_map = module_env.get_source_map()?.clone();
+            let source_path = env
+                .get_file(module_env.get_loc().file_id())
+                .to_string_lossy()
+                .to_string();
+            let spec_path = source_path.replace(".move", ".spec.move");
+            let source_paths = if Path::new(&spec_path).exists() {
+                vec![source_path, spec_path]
+            } else {
+                vec![source_path]
+            };
+            // Calls to inline functions are not visible as usage after inlining, so also
+            // consider the `use` declarations
+            let used_modules = module_env
+                .get_used_modules(true)
+                .into_iter()
+                .chain(
+                    module_env
+                        .get_use_decls()
+                        .iter()
+                        .filter_map(|decl| decl.module_id),
+                )
+                .map(|id| env.get_module(id).get_full_name_str())
+                .collect();
+            let has_source_only_decls = module_env.get_functions().any(|fun| fun.is_inline())
+                || module_env.get_spec_funs().next().is_some()
+                || module_env.get_spec_vars().next().is_some();
+            Some((module_env.get_full_name_str(), Module {
+                module,
+                source_map,
+                source_paths,
+                used_modules,
+                has_source_only_decls,
+            }))
+        })
+        .collect())
+}
+
+fn run_round_trip(package: &str, deps: &[&str]) -> anyhow::Result<()> {
+    logging::setup_logging_for_testing(None);
+    let mut output = String::new();
+    let originals = compile(vec![framework_path(package).display().to_string()], deps)
+        .map_err(|errors| anyhow::anyhow!("compiling the framework failed: {:?}", errors))?;
+
+    // Decompile all modules into one file each
+    let mut decompiler = Decompiler::new(move_decompiler::Options::default());
+    let module_ids = originals
+        .iter()
+        .filter_map(|(name, original)| {
+            let id =
+                decompiler.load_module(original.module.clone(), original.source_map.clone())?;
+            Some((name.clone(), id))
+        })
+        .collect::<Vec<_>>();
+    let temp_dir = tempdir()?;
+    let mut decompiled = BTreeMap::new();
+    for (name, id) in module_ids {
+        let source = decompiler.decompile_loaded_module(id);
+        let path = temp_dir
+            .path()
+            .join(format!("{}.move", name.replace("::", "_")))
+            .display()
+            .to_string();
+        fs::write(&path, source)?;
+        decompiled.insert(name, path);
+    }
+    for name in originals.keys() {
+        if !decompiled.contains_key(name) {
+            output.push_str(&format!("module {}: unable to decompile\n", name))
+        }
+    }
+
+    // Recompile the decompiled modules. Modules whose decompiled source has errors are replaced
+    // by their original source. If errors are only in original sources, they are caused by
+    // declarations missing in decompiled dependencies, which are then replaced as well.
+    let mut failures = BTreeMap::new();
+    let recompiled = loop {
+        let sources = originals
+            .iter()
+            .flat_map(|(name, original)| match decompiled.get(name) {
+                Some(path) => vec![path.clone()],
+                None => original.source_paths.clone(),
+            })
+            .collect();
+        let errors = match compile(sources, deps) {
+            Ok(recompiled) => break recompiled,
+            Err(errors) => errors,
+        };
+        let mut failed = decompiled
+            .iter()
+            .filter_map(|(name, path)| {
+                let messages = errors.get(path)?;
+                Some((
+                    name.clone(),
+                    format!(
+                        "unable to recompile, {} error(s), first: {}",
+                        messages.len(),
+                        messages[0]
+                    ),
+                ))
+            })
+            .collect::<BTreeMap<_, _>>();
+        if failed.is_empty() {
+            for (name, original) in &originals {
+                if !original.source_paths.iter().any(|p| errors.contains_key(p)) {
+                    continue;
+                }
+                let used = original
+                    .used_modules
+                    .iter()
+                    .filter(|used| decompiled.contains_key(*used))
+                    .collect::<Vec<_>>();
+                let source_only = used
+                    .iter()
+                    .copied()
+                    .filter(|used| originals[*used].has_source_only_decls)
+                    .collect::<Vec<_>>();
+                let culprits = if source_only.is_empty() {
+                    used
+                } else {
+                    source_only
+                };
+                for culprit in culprits {
+                    failed.entry(culprit.clone()).or_insert_with(|| {
+                        format!("original source of `{}` does not compile against it", name)
+                    });
+                }
+            }
+        }
+        if failed.is_empty() {
+            anyhow::bail!("recompiling original sources failed: {:?}", errors)
+        }
+        for (name, reason) in failed {
+            decompiled.remove(&name);
+            failures.insert(name, reason);
+        }
+    };
+
+    // Compare the bytecode of the recompiled modules with the original one
+    let mut unexpected = vec![];
+    for (name, original) in &originals {
+        if let Some(reason) = failures.get(name) {
+            output.push_str(&format!("module {}: {}\n", name, reason));
+            unexpected.push(format!("module {}: {}", name, reason));
+            continue;
+        }
+        if !decompiled.contains_key(name) {
+            unexpected.push(format!("module {}: unable to decompile", name));
+            continue;
+        }
+        let differences = match recompiled.get(name) {
+            Some(module) => compare_modules(&original.module, &module.module),
+            None => vec!["module missing".to_string()],
+        };
+        if differences.is_empty() {
+            output.push_str(&format!("module {}: equivalent\n", name))
+        } else {
+            output.push_str(&format!(
+                "module {}: differs in {}\n",
+                name,
+                differences.join(", ")
+            ))
+        }
+        let allowed = ALLOWED_DIFFERENCES
+            .iter()
+            .filter(|(module, _, _)| module == name)
+            .map(|(_, function, _)| format!("function `{}`", function))
+            .collect::<BTreeSet<_>>();
+        for difference in &differences {
+            if !allowed.contains(difference) {
+                unexpected.push(format!("module {} differs in {}", name, difference))
+            }
+        }
+        for difference in allowed {
+            if !differences.contains(&difference) {
+                unexpected.push(format!(
+                    "module {} no longer differs in {}, remove it from `ALLOWED_DIFFERENCES`",
+                    name, difference
+                ))
+            }
+        }
+    }
+
+    let baseline_path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/round-trip")
+        .join(package)
+        .with_extension("exp");
+    baseline_test::verify_or_update_baseline(&baseline_path, &output)?;
+    if !unexpected.is_empty() {
+        anyhow::bail!(
+            "modules do not round-trip to equivalent bytecode:\n{}",
+            unexpected.join("\n")
+        )
+    }
+    Ok(())
+}
+
+/// Compares two versions of a module, returning the structs and functions which differ.
+fn compare_modules(old: &CompiledModule, new: &CompiledModule) -> Vec<String> {
+    let structs = |module: &CompiledModule| {
+        (0..module.struct_defs().len())
+            .map(|idx| {
+                let def = module.struct_def_at(StructDefinitionIndex(idx as u16));
+                let handle = module.struct_handle_at(def.struct_handle);
+                let layout = match &def.field_information {
+                    StructFieldInformation::Native => "native".to_string(),
+                    StructFieldInformation::Declared(fields) => fields
+                        .iter()
+                        .map(|field| {
+                            format!(
+                                "{}: {}",
+                                module.identifier_at(field.name),
+                                type_str(module, &field.signature.0)
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    StructFieldInformation::DeclaredVariants(variants) => variants
+                        .iter()
+                        .map(|variant| {
+                            let fields = variant
+                                .fields
+                                .iter()
+                                .map(|field| {
+                                    format!(
+                                        "{}: {}",
+                                        module.identifier_at(field.name),
+                                        type_str(module, &field.signature.0)
+                                    )
+                                })
+                                .collect::<Vec<_>>();
+                            format!(
+                                "{}({})",
+                                module.identifier_at(variant.name),
+                                fields.join(", ")
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                        .join(" | "),
+                };
+                (
+                    module.identifier_at(handle.name).to_string(),
+                    format!(
+                        "{:?} {:?} {}",
+                        handle.abilities, handle.type_parameters, layout
+                    ),
+                )
+            })
+            .collect::<BTreeMap<_, _>>()
+    };
+    let functions = |module: &CompiledModule| {
+        module
+            .function_defs()
+            .iter()
+            .map(|def| {
+                let handle = module.function_handle_at(def.function);
+                (
+                    module.identifier_at(handle.name).to_string(),
+                    normalized_function(module, def),
+                )
+            })
+            .collect::<BTreeMap<_, _>>()
+    };
+    let (old_structs, new_structs) = (structs(old), structs(new));
+    let (old_functions, new_functions) = (functions(old), functions(new));
+    let mut differences = vec![];
+    for (name, layout) in &old_structs {
+        if new_structs.get(name) != Some(layout) {
+            differences.push(format!("struct `{}`", name))
+        }
+    }
+    for (name, function) in &old_functions {
+        if new_functions.get(name) != Some(function) {
+            differences.push(format!("function `{}`", name))
+        }
+    }
+    differences
+}
+
+/// Renders a function such that it can be compared across modules, independent of the order
+/// of the pools of the module, of the allocation of locals, of the order in which operands are
+/// placed on the stack and of the layout of the basic blocks.
+///
+/// Each basic block is evaluated symbolically. A value stored in a local and read again in the
+/// same block is substituted into the expression reading it, such that a block renders as its
+/// effects (instructions without results, returns, aborts and assignments to locals which are
+/// live at the end of the block), in order, with their operands as expression trees. Jumps refer
+/// to their targets by content, skipping blocks which only jump, and the blocks of a function are
+/// sorted. Locals are split into webs, the assignments reaching common uses, and the webs other
+/// than those of parameters are numbered by the order in which they occur in the control flow,
+/// such that locals are renamed consistently, independent of their allocation.
+fn normalized_function(module: &CompiledModule, def: &FunctionDefinition) -> Vec<String> {
+    let handle = module.function_handle_at(def.function);
+    let params = &module.signature_at(handle.parameters).0;
+    let mut lines = vec![format!(
+        "{:?} entry={} {:?} ({}): ({}) {:?}",
+        def.visibility,
+        def.is_entry,
+        handle.type_parameters,
+        types_str(module, params),
+        types_str(module, &module.signature_at(handle.return_).0),
+        handle.attributes,
+    )];
+    let Some(code) = &def.code else {
+        lines.push("native".to_string());
+        return lines;
+    };
+    let instrs = &code.code;
+    let locals = &module.signature_at(code.locals).0;
+
+    // Whether the instruction at an offset moves a local only to drop its value
+    let is_dropped = |offset: usize| {
+        matches!(instrs[offset], Bytecode::MoveLoc(_))
+            && matches!(instrs.get(offset + 1), Some(Bytecode::Pop))
+    };
+
+    // Split the code into basic blocks
+    let mut starts = BTreeSet::from([0]);
+    for (offset, instr) in instrs.iter().enumerate() {
+        match instr {
+            Bytecode::Branch(target) | Bytecode::BrTrue(target) | Bytecode::BrFalse(target) => {
+                starts.insert(*target as usize);
+                starts.insert(offset + 1);
+            },
+            Bytecode::Ret | Bytecode::Abort => {
+                starts.insert(offset + 1);
+            },
+            _ => {},
+        }
+    }
+    let starts = starts
+        .into_iter()
+        .filter(|start| *start < instrs.len())
+        .collect::<Vec<_>>();
+    let block_at = |offset: usize| starts.partition_point(|start| *start <= offset) - 1;
+    let block_code =
+        |block: usize| starts[block]..starts.get(block + 1).copied().unwrap_or(instrs.len());
+    let ends = |block: usize| {
+        let code = &instrs[block_code(block)];
+        matches!(code.last(), Some(Bytecode::Ret | Bytecode::Abort))
+            || code
+                .iter()
+                .all(|instr| matches!(instr, Bytecode::Branch(_) | Bytecode::Nop))
+    };
+
+    // Compute the definitions of locals reaching each instruction, where parameters are defined
+    // on entry, and group the definitions reaching a common use into webs. Locals are named by
+    // their web, such that a local reused for an unrelated value is renamed.
+    let mut def_at = BTreeMap::new();
+    let mut def_local = (0..params.len() as LocalIndex).collect::<Vec<_>>();
+    for (offset, instr) in instrs.iter().enumerate() {
+        if let Bytecode::StLoc(idx) = instr {
+            def_at.insert(offset, def_local.len());
+            def_local.push(*idx);
+        }
+    }
+    let transfer = |offset: usize, reaching: &mut [BTreeSet<usize>]| {
+        if let Bytecode::StLoc(idx) = &instrs[offset] {
+            reaching[*idx as usize] = BTreeSet::from([def_at[&offset]])
+        }
+    };
+    let block_successors = |block: usize| {
+        let last = block_code(block).end - 1;
+        match &instrs[last] {
+            Bytecode::Branch(target) => vec![block_at(*target as usize)],
+            Bytecode::BrTrue(target) | Bytecode::BrFalse(target) => {
+                vec![block_at(*target as usize), block + 1]
+            },
+            Bytecode::Ret | Bytecode::Abort => vec![],
+            _ if block + 1 < starts.len() => vec![block + 1],
+            _ => vec![],
+        }
+    };
+    let mut reaching_in = vec![vec![BTreeSet::new(); params.len() + locals.len()]; starts.len()];
+    for (param, reaching) in reaching_in[0].iter_mut().take(params.len()).enumerate() {
+        reaching.insert(param);
+    }
+    loop {
+        let mut changed = false;
+        for block in 0..starts.len() {
+            let mut reaching = reaching_in[block].clone();
+            for offset in block_code(block) {
+                transfer(offset, &mut reaching)
+            }
+            for succ in block_successors(block) {
+                for (idx, defs) in reaching.iter().enumerate() {
+                    for def in defs {
+                        changed |= reaching_in[succ][idx].insert(*def)
+                    }
+                }
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+    let mut parents = (0..def_local.len()).collect::<Vec<_>>();
+    let find = |parents: &[usize], mut def: usize| {
+        while parents[def] != def {
+            def = parents[def]
+        }
+        def
+    };
+    let mut use_def = BTreeMap::new();
+    let mut reaching_at = BTreeMap::new();
+    for (block, reaching_in) in reaching_in.iter().enumerate() {
+        let mut reaching = reaching_in.clone();
+        for offset in block_code(block) {
+            if let Bytecode::CopyLoc(idx)
+            | Bytecode::MoveLoc(idx)
+            | Bytecode::MutBorrowLoc(idx)
+            | Bytecode::ImmBorrowLoc(idx) = &instrs[offset]
+            {
+                let defs = &reaching[*idx as usize];
+                if let Some(first) = defs.first() {
+                    let root = find(&parents, *first);
+                    for def in defs {
+                        let other = find(&parents, *def);
+                        parents[other] = root
+                    }
+                    use_def.insert(offset, *first);
+                }
+                reaching_at.insert(offset, reaching.clone());
+            }
+            transfer(offset, &mut reaching)
+        }
+    }
+    let web_at = |offset: usize| {
+        let def = use_def.get(&offset).or_else(|| def_at.get(&offset))?;
+        Some(find(&parents, *def))
+    };
+    let mut web_defs: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for def in 0..def_local.len() {
+        web_defs.entry(find(&parents, def)).or_default().push(def)
+    }
+
+    // A web which is only assigned once, to the value of another local, is a copy of that local
+    // and named like it, if the local is not assigned again before any use of the copy, and if
+    // neither is mutated while both are available. The instruction loading the value assigned to
+    // a local is found by following the stack within the block.
+    let mut loaded_at = BTreeMap::new();
+    for block in 0..starts.len() {
+        let mut stack: Vec<Option<usize>> = vec![];
+        for offset in block_code(block) {
+            let instr = &instrs[offset];
+            let (pops, pushes) = stack_effect(module, instr);
+            let popped = stack.split_off(stack.len().saturating_sub(pops));
+            if let (Bytecode::StLoc(_), [Some(load)]) = (instr, popped.as_slice()) {
+                loaded_at.insert(offset, *load);
+            }
+            let is_load = matches!(instr, Bytecode::CopyLoc(_) | Bytecode::MoveLoc(_));
+            stack.extend((0..pushes).map(|_| is_load.then_some(offset)))
+        }
+    }
+    let copies = web_defs
+        .iter()
+        .filter_map(|(web, defs)| {
+            let [def] = defs.as_slice() else {
+                return None;
+            };
+            let (offset, _) = def_at.iter().find(|(_, d)| *d == def)?;
+            let load = *loaded_at.get(offset)?;
+            let source = web_at(load)?;
+            let (Bytecode::CopyLoc(idx) | Bytecode::MoveLoc(idx)) = &instrs[load] else {
+                return None;
+            };
+            let uses = use_def
+                .keys()
+                .filter(|offset| web_at(**offset) == Some(*web))
+                .collect::<Vec<_>>();
+            let unchanged = uses.iter().all(|offset| {
+                reaching_at[*offset][*idx as usize] == reaching_at[&load][*idx as usize]
+            });
+            let mutated = matches!(instrs[load], Bytecode::CopyLoc(_))
+                && use_def.keys().any(|offset| {
+                    matches!(instrs[*offset], Bytecode::MutBorrowLoc(_))
+                        && [Some(*web), Some(source)].contains(&web_at(*offset))
+                });
+            (unchanged && !mutated).then_some((*web, source))
+        })
+        .collect::<BTreeMap<_, _>>();
+    let web_name = |mut web: usize| {
+        for _ in 0..copies.len() {
+            match copies.get(&web) {
+                Some(source) => web = *source,
+                None => break,
+            }
+        }
+        match web_defs[&web].iter().find(|def| **def < params.len()) {
+            Some(param) => format!("arg{}", param),
+            None => format!("$L{}$", web),
+        }
+    };
+    let local_name = |offset: usize| match &instrs[offset] {
+        Bytecode::CopyLoc(idx)
+        | Bytecode::MoveLoc(idx)
+        | Bytecode::MutBorrowLoc(idx)
+        | Bytecode::ImmBorrowLoc(idx)
+        | Bytecode::StLoc(idx) => match web_at(offset) {
+            Some(web) => web_name(web),
+            None => format!("$unreachable{}", idx),
+        },
+        _ => unreachable!("not an instruction on locals"),
+    };
+    let names = web_defs
+        .keys()
+        .map(|web| web_name(*web))
+        .collect::<BTreeSet<_>>();
+    // Dropping the value of a local, or a reference to it, has no effect, nor has dropping one
+    // of several results of an instruction, whose effect is accounted for by the others
+    let drop = |value: String, effects: &mut Vec<String>| {
+        let local = value.trim_start_matches("&mut ").trim_start_matches('&');
+        let is_result = value
+            .rsplit_once(").")
+            .is_some_and(|(_, idx)| idx.parse::<usize>().is_ok());
+        if !names.contains(local) && !is_result {
+            effects.push(format!("Pop({})", value))
+        }
+    };
+
+    // Append to each block the code of the blocks it jumps to, as long as those only jump or
+    // end the function, and compute the successors of the resulting blocks
+    let mut blocks_code = vec![];
+    let mut successors = vec![];
+    for block in 0..starts.len() {
+        let mut offsets = block_code(block).collect::<Vec<_>>();
+        let mut succs = vec![];
+        for _ in 0..starts.len() {
+            let last = offsets[offsets.len() - 1];
+            succs = match &instrs[last] {
+                Bytecode::Branch(target) => vec![block_at(*target as usize)],
+                Bytecode::BrTrue(target) | Bytecode::BrFalse(target) => {
+                    vec![block_at(*target as usize), block_at(last + 1)]
+                },
+                Bytecode::Ret | Bytecode::Abort => vec![],
+                _ if last + 1 < instrs.len() => vec![block_at(last + 1)],
+                _ => vec![],
+            };
+            match &instrs[last] {
+                Bytecode::BrTrue(_) | Bytecode::BrFalse(_) => break,
+                Bytecode::Branch(_) if ends(succs[0]) => {
+                    offsets.pop();
+                },
+                _ if succs.len() == 1 && ends(succs[0]) => {},
+                _ => break,
+            }
+            offsets.extend(block_code(succs[0]));
+        }
+        blocks_code.push(offsets);
+        successors.push(succs);
+    }
+
+    // Compute the locals live at the end of each block
+    let mut uses = vec![BTreeSet::new(); starts.len()];
+    let mut defs = vec![BTreeSet::new(); starts.len()];
+    for (block, offsets) in blocks_code.iter().enumerate() {
+        for offset in offsets {
+            match &instrs[*offset] {
+                _ if is_dropped(*offset) => {},
+                Bytecode::CopyLoc(idx)
+                | Bytecode::MoveLoc(idx)
+                | Bytecode::MutBorrowLoc(idx)
+                | Bytecode::ImmBorrowLoc(idx)
+                    if !defs[block].contains(idx) =>
+                {
+                    uses[block].insert(*idx);
+                },
+                Bytecode::StLoc(idx) => {
+                    defs[block].insert(*idx);
+                },
+                _ => {},
+            }
+        }
+    }
+    let mut live_in = uses.clone();
+    let live_out = |live_in: &[BTreeSet<LocalIndex>], block: usize| {
+        successors[block]
+            .iter()
+            .flat_map(|succ| live_in[*succ].iter().copied())
+            .collect::<BTreeSet<_>>()
+    };
+    loop {
+        let mut changed = false;
+        for block in (0..starts.len()).rev() {
+            let live = live_out(&live_in, block)
+                .difference(&defs[block])
+                .copied()
+                .collect::<Vec<_>>();
+            for idx in live {
+                changed |= live_in[block].insert(idx)
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    // Evaluate each block, resulting in its effects and how it continues, if at all. A stored
+    // value which is neither read nor live is dropped, as if it were popped.
+    let mut blocks = vec![];
+    for (block, offsets) in blocks_code.iter().enumerate() {
+        let live = live_out(&live_in, block);
+        let mut stack: Vec<String> = vec![];
+        let mut stored: Vec<(LocalIndex, String, String, bool)> = vec![];
+        let mut effects = vec![];
+        let mut exit = successors[block].first().map(|succ| Exit::Jump(*succ));
+        let mut offsets = offsets.iter().peekable();
+        while let Some(offset) = offsets.next() {
+            let instr = &instrs[*offset];
+            let stored_at = |local: &LocalIndex, stored: &[(LocalIndex, String, String, bool)]| {
+                stored.iter().position(|(idx, _, _, _)| idx == local)
+            };
+            match instr {
+                Bytecode::Nop => {},
+                Bytecode::CopyLoc(idx) => stack.push(match stored_at(idx, &stored) {
+                    Some(pos) => {
+                        stored[pos].3 = true;
+                        stored[pos].2.clone()
+                    },
+                    None => local_name(*offset),
+                }),
+                Bytecode::MoveLoc(idx) if is_dropped(*offset) => {
+                    offsets.next();
+                    if let Some(pos) = stored_at(idx, &stored) {
+                        let (_, _, value, read) = stored.remove(pos);
+                        if !read {
+                            drop(value, &mut effects)
+                        }
+                    }
+                },
+                Bytecode::MoveLoc(idx) => stack.push(match stored_at(idx, &stored) {
+                    Some(pos) => stored.remove(pos).2,
+                    None => local_name(*offset),
+                }),
+                Bytecode::StLoc(idx) => {
+                    if let Some(pos) = stored_at(idx, &stored) {
+                        let (_, _, value, read) = stored.remove(pos);
+                        if !read {
+                            drop(value, &mut effects)
+                        }
+                    }
+                    let value = stack.pop().unwrap_or_default();
+                    stored.push((*idx, local_name(*offset), value, false))
+                },
+                Bytecode::MutBorrowLoc(idx) | Bytecode::ImmBorrowLoc(idx) => {
+                    if let Some(pos) = stored_at(idx, &stored) {
+                        let (_, name, value, _) = stored.remove(pos);
+                        if name != value {
+                            effects.push(format!("{} = {}", name, value))
+                        }
+                    }
+                    let mutability = if matches!(instr, Bytecode::MutBorrowLoc(_)) {
+                        "mut "
+                    } else {
+                        ""
+                    };
+                    stack.push(format!("&{}{}", mutability, local_name(*offset)))
+                },
+                // Empty vectors are either loaded as constants or packed
+                Bytecode::LdConst(idx) => {
+                    let constant = module.constant_at(*idx);
+                    stack.push(match &constant.type_ {
+                        SignatureToken::Vector(elem) if constant.data == [0] => {
+                            format!("VecPack({}, 0)()", type_str(module, elem))
+                        },
+                        _ => format!("{}()", instr_str(module, instr)),
+                    })
+                },
+                Bytecode::Pop => drop(stack.pop().unwrap_or_default(), &mut effects),
+                Bytecode::Branch(target) => exit = Some(Exit::Jump(block_at(*target as usize))),
+                Bytecode::BrTrue(target) => {
+                    let cond = stack.pop().unwrap_or_default();
+                    exit = Some(Exit::branch(
+                        cond,
+                        block_at(*target as usize),
+                        block_at(offset + 1),
+                    ))
+                },
+                Bytecode::BrFalse(target) => {
+                    let cond = stack.pop().unwrap_or_default();
+                    exit = Some(Exit::branch(
+                        cond,
+                        block_at(offset + 1),
+                        block_at(*target as usize),
+                    ))
+                },
+                _ => {
+                    let (pops, pushes) = match instr {
+                        Bytecode::Ret => (module.signature_at(handle.return_).len(), 0),
+                        _ => stack_effect(module, instr),
+                    };
+                    let mut args = stack.split_off(stack.len().saturating_sub(pops));
+                    // Reading through a reference does not depend on its mutability
+                    if let (Bytecode::ReadRef, [arg]) = (instr, args.as_mut_slice()) {
+                        if let Some(borrow) = arg.strip_prefix("MutBorrow") {
+                            *arg = format!("ImmBorrow{}", borrow)
+                        }
+                    }
+                    let value = format!("{}({})", instr_str(module, instr), args.join(", "));
+                    match pushes {
+                        0 => effects.push(value),
+                        1 => stack.push(value),
+                        _ => stack.extend((0..pushes).map(|idx| format!("{}.{}", value, idx))),
+                    }
+                    if matches!(instr, Bytecode::Ret | Bytecode::Abort) {
+                        exit = None
+                    }
+                },
+            }
+        }
+        for (idx, name, value, read) in stored {
+            if name == value {
+                continue;
+            } else if live.contains(&idx) {
+                effects.push(format!("{} = {}", name, value))
+            } else if !read {
+                drop(value, &mut effects)
+            }
+        }
+        blocks.push((effects, exit));
+    }
+
+    // Normalize the control flow. A jump to a branch on a boolean local, which was assigned
+    // right before the jump, branches on the assigned value instead, as `&&` and `||` are
+    // lowered either way. A jump to a block which only jumps, or which ends the function, is
+    // replaced by the code of that block.
+    for _ in 0..blocks.len() {
+        let previous = blocks.clone();
+        for block in 0..blocks.len() {
+            normalize_jump(&mut blocks, block)
+        }
+        if blocks == previous {
+            break;
+        }
+    }
+
+    // Number the webs of locals other than parameters in the order they occur when visiting the
+    // blocks reachable from the entry depth-first, the block if a branch is true before the one
+    // if it is false. Up to here, they are referred to by placeholders `$L<web>$`.
+    let mut reachable = BTreeSet::new();
+    let mut numbers = BTreeMap::new();
+    let mut todo = vec![0];
+    while let Some(block) = todo.pop() {
+        if block >= blocks.len() || !reachable.insert(block) {
+            continue;
+        }
+        let (effects, exit) = &blocks[block];
+        let cond = match exit {
+            Some(Exit::Branch(cond, _, _)) => Some(cond),
+            _ => None,
+        };
+        for text in effects.iter().chain(cond) {
+            for placeholder in text.split("$L").skip(1) {
+                if let Some(web) = placeholder
+                    .split_once('$')
+                    .and_then(|(web, _)| web.parse::<usize>().ok())
+                {
+                    let number = numbers.len();
+                    numbers.entry(web).or_insert(number);
+                }
+            }
+        }
+        match exit {
+            Some(Exit::Jump(target)) => todo.push(*target),
+            Some(Exit::Branch(_, if_true, if_false)) => todo.extend([*if_false, *if_true]),
+            None => {},
+        }
+    }
+    let rename = |text: String| {
+        numbers.iter().fold(text, |text, (web, number)| {
+            let ty = params.iter().chain(locals).nth(def_local[*web] as usize);
+            text.replace(
+                &format!("$L{}$", web),
+                &format!("loc{}: {}", number, type_str(module, ty.expect("local"))),
+            )
+        })
+    };
+
+    // Render the blocks reachable from the entry, skipping those which only jump. Blocks are
+    // referred to by their content, such that duplicated blocks are indistinguishable.
+    let label = |mut block: usize| {
+        for _ in 0..blocks.len() {
+            match &blocks[block] {
+                (effects, Some(Exit::Jump(target))) if effects.is_empty() => block = *target,
+                _ => break,
+            }
+        }
+        match &blocks[block] {
+            (effects, Some(Exit::Branch(cond, _, _))) if effects.is_empty() => {
+                format!("[if {}]", cond)
+            },
+            (effects, _) => format!("[{}]", effects.join("; ")),
+        }
+    };
+    let rendered = reachable
+        .into_iter()
+        .filter_map(|block| {
+            let (effects, exit) = &blocks[block];
+            let exit = match exit {
+                Some(Exit::Jump(_)) if effects.is_empty() => return None,
+                Some(Exit::Jump(target)) => format!("goto {}", label(*target)),
+                Some(Exit::Branch(cond, if_true, if_false)) => format!(
+                    "if {} goto {} else {}",
+                    cond,
+                    label(*if_true),
+                    label(*if_false)
+                ),
+                None => "end".to_string(),
+            };
+            Some(rename(format!("{} => {}", effects.join("; "), exit)))
+        })
+        .collect::<BTreeSet<_>>();
+    lines.extend(rendered);
+    lines
+}
+
+/// Follows the jump at the end of a block, as described in `normalized_function`.
+fn normalize_jump(blocks: &mut [(Vec<String>, Option<Exit>)], block: usize) {
+    for _ in 0..blocks.len() {
+        let Some(Exit::Jump(target)) = blocks[block].1 else {
+            return;
+        };
+        let (target_effects, target_exit) = blocks[target].clone();
+        let (effects, exit) = &mut blocks[block];
+        match target_exit {
+            Some(Exit::Branch(cond, if_true, if_false)) if target_effects.is_empty() => {
+                let Some(value) = effects
+                    .last()
+                    .and_then(|effect| effect.strip_prefix(&format!("{} = ", cond)))
+                else {
+                    return;
+                };
+                *exit = Some(match value {
+                    "LdTrue()" => Exit::Jump(if_true),
+                    "LdFalse()" => Exit::Jump(if_false),
+                    _ => Exit::branch(value.to_string(), if_true, if_false),
+                });
+                effects.pop();
+            },
+            _ if target_effects.is_empty() || target_exit.is_none() => {
+                effects.extend(target_effects);
+                *exit = target_exit
+            },
+            _ => return,
+        }
+    }
+}
+
+/// How a basic block continues.
+#[derive(Clone, PartialEq)]
+enum Exit {
+    Jump(usize),
+    /// A branch on a condition, to the blocks if true and if false.
+    Branch(String, usize, usize),
+}
+
+impl Exit {
+    /// Creates a branch, where a negated condition is replaced by swapping the targets.
+    fn branch(cond: String, if_true: usize, if_false: usize) -> Self {
+        match cond
+            .strip_prefix("Not(")
+            .and_then(|cond| cond.strip_suffix(')'))
+        {
+            Some(cond) => Exit::Branch(cond.to_string(), if_false, if_true),
+            None => Exit::Branch(cond, if_true, if_false),
+        }
+    }
+}
+
+/// Returns the number of values an instruction pops from and pushes onto the stack. Instructions
+/// on locals, branches and returns are evaluated by `normalized_function` itself.
+fn stack_effect(module: &CompiledModule, instr: &Bytecode) -> (usize, usize) {
+    use Bytecode::*;
+    let call = |idx| {
+        let handle = module.function_handle_at(idx);
+        (
+            module.signature_at(handle.parameters).len(),
+            module.signature_at(handle.return_).len(),
+        )
+    };
+    let field_count = |idx: StructDefinitionIndex, variant| {
+        module
+            .struct_def_at(idx)
+            .field_information
+            .field_count(variant)
+    };
+    let variant_field_count = |idx| {
+        let handle = module.struct_variant_handle_at(idx);
+        field_count(handle.struct_index, Some(handle.variant))
+    };
+    match instr {
+        Pop | Abort => (1, 0),
+        LdU8(_) | LdU16(_) | LdU32(_) | LdU64(_) | LdU128(_) | LdU256(_) | LdTrue | LdFalse
+        | LdConst(_) => (0, 1),
+        Not
+        | FreezeRef
+        | ReadRef
+        | Exists(_)
+        | ExistsGeneric(_)
+        | MutBorrowGlobal(_)
+        | MutBorrowGlobalGeneric(_)
+        | ImmBorrowGlobal(_)
+        | ImmBorrowGlobalGeneric(_)
+        | MutBorrowField(_)
+        | MutBorrowFieldGeneric(_)
+        | ImmBorrowField(_)
+        | ImmBorrowFieldGeneric(_)
+        | MutBorrowVariantField(_)
+        | MutBorrowVariantFieldGeneric(_)
+        | ImmBorrowVariantField(_)
+        | ImmBorrowVariantFieldGeneric(_)
+        | TestVariant(_)
+        | TestVariantGeneric(_)
+        | MoveFrom(_)
+        | MoveFromGeneric(_)
+        | CastU8
+        | CastU16
+        | CastU32
+        | CastU64
+        | CastU128
+        | CastU256
+        | VecLen(_)
+        | VecPopBack(_) => (1, 1),
+        Add | Sub | Mul | Mod | Div | BitOr | BitAnd | Xor | Shl | Shr | Or | And | Eq | Neq
+        | Lt | Gt | Le | Ge | VecImmBorrow(_) | VecMutBorrow(_) => (2, 1),
+        MoveTo(_) | MoveToGeneric(_) | WriteRef | VecPushBack(_) => (2, 0),
+        VecSwap(_) => (3, 0),
+        VecPack(_, n) => (*n as usize, 1),
+        VecUnpack(_, n) => (1, *n as usize),
+        Call(idx) => call(*idx),
+        CallGeneric(idx) => call(module.function_instantiation_at(*idx).handle),
+        CallClosure(idx) => match module.signature_at(*idx).0.first() {
+            Some(SignatureToken::Function(args, results, _)) => (1 + args.len(), results.len()),
+            _ => (0, 0),
+        },
+        PackClosure(_, mask) | PackClosureGeneric(_, mask) => (mask.captured_count() as usize, 1),
+        Pack(idx) => (field_count(*idx, None), 1),
+        PackGeneric(idx) => (
+            field_count(module.struct_instantiation_at(*idx).def, None),
+            1,
+        ),
+        PackVariant(idx) => (variant_field_count(*idx), 1),
+        PackVariantGeneric(idx) => (
+            variant_field_count(module.struct_variant_instantiation_at(*idx).handle),
+            1,
+        ),
+        Unpack(idx) => (1, field_count(*idx, None)),
+        UnpackGeneric(idx) => (
+            1,
+            field_count(module.struct_instantiation_at(*idx).def, None),
+        ),
+        UnpackVariant(idx) => (1, variant_field_count(*idx)),
+        UnpackVariantGeneric(idx) => (
+            1,
+            variant_field_count(module.struct_variant_instantiation_at(*idx).handle),
+        ),
+        BrTrue(_) | BrFalse(_) | StLoc(_) => (1, 0),
+        CopyLoc(_) | MoveLoc(_) | MutBorrowLoc(_) | ImmBorrowLoc(_) => (0, 1),
+        Branch(_) | Ret | Nop => (0, 0),
+    }
+}
+
+/// Renders an instruction, with indices into the pools of the module resolved.
+fn instr_str(module: &CompiledModule, instr: &Bytecode) -> String {
+    use Bytecode::*;
+    let struct_name = |idx: StructDefinitionIndex| {
+        struct_handle_str(module, module.struct_def_at(idx).struct_handle)
+    };
+    let inst = |idx| types_str(module, &module.signature_at(idx).0);
+    let function_name = |idx| {
+        let handle = module.function_handle_at(idx);
+        format!(
+            "{}::{}",
+            module.module_id_for_handle(module.module_handle_at(handle.module)),
+            module.identifier_at(handle.name)
+        )
+    };
+    let field_name =
+        |owner: StructDefinitionIndex, field: u16| format!("{}.{}", struct_name(owner), field);
+    let variant_name = |idx| {
+        let handle = module.struct_variant_handle_at(idx);
+        format!("{}::{}", struct_name(handle.struct_index), handle.variant)
+    };
+    let variant_field_name = |idx| {
+        let handle = module.variant_field_handle_at(idx);
+        format!(
+            "{}{:?}.{}",
+            struct_name(handle.struct_index),
+            handle.variants,
+            handle.field
+        )
+    };
+    match instr {
+        LdConst(idx) => {
+            let constant = module.constant_at(*idx);
+            format!(
+                "LdConst({}, {:?})",
+                type_str(module, &constant.type_),
+                constant.data
+            )
+        },
+        Call(idx) => format!("Call({})", function_name(*idx)),
+        CallGeneric(idx) => {
+            let fi = module.function_instantiation_at(*idx);
+            format!(
+                "CallGeneric({}<{}>)",
+                function_name(fi.handle),
+                inst(fi.type_parameters)
+            )
+        },
+        PackClosure(idx, mask) => format!("PackClosure({}, {})", function_name(*idx), mask),
+        PackClosureGeneric(idx, mask) => {
+            let fi = module.function_instantiation_at(*idx);
+            format!(
+                "PackClosureGeneric({}<{}>, {})",
+                function_name(fi.handle),
+                inst(fi.type_parameters),
+                mask
+            )
+        },
+        CallClosure(idx) => format!("CallClosure({})", inst(*idx)),
+        Pack(idx) | Unpack(idx) | MutBorrowGlobal(idx) | ImmBorrowGlobal(idx) | Exists(idx)
+        | MoveFrom(idx) | MoveTo(idx) => {
+            format!("{}({})", opcode_name(instr), struct_name(*idx))
+        },
+        PackGeneric(idx)
+        | UnpackGeneric(idx)
+        | MutBorrowGlobalGeneric(idx)
+        | ImmBorrowGlobalGeneric(idx)
+        | ExistsGeneric(idx)
+        | MoveFromGeneric(idx)
+        | MoveToGeneric(idx) => {
+            let si = module.struct_instantiation_at(*idx);
+            format!(
+                "{}({}<{}>)",
+                opcode_name(instr),
+                struct_name(si.def),
+                inst(si.type_parameters)
+            )
+        },
+        PackVariant(idx) | UnpackVariant(idx) | TestVariant(idx) => {
+            format!("{}({})", opcode_name(instr), variant_name(*idx))
+        },
+        PackVariantGeneric(idx) | UnpackVariantGeneric(idx) | TestVariantGeneric(idx) => {
+            let vi = module.struct_variant_instantiation_at(*idx);
+            format!(
+                "{}({}<{}>)",
+                opcode_name(instr),
+                variant_name(vi.handle),
+                inst(vi.type_parameters)
+            )
+        },
+        MutBorrowField(idx) | ImmBorrowField(idx) => {
+            let handle = module.field_handle_at(*idx);
+            format!(
+                "{}({})",
+                opcode_name(instr),
+                field_name(handle.owner, handle.field)
+            )
+        },
+        MutBorrowFieldGeneric(idx) | ImmBorrowFieldGeneric(idx) => {
+            let fi = module.field_instantiation_at(*idx);
+            let handle = module.field_handle_at(fi.handle);
+            format!(
+                "{}({}<{}>)",
+                opcode_name(instr),
+                field_name(handle.owner, handle.field),
+                inst(fi.type_parameters)
+            )
+        },
+        MutBorrowVariantField(idx) | ImmBorrowVariantField(idx) => {
+            format!("{}({})", opcode_name(instr), variant_field_name(*idx))
+        },
+        MutBorrowVariantFieldGeneric(idx) | ImmBorrowVariantFieldGeneric(idx) => {
+            let vi = module.variant_field_instantiation_at(*idx);
+            format!(
+                "{}({}<{}>)",
+                opcode_name(instr),
+                variant_field_name(vi.handle),
+                inst(vi.type_parameters)
+            )
+        },
+        VecPack(idx, n) | VecUnpack(idx, n) => {
+            format!("{}({}, {})", opcode_name(instr), inst(*idx), n)
+        },
+        VecLen(idx) | VecImmBorrow(idx) | VecMutBorrow(idx) | VecPushBack(idx)
+        | VecPopBack(idx) | VecSwap(idx) => format!("{}({})", opcode_name(instr), inst(*idx)),
+        _ => format!("{:?}", instr),
+    }
+}
+
+/// Returns the name of the opcode of an instruction, without its operands.
+fn opcode_name(instr: &Bytecode) -> String {
+    let debug = format!("{:?}", instr);
+    debug.split('(').next().unwrap_or_default().to_string()
+}
+
+fn struct_handle_str(module: &CompiledModule, idx: StructHandleIndex) -> String {
+    let handle = module.struct_handle_at(idx);
+    format!(
+        "{}::{}",
+        module.module_id_for_handle(module.module_handle_at(handle.module)),
+        module.identifier_at(handle.name)
+    )
+}
+
+fn types_str(module: &CompiledModule, tys: &[SignatureToken]) -> String {
+    tys.iter()
+        .map(|ty| type_str(module, ty))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Renders a type, with struct handles resolved.
+fn type_str(module: &CompiledModule, ty: &SignatureToken) -> String {
+    use SignatureToken::*;
+    match ty {
+        Struct(idx) => struct_handle_str(module, *idx),
+        StructInstantiation(idx, inst) => format!(
+            "{}<{}>",
+            struct_handle_str(module, *idx),
+            types_str(module, inst)
+        ),
+        Vector(elem) => format!("vector<{}>", type_str(module, elem)),
+        Reference(ty) => format!("&{}", type_str(module, ty)),
+        MutableReference(ty) => format!("&mut {}", type_str(module, ty)),
+        Function(args, results, abilities) => format!(
+            "|{}|({}) {:?}",
+            types_str(module, args),
+            types_str(module, results),
+            abilities
+        ),
+        _ => format!("{:?}", ty),
+    }
+}
+
+fn main() {
+    let args = Arguments::from_args();
+    let tests = PACKAGES
+        .iter()
+        .map(|(package, deps)| {
+            Trial::test(format!("decompiler::round-trip::{}", package), move || {
+                run_round_trip(package, deps).map_err(|err| format!("{:?}", err).into())
+            })
+        })
+        .collect();
+    libtest_mimic::run(&args, tests).exit()
+}
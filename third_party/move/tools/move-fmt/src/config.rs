@@ -0,0 +1,107 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{anyhow, bail, Context, Result};
+use std::{fs, path::Path};
+
+/// The name of the configuration file which is looked up next to the sources being formatted.
+pub const CONFIG_FILE_NAME: &str = "movefmt.toml";
+
+/// Options controlling the layout of formatted code.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FormatConfig {
+    /// The maximal width of a line. Lines are only longer if they cannot be broken.
+    pub max_width: usize,
+    /// The number of spaces used for one level of indentation.
+    pub indent_size: usize,
+}
+
+impl Default for FormatConfig {
+    fn default() -> Self {
+        Self {
+            max_width: 90,
+            indent_size: 4,
+        }
+    }
+}
+
+impl FormatConfig {
+    /// Sets the option `key` to `value`, as given in a configuration file or on the command
+    /// line. Unknown options, e.g., those of earlier versions of movefmt, are ignored with a
+    /// warning, so that existing configuration files keep working.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        let parse = |value: &str| {
+            value
+                .parse::<usize>()
+                .map_err(|_| anyhow!("invalid value `{}` for `{}`, expected a number", value, key))
+        };
+        match key {
+            "max_width" => self.max_width = parse(value)?,
+            // `tab_spaces` is the name used by rustfmt and earlier versions of movefmt
+            "indent_size" | "tab_spaces" => self.indent_size = parse(value)?,
+            _ => {
+                eprintln!(
+                    "Warning: ignoring unknown formatter option `{}`, expected one of `max_width` or `indent_size`",
+                    key
+                );
+                return Ok(());
+            },
+        }
+        if self.indent_size == 0 || self.indent_size >= self.max_width {
+            bail!("`indent_size` must be positive and smaller than `max_width`")
+        }
+        Ok(())
+    }
+
+    /// Applies the options of the TOML file at `path`.
+    pub fn load(&mut self, path: &Path) -> Result<()> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("failed to read `{}`", path.display()))?;
+        let table: toml::Table = content
+            .parse()
+            .with_context(|| format!("failed to parse `{}`", path.display()))?;
+        for (key, value) in table {
+            let value = match value {
+                toml::Value::String(s) => s,
+                value => value.to_string(),
+            };
+            self.set(&key, &value)
+                .with_context(|| format!("in `{}`", path.display()))?;
+        }
+        Ok(())
+    }
+
+    /// Searches `dir` and its parents for a configuration file.
+    pub fn find_config_file(dir: &Path) -> Option<std::path::PathBuf> {
+        dir.ancestors()
+            .map(|dir| dir.join(CONFIG_FILE_NAME))
+            .find(|path| path.is_file())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_options() {
+        let mut config = FormatConfig::default();
+        config.set("max_width", "100").unwrap();
+        config.set("indent_size", "2").unwrap();
+        assert_eq!(config, FormatConfig {
+            max_width: 100,
+            indent_size: 2
+        });
+        assert!(config.set("max_width", "wide").is_err());
+        // Options of earlier versions of movefmt are ignored
+        config.set("emit_mode", "diff").unwrap();
+        config
+            .set("prefer_one_line_for_short_call_para_list", "true")
+            .unwrap();
+        assert_eq!(config, FormatConfig {
+            max_width: 100,
+            indent_size: 2
+        });
+        assert!(config.set("indent_size", "0").is_err());
+    }
+}
@@ -0,0 +1,622 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Collects the facts about tokens which the layout needs, but which cannot be told from
+//! the token stream alone, from the AST. For example, whether `{` opens a block or a struct
+//! literal, or whether `*` is a multiplication or a dereference.
+
+use crate::tokens::Token;
+use legacy_move_compiler::parser::{ast::*, lexer::Tok};
+use move_ir_types::location::{Loc, Spanned};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// How the items of a block, whose braces are always broken across lines, are separated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum BlockKind {
+    /// Items of a sequence, a module, or a spec block, ending in `;` or a nested block.
+    Seq,
+    /// Fields of a struct or variants of an enum, which always end in `,`.
+    Fields,
+    /// Arms of a match expression, where `,` is optional after a block.
+    Arms,
+}
+
+/// Facts about tokens, keyed by the byte offset of the token in the source.
+#[derive(Debug, Default)]
+pub(crate) struct Hints {
+    /// `{` which open blocks.
+    pub blocks: BTreeMap<usize, BlockKind>,
+    /// `<` which open type parameters or arguments.
+    pub type_args: BTreeSet<usize>,
+    /// `|` which open the parameters of a lambda or function type.
+    pub pipes: BTreeSet<usize>,
+    /// `!`, `&`, `&mut` and `*` used as prefix operators.
+    pub unary_ops: BTreeSet<usize>,
+    /// `(` which open the arguments of a call, or the parameters of a declaration.
+    pub call_parens: BTreeSet<usize>,
+    /// Tokens which need a space before them, which the default rules would omit.
+    pub space_before: BTreeSet<usize>,
+    /// Tokens which continue the line after a block, as `else` does.
+    pub glued: BTreeSet<usize>,
+    /// Tokens whose spacing is kept as in the source.
+    pub verbatim: BTreeSet<usize>,
+}
+
+pub(crate) fn collect(defs: &[Definition], tokens: &[Token]) -> Hints {
+    let mut collector = Collector {
+        offsets: tokens
+            .iter()
+            .filter(|token| !token.is_comment())
+            .map(|token| (token.start, token.tok().unwrap()))
+            .collect(),
+        hints: Hints::default(),
+    };
+    for def in defs {
+        collector.definition(def);
+    }
+    collector.hints
+}
+
+struct Collector {
+    /// Offsets and kinds of all tokens, in source order.
+    offsets: Vec<(usize, Tok)>,
+    hints: Hints,
+}
+
+impl Collector {
+    /// Returns the index of the first token at or after `offset`.
+    fn index_at(&self, offset: u32) -> usize {
+        self.offsets
+            .partition_point(|(start, _)| *start < offset as usize)
+    }
+
+    /// Returns the offset of the first token at or after `offset`, skipping `(`.
+    fn skip_parens(&self, offset: u32) -> Option<(usize, Tok)> {
+        self.offsets[self.index_at(offset)..]
+            .iter()
+            .find(|(_, tok)| *tok != Tok::LParen)
+            .copied()
+    }
+
+    /// Returns the offset of the first `tok` at or after `offset`.
+    fn find(&self, offset: u32, tok: Tok) -> Option<usize> {
+        self.offsets[self.index_at(offset)..]
+            .iter()
+            .find(|(_, t)| *t == tok)
+            .map(|(start, _)| *start)
+    }
+
+    /// Returns the token at or after `offset` if it is `tok`.
+    fn expect(&self, offset: u32, tok: Tok) -> Option<usize> {
+        match self.offsets.get(self.index_at(offset)) {
+            Some((start, t)) if *t == tok => Some(*start),
+            _ => None,
+        }
+    }
+
+    fn block_after(&mut self, offset: u32, kind: BlockKind) {
+        if let Some(start) = self.find(offset, Tok::LBrace) {
+            self.hints.blocks.insert(start, kind);
+        }
+    }
+
+    fn type_args_after(&mut self, name: Loc) {
+        let idx = self.index_at(name.end());
+        let start = match self.offsets.get(idx..idx + 2) {
+            Some([(start, Tok::Less), _]) => *start,
+            Some([(_, Tok::ColonColon), (start, Tok::Less)]) => *start,
+            _ => return,
+        };
+        self.hints.type_args.insert(start);
+    }
+
+    /// Marks the `(` after `name` and its optional type arguments as a call or declaration.
+    fn call_paren_after(&mut self, name: Loc) {
+        let mut depth = 0;
+        for (start, tok) in &self.offsets[self.index_at(name.end())..] {
+            match tok {
+                Tok::Less => depth += 1,
+                Tok::Greater => depth -= 1,
+                Tok::GreaterGreater => depth -= 2,
+                Tok::ColonColon if depth == 0 => (),
+                Tok::LParen if depth <= 0 => {
+                    self.hints.call_parens.insert(*start);
+                    return;
+                },
+                _ if depth > 0 => (),
+                _ => return,
+            }
+        }
+    }
+
+    fn unary_at(&mut self, loc: Loc) {
+        if let Some((start, Tok::Exclaim | Tok::Amp | Tok::AmpMut | Tok::Star)) =
+            self.skip_parens(loc.start())
+        {
+            self.hints.unary_ops.insert(start);
+        }
+    }
+
+    fn verbatim(&mut self, loc: Loc) {
+        let from = self.index_at(loc.start());
+        let to = self.index_at(loc.end());
+        for (start, _) in &self.offsets[from..to] {
+            self.hints.verbatim.insert(*start);
+        }
+    }
+
+    fn definition(&mut self, def: &Definition) {
+        match def {
+            Definition::Module(module) => self.module(module),
+            Definition::Address(address) => {
+                self.attributes(&address.attributes);
+                self.block_after(address.addr.loc.end(), BlockKind::Seq);
+                for module in &address.modules {
+                    self.module(module);
+                }
+            },
+            Definition::Script(script) => {
+                self.attributes(&script.attributes);
+                self.block_after(script.loc.start(), BlockKind::Seq);
+                for use_decl in &script.uses {
+                    self.attributes(&use_decl.attributes);
+                }
+                for constant in &script.constants {
+                    self.constant(constant);
+                }
+                self.function(&script.function);
+                for spec in &script.specs {
+                    self.spec_block(spec);
+                }
+            },
+        }
+    }
+
+    fn module(&mut self, module: &ModuleDefinition) {
+        self.attributes(&module.attributes);
+        self.block_after(module.name.0.loc.end(), BlockKind::Seq);
+        for member in &module.members {
+            match member {
+                ModuleMember::Function(fun) => self.function(fun),
+                ModuleMember::Struct(struct_def) => self.struct_def(struct_def),
+                ModuleMember::Use(use_decl) => self.attributes(&use_decl.attributes),
+                ModuleMember::Friend(friend) => self.attributes(&friend.attributes),
+                ModuleMember::Constant(constant) => self.constant(constant),
+                ModuleMember::Spec(spec) => self.spec_block(spec),
+            }
+        }
+    }
+
+    fn attributes(&mut self, attributes: &[Attributes]) {
+        for attrs in attributes {
+            for attr in &attrs.value {
+                if let Attribute_::Parameterized(name, inner) = &attr.value {
+                    self.call_paren_after(name.loc);
+                    self.attributes(std::slice::from_ref(inner));
+                }
+            }
+        }
+    }
+
+    fn constant(&mut self, constant: &Constant) {
+        self.attributes(&constant.attributes);
+        self.type_(&constant.signature);
+        self.exp(&constant.value);
+    }
+
+    fn function(&mut self, fun: &Function) {
+        self.attributes(&fun.attributes);
+        self.signature(fun.name.0.loc, &fun.signature);
+        for specifier in fun.access_specifiers.iter().flatten() {
+            self.verbatim(specifier.loc);
+        }
+        self.function_body(&fun.body);
+    }
+
+    fn signature(&mut self, name: Loc, signature: &FunctionSignature) {
+        if !signature.type_parameters.is_empty() {
+            self.type_args_after(name);
+        }
+        self.call_paren_after(name);
+        for (_, ty) in &signature.parameters {
+            self.type_(ty);
+        }
+        self.type_(&signature.return_type);
+    }
+
+    fn function_body(&mut self, body: &FunctionBody) {
+        if let FunctionBody_::Defined(seq) = &body.value {
+            self.block_after(body.loc.start(), BlockKind::Seq);
+            self.sequence(seq);
+        }
+    }
+
+    fn struct_def(&mut self, struct_def: &StructDefinition) {
+        self.attributes(&struct_def.attributes);
+        let name = struct_def.name.0.loc;
+        if !struct_def.type_parameters.is_empty() {
+            self.type_args_after(name);
+        }
+        match &struct_def.layout {
+            StructLayout::Singleton(fields, is_positional) => {
+                if *is_positional {
+                    self.call_paren_after(name);
+                } else {
+                    self.block_after(name.end(), BlockKind::Fields);
+                }
+                self.fields(fields);
+            },
+            StructLayout::Variants(variants) => {
+                self.block_after(name.end(), BlockKind::Fields);
+                for variant in variants {
+                    self.attributes(&variant.attributes);
+                    if variant.is_positional {
+                        self.call_paren_after(variant.name.0.loc);
+                    }
+                    self.fields(&variant.fields);
+                }
+            },
+            StructLayout::Native(_) => (),
+        }
+    }
+
+    fn fields(&mut self, fields: &[(Field, Type)]) {
+        for (_, ty) in fields {
+            self.type_(ty);
+        }
+    }
+
+    fn spec_block(&mut self, spec: &SpecBlock) {
+        self.attributes(&spec.value.attributes);
+        self.block_after(spec.loc.start(), BlockKind::Seq);
+        match &spec.value.target.value {
+            SpecBlockTarget_::Member(name, Some(signature)) => self.signature(name.loc, signature),
+            SpecBlockTarget_::Schema(name, type_parameters) if !type_parameters.is_empty() => {
+                self.type_args_after(name.loc)
+            },
+            _ => (),
+        }
+        for use_decl in &spec.value.uses {
+            self.attributes(&use_decl.attributes);
+        }
+        for member in &spec.value.members {
+            self.spec_member(member);
+        }
+    }
+
+    fn spec_member(&mut self, member: &SpecBlockMember) {
+        match &member.value {
+            SpecBlockMember_::Condition {
+                kind,
+                properties,
+                exp,
+                additional_exps,
+            } => {
+                if let SpecConditionKind_::Invariant(params)
+                | SpecConditionKind_::InvariantUpdate(params)
+                | SpecConditionKind_::Axiom(params) = &kind.value
+                {
+                    if !params.is_empty() {
+                        if let Some(start) = self.find(kind.loc.start(), Tok::Less) {
+                            self.hints.type_args.insert(start);
+                        }
+                    }
+                }
+                self.properties(kind.loc, properties);
+                self.exp(exp);
+                for exp in additional_exps {
+                    self.exp(exp);
+                }
+            },
+            SpecBlockMember_::Function {
+                name,
+                signature,
+                body,
+                ..
+            } => {
+                self.signature(name.0.loc, signature);
+                self.function_body(body);
+            },
+            SpecBlockMember_::Variable {
+                name,
+                type_parameters,
+                type_,
+                init,
+                ..
+            } => {
+                if !type_parameters.is_empty() {
+                    self.type_args_after(name.loc);
+                }
+                self.type_(type_);
+                if let Some(init) = init {
+                    self.exp(init);
+                }
+            },
+            SpecBlockMember_::Let { def, .. } => self.exp(def),
+            SpecBlockMember_::Update { lhs, rhs } => {
+                self.exp(lhs);
+                self.exp(rhs);
+            },
+            SpecBlockMember_::Include { properties, exp } => {
+                self.properties(member.loc, properties);
+                self.exp(exp);
+            },
+            SpecBlockMember_::Apply {
+                exp,
+                patterns,
+                exclusion_patterns,
+            } => {
+                self.exp(exp);
+                for pattern in patterns.iter().chain(exclusion_patterns) {
+                    self.verbatim(pattern.loc);
+                }
+            },
+            SpecBlockMember_::Pragma { .. } => (),
+        }
+    }
+
+    /// Keeps a space between a condition keyword and its properties, as in `ensures [abstract]`.
+    fn properties(&mut self, keyword: Loc, properties: &[PragmaProperty]) {
+        if !properties.is_empty() {
+            if let Some(start) = self.find(keyword.start(), Tok::LBracket) {
+                self.hints.space_before.insert(start);
+            }
+        }
+    }
+
+    fn type_(&mut self, ty: &Type) {
+        match &ty.value {
+            Type_::Apply(name, args) => {
+                if !args.is_empty() {
+                    self.type_args_after(name.loc);
+                }
+                self.types(args);
+            },
+            Type_::Ref(_, inner) => {
+                self.unary_at(ty.loc);
+                self.type_(inner);
+            },
+            Type_::Fun(args, result, _) => {
+                if let Some(start) = self.expect(ty.loc.start(), Tok::Pipe) {
+                    self.hints.pipes.insert(start);
+                }
+                self.types(args);
+                self.type_(result);
+            },
+            Type_::Multiple(tys) => self.types(tys),
+            Type_::Unit => (),
+        }
+    }
+
+    fn types(&mut self, tys: &[Type]) {
+        for ty in tys {
+            self.type_(ty);
+        }
+    }
+
+    fn opt_types(&mut self, name: Loc, tys: &Option<Vec<Type>>) {
+        if let Some(tys) = tys {
+            self.type_args_after(name);
+            self.types(tys);
+        }
+    }
+
+    fn sequence(&mut self, seq: &Sequence) {
+        let (uses, items, _, result) = seq;
+        for use_decl in uses {
+            self.attributes(&use_decl.attributes);
+        }
+        for item in items {
+            match &item.value {
+                SequenceItem_::Seq(exp) => self.exp(exp),
+                SequenceItem_::Declare(binds, ty) => {
+                    self.binds(&binds.value);
+                    if let Some(ty) = ty {
+                        self.type_(ty);
+                    }
+                },
+                SequenceItem_::Bind(binds, ty, exp) => {
+                    self.binds(&binds.value);
+                    if let Some(ty) = ty {
+                        self.type_(ty);
+                    }
+                    self.exp(exp);
+                },
+            }
+        }
+        if let Some(exp) = result.as_ref() {
+            self.exp(exp);
+        }
+    }
+
+    fn binds(&mut self, binds: &[Bind]) {
+        for bind in binds {
+            self.bind(bind);
+        }
+    }
+
+    fn bind(&mut self, bind: &Bind) {
+        match &bind.value {
+            Bind_::Var(_) => (),
+            Bind_::Unpack(name, tys, fields) => {
+                self.opt_types(name.loc, tys);
+                for field in fields {
+                    if let BindFieldOrDotDot_::FieldBind(_, bind) = &field.value {
+                        self.bind(bind);
+                    }
+                }
+            },
+            Bind_::PositionalUnpack(name, tys, binds) => {
+                self.opt_types(name.loc, tys);
+                self.call_paren_after(name.loc);
+                for bind in binds {
+                    if let BindOrDotDot_::Bind(bind) = &bind.value {
+                        self.bind(bind);
+                    }
+                }
+            },
+        }
+    }
+
+    fn exps(&mut self, exps: &[Exp]) {
+        for exp in exps {
+            self.exp(exp);
+        }
+    }
+
+    fn exp(&mut self, exp: &Exp) {
+        match &exp.value {
+            Exp_::Value(_)
+            | Exp_::Move(_)
+            | Exp_::Copy(_)
+            | Exp_::Unit
+            | Exp_::Break(_)
+            | Exp_::Continue(_)
+            | Exp_::UnresolvedError => (),
+            Exp_::Name(name, tys) => self.opt_types(name.loc, tys),
+            Exp_::Call(name, kind, tys, args) => {
+                if let NameAccessChain_::One(name) = &name.value {
+                    if name.value.as_str().starts_with('$') {
+                        // Built-in calls made up by the parser, as for quantifier domains
+                        if let Some(tys) = tys {
+                            self.types(tys);
+                        }
+                        return self.exps(&args.value);
+                    }
+                }
+                self.opt_types(name.loc, tys);
+                match kind {
+                    CallKind::Regular | CallKind::Macro => {
+                        if let Some(start) = self.expect(args.loc.start(), Tok::LParen) {
+                            self.hints.call_parens.insert(start);
+                        }
+                    },
+                    // The location of the arguments includes the receiver
+                    CallKind::Receiver => self.call_paren_after(name.loc),
+                }
+                self.exps(&args.value);
+            },
+            Exp_::ExpCall(fun, args) => {
+                self.exp(fun);
+                if let Some(start) = self.expect(args.loc.start(), Tok::LParen) {
+                    self.hints.call_parens.insert(start);
+                }
+                self.exps(&args.value);
+            },
+            Exp_::Pack(name, tys, fields) => {
+                self.opt_types(name.loc, tys);
+                for (_, exp) in fields {
+                    self.exp(exp);
+                }
+            },
+            Exp_::Vector(name, tys, args) => {
+                self.opt_types(*name, tys);
+                self.exps(&args.value);
+            },
+            Exp_::IfElse(cond, then, otherwise) => {
+                self.exp(cond);
+                self.exp(then);
+                if let Some(otherwise) = otherwise {
+                    self.exp(otherwise);
+                }
+            },
+            Exp_::While(_, cond, body) => {
+                // A loop invariant `while (c) body spec { .. }` is parsed into the condition
+                if let Exp_::Block((_, items, _, _)) = &cond.value {
+                    if let [Spanned {
+                        value: SequenceItem_::Seq(spec),
+                        ..
+                    }] = items.as_slice()
+                    {
+                        if spec.loc.start() > body.loc.start() {
+                            if let Some(start) = self.expect(spec.loc.start(), Tok::Spec) {
+                                self.hints.glued.insert(start);
+                            }
+                        }
+                    }
+                }
+                self.exp(cond);
+                self.exp(body);
+            },
+            Exp_::Loop(_, body) => self.exp(body),
+            Exp_::Match(scrutinee, arms) => {
+                self.exp(scrutinee);
+                self.block_after(scrutinee.loc.end(), BlockKind::Arms);
+                for arm in arms {
+                    let (binds, cond, body) = &arm.value;
+                    self.binds(&binds.value);
+                    if let Some(cond) = cond {
+                        self.exp(cond);
+                    }
+                    self.exp(body);
+                }
+            },
+            Exp_::Block(seq) => {
+                if let Some((start, Tok::LBrace)) = self.skip_parens(exp.loc.start()) {
+                    self.hints.blocks.insert(start, BlockKind::Seq);
+                }
+                self.sequence(seq);
+            },
+            Exp_::Lambda(binds, body, _, spec) => {
+                if let Some(start) = self.expect(binds.loc.start(), Tok::Pipe) {
+                    self.hints.pipes.insert(start);
+                }
+                for bind in &binds.value {
+                    self.bind(&bind.value.0);
+                    if let Some(ty) = &bind.value.1 {
+                        self.type_(ty);
+                    }
+                }
+                self.exp(body);
+                if let Some(spec) = spec {
+                    self.exp(spec);
+                }
+            },
+            Exp_::Quant(_, ranges, triggers, cond, body) => {
+                for range in &ranges.value {
+                    self.bind(&range.value.0);
+                    self.exp(&range.value.1);
+                }
+                for trigger in triggers {
+                    self.exps(trigger);
+                }
+                if let Some(cond) = cond {
+                    self.exp(cond);
+                }
+                self.exp(body);
+            },
+            Exp_::ExpList(exps) => self.exps(exps),
+            Exp_::Assign(lhs, _, rhs) | Exp_::BinopExp(lhs, _, rhs) => {
+                self.exp(lhs);
+                self.exp(rhs);
+            },
+            Exp_::Return(exp) => {
+                if let Some(exp) = exp {
+                    self.exp(exp);
+                }
+            },
+            Exp_::Abort(exp) | Exp_::Dot(exp, _) => self.exp(exp),
+            Exp_::Dereference(inner) | Exp_::Borrow(_, inner) => {
+                self.unary_at(exp.loc);
+                self.exp(inner);
+            },
+            Exp_::UnaryExp(op, inner) => {
+                self.hints.unary_ops.insert(op.loc.start() as usize);
+                self.exp(inner);
+            },
+            Exp_::Index(exp, index) => {
+                self.exp(exp);
+                self.exp(index);
+            },
+            Exp_::Cast(exp, ty) | Exp_::Annotate(exp, ty) => {
+                self.exp(exp);
+                self.type_(ty);
+            },
+            Exp_::Test(exp, tys) => {
+                self.exp(exp);
+                self.types(tys);
+            },
+            Exp_::Spec(spec) => self.spec_block(spec),
+        }
+    }
+}
@@ -0,0 +1,113 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! A formatter for Move source code.
+//!
+//! The formatter prints the tokens of the source, including comments, with new whitespace
+//! in between. Where the layout depends on the meaning of a token, e.g. whether a `{` opens
+//! a block, this is taken from the AST of the parser. Since only whitespace changes, the
+//! formatted code has the same meaning as the original, and formatting is idempotent.
+
+mod config;
+mod hints;
+mod printer;
+mod tokens;
+mod tree;
+
+use anyhow::{anyhow, bail, Result};
+pub use config::{FormatConfig, CONFIG_FILE_NAME};
+use legacy_move_compiler::{
+    diagnostics::{report_diagnostics_to_buffer, FilesSourceText},
+    parser::{ast::Definition, lexer::Tok, syntax::parse_file_string},
+    shared::{CompilationEnv, LanguageVersion},
+    Flags,
+};
+use move_command_line_common::files::FileHash;
+use move_symbol_pool::Symbol;
+use std::collections::BTreeSet;
+use tokens::TokenKind;
+
+/// Formats the Move source code in `source`, read from `file_name`. Fails if the source does
+/// not parse.
+pub fn format_source(file_name: &str, source: &str, config: &FormatConfig) -> Result<String> {
+    let defs = parse(file_name, source)?;
+    let mut tokens = tokens::tokenize(source)?;
+    let hints = hints::collect(&defs, &tokens);
+    let (nodes, roles) = tree::build(&mut tokens, &hints)?;
+    let formatted = printer::Printer::new(config, &tokens, &roles, &hints).print_file(&nodes);
+    check_same_tokens(file_name, source, &formatted)?;
+    Ok(formatted)
+}
+
+fn parse(file_name: &str, source: &str) -> Result<Vec<Definition>> {
+    let flags = Flags::empty()
+        .set_skip_attribute_checks(true)
+        .set_language_version(LanguageVersion::V2_3);
+    let mut env = CompilationEnv::new(flags, BTreeSet::new());
+    let file_hash = FileHash::new(source);
+    parse_file_string(&mut env, file_hash, source)
+        .map(|(defs, _)| defs)
+        .map_err(|diags| {
+            let files: FilesSourceText =
+                [(file_hash, (Symbol::from(file_name), source.to_string()))]
+                    .into_iter()
+                    .collect();
+            let report = report_diagnostics_to_buffer(&files, diags);
+            anyhow!(
+                "failed to parse `{}`:\n{}",
+                file_name,
+                String::from_utf8_lossy(&report)
+            )
+        })
+}
+
+/// Checks that the formatted code consists of the same tokens and comments as the original,
+/// as a guard against bugs in the formatter. Trailing commas, which the formatter may add,
+/// are ignored.
+fn check_same_tokens(file_name: &str, source: &str, formatted: &str) -> Result<()> {
+    let texts = |source: &str| -> Result<Vec<String>> {
+        let tokens = tokens::tokenize(source)?;
+        let mut texts = vec![];
+        for (idx, token) in tokens.iter().enumerate() {
+            match token.kind {
+                TokenKind::Tok(Tok::Comma) => {
+                    let next = tokens[idx + 1..].iter().find_map(|token| token.tok());
+                    if !matches!(next, Some(Tok::RParen | Tok::RBracket | Tok::RBrace)) {
+                        texts.push(token.text.clone())
+                    }
+                },
+                // `>>` may close two type argument lists, which are printed as `> >` if
+                // broken across lines
+                TokenKind::Tok(Tok::GreaterGreater) => {
+                    texts.extend([">".to_string(), ">".to_string()])
+                },
+                _ => texts.push(token.text.clone()),
+            }
+        }
+        Ok(texts)
+    };
+    let (before, after) = (texts(source)?, texts(formatted)?);
+    if let Some((pos, (before, after))) = before
+        .iter()
+        .zip(&after)
+        .enumerate()
+        .find(|(_, (before, after))| before != after)
+    {
+        bail!(
+            "formatting `{}` changed token {} from `{}` to `{}`",
+            file_name,
+            pos,
+            before,
+            after
+        )
+    }
+    if before.len() != after.len() {
+        bail!(
+            "formatting `{}` changed the number of tokens from {} to {}",
+            file_name,
+            before.len(),
+            after.len()
+        )
+    }
+    Ok(())
+}
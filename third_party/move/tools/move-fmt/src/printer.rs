@@ -0,0 +1,883 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Prints the token tree. Lists are kept on one line if they fit, and otherwise broken with
+//! one element per line; blocks are always broken.
+
+use crate::{
+    config::FormatConfig,
+    hints::{BlockKind, Hints},
+    tokens::{Token, TokenKind},
+    tree::{Group, GroupKind, Node, Role},
+};
+use legacy_move_compiler::parser::lexer::{Lexer, Tok};
+use move_command_line_common::files::FileHash;
+use std::collections::BTreeMap;
+
+pub(crate) struct Printer<'a> {
+    config: &'a FormatConfig,
+    tokens: &'a [Token],
+    roles: &'a [Role],
+    hints: &'a Hints,
+    /// The width of each group if printed on one line, by its opening token.
+    flat_widths: BTreeMap<usize, Option<usize>>,
+    out: String,
+    /// The column the next character is printed at.
+    col: usize,
+    /// The indentation of the current line.
+    line_indent: usize,
+    /// The last token or comment printed.
+    last: Option<usize>,
+    /// Whether nothing has been printed on the current line yet.
+    line_start: bool,
+    /// Set after a line comment, which must be followed by a line break.
+    pending_newline: bool,
+    /// Set while printing a group on one line, which forces nested groups on one line, too.
+    flat: usize,
+}
+
+/// An element of a list, separated from the next by a comma.
+struct Element<'n> {
+    nodes: Vec<&'n Node>,
+    comma: Option<usize>,
+    /// Comments following the comma on the same line.
+    comments: Vec<usize>,
+}
+
+impl Element<'_> {
+    fn is_comment_only(&self, tokens: &[Token]) -> bool {
+        self.nodes.iter().all(|node| match node {
+            Node::Token(idx) => tokens[*idx].is_comment(),
+            Node::Group(_) => false,
+        })
+    }
+}
+
+impl<'a> Printer<'a> {
+    pub fn new(
+        config: &'a FormatConfig,
+        tokens: &'a [Token],
+        roles: &'a [Role],
+        hints: &'a Hints,
+    ) -> Self {
+        Self {
+            config,
+            tokens,
+            roles,
+            hints,
+            flat_widths: BTreeMap::new(),
+            out: String::new(),
+            col: 0,
+            line_indent: 0,
+            last: None,
+            line_start: true,
+            pending_newline: false,
+            flat: 0,
+        }
+    }
+
+    /// Prints the top-level nodes of a file.
+    pub fn print_file(mut self, nodes: &[Node]) -> String {
+        for node in nodes {
+            self.compute_flat_widths(node);
+        }
+        self.items(nodes, BlockKind::Seq, 0);
+        self.trim_trailing_spaces();
+        if !self.out.is_empty() {
+            self.out.push('\n');
+        }
+        self.out
+    }
+
+    // ----------------------------------------------------------------------------------
+    // Output
+
+    fn emit(&mut self, idx: usize) {
+        let text = &self.tokens[idx].text;
+        self.out.push_str(text);
+        match text.rfind('\n') {
+            Some(pos) => self.col = text[pos + 1..].chars().count(),
+            None => self.col += text.chars().count(),
+        }
+        self.last = Some(idx);
+        self.line_start = false;
+        if self.tokens[idx].kind == TokenKind::LineComment {
+            self.pending_newline = true;
+        }
+    }
+
+    fn emit_str(&mut self, s: &str) {
+        self.out.push_str(s);
+        self.col += s.chars().count();
+        self.line_start = false;
+    }
+
+    fn space(&mut self) {
+        self.emit_str(" ");
+    }
+
+    fn newline(&mut self, indent: usize) {
+        self.trim_trailing_spaces();
+        self.out.push('\n');
+        self.out.push_str(&" ".repeat(indent));
+        self.col = indent;
+        self.line_indent = indent;
+        self.line_start = true;
+        self.pending_newline = false;
+    }
+
+    fn blank_line(&mut self) {
+        self.trim_trailing_spaces();
+        self.out.push('\n');
+    }
+
+    fn trim_trailing_spaces(&mut self) {
+        let len = self.out.trim_end_matches(' ').len();
+        self.out.truncate(len);
+    }
+
+    fn fits(&self, width: usize) -> bool {
+        self.col + width <= self.config.max_width
+    }
+
+    // ----------------------------------------------------------------------------------
+    // Spacing
+
+    fn tok(&self, idx: usize) -> Option<Tok> {
+        self.tokens[idx].tok()
+    }
+
+    fn is_unary(&self, idx: usize) -> bool {
+        self.hints.unary_ops.contains(&self.tokens[idx].start)
+    }
+
+    fn is_verbatim(&self, idx: usize) -> bool {
+        self.hints.verbatim.contains(&self.tokens[idx].start)
+    }
+
+    /// Whether the token ends an operand, such that `[` following it indexes into it.
+    fn ends_operand(&self, idx: usize) -> bool {
+        match self.roles[idx] {
+            Role::Close(kind) => kind != GroupKind::Pipe,
+            Role::Open(_) => false,
+            Role::Plain => matches!(
+                self.tok(idx),
+                Some(
+                    Tok::Identifier
+                        | Tok::NumValue
+                        | Tok::NumTypedValue
+                        | Tok::ByteStringValue
+                        | Tok::True
+                        | Tok::False
+                )
+            ),
+        }
+    }
+
+    /// Whether there is a space between the tokens `a` and `b`, if printed on the same line.
+    fn space_between(&self, a: usize, b: usize) -> bool {
+        let space = self.wants_space(a, b);
+        // Never glue tokens which would lex as a different token
+        space || self.would_merge(a, b)
+    }
+
+    fn wants_space(&self, a: usize, b: usize) -> bool {
+        let (ta, tb) = (&self.tokens[a], &self.tokens[b]);
+        let (ka, kb) = match (ta.tok(), tb.tok()) {
+            (Some(ka), Some(kb)) => (ka, kb),
+            _ => return true,
+        };
+        if self.is_verbatim(a) && self.is_verbatim(b) {
+            return ta.end < tb.start;
+        }
+        if self.hints.space_before.contains(&tb.start) {
+            return true;
+        }
+        if matches!(
+            kb,
+            Tok::Comma | Tok::Semicolon | Tok::Period | Tok::ColonColon | Tok::Colon
+        ) {
+            return false;
+        }
+        if let Role::Close(_) = self.roles[b] {
+            return false;
+        }
+        if let Role::Open(_) = self.roles[a] {
+            return false;
+        }
+        if matches!(
+            ka,
+            Tok::Period | Tok::ColonColon | Tok::AtSign | Tok::NumSign
+        ) {
+            return false;
+        }
+        if self.is_unary(a) {
+            return ka == Tok::AmpMut;
+        }
+        match ka {
+            // The `!` of a macro call
+            Tok::Exclaim => return false,
+            Tok::AmpMut => return true,
+            Tok::PeriodPeriod => return false,
+            _ => (),
+        }
+        match kb {
+            Tok::Exclaim if !self.is_unary(b) => return false,
+            Tok::PeriodPeriod => return !self.ends_operand(a),
+            _ => (),
+        }
+        match self.roles[b] {
+            Role::Open(GroupKind::Paren) => {
+                !(self.hints.call_parens.contains(&tb.start) || ka == Tok::Public)
+            },
+            Role::Open(GroupKind::Bracket) => !self.ends_operand(a),
+            Role::Open(GroupKind::Angle) => false,
+            _ => true,
+        }
+    }
+
+    /// Whether the texts of `a` and `b` would lex as different tokens if printed without
+    /// space in between.
+    fn would_merge(&self, a: usize, b: usize) -> bool {
+        let (ta, tb) = (&self.tokens[a], &self.tokens[b]);
+        if ta.is_comment() || tb.is_comment() {
+            return true;
+        }
+        if ta.is(Tok::Greater) && tb.is(Tok::Greater) {
+            // Closing nested type arguments, which the parser splits again
+            return false;
+        }
+        let text = format!("{}{}", ta.text, tb.text);
+        let mut lexer = Lexer::new(&text, FileHash::new(&text));
+        match lexer.advance() {
+            Ok(()) => lexer.peek() != ta.tok().unwrap() || lexer.content() != ta.text,
+            Err(_) => true,
+        }
+    }
+
+    /// Separates the node about to be printed from the previous one on the same line,
+    /// or starts a new line at `indent` if needed.
+    fn separate(&mut self, next: usize, indent: usize) {
+        let token = &self.tokens[next];
+        if self.pending_newline || (token.is_comment() && token.newlines_before > 0) {
+            if !self.line_start {
+                self.newline(indent);
+            }
+            return;
+        }
+        match self.last {
+            Some(last) if !self.line_start => {
+                if self.space_between(last, next) {
+                    self.space()
+                }
+            },
+            _ => (),
+        }
+    }
+
+    // ----------------------------------------------------------------------------------
+    // Widths
+
+    fn compute_flat_widths(&mut self, node: &Node) {
+        if let Node::Group(group) = node {
+            for child in &group.children {
+                self.compute_flat_widths(child);
+            }
+            let width = self.group_width(group);
+            self.flat_widths.insert(group.open, width);
+        }
+    }
+
+    fn flat_width(&self, group: &Group) -> Option<usize> {
+        self.flat_widths.get(&group.open).copied().flatten()
+    }
+
+    fn group_width(&self, group: &Group) -> Option<usize> {
+        let delimiters = self.tokens[group.open].width()? + self.tokens[group.close].width()?;
+        if group.children.is_empty() {
+            return Some(delimiters);
+        }
+        if let GroupKind::Block(_) = group.kind {
+            return None;
+        }
+        let elements = self.elements(group);
+        let mut width = delimiters;
+        if self.padded(group) {
+            width += 2;
+        }
+        for (i, element) in elements.iter().enumerate() {
+            width += self.nodes_width(&element.nodes)?;
+            for comment in &element.comments {
+                width += 1 + self.comment_width(*comment)?;
+            }
+            if i + 1 < elements.len() {
+                width += 2;
+            }
+        }
+        Some(width)
+    }
+
+    fn comment_width(&self, idx: usize) -> Option<usize> {
+        let token = &self.tokens[idx];
+        match token.kind {
+            TokenKind::BlockComment if token.newlines_before == 0 => token.width(),
+            _ => None,
+        }
+    }
+
+    /// The width of `nodes` printed on one line, if possible.
+    fn nodes_width(&self, nodes: &[&Node]) -> Option<usize> {
+        let mut width = 0;
+        for (i, node) in nodes.iter().enumerate() {
+            if i > 0 && self.space_between(nodes[i - 1].last_token(), node.first_token()) {
+                width += 1;
+            }
+            width += match node {
+                Node::Token(idx) if self.tokens[*idx].is_comment() => self.comment_width(*idx)?,
+                Node::Token(idx) => self.tokens[*idx].width()?,
+                Node::Group(group) => self.flat_width(group)?,
+            };
+        }
+        Some(width)
+    }
+
+    /// The width of `nodes` up to the first place where the line can be broken. Types are
+    /// not broken in preference to the lists before them, as the parameters of a function
+    /// rather than its type parameters or result type.
+    fn suffix_width(&self, prev: Option<usize>, nodes: &[&Node]) -> usize {
+        let mut width = 0;
+        let mut prev = prev;
+        for node in nodes {
+            let first = node.first_token();
+            if self.tokens[first].is_comment() {
+                break;
+            }
+            if let Some(prev) = prev {
+                if self.space_between(prev, first) {
+                    width += 1;
+                }
+            }
+            match node {
+                Node::Token(idx) => width += self.tokens[*idx].width().unwrap_or(0),
+                Node::Group(group) => match self.flat_width(group) {
+                    Some(group_width) if group.children.is_empty() || self.is_type(group) => {
+                        width += group_width
+                    },
+                    _ => {
+                        width += self.tokens[group.open].text.len();
+                        break;
+                    },
+                },
+            }
+            prev = Some(node.last_token());
+        }
+        width
+    }
+
+    // ----------------------------------------------------------------------------------
+    // Sequences
+
+    /// Prints nodes which belong on one line, apart from breaks within groups. Continuation
+    /// lines, after line comments, are indented by `indent`. `trailing` is the width of what
+    /// follows the nodes on the same line.
+    fn seq(&mut self, nodes: &[&Node], indent: usize, trailing: usize) {
+        for (i, node) in nodes.iter().enumerate() {
+            let first = node.first_token();
+            if i > 0 || self.pending_newline {
+                self.separate(first, indent);
+            }
+            match node {
+                Node::Token(idx) => self.emit(*idx),
+                Node::Group(group) => {
+                    let rest = self.suffix_width(Some(group.close), &nodes[i + 1..]) + trailing;
+                    self.group(group, rest);
+                },
+            }
+        }
+    }
+
+    /// Prints an item of a block or an element of a list, which starts on a new line at
+    /// `indent`. If it is too long and contains logical operators at the top level, it is
+    /// broken before them.
+    fn line(&mut self, nodes: &[&Node], indent: usize, trailing: usize) {
+        let breaks: Vec<usize> = nodes
+            .iter()
+            .enumerate()
+            .filter(|(i, node)| {
+                *i > 0
+                    && node.as_token().is_some_and(|idx| {
+                        matches!(
+                            self.tok(idx),
+                            Some(
+                                Tok::AmpAmp
+                                    | Tok::PipePipe
+                                    | Tok::EqualEqualGreater
+                                    | Tok::LessEqualEqualGreater
+                            )
+                        )
+                    })
+            })
+            .map(|(i, _)| i)
+            .collect();
+        let too_long = self.flat == 0
+            && self
+                .nodes_width(nodes)
+                .is_some_and(|width| !self.fits(width + trailing));
+        if breaks.is_empty() || !too_long {
+            return self.seq(nodes, indent + self.config.indent_size, trailing);
+        }
+        let continuation = indent + self.config.indent_size;
+        let mut start = 0;
+        for at in breaks.into_iter().chain(std::iter::once(nodes.len())) {
+            if start > 0 {
+                self.newline(continuation);
+            }
+            let rest = if at == nodes.len() { trailing } else { 0 };
+            self.seq(
+                &nodes[start..at],
+                continuation + self.config.indent_size,
+                rest,
+            );
+            start = at;
+        }
+    }
+
+    // ----------------------------------------------------------------------------------
+    // Groups
+
+    fn group(&mut self, group: &Group, trailing: usize) {
+        match group.kind {
+            GroupKind::Block(kind) => self.block(group, kind),
+            _ => self.list(group, trailing),
+        }
+    }
+
+    /// Whether the group is a type, as type arguments or a tuple result type.
+    fn is_type(&self, group: &Group) -> bool {
+        match group.kind {
+            GroupKind::Angle => true,
+            GroupKind::Paren => matches!(self.last_before(group.open), Some(Tok::Colon)),
+            _ => false,
+        }
+    }
+
+    /// Whether the contents of braces are separated from them by spaces.
+    fn padded(&self, group: &Group) -> bool {
+        group.kind == GroupKind::Brace
+            && !group.children.is_empty()
+            && !matches!(self.last_before(group.open), Some(Tok::ColonColon))
+    }
+
+    /// The token before the token at `idx` in the source.
+    fn last_before(&self, idx: usize) -> Option<Tok> {
+        self.tokens[..idx]
+            .iter()
+            .rev()
+            .find_map(|token| token.tok())
+    }
+
+    fn block(&mut self, group: &Group, kind: BlockKind) {
+        let indent = self.line_indent;
+        self.emit(group.open);
+        if group.children.is_empty() {
+            self.emit(group.close);
+            return;
+        }
+        let children: Vec<&Node> = group.children.iter().collect();
+        let children = self.leading_comments(&children);
+        self.items_of(&children, kind, indent + self.config.indent_size);
+        self.newline(indent);
+        self.emit(group.close);
+    }
+
+    /// Prints comments at the start of `nodes` which are on the same line as the previous
+    /// token, returning the remaining nodes.
+    fn leading_comments<'n>(&mut self, nodes: &[&'n Node]) -> Vec<&'n Node> {
+        let mut rest = nodes;
+        while let Some((Node::Token(idx), tail)) = rest.split_first() {
+            let token = &self.tokens[*idx];
+            if !token.is_comment() || token.newlines_before > 0 {
+                break;
+            }
+            self.space();
+            self.emit(*idx);
+            rest = tail;
+        }
+        rest.to_vec()
+    }
+
+    fn items(&mut self, nodes: &[Node], kind: BlockKind, indent: usize) {
+        let nodes: Vec<&Node> = nodes.iter().collect();
+        self.items_of(&nodes, kind, indent)
+    }
+
+    /// Prints the items of a block, or of the file, each on a new line at `indent`.
+    fn items_of(&mut self, nodes: &[&Node], kind: BlockKind, indent: usize) {
+        let items = self.split_items(nodes, kind);
+        let last_item = items
+            .iter()
+            .rposition(|item| !self.is_comments(item))
+            .unwrap_or(0);
+        for (i, item) in items.iter().enumerate() {
+            let first = &self.tokens[item[0].first_token()];
+            if !self.out.is_empty() {
+                if i > 0 && first.newlines_before > 1 {
+                    self.blank_line();
+                }
+                self.newline(indent);
+            } else {
+                self.line_indent = indent;
+                self.col = indent;
+            }
+            // A comma after the last field of a struct or arm of a match is added if missing
+            let missing_comma = matches!(kind, BlockKind::Fields | BlockKind::Arms)
+                && i == last_item
+                && !item.iter().any(|node| {
+                    node.as_token()
+                        .is_some_and(|idx| self.tokens[idx].is(Tok::Comma))
+                });
+            if missing_comma {
+                let end = item
+                    .iter()
+                    .rposition(|node| !self.is_comment(node))
+                    .map_or(item.len(), |pos| pos + 1);
+                self.line(&item[..end], indent, 1);
+                self.emit_str(",");
+                self.seq_rest(&item[end..], indent);
+            } else {
+                self.line(item, indent, 0);
+            }
+        }
+    }
+
+    /// Prints trailing comments after an item.
+    fn seq_rest(&mut self, nodes: &[&Node], indent: usize) {
+        for node in nodes {
+            self.separate(node.first_token(), indent);
+            self.emit(node.first_token());
+        }
+    }
+
+    fn is_comment(&self, node: &Node) -> bool {
+        node.as_token()
+            .is_some_and(|idx| self.tokens[idx].is_comment())
+    }
+
+    fn is_comments(&self, nodes: &[&Node]) -> bool {
+        nodes.iter().all(|node| self.is_comment(node))
+    }
+
+    /// Splits the contents of a block into items, which are printed on lines of their own.
+    fn split_items<'n>(&self, nodes: &[&'n Node], kind: BlockKind) -> Vec<Vec<&'n Node>> {
+        let separator = match kind {
+            BlockKind::Seq => Tok::Semicolon,
+            BlockKind::Fields | BlockKind::Arms => Tok::Comma,
+        };
+        let mut items: Vec<Vec<&Node>> = vec![];
+        let mut current: Vec<&Node> = vec![];
+        for (i, node) in nodes.iter().enumerate() {
+            if let Some(idx) = node.as_token() {
+                let token = &self.tokens[idx];
+                if token.is_comment() {
+                    if token.newlines_before == 0 && current.is_empty() && !items.is_empty() {
+                        // A comment after the end of the previous item
+                        items.last_mut().unwrap().push(node);
+                    } else if current.is_empty() {
+                        // A comment on a line of its own
+                        items.push(vec![node]);
+                    } else {
+                        current.push(node);
+                    }
+                    continue;
+                }
+            }
+            current.push(node);
+            let ends_item = match node {
+                Node::Token(idx) => self.tokens[*idx].is(separator),
+                Node::Group(group) => match group.kind {
+                    GroupKind::Block(_) => !self.continues_after_block(&nodes[i + 1..]),
+                    // An attribute
+                    GroupKind::Bracket => {
+                        current.len() == 2
+                            && current[0]
+                                .as_token()
+                                .is_some_and(|idx| self.tokens[idx].is(Tok::NumSign))
+                    },
+                    _ => false,
+                },
+            };
+            if ends_item {
+                items.push(std::mem::take(&mut current));
+            }
+        }
+        if !current.is_empty() {
+            items.push(current);
+        }
+        items
+    }
+
+    /// Whether the item continues after a block, as for `} else {`.
+    fn continues_after_block(&self, rest: &[&Node]) -> bool {
+        let next = match rest.iter().find(|node| !self.is_comment(node)) {
+            Some(node) => node.first_token(),
+            None => return false,
+        };
+        let token = &self.tokens[next];
+        let is_operator = !self.is_unary(next)
+            && matches!(
+                token.tok(),
+                Some(
+                    Tok::Plus
+                        | Tok::Minus
+                        | Tok::Star
+                        | Tok::Slash
+                        | Tok::Percent
+                        | Tok::Amp
+                        | Tok::AmpAmp
+                        | Tok::Pipe
+                        | Tok::PipePipe
+                        | Tok::Caret
+                        | Tok::LessLess
+                        | Tok::GreaterGreater
+                        | Tok::EqualEqual
+                        | Tok::ExclaimEqual
+                        | Tok::Less
+                        | Tok::LessEqual
+                        | Tok::Greater
+                        | Tok::GreaterEqual
+                        | Tok::EqualEqualGreater
+                        | Tok::LessEqualEqualGreater
+                        | Tok::PeriodPeriod
+                        | Tok::Equal
+                        | Tok::As
+                )
+            );
+        is_operator
+            || matches!(
+                token.tok(),
+                Some(Tok::Else | Tok::Semicolon | Tok::Comma | Tok::Period)
+            )
+            || token.is_ident("has")
+            || token.is_ident("is")
+            || self.hints.glued.contains(&token.start)
+    }
+
+    /// Splits the contents of a list group at its commas.
+    fn elements<'n>(&self, group: &'n Group) -> Vec<Element<'n>> {
+        let mut elements = vec![];
+        let mut current = Element {
+            nodes: vec![],
+            comma: None,
+            comments: vec![],
+        };
+        let mut after_comma = false;
+        for node in &group.children {
+            if let Some(idx) = node.as_token() {
+                let token = &self.tokens[idx];
+                if token.is(Tok::Comma) {
+                    current.comma = Some(idx);
+                    elements.push(std::mem::replace(&mut current, Element {
+                        nodes: vec![],
+                        comma: None,
+                        comments: vec![],
+                    }));
+                    after_comma = true;
+                    continue;
+                }
+                if after_comma && token.is_comment() && token.newlines_before == 0 {
+                    elements.last_mut().unwrap().comments.push(idx);
+                    continue;
+                }
+            }
+            after_comma = false;
+            current.nodes.push(node);
+        }
+        if !current.nodes.is_empty() {
+            elements.push(current);
+        }
+        elements
+    }
+
+    fn list(&mut self, group: &Group, trailing: usize) {
+        if group.children.is_empty() {
+            self.emit(group.open);
+            self.emit(group.close);
+            return;
+        }
+        let elements = self.elements(group);
+        let flat_width = self.flat_width(group);
+        // Type arguments are only broken if they do not fit by themselves
+        let trailing = if group.kind == GroupKind::Angle {
+            0
+        } else {
+            trailing
+        };
+        if self.flat > 0 || flat_width.is_some_and(|width| self.fits(width + trailing)) {
+            self.flat += 1;
+            self.flat_list(group, &elements);
+            self.flat -= 1;
+        } else if !self.hug_list(group, &elements) {
+            self.broken_list(group, &elements);
+        }
+    }
+
+    fn flat_list(&mut self, group: &Group, elements: &[Element]) {
+        let padded = self.padded(group);
+        self.emit(group.open);
+        if padded {
+            self.space();
+        }
+        for (i, element) in elements.iter().enumerate() {
+            if i > 0 {
+                self.space();
+            }
+            self.seq(&element.nodes, self.line_indent, 0);
+            if i + 1 < elements.len() {
+                self.emit_str(",");
+            }
+            for comment in &element.comments {
+                self.space();
+                self.emit(*comment);
+            }
+        }
+        if padded {
+            self.space();
+        }
+        self.emit(group.close);
+    }
+
+    /// Prints a list whose last element ends in a block or struct literal on one line, apart
+    /// from the contents of the block, as for a lambda passed as the last argument of a call.
+    /// Returns false if this is not possible.
+    fn hug_list(&mut self, group: &Group, elements: &[Element]) -> bool {
+        let (last, init) = match elements.split_last() {
+            Some(split) => split,
+            None => return false,
+        };
+        let (block, head) = match last.nodes.split_last() {
+            Some((Node::Group(block), head))
+                if matches!(block.kind, GroupKind::Block(_))
+                    || (block.kind == GroupKind::Brace && !head.is_empty()) =>
+            {
+                (block, head)
+            },
+            _ => return false,
+        };
+        let mut width = self.tokens[group.open].text.len() + 2;
+        for element in init {
+            if !element.comments.is_empty() {
+                return false;
+            }
+            match self.nodes_width(&element.nodes) {
+                Some(element_width) => width += element_width + 2,
+                None => return false,
+            }
+        }
+        match self.nodes_width(head) {
+            Some(head_width) => width += head_width,
+            None => return false,
+        }
+        if !self.fits(width) || last.nodes.iter().any(|node| self.is_comment(node)) {
+            return false;
+        }
+        let padded = self.padded(group);
+        self.emit(group.open);
+        if padded {
+            self.space();
+        }
+        for element in init {
+            self.flat += 1;
+            self.seq(&element.nodes, self.line_indent, 0);
+            self.flat -= 1;
+            self.emit_str(", ");
+        }
+        self.seq(head, self.line_indent, 0);
+        if !head.is_empty() {
+            self.separate(block.open, self.line_indent);
+        }
+        let close_width = self.tokens[group.close].text.len() + padded as usize;
+        self.group(block, close_width);
+        if padded {
+            self.space();
+        }
+        self.emit(group.close);
+        true
+    }
+
+    fn broken_list(&mut self, group: &Group, elements: &[Element]) {
+        let indent = self.line_indent;
+        let inner = indent + self.config.indent_size;
+        // Trailing commas are added to lists separated by commas, unless the language does
+        // not allow them
+        let has_commas = elements.iter().any(|element| element.comma.is_some());
+        let trailing_comma = has_commas
+            && matches!(
+                group.kind,
+                GroupKind::Paren | GroupKind::Bracket | GroupKind::Brace
+            );
+        let last_element = elements
+            .iter()
+            .rposition(|element| !element.is_comment_only(self.tokens));
+        self.emit(group.open);
+        let mut elements_iter = elements.iter().enumerate().peekable();
+        // Comments on the line of the opening delimiter stay there
+        if let Some((_, first)) = elements_iter.peek() {
+            let nodes = self.leading_comments(&first.nodes);
+            if nodes.len() < first.nodes.len() {
+                let (i, first) = elements_iter.next().unwrap();
+                let rest = Element {
+                    nodes,
+                    comma: first.comma,
+                    comments: first.comments.clone(),
+                };
+                self.broken_element(&rest, inner, Some(i) == last_element, trailing_comma);
+            }
+        }
+        for (i, element) in elements_iter {
+            self.broken_element(element, inner, Some(i) == last_element, trailing_comma);
+        }
+        self.newline(indent);
+        self.emit(group.close);
+    }
+
+    fn broken_element(
+        &mut self,
+        element: &Element,
+        indent: usize,
+        is_last: bool,
+        trailing_comma: bool,
+    ) {
+        if element.nodes.is_empty() {
+            return;
+        }
+        self.newline(indent);
+        let comma = if is_last {
+            trailing_comma
+        } else {
+            element.comma.is_some()
+        };
+        if element.is_comment_only(self.tokens) {
+            self.seq_rest(&element.nodes, indent);
+        } else {
+            // An added comma goes before any comments at the end of the last element
+            let end = if element.comma.is_none() {
+                element
+                    .nodes
+                    .iter()
+                    .rposition(|node| !self.is_comment(node))
+                    .map_or(element.nodes.len(), |pos| pos + 1)
+            } else {
+                element.nodes.len()
+            };
+            self.line(&element.nodes[..end], indent, comma as usize);
+            if comma {
+                self.emit_str(",");
+            }
+            self.seq_rest(&element.nodes[end..], indent);
+        }
+        for comment in &element.comments {
+            self.space();
+            self.emit(*comment);
+        }
+    }
+}
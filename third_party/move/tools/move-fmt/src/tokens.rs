@@ -0,0 +1,158 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Splits a source file into tokens, including the comments in between them, which the
+//! lexer of the compiler skips.
+
+use anyhow::{anyhow, Result};
+use legacy_move_compiler::parser::lexer::{Lexer, Tok};
+use move_command_line_common::files::FileHash;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum TokenKind {
+    Tok(Tok),
+    LineComment,
+    BlockComment,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct Token {
+    pub kind: TokenKind,
+    pub text: String,
+    /// Byte offset of the token in the source.
+    pub start: usize,
+    pub end: usize,
+    /// The number of line breaks between the previous token and this one.
+    pub newlines_before: usize,
+}
+
+impl Token {
+    pub fn tok(&self) -> Option<Tok> {
+        match self.kind {
+            TokenKind::Tok(tok) => Some(tok),
+            TokenKind::LineComment | TokenKind::BlockComment => None,
+        }
+    }
+
+    pub fn is(&self, tok: Tok) -> bool {
+        self.kind == TokenKind::Tok(tok)
+    }
+
+    pub fn is_comment(&self) -> bool {
+        self.tok().is_none()
+    }
+
+    pub fn is_ident(&self, name: &str) -> bool {
+        self.is(Tok::Identifier) && self.text == name
+    }
+
+    /// The number of columns the token takes up, if it fits on a single line.
+    pub fn width(&self) -> Option<usize> {
+        (!self.text.contains('\n')).then(|| self.text.chars().count())
+    }
+}
+
+/// Returns the tokens and comments of `source`, in order.
+pub(crate) fn tokenize(source: &str) -> Result<Vec<Token>> {
+    let mut lexer = Lexer::new(source, FileHash::new(source));
+    let mut tokens = vec![];
+    let mut prev_end = 0;
+    loop {
+        lexer
+            .advance()
+            .map_err(|diag| anyhow!("failed to tokenize: {:?}", diag))?;
+        let start = lexer.start_loc();
+        let newlines = scan_comments(source, prev_end, start, &mut tokens);
+        let tok = lexer.peek();
+        if tok == Tok::EOF {
+            break;
+        }
+        let mut text = lexer.content();
+        if tok == Tok::AmpMut {
+            // The lexer includes the whitespace after `&mut` in the token
+            text = text.trim_end();
+        }
+        tokens.push(Token {
+            kind: TokenKind::Tok(tok),
+            text: text.to_string(),
+            start,
+            end: start + text.len(),
+            newlines_before: newlines,
+        });
+        prev_end = start + text.len();
+    }
+    Ok(tokens)
+}
+
+/// Adds the comments in `source[start..end]`, which contains only whitespace and comments, to
+/// `tokens`. Returns the number of line breaks after the last comment.
+fn scan_comments(source: &str, start: usize, end: usize, tokens: &mut Vec<Token>) -> usize {
+    let bytes = source.as_bytes();
+    let mut pos = start;
+    let mut newlines = 0;
+    while pos < end {
+        if bytes[pos] == b'\n' {
+            newlines += 1;
+            pos += 1;
+        } else if bytes[pos..end].starts_with(b"//") {
+            let comment_end = source[pos..end]
+                .find('\n')
+                .map_or(end, |offset| pos + offset);
+            push_comment(
+                tokens,
+                TokenKind::LineComment,
+                source[pos..comment_end].trim_end(),
+                pos,
+                newlines,
+            );
+            newlines = 0;
+            pos = comment_end;
+        } else if bytes[pos..end].starts_with(b"/*") {
+            // Block comments nest
+            let mut depth = 0;
+            let mut comment_end = pos;
+            while comment_end < end {
+                if bytes[comment_end..end].starts_with(b"/*") {
+                    depth += 1;
+                    comment_end += 2;
+                } else if bytes[comment_end..end].starts_with(b"*/") {
+                    depth -= 1;
+                    comment_end += 2;
+                    if depth == 0 {
+                        break;
+                    }
+                } else {
+                    comment_end += 1;
+                }
+            }
+            push_comment(
+                tokens,
+                TokenKind::BlockComment,
+                &source[pos..comment_end],
+                pos,
+                newlines,
+            );
+            newlines = 0;
+            pos = comment_end;
+        } else {
+            pos += 1;
+        }
+    }
+    newlines
+}
+
+fn push_comment(
+    tokens: &mut Vec<Token>,
+    kind: TokenKind,
+    text: &str,
+    start: usize,
+    newlines_before: usize,
+) {
+    tokens.push(Token {
+        kind,
+        text: text.to_string(),
+        start,
+        end: start + text.len(),
+        newlines_before,
+    });
+}
@@ -0,0 +1,198 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Nests the tokens of a file into groups of matching delimiters.
+
+use crate::{
+    hints::{BlockKind, Hints},
+    tokens::{Token, TokenKind},
+};
+use anyhow::{bail, Result};
+use legacy_move_compiler::parser::lexer::Tok;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum GroupKind {
+    Paren,
+    Bracket,
+    /// Type parameters or arguments.
+    Angle,
+    /// Parameters of a lambda or function type.
+    Pipe,
+    /// Braces which are laid out like a list, as for struct literals or `use` groups.
+    Brace,
+    /// Braces which are always broken across lines.
+    Block(BlockKind),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Role {
+    Plain,
+    Open(GroupKind),
+    Close(GroupKind),
+}
+
+#[derive(Debug)]
+pub(crate) enum Node {
+    /// A token or comment, as an index into the tokens.
+    Token(usize),
+    Group(Group),
+}
+
+#[derive(Debug)]
+pub(crate) struct Group {
+    pub kind: GroupKind,
+    pub open: usize,
+    pub close: usize,
+    pub children: Vec<Node>,
+}
+
+impl Node {
+    pub fn first_token(&self) -> usize {
+        match self {
+            Node::Token(idx) => *idx,
+            Node::Group(group) => group.open,
+        }
+    }
+
+    pub fn last_token(&self) -> usize {
+        match self {
+            Node::Token(idx) => *idx,
+            Node::Group(group) => group.close,
+        }
+    }
+
+    pub fn as_token(&self) -> Option<usize> {
+        match self {
+            Node::Token(idx) => Some(*idx),
+            Node::Group(_) => None,
+        }
+    }
+}
+
+/// Builds the tree of `tokens`, returning its top-level nodes and the role of each token.
+/// `>>` closing two type argument lists is split into two tokens, which are appended to
+/// `tokens`.
+pub(crate) fn build(tokens: &mut Vec<Token>, hints: &Hints) -> Result<(Vec<Node>, Vec<Role>)> {
+    let mut roles = vec![Role::Plain; tokens.len()];
+    // The open groups, each with the index of its opening token and its children so far
+    let mut stack: Vec<(GroupKind, usize, Vec<Node>)> = vec![];
+    let mut top = vec![];
+    let count = tokens.len();
+    for idx in 0..count {
+        let token = tokens[idx].clone();
+        let tok = match token.kind {
+            TokenKind::Tok(tok) => tok,
+            TokenKind::LineComment | TokenKind::BlockComment => {
+                push(&mut stack, &mut top, Node::Token(idx));
+                continue;
+            },
+        };
+        let innermost = stack.last().map(|(kind, _, _)| *kind);
+        let open = match tok {
+            Tok::LParen => Some(GroupKind::Paren),
+            Tok::LBracket => Some(GroupKind::Bracket),
+            Tok::LBrace => Some(match hints.blocks.get(&token.start) {
+                Some(kind) => GroupKind::Block(*kind),
+                None => GroupKind::Brace,
+            }),
+            Tok::Less if is_type_args(tokens, idx, hints) => Some(GroupKind::Angle),
+            Tok::Pipe if hints.pipes.contains(&token.start) => Some(GroupKind::Pipe),
+            _ => None,
+        };
+        if let Some(kind) = open {
+            roles[idx] = Role::Open(kind);
+            stack.push((kind, idx, vec![]));
+            continue;
+        }
+        let closes = match (tok, innermost) {
+            (Tok::RParen, Some(GroupKind::Paren))
+            | (Tok::RBracket, Some(GroupKind::Bracket))
+            | (Tok::RBrace, Some(GroupKind::Brace | GroupKind::Block(_)))
+            | (Tok::Greater, Some(GroupKind::Angle))
+            | (Tok::Pipe, Some(GroupKind::Pipe)) => true,
+            (Tok::GreaterGreater, Some(GroupKind::Angle)) => {
+                // Split `>>` into two `>`, the second of which is appended to the tokens
+                let second = Token {
+                    kind: TokenKind::Tok(Tok::Greater),
+                    text: ">".to_string(),
+                    start: token.start + 1,
+                    end: token.end,
+                    newlines_before: 0,
+                };
+                let first = &mut tokens[idx];
+                first.kind = TokenKind::Tok(Tok::Greater);
+                first.text = ">".to_string();
+                first.end = first.start + 1;
+                close(&mut stack, &mut top, &mut roles, idx);
+                tokens.push(second);
+                roles.push(Role::Plain);
+                let second = tokens.len() - 1;
+                if matches!(stack.last(), Some((GroupKind::Angle, _, _))) {
+                    close(&mut stack, &mut top, &mut roles, second);
+                } else {
+                    push(&mut stack, &mut top, Node::Token(second));
+                }
+                continue;
+            },
+            (Tok::RParen | Tok::RBracket | Tok::RBrace, _) => {
+                bail!("unbalanced `{}` at offset {}", token.text, token.start)
+            },
+            _ => false,
+        };
+        if closes {
+            close(&mut stack, &mut top, &mut roles, idx);
+        } else {
+            push(&mut stack, &mut top, Node::Token(idx));
+        }
+    }
+    if let Some((_, open, _)) = stack.last() {
+        bail!(
+            "unbalanced `{}` at offset {}",
+            tokens[*open].text,
+            tokens[*open].start
+        )
+    }
+    Ok((top, roles))
+}
+
+fn push(stack: &mut [(GroupKind, usize, Vec<Node>)], top: &mut Vec<Node>, node: Node) {
+    match stack.last_mut() {
+        Some((_, _, children)) => children.push(node),
+        None => top.push(node),
+    }
+}
+
+fn close(
+    stack: &mut Vec<(GroupKind, usize, Vec<Node>)>,
+    top: &mut Vec<Node>,
+    roles: &mut [Role],
+    idx: usize,
+) {
+    let (kind, open, children) = stack.pop().expect("open group");
+    roles[idx] = Role::Close(kind);
+    push(
+        stack,
+        top,
+        Node::Group(Group {
+            kind,
+            open,
+            close: idx,
+            children,
+        }),
+    );
+}
+
+/// Whether the `<` at `idx` opens type arguments. Besides the declarations and types
+/// collected from the AST, this is the case where it directly follows a name, as the parser
+/// only accepts type arguments of expressions if there is no space in between.
+fn is_type_args(tokens: &[Token], idx: usize, hints: &Hints) -> bool {
+    let token = &tokens[idx];
+    if hints.type_args.contains(&token.start) {
+        return true;
+    }
+    match idx.checked_sub(1).map(|prev| &tokens[prev]) {
+        Some(prev) if prev.is(Tok::Identifier) => prev.end == token.start,
+        Some(prev) => prev.is(Tok::ColonColon),
+        None => false,
+    }
+}
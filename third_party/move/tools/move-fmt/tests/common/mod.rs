@@ -0,0 +1,50 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use move_fmt::{format_source, FormatConfig};
+use move_prover_test_utils::baseline_test;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Checks that the source at `path` can be formatted, and that formatting it again does not
+/// change it any further. The formatter itself verifies that the tokens are preserved. If the
+/// source is one of the `golden_sources` (relative to `root`), the formatted source is also
+/// compared with its baseline in `golden_dir`.
+pub fn check_formatting(
+    path: &Path,
+    root: &Path,
+    golden_sources: &[&str],
+    golden_dir: &str,
+) -> datatest_stable::Result<()> {
+    let source = fs::read_to_string(path)?;
+    let file_name = path.display().to_string();
+    let config = FormatConfig::default();
+    let formatted = format_source(&file_name, &source, &config)?;
+    let reformatted = format_source(&file_name, &formatted, &config)?;
+    if reformatted != formatted {
+        return Err(format!("formatting `{}` is not idempotent", file_name).into());
+    }
+    if let Some(baseline_path) = golden_path(path, root, golden_sources, golden_dir) {
+        baseline_test::verify_or_update_baseline(&baseline_path, &formatted)?;
+    }
+    Ok(())
+}
+
+fn golden_path(
+    path: &Path,
+    root: &Path,
+    golden_sources: &[&str],
+    golden_dir: &str,
+) -> Option<PathBuf> {
+    let relative = path.strip_prefix(root).ok()?;
+    golden_sources
+        .iter()
+        .any(|source| relative == Path::new(source))
+        .then(|| {
+            Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join(golden_dir)
+                .join(relative.with_extension("exp"))
+        })
+}
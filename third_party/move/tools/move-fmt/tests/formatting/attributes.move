@@ -0,0 +1,16 @@
+#[test_only]
+module 0x42::attributes {
+    #[test(account = @0x1)] #[expected_failure(abort_code = 1, location = Self)]
+    fun test_abort(account: &signer) { abort 1 }
+
+    #[view]
+    public fun view(): u64 { 1 }
+
+    public entry fun entry(s: &signer) {}
+
+    friend 0x42::other;
+
+    public(friend) fun friend_only() {}
+
+    package fun package_only() {}
+}
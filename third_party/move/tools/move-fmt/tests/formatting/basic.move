@@ -0,0 +1,43 @@
+module 0x42::basic {
+    use std::vector;
+    use std::option::{Self,Option};
+    const EMAX: u64=10;
+    const NAMES: vector<vector<u8>> = vector[b"a",b"b"];
+
+    struct Pair<T1:copy+drop,T2> has copy, drop { first: T1, second: T2 }
+
+    struct Empty has drop {}
+
+    public fun make_pair<T1: copy + drop, T2>(first: T1, second: T2): Pair<T1, T2> { Pair { first, second } }
+
+    fun arithmetic(x: u64, y: u64): u64 {
+        let z = x+y*2-(x%y);
+        if (z>EMAX) { z = z/2 } else if (z == 0) { abort EMAX } else { z = z << 1 };
+        while (z > 100) z = z - 1;
+        loop {
+            if (z < 10) break;
+            z = z - 10;
+        };
+        z
+    }
+
+    fun references(v: &mut vector<u64>, r: &u64): u64 {
+        let x = *r;
+        let first = &mut v[0];
+        *first = x;
+        v.push_back(*vector::borrow(v, 0));
+        let (a, b) = (1, 2);
+        a + b + (x as u64)
+    }
+
+    fun lambdas(v: vector<u64>): u64 {
+        let sum = 0;
+        v.for_each_ref(|x| sum = sum + *x);
+        v.for_each(|x| {
+            sum = sum + x;
+        });
+        sum
+    }
+
+    fun options(o: Option<u64>): u64 { if (option::is_some(&o)) option::destroy_some(o) else 0 }
+}
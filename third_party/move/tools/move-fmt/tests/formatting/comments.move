@@ -0,0 +1,35 @@
+/// Doc comment of the module.
+module 0x42::comments {
+    // A comment before a constant.
+    const A: u64 = 1; // A trailing comment.
+
+
+
+    /* A block comment */ const B: u64 = 2;
+
+    struct S {
+        // The first field.
+        x: u64,
+        y: u64 // The last field, without a comma.
+    }
+
+    fun f(
+        a: u64, // The first parameter.
+        b: u64
+    ): u64 { // A comment after the opening brace.
+        let c = a /* inline */ + b;
+        // A comment at the end of a block.
+    c
+    }
+
+    fun g(): vector<u64> {
+        vector[
+            1, // one
+            2 // two
+        ]
+    }
+
+    fun empty() {
+        // Nothing here.
+    }
+}
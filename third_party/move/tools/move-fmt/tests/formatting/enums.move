@@ -0,0 +1,15 @@
+module 0x42::enums {
+    enum Shape has drop { Circle { radius: u64 }, Rectangle { width: u64, height: u64 }, Unit }
+
+    enum Wrapper<T> has drop { One(T), Two(T, T) }
+
+    fun area(s: &Shape): u64 {
+        match (s) { Shape::Circle { radius } => *radius * *radius * 3, Shape::Rectangle { width, height } => { *width * *height }, Shape::Unit => 0 }
+    }
+
+    fun is_circle(s: &Shape): bool { s is Circle }
+
+    fun first<T: drop>(w: Wrapper<T>): T {
+        match (w) { Wrapper::One(x) => x, Wrapper::Two(x, _) => x }
+    }
+}
@@ -0,0 +1,10 @@
+// indent_size = 2
+module 0x42::indent_size {
+    struct S { x: u64 }
+
+    fun f(s: &S): u64 {
+        if (s.x > 0) {
+            s.x
+        } else { 0 }
+    }
+}
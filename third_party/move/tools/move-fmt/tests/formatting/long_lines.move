@@ -0,0 +1,20 @@
+// max_width = 60
+module 0x42::long_lines {
+    fun call_with_many_arguments(first_argument: u64, second_argument: u64, third_argument: u64): u64 {
+        call_with_many_arguments(first_argument + 1, second_argument * 2, third_argument)
+    }
+
+    fun conditions(a: bool, b: bool, c: bool, d: bool): bool {
+        a && b || c && d && a && b || c && d && a && b || c
+    }
+
+    fun struct_literal(): Point {
+        Point { x_coordinate: 1000000, y_coordinate: 2000000, z: 3 }
+    }
+
+    fun lambda_argument(v: vector<u64>) {
+        v.for_each(|element| { assert!(element > 0, 1); });
+    }
+
+    fun long_types(): Table<address, vector<Option<u64>>> { abort 0 }
+}
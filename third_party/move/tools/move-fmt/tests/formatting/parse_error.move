@@ -0,0 +1,3 @@
+module 0x42::parse_error {
+    fun f() { let x = ; }
+}
@@ -0,0 +1,32 @@
+module 0x42::specs {
+    struct Counter has key { value: u64 }
+
+    fun increment(addr: address) acquires Counter {
+        let c = borrow_global_mut<Counter>(addr);
+        c.value = c.value + 1;
+    }
+    spec increment {
+        pragma opaque;
+        let post new_value = global<Counter>(addr).value;
+        aborts_if !exists<Counter>(addr);
+        aborts_if global<Counter>(addr).value + 1 > MAX_U64;
+        ensures new_value == old(global<Counter>(addr).value) + 1;
+        ensures forall a: address where a != addr: global<Counter>(a) == old(global<Counter>(a));
+    }
+
+    spec schema CounterExists { addr: address; requires exists<Counter>(addr); }
+
+    spec module {
+        invariant [suspendable] forall a: address: exists<Counter>(a) ==> global<Counter>(a).value < 100;
+    }
+
+    fun sum(n: u64): u64 {
+        let i = 0;
+        let s = 0;
+        while ({ spec { invariant i <= n; }; i < n }) {
+            i = i + 1;
+            s = s + i;
+        };
+        s
+    }
+}
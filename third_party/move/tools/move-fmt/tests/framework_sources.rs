@@ -0,0 +1,29 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use std::path::{Path, PathBuf};
+
+mod common;
+
+/// Sources whose formatted output is kept in `tests/goldens/framework`, covering specs,
+/// generics, attributes and long expressions
+const GOLDEN_SOURCES: &[&str] = &[
+    "move-stdlib/sources/option.move",
+    "aptos-stdlib/sources/simple_map.move",
+    "aptos-framework/sources/aggregator_v2/aggregator_v2.move",
+];
+
+fn framework_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("../../../../aptos-move/framework")
+}
+
+fn check_formatting(path: &Path) -> datatest_stable::Result<()> {
+    common::check_formatting(
+        path,
+        &framework_root(),
+        GOLDEN_SOURCES,
+        "tests/goldens/framework",
+    )
+}
+
+datatest_stable::harness!(check_formatting, framework_root().display(), r"\.move$");
@@ -0,0 +1,33 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use std::path::{Path, PathBuf};
+
+mod common;
+
+/// Sources whose formatted output is kept in `tests/goldens/move-examples`, covering entry
+/// functions, events, tests and enums
+const GOLDEN_SOURCES: &[&str] = &[
+    "hello_blockchain/sources/hello_blockchain.move",
+    "fungible_asset/fa_coin/sources/FACoin.move",
+    "function_values/calculator/sources/calculator.move",
+];
+
+fn examples_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("../../../../aptos-move/move-examples")
+}
+
+fn check_formatting(path: &Path) -> datatest_stable::Result<()> {
+    // This step of the tutorial leaves function bodies as an exercise, so it does not parse
+    if path.ends_with("move-tutorial/step_3/basic_coin.move") {
+        return Ok(());
+    }
+    common::check_formatting(
+        path,
+        &examples_root(),
+        GOLDEN_SOURCES,
+        "tests/goldens/move-examples",
+    )
+}
+
+datatest_stable::harness!(check_formatting, examples_root().display(), r"\.move$");
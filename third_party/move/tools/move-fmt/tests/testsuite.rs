@@ -0,0 +1,36 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use move_fmt::{format_source, FormatConfig};
+use move_prover_test_utils::baseline_test;
+use std::{fs, path::Path};
+
+/// Extension for expected output files.
+pub const EXP_EXT: &str = "exp";
+
+datatest_stable::harness!(test_runner, "tests/formatting", r".*\.move$");
+
+fn test_runner(path: &Path) -> datatest_stable::Result<()> {
+    let source = fs::read_to_string(path)?;
+    let mut config = FormatConfig::default();
+    // Options can be set in a leading comment, as in `// max_width = 60`
+    for line in source.lines().take_while(|line| line.starts_with("//")) {
+        if let Some((key, value)) = line.trim_start_matches('/').split_once('=') {
+            config.set(key.trim(), value.trim())?;
+        }
+    }
+    let file_name = path.display().to_string();
+    let output = match format_source(&file_name, &source, &config) {
+        Ok(formatted) => {
+            let reformatted = format_source(&file_name, &formatted, &config)?;
+            if reformatted != formatted {
+                return Err(format!("formatting `{}` is not idempotent", file_name).into());
+            }
+            formatted
+        },
+        Err(err) => format!("Error: {:#}\n", err),
+    };
+    let baseline_path = path.with_extension(EXP_EXT);
+    baseline_test::verify_or_update_baseline(baseline_path.as_path(), &output)?;
+    Ok(())
+}
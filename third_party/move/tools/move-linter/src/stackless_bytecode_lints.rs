@@ -6,7 +6,12 @@
 //! The lint checks also assume that all the correctness checks have already been performed.
 
 mod avoid_copy_on_identity_comparison;
+mod exposed_object_refs;
+mod missing_event_emission;
 mod needless_mutable_reference;
+mod public_randomness_use;
+mod unbounded_vector_iteration;
+mod unchecked_fund_transfer;
 
 use move_compiler_v2::external_checks::StacklessBytecodeChecker;
 use std::collections::BTreeMap;
@@ -16,13 +21,22 @@ pub fn get_default_linter_pipeline(
     config: &BTreeMap<String, String>,
 ) -> Vec<Box<dyn StacklessBytecodeChecker>> {
     // Start with the default set of checks.
-    let checks: Vec<Box<dyn StacklessBytecodeChecker>> = vec![
+    let mut checks: Vec<Box<dyn StacklessBytecodeChecker>> = vec![
         Box::new(avoid_copy_on_identity_comparison::AvoidCopyOnIdentityComparison {}),
+        Box::new(exposed_object_refs::ExposedObjectRefs {}),
         Box::new(needless_mutable_reference::NeedlessMutableReference {}),
+        Box::new(public_randomness_use::PublicRandomnessUse {}),
     ];
     let checks_category = config.get("checks").map_or("default", |s| s.as_str());
     if checks_category == "strict" || checks_category == "experimental" {
         // Push strict checks to `checks`.
+        // The security checks below understand the Aptos framework, but are heuristic and
+        // can flag code which is safe.
+        checks.push(Box::new(missing_event_emission::MissingEventEmission {}));
+        checks.push(Box::new(
+            unbounded_vector_iteration::UnboundedVectorIteration {},
+        ));
+        checks.push(Box::new(unchecked_fund_transfer::UncheckedFundTransfer {}));
     }
     if checks_category == "experimental" {
         // Push experimental checks to `checks`.
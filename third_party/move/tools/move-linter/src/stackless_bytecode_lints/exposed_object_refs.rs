@@ -0,0 +1,46 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module implements a stackless-bytecode linter that checks for public functions which
+//! return an `object::ExtendRef`, `object::TransferRef` or `object::DeleteRef` (or a reference
+//! to one). These refs grant the power to sign for, transfer or delete an object, so any module
+//! calling such a function obtains that power over the object.
+
+use crate::utils::framework_struct_name;
+use move_compiler_v2::external_checks::StacklessBytecodeChecker;
+use move_model::model::Visibility;
+use move_stackless_bytecode::function_target::FunctionTarget;
+
+const OBJECT_REFS: [&str; 3] = ["ExtendRef", "TransferRef", "DeleteRef"];
+
+pub struct ExposedObjectRefs {}
+
+impl StacklessBytecodeChecker for ExposedObjectRefs {
+    fn get_name(&self) -> String {
+        "exposed_object_refs".to_string()
+    }
+
+    fn check(&self, target: &FunctionTarget) {
+        let func_env = target.func_env;
+        if func_env.visibility() != Visibility::Public {
+            return;
+        }
+        let env = target.global_env();
+        let mut exposed_ref = None;
+        func_env.get_result_type().visit(&mut |ty| {
+            if exposed_ref.is_none() {
+                exposed_ref = framework_struct_name(env, ty, "object", &OBJECT_REFS);
+            }
+        });
+        if let Some(exposed_ref) = exposed_ref {
+            self.report(
+                env,
+                &func_env.get_result_type_loc(),
+                &format!(
+                    "Public function exposes an `object::{}`, which gives any caller control over the object. Keep the ref in a resource and expose only the operations which need it.",
+                    exposed_ref
+                ),
+            );
+        }
+    }
+}
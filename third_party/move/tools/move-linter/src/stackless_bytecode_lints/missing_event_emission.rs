@@ -0,0 +1,96 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module implements a stackless-bytecode linter that checks for entry functions which
+//! modify global storage (via `move_to`, `move_from` or `borrow_global_mut`) without emitting
+//! an event. Off-chain indexers and wallets rely on events to observe changes of on-chain state.
+//! The functions called by the entry function, directly or transitively and also in other
+//! modules, are taken into account, both for the modifications and the events. Functions whose
+//! code is not available (e.g., natives) are assumed to do neither.
+
+use crate::utils::is_framework_function;
+use move_compiler_v2::external_checks::StacklessBytecodeChecker;
+use move_model::{
+    ast::{ExpData, Operation},
+    model::{FunId, GlobalEnv, QualifiedId},
+    ty::ReferenceKind,
+};
+use move_stackless_bytecode::function_target::FunctionTarget;
+use std::collections::BTreeSet;
+
+pub struct MissingEventEmission {}
+
+impl StacklessBytecodeChecker for MissingEventEmission {
+    fn get_name(&self) -> String {
+        "missing_event_emission".to_string()
+    }
+
+    fn check(&self, target: &FunctionTarget) {
+        let func_env = target.func_env;
+        if !func_env.is_entry() {
+            return;
+        }
+        let mut effects = StorageEffects::default();
+        effects.collect(
+            target.global_env(),
+            func_env.get_qualified_id(),
+            &mut BTreeSet::new(),
+        );
+        if effects.modifies_storage && !effects.emits_event {
+            self.report(
+                target.global_env(),
+                &func_env.get_id_loc(),
+                "Entry function modifies global storage without emitting an event. Emit an event (with `event::emit`) so that the change can be observed off-chain.",
+            );
+        }
+    }
+}
+
+#[derive(Default)]
+struct StorageEffects {
+    modifies_storage: bool,
+    emits_event: bool,
+}
+
+impl StorageEffects {
+    /// Collects the effects of the function `fun_id`, and of the functions it calls (which have
+    /// not been `visited` yet).
+    fn collect(
+        &mut self,
+        env: &GlobalEnv,
+        fun_id: QualifiedId<FunId>,
+        visited: &mut BTreeSet<QualifiedId<FunId>>,
+    ) {
+        if !visited.insert(fun_id) {
+            return;
+        }
+        let Some(def) = env.get_function(fun_id).get_def().cloned() else {
+            return;
+        };
+        let mut callees = vec![];
+        def.visit_pre_order(&mut |exp| {
+            if let ExpData::Call(_, op, _) = exp {
+                match op {
+                    Operation::MoveTo
+                    | Operation::MoveFrom
+                    | Operation::BorrowGlobal(ReferenceKind::Mutable) => {
+                        self.modifies_storage = true;
+                    },
+                    Operation::MoveFunction(mid, fid) => {
+                        let callee = env.get_function(mid.qualified(*fid));
+                        if is_framework_function(&callee, "event", &["emit", "emit_event"]) {
+                            self.emits_event = true;
+                        } else {
+                            callees.push(mid.qualified(*fid));
+                        }
+                    },
+                    _ => {},
+                }
+            }
+            true
+        });
+        for callee in callees {
+            self.collect(env, callee, visited);
+        }
+    }
+}
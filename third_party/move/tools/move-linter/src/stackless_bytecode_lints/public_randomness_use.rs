@@ -0,0 +1,41 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module implements a stackless-bytecode linter that checks for public functions which
+//! call the `randomness` module. Another module (or script) calling such a function can observe
+//! the random outcome and abort the transaction if it does not like it, retrying until it gets
+//! a favorable one (a test-and-abort attack). This applies to `public entry` functions too,
+//! since they can also be called from Move code.
+
+use crate::utils::{called_function, is_framework_module};
+use move_compiler_v2::external_checks::StacklessBytecodeChecker;
+use move_model::model::Visibility;
+use move_stackless_bytecode::function_target::FunctionTarget;
+
+pub struct PublicRandomnessUse {}
+
+impl StacklessBytecodeChecker for PublicRandomnessUse {
+    fn get_name(&self) -> String {
+        "public_randomness_use".to_string()
+    }
+
+    fn check(&self, target: &FunctionTarget) {
+        if target.func_env.visibility() != Visibility::Public {
+            return;
+        }
+        for bc in target.get_bytecode() {
+            let Some((id, callee)) = called_function(target, bc) else {
+                continue;
+            };
+            if is_framework_module(&callee.module_env, "randomness") {
+                // Report only the first use in the function.
+                self.report(
+                    target.global_env(),
+                    &target.get_bytecode_loc(id),
+                    "Public functions using randomness are open to test-and-abort attacks, where a caller aborts on unfavorable outcomes. Use randomness only in private entry functions with the `#[randomness]` attribute.",
+                );
+                return;
+            }
+        }
+    }
+}
@@ -0,0 +1,173 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module implements a stackless-bytecode linter that checks for entry functions which
+//! loop over a vector whose length is not bounded: a vector passed in by the caller, or read
+//! from global storage (where it can grow over time). Such a loop can use more gas than a
+//! transaction is allowed, making the function unusable. The lint is not triggered if the
+//! length of the vector is checked against a constant upper bound (e.g., with `assert!`).
+
+use crate::utils::{called_function, is_framework_function};
+use move_compiler_v2::external_checks::StacklessBytecodeChecker;
+use move_stackless_bytecode::{
+    function_target::FunctionTarget,
+    stackless_bytecode::{Bytecode, Operation},
+};
+use std::collections::{BTreeMap, BTreeSet};
+
+pub struct UnboundedVectorIteration {}
+
+impl StacklessBytecodeChecker for UnboundedVectorIteration {
+    fn get_name(&self) -> String {
+        "unbounded_vector_iteration".to_string()
+    }
+
+    fn check(&self, target: &FunctionTarget) {
+        if !target.func_env.is_entry() {
+            return;
+        }
+        let code = target.get_bytecode();
+        if !has_loop(code) {
+            return;
+        }
+        let unbounded_vectors = unbounded_vectors(target);
+        let constants = constants(code);
+
+        // Temporaries holding the lengths of unbounded vectors, with the location of the call
+        // computing the length.
+        let mut lengths = BTreeMap::new();
+        for bc in code {
+            match bc {
+                Bytecode::Call(_, dests, _, srcs, _) => {
+                    let Some((id, callee)) = called_function(target, bc) else {
+                        continue;
+                    };
+                    if is_framework_function(&callee, "vector", &["length"])
+                        && unbounded_vectors.contains(&srcs[0])
+                    {
+                        lengths.insert(dests[0], id);
+                    }
+                },
+                Bytecode::Assign(_, dest, src, _) => {
+                    if let Some(id) = lengths.get(src).copied() {
+                        lengths.insert(*dest, id);
+                    }
+                },
+                _ => {},
+            }
+        }
+
+        // Length computations whose result is used in a comparison (i.e., the loop condition),
+        // and those whose result is compared against a constant upper bound.
+        let mut iterated = BTreeSet::new();
+        let mut bounded = BTreeSet::new();
+        for bc in code {
+            let Bytecode::Call(_, _, op, srcs, _) = bc else {
+                continue;
+            };
+            // Normalize the comparison to `lhs < rhs` or `lhs <= rhs`.
+            let (lhs, rhs) = match op {
+                Operation::Lt | Operation::Le => (srcs[0], srcs[1]),
+                Operation::Gt | Operation::Ge => (srcs[1], srcs[0]),
+                _ => continue,
+            };
+            for length in [lhs, rhs] {
+                if let Some(id) = lengths.get(&length) {
+                    iterated.insert(*id);
+                }
+            }
+            if let Some(id) = lengths.get(&lhs) {
+                if constants.contains(&rhs) {
+                    bounded.insert(*id);
+                }
+            }
+        }
+
+        for id in iterated.difference(&bounded) {
+            self.report(
+                target.global_env(),
+                &target.get_bytecode_loc(*id),
+                "Entry function iterates over a vector of unbounded length, which is passed in by the caller or grows in global storage. Check the length against a maximum, so that the loop cannot run out of gas.",
+            );
+        }
+    }
+}
+
+/// Returns `true` if the code has a backward jump.
+fn has_loop(code: &[Bytecode]) -> bool {
+    let label_offsets: BTreeMap<_, _> = code
+        .iter()
+        .enumerate()
+        .filter_map(|(offset, bc)| match bc {
+            Bytecode::Label(_, label) => Some((*label, offset)),
+            _ => None,
+        })
+        .collect();
+    code.iter().enumerate().any(|(offset, bc)| {
+        let targets = match bc {
+            Bytecode::Jump(_, label) => vec![*label],
+            Bytecode::Branch(_, then_label, else_label, _) => vec![*then_label, *else_label],
+            _ => return false,
+        };
+        targets.iter().any(|label| {
+            label_offsets
+                .get(label)
+                .is_some_and(|target| *target <= offset)
+        })
+    })
+}
+
+/// Returns the temporaries holding vectors passed in by the caller or borrowed from global
+/// storage, and anything derived from them (copies, references and fields).
+fn unbounded_vectors(target: &FunctionTarget) -> BTreeSet<usize> {
+    let mut temps: BTreeSet<_> = (0..target.get_parameter_count())
+        .filter(|idx| target.get_local_type(*idx).skip_reference().is_vector())
+        .collect();
+    loop {
+        let mut changed = false;
+        for bc in target.get_bytecode() {
+            let (dests, derived) = match bc {
+                Bytecode::Assign(_, dest, src, _) => (vec![*dest], temps.contains(src)),
+                Bytecode::Call(_, dests, op, srcs, _) => match op {
+                    Operation::BorrowGlobal(..) => (dests.clone(), true),
+                    Operation::BorrowLoc
+                    | Operation::BorrowField(..)
+                    | Operation::BorrowVariantField(..)
+                    | Operation::ReadRef
+                    | Operation::FreezeRef(_) => {
+                        (dests.clone(), srcs.iter().any(|src| temps.contains(src)))
+                    },
+                    _ => continue,
+                },
+                _ => continue,
+            };
+            if derived {
+                for dest in dests {
+                    changed |= temps.insert(dest);
+                }
+            }
+        }
+        if !changed {
+            return temps;
+        }
+    }
+}
+
+/// Returns the temporaries which are only ever assigned constants.
+fn constants(code: &[Bytecode]) -> BTreeSet<usize> {
+    let mut loaded = BTreeSet::new();
+    let mut assigned = BTreeSet::new();
+    for bc in code {
+        match bc {
+            Bytecode::Load(_, dest, _) => {
+                loaded.insert(*dest);
+            },
+            Bytecode::Assign(_, dest, _, _) => {
+                assigned.insert(*dest);
+            },
+            Bytecode::Call(_, dests, _, _, _) => assigned.extend(dests.iter().copied()),
+            _ => {},
+        }
+    }
+    loaded.difference(&assigned).copied().collect()
+}
@@ -0,0 +1,148 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module implements a stackless-bytecode linter that checks for public (non-entry)
+//! functions which take a signer and use it to withdraw or transfer funds, without checking
+//! anything before doing so. A transfer counts as checked if every path to it passes a
+//! conditional branch to an abort (e.g., an `assert!`). Any module holding a user's signer can
+//! call such a function to move the user's funds, so it should validate its arguments (amounts,
+//! recipients, ...).
+
+use crate::utils::{called_function, is_framework_function};
+use move_binary_format::file_format::CodeOffset;
+use move_compiler_v2::external_checks::StacklessBytecodeChecker;
+use move_model::model::{FunctionEnv, Visibility};
+use move_stackless_bytecode::{
+    function_target::FunctionTarget,
+    stackless_bytecode::Bytecode,
+    stackless_control_flow_graph::{BlockId, StacklessControlFlowGraph},
+};
+use std::collections::BTreeSet;
+
+/// Framework functions moving funds out of the account of the signer passed to them, by module.
+const FUND_TRANSFERS: [(&str, &[&str]); 5] = [
+    ("coin", &["transfer", "withdraw"]),
+    ("aptos_account", &[
+        "batch_transfer",
+        "batch_transfer_coins",
+        "transfer",
+        "transfer_coins",
+        "transfer_fungible_assets",
+    ]),
+    ("primary_fungible_store", &[
+        "transfer",
+        "transfer_assert_minimum_deposit",
+        "withdraw",
+    ]),
+    ("fungible_asset", &["transfer", "withdraw"]),
+    ("dispatchable_fungible_asset", &[
+        "transfer",
+        "transfer_assert_minimum_deposit",
+        "withdraw",
+    ]),
+];
+
+pub struct UncheckedFundTransfer {}
+
+impl StacklessBytecodeChecker for UncheckedFundTransfer {
+    fn get_name(&self) -> String {
+        "unchecked_fund_transfer".to_string()
+    }
+
+    fn check(&self, target: &FunctionTarget) {
+        let func_env = target.func_env;
+        if func_env.visibility() != Visibility::Public || func_env.is_entry() {
+            return;
+        }
+        let code = target.get_bytecode();
+        // Temporaries holding the signers passed to the function.
+        let mut signers: BTreeSet<_> = (0..target.get_parameter_count())
+            .filter(|idx| target.get_local_type(*idx).skip_reference().is_signer())
+            .collect();
+        if signers.is_empty() {
+            return;
+        }
+        let cfg = StacklessControlFlowGraph::new_forward(code);
+        let checks = checks(code, &cfg);
+        for (offset, bc) in code.iter().enumerate() {
+            match bc {
+                Bytecode::Assign(_, dest, src, _) if signers.contains(src) => {
+                    signers.insert(*dest);
+                },
+                Bytecode::Call(_, _, _, srcs, _) => {
+                    let Some((id, callee)) = called_function(target, bc) else {
+                        continue;
+                    };
+                    if is_fund_transfer(&callee)
+                        && srcs.iter().any(|src| signers.contains(src))
+                        && !is_checked(&cfg, &checks, offset as CodeOffset)
+                    {
+                        self.report(
+                            target.global_env(),
+                            &target.get_bytecode_loc(id),
+                            "Public function moves funds of the signer passed to it without checking anything. Any module holding a signer can call it, so validate the arguments (e.g., with `assert!`) before moving funds.",
+                        );
+                    }
+                },
+                _ => {},
+            }
+        }
+    }
+}
+
+fn is_fund_transfer(function: &FunctionEnv) -> bool {
+    FUND_TRANSFERS
+        .iter()
+        .any(|(module, names)| is_framework_function(function, module, names))
+}
+
+/// Returns the blocks ending with a conditional branch to code which aborts, i.e., the checks
+/// in the function.
+fn checks(code: &[Bytecode], cfg: &StacklessControlFlowGraph) -> BTreeSet<BlockId> {
+    cfg.blocks()
+        .into_iter()
+        .filter(|block| {
+            last_instr(code, cfg, *block).is_some_and(|bc| matches!(bc, Bytecode::Branch(..)))
+                && cfg
+                    .successors(*block)
+                    .iter()
+                    .any(|succ| aborts(code, cfg, *succ))
+        })
+        .collect()
+}
+
+/// Returns true if the code starting at `block` aborts without branching.
+fn aborts(code: &[Bytecode], cfg: &StacklessControlFlowGraph, mut block: BlockId) -> bool {
+    let mut visited = BTreeSet::new();
+    while visited.insert(block) {
+        match last_instr(code, cfg, block) {
+            Some(Bytecode::Abort(..)) => return true,
+            Some(Bytecode::Branch(..) | Bytecode::Ret(..)) | None => return false,
+            Some(_) => match cfg.successors(block)[..] {
+                [succ] => block = succ,
+                _ => return false,
+            },
+        }
+    }
+    false
+}
+
+/// Returns true if every path from the entry of the function to the instruction at `offset`
+/// passes one of the `checks`.
+fn is_checked(
+    cfg: &StacklessControlFlowGraph,
+    checks: &BTreeSet<BlockId>,
+    offset: CodeOffset,
+) -> bool {
+    let unchecked = cfg.reachable_blocks(cfg.entry_block(), |from, _| !checks.contains(&from));
+    !unchecked.contains(&cfg.enclosing_block(offset))
+}
+
+fn last_instr<'a>(
+    code: &'a [Bytecode],
+    cfg: &StacklessControlFlowGraph,
+    block: BlockId,
+) -> Option<&'a Bytecode> {
+    let (_, upper) = cfg.instr_offset_bounds(block)?;
+    code.get(upper as usize)
+}
@@ -3,7 +3,15 @@
 
 //! This module holds utility functions for the Move linter.
 
-use move_model::ast::{ExpData, Operation};
+use move_model::{
+    ast::{ExpData, Operation},
+    model::{FunctionEnv, GlobalEnv, ModuleEnv},
+    ty::Type,
+};
+use move_stackless_bytecode::{
+    function_target::FunctionTarget,
+    stackless_bytecode::{AttrId, Bytecode, Operation as BytecodeOperation},
+};
 
 /// Returns `true` if two expressions represent the same simple access pattern.
 /// This compares nested `Select`, `Borrow`, and local variable references for structural equality.
@@ -33,3 +41,52 @@ pub(crate) fn is_simple_access_equal(expr1: &ExpData, expr2: &ExpData) -> bool {
         _ => false,
     }
 }
+
+/// Returns `true` if `module` is the Aptos framework (or Move stdlib) module named `name`.
+pub(crate) fn is_framework_module(module: &ModuleEnv, name: &str) -> bool {
+    let module_name = module.get_name();
+    *module_name.addr() == module.env.get_stdlib_address()
+        && module.symbol_pool().string(module_name.name()).as_str() == name
+}
+
+/// Returns `true` if `function` is one of the functions `names` of the Aptos framework (or
+/// Move stdlib) module named `module`.
+pub(crate) fn is_framework_function(function: &FunctionEnv, module: &str, names: &[&str]) -> bool {
+    is_framework_module(&function.module_env, module)
+        && names.contains(&function.get_name_str().as_str())
+}
+
+/// If `ty` is one of the structs `names` of the Aptos framework module named `module`, returns
+/// the name of the struct.
+pub(crate) fn framework_struct_name(
+    env: &GlobalEnv,
+    ty: &Type,
+    module: &str,
+    names: &[&str],
+) -> Option<String> {
+    let Type::Struct(mid, sid, _) = ty else {
+        return None;
+    };
+    let struct_env = env.get_struct(mid.qualified(*sid));
+    let name = struct_env.get_name().display(env.symbol_pool()).to_string();
+    (is_framework_module(&struct_env.module_env, module) && names.contains(&name.as_str()))
+        .then_some(name)
+}
+
+/// If `bc` calls a Move function, returns the attribute id of the call and the called function.
+pub(crate) fn called_function<'env>(
+    target: &FunctionTarget<'env>,
+    bc: &Bytecode,
+) -> Option<(AttrId, FunctionEnv<'env>)> {
+    match bc {
+        Bytecode::Call(id, _, BytecodeOperation::Function(mid, fid, _), _, _) => Some((
+            *id,
+            target
+                .func_env
+                .module_env
+                .env
+                .get_function(mid.qualified(*fid)),
+        )),
+        _ => None,
+    }
+}
@@ -0,0 +1,46 @@
+module aptos_framework::object {
+    struct ExtendRef has drop, store {
+        addr: address,
+    }
+
+    struct TransferRef has drop, store {
+        addr: address,
+    }
+}
+
+module 0xc0ffee::m {
+    use aptos_framework::object::{ExtendRef, TransferRef};
+
+    struct Refs has key {
+        extend_ref: ExtendRef,
+        transfer_ref: TransferRef,
+    }
+
+    public fun take_extend_ref_warn(addr: address): ExtendRef acquires Refs {
+        let Refs { extend_ref, transfer_ref: _ } = move_from<Refs>(addr);
+        extend_ref
+    }
+
+    public fun borrow_transfer_ref_warn(refs: &Refs): &TransferRef {
+        &refs.transfer_ref
+    }
+
+    public fun take_refs_warn(addr: address): (ExtendRef, TransferRef) acquires Refs {
+        let Refs { extend_ref, transfer_ref } = move_from<Refs>(addr);
+        (extend_ref, transfer_ref)
+    }
+
+    public(friend) fun take_extend_ref_friend_no_warn(addr: address): ExtendRef acquires Refs {
+        let Refs { extend_ref, transfer_ref: _ } = move_from<Refs>(addr);
+        extend_ref
+    }
+
+    public fun refs_exist_no_warn(addr: address): bool {
+        exists<Refs>(addr)
+    }
+
+    #[lint::skip(exposed_object_refs)]
+    public fun borrow_extend_ref_skipped(refs: &Refs): &ExtendRef {
+        &refs.extend_ref
+    }
+}
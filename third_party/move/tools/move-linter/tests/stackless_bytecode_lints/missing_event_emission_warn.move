@@ -0,0 +1,80 @@
+module aptos_framework::event {
+    native public fun emit<T: store + drop>(msg: T);
+}
+
+module 0xc0ffee::registry {
+    use aptos_framework::event;
+
+    struct Entry has key {}
+
+    struct Registered has drop, store {
+        addr: address,
+    }
+
+    public fun register(account: &signer) {
+        move_to(account, Entry {});
+    }
+
+    public fun register_with_event(account: &signer, addr: address) {
+        move_to(account, Entry {});
+        event::emit(Registered { addr });
+    }
+}
+
+module 0xc0ffee::m {
+    use 0xc0ffee::registry;
+    use aptos_framework::event;
+
+    struct Counter has key {
+        value: u64,
+    }
+
+    struct Incremented has drop, store {
+        value: u64,
+    }
+
+    public entry fun create_warn(account: &signer) {
+        move_to(account, Counter { value: 0 });
+    }
+
+    public entry fun increment_warn(addr: address) acquires Counter {
+        increment(addr);
+    }
+
+    public entry fun increment_with_event_no_warn(addr: address) acquires Counter {
+        let value = increment(addr);
+        event::emit(Incremented { value });
+    }
+
+    public entry fun increment_with_helper_event_no_warn(addr: address) acquires Counter {
+        increment(addr);
+        emit_incremented(addr);
+    }
+
+    public entry fun register_warn(account: &signer) {
+        registry::register(account);
+    }
+
+    public entry fun register_with_event_no_warn(account: &signer, addr: address) {
+        registry::register_with_event(account, addr);
+    }
+
+    entry fun read_no_warn(addr: address) acquires Counter {
+        assert!(borrow_global<Counter>(addr).value != 0, 0);
+    }
+
+    #[lint::skip(missing_event_emission)]
+    public entry fun reset_skipped(addr: address) acquires Counter {
+        borrow_global_mut<Counter>(addr).value = 0;
+    }
+
+    fun increment(addr: address): u64 acquires Counter {
+        let counter = borrow_global_mut<Counter>(addr);
+        counter.value = counter.value + 1;
+        counter.value
+    }
+
+    fun emit_incremented(addr: address) acquires Counter {
+        event::emit(Incremented { value: borrow_global<Counter>(addr).value });
+    }
+}
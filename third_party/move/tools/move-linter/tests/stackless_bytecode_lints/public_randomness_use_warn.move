@@ -0,0 +1,33 @@
+module aptos_framework::randomness {
+    native public fun u64_integer(): u64;
+
+    native public fun u64_range(min_incl: u64, max_excl: u64): u64;
+}
+
+module 0xc0ffee::m {
+    use aptos_framework::randomness;
+
+    public fun roll_warn(): u64 {
+        randomness::u64_range(0, 6)
+    }
+
+    public entry fun public_entry_roll_warn() {
+        let roll = randomness::u64_integer();
+        let other = randomness::u64_integer();
+        assert!(roll != other, 0);
+    }
+
+    entry fun private_entry_roll_no_warn() {
+        let value = randomness::u64_integer();
+        assert!(value != 0, 0);
+    }
+
+    public(friend) fun friend_roll_no_warn(): u64 {
+        randomness::u64_integer()
+    }
+
+    #[lint::skip(public_randomness_use)]
+    public fun roll_skipped(): u64 {
+        randomness::u64_integer()
+    }
+}
@@ -0,0 +1,68 @@
+module 0xc0ffee::m {
+    use std::vector;
+
+    const MAX_AMOUNTS: u64 = 100;
+
+    struct Registry has key {
+        members: vector<address>,
+    }
+
+    entry fun sum_warn(amounts: vector<u64>) {
+        let total = 0;
+        let i = 0;
+        let len = vector::length(&amounts);
+        while (i < len) {
+            total = total + *vector::borrow(&amounts, i);
+            i = i + 1;
+        };
+        assert!(total != 0, 0);
+    }
+
+    entry fun sum_bounded_no_warn(amounts: vector<u64>) {
+        let len = vector::length(&amounts);
+        assert!(len <= MAX_AMOUNTS, 1);
+        let total = 0;
+        let i = 0;
+        while (i < len) {
+            total = total + *vector::borrow(&amounts, i);
+            i = i + 1;
+        };
+        assert!(total != 0, 0);
+    }
+
+    entry fun count_others_warn(addr: address) acquires Registry {
+        let members = &borrow_global<Registry>(addr).members;
+        let count = 0;
+        let i = 0;
+        while (i < vector::length(members)) {
+            if (*vector::borrow(members, i) != addr) {
+                count = count + 1;
+            };
+            i = i + 1;
+        };
+        assert!(count != 0, 0);
+    }
+
+    public fun sum_public_no_warn(amounts: vector<u64>): u64 {
+        let total = 0;
+        let i = 0;
+        let len = vector::length(&amounts);
+        while (i < len) {
+            total = total + *vector::borrow(&amounts, i);
+            i = i + 1;
+        };
+        total
+    }
+
+    #[lint::skip(unbounded_vector_iteration)]
+    entry fun sum_skipped(amounts: vector<u64>) {
+        let total = 0;
+        let i = 0;
+        let len = vector::length(&amounts);
+        while (i < len) {
+            total = total + *vector::borrow(&amounts, i);
+            i = i + 1;
+        };
+        assert!(total != 0, 0);
+    }
+}
@@ -0,0 +1,65 @@
+module aptos_framework::coin {
+    native public fun transfer<CoinType>(from: &signer, to: address, amount: u64);
+}
+
+module aptos_framework::aptos_account {
+    native public fun transfer(source: &signer, to: address, amount: u64);
+}
+
+module 0xc0ffee::m {
+    use aptos_framework::aptos_account;
+    use aptos_framework::coin;
+
+    const E_ZERO_AMOUNT: u64 = 1;
+
+    struct MyCoin {}
+
+    public fun pay_warn(user: &signer, to: address, amount: u64) {
+        coin::transfer<MyCoin>(user, to, amount);
+    }
+
+    public fun pay_twice_warn(user: &signer, to: address, amount: u64) {
+        let payer = user;
+        aptos_account::transfer(payer, to, amount);
+        aptos_account::transfer(payer, @0xcafe, amount);
+    }
+
+    public fun pay_checked_no_warn(user: &signer, to: address, amount: u64) {
+        assert!(amount != 0, E_ZERO_AMOUNT);
+        coin::transfer<MyCoin>(user, to, amount);
+    }
+
+    public fun pay_if_nonzero_no_warn(user: &signer, to: address, amount: u64) {
+        if (amount == 0) abort E_ZERO_AMOUNT;
+        if (amount > 100) {
+            coin::transfer<MyCoin>(user, to, amount);
+        } else {
+            aptos_account::transfer(user, to, amount);
+        }
+    }
+
+    public fun pay_checked_on_one_path_warn(user: &signer, to: address, amount: u64, check: bool) {
+        if (check) {
+            assert!(amount != 0, E_ZERO_AMOUNT);
+        };
+        coin::transfer<MyCoin>(user, to, amount);
+    }
+
+    public fun pay_checked_after_warn(user: &signer, to: address, amount: u64) {
+        coin::transfer<MyCoin>(user, to, amount);
+        assert!(amount != 0, E_ZERO_AMOUNT);
+    }
+
+    public entry fun pay_entry_no_warn(user: &signer, to: address, amount: u64) {
+        coin::transfer<MyCoin>(user, to, amount);
+    }
+
+    public(friend) fun pay_friend_no_warn(user: &signer, to: address, amount: u64) {
+        coin::transfer<MyCoin>(user, to, amount);
+    }
+
+    #[lint::skip(unchecked_fund_transfer)]
+    public fun pay_skipped(user: &signer, to: address, amount: u64) {
+        coin::transfer<MyCoin>(user, to, amount);
+    }
+}
@@ -0,0 +1,28 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! # Move Language Server
+//!
+//! A [Language Server Protocol](https://microsoft.github.io/language-server-protocol/) server
+//! for Move packages. Packages are checked with the front end of the v2 compiler and the
+//! Move linter, and the diagnostics of both are published to the client. Hover, go to
+//! definition, find references and completion of module members and struct fields are served
+//! from an index of the model of the last check without errors.
+//!
+//! Documents are synchronized in full. The text of open documents is checked in place of the
+//! files on disk, and a package is only checked again after one of its files changed, once
+//! the client has no more pending messages (or needs the result of the check).
+
+mod protocol;
+mod server;
+mod symbols;
+mod text;
+mod workspace;
+
+use anyhow::Result;
+use std::io::{self, BufReader};
+
+/// Serves a client over stdin and stdout, until the client exits
+pub fn run_stdio() -> Result<()> {
+    server::serve(BufReader::new(io::stdin()), io::stdout())
+}
@@ -0,0 +1,6 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+fn main() -> anyhow::Result<()> {
+    move_lsp::run_stdio()
+}
@@ -0,0 +1,195 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Wire format of the Language Server Protocol. Every message is a JSON-RPC 2.0 object,
+//! preceded by a `Content-Length` header and an empty line.
+
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{
+    io::{BufRead, Write},
+    path::{Path, PathBuf},
+};
+use url::Url;
+
+const CONTENT_LENGTH_HEADER: &str = "Content-Length:";
+
+/// The params of the request are invalid
+pub const INVALID_PARAMS: i64 = -32602;
+/// The method of the request is not supported
+pub const METHOD_NOT_FOUND: i64 = -32601;
+/// The request is valid, but could not be served
+pub const REQUEST_FAILED: i64 = -32803;
+
+/// A request or notification sent by the client. Notifications have no id.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Message {
+    #[serde(default)]
+    pub id: Option<Value>,
+    pub method: String,
+    #[serde(default)]
+    pub params: Value,
+}
+
+/// A position in a text document, as a zero-based line and a zero-based offset
+/// into the line in UTF-16 code units.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Position {
+    pub line: u32,
+    pub character: u32,
+}
+
+/// A range in a text document, the end is exclusive.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Range {
+    pub start: Position,
+    pub end: Position,
+}
+
+/// Returns the path of a `file` URI
+pub fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    Url::parse(uri).ok()?.to_file_path().ok()
+}
+
+/// Returns the `file` URI of an absolute path
+pub fn path_to_uri(path: &Path) -> String {
+    Url::from_file_path(path)
+        .map(|url| url.to_string())
+        .unwrap_or_else(|_| path.to_string_lossy().to_string())
+}
+
+/// Reads the next message from the given reader. Returns None if the
+/// connection was closed before a new message started.
+pub fn read_message<R: BufRead>(reader: &mut R) -> Result<Option<Value>> {
+    let mut content_length = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            if content_length.is_none() {
+                return Ok(None);
+            }
+            bail!("Connection closed while reading message headers");
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            // Headers are only terminated once a content length was received
+            if content_length.is_some() {
+                break;
+            }
+            continue;
+        }
+        if let Some(length) = line.strip_prefix(CONTENT_LENGTH_HEADER) {
+            content_length = Some(length.trim().parse::<usize>()?);
+        }
+    }
+
+    let content_length =
+        content_length.ok_or_else(|| anyhow!("Message is missing a content length"))?;
+    let mut content = vec![0; content_length];
+    reader.read_exact(&mut content)?;
+    Ok(Some(serde_json::from_slice(&content)?))
+}
+
+/// Writes responses and notifications to the client
+pub struct MessageWriter<W> {
+    writer: W,
+}
+
+impl<W: Write> MessageWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    /// Sends a successful response to the request with the given id
+    pub fn send_response(&mut self, id: &Value, result: Value) -> Result<()> {
+        self.send(json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "result": result,
+        }))
+    }
+
+    /// Sends a failed response to the request with the given id
+    pub fn send_error_response(&mut self, id: &Value, code: i64, message: &str) -> Result<()> {
+        self.send(json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": code, "message": message },
+        }))
+    }
+
+    /// Sends a notification with the given params
+    pub fn send_notification(&mut self, method: &str, params: Value) -> Result<()> {
+        self.send(json!({
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": params,
+        }))
+    }
+
+    fn send(&mut self, message: Value) -> Result<()> {
+        let content = serde_json::to_vec(&message)?;
+        write!(
+            self.writer,
+            "{} {}\r\n\r\n",
+            CONTENT_LENGTH_HEADER,
+            content.len()
+        )?;
+        self.writer.write_all(&content)?;
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    #[cfg(test)]
+    pub(crate) fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_message_round_trip() {
+        // Write a response, an error and a notification
+        let mut writer = MessageWriter::new(vec![]);
+        writer.send_response(&json!(1), json!(null)).unwrap();
+        writer
+            .send_error_response(&json!("two"), METHOD_NOT_FOUND, "Unsupported method")
+            .unwrap();
+        writer
+            .send_notification("window/logMessage", json!({ "message": "hello" }))
+            .unwrap();
+
+        // Read the messages back
+        let mut reader = Cursor::new(writer.into_inner());
+        let response = read_message(&mut reader).unwrap().unwrap();
+        assert_eq!(response["id"], 1);
+        assert!(response["result"].is_null());
+        let error = read_message(&mut reader).unwrap().unwrap();
+        assert_eq!(error["id"], "two");
+        assert_eq!(error["error"]["code"], METHOD_NOT_FOUND);
+        let notification = read_message(&mut reader).unwrap().unwrap();
+        assert_eq!(notification["method"], "window/logMessage");
+        assert_eq!(notification["params"]["message"], "hello");
+
+        // Verify the end of the stream is detected
+        assert!(read_message(&mut reader).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_read_notification() {
+        let content = r#"{"jsonrpc":"2.0","method":"initialized"}"#;
+        let message = format!("Content-Length: {}\r\n\r\n{}", content.len(), content);
+        let message = read_message(&mut Cursor::new(message)).unwrap().unwrap();
+
+        // Verify a missing id and params are accepted
+        let message: Message = serde_json::from_value(message).unwrap();
+        assert_eq!(message.method, "initialized");
+        assert!(message.id.is_none());
+        assert!(message.params.is_null());
+    }
+}
@@ -0,0 +1,411 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    protocol::{
+        path_to_uri, read_message, uri_to_path, Message, MessageWriter, Position, INVALID_PARAMS,
+        METHOD_NOT_FOUND, REQUEST_FAILED,
+    },
+    symbols::{completion_context, CompletionContext, SymbolLocation},
+    text::SourceText,
+    workspace::Workspace,
+};
+use anyhow::Result;
+use serde_json::{json, Value};
+use std::{
+    io::{BufRead, Write},
+    path::PathBuf,
+    sync::mpsc,
+    thread,
+};
+
+/// The error of a request, with the error code of the protocol
+type RequestError = (i64, String);
+
+/// Serves the client's requests until it exits (or the connection is closed). Messages are
+/// read on a background thread, and packages are checked once no more messages are pending,
+/// or when a request needs the result of a check.
+pub fn serve<R, W>(reader: R, writer: W) -> Result<()>
+where
+    R: BufRead + Send + 'static,
+    W: Write,
+{
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut reader = reader;
+        while let Ok(Some(message)) = read_message(&mut reader) {
+            if sender.send(message).is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut server = Server {
+        workspace: Workspace::new()?,
+        writer: MessageWriter::new(writer),
+    };
+    while let Ok(message) = receiver.recv() {
+        let mut next = Some(message);
+        while let Some(message) = next {
+            if !server.handle_message(message)? {
+                return Ok(());
+            }
+            next = receiver.try_recv().ok();
+        }
+        server.check_packages()?;
+    }
+    Ok(())
+}
+
+struct Server<W> {
+    workspace: Workspace,
+    writer: MessageWriter<W>,
+}
+
+impl<W: Write> Server<W> {
+    /// Handles a request or notification. Returns false once the client asked the server
+    /// to exit.
+    fn handle_message(&mut self, message: Value) -> Result<bool> {
+        // Ignore anything that isn't a request or notification (e.g., responses)
+        let Ok(message) = serde_json::from_value::<Message>(message) else {
+            return Ok(true);
+        };
+        let Some(id) = message.id.clone() else {
+            self.handle_notification(&message);
+            return Ok(message.method != "exit");
+        };
+        match self.handle_request(&message) {
+            Ok(result) => self.writer.send_response(&id, result)?,
+            Err((code, error)) => self.writer.send_error_response(&id, code, &error)?,
+        }
+        Ok(true)
+    }
+
+    fn handle_request(&mut self, message: &Message) -> Result<Value, RequestError> {
+        let params = &message.params;
+        match message.method.as_str() {
+            "initialize" => Ok(json!({
+                "capabilities": {
+                    "textDocumentSync": { "openClose": true, "change": 1, "save": true },
+                    "hoverProvider": true,
+                    "definitionProvider": true,
+                    "referencesProvider": true,
+                    "completionProvider": { "triggerCharacters": [".", ":"] },
+                },
+                "serverInfo": { "name": "move-lsp", "version": env!("CARGO_PKG_VERSION") },
+            })),
+            "shutdown" => Ok(Value::Null),
+            "textDocument/hover" => {
+                let (path, position) = document_position(params)?;
+                self.check_packages().map_err(request_failed)?;
+                Ok(self
+                    .workspace
+                    .index(&path)
+                    .and_then(|index| index.hover(&path, position))
+                    .map_or(Value::Null, |(range, contents)| {
+                        json!({
+                            "contents": { "kind": "markdown", "value": contents },
+                            "range": range,
+                        })
+                    }))
+            },
+            "textDocument/definition" => {
+                let (path, position) = document_position(params)?;
+                self.check_packages().map_err(request_failed)?;
+                Ok(self
+                    .workspace
+                    .index(&path)
+                    .and_then(|index| index.definition(&path, position))
+                    .map_or(Value::Null, |location| to_lsp_location(&location)))
+            },
+            "textDocument/references" => {
+                let (path, position) = document_position(params)?;
+                let include_declaration = params["context"]["includeDeclaration"]
+                    .as_bool()
+                    .unwrap_or(true);
+                self.check_packages().map_err(request_failed)?;
+                let locations = self
+                    .workspace
+                    .index(&path)
+                    .map(|index| index.references(&path, position, include_declaration))
+                    .unwrap_or_default();
+                Ok(Value::Array(
+                    locations.iter().map(to_lsp_location).collect(),
+                ))
+            },
+            "textDocument/completion" => {
+                let (path, position) = document_position(params)?;
+                self.check_packages().map_err(request_failed)?;
+                // The context is taken from the current text, which may not check
+                let Some(text) = self.workspace.document_text(&path) else {
+                    return Ok(json!([]));
+                };
+                let text = SourceText::new(text);
+                let line_start = text.offset(Position {
+                    line: position.line,
+                    character: 0,
+                });
+                let line_prefix = &text.text()[line_start..text.offset(position)];
+                let (Some(context), Some(index)) =
+                    (completion_context(line_prefix), self.workspace.index(&path))
+                else {
+                    return Ok(json!([]));
+                };
+                let items = match context {
+                    CompletionContext::ModuleMembers(module) => {
+                        index.module_members(&path, position, &module)
+                    },
+                    CompletionContext::Fields(access_path) => {
+                        index.fields(&path, position, &access_path)
+                    },
+                };
+                Ok(items
+                    .into_iter()
+                    .map(|item| {
+                        json!({
+                            "label": item.label,
+                            "kind": item.kind as u8,
+                            "detail": item.detail,
+                        })
+                    })
+                    .collect())
+            },
+            _ => Err((METHOD_NOT_FOUND, "Unsupported method".to_string())),
+        }
+    }
+
+    fn handle_notification(&mut self, message: &Message) {
+        let params = &message.params;
+        let path = params["textDocument"]["uri"].as_str().and_then(uri_to_path);
+        match (message.method.as_str(), path) {
+            ("textDocument/didOpen", Some(path)) => {
+                if let Some(text) = params["textDocument"]["text"].as_str() {
+                    self.workspace.set_document(path, text.to_string());
+                }
+            },
+            ("textDocument/didChange", Some(path)) => {
+                // The full text is synchronized, so the last change is the new text
+                let text = params["contentChanges"]
+                    .as_array()
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change["text"].as_str());
+                if let Some(text) = text {
+                    self.workspace.set_document(path, text.to_string());
+                }
+            },
+            ("textDocument/didSave", Some(path)) => self.workspace.save_document(&path),
+            ("textDocument/didClose", Some(path)) => self.workspace.close_document(&path),
+            _ => {},
+        }
+    }
+
+    /// Checks the packages which changed, and publishes their diagnostics
+    fn check_packages(&mut self) -> Result<()> {
+        for result in self.workspace.check_packages() {
+            match result {
+                Ok(diagnostics) => {
+                    for (path, diagnostics) in diagnostics {
+                        self.writer.send_notification(
+                            "textDocument/publishDiagnostics",
+                            json!({ "uri": path_to_uri(&path), "diagnostics": diagnostics }),
+                        )?;
+                    }
+                },
+                Err(err) => {
+                    self.writer.send_notification(
+                        "window/showMessage",
+                        json!({ "type": 1, "message": format!("{:#}", err) }),
+                    )?;
+                },
+            }
+        }
+        Ok(())
+    }
+}
+
+fn document_position(params: &Value) -> Result<(PathBuf, Position), RequestError> {
+    let path = params["textDocument"]["uri"]
+        .as_str()
+        .and_then(uri_to_path)
+        .ok_or_else(|| (INVALID_PARAMS, "Expected a file URI".to_string()))?;
+    let position = serde_json::from_value(params["position"].clone())
+        .map_err(|err| (INVALID_PARAMS, err.to_string()))?;
+    Ok((path, position))
+}
+
+fn to_lsp_location(location: &SymbolLocation) -> Value {
+    json!({ "uri": path_to_uri(&location.path), "range": location.range })
+}
+
+fn request_failed(err: anyhow::Error) -> RequestError {
+    (REQUEST_FAILED, err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs, io::Cursor};
+
+    const MANIFEST: &str = r#"
+[package]
+name = "Test"
+version = "0.0.0"
+"#;
+
+    const MODULE: &str = r#"module 0x42::m {
+    struct Coin has drop {
+        value: u64,
+    }
+
+    public fun make(value: u64): Coin {
+        Coin { value }
+    }
+
+    public fun get(): u64 {
+        let coin = make(1);
+        coin.value
+    }
+}
+"#;
+
+    fn request(id: u64, method: &str, params: Value) -> Value {
+        json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params })
+    }
+
+    fn notification(method: &str, params: Value) -> Value {
+        json!({ "jsonrpc": "2.0", "method": method, "params": params })
+    }
+
+    fn position_params(uri: &str, line: u32, character: u32) -> Value {
+        json!({
+            "textDocument": { "uri": uri },
+            "position": { "line": line, "character": character },
+        })
+    }
+
+    #[test]
+    fn test_session() {
+        // Create a package with a single module
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        fs::create_dir(root.join("sources")).unwrap();
+        fs::write(root.join("Move.toml"), MANIFEST).unwrap();
+        let path = root.join("sources").join("m.move");
+        fs::write(&path, MODULE).unwrap();
+        let uri = path_to_uri(&path);
+
+        // Open the module, navigate it, and complete after breaking it
+        let changed = MODULE.replace("        coin.value\n", "        coin.\n        Self::\n");
+        let messages = [
+            request(1, "initialize", json!({ "capabilities": {} })),
+            notification("initialized", json!({})),
+            notification(
+                "textDocument/didOpen",
+                json!({
+                    "textDocument": { "uri": uri, "languageId": "move", "version": 1, "text": MODULE },
+                }),
+            ),
+            request(2, "textDocument/hover", position_params(&uri, 11, 9)),
+            request(3, "textDocument/definition", position_params(&uri, 10, 20)),
+            request(
+                4,
+                "textDocument/references",
+                json!({
+                    "textDocument": { "uri": uri },
+                    "position": { "line": 5, "character": 16 },
+                    "context": { "includeDeclaration": true },
+                }),
+            ),
+            request(5, "textDocument/hover", position_params(&uri, 11, 14)),
+            notification(
+                "textDocument/didChange",
+                json!({
+                    "textDocument": { "uri": uri, "version": 2 },
+                    "contentChanges": [{ "text": changed }],
+                }),
+            ),
+            request(6, "textDocument/completion", position_params(&uri, 11, 13)),
+            request(7, "textDocument/completion", position_params(&uri, 12, 14)),
+            request(8, "textDocument/unknown", json!({})),
+            request(9, "shutdown", Value::Null),
+            notification("exit", Value::Null),
+        ];
+        let mut input = vec![];
+        for message in messages {
+            let content = message.to_string();
+            write!(
+                input,
+                "Content-Length: {}\r\n\r\n{}",
+                content.len(),
+                content
+            )
+            .unwrap();
+        }
+        let mut output = vec![];
+        serve(Cursor::new(input), &mut output).unwrap();
+
+        // Collect the responses by id, and the published diagnostics
+        let mut reader = Cursor::new(output);
+        let mut responses = std::collections::BTreeMap::new();
+        let mut diagnostics = vec![];
+        while let Some(message) = read_message(&mut reader).unwrap() {
+            if let Some(id) = message["id"].as_u64() {
+                responses.insert(id, message);
+            } else if message["method"] == "textDocument/publishDiagnostics" {
+                assert_eq!(message["params"]["uri"], uri);
+                diagnostics.push(message["params"]["diagnostics"].clone());
+            }
+        }
+        assert_eq!(
+            responses[&1]["result"]["capabilities"]["hoverProvider"],
+            true
+        );
+
+        // Verify the type of the local is shown
+        let hover = responses[&2]["result"]["contents"]["value"]
+            .as_str()
+            .unwrap();
+        assert!(
+            hover.contains("coin: ") && hover.contains("Coin"),
+            "{}",
+            hover
+        );
+
+        // Verify the call navigates to the declaration of the function
+        let definition = &responses[&3]["result"];
+        assert_eq!(definition["uri"], uri);
+        assert_eq!(
+            definition["range"]["start"],
+            json!({ "line": 5, "character": 15 })
+        );
+
+        // Verify the declaration and the call are found
+        assert_eq!(responses[&4]["result"].as_array().unwrap().len(), 2);
+
+        // Verify the type of the field is shown
+        let hover = responses[&5]["result"]["contents"]["value"]
+            .as_str()
+            .unwrap();
+        assert!(hover.contains("value: u64"), "{}", hover);
+
+        // Verify the broken module was reported, and members are completed from the last
+        // successful check
+        let errors = diagnostics.last().unwrap().as_array().unwrap();
+        assert!(errors.iter().any(|diagnostic| diagnostic["severity"] == 1));
+        let labels = |id: u64| -> Vec<String> {
+            responses[&id]["result"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|item| item["label"].as_str().unwrap().to_string())
+                .collect()
+        };
+        assert_eq!(labels(6), vec!["value"]);
+        let members = labels(7);
+        assert!(members.contains(&"make".to_string()) && members.contains(&"Coin".to_string()));
+
+        // Verify unknown methods are rejected
+        assert_eq!(responses[&8]["error"]["code"], METHOD_NOT_FOUND);
+        assert!(responses[&9]["result"].is_null());
+    }
+}
@@ -0,0 +1,853 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! An index of the symbols of a checked package, for navigation and completion.
+//!
+//! The index is built from the model right after type checking, before function bodies are
+//! rewritten (e.g., by inlining). It owns all of its data, so that it outlives the model and
+//! keeps serving requests while the package is edited and does not check.
+
+use crate::{
+    protocol::{Position, Range},
+    text::SourceText,
+};
+use codespan::FileId;
+use move_model::{
+    ast::{ExpData, Operation, Pattern},
+    model::{FunctionEnv, GlobalEnv, Loc, ModuleEnv, NodeId, Parameter, StructEnv, Visibility},
+    symbol::Symbol,
+    ty::Type,
+};
+use std::{
+    collections::BTreeMap,
+    ops,
+    path::{Path, PathBuf},
+};
+
+/// A symbol which can be referenced from code
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum SymbolId {
+    Module(String),
+    Function(String),
+    Struct(String),
+    /// A field, identified by the struct and the field name
+    Field(String, String),
+    Constant(String),
+    /// A local or parameter, identified by the file and offset of its declaration
+    Local(PathBuf, usize),
+}
+
+/// A range in a source file
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SymbolLocation {
+    pub path: PathBuf,
+    pub range: Range,
+}
+
+/// The kind of a completion item, with the values of the `CompletionItemKind` of the protocol
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ItemKind {
+    Function = 3,
+    Field = 5,
+    Constant = 21,
+    Struct = 22,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CompletionItem {
+    pub label: String,
+    pub kind: ItemKind,
+    pub detail: String,
+}
+
+/// What is completed at a position, derived from the text of the line before the position
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CompletionContext {
+    /// Members of the module with the given name, after `module::`
+    ModuleMembers(String),
+    /// Fields of the value of a path of a local and fields, after `local.field.`
+    Fields(Vec<String>),
+}
+
+struct Occurrence {
+    span: ops::Range<usize>,
+    symbol: SymbolId,
+    /// The hover text, if it differs from the one of the declaration
+    hover: Option<String>,
+}
+
+struct Definition {
+    path: PathBuf,
+    span: ops::Range<usize>,
+    hover: String,
+}
+
+struct FunctionScope {
+    span: ops::Range<usize>,
+    /// The locals and parameters in order of declaration, with the struct of their type
+    locals: Vec<(String, Option<String>)>,
+}
+
+struct FileSymbols {
+    /// The text the file was checked with
+    text: SourceText,
+    occurrences: Vec<Occurrence>,
+    scopes: Vec<FunctionScope>,
+    /// The spans of the modules in the file, with their full names
+    modules: Vec<(ops::Range<usize>, String)>,
+}
+
+struct ModuleSymbols {
+    name: String,
+    /// The members of the module, and whether they are accessible from other modules
+    members: Vec<(CompletionItem, bool)>,
+}
+
+#[derive(Default)]
+pub struct SymbolIndex {
+    files: BTreeMap<PathBuf, FileSymbols>,
+    definitions: BTreeMap<SymbolId, Definition>,
+    modules: BTreeMap<String, ModuleSymbols>,
+    /// The fields of structs, with the struct of their type
+    structs: BTreeMap<String, Vec<(CompletionItem, Option<String>)>>,
+}
+
+impl SymbolIndex {
+    /// Indexes all modules in the model. `file_path` maps the file names of the model to
+    /// the paths of the files as known to the client.
+    pub fn new(env: &GlobalEnv, file_path: impl Fn(&str) -> PathBuf) -> Self {
+        let mut builder = IndexBuilder {
+            env,
+            file_path,
+            paths: BTreeMap::new(),
+            index: SymbolIndex::default(),
+        };
+        for module_env in env.get_modules() {
+            builder.add_module(&module_env);
+        }
+        builder.index
+    }
+
+    pub fn contains_file(&self, path: &Path) -> bool {
+        self.files.contains_key(path)
+    }
+
+    /// Returns the hover text of the symbol at the given position, and the range of its
+    /// occurrence.
+    pub fn hover(&self, path: &Path, position: Position) -> Option<(Range, String)> {
+        let file = self.files.get(path)?;
+        let occurrence = file.occurrence_at(position)?;
+        let hover = match &occurrence.hover {
+            Some(hover) => hover.clone(),
+            None => self.definitions.get(&occurrence.symbol)?.hover.clone(),
+        };
+        Some((file.text.range(occurrence.span.clone()), hover))
+    }
+
+    /// Returns the location of the declaration of the symbol at the given position
+    pub fn definition(&self, path: &Path, position: Position) -> Option<SymbolLocation> {
+        let occurrence = self.files.get(path)?.occurrence_at(position)?;
+        self.definitions
+            .get(&occurrence.symbol)
+            .and_then(|definition| self.location(&definition.path, definition.span.clone()))
+    }
+
+    /// Returns the locations of all occurrences of the symbol at the given position,
+    /// optionally including its declaration.
+    pub fn references(
+        &self,
+        path: &Path,
+        position: Position,
+        include_declaration: bool,
+    ) -> Vec<SymbolLocation> {
+        let Some(symbol) = self
+            .files
+            .get(path)
+            .and_then(|file| file.occurrence_at(position))
+            .map(|occurrence| &occurrence.symbol)
+        else {
+            return vec![];
+        };
+        let definition = self.definitions.get(symbol);
+        self.files
+            .iter()
+            .flat_map(|(path, file)| {
+                file.occurrences
+                    .iter()
+                    .filter(move |occurrence| {
+                        &occurrence.symbol == symbol
+                            && (include_declaration
+                                || !definition.is_some_and(|definition| {
+                                    definition.path == *path && definition.span == occurrence.span
+                                }))
+                    })
+                    .map(move |occurrence| SymbolLocation {
+                        path: path.clone(),
+                        range: file.text.range(occurrence.span.clone()),
+                    })
+            })
+            .collect()
+    }
+
+    /// Returns the members of the module with the given name which are accessible at the
+    /// given position. `Self` refers to the module at the position.
+    pub fn module_members(
+        &self,
+        path: &Path,
+        position: Position,
+        module: &str,
+    ) -> Vec<CompletionItem> {
+        let current_module = self.files.get(path).and_then(|file| {
+            let offset = file.text.offset(position);
+            file.modules
+                .iter()
+                .find(|(span, _)| span.contains(&offset))
+                .map(|(_, name)| name.as_str())
+        });
+        self.modules
+            .iter()
+            .filter(|(full_name, symbols)| {
+                if module == "Self" {
+                    current_module == Some(full_name.as_str())
+                } else {
+                    symbols.name == module
+                }
+            })
+            .flat_map(|(full_name, symbols)| {
+                let is_current = current_module == Some(full_name.as_str());
+                symbols
+                    .members
+                    .iter()
+                    .filter(move |(_, accessible)| is_current || *accessible)
+                    .map(|(item, _)| item.clone())
+            })
+            .collect()
+    }
+
+    /// Returns the fields of the value at the end of the given path, which starts with a
+    /// local of the function at the given position.
+    pub fn fields(
+        &self,
+        path: &Path,
+        position: Position,
+        access_path: &[String],
+    ) -> Vec<CompletionItem> {
+        let Some((local, fields)) = access_path.split_first() else {
+            return vec![];
+        };
+        let Some(file) = self.files.get(path) else {
+            return vec![];
+        };
+        let offset = file.text.offset(position);
+        let mut struct_key = file
+            .scopes
+            .iter()
+            .find(|scope| scope.span.contains(&offset))
+            .and_then(|scope| scope.locals.iter().rev().find(|(name, _)| name == local))
+            .and_then(|(_, struct_key)| struct_key.as_ref());
+        for field in fields {
+            struct_key = struct_key
+                .and_then(|key| self.structs.get(key))
+                .and_then(|fields| fields.iter().find(|(item, _)| &item.label == field))
+                .and_then(|(_, struct_key)| struct_key.as_ref());
+        }
+        struct_key
+            .and_then(|key| self.structs.get(key))
+            .map(|fields| fields.iter().map(|(item, _)| item.clone()).collect())
+            .unwrap_or_default()
+    }
+
+    fn location(&self, path: &Path, span: ops::Range<usize>) -> Option<SymbolLocation> {
+        let file = self.files.get(path)?;
+        Some(SymbolLocation {
+            path: path.to_path_buf(),
+            range: file.text.range(span),
+        })
+    }
+}
+
+impl FileSymbols {
+    /// Returns the innermost occurrence at the given position. The position right after an
+    /// occurrence is part of it, so that a symbol can be found with the cursor behind it.
+    fn occurrence_at(&self, position: Position) -> Option<&Occurrence> {
+        let offset = self.text.offset(position);
+        self.occurrences
+            .iter()
+            .filter(|occurrence| occurrence.span.start <= offset && offset <= occurrence.span.end)
+            .min_by_key(|occurrence| occurrence.span.len())
+    }
+}
+
+/// Returns what is completed at the end of the given text, if anything
+pub fn completion_context(line_prefix: &str) -> Option<CompletionContext> {
+    // Skip the part of the identifier which was typed already
+    let rest = line_prefix.trim_end_matches(is_identifier_char);
+    if let Some(rest) = rest.strip_suffix("::") {
+        let module = last_identifier(rest)?;
+        return Some(CompletionContext::ModuleMembers(module.to_string()));
+    }
+    let mut rest = rest.strip_suffix('.')?;
+    let mut access_path = vec![];
+    loop {
+        let identifier = last_identifier(rest)?;
+        access_path.push(identifier.to_string());
+        rest = &rest[..rest.len() - identifier.len()];
+        match rest.strip_suffix('.') {
+            Some(prefix) => rest = prefix,
+            None => break,
+        }
+    }
+    access_path.reverse();
+    Some(CompletionContext::Fields(access_path))
+}
+
+fn is_identifier_char(ch: char) -> bool {
+    ch.is_ascii_alphanumeric() || ch == '_'
+}
+
+/// Returns the identifier at the end of the text, which must not start with a digit
+fn last_identifier(text: &str) -> Option<&str> {
+    let identifier = &text[text.trim_end_matches(is_identifier_char).len()..];
+    identifier
+        .starts_with(|ch: char| ch.is_ascii_alphabetic() || ch == '_')
+        .then_some(identifier)
+}
+
+/// Returns the span of the first occurrence of `name` as a whole word in the span, or the
+/// span itself if there is none.
+fn find_name(text: &str, span: ops::Range<usize>, name: &str) -> ops::Range<usize> {
+    let Some(source) = text.get(span.clone()) else {
+        return span;
+    };
+    source
+        .match_indices(name)
+        .find(|(offset, _)| {
+            let before = source[..*offset].chars().next_back();
+            let after = source[offset + name.len()..].chars().next();
+            !before.is_some_and(is_identifier_char) && !after.is_some_and(is_identifier_char)
+        })
+        .map(|(offset, _)| span.start + offset..span.start + offset + name.len())
+        .unwrap_or(span)
+}
+
+/// Returns the span of the name in an expression which starts with a (possibly qualified)
+/// name, e.g., a call or a pack, or ends with one after a dot, e.g., a field selection
+/// or a method call.
+fn name_span(text: &str, span: ops::Range<usize>) -> ops::Range<usize> {
+    let Some(source) = text.get(span.clone()) else {
+        return span;
+    };
+    let head = source
+        .split(['(', '<', '{', '['])
+        .next()
+        .unwrap_or_default()
+        .trim_end();
+    let start = head.rfind('.').map_or(0, |offset| offset + 1);
+    let name = head[start..].trim_start();
+    if name.is_empty() {
+        return span;
+    }
+    let start = span.start + head.len() - name.len();
+    start..start + name.len()
+}
+
+fn markdown(code: &str, doc: &str) -> String {
+    let doc = doc.trim();
+    if doc.is_empty() {
+        format!("```move\n{}\n```", code)
+    } else {
+        format!("```move\n{}\n```\n\n{}", code, doc)
+    }
+}
+
+/// A reference from a function body, which is resolved once all locals are known
+enum BodyReference {
+    Local(NodeId, Symbol),
+    Parameter(NodeId, usize),
+    Function(NodeId, String),
+    Struct(NodeId, String),
+    Field(NodeId, String, String),
+}
+
+struct IndexBuilder<'env, F> {
+    env: &'env GlobalEnv,
+    file_path: F,
+    paths: BTreeMap<FileId, PathBuf>,
+    index: SymbolIndex,
+}
+
+impl<F: Fn(&str) -> PathBuf> IndexBuilder<'_, F> {
+    fn file(&mut self, file_id: FileId) -> (PathBuf, &mut FileSymbols) {
+        let env = self.env;
+        let path = self
+            .paths
+            .entry(file_id)
+            .or_insert_with(|| (self.file_path)(&env.get_file(file_id).to_string_lossy()))
+            .clone();
+        let file = self
+            .index
+            .files
+            .entry(path.clone())
+            .or_insert_with(|| FileSymbols {
+                text: SourceText::new(env.get_file_source(file_id).to_string()),
+                occurrences: vec![],
+                scopes: vec![],
+                modules: vec![],
+            });
+        (path, file)
+    }
+
+    fn add_occurrence(
+        &mut self,
+        symbol: SymbolId,
+        loc: &Loc,
+        span: impl FnOnce(&str, ops::Range<usize>) -> ops::Range<usize>,
+        hover: Option<String>,
+    ) -> (PathBuf, ops::Range<usize>) {
+        let (path, file) = self.file(loc.file_id());
+        let span = span(
+            file.text.text(),
+            loc.span().start().to_usize()..loc.span().end().to_usize(),
+        );
+        file.occurrences.push(Occurrence {
+            span: span.clone(),
+            symbol,
+            hover,
+        });
+        (path, span)
+    }
+
+    fn add_definition(&mut self, symbol: SymbolId, loc: &Loc, name: &str, hover: String) {
+        let (path, span) = self.add_occurrence(
+            symbol.clone(),
+            loc,
+            |text, span| find_name(text, span, name),
+            None,
+        );
+        // Fields of variants are declared once per variant, the first one is the definition
+        self.index
+            .definitions
+            .entry(symbol)
+            .or_insert(Definition { path, span, hover });
+    }
+
+    fn add_module(&mut self, module_env: &ModuleEnv) {
+        let full_name = module_env.get_full_name_str();
+        let name = module_env
+            .get_name()
+            .name()
+            .display(module_env.symbol_pool())
+            .to_string();
+        let loc = module_env.get_loc();
+        if !module_env.is_script_module() {
+            // The name of the module is the last one before its body, after the address
+            let hover = markdown(&format!("module {}", full_name), module_env.get_doc());
+            let (path, file) = self.file(loc.file_id());
+            let span = loc.span().start().to_usize()..loc.span().end().to_usize();
+            file.modules.push((span.clone(), full_name.clone()));
+            let header = file.text.text().get(span.clone()).unwrap_or_default();
+            let header = &header[..header.find('{').unwrap_or(header.len())];
+            let name_span = header.rfind(name.as_str()).map_or(span.clone(), |offset| {
+                span.start + offset..span.start + offset + name.len()
+            });
+            file.occurrences.push(Occurrence {
+                span: name_span.clone(),
+                symbol: SymbolId::Module(full_name.clone()),
+                hover: None,
+            });
+            self.index
+                .definitions
+                .insert(SymbolId::Module(full_name.clone()), Definition {
+                    path,
+                    span: name_span,
+                    hover,
+                });
+        }
+
+        let mut members = vec![];
+        for struct_env in module_env.get_structs() {
+            members.push((self.add_struct(&struct_env), true));
+        }
+        for fun_env in module_env.get_functions() {
+            let fun_name = fun_env.get_simple_name_string().to_string();
+            let header = fun_env.get_header_string();
+            self.add_definition(
+                SymbolId::Function(fun_env.get_full_name_with_address()),
+                &fun_env.get_id_loc(),
+                &fun_name,
+                markdown(&header, fun_env.get_doc()),
+            );
+            self.add_function_body(&fun_env);
+            members.push((
+                CompletionItem {
+                    label: fun_name,
+                    kind: ItemKind::Function,
+                    detail: header,
+                },
+                fun_env.visibility() != Visibility::Private,
+            ));
+        }
+        for const_env in module_env.get_named_constants() {
+            let const_name = const_env
+                .get_name()
+                .display(module_env.symbol_pool())
+                .to_string();
+            let header = format!(
+                "const {}: {}",
+                const_name,
+                const_env
+                    .get_type()
+                    .display(&const_env.get_type_display_ctx())
+            );
+            self.add_definition(
+                SymbolId::Constant(format!("{}::{}", full_name, const_name)),
+                &const_env.get_loc(),
+                &const_name,
+                markdown(&header, const_env.get_doc()),
+            );
+            // Constants are private to their module
+            members.push((
+                CompletionItem {
+                    label: const_name,
+                    kind: ItemKind::Constant,
+                    detail: header,
+                },
+                false,
+            ));
+        }
+        self.index
+            .modules
+            .insert(full_name, ModuleSymbols { name, members });
+    }
+
+    fn add_struct(&mut self, struct_env: &StructEnv) -> CompletionItem {
+        let pool = struct_env.symbol_pool();
+        let ctx = struct_env.get_type_display_ctx();
+        let key = struct_env.get_full_name_with_address();
+        let name = struct_env.get_name().display(pool).to_string();
+
+        let mut header = format!(
+            "{} {}",
+            if struct_env.has_variants() {
+                "enum"
+            } else {
+                "struct"
+            },
+            name
+        );
+        let type_params = struct_env
+            .get_type_parameters()
+            .iter()
+            .map(|param| param.0.display(pool).to_string())
+            .collect::<Vec<_>>();
+        if !type_params.is_empty() {
+            header.push_str(&format!("<{}>", type_params.join(", ")));
+        }
+        let abilities = struct_env
+            .get_abilities()
+            .iter()
+            .map(|ability| ability.to_string())
+            .collect::<Vec<_>>();
+        if !abilities.is_empty() {
+            header.push_str(&format!(" has {}", abilities.join(", ")));
+        }
+
+        let mut fields: Vec<(CompletionItem, Option<String>)> = vec![];
+        let mut field_lines = vec![];
+        for field_env in struct_env.get_fields() {
+            let field_name = field_env.get_name().display(pool).to_string();
+            let field_type = field_env.get_type();
+            let field_header = format!("{}: {}", field_name, field_type.display(&ctx));
+            self.add_definition(
+                SymbolId::Field(key.clone(), field_name.clone()),
+                field_env.get_loc(),
+                &field_name,
+                markdown(&field_header, field_env.get_doc()),
+            );
+            if field_env.get_variant().is_none() {
+                field_lines.push(format!("    {},", field_header));
+            }
+            if !fields.iter().any(|(item, _)| item.label == field_name) {
+                fields.push((
+                    CompletionItem {
+                        label: field_name,
+                        kind: ItemKind::Field,
+                        detail: field_header,
+                    },
+                    self.struct_key(&field_type),
+                ));
+            }
+        }
+        let hover = if field_lines.is_empty() {
+            header.clone()
+        } else {
+            format!("{} {{\n{}\n}}", header, field_lines.join("\n"))
+        };
+        self.add_definition(
+            SymbolId::Struct(key.clone()),
+            &struct_env.get_loc(),
+            &name,
+            markdown(&hover, struct_env.get_doc()),
+        );
+        self.index.structs.insert(key, fields);
+
+        CompletionItem {
+            label: name,
+            kind: ItemKind::Struct,
+            detail: header,
+        }
+    }
+
+    fn add_function_body(&mut self, fun_env: &FunctionEnv) {
+        let env = self.env;
+        let pool = env.symbol_pool();
+        let ctx = fun_env.get_type_display_ctx();
+        let loc = fun_env.get_loc();
+        let (path, _) = self.file(loc.file_id());
+
+        // Collect the declarations of locals and the references in the body
+        let params = fun_env.get_parameters();
+        let mut decls: Vec<_> = params
+            .iter()
+            .map(|Parameter(name, ty, loc)| (*name, ty.clone(), loc.clone()))
+            .collect();
+        let mut references = vec![];
+        let mut patterns = vec![];
+        if let Some(body) = fun_env.get_def() {
+            body.visit_pre_order(&mut |exp| {
+                match exp {
+                    ExpData::Block(_, pattern, ..) | ExpData::Lambda(_, pattern, ..) => {
+                        patterns.push(pattern.clone());
+                    },
+                    ExpData::Match(_, _, arms) => {
+                        patterns.extend(arms.iter().map(|arm| arm.pattern.clone()));
+                    },
+                    ExpData::Assign(_, pattern, _) => {
+                        references.extend(
+                            pattern
+                                .vars()
+                                .into_iter()
+                                .map(|(id, name)| BodyReference::Local(id, name)),
+                        );
+                        collect_struct_references(env, pattern, &mut references);
+                    },
+                    ExpData::LocalVar(id, name) => {
+                        references.push(BodyReference::Local(*id, *name));
+                    },
+                    ExpData::Temporary(id, index) => {
+                        references.push(BodyReference::Parameter(*id, *index));
+                    },
+                    ExpData::Call(id, Operation::MoveFunction(mid, fid), _) => {
+                        let fun_env = env.get_function(mid.qualified(*fid));
+                        references.push(BodyReference::Function(
+                            *id,
+                            fun_env.get_full_name_with_address(),
+                        ));
+                    },
+                    ExpData::Call(id, Operation::Pack(mid, sid, _), _) => {
+                        let struct_env = env.get_module(*mid).into_struct(*sid);
+                        references.push(BodyReference::Struct(
+                            *id,
+                            struct_env.get_full_name_with_address(),
+                        ));
+                    },
+                    ExpData::Call(id, Operation::Select(mid, sid, fid), _) => {
+                        let struct_env = env.get_module(*mid).into_struct(*sid);
+                        let field_name = struct_env.get_field(*fid).get_name();
+                        references.push(BodyReference::Field(
+                            *id,
+                            struct_env.get_full_name_with_address(),
+                            field_name.display(pool).to_string(),
+                        ));
+                    },
+                    ExpData::Call(id, Operation::SelectVariants(mid, sid, fids), _) => {
+                        let struct_env = env.get_module(*mid).into_struct(*sid);
+                        if let Some(fid) = fids.first() {
+                            let field_name = struct_env.get_field(*fid).get_name();
+                            references.push(BodyReference::Field(
+                                *id,
+                                struct_env.get_full_name_with_address(),
+                                field_name.display(pool).to_string(),
+                            ));
+                        }
+                    },
+                    _ => {},
+                }
+                true
+            });
+        }
+        for pattern in &patterns {
+            decls.extend(
+                pattern
+                    .vars()
+                    .into_iter()
+                    .map(|(id, name)| (name, env.get_node_type(id), env.get_node_loc(id))),
+            );
+            collect_struct_references(env, pattern, &mut references);
+        }
+        decls.sort_by_key(|(_, _, loc)| loc.span().start());
+
+        // Add the declarations, and the scope for the completion of fields
+        let mut locals = vec![];
+        let mut decl_offsets = vec![];
+        for (name, ty, loc) in &decls {
+            let name_str = name.display(pool).to_string();
+            let offset = loc.span().start().to_usize();
+            self.add_definition(
+                SymbolId::Local(path.clone(), offset),
+                loc,
+                &name_str,
+                markdown(&format!("{}: {}", name_str, ty.display(&ctx)), ""),
+            );
+            locals.push((name_str, self.struct_key(ty)));
+            decl_offsets.push((*name, offset));
+        }
+        let (_, file) = self.file(loc.file_id());
+        file.scopes.push(FunctionScope {
+            span: loc.span().start().to_usize()..loc.span().end().to_usize(),
+            locals,
+        });
+
+        // Add the references
+        for reference in references {
+            match reference {
+                BodyReference::Local(id, name) => {
+                    // Resolve to the closest preceding declaration with the same name
+                    let node_loc = env.get_node_loc(id);
+                    let start = node_loc.span().start().to_usize();
+                    let Some((_, offset)) = decl_offsets
+                        .iter()
+                        .rev()
+                        .find(|(decl_name, offset)| *decl_name == name && *offset <= start)
+                    else {
+                        continue;
+                    };
+                    // Declarations are occurrences already
+                    if *offset == start {
+                        continue;
+                    }
+                    let name_str = name.display(pool).to_string();
+                    let hover = format!("{}: {}", name_str, env.get_node_type(id).display(&ctx));
+                    self.add_occurrence(
+                        SymbolId::Local(path.clone(), *offset),
+                        &node_loc,
+                        |text, span| find_name(text, span, &name_str),
+                        Some(markdown(&hover, "")),
+                    );
+                },
+                BodyReference::Parameter(id, index) => {
+                    let Some(Parameter(name, _, param_loc)) = params.get(index) else {
+                        continue;
+                    };
+                    let name_str = name.display(pool).to_string();
+                    let hover = format!("{}: {}", name_str, env.get_node_type(id).display(&ctx));
+                    self.add_occurrence(
+                        SymbolId::Local(path.clone(), param_loc.span().start().to_usize()),
+                        &env.get_node_loc(id),
+                        |_, span| span,
+                        Some(markdown(&hover, "")),
+                    );
+                },
+                BodyReference::Function(id, key) => {
+                    let symbol = SymbolId::Function(key);
+                    self.add_occurrence(symbol, &env.get_node_loc(id), name_span, None);
+                },
+                BodyReference::Struct(id, key) => {
+                    let symbol = SymbolId::Struct(key);
+                    self.add_occurrence(symbol, &env.get_node_loc(id), name_span, None);
+                },
+                BodyReference::Field(id, key, field_name) => {
+                    let symbol = SymbolId::Field(key, field_name);
+                    self.add_occurrence(symbol, &env.get_node_loc(id), name_span, None);
+                },
+            }
+        }
+    }
+
+    /// Returns the key of the struct of the type, or of the type it references
+    fn struct_key(&self, ty: &Type) -> Option<String> {
+        match ty.skip_reference() {
+            Type::Struct(mid, sid, _) => Some(
+                self.env
+                    .get_module(*mid)
+                    .into_struct(*sid)
+                    .get_full_name_with_address(),
+            ),
+            _ => None,
+        }
+    }
+}
+
+fn collect_struct_references(
+    env: &GlobalEnv,
+    pattern: &Pattern,
+    references: &mut Vec<BodyReference>,
+) {
+    match pattern {
+        Pattern::Struct(id, struct_id, _, patterns) => {
+            let struct_env = env.get_struct(struct_id.to_qualified_id());
+            references.push(BodyReference::Struct(
+                *id,
+                struct_env.get_full_name_with_address(),
+            ));
+            for pattern in patterns {
+                collect_struct_references(env, pattern, references);
+            }
+        },
+        Pattern::Tuple(_, patterns) => {
+            for pattern in patterns {
+                collect_struct_references(env, pattern, references);
+            }
+        },
+        Pattern::Var(..) | Pattern::Wildcard(_) | Pattern::Error(_) => {},
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_completion_context() {
+        let fields = |path: &[&str]| {
+            Some(CompletionContext::Fields(
+                path.iter().map(|name| name.to_string()).collect(),
+            ))
+        };
+        assert_eq!(
+            completion_context("        coin::"),
+            Some(CompletionContext::ModuleMembers("coin".to_string()))
+        );
+        assert_eq!(
+            completion_context("    0x1::coin::tra"),
+            Some(CompletionContext::ModuleMembers("coin".to_string()))
+        );
+        assert_eq!(completion_context("    let x = s."), fields(&["s"]));
+        assert_eq!(
+            completion_context("    s.inner.va"),
+            fields(&["s", "inner"])
+        );
+
+        // Calls, literals and plain identifiers are not completed
+        assert_eq!(completion_context("    f()."), None);
+        assert_eq!(completion_context("    1."), None);
+        assert_eq!(completion_context("    let x"), None);
+    }
+
+    #[test]
+    fn test_spans() {
+        let text = "let c = coin::mint<T>(1); c.inner.value; Coin { value }";
+        let span_of = |s: &str| {
+            let start = text.find(s).unwrap();
+            start..start + s.len()
+        };
+        let name_of = |span: ops::Range<usize>| &text[name_span(text, span)];
+        assert_eq!(name_of(span_of("coin::mint<T>(1)")), "coin::mint");
+        assert_eq!(name_of(span_of("c.inner.value")), "value");
+        assert_eq!(name_of(span_of("Coin { value }")), "Coin");
+
+        // Names are matched as whole words
+        let span = find_name(text, span_of("c = coin"), "c");
+        assert_eq!(span, 4..5);
+    }
+}
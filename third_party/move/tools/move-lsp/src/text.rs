@@ -0,0 +1,104 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::protocol::{Position, Range};
+
+/// The text of a source file, which maps between byte offsets (as used by the compiler)
+/// and positions (as used by the client).
+#[derive(Clone, Debug)]
+pub struct SourceText {
+    text: String,
+    line_starts: Vec<usize>,
+}
+
+impl SourceText {
+    pub fn new(text: String) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(offset, _)| offset + 1))
+            .collect();
+        Self { text, line_starts }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns the position of the given byte offset. Offsets past the end of the text
+    /// are mapped to the end of the text.
+    pub fn position(&self, offset: usize) -> Position {
+        let offset = self.floor_char_boundary(offset);
+        let line = self.line_starts.partition_point(|start| *start <= offset) - 1;
+        let character = self.text[self.line_starts[line]..offset]
+            .chars()
+            .map(char::len_utf16)
+            .sum::<usize>();
+        Position {
+            line: line as u32,
+            character: character as u32,
+        }
+    }
+
+    pub fn range(&self, span: std::ops::Range<usize>) -> Range {
+        Range {
+            start: self.position(span.start),
+            end: self.position(span.end),
+        }
+    }
+
+    /// Returns the byte offset of the given position. Positions past the end of a line
+    /// are mapped to the end of the line, and lines past the end of the text to the
+    /// end of the text.
+    pub fn offset(&self, position: Position) -> usize {
+        let Some(line_start) = self.line_starts.get(position.line as usize) else {
+            return self.text.len();
+        };
+        let line = self.text[*line_start..]
+            .split_inclusive('\n')
+            .next()
+            .unwrap_or_default()
+            .trim_end_matches(['\r', '\n']);
+        let mut character = 0;
+        for (offset, ch) in line.char_indices() {
+            if character >= position.character as usize {
+                return line_start + offset;
+            }
+            character += ch.len_utf16();
+        }
+        line_start + line.len()
+    }
+
+    fn floor_char_boundary(&self, offset: usize) -> usize {
+        let mut offset = offset.min(self.text.len());
+        while !self.text.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        offset
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_positions() {
+        let text = SourceText::new("module 0x1::m {\r\n  // π = 3.14\n}".to_string());
+        let pos = |line, character| Position { line, character };
+
+        // Offsets and positions on the first line map to each other
+        assert_eq!(text.position(7), pos(0, 7));
+        assert_eq!(text.offset(pos(0, 7)), 7);
+
+        // The carriage return is not part of the line
+        assert_eq!(text.offset(pos(0, 100)), 15);
+
+        // Characters outside of ASCII are counted in UTF-16 code units
+        let after_pi = text.text().find(" =").unwrap();
+        assert_eq!(text.position(after_pi), pos(1, 6));
+        assert_eq!(text.offset(pos(1, 6)), after_pi);
+
+        // Offsets and positions past the end are clamped
+        assert_eq!(text.position(1000), pos(2, 1));
+        assert_eq!(text.offset(pos(5, 0)), text.text().len());
+    }
+}
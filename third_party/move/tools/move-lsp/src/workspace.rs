@@ -0,0 +1,355 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{protocol::path_to_uri, symbols::SymbolIndex, text::SourceText};
+use anyhow::{anyhow, Context, Result};
+use codespan::{FileId, Files};
+use codespan_reporting::diagnostic::{Diagnostic, LabelStyle, Severity};
+use move_compiler_v2::{
+    env_check_and_transform_pipeline, run_checker, run_stackless_bytecode_gen,
+    run_stackless_bytecode_pipeline, stackless_bytecode_check_pipeline, Experiment, Options,
+};
+use move_linter::MoveLintChecks;
+use move_model::metadata::{CompilerVersion, LanguageVersion};
+use move_package::{
+    compilation::model_builder::ModelBuilder, source_package::layout::SourcePackageLayout,
+    BuildConfig, CompilerConfig, ModelConfig,
+};
+use serde_json::{json, Value};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+};
+use tempfile::TempDir;
+
+/// The diagnostics of a check, per file
+pub type FileDiagnostics = BTreeMap<PathBuf, Vec<Value>>;
+
+/// A package with open documents, and the results of its last check
+#[derive(Default)]
+struct Package {
+    /// The compiler options of the package, resolved from its manifest. These are resolved
+    /// again when the manifest is saved, or a source file is added.
+    options: Option<Options>,
+    /// The source files of the package and its dependencies
+    files: BTreeSet<PathBuf>,
+    /// Whether a file of the package changed since the last check
+    dirty: bool,
+    /// The index of the last check without errors
+    index: Option<SymbolIndex>,
+    /// The files for which diagnostics were reported by the last check
+    files_with_diagnostics: BTreeSet<PathBuf>,
+}
+
+/// The documents opened by the client, and the packages they belong to. Packages are only
+/// checked again once one of their files changed, and only the first time they are needed
+/// after that, so that a burst of edits leads to a single check. An edit only checks the
+/// package of the edited file again; the packages which depend on it are checked again once
+/// the file is saved or closed. Note that each check builds the model of the dependencies
+/// from source again, as the compiler cannot reuse a model built before; only the resolved
+/// options of a package are cached.
+pub struct Workspace {
+    /// The text of open documents, which may not be saved yet
+    documents: BTreeMap<PathBuf, String>,
+    /// The packages, by the path of their root directory
+    packages: BTreeMap<PathBuf, Package>,
+    /// The directory for the text of open documents, which is passed to the compiler in
+    /// place of the files on disk
+    overlay_dir: TempDir,
+}
+
+impl Workspace {
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            documents: BTreeMap::new(),
+            packages: BTreeMap::new(),
+            overlay_dir: tempfile::tempdir()?,
+        })
+    }
+
+    /// Sets the text of a document, when it is opened or changed
+    pub fn set_document(&mut self, path: PathBuf, text: String) {
+        let path = normalize(&path);
+        self.documents.insert(path.clone(), text);
+        self.file_changed(&path, false);
+    }
+
+    /// Closes a document, the file on disk is used from now on
+    pub fn close_document(&mut self, path: &Path) {
+        let path = normalize(path);
+        if self.documents.remove(&path).is_some() {
+            self.file_changed(&path, true);
+        }
+    }
+
+    /// Notifies that a file was saved, which matters for files that are not open
+    /// (e.g., the manifest)
+    pub fn save_document(&mut self, path: &Path) {
+        let path = normalize(path);
+        if path.file_name() == Some(SourcePackageLayout::Manifest.path().as_os_str()) {
+            if let Some(package) = path.parent().and_then(|root| self.packages.get_mut(root)) {
+                package.options = None;
+                package.dirty = true;
+            }
+        } else {
+            self.file_changed(&path, true);
+        }
+    }
+
+    /// Returns the text of a file, as opened by the client or on disk
+    pub fn document_text(&self, path: &Path) -> Option<String> {
+        let path = normalize(path);
+        match self.documents.get(&path) {
+            Some(text) => Some(text.clone()),
+            None => fs::read_to_string(&path).ok(),
+        }
+    }
+
+    /// Returns the index of the last successful check of a package containing the file
+    pub fn index(&self, path: &Path) -> Option<&SymbolIndex> {
+        let path = normalize(path);
+        self.packages
+            .values()
+            .filter_map(|package| package.index.as_ref())
+            .find(|index| index.contains_file(&path))
+    }
+
+    /// Checks the packages which changed since their last check. Returns the diagnostics to
+    /// publish for each package, which include empty diagnostics for files which had some
+    /// after the previous check.
+    pub fn check_packages(&mut self) -> Vec<Result<FileDiagnostics>> {
+        let roots: Vec<_> = self
+            .packages
+            .iter()
+            .filter(|(_, package)| package.dirty)
+            .map(|(root, _)| root.clone())
+            .collect();
+        roots
+            .into_iter()
+            .map(|root| {
+                self.check_package(&root)
+                    .with_context(|| format!("Failed to check package {}", root.display()))
+            })
+            .collect()
+    }
+
+    fn check_package(&mut self, root: &Path) -> Result<FileDiagnostics> {
+        let package = self.packages.entry(root.to_path_buf()).or_default();
+        package.dirty = false;
+        let options = match &package.options {
+            Some(options) => options.clone(),
+            None => {
+                let options = resolve_options(root)?;
+                package.files = options
+                    .sources
+                    .iter()
+                    .chain(&options.dependencies)
+                    .map(|file| normalize(Path::new(file)))
+                    .collect();
+                package.options = Some(options.clone());
+                options
+            },
+        };
+
+        // Pass the text of open documents instead of the files on disk
+        let mut overlays = BTreeMap::new();
+        let mut overlay = |files: Vec<String>| -> Result<Vec<String>> {
+            files
+                .into_iter()
+                .map(|file| {
+                    let path = normalize(Path::new(&file));
+                    let Some(text) = self.documents.get(&path) else {
+                        return Ok(file);
+                    };
+                    let dir = self.overlay_dir.path().join(overlays.len().to_string());
+                    fs::create_dir_all(&dir)?;
+                    let overlay_path = dir.join(path.file_name().unwrap_or_default());
+                    fs::write(&overlay_path, text)?;
+                    overlays.insert(normalize(&overlay_path), path);
+                    Ok(overlay_path.to_string_lossy().to_string())
+                })
+                .collect()
+        };
+        let sources = overlay(options.sources.clone())?;
+        let dependencies = overlay(options.dependencies.clone())?;
+        let options = Options {
+            sources,
+            dependencies,
+            ..options
+        };
+        let file_path = |file: &str| {
+            let path = normalize(Path::new(file));
+            overlays.get(&path).cloned().unwrap_or(path)
+        };
+
+        // The compiler panics on some internal errors, which must not stop the server
+        let (index, mut diagnostics) =
+            panic::catch_unwind(AssertUnwindSafe(|| check(options, &file_path)))
+                .map_err(|_| anyhow!("The compiler panicked"))??;
+
+        let package = self.packages.entry(root.to_path_buf()).or_default();
+        if index.is_some() {
+            package.index = index;
+        }
+        let files_with_diagnostics = diagnostics.keys().cloned().collect();
+        for path in std::mem::replace(&mut package.files_with_diagnostics, files_with_diagnostics) {
+            diagnostics.entry(path).or_default();
+        }
+        Ok(diagnostics)
+    }
+
+    /// Marks the package of the file as changed, adding it if it is new. With `dependents`,
+    /// also marks the other packages which contain the file as changed.
+    fn file_changed(&mut self, path: &Path, dependents: bool) {
+        if dependents {
+            for package in self.packages.values_mut() {
+                if package.files.contains(path) {
+                    package.dirty = true;
+                }
+            }
+        }
+        let Some(root) = path
+            .parent()
+            .and_then(|dir| SourcePackageLayout::try_find_root(dir).ok())
+        else {
+            return;
+        };
+        let package = self.packages.entry(root).or_default();
+        package.dirty = true;
+        if path.extension().is_some_and(|ext| ext == "move") && !package.files.contains(path) {
+            // The file is new, or the package was not resolved yet
+            package.options = None;
+        }
+    }
+}
+
+/// Resolves the dependencies of the package, and returns the options to check it,
+/// including tests and lints.
+fn resolve_options(root: &Path) -> Result<Options> {
+    let build_config = BuildConfig {
+        test_mode: true,
+        skip_fetch_latest_git_deps: true,
+        compiler_config: CompilerConfig {
+            skip_attribute_checks: true,
+            ..CompilerConfig::default()
+        },
+        ..BuildConfig::default()
+    };
+    let resolved_graph = build_config.resolution_graph_for_package(root, &mut Vec::new())?;
+    let model_config = ModelConfig {
+        all_files_as_targets: false,
+        target_filter: None,
+        compiler_version: CompilerVersion::latest_stable(),
+        language_version: LanguageVersion::latest_stable(),
+    };
+    let mut options = ModelBuilder::create(resolved_graph, model_config).compiler_options()?;
+    options.compile_test_code = true;
+    options.external_checks = vec![MoveLintChecks::make(BTreeMap::from([(
+        "checks".to_string(),
+        "default".to_string(),
+    )]))];
+    Ok(options.set_experiment(Experiment::LINT_CHECKS, true))
+}
+
+/// Runs the checks of the compiler (without generating code) and the lints, and returns
+/// the index if the package type checks, and the diagnostics.
+fn check(
+    options: Options,
+    file_path: &impl Fn(&str) -> PathBuf,
+) -> Result<(Option<SymbolIndex>, FileDiagnostics)> {
+    let mut env = run_checker(options.clone())?;
+    let index = (!env.has_errors()).then(|| SymbolIndex::new(&env, file_path));
+    if !env.has_errors() {
+        env_check_and_transform_pipeline(&options).run(&mut env);
+    }
+    if !env.has_errors() {
+        let mut targets = run_stackless_bytecode_gen(&env);
+        if !env.has_errors() {
+            run_stackless_bytecode_pipeline(
+                &env,
+                stackless_bytecode_check_pipeline(&options),
+                &mut targets,
+            );
+        }
+    }
+
+    let mut diagnostics = FileDiagnostics::new();
+    let mut texts = BTreeMap::new();
+    env.report_diag_with_filter(
+        |files, diag| {
+            if let Some((path, diagnostic)) = to_lsp_diagnostic(files, diag, file_path, &mut texts)
+            {
+                diagnostics.entry(path).or_default().push(diagnostic);
+            }
+        },
+        |diag| diag.severity >= options.report_severity(),
+    );
+    Ok((index, diagnostics))
+}
+
+/// Converts a diagnostic of the compiler to one of the protocol, at its primary label
+fn to_lsp_diagnostic(
+    files: &Files<String>,
+    diag: &Diagnostic<FileId>,
+    file_path: &impl Fn(&str) -> PathBuf,
+    texts: &mut BTreeMap<FileId, SourceText>,
+) -> Option<(PathBuf, Value)> {
+    let mut location = |file_id: FileId, span: std::ops::Range<usize>| {
+        let path = file_path(&files.name(file_id).to_string_lossy());
+        let text = texts
+            .entry(file_id)
+            .or_insert_with(|| SourceText::new(files.source(file_id).clone()));
+        (path, text.range(span))
+    };
+
+    let primary = diag
+        .labels
+        .iter()
+        .find(|label| label.style == LabelStyle::Primary)
+        .or_else(|| diag.labels.first())?;
+    let (path, range) = location(primary.file_id, primary.range.clone());
+    let mut message = diag.message.clone();
+    if !primary.message.is_empty() {
+        message = format!("{}\n{}", message, primary.message);
+    }
+    for note in &diag.notes {
+        message = format!("{}\n{}", message, note);
+    }
+    let related: Vec<_> = diag
+        .labels
+        .iter()
+        .filter(|label| !std::ptr::eq(*label, primary))
+        .map(|label| {
+            let (path, range) = location(label.file_id, label.range.clone());
+            json!({
+                "location": { "uri": path_to_uri(&path), "range": range },
+                "message": label.message,
+            })
+        })
+        .collect();
+    let severity = match diag.severity {
+        Severity::Bug | Severity::Error => 1,
+        Severity::Warning => 2,
+        Severity::Note => 3,
+        Severity::Help => 4,
+    };
+
+    let mut diagnostic = json!({
+        "range": range,
+        "severity": severity,
+        "source": "move",
+        "message": message,
+        "relatedInformation": related,
+    });
+    if let Some(code) = &diag.code {
+        diagnostic["code"] = json!(code);
+    }
+    Some((path, diagnostic))
+}
+
+/// Makes paths of the client and of the package system comparable
+fn normalize(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
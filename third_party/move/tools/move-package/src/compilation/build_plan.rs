@@ -5,8 +5,11 @@
 
 use super::package_layout::CompiledPackageLayout;
 use crate::{
-    compilation::compiled_package::{
-        build_and_report_no_exit_v2_driver, build_and_report_v2_driver, CompiledPackage,
+    compilation::{
+        compilation_cache::CompilationCache,
+        compiled_package::{
+            build_and_report_no_exit_v2_driver, build_and_report_v2_driver, CompiledPackage,
+        },
     },
     resolution::resolution_graph::ResolvedGraph,
     source_package::parsed_manifest::PackageName,
@@ -17,7 +20,12 @@ use legacy_move_compiler::{compiled_unit::AnnotatedCompiledUnit, diagnostics::Fi
 use move_compiler_v2::external_checks::ExternalChecks;
 use move_model::model;
 use petgraph::algo::toposort;
-use std::{collections::BTreeSet, io::Write, path::Path, sync::Arc};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::Write,
+    path::Path,
+    sync::Arc,
+};
 
 #[derive(Debug, Clone)]
 pub struct BuildPlan {
@@ -94,10 +102,39 @@ impl BuildPlan {
             Some(under_path) => under_path.clone(),
             None => self.resolution_graph.root_package_path.clone(),
         };
+        let build_root = project_root.join(CompiledPackageLayout::Root.path());
         let immediate_dependencies_names =
             root_package.immediate_dependencies(&self.resolution_graph);
-        let transitive_dependencies = root_package
-            .transitive_dependencies(&self.resolution_graph)
+        let transitive_dependencies_names =
+            root_package.transitive_dependencies(&self.resolution_graph);
+
+        // Compiled dependencies are reused if neither they nor their dependencies changed
+        let cache = CompilationCache::new(&build_root);
+        let cache_keys = CompilationCache::compute_keys(
+            &self.resolution_graph,
+            &self
+                .sorted_deps
+                .iter()
+                .copied()
+                .filter(|name| transitive_dependencies_names.contains(name))
+                .collect::<Vec<_>>(),
+            config,
+        )?;
+        let cached_dependencies = if self.resolution_graph.build_options.force_recompilation {
+            BTreeMap::new()
+        } else {
+            cache_keys
+                .iter()
+                .filter_map(|(name, key)| {
+                    let units = cache.load(self.resolution_graph.get_package(name), key)?;
+                    Some((*name, units))
+                })
+                .collect::<BTreeMap<_, _>>()
+        };
+        let cached_dependencies_names =
+            cached_dependencies.keys().copied().collect::<BTreeSet<_>>();
+
+        let transitive_dependencies = transitive_dependencies_names
             .into_iter()
             .map(|package_name| {
                 let dep_package = self
@@ -129,16 +166,35 @@ impl BuildPlan {
             &project_root,
             root_package.clone(),
             transitive_dependencies,
+            cached_dependencies,
             config,
             external_checks,
             &self.resolution_graph,
             driver,
         )?;
 
-        Self::clean(
-            &project_root.join(CompiledPackageLayout::Root.path()),
-            self.sorted_deps.iter().copied().collect(),
-        )?;
+        let bytecode_version = config
+            .language_version
+            .unwrap_or_default()
+            .infer_bytecode_version(config.bytecode_version);
+        for (name, key) in &cache_keys {
+            if !cached_dependencies_names.contains(name) {
+                let units = compiled
+                    .deps_compiled_units
+                    .iter()
+                    .filter(|(package_name, _)| package_name == name)
+                    .map(|(_, unit)| unit);
+                cache.store(
+                    self.resolution_graph.get_package(name),
+                    key,
+                    units,
+                    bytecode_version,
+                )?;
+            }
+        }
+        cache.clean(&cache_keys.keys().copied().collect())?;
+
+        Self::clean(&build_root, self.sorted_deps.iter().copied().collect())?;
         Ok((compiled, model))
     }
 
@@ -154,7 +210,10 @@ impl BuildPlan {
                     )
                 })?
                 .path();
-            if path.is_dir() && !keep_paths.iter().any(|name| path.ends_with(name.as_str())) {
+            if path.is_dir()
+                && !path.ends_with(CompiledPackageLayout::CompilationCache.path())
+                && !keep_paths.iter().any(|name| path.ends_with(name.as_str()))
+            {
                 std::fs::remove_dir_all(&path).with_context(|| {
                     format!("When deleting directory {}", path.to_string_lossy())
                 })?;
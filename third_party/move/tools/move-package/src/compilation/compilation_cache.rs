@@ -0,0 +1,279 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! A cache of the compiled dependencies of a package.
+//!
+//! A dependency compiles to the same bytecode as long as its sources, the compiler and its
+//! configuration, its named addresses and its own dependencies are unchanged. The compiled units
+//! of a dependency are therefore cached under a key hashing all of these, where the keys of the
+//! dependencies stand in for their code. Touching a dependency changes its key, and with it the
+//! keys of all packages depending on it.
+//!
+//! Only the code generation of a dependency is skipped on a cache hit. The model is not cached:
+//! a cached dependency is still passed to the compiler as source, and parsed and type checked on
+//! every build, as the packages using it are type checked against it.
+
+use crate::{
+    compilation::{
+        compiled_package::{decode_compiled_unit, CompiledUnitWithSource},
+        package_layout::CompiledPackageLayout,
+    },
+    resolution::resolution_graph::{ResolvedGraph, ResolvedPackage},
+    source_package::parsed_manifest::PackageName,
+    CompilerConfig,
+};
+use anyhow::Result;
+use legacy_move_compiler::compiled_unit::CompiledUnit;
+use move_bytecode_source_map::utils::source_map_from_file;
+use move_command_line_common::files::{MOVE_COMPILED_EXTENSION, SOURCE_MAP_EXTENSION};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+/// The name of the file listing the compiled units of a cache entry.
+const CACHE_INFO_FILE: &str = "CacheInfo.yaml";
+
+/// How many entries are kept for each package, e.g. for compiling with and without test mode.
+const MAX_ENTRIES_PER_PACKAGE: usize = 4;
+
+pub type CacheKey = String;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheInfo {
+    units: Vec<CachedUnit>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedUnit {
+    /// The path of the bytecode, relative to the cache entry
+    bytecode: PathBuf,
+    /// The path of the source file, relative to the package
+    source: PathBuf,
+}
+
+/// The compiled dependencies of a package, stored under `build/.cache/<package>/<key>`.
+pub struct CompilationCache {
+    root: PathBuf,
+}
+
+impl CompilationCache {
+    pub fn new(build_root: &Path) -> Self {
+        Self {
+            root: build_root.join(CompiledPackageLayout::CompilationCache.path()),
+        }
+    }
+
+    /// Computes the cache keys of `packages`, which must be ordered such that every package
+    /// comes after its dependencies. Packages without sources, and packages depending on a
+    /// package without a key, can't be cached and get no key. No package gets a key if the
+    /// build of the compiler can't be identified.
+    pub fn compute_keys(
+        resolution_graph: &ResolvedGraph,
+        packages: &[PackageName],
+        config: &CompilerConfig,
+    ) -> Result<BTreeMap<PackageName, CacheKey>> {
+        let Some(compiler_build) = compiler_build_id() else {
+            return Ok(BTreeMap::new());
+        };
+        let build_options = &resolution_graph.build_options;
+        let language_version = config.language_version.unwrap_or_default();
+        let mut common = Sha256::new();
+        for part in [
+            compiler_build,
+            config.compiler_version.unwrap_or_default().to_string(),
+            language_version.to_string(),
+            language_version
+                .infer_bytecode_version(config.bytecode_version)
+                .to_string(),
+            build_options.test_mode.to_string(),
+            build_options
+                .compiler_config
+                .skip_attribute_checks
+                .to_string(),
+            format!("{:?}", build_options.compiler_config.known_attributes),
+            format!("{:?}", config.experiments.iter().collect::<BTreeSet<_>>()),
+        ] {
+            hash_part(&mut common, &part);
+        }
+
+        let mut keys: BTreeMap<PackageName, CacheKey> = BTreeMap::new();
+        'packages: for package_name in packages {
+            let package = resolution_graph.get_package(package_name);
+            let sources = package.get_sources(build_options)?;
+            if sources.is_empty() {
+                continue;
+            }
+            let mut hasher = common.clone();
+            hash_part(&mut hasher, package_name.as_str());
+            hash_part(&mut hasher, package.source_digest.as_str());
+            for source in &sources {
+                let source = Path::new(source.as_str());
+                let relative = source.strip_prefix(&package.package_path).unwrap_or(source);
+                hash_part(&mut hasher, &relative.to_string_lossy());
+            }
+            hash_part(&mut hasher, &format!("{:?}", package.resolution_table));
+            hash_part(&mut hasher, &format!("{:?}", package.renaming));
+            for dep_name in package.immediate_dependencies(resolution_graph) {
+                let Some(dep_key) = keys.get(&dep_name) else {
+                    continue 'packages;
+                };
+                hash_part(&mut hasher, dep_name.as_str());
+                hash_part(&mut hasher, dep_key);
+            }
+            keys.insert(*package_name, format!("{:X}", hasher.finalize()));
+        }
+        Ok(keys)
+    }
+
+    /// Loads the compiled units of `package` cached under `key`, if any.
+    pub fn load(
+        &self,
+        package: &ResolvedPackage,
+        key: &str,
+    ) -> Option<Vec<CompiledUnitWithSource>> {
+        let entry = self.entry_path(package, key);
+        if !entry.join(CACHE_INFO_FILE).is_file() {
+            return None;
+        }
+        // A corrupted entry is recompiled, and overwritten afterwards
+        self.load_entry(package, &entry).ok()
+    }
+
+    fn load_entry(
+        &self,
+        package: &ResolvedPackage,
+        entry: &Path,
+    ) -> Result<Vec<CompiledUnitWithSource>> {
+        let info: CacheInfo =
+            serde_yaml::from_str(&std::fs::read_to_string(entry.join(CACHE_INFO_FILE))?)?;
+        info.units
+            .into_iter()
+            .map(|cached| {
+                let bytecode_path = entry.join(&cached.bytecode);
+                let source_map =
+                    source_map_from_file(&bytecode_path.with_extension(SOURCE_MAP_EXTENSION))?;
+                let unit = decode_compiled_unit(
+                    package.source_package.package.name,
+                    &bytecode_path,
+                    &std::fs::read(&bytecode_path)?,
+                    source_map,
+                )?;
+                Ok(CompiledUnitWithSource {
+                    unit,
+                    source_path: package.package_path.join(cached.source),
+                })
+            })
+            .collect()
+    }
+
+    /// Stores the compiled units of `package` under `key`, evicting the oldest entries of the
+    /// package.
+    pub fn store<'a>(
+        &self,
+        package: &ResolvedPackage,
+        key: &str,
+        units: impl IntoIterator<Item = &'a CompiledUnitWithSource>,
+        bytecode_version: u32,
+    ) -> Result<()> {
+        let entry = self.entry_path(package, key);
+        if entry.is_dir() {
+            std::fs::remove_dir_all(&entry)?;
+        }
+        let mut info = CacheInfo { units: vec![] };
+        for unit in units {
+            let (category, name) = match &unit.unit {
+                CompiledUnit::Script(named) => (CompiledPackageLayout::CompiledScripts, named.name),
+                CompiledUnit::Module(named) => (CompiledPackageLayout::CompiledModules, named.name),
+            };
+            let bytecode = category
+                .path()
+                .join(name.as_str())
+                .with_extension(MOVE_COMPILED_EXTENSION);
+            let bytecode_path = entry.join(&bytecode);
+            std::fs::create_dir_all(bytecode_path.parent().unwrap())?;
+            std::fs::write(&bytecode_path, unit.unit.serialize(Some(bytecode_version)))?;
+            std::fs::write(
+                bytecode_path.with_extension(SOURCE_MAP_EXTENSION),
+                unit.unit.serialize_source_map(),
+            )?;
+            let source = unit
+                .source_path
+                .strip_prefix(&package.package_path)
+                .unwrap_or(&unit.source_path)
+                .to_path_buf();
+            info.units.push(CachedUnit { bytecode, source });
+        }
+        // The info file is written last, so that incomplete entries are never loaded
+        std::fs::create_dir_all(&entry)?;
+        std::fs::write(entry.join(CACHE_INFO_FILE), serde_yaml::to_string(&info)?)?;
+        self.evict_old_entries(package)
+    }
+
+    fn evict_old_entries(&self, package: &ResolvedPackage) -> Result<()> {
+        let mut entries = std::fs::read_dir(self.package_path(package))?
+            .map(|entry| {
+                let path = entry?.path();
+                let modified = std::fs::metadata(path.join(CACHE_INFO_FILE))
+                    .and_then(|metadata| metadata.modified())
+                    .ok();
+                Ok((modified, path))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        // Newest first, with incomplete entries last
+        entries.sort_by(|(a, _), (b, _)| b.cmp(a));
+        for (_, path) in entries.into_iter().skip(MAX_ENTRIES_PER_PACKAGE) {
+            std::fs::remove_dir_all(path)?;
+        }
+        Ok(())
+    }
+
+    /// Removes the entries of all packages not in `packages`.
+    pub fn clean(&self, packages: &BTreeSet<PackageName>) -> Result<()> {
+        if !self.root.is_dir() {
+            return Ok(());
+        }
+        for dir in std::fs::read_dir(&self.root)? {
+            let path = dir?.path();
+            let is_used = path
+                .file_name()
+                .is_some_and(|name| packages.iter().any(|package| name == package.as_str()));
+            if path.is_dir() && !is_used {
+                std::fs::remove_dir_all(&path)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn package_path(&self, package: &ResolvedPackage) -> PathBuf {
+        self.root.join(package.source_package.package.name.as_str())
+    }
+
+    fn entry_path(&self, package: &ResolvedPackage, key: &str) -> PathBuf {
+        self.package_path(package).join(key)
+    }
+}
+
+/// Identifies the build of the compiler, i.e., of the executable it is linked into, by the path,
+/// size and modification time of the executable. The versions of the compiler crates are not
+/// bumped when the compiler changes, so they can't tell builds apart.
+fn compiler_build_id() -> Option<String> {
+    let executable = std::env::current_exe().ok()?;
+    let metadata = std::fs::metadata(&executable).ok()?;
+    let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    Some(format!(
+        "{}:{}:{}",
+        executable.display(),
+        metadata.len(),
+        modified.as_nanos()
+    ))
+}
+
+/// Adds `part` to the hash, terminated so that consecutive parts can't run into each other.
+fn hash_part(hasher: &mut Sha256, part: &str) {
+    hasher.update(part.as_bytes());
+    hasher.update([0]);
+}
@@ -23,7 +23,7 @@ use legacy_move_compiler::{
 };
 use move_abigen::{Abigen, AbigenOptions};
 use move_binary_format::file_format::{CompiledModule, CompiledScript};
-use move_bytecode_source_map::utils::source_map_from_file;
+use move_bytecode_source_map::{source_map::SourceMap, utils::source_map_from_file};
 use move_bytecode_utils::Modules;
 use move_command_line_common::files::{
     extension_equals, find_filenames, MOVE_COMPILED_EXTENSION, MOVE_EXTENSION, SOURCE_MAP_EXTENSION,
@@ -212,7 +212,6 @@ impl OnDiskCompiledPackage {
         package_name: Symbol,
         bytecode_path_str: &str,
     ) -> Result<CompiledUnitWithSource> {
-        let package_name_opt = Some(package_name);
         let bytecode_path = Path::new(bytecode_path_str);
         let path_to_file = CompiledPackageLayout::path_to_file_after_category(bytecode_path);
         let bytecode_bytes = std::fs::read(bytecode_path)?;
@@ -236,44 +235,8 @@ impl OnDiskCompiledPackage {
             bytecode_path_str,
             package_name
         );
-        match CompiledScript::deserialize(&bytecode_bytes) {
-            Ok(script) => {
-                let name = FileName::from(
-                    bytecode_path
-                        .file_stem()
-                        .unwrap()
-                        .to_string_lossy()
-                        .to_string(),
-                );
-                let unit = CompiledUnit::Script(NamedCompiledScript {
-                    package_name: package_name_opt,
-                    name,
-                    script,
-                    source_map,
-                });
-                Ok(CompiledUnitWithSource { unit, source_path })
-            },
-            Err(_) => {
-                let module = CompiledModule::deserialize(&bytecode_bytes)?;
-                let (address_bytes, module_name) = {
-                    let id = module.self_id();
-                    let parsed_addr = NumericalAddress::new(
-                        id.address().into_bytes(),
-                        legacy_move_compiler::shared::NumberFormat::Hex,
-                    );
-                    let module_name = FileName::from(id.name().as_str());
-                    (parsed_addr, module_name)
-                };
-                let unit = CompiledUnit::Module(NamedCompiledModule {
-                    package_name: package_name_opt,
-                    address: address_bytes,
-                    name: module_name,
-                    module,
-                    source_map,
-                });
-                Ok(CompiledUnitWithSource { unit, source_path })
-            },
-        }
+        let unit = decode_compiled_unit(package_name, bytecode_path, &bytecode_bytes, source_map)?;
+        Ok(CompiledUnitWithSource { unit, source_path })
     }
 
     /// Save `bytes` under `path_under` relative to the package on disk
@@ -542,6 +505,7 @@ impl CompiledPackage {
             /* address mapping */ &ResolvedTable,
             /* whether source is available */ bool,
         )>,
+        cached_dependencies: BTreeMap<PackageName, Vec<CompiledUnitWithSource>>,
         config: &CompilerConfig,
         external_checks: Vec<Arc<dyn ExternalChecks>>,
         resolution_graph: &ResolvedGraph,
@@ -600,9 +564,14 @@ impl CompiledPackage {
             .clone();
         KnownAttribute::add_attribute_names(&mut known_attributes);
 
+        // Dependencies loaded from the compilation cache are only type checked against
+        let (cached_deps, deps_package_paths): (Vec<_>, Vec<_>) =
+            deps_package_paths.into_iter().partition(|(p, _)| {
+                p.name
+                    .is_some_and(|name| cached_dependencies.contains_key(&name))
+            });
         // Partition deps_package according whether src is available
         let (src_deps, bytecode_deps): (Vec<_>, Vec<_>) = deps_package_paths
-            .clone()
             .into_iter()
             .partition_map(|(p, b)| if b { Either::Left(p) } else { Either::Right(p) });
         // If bytecode dependency is not empty, do not allow renaming
@@ -634,6 +603,7 @@ impl CompiledPackage {
                     for pack in std::iter::once(&sources_package_paths)
                         .chain(src_deps.iter())
                         .chain(bytecode_deps.iter())
+                        .chain(cached_deps.iter().map(|(p, _)| p))
                     {
                         for (name, val) in &pack.named_address_map {
                             if let Some(old) =
@@ -662,6 +632,7 @@ impl CompiledPackage {
                         sources_deps: src_deps.iter().flat_map(|x| to_str_vec(&x.paths)).collect(),
                         dependencies: bytecode_deps
                             .iter()
+                            .chain(cached_deps.iter().map(|(p, _)| p))
                             .flat_map(|x| to_str_vec(&x.paths))
                             .collect(),
                         named_address_mapping: global_address_map
@@ -718,6 +689,9 @@ impl CompiledPackage {
                 deps_compiled_units.push((package_name, unit))
             }
         }
+        deps_compiled_units.extend(cached_dependencies.into_iter().flat_map(
+            |(package_name, units)| units.into_iter().map(move |unit| (package_name, unit)),
+        ));
         let bytecode_version = config
             .language_version
             .unwrap_or_default()
@@ -1147,6 +1121,51 @@ pub fn build_and_report_no_exit_v2_driver(
     Ok((move_compiler_v2::make_files_source_text(&env), units, env))
 }
 
+/// Decodes the bytecode of a script or module in `package_name`, stored at `bytecode_path`.
+pub(crate) fn decode_compiled_unit(
+    package_name: Symbol,
+    bytecode_path: &Path,
+    bytecode_bytes: &[u8],
+    source_map: SourceMap,
+) -> Result<CompiledUnit> {
+    match CompiledScript::deserialize(bytecode_bytes) {
+        Ok(script) => {
+            let name = FileName::from(
+                bytecode_path
+                    .file_stem()
+                    .unwrap()
+                    .to_string_lossy()
+                    .to_string(),
+            );
+            Ok(CompiledUnit::Script(NamedCompiledScript {
+                package_name: Some(package_name),
+                name,
+                script,
+                source_map,
+            }))
+        },
+        Err(_) => {
+            let module = CompiledModule::deserialize(bytecode_bytes)?;
+            let (address_bytes, module_name) = {
+                let id = module.self_id();
+                let parsed_addr = NumericalAddress::new(
+                    id.address().into_bytes(),
+                    legacy_move_compiler::shared::NumberFormat::Hex,
+                );
+                let module_name = FileName::from(id.name().as_str());
+                (parsed_addr, module_name)
+            };
+            Ok(CompiledUnit::Module(NamedCompiledModule {
+                package_name: Some(package_name),
+                address: address_bytes,
+                name: module_name,
+                module,
+                source_map,
+            }))
+        },
+    }
+}
+
 /// Returns the deserialized module from the bytecode file
 fn get_module_in_package(pkg_name: Symbol, pkg_path: &str) -> Result<CompiledModule> {
     // Read the bytecode file
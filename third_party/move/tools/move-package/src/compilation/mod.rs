@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub mod build_plan;
+pub mod compilation_cache;
 pub mod compiled_package;
 pub mod model_builder;
 pub mod package_layout;
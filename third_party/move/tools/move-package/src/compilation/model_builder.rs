@@ -33,6 +33,15 @@ impl ModelBuilder {
     // TODO: In the future we will need a better way to do this to support renaming in packages
     // where we want to support building a Move model.
     pub fn build_model(&self) -> Result<GlobalEnv> {
+        let options = self.compiler_options()?;
+        let mut error_writer = StandardStream::stderr(ColorChoice::Auto);
+        move_compiler_v2::run_move_compiler_for_analysis(&mut error_writer, options)
+    }
+
+    /// Returns the options for the v2 compiler to build the model, with the targets and
+    /// dependencies of the package as sources. This allows tools to run their own pipeline
+    /// on the package, e.g., to check it without generating code.
+    pub fn compiler_options(&self) -> Result<Options> {
         // Make sure no renamings have been performed
         if let Some(pkg_name) = self.resolution_graph.contains_renaming() {
             anyhow::bail!(
@@ -147,8 +156,7 @@ impl ModelBuilder {
                         .compiler_config
                         .experiments,
                 );
-                let mut error_writer = StandardStream::stderr(ColorChoice::Auto);
-                move_compiler_v2::run_move_compiler_for_analysis(&mut error_writer, options)
+                Ok(options)
             },
         }
     }
@@ -91,6 +91,16 @@ pub struct BuildConfig {
     #[clap(long = "skip-fetch-latest-git-deps", global = true)]
     pub skip_fetch_latest_git_deps: bool,
 
+    /// Fail if the git and on-chain dependencies do not resolve to the versions pinned in
+    /// Move.lock
+    #[clap(long = "locked", global = true)]
+    pub locked: bool,
+
+    /// Ignore the versions pinned in Move.lock, and pin the latest versions of the dependencies
+    /// instead. Other builds only read Move.lock. Used programmatically only.
+    #[clap(skip)]
+    pub update_deps: bool,
+
     #[clap(flatten)]
     pub compiler_config: CompilerConfig,
 }
@@ -212,7 +222,14 @@ impl BuildConfig {
         // possibly be set by a different process in parallel.
         let manifest = manifest_parser::parse_source_manifest(toml_manifest)?;
         let resolution_graph = ResolutionGraph::new(manifest, path, self, writer)?;
-        let ret = resolution_graph.resolve();
+        let ret = resolution_graph.resolve().and_then(|resolved_graph| {
+            // The lock file is only written when the dependencies are updated
+            let options = &resolved_graph.build_options;
+            if options.update_deps || options.locked {
+                resolved_graph.update_lock_file()?;
+            }
+            Ok(resolved_graph)
+        });
         mutx.unlock();
         ret
     }
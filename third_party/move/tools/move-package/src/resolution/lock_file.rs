@@ -0,0 +1,194 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::source_package::parsed_manifest::{CustomDepInfo, GitInfo, PackageDigest, PackageName};
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+/// The name of the lock file, next to the manifest of the root package
+pub const LOCK_FILE_NAME: &str = "Move.lock";
+
+const LOCK_FILE_VERSION: u64 = 1;
+
+const LOCK_FILE_HEADER: &str =
+    "# This file is generated by the Move package system. It is not intended for manual editing.\n";
+
+/// The lock file of a package, which pins each git and on-chain dependency in the package graph
+/// to the version it resolved to: the commit of a git dependency, and the digest of the sources
+/// and manifest of any remote dependency. For an on-chain dependency, this digest is taken over
+/// the package as downloaded from the chain, and so serves as the hash of the on-chain package.
+///
+/// Packages are kept sorted by name.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockFile {
+    pub version: u64,
+    #[serde(default, rename = "package")]
+    pub packages: Vec<LockedPackage>,
+}
+
+/// A remote dependency, as pinned in the lock file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockedPackage {
+    pub name: String,
+    /// The clone url of a git dependency
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git: Option<String>,
+    /// The revision of a git dependency, as given in the manifest
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rev: Option<String>,
+    /// The path of a git dependency in its repository
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subdir: Option<String>,
+    /// The commit the revision of a git dependency resolved to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    /// The url of the node an on-chain dependency is downloaded from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub node: Option<String>,
+    /// The address an on-chain dependency is published at
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    /// The digest of the sources and manifest of the dependency
+    pub digest: String,
+}
+
+impl Default for LockFile {
+    fn default() -> Self {
+        Self {
+            version: LOCK_FILE_VERSION,
+            packages: vec![],
+        }
+    }
+}
+
+impl LockFile {
+    pub fn path(root_path: &Path) -> PathBuf {
+        root_path.join(LOCK_FILE_NAME)
+    }
+
+    /// Reads the lock file of the package at `root_path`, if it has one.
+    pub fn read(root_path: &Path) -> Result<Option<Self>> {
+        let path = Self::path(root_path);
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let contents = fs::read_to_string(&path)
+            .with_context(|| format!("Unable to read lock file {}", path.display()))?;
+        let lock_file: LockFile = toml::from_str(&contents)
+            .with_context(|| format!("Unable to parse lock file {}", path.display()))?;
+        if lock_file.version != LOCK_FILE_VERSION {
+            bail!(
+                "Unsupported version {} of lock file {}, expected version {}",
+                lock_file.version,
+                path.display(),
+                LOCK_FILE_VERSION
+            )
+        }
+        Ok(Some(lock_file))
+    }
+
+    pub fn write(&self, root_path: &Path) -> Result<()> {
+        let path = Self::path(root_path);
+        let contents = format!("{}\n{}", LOCK_FILE_HEADER, toml::to_string(self)?);
+        fs::write(&path, contents)
+            .with_context(|| format!("Unable to write lock file {}", path.display()))
+    }
+
+    /// Adds a package, replacing any package of the same name.
+    pub fn insert(&mut self, package: LockedPackage) {
+        match self
+            .packages
+            .binary_search_by(|other| other.name.cmp(&package.name))
+        {
+            Ok(index) => self.packages[index] = package,
+            Err(index) => self.packages.insert(index, package),
+        }
+    }
+
+    /// Returns the commit pinned for a git dependency, as long as its url, revision and
+    /// subdirectory did not change since.
+    pub fn git_commit(&self, git_info: &GitInfo) -> Option<&str> {
+        let subdir = git_info.subdir.to_string_lossy();
+        self.packages
+            .iter()
+            .find(|package| {
+                package.git.as_deref() == Some(git_info.git_url.as_str())
+                    && package.rev.as_deref() == Some(git_info.git_rev.as_str())
+                    && package.subdir.as_deref() == Some(subdir.as_ref())
+            })
+            .and_then(|package| package.commit.as_deref())
+    }
+
+    /// Describes the packages of `other` which are not pinned the same way in this lock file.
+    pub fn drift(&self, other: &LockFile) -> Vec<String> {
+        other
+            .packages
+            .iter()
+            .filter_map(|package| {
+                match self
+                    .packages
+                    .iter()
+                    .find(|locked| locked.name == package.name)
+                {
+                    None => Some(format!(
+                        "dependency '{}' is not locked, but resolved to {}",
+                        package.name, package
+                    )),
+                    Some(locked) if locked != package => Some(format!(
+                        "dependency '{}' is locked to {}, but resolved to {}",
+                        package.name, locked, package
+                    )),
+                    Some(_) => None,
+                }
+            })
+            .collect()
+    }
+}
+
+impl LockedPackage {
+    pub fn git(
+        name: PackageName,
+        git_info: &GitInfo,
+        commit: String,
+        digest: PackageDigest,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            git: Some(git_info.git_url.to_string()),
+            rev: Some(git_info.git_rev.to_string()),
+            subdir: Some(git_info.subdir.to_string_lossy().to_string()),
+            commit: Some(commit),
+            node: None,
+            address: None,
+            digest: digest.to_string(),
+        }
+    }
+
+    pub fn on_chain(name: PackageName, node_info: &CustomDepInfo, digest: PackageDigest) -> Self {
+        Self {
+            name: name.to_string(),
+            git: None,
+            rev: None,
+            subdir: None,
+            commit: None,
+            node: Some(node_info.node_url.to_string()),
+            address: Some(node_info.package_address.to_string()),
+            digest: digest.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for LockedPackage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let (Some(git), Some(commit)) = (&self.git, &self.commit) {
+            write!(f, "commit {} of {}", commit, git)?;
+        } else if let (Some(node), Some(address)) = (&self.node, &self.address) {
+            write!(f, "package at {} on {}", address, node)?;
+        }
+        write!(f, " with digest {}", self.digest)
+    }
+}
@@ -4,10 +4,13 @@
 
 use crate::{
     package_hooks,
-    resolution::digest::compute_digest,
+    resolution::{
+        digest::compute_digest,
+        lock_file::{LockFile, LockedPackage},
+    },
     source_package::{
         layout::SourcePackageLayout,
-        manifest_parser::{parse_move_manifest_string, parse_source_manifest},
+        manifest_parser::{git_repo_cache_path, parse_move_manifest_string, parse_source_manifest},
         parsed_manifest::{
             Dependencies, Dependency, FileName, NamedAddress, PackageDigest, PackageName,
             SourceManifest, SubstOrRename,
@@ -235,24 +238,9 @@ impl ResolvingGraph {
             })
             .collect();
 
-        // include dev dependencies if in dev mode
-        let additional_deps = if self.build_options.dev_mode {
-            package.dev_dependencies.clone()
-        } else {
-            BTreeMap::new()
-        };
-
-        for (dep_name, mut dep) in package
-            .dependencies
-            .clone()
-            .into_iter()
-            .chain(additional_deps.into_iter())
+        for (dep_name, dep) in
+            Self::package_dependencies(&package, self.build_options.dev_mode, override_std)
         {
-            if let Some(std_version) = &override_std {
-                if let Some(std_lib) = StdLib::from_package_name(dep_name) {
-                    dep = std_lib.dependency(std_version);
-                }
-            }
             let dep_node_id = self.get_or_add_node(dep_name).with_context(|| {
                 format!(
                     "Cycle between packages {} and {} found",
@@ -406,10 +394,14 @@ impl ResolvingGraph {
         override_std: &Option<StdVersion>,
         writer: &mut W,
     ) -> Result<(Renaming, ResolvingTable)> {
+        let locked_commit =
+            Self::locked_commit(&dep, &self.build_options, &self.root_package_path)?;
+        let dep = Self::locked_dependency(dep, locked_commit.as_deref());
         Self::download_and_update_if_remote(
             dep_name_in_pkg,
             &dep,
             self.build_options.skip_fetch_latest_git_deps,
+            locked_commit.as_deref(),
             writer,
         )?;
         let (dep_package, dep_package_dir) =
@@ -554,10 +546,13 @@ impl ResolvingGraph {
         };
 
         for (dep_name, dep) in manifest.dependencies.iter().chain(additional_deps.iter()) {
+            let locked_commit = Self::locked_commit(dep, build_options, root_path)?;
+            let dep = &Self::locked_dependency(dep.clone(), locked_commit.as_deref());
             Self::download_and_update_if_remote(
                 *dep_name,
                 dep,
                 build_options.skip_fetch_latest_git_deps,
+                locked_commit.as_deref(),
                 writer,
             )?;
 
@@ -570,19 +565,52 @@ impl ResolvingGraph {
         Ok(())
     }
 
+    /// Returns the commit the lock file of the root package pins a git dependency to, unless
+    /// the dependencies are being updated.
+    fn locked_commit(
+        dep: &Dependency,
+        build_options: &BuildConfig,
+        root_path: &Path,
+    ) -> Result<Option<String>> {
+        let Some(git_info) = &dep.git_info else {
+            return Ok(None);
+        };
+        if build_options.update_deps {
+            return Ok(None);
+        }
+        Ok(LockFile::read(root_path)?.and_then(|lock_file| {
+            lock_file
+                .git_commit(git_info)
+                .map(|commit| commit.to_string())
+        }))
+    }
+
+    /// Returns the dependency to download, where a git dependency pinned to a commit is
+    /// downloaded to a directory of its own, keyed by the commit instead of the revision. The
+    /// checkout of the revision, which other packages may share, is then left alone.
+    fn locked_dependency(mut dep: Dependency, locked_commit: Option<&str>) -> Dependency {
+        if let (Some(git_info), Some(commit)) = (&mut dep.git_info, locked_commit) {
+            git_info.download_to = git_repo_cache_path(git_info.git_url.as_str(), commit);
+            dep.local = git_info.download_to.join(&git_info.subdir);
+        }
+        dep
+    }
+
     fn download_and_update_if_remote<W: Write>(
         dep_name: PackageName,
         dep: &Dependency,
         skip_fetch_latest_git_deps: bool,
+        locked_commit: Option<&str>,
         writer: &mut W,
     ) -> Result<()> {
         if let Some(git_info) = &dep.git_info {
             let git_url = git_info.git_url.as_str();
             let git_rev = git_info.git_rev.as_str();
             let git_path = &git_info.download_to.display().to_string();
+            let cached = git_info.download_to.exists();
 
             // If there is no cached dependency, download it
-            if !git_info.download_to.exists() {
+            if !cached {
                 writeln!(
                     writer,
                     "{} {}",
@@ -610,7 +638,12 @@ impl ResolvingGraph {
                             dep_name
                         )
                     })?;
-            } else if !skip_fetch_latest_git_deps {
+            }
+            if let Some(commit) = locked_commit {
+                // A locked dependency is not updated, but kept at the commit it is locked to,
+                // in the directory of that commit
+                Self::checkout_locked_commit(dep_name, git_url, git_path, commit, writer)?;
+            } else if cached && !skip_fetch_latest_git_deps {
                 // Confirm git is available.
                 confirm_git_available()?;
 
@@ -709,6 +742,111 @@ impl ResolvingGraph {
         }
         Ok(())
     }
+
+    fn checkout_locked_commit<W: Write>(
+        dep_name: PackageName,
+        git_url: &str,
+        git_path: &str,
+        commit: &str,
+        writer: &mut W,
+    ) -> Result<()> {
+        confirm_git_available()?;
+        if git_head_commit(Path::new(git_path))? == commit {
+            return Ok(());
+        }
+
+        // Fetch the commit first if it is not in the cached repository yet
+        let has_commit = Command::new("git")
+            .args([
+                "-C",
+                git_path,
+                "cat-file",
+                "-e",
+                &format!("{}^{{commit}}", commit),
+            ])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success());
+        if !has_commit {
+            writeln!(
+                writer,
+                "{} {}",
+                "UPDATING GIT DEPENDENCY".bold().green(),
+                git_url,
+            )?;
+            let status = Command::new("git")
+                .args(["-C", git_path, "fetch", "origin"])
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()
+                .map_err(|_| {
+                    anyhow::anyhow!(
+                        "Failed to fetch locked commit '{}' for package '{}'",
+                        commit,
+                        dep_name
+                    )
+                })?;
+            if !status.success() {
+                bail!(
+                    "Failed to fetch locked commit '{}' for package '{}' | Exit status: {}",
+                    commit,
+                    dep_name,
+                    status
+                );
+            }
+        }
+
+        let status = Command::new("git")
+            .args(["-C", git_path, "checkout", "--force", commit])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .map_err(|_| {
+                anyhow::anyhow!(
+                    "Failed to checkout locked commit '{}' for package '{}'",
+                    commit,
+                    dep_name
+                )
+            })?;
+        if !status.success() {
+            bail!(
+                "Failed to checkout locked commit '{}' for package '{}' | Exit status: {}",
+                commit,
+                dep_name,
+                status
+            );
+        }
+        Ok(())
+    }
+
+    /// Returns the dependencies of a package, including its dev dependencies in dev mode, with
+    /// the standard library replaced by the given version.
+    fn package_dependencies(
+        package: &SourceManifest,
+        dev_mode: bool,
+        override_std: &Option<StdVersion>,
+    ) -> Vec<(PackageName, Dependency)> {
+        let additional_deps = if dev_mode {
+            package.dev_dependencies.clone()
+        } else {
+            BTreeMap::new()
+        };
+        package
+            .dependencies
+            .clone()
+            .into_iter()
+            .chain(additional_deps)
+            .map(
+                |(dep_name, dep)| match (override_std, StdLib::from_package_name(dep_name)) {
+                    (Some(std_version), Some(std_lib)) => {
+                        (dep_name, std_lib.dependency(std_version))
+                    },
+                    _ => (dep_name, dep),
+                },
+            )
+            .collect()
+    }
 }
 
 impl ResolvingPackage {
@@ -876,6 +1014,64 @@ impl ResolvedGraph {
             .collect()
     }
 
+    /// Pins the git and on-chain dependencies of the graph in the lock file of the root
+    /// package. Packages which are pinned already but not part of the graph (e.g., dev
+    /// dependencies outside of dev mode) are kept, unless the dependencies are being updated.
+    /// In a `--locked` build, fails instead if the lock file does not pin a dependency to what
+    /// it resolved to. Only called when the dependencies are updated, or in a `--locked`
+    /// build, so that other builds never write the lock file.
+    pub fn update_lock_file(&self) -> Result<()> {
+        let existing = LockFile::read(&self.root_package_path)?;
+        let mut lock_file = if self.build_options.update_deps {
+            LockFile::default()
+        } else {
+            existing.clone().unwrap_or_default()
+        };
+        let mut seen = BTreeSet::new();
+        for package in self.package_table.values() {
+            for (dep_name, dep) in ResolvingGraph::package_dependencies(
+                &package.source_package,
+                self.build_options.dev_mode,
+                &self.build_options.override_std,
+            ) {
+                if (dep.git_info.is_none() && dep.node_info.is_none()) || !seen.insert(dep_name) {
+                    continue;
+                }
+                // The digest does not depend on the mode, so that it is the same for all builds
+                let dep_package = self.get_package(&dep_name);
+                let digest = ResolvingPackage::get_package_digest_for_config(
+                    &dep_package.package_path,
+                    &BuildConfig::default(),
+                )?;
+                if let Some(git_info) = &dep.git_info {
+                    // The package is in the checkout it was resolved from, which is keyed by
+                    // the commit if the dependency is locked
+                    let commit = git_head_commit(&dep_package.package_path)?;
+                    lock_file.insert(LockedPackage::git(dep_name, git_info, commit, digest));
+                } else if let Some(node_info) = &dep.node_info {
+                    lock_file.insert(LockedPackage::on_chain(dep_name, node_info, digest));
+                }
+            }
+        }
+
+        let unchanged = match &existing {
+            Some(existing) => existing == &lock_file,
+            None => lock_file.packages.is_empty(),
+        };
+        if unchanged {
+            return Ok(());
+        }
+        if self.build_options.locked {
+            bail!(
+                "The lock file of package '{}' is out of date:\n{}\n\
+                To update it, update the dependencies of the package",
+                self.root_package.package.name,
+                existing.unwrap_or_default().drift(&lock_file).join("\n")
+            )
+        }
+        lock_file.write(&self.root_package_path)
+    }
+
     pub fn contains_renaming(&self) -> Option<PackageName> {
         // Make sure no renamings have been performed
         for (pkg_name, pkg) in self.package_table.iter() {
@@ -961,6 +1157,24 @@ impl ResolvedPackage {
     }
 }
 
+/// Returns the commit checked out in a git repository
+fn git_head_commit(git_path: &Path) -> Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(git_path)
+        .args(["rev-parse", "HEAD"])
+        .output()
+        .with_context(|| format!("Failed to get the commit of {}", git_path.display()))?;
+    if !output.status.success() {
+        bail!(
+            "Failed to get the commit of {} | Exit status: {}",
+            git_path.display(),
+            output.status
+        );
+    }
+    Ok(String::from_utf8(output.stdout)?.trim().to_string())
+}
+
 fn confirm_git_available() -> Result<()> {
     match Command::new("git").arg("--version").output() {
         Ok(_) => Ok(()),
@@ -0,0 +1,158 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use move_package::{
+    compilation::{compiled_package::CompiledPackage, package_layout::CompiledPackageLayout},
+    BuildConfig,
+};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+use tempfile::tempdir;
+
+/// Writes the package `Root`, which depends on `Mid`, which depends on `Leaf`.
+fn write_packages(dir: &Path, scale: u64) {
+    let write = |path: &str, contents: &str| {
+        let path = dir.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    };
+    write(
+        "Root/Move.toml",
+        "[package]\nname = \"Root\"\nversion = \"0.0.0\"\n\n\
+         [addresses]\nroot = \"0x44\"\n\n\
+         [dependencies]\nMid = { local = \"../Mid\" }\n",
+    );
+    write(
+        "Root/sources/root.move",
+        "module root::root { public fun run(): u64 { mid::mid::scaled() } }\n",
+    );
+    write(
+        "Mid/Move.toml",
+        "[package]\nname = \"Mid\"\nversion = \"0.0.0\"\n\n\
+         [addresses]\nmid = \"0x43\"\n\n\
+         [dependencies]\nLeaf = { local = \"../Leaf\" }\n",
+    );
+    write(
+        "Mid/sources/mid.move",
+        "module mid::mid { public fun scaled(): u64 { leaf::leaf::scale(leaf::leaf::value()) } }\n",
+    );
+    write(
+        "Leaf/Move.toml",
+        "[package]\nname = \"Leaf\"\nversion = \"0.0.0\"\n\n[addresses]\nleaf = \"0x42\"\n",
+    );
+    write(
+        "Leaf/sources/leaf.move",
+        &format!(
+            "module leaf::leaf {{\n\
+             public fun value(): u64 {{ 1 }}\n\
+             public inline fun scale(x: u64): u64 {{ x * {} }}\n\
+             }}\n",
+            scale
+        ),
+    );
+}
+
+fn compile(root: &Path, install_dir: &Path, force_recompilation: bool) -> CompiledPackage {
+    BuildConfig {
+        install_dir: Some(install_dir.to_path_buf()),
+        force_recompilation,
+        ..Default::default()
+    }
+    .compile_package(root, &mut Vec::new())
+    .unwrap()
+}
+
+fn module_bytes(package: &CompiledPackage, package_name: &str, module_name: &str) -> Vec<u8> {
+    package
+        .get_module_by_name(package_name, module_name)
+        .unwrap()
+        .unit
+        .serialize(None)
+}
+
+/// The cache info files of the entries of `package`, with their modification times.
+fn cache_entries(install_dir: &Path, package: &str) -> Vec<(PathBuf, SystemTime)> {
+    let package_dir = install_dir
+        .join(CompiledPackageLayout::Root.path())
+        .join(CompiledPackageLayout::CompilationCache.path())
+        .join(package);
+    let mut entries = fs::read_dir(package_dir)
+        .unwrap()
+        .map(|entry| {
+            let info = entry.unwrap().path().join("CacheInfo.yaml");
+            let modified = fs::metadata(&info).unwrap().modified().unwrap();
+            (info, modified)
+        })
+        .collect::<Vec<_>>();
+    entries.sort();
+    entries
+}
+
+#[test]
+fn unchanged_dependencies_are_loaded_from_cache() {
+    let sources = tempdir().unwrap();
+    let install_dir = tempdir().unwrap();
+    write_packages(sources.path(), 2);
+    let root = sources.path().join("Root");
+
+    let first = compile(&root, install_dir.path(), false);
+    let leaf_entries = cache_entries(install_dir.path(), "Leaf");
+    let mid_entries = cache_entries(install_dir.path(), "Mid");
+    assert_eq!(leaf_entries.len(), 1);
+    assert_eq!(mid_entries.len(), 1);
+
+    let second = compile(&root, install_dir.path(), false);
+    assert_eq!(cache_entries(install_dir.path(), "Leaf"), leaf_entries);
+    assert_eq!(cache_entries(install_dir.path(), "Mid"), mid_entries);
+    for (package_name, module_name) in [("Leaf", "leaf"), ("Mid", "mid"), ("Root", "root")] {
+        assert_eq!(
+            module_bytes(&first, package_name, module_name),
+            module_bytes(&second, package_name, module_name)
+        );
+    }
+    assert!(second
+        .get_module_by_name("Leaf", "leaf")
+        .unwrap()
+        .source_path
+        .ends_with("Leaf/sources/leaf.move"));
+}
+
+#[test]
+fn touching_a_dependency_invalidates_its_dependents() {
+    let sources = tempdir().unwrap();
+    let install_dir = tempdir().unwrap();
+    write_packages(sources.path(), 2);
+    let root = sources.path().join("Root");
+
+    let before = compile(&root, install_dir.path(), false);
+    // `Mid` inlines `Leaf::scale`, so its code changes with the code of `Leaf`
+    write_packages(sources.path(), 3);
+    let after = compile(&root, install_dir.path(), false);
+    assert_eq!(cache_entries(install_dir.path(), "Leaf").len(), 2);
+    assert_eq!(cache_entries(install_dir.path(), "Mid").len(), 2);
+    assert_ne!(
+        module_bytes(&before, "Mid", "mid"),
+        module_bytes(&after, "Mid", "mid")
+    );
+
+    // The result is the same as without the cache
+    let fresh = compile(&root, tempdir().unwrap().path(), true);
+    for (package_name, module_name) in [("Leaf", "leaf"), ("Mid", "mid"), ("Root", "root")] {
+        assert_eq!(
+            module_bytes(&after, package_name, module_name),
+            module_bytes(&fresh, package_name, module_name)
+        );
+    }
+
+    // Going back to the original code reuses the original entries
+    write_packages(sources.path(), 2);
+    let reverted = compile(&root, install_dir.path(), false);
+    assert_eq!(cache_entries(install_dir.path(), "Mid").len(), 2);
+    assert_eq!(
+        module_bytes(&before, "Mid", "mid"),
+        module_bytes(&reverted, "Mid", "mid")
+    );
+}
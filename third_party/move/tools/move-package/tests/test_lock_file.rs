@@ -0,0 +1,147 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use move_package::{
+    resolution::lock_file::LockFile, source_package::manifest_parser::git_repo_cache_path,
+    BuildConfig,
+};
+use std::{fs, path::Path, process::Command};
+use tempfile::tempdir;
+
+fn git(repo: &Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(["-c", "user.name=test", "-c", "user.email=test@test"])
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "git {:?} failed", args);
+    String::from_utf8(output.stdout).unwrap().trim().to_string()
+}
+
+fn commit_dependency(repo: &Path, value: u64) -> String {
+    fs::create_dir_all(repo.join("sources")).unwrap();
+    fs::write(
+        repo.join("Move.toml"),
+        "[package]\nname = \"Dep\"\nversion = \"0.0.0\"\n",
+    )
+    .unwrap();
+    fs::write(
+        repo.join("sources/dep.move"),
+        format!("module 0x42::dep {{ public fun f(): u64 {{ {} }} }}", value),
+    )
+    .unwrap();
+    git(repo, &["add", "."]);
+    git(repo, &[
+        "commit",
+        "--quiet",
+        "--no-gpg-sign",
+        "-m",
+        "update",
+    ]);
+    git(repo, &["rev-parse", "HEAD"])
+}
+
+fn resolve(root: &Path, config: BuildConfig) -> anyhow::Result<()> {
+    config
+        .resolution_graph_for_package(root, &mut Vec::new())
+        .map(|_| ())
+}
+
+#[test]
+fn test_lock_file() {
+    let dir = tempdir().unwrap();
+    // Git dependencies are downloaded to the Move home
+    std::env::set_var("MOVE_HOME", dir.path().join("move_home"));
+
+    let repo = dir.path().join("dep");
+    fs::create_dir_all(&repo).unwrap();
+    git(&repo, &["init", "--quiet"]);
+    git(&repo, &["checkout", "--quiet", "-b", "main"]);
+    let first_commit = commit_dependency(&repo, 1);
+
+    let root = dir.path().join("root");
+    fs::create_dir_all(root.join("sources")).unwrap();
+    fs::write(
+        root.join("Move.toml"),
+        format!(
+            "[package]\nname = \"Root\"\nversion = \"0.0.0\"\n\n\
+            [dependencies]\nDep = {{ git = \"file://{}\", rev = \"main\" }}\n",
+            repo.display()
+        ),
+    )
+    .unwrap();
+
+    // Builds do not write the lock file
+    resolve(&root, BuildConfig::default()).unwrap();
+    assert!(LockFile::read(&root).unwrap().is_none());
+
+    // Updating the dependencies pins the dependency
+    let update = || BuildConfig {
+        update_deps: true,
+        ..BuildConfig::default()
+    };
+    resolve(&root, update()).unwrap();
+    let lock_file = LockFile::read(&root).unwrap().unwrap();
+    assert_eq!(lock_file.packages.len(), 1);
+    assert_eq!(lock_file.packages[0].name, "Dep");
+    assert_eq!(lock_file.packages[0].rev.as_deref(), Some("main"));
+    assert_eq!(
+        lock_file.packages[0].commit.as_deref(),
+        Some(first_commit.as_str())
+    );
+
+    // A new commit of the dependency is not picked up by later builds, which check out the
+    // pinned commit in a directory of its own, leaving the checkout of the revision alone
+    let second_commit = commit_dependency(&repo, 2);
+    let url = format!("file://{}", repo.display());
+    let rev_checkout = git_repo_cache_path(&url, "main");
+    let commit_checkout = git_repo_cache_path(&url, &first_commit);
+    git(&rev_checkout, &["fetch", "--quiet", "origin"]);
+    git(&rev_checkout, &[
+        "reset",
+        "--quiet",
+        "--hard",
+        "origin/main",
+    ]);
+    resolve(&root, BuildConfig::default()).unwrap();
+    resolve(&root, BuildConfig {
+        locked: true,
+        ..BuildConfig::default()
+    })
+    .unwrap();
+    assert_eq!(LockFile::read(&root).unwrap().unwrap(), lock_file);
+    assert_eq!(git(&commit_checkout, &["rev-parse", "HEAD"]), first_commit);
+    assert_eq!(git(&rev_checkout, &["rev-parse", "HEAD"]), second_commit);
+
+    // Updating the dependencies pins the new commit
+    resolve(&root, update()).unwrap();
+    let updated_lock_file = LockFile::read(&root).unwrap().unwrap();
+    assert_eq!(
+        updated_lock_file.packages[0].commit.as_deref(),
+        Some(second_commit.as_str())
+    );
+    assert_ne!(
+        updated_lock_file.packages[0].digest,
+        lock_file.packages[0].digest
+    );
+
+    // A locked build fails if the dependency does not match the lock file, which other builds
+    // leave as it is
+    let mut stale_lock_file = updated_lock_file.clone();
+    stale_lock_file.packages[0].digest = "0".to_string();
+    stale_lock_file.write(&root).unwrap();
+    let error = resolve(&root, BuildConfig {
+        locked: true,
+        ..BuildConfig::default()
+    })
+    .unwrap_err();
+    assert!(format!("{:#}", error).contains("out of date"));
+    resolve(&root, BuildConfig::default()).unwrap();
+    assert_eq!(LockFile::read(&root).unwrap().unwrap(), stale_lock_file);
+
+    // Updating the dependencies fixes the lock file
+    resolve(&root, update()).unwrap();
+    assert_eq!(LockFile::read(&root).unwrap().unwrap(), updated_lock_file);
+}
@@ -0,0 +1,7 @@
+module 0x42::foo {
+
+    // NOTE: Functions are 'native' for simplicity. They may or may not be native in actuality.
+    #[native_interface]
+    native public fun foo(): u8;
+
+}
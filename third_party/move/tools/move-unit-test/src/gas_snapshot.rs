@@ -0,0 +1,197 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Gas snapshots record the gas used by each passing test, one test per line:
+//!
+//! ```text
+//! 0x1::coin::test_transfer (gas: 1234)
+//! ```
+//!
+//! Checked into the repository next to a package, a snapshot makes changes in gas consumption
+//! visible in diffs, and can be checked to catch regressions.
+
+use std::{
+    collections::BTreeMap,
+    fmt, fs,
+    io::{Error, ErrorKind, Result},
+    path::Path,
+};
+
+/// The default name of the gas snapshot file of a package.
+pub const GAS_SNAPSHOT_FILE_NAME: &str = ".gas-snapshot";
+
+/// The gas used by tests, by their qualified (`<addr>::<module_name>::<fn_name>`) name.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GasSnapshot(pub BTreeMap<String, u64>);
+
+/// A difference in the gas used by a test between two snapshots.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GasChange {
+    pub test_name: String,
+    /// The gas used in the baseline snapshot, if the test was in it.
+    pub before: Option<u64>,
+    /// The gas used in the new snapshot, if the test is still in it.
+    pub after: Option<u64>,
+}
+
+impl GasSnapshot {
+    pub fn parse(contents: &str) -> Result<Self> {
+        let mut entries = BTreeMap::new();
+        for (idx, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let entry = line
+                .strip_suffix(')')
+                .and_then(|line| line.rsplit_once(" (gas: "))
+                .and_then(|(name, gas)| Some((name.to_string(), gas.parse().ok()?)));
+            match entry {
+                Some((name, gas)) => {
+                    entries.insert(name, gas);
+                },
+                None => {
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        format!(
+                            "Invalid gas snapshot entry on line {}: `{}`, expected \
+                             `<test name> (gas: <gas used>)`",
+                            idx + 1,
+                            line
+                        ),
+                    ))
+                },
+            }
+        }
+        Ok(Self(entries))
+    }
+
+    pub fn read(path: &Path) -> Result<Self> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        fs::write(path, self.to_string())
+    }
+
+    /// Adds the entries of `other` for tests which are not in this snapshot, e.g. to keep the
+    /// entries of tests which were filtered out of a run.
+    pub fn extend_missing(&mut self, other: &GasSnapshot) {
+        for (name, gas) in &other.0 {
+            self.0.entry(name.clone()).or_insert(*gas);
+        }
+    }
+
+    /// Returns the tests whose gas usage differs from `baseline`, including tests which were
+    /// added or removed.
+    pub fn diff(&self, baseline: &GasSnapshot) -> Vec<GasChange> {
+        let mut names: Vec<&String> = self.0.keys().chain(baseline.0.keys()).collect();
+        names.sort();
+        names.dedup();
+        names
+            .into_iter()
+            .filter_map(|name| {
+                let before = baseline.0.get(name).copied();
+                let after = self.0.get(name).copied();
+                (before != after).then(|| GasChange {
+                    test_name: name.clone(),
+                    before,
+                    after,
+                })
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for GasSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, gas) in &self.0 {
+            writeln!(f, "{} (gas: {})", name, gas)?;
+        }
+        Ok(())
+    }
+}
+
+impl GasChange {
+    /// Returns true if the test now uses more than `tolerance` percent more gas than before.
+    pub fn is_regression(&self, tolerance: f64) -> bool {
+        match (self.before, self.after) {
+            (Some(before), Some(after)) => after as f64 > before as f64 * (1.0 + tolerance / 100.0),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for GasChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.before, self.after) {
+            (Some(before), Some(after)) => {
+                let delta = after as i128 - before as i128;
+                write!(
+                    f,
+                    "{} (gas: {} → {}, {:+}",
+                    self.test_name, before, after, delta
+                )?;
+                if before > 0 {
+                    write!(f, ", {:+.2}%", delta as f64 * 100.0 / before as f64)?;
+                }
+                write!(f, ")")
+            },
+            (None, Some(after)) => write!(f, "{} (gas: {}, new)", self.test_name, after),
+            (Some(before), None) => write!(f, "{} (gas: {}, removed)", self.test_name, before),
+            (None, None) => write!(f, "{}", self.test_name),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(entries: &[(&str, u64)]) -> GasSnapshot {
+        GasSnapshot(
+            entries
+                .iter()
+                .map(|(name, gas)| (name.to_string(), *gas))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn parse_round_trip() {
+        let snapshot = snapshot(&[("0x1::m::a", 10), ("0x1::m::b", 200)]);
+        let contents = snapshot.to_string();
+        assert_eq!(contents, "0x1::m::a (gas: 10)\n0x1::m::b (gas: 200)\n");
+        assert_eq!(GasSnapshot::parse(&contents).unwrap(), snapshot);
+    }
+
+    #[test]
+    fn parse_invalid_entry() {
+        let err = GasSnapshot::parse("0x1::m::a (gas: 10)\n0x1::m::b 200\n").unwrap_err();
+        assert!(err.to_string().contains("line 2"));
+    }
+
+    #[test]
+    fn diff_and_regressions() {
+        let baseline = snapshot(&[("a", 100), ("b", 100), ("c", 100), ("d", 100)]);
+        let current = snapshot(&[("a", 100), ("b", 104), ("c", 90), ("e", 5)]);
+        let changes = current.diff(&baseline);
+        assert_eq!(
+            changes
+                .iter()
+                .map(|change| change.test_name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["b", "c", "d", "e"]
+        );
+        let regressions = |tolerance| {
+            changes
+                .iter()
+                .filter(|change| change.is_regression(tolerance))
+                .map(|change| change.test_name.as_str())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(regressions(0.0), vec!["b"]);
+        assert!(regressions(5.0).is_empty());
+        assert_eq!(changes[0].to_string(), "b (gas: 100 → 104, +4, +4.00%)");
+    }
+}
@@ -3,10 +3,15 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub mod extensions;
+pub mod gas_snapshot;
+pub mod property;
 pub mod test_reporter;
 pub mod test_runner;
 
-use crate::test_runner::TestRunner;
+use crate::{
+    gas_snapshot::GasSnapshot, property::PropertyTestingConfig, test_reporter::TestResults,
+    test_runner::TestRunner,
+};
 use clap::*;
 use legacy_move_compiler::{
     self,
@@ -23,6 +28,7 @@ use std::{
     collections::BTreeMap,
     io::{Result, Write},
     marker::Send,
+    path::{Path, PathBuf},
     sync::Mutex,
 };
 use test_reporter::UnitTestFactory;
@@ -102,6 +108,45 @@ pub struct UnitTestingConfig {
     /// Verbose mode
     #[clap(short = 'v', long = "verbose")]
     pub verbose: bool,
+
+    /// Address (e.g., `127.0.0.1:4711`) on which to wait for a Debug Adapter Protocol
+    /// client before running the tests. Tests run on a single thread while debugging.
+    /// Requires the `debugging` feature.
+    #[clap(long = "debug-adapter-address")]
+    pub debug_adapter_address: Option<String>,
+
+    /// Number of inputs to run each test with generated parameters with
+    #[clap(long = "fuzz-iterations", default_value_t = property::DEFAULT_ITERATIONS)]
+    pub fuzz_iterations: u64,
+
+    /// Seed for the inputs of tests with generated parameters, which is reported when a test
+    /// fails. The default seed makes runs reproducible.
+    #[clap(long = "fuzz-seed", default_value_t = property::DEFAULT_SEED)]
+    pub fuzz_seed: u64,
+
+    /// Derive inputs of tests with generated parameters from earlier inputs which covered new
+    /// instructions, instead of only generating fresh inputs.
+    #[clap(long = "coverage-guided")]
+    pub coverage_guided: bool,
+
+    /// File to write the gas used by each passing test to, reporting changes to the gas used
+    /// since the file was last written
+    #[clap(long = "gas-snapshot")]
+    pub gas_snapshot: Option<PathBuf>,
+
+    /// Compare the gas used by tests against the gas snapshot instead of writing it, failing
+    /// if a test uses more gas than recorded
+    #[clap(long = "gas-snapshot-check", requires = "gas_snapshot")]
+    pub check_gas_snapshot: bool,
+
+    /// Percentage by which a test may exceed the gas recorded in the snapshot when checking it
+    #[clap(long = "gas-tolerance", default_value_t = 0.0)]
+    pub gas_tolerance: f64,
+
+    /// Whether the gas used by tests is metered with a gas schedule. Tests with a
+    /// `#[max_gas = N]` bound fail if it is not, since their bound cannot be checked.
+    #[clap(skip = true)]
+    pub meters_gas: bool,
 }
 
 fn format_module_id(module_id: &ModuleId) -> String {
@@ -127,6 +172,14 @@ impl Default for UnitTestingConfig {
             verbose: false,
             list: false,
             named_address_values: vec![],
+            debug_adapter_address: None,
+            fuzz_iterations: property::DEFAULT_ITERATIONS,
+            fuzz_seed: property::DEFAULT_SEED,
+            coverage_guided: false,
+            gas_snapshot: None,
+            check_gas_snapshot: false,
+            gas_tolerance: 0.0,
+            meters_gas: true,
         }
     }
 }
@@ -210,9 +263,17 @@ impl UnitTestingConfig {
             return Ok((shared_writer.into_inner().unwrap(), true));
         }
 
+        // The debugger is detached once the session is dropped (after the tests ran)
+        let debug_session = self.start_debug_session(&test_plan)?;
+        let num_threads = if debug_session.is_some() {
+            1
+        } else {
+            self.num_threads
+        };
+
         writeln!(shared_writer.lock().unwrap(), "Running Move unit tests")?;
         let mut test_runner = TestRunner::new(
-            self.num_threads,
+            num_threads,
             self.report_storage_on_error,
             self.report_stacktrace_on_abort,
             test_plan,
@@ -220,7 +281,13 @@ impl UnitTestingConfig {
             genesis_state,
             self.verbose,
         )
-        .unwrap();
+        .unwrap()
+        .with_property_testing(PropertyTestingConfig {
+            iterations: self.fuzz_iterations,
+            seed: self.fuzz_seed,
+            coverage_guided: self.coverage_guided,
+        })
+        .with_gas_metering(self.meters_gas);
 
         if let Some(filter_str) = &self.filter {
             test_runner.filter(filter_str)
@@ -235,11 +302,125 @@ impl UnitTestingConfig {
             test_results.report_goldens(&shared_writer)?;
         }
 
-        let ok = test_results.summarize(&shared_writer)?;
+        let gas_snapshot_ok = match &self.gas_snapshot {
+            Some(path) => self.report_gas_snapshot(path, &test_results, &shared_writer)?,
+            None => true,
+        };
+
+        let ok = test_results.summarize(&shared_writer)? && gas_snapshot_ok;
 
         let writer = shared_writer.into_inner().unwrap();
         Ok((writer, ok))
     }
+
+    /// Writes the gas snapshot of the test results to `path`, or checks them against it.
+    /// Returns `false` if the check found gas regressions.
+    fn report_gas_snapshot<W: Write>(
+        &self,
+        path: &Path,
+        test_results: &TestResults,
+        writer: &Mutex<W>,
+    ) -> Result<bool> {
+        let mut snapshot = test_results.gas_snapshot();
+        let baseline = if path.exists() {
+            Some(GasSnapshot::read(path)?)
+        } else {
+            None
+        };
+
+        if self.check_gas_snapshot {
+            let Some(baseline) = baseline else {
+                writeln!(
+                    writer.lock().unwrap(),
+                    "\nNo gas snapshot found at {} to check against",
+                    path.display()
+                )?;
+                return Ok(false);
+            };
+            // Tests which did not run are not compared
+            let changes: Vec<_> = snapshot
+                .diff(&baseline)
+                .into_iter()
+                .filter(|change| change.after.is_some())
+                .collect();
+            let regressions = changes
+                .iter()
+                .filter(|change| change.is_regression(self.gas_tolerance))
+                .count();
+            if !changes.is_empty() {
+                writeln!(writer.lock().unwrap(), "\nGas changes:")?;
+                for change in &changes {
+                    writeln!(writer.lock().unwrap(), "  {}", change)?;
+                }
+            }
+            if regressions > 0 {
+                writeln!(
+                    writer.lock().unwrap(),
+                    "Gas snapshot check failed: {} test(s) used more than {}% more gas than \
+                     recorded in {}",
+                    regressions,
+                    self.gas_tolerance,
+                    path.display()
+                )?;
+            }
+            return Ok(regressions == 0);
+        }
+
+        // When only some tests ran, keep the entries of the others
+        if let Some(baseline) = &baseline {
+            if self.filter.is_some() {
+                snapshot.extend_missing(baseline);
+            }
+            let changes = snapshot.diff(baseline);
+            if !changes.is_empty() {
+                writeln!(writer.lock().unwrap(), "\nGas changes:")?;
+                for change in &changes {
+                    writeln!(writer.lock().unwrap(), "  {}", change)?;
+                }
+            }
+        }
+        snapshot.write(path)?;
+        writeln!(
+            writer.lock().unwrap(),
+            "Wrote gas snapshot to {}",
+            path.display()
+        )?;
+        Ok(true)
+    }
+
+    /// Waits for a debug adapter client to connect (if an address was given), and
+    /// attaches a debugger that resolves source locations using the test plan.
+    #[cfg(feature = "debugging")]
+    fn start_debug_session(&self, test_plan: &TestPlan) -> Result<Option<move_dap::DebugSession>> {
+        let Some(address) = &self.debug_adapter_address else {
+            return Ok(None);
+        };
+
+        let mut resolver = move_dap::SourceResolver::new();
+        for (file_name, contents) in test_plan.files.values() {
+            resolver.add_source_file(std::path::Path::new(file_name.as_str()), contents);
+        }
+        for module_info in test_plan.module_info.values() {
+            if let legacy_move_compiler::unit_test::NamedOrBytecodeModule::Named(named_module) =
+                module_info
+            {
+                resolver.add_module(named_module.module.clone(), named_module.source_map.clone());
+            }
+        }
+        move_dap::DebugSession::start(address, resolver)
+            .map(Some)
+            .map_err(|error| std::io::Error::other(format!("{:#}", error)))
+    }
+
+    #[cfg(not(feature = "debugging"))]
+    fn start_debug_session(&self, _test_plan: &TestPlan) -> Result<Option<()>> {
+        if self.debug_adapter_address.is_some() {
+            return Err(std::io::Error::other(
+                "Debugging requires building with the `debugging` feature",
+            ));
+        }
+        Ok(None)
+    }
 }
 
 #[test]
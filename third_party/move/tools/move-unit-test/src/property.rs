@@ -0,0 +1,468 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Generation and shrinking of the inputs of property-based tests, i.e., of `#[test]` functions
+//! marked with `#[fuzz]` whose parameters are not all assigned in the test attribute. Those
+//! parameters get generated values.
+
+use legacy_move_compiler::unit_test::{GeneratedParameter, TestCase};
+use move_core_types::{
+    account_address::AccountAddress,
+    language_storage::ModuleId,
+    u256::U256,
+    value::{MoveTypeLayout, MoveValue},
+};
+use move_vm_runtime::execution_trace::TraceEvent;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::collections::BTreeSet;
+
+/// The default number of inputs each property-based test is run with
+pub const DEFAULT_ITERATIONS: u64 = 100;
+
+/// The default seed inputs are generated from
+pub const DEFAULT_SEED: u64 = 0;
+
+/// The maximal length of generated vectors
+const MAX_VECTOR_LENGTH: usize = 16;
+
+/// The maximal number of times a test is run while shrinking a failing input
+const MAX_SHRINK_RUNS: usize = 1_000;
+
+/// Configuration of property-based tests
+#[derive(Debug, Clone)]
+pub struct PropertyTestingConfig {
+    /// The number of inputs to run each test with
+    pub iterations: u64,
+    /// The seed inputs are generated from
+    pub seed: u64,
+    /// Whether to derive new inputs from inputs which increased instruction coverage
+    pub coverage_guided: bool,
+}
+
+impl Default for PropertyTestingConfig {
+    fn default() -> Self {
+        Self {
+            iterations: DEFAULT_ITERATIONS,
+            seed: DEFAULT_SEED,
+            coverage_guided: false,
+        }
+    }
+}
+
+impl PropertyTestingConfig {
+    /// The seed of the inputs of a single test, so that its inputs do not depend on the
+    /// order tests are run in.
+    pub fn test_seed(&self, module_id: &ModuleId, test_name: &str) -> u64 {
+        // FNV-1a, which is stable across platforms and releases
+        format!("{}::{}", module_id, test_name)
+            .bytes()
+            .fold(self.seed ^ 0xCBF2_9CE4_8422_2325, |hash, byte| {
+                (hash ^ byte as u64).wrapping_mul(0x100_0000_01B3)
+            })
+    }
+}
+
+pub struct InputGenerator {
+    rng: StdRng,
+}
+
+impl InputGenerator {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Generates the values of the generated parameters of a test for its next run: either
+    /// fresh values, or (half of the time) values derived from a random input of `corpus` by
+    /// mutating one of its values.
+    pub fn next_input(
+        &mut self,
+        parameters: &[GeneratedParameter],
+        corpus: &[Vec<MoveValue>],
+    ) -> Vec<MoveValue> {
+        if corpus.is_empty() || self.rng.gen_bool(0.5) {
+            return parameters
+                .iter()
+                .map(|param| self.generate(&param.layout))
+                .collect();
+        }
+        let mut input = corpus[self.rng.gen_range(0, corpus.len())].clone();
+        let index = self.rng.gen_range(0, parameters.len());
+        input[index] = self.mutate(&input[index], &parameters[index].layout);
+        input
+    }
+
+    /// Generates a value of the given layout, which must be one of the layouts the test plan
+    /// builder generates parameters for.
+    pub fn generate(&mut self, layout: &MoveTypeLayout) -> MoveValue {
+        match layout {
+            MoveTypeLayout::Bool => MoveValue::Bool(self.rng.gen_bool(0.5)),
+            MoveTypeLayout::Address => MoveValue::Address(self.generate_address()),
+            MoveTypeLayout::Signer => MoveValue::Signer(self.generate_address()),
+            MoveTypeLayout::Vector(elem) => {
+                let len = self.rng.gen_range(0, MAX_VECTOR_LENGTH + 1);
+                MoveValue::Vector((0..len).map(|_| self.generate(elem)).collect())
+            },
+            _ => {
+                let bits = integer_bits(layout).expect("layout of generated parameter");
+                integer_value(layout, self.generate_integer(bits))
+            },
+        }
+    }
+
+    /// Derives a new value from `value`, e.g., by flipping a bit of an integer, or by adding or
+    /// removing an element of a vector.
+    pub fn mutate(&mut self, value: &MoveValue, layout: &MoveTypeLayout) -> MoveValue {
+        match (value, layout) {
+            (MoveValue::Bool(b), _) => MoveValue::Bool(!b),
+            (MoveValue::Vector(elems), MoveTypeLayout::Vector(elem_layout)) => {
+                let mut elems = elems.clone();
+                match self.rng.gen_range(0, 3) {
+                    0 if elems.len() < MAX_VECTOR_LENGTH => {
+                        let index = self.rng.gen_range(0, elems.len() + 1);
+                        elems.insert(index, self.generate(elem_layout));
+                    },
+                    1 if !elems.is_empty() => {
+                        let index = self.rng.gen_range(0, elems.len());
+                        elems.remove(index);
+                    },
+                    _ if !elems.is_empty() => {
+                        let index = self.rng.gen_range(0, elems.len());
+                        elems[index] = self.mutate(&elems[index], elem_layout);
+                    },
+                    _ => return self.generate(layout),
+                }
+                MoveValue::Vector(elems)
+            },
+            _ => match (integer_bits(layout), as_u256(value)) {
+                (Some(bits), Some(n)) => {
+                    let mutated = match self.rng.gen_range(0, 3) {
+                        0 => n ^ (U256::one() << self.rng.gen_range(0, bits)),
+                        1 => n.wrapping_add(U256::from(self.rng.gen_range(1u64, 17))),
+                        _ => n.wrapping_sub(U256::from(self.rng.gen_range(1u64, 17))),
+                    };
+                    integer_value(layout, mutated & integer_max(bits))
+                },
+                // Addresses and signers are regenerated
+                _ => self.generate(layout),
+            },
+        }
+    }
+
+    /// Generates an integer of the given number of bits, favouring edge cases and small values:
+    /// the number of significant bits is chosen uniformly.
+    fn generate_integer(&mut self, bits: u32) -> U256 {
+        let max = integer_max(bits);
+        if self.rng.gen_range(0, 5) == 0 {
+            return match self.rng.gen_range(0, 4) {
+                0 => U256::zero(),
+                1 => U256::one(),
+                2 => max,
+                _ => max - U256::one(),
+            };
+        }
+        let significant_bits = self.rng.gen_range(0, bits + 1);
+        if significant_bits == 0 {
+            return U256::zero();
+        }
+        let mut bytes = [0u8; 32];
+        self.rng.fill(&mut bytes[..]);
+        U256::from_le_bytes(&bytes) >> (256 - significant_bits) as u8
+    }
+
+    /// Generates an address, favouring the small addresses tests commonly use.
+    fn generate_address(&mut self) -> AccountAddress {
+        let mut bytes = [0u8; AccountAddress::LENGTH];
+        if self.rng.gen_bool(0.5) {
+            bytes[AccountAddress::LENGTH - 1] = self.rng.gen_range(0, 16);
+        } else {
+            self.rng.fill(&mut bytes[..]);
+        }
+        AccountAddress::new(bytes)
+    }
+}
+
+/// Returns simpler values than `value` to try while shrinking a failing input, simplest first.
+pub fn shrink_candidates(value: &MoveValue) -> Vec<MoveValue> {
+    match value {
+        MoveValue::Bool(true) => vec![MoveValue::Bool(false)],
+        MoveValue::Address(addr) | MoveValue::Signer(addr) => {
+            [AccountAddress::ZERO, AccountAddress::ONE]
+                .into_iter()
+                .filter(|candidate| candidate < addr)
+                .map(|candidate| match value {
+                    MoveValue::Signer(_) => MoveValue::Signer(candidate),
+                    _ => MoveValue::Address(candidate),
+                })
+                .collect()
+        },
+        MoveValue::Vector(elems) if !elems.is_empty() => {
+            let mut candidates = vec![
+                MoveValue::Vector(vec![]),
+                MoveValue::Vector(elems[..elems.len() / 2].to_vec()),
+            ];
+            for index in 0..elems.len() {
+                let mut fewer = elems.clone();
+                fewer.remove(index);
+                candidates.push(MoveValue::Vector(fewer));
+            }
+            for (index, elem) in elems.iter().enumerate() {
+                for elem_candidate in shrink_candidates(elem) {
+                    let mut simpler = elems.clone();
+                    simpler[index] = elem_candidate;
+                    candidates.push(MoveValue::Vector(simpler));
+                }
+            }
+            candidates.dedup();
+            candidates.retain(|candidate| candidate != value);
+            candidates
+        },
+        _ => match as_u256(value) {
+            Some(n) if n != U256::zero() => {
+                let mut candidates = vec![U256::zero(), n >> 1, n - U256::one()];
+                candidates.dedup();
+                candidates
+                    .into_iter()
+                    .map(|candidate| with_integer(value, candidate))
+                    .collect()
+            },
+            _ => vec![],
+        },
+    }
+}
+
+/// Shrinks a failing input: repeatedly replaces a value of the input by a simpler one while the
+/// test still fails with it. `fails` runs the test with the given input, and returns whether it
+/// failed. Returns the number of simplifications made.
+pub fn shrink(input: &mut [MoveValue], mut fails: impl FnMut(&[MoveValue]) -> bool) -> usize {
+    let mut runs = 0;
+    let mut simplifications = 0;
+    'simplify: loop {
+        for index in 0..input.len() {
+            for candidate in shrink_candidates(&input[index]) {
+                if runs == MAX_SHRINK_RUNS {
+                    break 'simplify;
+                }
+                runs += 1;
+                let previous = std::mem::replace(&mut input[index], candidate);
+                if fails(input) {
+                    simplifications += 1;
+                    continue 'simplify;
+                }
+                input[index] = previous;
+            }
+        }
+        break;
+    }
+    simplifications
+}
+
+/// Returns the arguments of a run of a test with the given values of its generated parameters.
+pub fn test_arguments(test_info: &TestCase, input: &[MoveValue]) -> Vec<MoveValue> {
+    let mut arguments = test_info.arguments.clone();
+    // Generated parameters are ordered by position, so earlier positions are always filled
+    for (param, value) in test_info.generated_parameters.iter().zip(input) {
+        arguments.insert(param.position, value.clone());
+    }
+    arguments
+}
+
+/// Describes a failing input for the test report.
+pub fn describe_input(
+    parameters: &[GeneratedParameter],
+    input: &[MoveValue],
+    seed: u64,
+    runs: u64,
+    shrinks: usize,
+) -> String {
+    let mut description = format!(
+        "Failed after {} run(s) with seed {}, shrunk {} time(s):",
+        runs, seed, shrinks
+    );
+    for (param, value) in parameters.iter().zip(input) {
+        description.push_str(&format!("\n  {} = {}", param.name, format_value(value)));
+    }
+    description
+}
+
+/// Adds the control flow edges taken in the recorded execution to `covered`, and returns whether
+/// any of them were not covered before.
+pub fn add_coverage(covered: &mut BTreeSet<(String, u16, u16)>, events: Vec<TraceEvent>) -> bool {
+    let mut new_coverage = false;
+    let mut previous: Option<(String, u16)> = None;
+    for event in events {
+        if let TraceEvent::Instruction { function, pc, .. } = event {
+            let from = match &previous {
+                Some((previous_function, previous_pc)) if *previous_function == function => {
+                    *previous_pc
+                },
+                _ => u16::MAX,
+            };
+            new_coverage |= covered.insert((function.clone(), from, pc));
+            previous = Some((function, pc));
+        }
+    }
+    new_coverage
+}
+
+/// Formats a generated value the way it is written in Move source.
+pub fn format_value(value: &MoveValue) -> String {
+    match value {
+        MoveValue::Bool(b) => b.to_string(),
+        MoveValue::U8(n) => format!("{}u8", n),
+        MoveValue::U16(n) => format!("{}u16", n),
+        MoveValue::U32(n) => format!("{}u32", n),
+        MoveValue::U64(n) => format!("{}", n),
+        MoveValue::U128(n) => format!("{}u128", n),
+        MoveValue::U256(n) => format!("{}u256", n),
+        MoveValue::Address(addr) => format!("@0x{}", addr.short_str_lossless()),
+        MoveValue::Signer(addr) => format!("signer @0x{}", addr.short_str_lossless()),
+        MoveValue::Vector(elems) => format!(
+            "vector[{}]",
+            elems
+                .iter()
+                .map(format_value)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        _ => format!("{:?}", value),
+    }
+}
+
+fn integer_bits(layout: &MoveTypeLayout) -> Option<u32> {
+    Some(match layout {
+        MoveTypeLayout::U8 => 8,
+        MoveTypeLayout::U16 => 16,
+        MoveTypeLayout::U32 => 32,
+        MoveTypeLayout::U64 => 64,
+        MoveTypeLayout::U128 => 128,
+        MoveTypeLayout::U256 => 256,
+        _ => return None,
+    })
+}
+
+fn integer_max(bits: u32) -> U256 {
+    U256::max_value() >> (256 - bits) as u8
+}
+
+fn integer_value(layout: &MoveTypeLayout, n: U256) -> MoveValue {
+    match layout {
+        MoveTypeLayout::U8 => MoveValue::U8(n.unchecked_as_u8()),
+        MoveTypeLayout::U16 => MoveValue::U16(n.unchecked_as_u16()),
+        MoveTypeLayout::U32 => MoveValue::U32(n.unchecked_as_u32()),
+        MoveTypeLayout::U64 => MoveValue::U64(n.unchecked_as_u64()),
+        MoveTypeLayout::U128 => MoveValue::U128(n.unchecked_as_u128()),
+        _ => MoveValue::U256(n),
+    }
+}
+
+/// Replaces the integer `value` by `n`, keeping its type.
+fn with_integer(value: &MoveValue, n: U256) -> MoveValue {
+    match value {
+        MoveValue::U8(_) => integer_value(&MoveTypeLayout::U8, n),
+        MoveValue::U16(_) => integer_value(&MoveTypeLayout::U16, n),
+        MoveValue::U32(_) => integer_value(&MoveTypeLayout::U32, n),
+        MoveValue::U64(_) => integer_value(&MoveTypeLayout::U64, n),
+        MoveValue::U128(_) => integer_value(&MoveTypeLayout::U128, n),
+        _ => integer_value(&MoveTypeLayout::U256, n),
+    }
+}
+
+fn as_u256(value: &MoveValue) -> Option<U256> {
+    Some(match value {
+        MoveValue::U8(n) => U256::from(*n),
+        MoveValue::U16(n) => U256::from(*n),
+        MoveValue::U32(n) => U256::from(*n),
+        MoveValue::U64(n) => U256::from(*n),
+        MoveValue::U128(n) => U256::from(*n),
+        MoveValue::U256(n) => *n,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parameter(position: usize, layout: MoveTypeLayout) -> GeneratedParameter {
+        GeneratedParameter {
+            position,
+            name: format!("p{}", position),
+            layout,
+        }
+    }
+
+    #[test]
+    fn test_generated_values_have_layout() {
+        let mut generator = InputGenerator::new(42);
+        for _ in 0..1000 {
+            match generator.generate(&MoveTypeLayout::U8) {
+                MoveValue::U8(_) => {},
+                value => panic!("unexpected value {:?}", value),
+            }
+            match generator.generate(&MoveTypeLayout::Vector(Box::new(MoveTypeLayout::U64))) {
+                MoveValue::Vector(elems) => {
+                    assert!(elems.len() <= MAX_VECTOR_LENGTH);
+                    assert!(elems.iter().all(|elem| matches!(elem, MoveValue::U64(_))));
+                },
+                value => panic!("unexpected value {:?}", value),
+            }
+        }
+    }
+
+    #[test]
+    fn test_generation_is_deterministic() {
+        let parameters = vec![
+            parameter(0, MoveTypeLayout::U128),
+            parameter(1, MoveTypeLayout::Signer),
+        ];
+        let inputs = |seed| {
+            let mut generator = InputGenerator::new(seed);
+            (0..10)
+                .map(|_| generator.next_input(&parameters, &[]))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(inputs(7), inputs(7));
+        assert_ne!(inputs(7), inputs(8));
+    }
+
+    #[test]
+    fn test_shrink() {
+        // The property fails for any x >= 10 with a non-empty vector
+        let mut input = vec![
+            MoveValue::U64(123_456),
+            MoveValue::Vector(vec![MoveValue::U8(7), MoveValue::U8(200)]),
+        ];
+        shrink(&mut input, |input| match input {
+            [MoveValue::U64(x), MoveValue::Vector(v)] => *x >= 10 && !v.is_empty(),
+            _ => false,
+        });
+        assert_eq!(input, vec![
+            MoveValue::U64(10),
+            MoveValue::Vector(vec![MoveValue::U8(0)])
+        ]);
+    }
+
+    #[test]
+    fn test_arguments_interleave_fixed_and_generated() {
+        let test_info = TestCase {
+            test_name: "t".to_string(),
+            arguments: vec![MoveValue::Signer(AccountAddress::ONE)],
+            generated_parameters: vec![
+                parameter(0, MoveTypeLayout::U64),
+                parameter(2, MoveTypeLayout::Bool),
+            ],
+            expected_failure: None,
+            max_gas: None,
+        };
+        assert_eq!(
+            test_arguments(&test_info, &[MoveValue::U64(1), MoveValue::Bool(true)]),
+            vec![
+                MoveValue::U64(1),
+                MoveValue::Signer(AccountAddress::ONE),
+                MoveValue::Bool(true)
+            ]
+        );
+    }
+}
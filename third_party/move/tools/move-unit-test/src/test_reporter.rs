@@ -2,7 +2,7 @@
 // Copyright (c) The Move Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{format_module_id, DEFAULT_EXECUTION_BOUND};
+use crate::{format_module_id, gas_snapshot::GasSnapshot, DEFAULT_EXECUTION_BOUND};
 use codespan_reporting::files::{Files, SimpleFiles};
 use colored::{control, Colorize};
 pub use legacy_move_compiler::unit_test::ExpectedMoveError as MoveError;
@@ -98,6 +98,10 @@ pub enum FailureReason {
     UnexpectedError(String, MoveError),
     // Test timed out
     Timeout(String),
+    // Executed fully, but used more gas than the bound given with `#[max_gas = ...]`
+    GasLimitExceeded(String, u64, u64),
+    // Has a `#[max_gas = ...]` bound, but tests are not metered with a gas schedule
+    GasNotMetered(String),
     // The execution results of the Move VM and stackless VM does not match
     Mismatch {
         move_vm_return_values: Box<VMResult<Vec<Vec<u8>>>>,
@@ -115,6 +119,8 @@ pub struct TestFailure {
     pub vm_error: Option<VMError>,
     pub failure_reason: FailureReason,
     pub storage_state: Option<String>,
+    /// The generated input the test failed with, if it has generated parameters
+    pub input: Option<String>,
 }
 
 #[derive(Debug, Clone, Ord, PartialOrd, PartialEq, Eq)]
@@ -176,6 +182,21 @@ impl FailureReason {
         FailureReason::Timeout("Test timed out".to_string())
     }
 
+    pub fn gas_limit_exceeded(max_gas: u64, gas_used: u64) -> Self {
+        FailureReason::GasLimitExceeded(
+            "Test used more gas than allowed".to_string(),
+            max_gas,
+            gas_used,
+        )
+    }
+
+    pub fn gas_not_metered() -> Self {
+        FailureReason::GasNotMetered(
+            "Test has a #[max_gas] bound, but tests are not metered with a gas schedule"
+                .to_string(),
+        )
+    }
+
     pub fn mismatch(
         move_vm_return_values: VMResult<Vec<Vec<u8>>>,
         move_vm_change_set: VMResult<ChangeSet>,
@@ -207,13 +228,24 @@ impl TestFailure {
             vm_error,
             failure_reason,
             storage_state,
+            input: None,
         }
     }
 
+    pub fn with_input(mut self, input: Option<String>) -> Self {
+        self.input = input;
+        self
+    }
+
     pub fn render_error(&self, test_plan: &TestPlan) -> String {
         let error_string = match &self.failure_reason {
             FailureReason::NoError(message) => message.to_string(),
             FailureReason::Timeout(message) => message.to_string(),
+            FailureReason::GasNotMetered(message) => message.to_string(),
+            FailureReason::GasLimitExceeded(message, max_gas, gas_used) => format!(
+                "{}. Expected test to use at most {} gas, but it used {}",
+                message, max_gas, gas_used
+            ),
             FailureReason::WrongError(message, expected, actual) => {
                 let base_message = format!(
                     "{message}. Expected test {} but instead it {} rooted here",
@@ -277,6 +309,10 @@ impl TestFailure {
             },
             FailureReason::Property(message) => message.clone(),
         };
+        let error_string = match &self.input {
+            None => error_string,
+            Some(input) => format!("{}\n────── Failing input ──────\n{}", error_string, input),
+        };
 
         match &self.storage_state {
             None => error_string,
@@ -501,6 +537,28 @@ impl TestResults {
         }
     }
 
+    /// The gas used by each test which passed
+    pub fn gas_snapshot(&self) -> GasSnapshot {
+        GasSnapshot(
+            self.final_statistics
+                .passed
+                .iter()
+                .flat_map(|(module_id, test_results)| {
+                    test_results.iter().map(move |test_result| {
+                        (
+                            format!(
+                                "{}::{}",
+                                format_module_id(module_id),
+                                test_result.function_ident
+                            ),
+                            test_result.gas_used,
+                        )
+                    })
+                })
+                .collect(),
+        )
+    }
+
     pub fn report_goldens<W: Write>(&self, writer: &Mutex<W>) -> Result<()> {
         for (module_name, test_outputs) in self.final_statistics.output.iter() {
             for (test_name, write_set) in test_outputs.iter() {
@@ -4,6 +4,7 @@
 
 use crate::{
     extensions, format_module_id,
+    property::{self, InputGenerator, PropertyTestingConfig},
     test_reporter::{
         FailureReason, MoveError, TestFailure, TestResults, TestRunInfo, TestStatistics,
         UnitTestFactory,
@@ -15,7 +16,7 @@ use legacy_move_compiler::unit_test::{
     ExpectedFailure, ModuleTestPlan, NamedOrBytecodeModule, TestCase, TestPlan,
 };
 use move_binary_format::{
-    errors::{Location, VMResult},
+    errors::{Location, VMError, VMResult},
     file_format::CompiledModule,
 };
 use move_bytecode_utils::Modules;
@@ -23,13 +24,14 @@ use move_core_types::{
     account_address::AccountAddress,
     effects::{ChangeSet, Op},
     identifier::IdentStr,
-    value::serialize_values,
+    value::{serialize_values, MoveValue},
     vm_status::StatusCode,
 };
 use move_resource_viewer::MoveValueAnnotator;
 use move_vm_runtime::{
     data_cache::TransactionDataCache,
     dispatch_loader,
+    execution_trace::ExecutionTraceRecorder,
     module_traversal::{TraversalContext, TraversalStorage},
     move_vm::MoveVM,
     native_extensions::NativeContextExtensions,
@@ -39,7 +41,13 @@ use move_vm_runtime::{
 };
 use move_vm_test_utils::InMemoryStorage;
 use rayon::prelude::*;
-use std::{io::Write, marker::Send, sync::Mutex, time::Instant};
+use std::{
+    collections::BTreeSet,
+    io::Write,
+    marker::Send,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
 
 /// Test state common to all tests
 pub struct SharedTestingConfig {
@@ -49,6 +57,8 @@ pub struct SharedTestingConfig {
     #[allow(dead_code)] // used by some features
     source_files: Vec<String>,
     record_writeset: bool,
+    property_testing: PropertyTestingConfig,
+    meters_gas: bool,
 }
 
 pub struct TestRunner {
@@ -148,12 +158,27 @@ impl TestRunner {
                 starting_storage_state,
                 source_files,
                 record_writeset,
+                property_testing: PropertyTestingConfig::default(),
+                meters_gas: true,
             },
             num_threads,
             tests,
         })
     }
 
+    /// Sets how tests with generated parameters are run
+    pub fn with_property_testing(mut self, property_testing: PropertyTestingConfig) -> Self {
+        self.testing_config.property_testing = property_testing;
+        self
+    }
+
+    /// Sets whether the gas used by tests is metered with a gas schedule. Otherwise, tests with
+    /// a `#[max_gas = N]` bound fail instead of being run.
+    pub fn with_gas_metering(mut self, meters_gas: bool) -> Self {
+        self.testing_config.meters_gas = meters_gas;
+        self
+    }
+
     pub fn run<W: Write + Send, F: UnitTestFactory + Send>(
         self,
         writer: &Mutex<W>,
@@ -226,6 +251,39 @@ impl<W: Write> TestOutput<'_, '_, W> {
         .unwrap()
     }
 
+    fn report(
+        &self,
+        stats: &mut TestStatistics,
+        fn_name: &str,
+        outcome: TestOutcome,
+        test_run_info: TestRunInfo,
+        storage_state: impl FnOnce() -> Option<String>,
+        input: Option<String>,
+    ) {
+        let test_failure = match outcome {
+            TestOutcome::Pass => {
+                self.pass(fn_name);
+                stats.test_success(test_run_info, self.test_plan);
+                return;
+            },
+            TestOutcome::Fail(failure_reason, vm_error) => {
+                self.fail(fn_name);
+                TestFailure::new(failure_reason, test_run_info, vm_error, storage_state())
+            },
+            TestOutcome::Timeout(vm_error) => {
+                // Ran out of ticks, report a test timeout and log a test failure
+                self.timeout(fn_name);
+                TestFailure::new(
+                    FailureReason::timeout(),
+                    test_run_info,
+                    Some(vm_error),
+                    storage_state(),
+                )
+            },
+        };
+        stats.test_failure(test_failure.with_input(input), self.test_plan)
+    }
+
     fn timeout(&self, fn_name: &str) {
         writeln!(
             self.writer.lock().unwrap(),
@@ -238,13 +296,85 @@ impl<W: Write> TestOutput<'_, '_, W> {
     }
 }
 
+/// The outcome of a single run of a test
+enum TestOutcome {
+    Pass,
+    Fail(FailureReason, Option<VMError>),
+    Timeout(VMError),
+}
+
+impl TestOutcome {
+    fn new(
+        test_info: &TestCase,
+        exec_result: VMResult<Vec<Vec<u8>>>,
+        test_run_info: &TestRunInfo,
+    ) -> Self {
+        let err = match exec_result {
+            // Expected the test to fail, but it executed
+            Ok(_) if test_info.expected_failure.is_some() => {
+                return TestOutcome::Fail(FailureReason::no_error(), None)
+            },
+            // Expected the test to execute fully and it did, but used more gas than allowed
+            Ok(_)
+                if test_info
+                    .max_gas
+                    .is_some_and(|max_gas| test_run_info.gas_used > max_gas) =>
+            {
+                return TestOutcome::Fail(
+                    FailureReason::gas_limit_exceeded(
+                        test_info.max_gas.unwrap(),
+                        test_run_info.gas_used,
+                    ),
+                    None,
+                )
+            },
+            // Expected the test to execute fully and it did
+            Ok(_) => return TestOutcome::Pass,
+            Err(err) => err,
+        };
+        let actual_err = MoveError(
+            err.major_status(),
+            err.sub_status(),
+            err.location().clone(),
+            err.message().cloned(),
+        );
+        assert!(err.major_status() != StatusCode::EXECUTED);
+        match test_info.expected_failure.as_ref() {
+            Some(ExpectedFailure::Expected) => TestOutcome::Pass,
+            Some(ExpectedFailure::ExpectedWithError(expected_err))
+                if expected_err == &actual_err =>
+            {
+                TestOutcome::Pass
+            },
+            Some(ExpectedFailure::ExpectedWithCodeDEPRECATED(code))
+                if actual_err.0 == StatusCode::ABORTED
+                    && actual_err.1.is_some()
+                    && actual_err.1.unwrap() == *code =>
+            {
+                TestOutcome::Pass
+            },
+            // incorrect cases
+            Some(ExpectedFailure::ExpectedWithError(expected_err)) => TestOutcome::Fail(
+                FailureReason::wrong_error(expected_err.clone(), actual_err),
+                Some(err),
+            ),
+            Some(ExpectedFailure::ExpectedWithCodeDEPRECATED(expected_code)) => TestOutcome::Fail(
+                FailureReason::wrong_abort_deprecated(*expected_code, actual_err),
+                Some(err),
+            ),
+            None if err.major_status() == StatusCode::OUT_OF_GAS => TestOutcome::Timeout(err),
+            None => TestOutcome::Fail(FailureReason::unexpected_error(actual_err), Some(err)),
+        }
+    }
+}
+
 impl SharedTestingConfig {
     #[allow(clippy::field_reassign_with_default)]
     fn execute_via_move_vm<F: UnitTestFactory>(
         &self,
         test_plan: &ModuleTestPlan,
         function_name: &str,
-        test_info: &TestCase,
+        arguments: &[MoveValue],
         factory: &Mutex<F>,
     ) -> (
         VMResult<ChangeSet>,
@@ -275,7 +405,7 @@ impl SharedTestingConfig {
                     &[],
                 )
                 .and_then(|function| {
-                    let args = serialize_values(test_info.arguments.iter());
+                    let args = serialize_values(arguments.iter());
                     MoveVM::execute_loaded_function(
                         function,
                         args,
@@ -335,8 +465,32 @@ impl SharedTestingConfig {
         let mut stats = TestStatistics::new();
 
         for (function_name, test_info) in &test_plan.tests {
+            if test_info.max_gas.is_some() && !self.meters_gas {
+                output.report(
+                    &mut stats,
+                    function_name,
+                    TestOutcome::Fail(FailureReason::gas_not_metered(), None),
+                    TestRunInfo::new(function_name.to_string(), Duration::ZERO),
+                    || None,
+                    None,
+                );
+                continue;
+            }
+
+            if !test_info.generated_parameters.is_empty() {
+                self.exec_property_test(
+                    test_plan,
+                    function_name,
+                    test_info,
+                    output,
+                    &mut stats,
+                    factory,
+                );
+                continue;
+            }
+
             let (cs_result, ext_result, exec_result, test_run_info) =
-                self.execute_via_move_vm(test_plan, function_name, test_info, factory);
+                self.execute_via_move_vm(test_plan, function_name, &test_info.arguments, factory);
 
             if self.record_writeset {
                 stats.test_output(
@@ -346,129 +500,120 @@ impl SharedTestingConfig {
                 );
             }
 
-            let save_session_state = || {
-                if self.save_storage_state_on_failure {
-                    cs_result.ok().and_then(|changeset| {
-                        ext_result.ok().and_then(|mut extensions| {
-                            print_resources_and_extensions(
-                                &changeset,
-                                &mut extensions,
-                                &self.starting_storage_state,
-                            )
-                            .ok()
-                        })
-                    })
-                } else {
-                    None
+            let outcome = TestOutcome::new(test_info, exec_result, &test_run_info);
+            output.report(
+                &mut stats,
+                function_name,
+                outcome,
+                test_run_info,
+                || self.storage_state(cs_result, ext_result),
+                None,
+            );
+        }
+
+        stats
+    }
+
+    /// Runs a test with generated parameters with the configured number of inputs, until an
+    /// input makes it fail. Such an input is shrunk before the failure is reported.
+    fn exec_property_test<F: UnitTestFactory>(
+        &self,
+        test_plan: &ModuleTestPlan,
+        function_name: &str,
+        test_info: &TestCase,
+        output: &TestOutput<impl Write>,
+        stats: &mut TestStatistics,
+        factory: &Mutex<F>,
+    ) {
+        let config = &self.property_testing;
+        let mut generator =
+            InputGenerator::new(config.test_seed(&test_plan.module_id, function_name));
+        // Inputs which covered new control flow edges, to derive further inputs from
+        let mut corpus = vec![];
+        let mut covered = BTreeSet::new();
+        let now = Instant::now();
+        let mut test_run_info = TestRunInfo::new(function_name.to_string(), now.elapsed());
+
+        for run in 1..=config.iterations {
+            let input = generator.next_input(&test_info.generated_parameters, &corpus);
+            let arguments = property::test_arguments(test_info, &input);
+            let recorder = config.coverage_guided.then(ExecutionTraceRecorder::start);
+            let (_, _, exec_result, run_info) =
+                self.execute_via_move_vm(test_plan, function_name, &arguments, factory);
+            if let Some(recorder) = recorder {
+                if property::add_coverage(&mut covered, recorder.finish()) {
+                    corpus.push(input.clone());
                 }
-            };
-
-            match exec_result {
-                Err(err) => {
-                    let actual_err = MoveError(
-                        err.major_status(),
-                        err.sub_status(),
-                        err.location().clone(),
-                        err.message().cloned(),
-                    );
-                    assert!(err.major_status() != StatusCode::EXECUTED);
-                    match test_info.expected_failure.as_ref() {
-                        Some(ExpectedFailure::Expected) => {
-                            output.pass(function_name);
-                            stats.test_success(test_run_info, test_plan);
-                        },
-                        Some(ExpectedFailure::ExpectedWithError(expected_err))
-                            if expected_err == &actual_err =>
-                        {
-                            output.pass(function_name);
-                            stats.test_success(test_run_info, test_plan);
-                        },
-                        Some(ExpectedFailure::ExpectedWithCodeDEPRECATED(code))
-                            if actual_err.0 == StatusCode::ABORTED
-                                && actual_err.1.is_some()
-                                && actual_err.1.unwrap() == *code =>
-                        {
-                            output.pass(function_name);
-                            stats.test_success(test_run_info, test_plan);
-                        },
-                        // incorrect cases
-                        Some(ExpectedFailure::ExpectedWithError(expected_err)) => {
-                            output.fail(function_name);
-                            stats.test_failure(
-                                TestFailure::new(
-                                    FailureReason::wrong_error(expected_err.clone(), actual_err),
-                                    test_run_info,
-                                    Some(err),
-                                    save_session_state(),
-                                ),
-                                test_plan,
-                            )
-                        },
-                        Some(ExpectedFailure::ExpectedWithCodeDEPRECATED(expected_code)) => {
-                            output.fail(function_name);
-                            stats.test_failure(
-                                TestFailure::new(
-                                    FailureReason::wrong_abort_deprecated(
-                                        *expected_code,
-                                        actual_err,
-                                    ),
-                                    test_run_info,
-                                    Some(err),
-                                    save_session_state(),
-                                ),
-                                test_plan,
-                            )
-                        },
-                        None if err.major_status() == StatusCode::OUT_OF_GAS => {
-                            // Ran out of ticks, report a test timeout and log a test failure
-                            output.timeout(function_name);
-                            stats.test_failure(
-                                TestFailure::new(
-                                    FailureReason::timeout(),
-                                    test_run_info,
-                                    Some(err),
-                                    save_session_state(),
-                                ),
-                                test_plan,
-                            )
-                        },
-                        None => {
-                            output.fail(function_name);
-                            stats.test_failure(
-                                TestFailure::new(
-                                    FailureReason::unexpected_error(actual_err),
-                                    test_run_info,
-                                    Some(err),
-                                    save_session_state(),
-                                ),
-                                test_plan,
-                            )
-                        },
-                    }
-                },
-                Ok(_) => {
-                    // Expected the test to fail, but it executed
-                    if test_info.expected_failure.is_some() {
-                        output.fail(function_name);
-                        stats.test_failure(
-                            TestFailure::new(
-                                FailureReason::no_error(),
-                                test_run_info,
-                                None,
-                                save_session_state(),
-                            ),
-                            test_plan,
-                        )
-                    } else {
-                        // Expected the test to execute fully and it did
-                        output.pass(function_name);
-                        stats.test_success(test_run_info, test_plan);
-                    }
-                },
             }
+            if let TestOutcome::Pass = TestOutcome::new(test_info, exec_result, &run_info) {
+                test_run_info = run_info;
+                continue;
+            }
+
+            let mut input = input;
+            let shrinks = property::shrink(&mut input, |input| {
+                let arguments = property::test_arguments(test_info, input);
+                let (_, _, exec_result, run_info) =
+                    self.execute_via_move_vm(test_plan, function_name, &arguments, factory);
+                !matches!(
+                    TestOutcome::new(test_info, exec_result, &run_info),
+                    TestOutcome::Pass
+                )
+            });
+            // Run the shrunk input once more to report its failure
+            let arguments = property::test_arguments(test_info, &input);
+            let (cs_result, ext_result, exec_result, mut run_info) =
+                self.execute_via_move_vm(test_plan, function_name, &arguments, factory);
+            let outcome = TestOutcome::new(test_info, exec_result, &run_info);
+            run_info.elapsed_time = now.elapsed();
+            output.report(
+                stats,
+                function_name,
+                outcome,
+                run_info,
+                || self.storage_state(cs_result, ext_result),
+                Some(property::describe_input(
+                    &test_info.generated_parameters,
+                    &input,
+                    config.seed,
+                    run,
+                    shrinks,
+                )),
+            );
+            return;
         }
 
-        stats
+        test_run_info.elapsed_time = now.elapsed();
+        output.report(
+            stats,
+            function_name,
+            TestOutcome::Pass,
+            test_run_info,
+            || None,
+            None,
+        );
+    }
+
+    /// The storage state at the end of a failing test, if configured to be reported
+    fn storage_state(
+        &self,
+        cs_result: VMResult<ChangeSet>,
+        ext_result: VMResult<NativeContextExtensions>,
+    ) -> Option<String> {
+        if self.save_storage_state_on_failure {
+            cs_result.ok().and_then(|changeset| {
+                ext_result.ok().and_then(|mut extensions| {
+                    print_resources_and_extensions(
+                        &changeset,
+                        &mut extensions,
+                        &self.starting_storage_state,
+                    )
+                    .ok()
+                })
+            })
+        } else {
+            None
+        }
     }
 
     fn exec_module_tests<F: UnitTestFactory>(
@@ -14,12 +14,13 @@ use std::{
 
 // We don't support statistics tests as that includes times which are variable and will make these
 // tests flaky.
-const TEST_MODIFIER_STRS: &[&str] = &["storage"];
+const TEST_MODIFIER_STRS: &[&str] = &["storage", "unmetered"];
 
 pub fn modify(mut base_config: UnitTestingConfig, modifier_str: &str) -> Option<UnitTestingConfig> {
     // Add future test modifiers here
     match modifier_str {
         "storage" => base_config.report_storage_on_error = true,
+        "unmetered" => base_config.meters_gas = false,
         _ => return None,
     };
     Some(base_config)
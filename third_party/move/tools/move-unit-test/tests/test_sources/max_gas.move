@@ -0,0 +1,24 @@
+module 0x42::max_gas {
+    fun count(n: u64): u64 {
+        let i = 0;
+        while (i < n) i = i + 1;
+        i
+    }
+
+    #[test]
+    #[max_gas = 1000]
+    fun within_bound() {
+        count(10);
+    }
+
+    #[test]
+    #[max_gas = 10]
+    fun exceeds_bound() {
+        count(100);
+    }
+
+    #[test]
+    fun unbounded() {
+        count(100);
+    }
+}
@@ -0,0 +1,39 @@
+module 0x42::property_test {
+    use std::vector;
+
+    const E_TOO_LARGE: u64 = 1;
+
+    fun checked_half(x: u64): u64 {
+        assert!(x < 10, E_TOO_LARGE);
+        x / 2
+    }
+
+    fun all_small(v: &vector<u8>): bool {
+        let i = 0;
+        while (i < vector::length(v)) {
+            if (*vector::borrow(v, i) >= 200) return false;
+            i = i + 1;
+        };
+        true
+    }
+
+    // Fails for any input of 10 or more, which is shrunk to 10
+    #[test]
+    #[fuzz]
+    fun half_is_at_most_input(x: u64) {
+        assert!(checked_half(x) <= x, 0);
+    }
+
+    // Fails for vectors with a large element, which are shrunk to a vector of that element
+    #[test(_s = @0x1)]
+    #[fuzz]
+    fun elements_are_small(_s: &signer, v: vector<u8>, flag: bool) {
+        if (flag || !flag) assert!(all_small(&v), 2);
+    }
+
+    #[test]
+    #[fuzz]
+    fun addresses_are_equal_to_themselves(a: address, b: address) {
+        assert!(a == a && b == b, 3);
+    }
+}
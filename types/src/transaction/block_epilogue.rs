@@ -38,6 +38,13 @@ impl BlockEpiloguePayload {
             Self::V1 { block_end_info, .. } => Some(&block_end_info.to_make_hot),
         }
     }
+
+    pub fn try_get_slots_to_make_hot_mut(&mut self) -> Option<&mut BTreeMap<StateKey, StateSlot>> {
+        match self {
+            Self::V0 { .. } => None,
+            Self::V1 { block_end_info, .. } => Some(&mut block_end_info.to_make_hot),
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]